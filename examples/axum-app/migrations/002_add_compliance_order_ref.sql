@@ -0,0 +1,8 @@
+-- Optional correlation between a compliance check and a local order.
+-- Refund requests may reference orders that live outside this app, so the
+-- column is nullable and cleared if the referenced order is deleted.
+
+ALTER TABLE compliance_checks
+    ADD COLUMN IF NOT EXISTS order_ref INTEGER REFERENCES orders(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_compliance_checks_order_ref ON compliance_checks(order_ref);
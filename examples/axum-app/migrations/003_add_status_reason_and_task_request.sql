@@ -0,0 +1,23 @@
+-- Support for the stale-row sweeper.
+--
+-- status_reason: why a row reached its current status (e.g. swept to 'failed').
+-- task_request:  the task payload that failed to submit, kept so the sweeper
+--                can attempt one resubmission before giving up.
+
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS status_reason TEXT;
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS task_request JSONB;
+
+ALTER TABLE analytics_jobs ADD COLUMN IF NOT EXISTS status_reason TEXT;
+ALTER TABLE analytics_jobs ADD COLUMN IF NOT EXISTS task_request JSONB;
+
+ALTER TABLE service_requests ADD COLUMN IF NOT EXISTS status_reason TEXT;
+ALTER TABLE service_requests ADD COLUMN IF NOT EXISTS task_request JSONB;
+
+ALTER TABLE compliance_checks ADD COLUMN IF NOT EXISTS status_reason TEXT;
+ALTER TABLE compliance_checks ADD COLUMN IF NOT EXISTS task_request JSONB;
+
+-- The sweeper scans for old rows that never received a task
+CREATE INDEX IF NOT EXISTS idx_orders_status_created ON orders(status, created_at);
+CREATE INDEX IF NOT EXISTS idx_analytics_jobs_status_created ON analytics_jobs(status, created_at);
+CREATE INDEX IF NOT EXISTS idx_service_requests_status_created ON service_requests(status, created_at);
+CREATE INDEX IF NOT EXISTS idx_compliance_checks_status_created ON compliance_checks(status, created_at);
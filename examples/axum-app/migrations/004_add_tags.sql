@@ -0,0 +1,13 @@
+-- Free-form string tags (e.g. {"campaign": "black_friday"}) for filtering and
+-- cost allocation. GIN indexes support the `tags @> $1` containment filter
+-- used by the list endpoints.
+
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS tags JSONB NOT NULL DEFAULT '{}';
+ALTER TABLE analytics_jobs ADD COLUMN IF NOT EXISTS tags JSONB NOT NULL DEFAULT '{}';
+ALTER TABLE service_requests ADD COLUMN IF NOT EXISTS tags JSONB NOT NULL DEFAULT '{}';
+ALTER TABLE compliance_checks ADD COLUMN IF NOT EXISTS tags JSONB NOT NULL DEFAULT '{}';
+
+CREATE INDEX IF NOT EXISTS idx_orders_tags ON orders USING GIN (tags);
+CREATE INDEX IF NOT EXISTS idx_analytics_jobs_tags ON analytics_jobs USING GIN (tags);
+CREATE INDEX IF NOT EXISTS idx_service_requests_tags ON service_requests USING GIN (tags);
+CREATE INDEX IF NOT EXISTS idx_compliance_checks_tags ON compliance_checks USING GIN (tags);
@@ -0,0 +1,13 @@
+-- Customer email on compliance checks, so every domain that belongs to a
+-- customer can be looked up by email (GET /customers/{email}/workflows).
+-- Existing rows are backfilled from the stored request payload.
+
+ALTER TABLE compliance_checks ADD COLUMN IF NOT EXISTS customer_email VARCHAR(255);
+
+UPDATE compliance_checks
+SET customer_email = payload->>'customer_email'
+WHERE customer_email IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_orders_customer_email ON orders(customer_email);
+CREATE INDEX IF NOT EXISTS idx_service_requests_user_email ON service_requests(user_email);
+CREATE INDEX IF NOT EXISTS idx_compliance_checks_customer_email ON compliance_checks(customer_email);
@@ -0,0 +1,26 @@
+-- Client-facing SKUs mapped to internal catalog product ids.
+--
+-- Order routes resolve each cart item's SKU through this table and reject
+-- unknown SKUs. The bare numeric SKUs ('1'..'5') are kept as aliases for
+-- clients that still send product ids.
+
+CREATE TABLE IF NOT EXISTS skus (
+    sku VARCHAR(100) PRIMARY KEY,
+    product_id INTEGER NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_skus_product_id ON skus(product_id);
+
+INSERT INTO skus (sku, product_id) VALUES
+    ('WGT-A-001', 1),
+    ('WGT-B-002', 2),
+    ('WGT-C-003', 3),
+    ('GDG-X-004', 4),
+    ('GDG-Y-005', 5),
+    ('1', 1),
+    ('2', 2),
+    ('3', 3),
+    ('4', 4),
+    ('5', 5)
+ON CONFLICT (sku) DO NOTHING;
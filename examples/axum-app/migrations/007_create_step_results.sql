@@ -0,0 +1,17 @@
+-- Locally persisted step results, keyed by task and step name.
+--
+-- Written by the worker's post-handler callback when PERSIST_STEP_RESULTS is
+-- enabled, so results can be queried with SQL without calling orchestration.
+-- A retried step overwrites its previous attempt.
+
+CREATE TABLE IF NOT EXISTS step_results (
+    task_uuid UUID NOT NULL,
+    step_name VARCHAR(255) NOT NULL,
+    namespace VARCHAR(255) NOT NULL,
+    handler_name VARCHAR(255) NOT NULL,
+    success BOOLEAN NOT NULL,
+    result JSONB,
+    error_message TEXT,
+    recorded_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (task_uuid, step_name)
+);
@@ -0,0 +1,16 @@
+-- Soft archival of completed domain rows.
+--
+-- archived_at: set by the archiver once a row has been complete for longer
+--              than the retention period. List endpoints exclude archived
+--              rows unless ?include_archived=true.
+
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS archived_at TIMESTAMP;
+ALTER TABLE analytics_jobs ADD COLUMN IF NOT EXISTS archived_at TIMESTAMP;
+ALTER TABLE service_requests ADD COLUMN IF NOT EXISTS archived_at TIMESTAMP;
+ALTER TABLE compliance_checks ADD COLUMN IF NOT EXISTS archived_at TIMESTAMP;
+
+-- The archiver scans for completed rows that are not yet archived
+CREATE INDEX IF NOT EXISTS idx_orders_unarchived ON orders(status, updated_at) WHERE archived_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_analytics_jobs_unarchived ON analytics_jobs(status, updated_at) WHERE archived_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_service_requests_unarchived ON service_requests(status, updated_at) WHERE archived_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_compliance_checks_unarchived ON compliance_checks(status, updated_at) WHERE archived_at IS NULL;
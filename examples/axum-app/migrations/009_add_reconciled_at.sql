@@ -0,0 +1,10 @@
+-- Support for the status reconciler.
+--
+-- reconciled_at: when the reconciler last checked the row's task with
+--                orchestration. Runs process the least recently checked rows
+--                first, so a backlog larger than one run's cap still rotates.
+
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS reconciled_at TIMESTAMP;
+ALTER TABLE analytics_jobs ADD COLUMN IF NOT EXISTS reconciled_at TIMESTAMP;
+ALTER TABLE service_requests ADD COLUMN IF NOT EXISTS reconciled_at TIMESTAMP;
+ALTER TABLE compliance_checks ADD COLUMN IF NOT EXISTS reconciled_at TIMESTAMP;
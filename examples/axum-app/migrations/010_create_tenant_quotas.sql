@@ -0,0 +1,26 @@
+-- Per-tenant quotas and rate limits for workflow submissions.
+--
+-- Requests identify their tenant with the X-Tenant-Id header. A tenant with a
+-- tenant_quotas row is limited to max_workflows_per_day submissions per day
+-- and max_requests_per_minute per minute (NULL = unlimited). Tenants
+-- without a row, and requests without the header, are not limited.
+--
+-- tenant_usage holds one counter per tenant and window. Counters are
+-- incremented atomically with a conditional upsert, so concurrent requests
+-- cannot overshoot a limit.
+
+CREATE TABLE IF NOT EXISTS tenant_quotas (
+    tenant_id VARCHAR(100) PRIMARY KEY,
+    max_workflows_per_day INTEGER,
+    max_requests_per_minute INTEGER,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS tenant_usage (
+    tenant_id VARCHAR(100) NOT NULL,
+    window_kind VARCHAR(10) NOT NULL,
+    window_start TIMESTAMPTZ NOT NULL,
+    used INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (tenant_id, window_kind, window_start)
+);
@@ -0,0 +1,12 @@
+-- Deduplication of analytics job submissions.
+--
+-- dedup_key: "<job_name>|<start_date>|<end_date>" (dates empty without a
+--            date_range). POST /analytics returns the newest active job with
+--            the same key instead of creating a duplicate, unless forced.
+--            Rows created before this column existed have no key and are
+--            never matched.
+
+ALTER TABLE analytics_jobs ADD COLUMN IF NOT EXISTS dedup_key TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_analytics_jobs_dedup_key ON analytics_jobs(dedup_key, created_at)
+    WHERE dedup_key IS NOT NULL;
@@ -0,0 +1,14 @@
+-- Normalize stored customer emails (trimmed, lowercase).
+--
+-- The create routes now store normalized emails, and customer lookups
+-- (GET /customers/{email}/workflows) match on the normalized form. Rows
+-- created before that are rewritten so they keep matching.
+
+UPDATE orders SET customer_email = LOWER(TRIM(customer_email))
+WHERE customer_email <> LOWER(TRIM(customer_email));
+
+UPDATE service_requests SET user_email = LOWER(TRIM(user_email))
+WHERE user_email <> LOWER(TRIM(user_email));
+
+UPDATE compliance_checks SET customer_email = LOWER(TRIM(customer_email))
+WHERE customer_email <> LOWER(TRIM(customer_email));
@@ -0,0 +1,9 @@
+-- The payments-namespace task of a compliance check.
+--
+-- A refund compliance check submits two tasks: customer success (task_uuid)
+-- and payments (payments_task_uuid). Both are needed to cancel the check.
+
+ALTER TABLE compliance_checks ADD COLUMN IF NOT EXISTS payments_task_uuid UUID;
+
+CREATE INDEX IF NOT EXISTS idx_compliance_checks_payments_task_uuid
+    ON compliance_checks(payments_task_uuid);
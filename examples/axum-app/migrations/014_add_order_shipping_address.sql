@@ -0,0 +1,6 @@
+-- The shipping address of an order.
+--
+-- Kept on the row so PATCH /orders/{id} can correct it before the workflow
+-- prices shipping. Orders created before this migration have no address.
+
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS shipping_address JSONB;
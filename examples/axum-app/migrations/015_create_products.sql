@@ -0,0 +1,23 @@
+-- The e-commerce product catalog.
+--
+-- The cart validation and inventory handlers price and stock-check carts
+-- against these rows (loaded at startup). Seeded with the five demo products;
+-- `skus` maps client-facing SKUs to these ids.
+
+CREATE TABLE IF NOT EXISTS products (
+    id INTEGER PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    sku VARCHAR(100) NOT NULL UNIQUE,
+    price DECIMAL(10,2) NOT NULL,
+    stock INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+INSERT INTO products (id, name, sku, price, stock) VALUES
+    (1, 'Widget A', 'WGT-A-001', 29.99, 100),
+    (2, 'Widget B', 'WGT-B-002', 49.99, 50),
+    (3, 'Widget C', 'WGT-C-003', 99.99, 25),
+    (4, 'Gadget X', 'GDG-X-004', 149.99, 30),
+    (5, 'Gadget Y', 'GDG-Y-005', 199.99, 15)
+ON CONFLICT (id) DO NOTHING;
@@ -0,0 +1,9 @@
+-- Client-supplied Idempotency-Key of an order.
+--
+-- POST /orders with a key that is already stored returns the original order
+-- instead of creating another one (and another workflow). Orders created
+-- without the header have no key; NULLs never conflict.
+
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS idempotency_key VARCHAR(255);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_orders_idempotency_key ON orders(idempotency_key);
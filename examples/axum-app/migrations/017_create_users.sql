@@ -0,0 +1,23 @@
+-- Registered user accounts for the microservices registration workflow.
+--
+-- `create_user_account` inserts one row per normalized email and returns the
+-- stored `account` (the step result) when the email registers again. Seeded
+-- with `existing@example.com` so the already-registered path can be exercised
+-- without registering first.
+
+CREATE TABLE IF NOT EXISTS users (
+    id SERIAL PRIMARY KEY,
+    user_id VARCHAR(64) NOT NULL UNIQUE,
+    email VARCHAR(255) NOT NULL UNIQUE,
+    account JSONB NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+INSERT INTO users (user_id, email, account) VALUES (
+    'user_existing_001',
+    'existing@example.com',
+    '{"user_id": "user_existing_001", "email": "existing@example.com", "status": "created",
+      "created_at": "2025-01-01T00:00:00Z", "name": "Existing User", "plan": "free",
+      "source": "web"}'
+)
+ON CONFLICT (email) DO NOTHING;
@@ -0,0 +1,20 @@
+-- Discount codes for the e-commerce cart.
+--
+-- The cart validation handler applies a context `coupon_code` against these
+-- rows (loaded at startup): `percent` takes `amount` percent off the subtotal,
+-- `fixed` takes `amount` dollars off. A code is valid through `expires_on`
+-- (NULL = never expires). Seeded with the demo coupons.
+
+CREATE TABLE IF NOT EXISTS coupons (
+    code VARCHAR(50) PRIMARY KEY,
+    kind VARCHAR(20) NOT NULL CHECK (kind IN ('percent', 'fixed')),
+    amount DECIMAL(10,2) NOT NULL CHECK (amount > 0),
+    expires_on DATE,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+INSERT INTO coupons (code, kind, amount, expires_on) VALUES
+    ('SAVE10', 'percent', 10.00, NULL),
+    ('FIVEOFF', 'fixed', 5.00, NULL),
+    ('SPRING2020', 'percent', 20.00, '2020-05-31')
+ON CONFLICT (code) DO NOTHING;
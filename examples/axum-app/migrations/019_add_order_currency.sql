@@ -0,0 +1,7 @@
+-- The currency an order is priced in.
+--
+-- An ISO 4217 code from POST /orders (see money::SUPPORTED_CURRENCIES); it
+-- sets how the workflow rounds the order's amounts. Existing orders were all
+-- priced in USD.
+
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS currency VARCHAR(3) NOT NULL DEFAULT 'USD';
@@ -0,0 +1,17 @@
+-- Steps that failed permanently (dead-lettered).
+--
+-- Written by the worker's post-handler callback whenever a handler fails with
+-- a non-retryable error, so permanent failures have a durable record after
+-- orchestration stops retrying them. Served at GET /debug/failed-steps.
+
+CREATE TABLE IF NOT EXISTS failed_steps (
+    id BIGSERIAL PRIMARY KEY,
+    task_uuid UUID NOT NULL,
+    step_name VARCHAR(255) NOT NULL,
+    category VARCHAR(50) NOT NULL,
+    error TEXT NOT NULL,
+    occurred_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_failed_steps_task_uuid ON failed_steps (task_uuid);
+CREATE INDEX IF NOT EXISTS idx_failed_steps_occurred_at ON failed_steps (occurred_at);
@@ -0,0 +1,8 @@
+-- Onboarding results recorded per registered user.
+--
+-- `setup_billing_profile` and `initialize_preferences` store their results on
+-- the user's row the first time they run, and return the stored ones when the
+-- same email registers again, on any worker.
+
+ALTER TABLE users ADD COLUMN IF NOT EXISTS billing JSONB;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS preferences JSONB;
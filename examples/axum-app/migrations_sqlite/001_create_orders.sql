@@ -0,0 +1,46 @@
+-- SQLite schema for the `sqlite` feature (quick local demos).
+--
+-- Mirrors the Postgres orders and skus tables (migrations/) with
+-- SQLite-compatible column types:
+--   total     - TEXT holding the decimal string (SQLite has no DECIMAL)
+--   task_uuid - TEXT holding the hyphenated UUID
+--   JSON      - TEXT holding the serialized document
+--
+-- Only order storage is supported on SQLite; the other domain tables remain
+-- Postgres-only.
+
+CREATE TABLE IF NOT EXISTS orders (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    customer_email TEXT NOT NULL,
+    items TEXT NOT NULL DEFAULT '[]',
+    total TEXT NOT NULL DEFAULT '0.00',
+    status TEXT NOT NULL DEFAULT 'pending',
+    task_uuid TEXT,
+    status_reason TEXT,
+    task_request TEXT,
+    tags TEXT NOT NULL DEFAULT '{}',
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    archived_at TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_orders_task_uuid ON orders(task_uuid);
+CREATE INDEX IF NOT EXISTS idx_orders_customer_email ON orders(customer_email);
+
+CREATE TABLE IF NOT EXISTS skus (
+    sku TEXT PRIMARY KEY,
+    product_id INTEGER NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT OR IGNORE INTO skus (sku, product_id) VALUES
+    ('WGT-A-001', 1),
+    ('WGT-B-002', 2),
+    ('WGT-C-003', 3),
+    ('GDG-X-004', 4),
+    ('GDG-Y-005', 5),
+    ('1', 1),
+    ('2', 2),
+    ('3', 3),
+    ('4', 4),
+    ('5', 5);
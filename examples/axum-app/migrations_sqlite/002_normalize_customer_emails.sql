@@ -0,0 +1,5 @@
+-- Normalize stored customer emails (trimmed, lowercase), matching
+-- migrations/012_normalize_customer_emails.sql.
+
+UPDATE orders SET customer_email = LOWER(TRIM(customer_email))
+WHERE customer_email <> LOWER(TRIM(customer_email));
@@ -0,0 +1,4 @@
+-- The shipping address of an order, matching
+-- migrations/014_add_order_shipping_address.sql (JSON stored as TEXT).
+
+ALTER TABLE orders ADD COLUMN shipping_address TEXT;
@@ -0,0 +1,4 @@
+-- The currency an order is priced in, matching
+-- migrations/019_add_order_currency.sql.
+
+ALTER TABLE orders ADD COLUMN currency TEXT NOT NULL DEFAULT 'USD';
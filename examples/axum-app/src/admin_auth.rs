@@ -0,0 +1,71 @@
+//! Bearer-token auth for the admin routes.
+//!
+//! When `ADMIN_TOKEN` is set, every `/admin/*` request must carry
+//! `Authorization: Bearer <token>`; anything else is a 401 `unauthorized`
+//! error. When it is unset the admin routes stay open, which suits local
+//! development but not a shared deployment. A router that applies
+//! [`require_admin`] without installing an [`AdminAuth`] extension fails closed
+//! with a 500.
+
+use axum::extract::Request;
+use axum::http::{header, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use tracing::error;
+
+use crate::error::ApiError;
+
+/// The configured admin token, installed as a request extension.
+#[derive(Debug, Clone, Default)]
+pub struct AdminAuth {
+    token: Option<String>,
+}
+
+impl AdminAuth {
+    /// Require `token` (`None` or blank = admin routes are open).
+    pub fn new(token: Option<String>) -> Self {
+        Self {
+            token: token.filter(|t| !t.trim().is_empty()),
+        }
+    }
+
+    /// Read `ADMIN_TOKEN`.
+    pub fn from_env() -> Self {
+        Self::new(std::env::var("ADMIN_TOKEN").ok())
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.token.is_some()
+    }
+
+    /// Whether an `Authorization` header value grants admin access.
+    pub fn allows(&self, authorization: Option<&str>) -> bool {
+        let Some(expected) = &self.token else {
+            return true;
+        };
+        authorization
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|presented| constant_time_eq(presented.trim().as_bytes(), expected.as_bytes()))
+    }
+}
+
+/// Middleware rejecting admin requests without the configured token.
+pub async fn require_admin(req: Request, next: Next) -> Response {
+    let Some(auth) = req.extensions().get::<AdminAuth>() else {
+        error!("AdminAuth extension missing; rejecting {}", req.uri().path());
+        return ApiError::from(StatusCode::INTERNAL_SERVER_ERROR).into_response();
+    };
+    let authorization = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+    if !auth.allows(authorization) {
+        return ApiError::Unauthorized.into_response();
+    }
+    next.run(req).await
+}
+
+/// Compare without short-circuiting on the first differing byte.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
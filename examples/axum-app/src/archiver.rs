@@ -0,0 +1,131 @@
+//! Periodic archival of completed domain rows.
+//!
+//! Rows whose workflow completed longer ago than the retention period get an
+//! `archived_at` timestamp. Archived rows stay in their tables but are excluded
+//! from the list endpoints unless the request passes `?include_archived=true`.
+//! Single-row lookups (`GET /orders/{id}` etc.) still return archived rows.
+//!
+//! ## Configuration
+//!
+//! | Env var | Default | Meaning |
+//! |---------|---------|---------|
+//! | `ARCHIVER_INTERVAL_SECS` | 3600 | Time between runs (0 disables the archiver) |
+//! | `ARCHIVE_RETENTION_SECS` | 2592000 (30 days) | Age after completion before a row is archived |
+//!
+//! Each archived row increments `domain_rows_archived_total{table}`.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+
+use crate::db::AppDb;
+use crate::env::env_secs;
+use crate::error::ApiError;
+use crate::metrics;
+
+/// Domain tables whose completed rows are archived.
+const ARCHIVED_TABLES: &[&str] = &[
+    "orders",
+    "analytics_jobs",
+    "service_requests",
+    "compliance_checks",
+];
+
+/// Statuses of rows whose workflow has completed.
+const COMPLETED_STATUSES: &[&str] = &["complete", "completed"];
+
+/// Query parameter that includes archived rows in list responses.
+pub const INCLUDE_ARCHIVED_PARAM: &str = "include_archived";
+
+#[derive(Debug, Clone)]
+pub struct ArchiverConfig {
+    /// Time between archival runs.
+    pub interval: Duration,
+    /// Completed rows last updated longer ago than this are archived.
+    pub retention: Duration,
+}
+
+impl Default for ArchiverConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(3600),
+            retention: Duration::from_secs(30 * 24 * 3600),
+        }
+    }
+}
+
+impl ArchiverConfig {
+    /// Read the archiver configuration from the environment.
+    pub fn from_env() -> Result<Self, String> {
+        let defaults = Self::default();
+        Ok(Self {
+            interval: env_secs("ARCHIVER_INTERVAL_SECS")?.unwrap_or(defaults.interval),
+            retention: env_secs("ARCHIVE_RETENTION_SECS")?.unwrap_or(defaults.retention),
+        })
+    }
+}
+
+/// Parse `?include_archived=<bool>` (absent means `false`).
+pub fn include_archived(query: &HashMap<String, String>) -> Result<bool, ApiError> {
+    match query.get(INCLUDE_ARCHIVED_PARAM) {
+        None => Ok(false),
+        Some(raw) => raw.trim().parse::<bool>().map_err(|_| {
+            ApiError::validation(INCLUDE_ARCHIVED_PARAM, "include_archived must be true or false")
+        }),
+    }
+}
+
+/// Archive completed rows past the retention period in every domain table.
+/// Returns the number of rows archived.
+pub async fn archive_once(pool: &AppDb, config: &ArchiverConfig) -> Result<u64, sqlx::Error> {
+    let retention_secs = config.retention.as_secs() as i64;
+    let statuses: Vec<String> = COMPLETED_STATUSES.iter().map(|s| s.to_string()).collect();
+    let mut archived = 0;
+
+    for table in ARCHIVED_TABLES {
+        let result = sqlx::query(&format!(
+            r#"
+            UPDATE {table} SET archived_at = NOW()
+            WHERE archived_at IS NULL
+              AND status = ANY($1)
+              AND updated_at < NOW() - ($2::bigint * INTERVAL '1 second')
+            "#
+        ))
+        .bind(&statuses)
+        .bind(retention_secs)
+        .execute(pool)
+        .await?;
+
+        let count = result.rows_affected();
+        if count > 0 {
+            info!("Archiver archived {} {} rows", count, table);
+            metrics::registry().increment_counter_by(
+                "domain_rows_archived_total",
+                &[("table", table)],
+                count,
+            );
+            archived += count;
+        }
+    }
+
+    Ok(archived)
+}
+
+/// Spawn the archiver loop. Returns `None` when the interval is zero (disabled).
+pub fn spawn(pool: AppDb, config: ArchiverConfig) -> Option<JoinHandle<()>> {
+    if config.interval.is_zero() {
+        return None;
+    }
+
+    Some(tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(config.interval);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = archive_once(&pool, &config).await {
+                error!("Archiver run failed: {}", e);
+            }
+        }
+    }))
+}
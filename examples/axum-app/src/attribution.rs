@@ -0,0 +1,181 @@
+//! Per-request task attribution (`initiator` and `source_system`).
+//!
+//! Every submitted task names who started it. By default that is
+//! `axum-example-app` / `example-axum`; a client sharing this app with others
+//! can name itself with the `X-Initiator` and `X-Source-System` headers so the
+//! orchestration audit trail attributes its workflows correctly.
+//!
+//! Initiators must be on the allowlist (`ALLOWED_INITIATORS`, comma-separated;
+//! the default initiator is always allowed). An unknown initiator, or a
+//! malformed header, is a 422 naming the header. `X-Source-System` is free-form
+//! but limited to [`MAX_ATTRIBUTION_LEN`] letters, digits, `.`, `_`, and `-`.
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use axum::http::HeaderMap;
+
+use crate::error::ApiError;
+
+/// Request header naming the initiator.
+pub const INITIATOR_HEADER: &str = "x-initiator";
+
+/// Request header naming the source system.
+pub const SOURCE_SYSTEM_HEADER: &str = "x-source-system";
+
+/// Initiator used when the request names none.
+pub const DEFAULT_INITIATOR: &str = "axum-example-app";
+
+/// Source system used when the request names none.
+pub const DEFAULT_SOURCE_SYSTEM: &str = "example-axum";
+
+/// Longest accepted initiator or source system.
+pub const MAX_ATTRIBUTION_LEN: usize = 64;
+
+/// Initiators a request may name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InitiatorAllowlist {
+    initiators: BTreeSet<String>,
+}
+
+impl Default for InitiatorAllowlist {
+    /// Only the default initiator.
+    fn default() -> Self {
+        Self {
+            initiators: BTreeSet::from([DEFAULT_INITIATOR.to_string()]),
+        }
+    }
+}
+
+impl InitiatorAllowlist {
+    /// Allow `initiators` in addition to the default initiator.
+    pub fn new<I, S>(initiators: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut allowlist = Self::default();
+        allowlist
+            .initiators
+            .extend(initiators.into_iter().map(Into::into));
+        allowlist
+    }
+
+    /// Parse a comma-separated list such as `"billing-portal,support-console"`.
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let initiators: Vec<&str> = raw.split(',').map(str::trim).filter(|s| !s.is_empty()).collect();
+        for initiator in &initiators {
+            check_name(initiator).map_err(|e| format!("invalid initiator '{}': {}", initiator, e))?;
+        }
+        Ok(Self::new(initiators))
+    }
+
+    /// Read `ALLOWED_INITIATORS` (unset = only the default initiator).
+    pub fn from_env() -> Result<Self, String> {
+        match std::env::var("ALLOWED_INITIATORS") {
+            Ok(raw) => Self::parse(&raw),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    pub fn is_allowed(&self, initiator: &str) -> bool {
+        self.initiators.contains(initiator)
+    }
+
+    /// The allowed initiators, sorted.
+    pub fn initiators(&self) -> Vec<&str> {
+        self.initiators.iter().map(String::as_str).collect()
+    }
+}
+
+/// Who a submitted task is attributed to.
+///
+/// As an extractor, reads the attribution headers and checks the initiator
+/// against the `Arc<InitiatorAllowlist>` extension (the default allowlist when
+/// none is installed).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskAttribution {
+    pub initiator: String,
+    pub source_system: String,
+}
+
+impl Default for TaskAttribution {
+    fn default() -> Self {
+        Self {
+            initiator: DEFAULT_INITIATOR.to_string(),
+            source_system: DEFAULT_SOURCE_SYSTEM.to_string(),
+        }
+    }
+}
+
+impl TaskAttribution {
+    /// Read and validate the attribution headers.
+    pub fn from_headers(
+        headers: &HeaderMap,
+        allowlist: &InitiatorAllowlist,
+    ) -> Result<Self, ApiError> {
+        let mut attribution = Self::default();
+
+        if let Some(initiator) = header_value(headers, INITIATOR_HEADER)? {
+            if !allowlist.is_allowed(&initiator) {
+                return Err(ApiError::validation(
+                    INITIATOR_HEADER,
+                    format!(
+                        "unknown initiator '{}'; allowed: {}",
+                        initiator,
+                        allowlist.initiators().join(", ")
+                    ),
+                ));
+            }
+            attribution.initiator = initiator;
+        }
+
+        if let Some(source_system) = header_value(headers, SOURCE_SYSTEM_HEADER)? {
+            attribution.source_system = source_system;
+        }
+
+        Ok(attribution)
+    }
+}
+
+impl<S> FromRequestParts<S> for TaskAttribution
+where
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        match parts.extensions.get::<Arc<InitiatorAllowlist>>() {
+            Some(allowlist) => Self::from_headers(&parts.headers, allowlist),
+            None => Self::from_headers(&parts.headers, &InitiatorAllowlist::default()),
+        }
+    }
+}
+
+/// A trimmed, validated header value, or `None` if the header is absent.
+fn header_value(headers: &HeaderMap, name: &'static str) -> Result<Option<String>, ApiError> {
+    let Some(value) = headers.get(name) else {
+        return Ok(None);
+    };
+    let value = value
+        .to_str()
+        .map_err(|_| ApiError::validation(name, "must be visible ASCII"))?
+        .trim();
+    check_name(value).map_err(|e| ApiError::validation(name, e))?;
+    Ok(Some(value.to_string()))
+}
+
+fn check_name(value: &str) -> Result<(), String> {
+    if value.is_empty() || value.len() > MAX_ATTRIBUTION_LEN {
+        return Err(format!("must be 1-{} characters", MAX_ATTRIBUTION_LEN));
+    }
+    if !value
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+    {
+        return Err("may only contain letters, digits, '.', '_', and '-'".to_string());
+    }
+    Ok(())
+}
@@ -0,0 +1,52 @@
+//! Running several post-handler callbacks.
+//!
+//! The dispatch service takes a single [`PostHandlerCallback`]; a
+//! [`CallbackChain`] runs each configured callback in turn. An empty chain
+//! does nothing.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tasker_shared::messaging::StepExecutionResult;
+use tasker_shared::types::base::TaskSequenceStep;
+use tasker_worker::worker::handlers::PostHandlerCallback;
+
+#[derive(Default)]
+pub struct CallbackChain {
+    callbacks: Vec<Arc<dyn PostHandlerCallback>>,
+}
+
+impl CallbackChain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a callback; callbacks run in the order they were added.
+    pub fn with(mut self, callback: Arc<dyn PostHandlerCallback>) -> Self {
+        self.callbacks.push(callback);
+        self
+    }
+
+    /// Names of the chained callbacks, for logging.
+    pub fn names(&self) -> Vec<&str> {
+        self.callbacks.iter().map(|callback| callback.name()).collect()
+    }
+}
+
+#[async_trait]
+impl PostHandlerCallback for CallbackChain {
+    async fn on_handler_complete(
+        &self,
+        step: &TaskSequenceStep,
+        result: &StepExecutionResult,
+        worker_id: &str,
+    ) {
+        for callback in &self.callbacks {
+            callback.on_handler_complete(step, result, worker_id).await;
+        }
+    }
+
+    fn name(&self) -> &str {
+        "callback_chain"
+    }
+}
@@ -0,0 +1,138 @@
+//! Cancelling every workflow task behind a domain row.
+//!
+//! Most domain rows have one task, but a refund compliance check has two (one
+//! per namespace). Cancelling the row cancels each of its tasks and reports a
+//! result per namespace. The row becomes `cancelled` only when every task was
+//! cancelled; if some cancellations failed it becomes `partially_cancelled`,
+//! so a later cancel can be retried. If none succeeded the row is unchanged.
+//!
+//! Rows with at most one task (orders, analytics jobs, service requests) are
+//! cancelled with [`cancel_row`].
+
+use axum::http::StatusCode;
+use futures::future::join_all;
+use serde::Serialize;
+use sqlx::postgres::PgRow;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::db::AppDb;
+use crate::error::ApiError;
+use crate::orchestration::{OrchestrationClient, OrchestrationError};
+
+/// Row statuses from which a row can be cancelled.
+pub const CANCELLABLE_STATUSES: &[&str] = &["pending", "processing", "partially_cancelled"];
+
+/// One workflow task belonging to a domain row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkflowTask {
+    pub namespace: &'static str,
+    pub task_uuid: Uuid,
+}
+
+/// The outcome of cancelling one task.
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskCancellation {
+    pub namespace: &'static str,
+    pub task_uuid: Uuid,
+    pub cancelled: bool,
+    /// Why orchestration refused or failed the cancellation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Cancel `tasks` concurrently, returning one result per task in order.
+pub async fn cancel_all(client: &OrchestrationClient, tasks: &[WorkflowTask]) -> Vec<TaskCancellation> {
+    join_all(tasks.iter().map(|task| async move {
+        let result = client.cancel_task(task.task_uuid).await;
+        if let Err(e) = &result {
+            warn!(
+                "Failed to cancel {} task {}: {}",
+                task.namespace, task.task_uuid, e
+            );
+        }
+        TaskCancellation {
+            namespace: task.namespace,
+            task_uuid: task.task_uuid,
+            cancelled: result.is_ok(),
+            error: result.err().map(|e| e.to_string()),
+        }
+    }))
+    .await
+}
+
+/// The row status after `results`, or `None` to leave the row as it is
+/// (every cancellation failed).
+pub fn cancelled_status(results: &[TaskCancellation]) -> Option<&'static str> {
+    let cancelled = results.iter().filter(|r| r.cancelled).count();
+    if cancelled == results.len() {
+        Some("cancelled")
+    } else if cancelled > 0 {
+        Some("partially_cancelled")
+    } else {
+        None
+    }
+}
+
+/// Cancel row `id` of `table`, and its workflow task if one was submitted, and
+/// return the cancelled row.
+///
+/// The task is cancelled first; if orchestration fails the row is left as is
+/// (502, or 409 if orchestration says the task can no longer be cancelled).
+/// A row still awaiting submission is only cancelled locally. 409 if the row
+/// already finished, 404 if there is no such row.
+pub async fn cancel_row<T>(
+    pool: &AppDb,
+    client: &OrchestrationClient,
+    table: &str,
+    id: i32,
+) -> Result<T, ApiError>
+where
+    T: for<'r> sqlx::FromRow<'r, PgRow> + Send + Unpin,
+{
+    let db_error = |e: sqlx::Error| {
+        error!("Failed to cancel {} {}: {}", table, id, e);
+        ApiError::Db(e.to_string())
+    };
+    let (status, task_uuid): (String, Option<Uuid>) = sqlx::query_as(&format!(
+        "SELECT status, task_uuid FROM {table} WHERE id = $1"
+    ))
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+    .map_err(db_error)?
+    .ok_or(ApiError::NotFound)?;
+    if !CANCELLABLE_STATUSES.contains(&status.as_str()) {
+        return Err(StatusCode::CONFLICT.into());
+    }
+
+    if let Some(task_uuid) = task_uuid {
+        client.cancel_task(task_uuid).await.map_err(|e| match e {
+            OrchestrationError::Status { status, .. } if status == StatusCode::CONFLICT => {
+                ApiError::from(StatusCode::CONFLICT)
+            }
+            e => {
+                error!(
+                    "Failed to cancel task {} for {} {}: {}",
+                    task_uuid, table, id, e
+                );
+                ApiError::Upstream(e.to_string())
+            }
+        })?;
+    }
+
+    // Conditional on the row being unchanged since it was read, so a row
+    // submitted or finished in the meantime is a 409
+    sqlx::query_as(&format!(
+        "UPDATE {table} SET status = 'cancelled', updated_at = NOW() \
+         WHERE id = $1 AND status = $2 AND task_uuid IS NOT DISTINCT FROM $3 \
+         RETURNING *"
+    ))
+    .bind(id)
+    .bind(&status)
+    .bind(task_uuid)
+    .fetch_optional(pool)
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| StatusCode::CONFLICT.into())
+}
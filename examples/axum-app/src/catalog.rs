@@ -0,0 +1,73 @@
+//! Product catalog and SKU resolution for the Axum example application.
+//!
+//! Clients order by SKU (e.g. `WGT-A-001`); the e-commerce handlers work with
+//! internal catalog product ids. The `skus` table maps one to the other, and
+//! the `products` table holds the catalog the handlers validate carts against.
+//! The `coupons` table holds the discount codes carts may apply.
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+
+use crate::db::AppDb;
+use crate::handlers::ecommerce::{Coupon, CouponDiscount, Product};
+
+/// Load the product catalog, ordered by id.
+pub async fn load_products(pool: &AppDb) -> sqlx::Result<Vec<Product>> {
+    let rows: Vec<(i32, String, String, f64, i32)> = sqlx::query_as(
+        "SELECT id, name, sku, price::float8, stock FROM products ORDER BY id",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(id, name, sku, price, stock)| Product {
+            id: i64::from(id),
+            name,
+            sku,
+            price,
+            stock: i64::from(stock),
+        })
+        .collect())
+}
+
+/// Load the discount codes, ordered by code.
+pub async fn load_coupons(pool: &AppDb) -> sqlx::Result<Vec<Coupon>> {
+    let rows: Vec<(String, String, f64, Option<NaiveDate>)> =
+        sqlx::query_as("SELECT code, kind, amount::float8, expires_on FROM coupons ORDER BY code")
+            .fetch_all(pool)
+            .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(code, kind, amount, expires_on)| Coupon {
+            code: code.to_uppercase(),
+            // The table only allows `percent` and `fixed`
+            discount: if kind == "percent" {
+                CouponDiscount::Percent(amount)
+            } else {
+                CouponDiscount::Fixed(amount)
+            },
+            expires_on,
+        })
+        .collect())
+}
+
+/// Look up the product id for each SKU. SKUs with no mapping are absent from
+/// the returned map.
+pub async fn product_ids_for_skus(
+    pool: &AppDb,
+    skus: &[String],
+) -> sqlx::Result<HashMap<String, i64>> {
+    let rows: Vec<(String, i32)> =
+        sqlx::query_as("SELECT sku, product_id FROM skus WHERE sku = ANY($1)")
+            .bind(skus)
+            .fetch_all(pool)
+            .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(sku, product_id)| (sku, i64::from(product_id)))
+        .collect())
+}
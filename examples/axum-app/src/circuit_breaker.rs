@@ -0,0 +1,143 @@
+//! A consecutive-failure circuit breaker.
+//!
+//! Calls to a flaky dependency go through [`CircuitBreaker::allow`]. After
+//! `failure_threshold` consecutive failures the breaker opens and rejects
+//! calls for `cooldown`; it then half-opens and lets a single trial call
+//! through. A successful trial closes the breaker, a failed one reopens it.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+/// Where the breaker is in its cycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Calls go through.
+    Closed,
+    /// Calls are rejected until the cooldown elapses.
+    Open,
+    /// The cooldown elapsed; the next call is a trial.
+    HalfOpen,
+}
+
+impl CircuitState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Closed => "closed",
+            Self::Open => "open",
+            Self::HalfOpen => "half_open",
+        }
+    }
+}
+
+/// A snapshot of the breaker for status endpoints.
+#[derive(Debug, Clone, Serialize)]
+pub struct CircuitBreakerStatus {
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    pub failure_threshold: u32,
+    pub cooldown_secs: u64,
+}
+
+#[derive(Debug, Default)]
+struct BreakerState {
+    consecutive_failures: u32,
+    /// When the breaker last opened (`None` = closed).
+    opened_at: Option<Instant>,
+    /// When the current half-open trial call started. A trial whose outcome
+    /// is never recorded (e.g. a cancelled request) expires after the cooldown.
+    trial_started_at: Option<Instant>,
+}
+
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    cooldown: Duration,
+    state: Mutex<BreakerState>,
+}
+
+impl CircuitBreaker {
+    /// A closed breaker that opens after `failure_threshold` consecutive
+    /// failures (at least 1) and stays open for `cooldown`.
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            cooldown,
+            state: Mutex::new(BreakerState::default()),
+        }
+    }
+
+    pub fn failure_threshold(&self) -> u32 {
+        self.failure_threshold
+    }
+
+    pub fn cooldown(&self) -> Duration {
+        self.cooldown
+    }
+
+    /// The breaker's current state.
+    pub fn state(&self) -> CircuitState {
+        let state = self.state.lock().unwrap();
+        match state.opened_at {
+            None => CircuitState::Closed,
+            Some(opened_at) if opened_at.elapsed() < self.cooldown => CircuitState::Open,
+            Some(_) => CircuitState::HalfOpen,
+        }
+    }
+
+    pub fn status(&self) -> CircuitBreakerStatus {
+        CircuitBreakerStatus {
+            state: self.state(),
+            consecutive_failures: self.state.lock().unwrap().consecutive_failures,
+            failure_threshold: self.failure_threshold,
+            cooldown_secs: self.cooldown.as_secs(),
+        }
+    }
+
+    /// Whether a call may go ahead. Once the cooldown has elapsed, only one
+    /// trial call is allowed until its outcome is recorded.
+    pub fn allow(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        match state.opened_at {
+            None => true,
+            Some(opened_at) if opened_at.elapsed() < self.cooldown => false,
+            Some(_)
+                if state
+                    .trial_started_at
+                    .is_some_and(|started| started.elapsed() < self.cooldown) =>
+            {
+                false
+            }
+            Some(_) => {
+                state.trial_started_at = Some(Instant::now());
+                true
+            }
+        }
+    }
+
+    /// Record a successful call, closing the breaker. Returns `true` if the
+    /// breaker was open.
+    pub fn record_success(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let was_open = state.opened_at.is_some();
+        *state = BreakerState::default();
+        was_open
+    }
+
+    /// Record a failed call, opening the breaker once the threshold is
+    /// reached (or immediately, for a failed half-open trial). Returns `true`
+    /// if this failure opened a closed breaker.
+    pub fn record_failure(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        state.consecutive_failures = state.consecutive_failures.saturating_add(1);
+        let was_closed = state.opened_at.is_none();
+        if state.trial_started_at.is_some() || state.consecutive_failures >= self.failure_threshold {
+            state.opened_at = Some(Instant::now());
+            state.trial_started_at = None;
+            return was_closed;
+        }
+        false
+    }
+}
@@ -0,0 +1,151 @@
+//! Per-route limits on concurrent in-flight submissions.
+//!
+//! A burst of `POST /orders` requests can exhaust database connections and
+//! flood orchestration. Each workflow submission route (see
+//! [`SUBMISSION_ROUTES`]) can be given a maximum number of requests in flight
+//! at once; a request arriving while its route is saturated is shed with a 503
+//! and `Retry-After` instead of queueing. This bounds simultaneous work, unlike
+//! tenant quotas, which bound submissions over time.
+//!
+//! ## Configuration
+//!
+//! `CONCURRENCY_LIMITS` is a comma-separated list of `route=limit` entries,
+//! where `*` sets the limit for every submission route:
+//!
+//! ```text
+//! CONCURRENCY_LIMITS=*=64,/analytics=8
+//! ```
+//!
+//! Routes without a limit are unbounded (the default). Each shed request
+//! increments `requests_shed_total{route}`.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{MatchedPath, Request};
+use axum::http::Method;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use tokio::sync::Semaphore;
+
+use crate::error::ApiError;
+use crate::metrics;
+use crate::quotas::SUBMISSION_ROUTES;
+
+/// Concurrency limits for the submission routes.
+///
+/// Clones share their permits, so every router built from one set of limits
+/// counts against the same in-flight requests.
+#[derive(Debug, Clone, Default)]
+pub struct ConcurrencyLimits {
+    routes: HashMap<&'static str, RouteLimit>,
+}
+
+#[derive(Debug, Clone)]
+struct RouteLimit {
+    limit: usize,
+    permits: Arc<Semaphore>,
+}
+
+impl ConcurrencyLimits {
+    /// No limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow at most `limit` concurrent requests to `route`. A zero limit
+    /// removes the route's limit.
+    pub fn with_limit(mut self, route: &str, limit: usize) -> Result<Self, String> {
+        let route = SUBMISSION_ROUTES
+            .iter()
+            .find(|r| **r == route)
+            .ok_or_else(|| {
+                format!(
+                    "unknown route '{}'; expected one of: {}",
+                    route,
+                    SUBMISSION_ROUTES.join(", ")
+                )
+            })?;
+        if limit == 0 {
+            self.routes.remove(route);
+        } else {
+            self.routes.insert(
+                route,
+                RouteLimit {
+                    limit,
+                    permits: Arc::new(Semaphore::new(limit)),
+                },
+            );
+        }
+        Ok(self)
+    }
+
+    /// Parse a `CONCURRENCY_LIMITS` value such as `"*=64,/analytics=8"`.
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let mut limits = Self::new();
+        for entry in raw.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (route, limit) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("invalid entry '{}': expected route=limit", entry))?;
+            let limit: usize = limit
+                .trim()
+                .parse()
+                .map_err(|e| format!("invalid limit in '{}': {}", entry, e))?;
+            limits = match route.trim() {
+                "*" => SUBMISSION_ROUTES
+                    .iter()
+                    .try_fold(limits, |limits, route| limits.with_limit(route, limit))?,
+                route => limits.with_limit(route, limit)?,
+            };
+        }
+        Ok(limits)
+    }
+
+    /// Read `CONCURRENCY_LIMITS` (unset = no limits).
+    pub fn from_env() -> Result<Self, String> {
+        match std::env::var("CONCURRENCY_LIMITS") {
+            Ok(raw) => Self::parse(&raw),
+            Err(_) => Ok(Self::new()),
+        }
+    }
+
+    /// The configured limit for `route`, if any.
+    pub fn limit(&self, route: &str) -> Option<usize> {
+        self.routes.get(route).map(|route| route.limit)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty()
+    }
+}
+
+/// Middleware shedding submissions to a saturated route with a 503.
+///
+/// Reads the `Arc<ConcurrencyLimits>` extension installed by `create_app`;
+/// without it, nothing is limited.
+pub async fn limit_concurrency(req: Request, next: Next) -> Response {
+    if req.method() != Method::POST {
+        return next.run(req).await;
+    }
+    let Some(route) = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+    else {
+        return next.run(req).await;
+    };
+    let Some(permits) = req
+        .extensions()
+        .get::<Arc<ConcurrencyLimits>>()
+        .and_then(|limits| limits.routes.get(route.as_str()))
+        .map(|route| route.permits.clone())
+    else {
+        return next.run(req).await;
+    };
+
+    let Ok(_permit) = permits.try_acquire_owned() else {
+        metrics::registry().increment_counter("requests_shed_total", &[("route", &route)]);
+        return ApiError::Overloaded { route }.into_response();
+    };
+    next.run(req).await
+}
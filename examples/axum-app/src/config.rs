@@ -0,0 +1,110 @@
+//! Application configuration for the HTTP layer.
+//!
+//! [`AppConfig::from_env`] gathers the env-driven settings that `create_app`
+//! installs as request extensions and layers. Tests build an `AppConfig`
+//! directly to exercise non-default behavior without touching process env vars.
+
+use std::sync::Arc;
+
+use crate::admin_auth::AdminAuth;
+use crate::attribution::InitiatorAllowlist;
+use crate::concurrency::ConcurrencyLimits;
+use crate::cors::CorsConfig;
+use crate::extract::FieldAliases;
+use crate::handler_metrics::HandlerMetrics;
+use crate::handler_registry::AxumHandlerRegistry;
+use crate::handlers::ecommerce::PricingConfig;
+use crate::orchestration::OrchestrationClient;
+use crate::rate_limit::RateLimiter;
+use crate::routes::debug::debug_endpoints_from_env;
+use crate::security_headers::SecurityHeaders;
+use crate::task_events::TaskEventsConfig;
+use crate::webhook_auth::WebhookAuth;
+
+/// Default minimum response size, in bytes, before compression kicks in.
+pub const DEFAULT_COMPRESSION_MIN_BYTES: u16 = 1024;
+
+#[derive(Debug, Clone)]
+pub struct AppConfig {
+    /// Body field aliases accepted by the create endpoints (empty = strict).
+    pub field_aliases: FieldAliases,
+    /// Initiators a request may name with `X-Initiator`.
+    pub initiators: InitiatorAllowlist,
+    /// Maximum in-flight requests per submission route (empty = unbounded).
+    pub concurrency_limits: ConcurrencyLimits,
+    /// Per-client request rate limit (disabled by default).
+    pub rate_limiter: RateLimiter,
+    /// Responses smaller than this are never compressed.
+    pub compression_min_bytes: u16,
+    /// The worker's handler registry, shared with the admin routes.
+    pub handler_registry: Arc<AxumHandlerRegistry>,
+    /// Per-handler outcome counts, recorded by the worker's metrics callback.
+    pub handler_metrics: HandlerMetrics,
+    /// Orchestration client used by the admin routes.
+    pub orchestration: OrchestrationClient,
+    /// Token required by the admin routes (`ADMIN_TOKEN`; unset = open).
+    pub admin_auth: AdminAuth,
+    /// Secret verifying pushed task webhooks (`WEBHOOK_SECRET`; unset = rejected).
+    pub webhook_auth: WebhookAuth,
+    /// Security headers added to every response (disabled by default).
+    pub security_headers: SecurityHeaders,
+    /// Origins, methods, and headers browsers may use (permissive by default).
+    pub cors: CorsConfig,
+    /// Poll interval of the task progress event streams.
+    pub task_events: TaskEventsConfig,
+    /// Serve `GET /debug/registry` (`DEBUG_ENDPOINTS`; off by default).
+    pub debug_endpoints: bool,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            field_aliases: FieldAliases::default(),
+            initiators: InitiatorAllowlist::default(),
+            concurrency_limits: ConcurrencyLimits::default(),
+            rate_limiter: RateLimiter::disabled(),
+            compression_min_bytes: DEFAULT_COMPRESSION_MIN_BYTES,
+            handler_registry: Arc::new(AxumHandlerRegistry::new()),
+            handler_metrics: HandlerMetrics::new(),
+            orchestration: OrchestrationClient::from_env_endpoint(),
+            admin_auth: AdminAuth::default(),
+            webhook_auth: WebhookAuth::default(),
+            security_headers: SecurityHeaders::disabled(),
+            cors: CorsConfig::Permissive,
+            task_events: TaskEventsConfig::default(),
+            debug_endpoints: false,
+        }
+    }
+}
+
+impl AppConfig {
+    /// Read the configuration from the environment.
+    pub fn from_env() -> anyhow::Result<Self> {
+        let compression_min_bytes = match std::env::var("COMPRESSION_MIN_BYTES") {
+            Ok(raw) => raw.trim().parse::<u16>().map_err(|e| {
+                anyhow::anyhow!("Invalid COMPRESSION_MIN_BYTES '{}': {}", raw, e)
+            })?,
+            Err(_) => DEFAULT_COMPRESSION_MIN_BYTES,
+        };
+
+        let handler_registry = AxumHandlerRegistry::from_env()?;
+        handler_registry.set_pricing(PricingConfig::from_env().map_err(anyhow::Error::msg)?);
+
+        Ok(Self {
+            field_aliases: FieldAliases::from_env().map_err(anyhow::Error::msg)?,
+            initiators: InitiatorAllowlist::from_env().map_err(anyhow::Error::msg)?,
+            concurrency_limits: ConcurrencyLimits::from_env().map_err(anyhow::Error::msg)?,
+            rate_limiter: RateLimiter::from_env().map_err(anyhow::Error::msg)?,
+            compression_min_bytes,
+            handler_registry: Arc::new(handler_registry),
+            handler_metrics: HandlerMetrics::new(),
+            orchestration: OrchestrationClient::from_env().map_err(anyhow::Error::msg)?,
+            admin_auth: AdminAuth::from_env(),
+            webhook_auth: WebhookAuth::from_env(),
+            security_headers: SecurityHeaders::from_env().map_err(anyhow::Error::msg)?,
+            cors: CorsConfig::from_env().map_err(anyhow::Error::msg)?,
+            task_events: TaskEventsConfig::from_env().map_err(anyhow::Error::msg)?,
+            debug_endpoints: debug_endpoints_from_env(),
+        })
+    }
+}
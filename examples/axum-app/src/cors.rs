@@ -0,0 +1,153 @@
+//! Cross-origin resource sharing (CORS) policy.
+//!
+//! With `CORS_ALLOWED_ORIGINS` unset the policy is permissive (any origin,
+//! method, and header), which keeps the example easy to call from a local
+//! frontend. Setting it restricts browsers to the listed origins:
+//!
+//! - `CORS_ALLOWED_ORIGINS`: comma-separated origins, e.g.
+//!   `https://shop.example.com,https://admin.example.com`, or `*` for any origin
+//! - `CORS_ALLOWED_METHODS`: comma-separated methods (default
+//!   [`DEFAULT_ALLOWED_METHODS`])
+//! - `CORS_ALLOWED_HEADERS`: comma-separated request headers (default
+//!   [`DEFAULT_ALLOWED_HEADERS`])
+//!
+//! `X-Request-Id` is exposed to scripts on every response.
+
+use axum::http::{HeaderName, HeaderValue, Method};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+use crate::request_id::REQUEST_ID_HEADER;
+
+/// Methods allowed when `CORS_ALLOWED_METHODS` is unset.
+pub const DEFAULT_ALLOWED_METHODS: &[&str] = &["GET", "POST", "PATCH", "DELETE", "OPTIONS"];
+
+/// Request headers allowed when `CORS_ALLOWED_HEADERS` is unset: the ones the
+/// routes read, plus the orchestration `X-API-Key`.
+pub const DEFAULT_ALLOWED_HEADERS: &[&str] = &[
+    "content-type",
+    "authorization",
+    "x-api-key",
+    "idempotency-key",
+    "x-initiator",
+    "x-request-id",
+];
+
+/// The CORS policy installed by `create_app`.
+#[derive(Debug, Clone, Default)]
+pub enum CorsConfig {
+    /// Allow any origin, method, and header (the default).
+    #[default]
+    Permissive,
+    /// Allow only these origins (`None` = any origin), methods, and headers.
+    Restricted {
+        origins: Option<Vec<HeaderValue>>,
+        methods: Vec<Method>,
+        headers: Vec<HeaderName>,
+    },
+}
+
+impl CorsConfig {
+    /// A restricted policy for a comma-separated origin list (`*` = any
+    /// origin), with the default methods and headers.
+    pub fn from_origins(origins: &str) -> Result<Self, String> {
+        Ok(Self::Restricted {
+            origins: parse_origins(origins)?,
+            methods: parse_list(&DEFAULT_ALLOWED_METHODS.join(","), "method")?,
+            headers: parse_list(&DEFAULT_ALLOWED_HEADERS.join(","), "header")?,
+        })
+    }
+
+    /// Read `CORS_ALLOWED_ORIGINS`, `CORS_ALLOWED_METHODS`, and
+    /// `CORS_ALLOWED_HEADERS`; permissive when the origins are unset.
+    pub fn from_env() -> Result<Self, String> {
+        let Ok(origins) = std::env::var("CORS_ALLOWED_ORIGINS") else {
+            return Ok(Self::Permissive);
+        };
+        let mut config = Self::from_origins(&origins)?;
+        if let Self::Restricted {
+            methods, headers, ..
+        } = &mut config
+        {
+            if let Ok(raw) = std::env::var("CORS_ALLOWED_METHODS") {
+                *methods = parse_list(&raw.to_ascii_uppercase(), "method")?;
+            }
+            if let Ok(raw) = std::env::var("CORS_ALLOWED_HEADERS") {
+                *headers = parse_list(&raw, "header")?;
+            }
+        }
+        Ok(config)
+    }
+
+    pub fn is_permissive(&self) -> bool {
+        matches!(self, Self::Permissive)
+    }
+
+    /// The origins a browser may call from (`None` = any origin).
+    pub fn allowed_origins(&self) -> Option<&[HeaderValue]> {
+        match self {
+            Self::Permissive => None,
+            Self::Restricted { origins, .. } => origins.as_deref(),
+        }
+    }
+
+    /// Build the tower-http layer for this policy.
+    pub fn layer(&self) -> CorsLayer {
+        match self {
+            Self::Permissive => CorsLayer::permissive(),
+            Self::Restricted {
+                origins,
+                methods,
+                headers,
+            } => CorsLayer::new()
+                .allow_origin(match origins {
+                    Some(origins) => AllowOrigin::list(origins.clone()),
+                    None => AllowOrigin::any(),
+                })
+                .allow_methods(methods.clone())
+                .allow_headers(headers.clone())
+                .expose_headers([HeaderName::from_static(REQUEST_ID_HEADER)]),
+        }
+    }
+}
+
+/// Parse a comma-separated origin list; a lone `*` means any origin.
+fn parse_origins(raw: &str) -> Result<Option<Vec<HeaderValue>>, String> {
+    let entries: Vec<&str> = raw
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect();
+    match entries.as_slice() {
+        [] => Err("CORS_ALLOWED_ORIGINS must list at least one origin".to_string()),
+        ["*"] => Ok(None),
+        _ if entries.contains(&"*") => {
+            Err("CORS_ALLOWED_ORIGINS cannot mix '*' with other origins".to_string())
+        }
+        _ => entries
+            .iter()
+            .map(|origin| {
+                if !origin.starts_with("http://") && !origin.starts_with("https://") {
+                    return Err(format!(
+                        "invalid CORS origin '{}': expected http(s)://host",
+                        origin
+                    ));
+                }
+                HeaderValue::from_str(origin.trim_end_matches('/'))
+                    .map_err(|_| format!("invalid CORS origin '{}'", origin))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Some),
+    }
+}
+
+/// Parse a comma-separated list of methods or header names.
+fn parse_list<T: std::str::FromStr>(raw: &str, kind: &str) -> Result<Vec<T>, String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|item| {
+            item.parse::<T>()
+                .map_err(|_| format!("invalid CORS {} '{}'", kind, item))
+        })
+        .collect()
+}
@@ -3,12 +3,119 @@
 //! Provides type aliases and extractors for the application-specific database pool.
 //! This pool connects to the example_axum database for domain model storage,
 //! separate from Tasker's internal database.
+//!
+//! [`connect`] builds the pool for both the binary and the integration tests:
+//!
+//! | Variable | Default | Meaning |
+//! |----------|---------|---------|
+//! | `APP_DB_MAX_CONNECTIONS` | 10 | Maximum open connections |
+//! | `APP_DB_ACQUIRE_TIMEOUT` | 30 | Seconds to wait for a free connection |
+//! | `APP_DB_IDLE_TIMEOUT` | 600 | Seconds before an idle connection is closed (0 = never) |
+
+use std::time::Duration;
 
+use sqlx::postgres::PgPoolOptions;
 use sqlx::PgPool;
 
 /// Type alias for the application database pool.
 pub type AppDb = PgPool;
 
+/// Default maximum number of pool connections.
+pub const DEFAULT_MAX_CONNECTIONS: u32 = 10;
+
+/// Default time to wait for a free connection.
+pub const DEFAULT_ACQUIRE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default time before an idle connection is closed.
+pub const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Application database pool settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolConfig {
+    pub max_connections: u32,
+    pub acquire_timeout: Duration,
+    /// `None` keeps idle connections open.
+    pub idle_timeout: Option<Duration>,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            max_connections: DEFAULT_MAX_CONNECTIONS,
+            acquire_timeout: DEFAULT_ACQUIRE_TIMEOUT,
+            idle_timeout: Some(DEFAULT_IDLE_TIMEOUT),
+        }
+    }
+}
+
+impl PoolConfig {
+    /// Read `APP_DB_MAX_CONNECTIONS`, `APP_DB_ACQUIRE_TIMEOUT`, and
+    /// `APP_DB_IDLE_TIMEOUT`, falling back to the defaults when unset.
+    pub fn from_env() -> Result<Self, String> {
+        let defaults = Self::default();
+        let max_connections = match env_u64("APP_DB_MAX_CONNECTIONS")? {
+            Some(0) => {
+                return Err("Invalid APP_DB_MAX_CONNECTIONS '0': expected at least 1".to_string())
+            }
+            Some(n) => u32::try_from(n)
+                .map_err(|_| format!("Invalid APP_DB_MAX_CONNECTIONS '{}': too large", n))?,
+            None => defaults.max_connections,
+        };
+        let acquire_timeout = match env_u64("APP_DB_ACQUIRE_TIMEOUT")? {
+            Some(0) => {
+                return Err("Invalid APP_DB_ACQUIRE_TIMEOUT '0': expected at least 1".to_string())
+            }
+            Some(secs) => Duration::from_secs(secs),
+            None => defaults.acquire_timeout,
+        };
+        let idle_timeout = match env_u64("APP_DB_IDLE_TIMEOUT")? {
+            Some(0) => None,
+            Some(secs) => Some(Duration::from_secs(secs)),
+            None => defaults.idle_timeout,
+        };
+
+        Ok(Self {
+            max_connections,
+            acquire_timeout,
+            idle_timeout,
+        })
+    }
+
+    /// Pool options with these settings applied.
+    pub fn options(&self) -> PgPoolOptions {
+        PgPoolOptions::new()
+            .max_connections(self.max_connections)
+            .acquire_timeout(self.acquire_timeout)
+            .idle_timeout(self.idle_timeout)
+    }
+}
+
+/// Connect to `APP_DATABASE_URL` with the [`PoolConfig`] from the environment
+/// and run the application migrations.
+pub async fn connect() -> anyhow::Result<AppDb> {
+    let config = PoolConfig::from_env().map_err(anyhow::Error::msg)?;
+    connect_with(&pool_from_env(), &config).await
+}
+
+/// Connect to `url` with `config` and run the application migrations.
+pub async fn connect_with(url: &str, config: &PoolConfig) -> anyhow::Result<AppDb> {
+    let pool = config.options().connect(url).await?;
+    sqlx::migrate!("./migrations").run(&pool).await?;
+    Ok(pool)
+}
+
+/// Parse an unsigned integer env var; `None` when unset.
+fn env_u64(name: &str) -> Result<Option<u64>, String> {
+    match std::env::var(name) {
+        Ok(raw) => raw
+            .trim()
+            .parse::<u64>()
+            .map(Some)
+            .map_err(|_| format!("Invalid {} '{}': expected a whole number", name, raw)),
+        Err(_) => Ok(None),
+    }
+}
+
 /// Extract the application database pool from Axum extensions.
 ///
 /// Usage in route handlers:
@@ -0,0 +1,355 @@
+//! Dead-letter alerting.
+//!
+//! A step is dead-lettered when its handler fails with a non-retryable error,
+//! or with a retryable one on its last attempt (`attempts` has reached the
+//! step's `max_attempts`): either way orchestration will not run it again, so
+//! someone has to look at the task.
+//! When `DEAD_LETTER_WEBHOOK_URL` is set, the worker's post-handler callbacks
+//! include a [`DeadLetterAlerter`], which POSTs each dead-lettered step to
+//! that URL:
+//!
+//! ```json
+//! {"task_uuid": "...", "step_name": "process_payment",
+//!  "category": "permanent", "error": "Payment declined: insufficient funds"}
+//! ```
+//!
+//! Alerts are sent in the background, so a slow or down endpoint never delays
+//! step processing. Each alert is retried with jittered exponential backoff;
+//! after several consecutive undeliverable alerts a [`CircuitBreaker`] skips
+//! alerts (logging them instead) until a cooldown has passed.
+//!
+//! Whether or not alerting is configured, a [`DeadLetterCallback`] records
+//! every dead-lettered step in the `failed_steps` table, so operators have a
+//! durable record of permanent failures (`GET /debug/failed-steps`).
+//!
+//! ## Configuration
+//!
+//! | Env var | Default | Meaning |
+//! |---------|---------|---------|
+//! | `DEAD_LETTER_WEBHOOK_URL` | unset | Alert endpoint (unset disables alerting) |
+//! | `DEAD_LETTER_WEBHOOK_MAX_ATTEMPTS` | 3 | Deliveries tried per alert |
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use tasker_shared::messaging::StepExecutionResult;
+use tasker_shared::types::base::TaskSequenceStep;
+use tasker_worker::worker::handlers::PostHandlerCallback;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::circuit_breaker::CircuitBreaker;
+use crate::db::AppDb;
+use crate::models::FailedStep;
+use crate::retry::{jittered_delay, FailureCategory};
+
+/// Most rows returned by [`recent_failed_steps`].
+pub const MAX_FAILED_STEPS: i64 = 100;
+
+/// The alert payload for one dead-lettered step.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeadLetterAlert {
+    pub task_uuid: Uuid,
+    pub step_name: String,
+    /// The failure category (see [`FailureCategory`]).
+    pub category: String,
+    pub error: String,
+}
+
+impl DeadLetterAlert {
+    /// The alert for a step result, or `None` unless the step failed with a
+    /// non-retryable error, or with a retryable one after `retries_exhausted`.
+    pub fn from_result(
+        task_uuid: Uuid,
+        step_name: &str,
+        result: &StepExecutionResult,
+        retries_exhausted: bool,
+    ) -> Option<Self> {
+        let error = result.error.as_ref().filter(|_| !result.success)?;
+        if error.retryable && !retries_exhausted {
+            return None;
+        }
+        Some(Self {
+            task_uuid,
+            step_name: step_name.to_string(),
+            category: error
+                .error_type
+                .clone()
+                .unwrap_or_else(|| FailureCategory::Permanent.as_str().to_string()),
+            error: error.message.clone(),
+        })
+    }
+
+    /// The alert for the result of `step`, treating its retries as exhausted
+    /// once this attempt is its last (see [`retries_exhausted`]).
+    pub fn from_step(step: &TaskSequenceStep, result: &StepExecutionResult) -> Option<Self> {
+        Self::from_result(
+            step.task.task.task_uuid,
+            &step.workflow_step.name,
+            result,
+            retries_exhausted(step),
+        )
+    }
+}
+
+/// Whether `step` is on its last attempt, so a retryable failure will not be
+/// retried. `attempts` counts the current attempt; a step without a limit is
+/// never exhausted.
+pub fn retries_exhausted(step: &TaskSequenceStep) -> bool {
+    match (step.workflow_step.attempts, step.workflow_step.max_attempts) {
+        (Some(attempts), Some(max_attempts)) => attempts >= max_attempts,
+        _ => false,
+    }
+}
+
+/// Alert webhook settings.
+#[derive(Debug, Clone)]
+pub struct AlertWebhookConfig {
+    pub url: String,
+    /// Deliveries tried per alert (at least 1).
+    pub max_attempts: u32,
+    /// Delay before the first retry; doubled (then jittered down to as
+    /// little as half) for each further retry.
+    pub retry_delay: Duration,
+    /// Timeout for a single delivery.
+    pub timeout: Duration,
+    /// Consecutive undeliverable alerts that open the circuit breaker.
+    pub failure_threshold: u32,
+    /// How long the open breaker skips alerts.
+    pub cooldown: Duration,
+}
+
+impl AlertWebhookConfig {
+    /// Default delivery settings for `url`.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            max_attempts: 3,
+            retry_delay: Duration::from_millis(500),
+            timeout: Duration::from_secs(5),
+            failure_threshold: 5,
+            cooldown: Duration::from_secs(60),
+        }
+    }
+
+    /// Read `DEAD_LETTER_WEBHOOK_URL` and `DEAD_LETTER_WEBHOOK_MAX_ATTEMPTS`.
+    /// Returns `None` when no URL is configured.
+    pub fn from_env() -> Option<Self> {
+        let url = std::env::var("DEAD_LETTER_WEBHOOK_URL").ok()?;
+        if url.trim().is_empty() {
+            return None;
+        }
+        let mut config = Self::new(url.trim());
+        if let Some(max_attempts) = std::env::var("DEAD_LETTER_WEBHOOK_MAX_ATTEMPTS")
+            .ok()
+            .and_then(|v| v.trim().parse::<u32>().ok())
+        {
+            config.max_attempts = max_attempts;
+        }
+        Some(config)
+    }
+}
+
+/// Why an alert was not delivered.
+#[derive(Debug, thiserror::Error)]
+pub enum AlertError {
+    /// The breaker is open after repeated delivery failures.
+    #[error("alert webhook circuit is open")]
+    CircuitOpen,
+
+    #[error("alert webhook request failed: {0}")]
+    Request(#[from] reqwest::Error),
+
+    #[error("alert webhook returned {0}")]
+    Status(StatusCode),
+}
+
+/// Delivers alerts to the webhook, with retries and a circuit breaker.
+#[derive(Debug)]
+pub struct AlertWebhook {
+    http: reqwest::Client,
+    config: AlertWebhookConfig,
+    breaker: CircuitBreaker,
+}
+
+impl AlertWebhook {
+    pub fn new(config: AlertWebhookConfig) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            breaker: CircuitBreaker::new(config.failure_threshold, config.cooldown),
+            config,
+        }
+    }
+
+    pub fn breaker(&self) -> &CircuitBreaker {
+        &self.breaker
+    }
+
+    /// Deliver an alert, retrying failed deliveries. An alert that fails every
+    /// attempt counts as one failure towards opening the breaker.
+    pub async fn send(&self, alert: &DeadLetterAlert) -> Result<(), AlertError> {
+        if !self.breaker.allow() {
+            return Err(AlertError::CircuitOpen);
+        }
+
+        let max_attempts = self.config.max_attempts.max(1);
+        let mut attempt = 1;
+        loop {
+            match self.post(alert).await {
+                Ok(()) => {
+                    self.breaker.record_success();
+                    return Ok(());
+                }
+                Err(e) if attempt >= max_attempts => {
+                    self.breaker.record_failure();
+                    return Err(e);
+                }
+                Err(e) => {
+                    warn!(
+                        "Dead-letter alert for task {} failed (attempt {}/{}): {}",
+                        alert.task_uuid, attempt, max_attempts, e
+                    );
+                    tokio::time::sleep(jittered_delay(self.config.retry_delay, attempt)).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    async fn post(&self, alert: &DeadLetterAlert) -> Result<(), AlertError> {
+        let response = self
+            .http
+            .post(&self.config.url)
+            .timeout(self.config.timeout)
+            .json(alert)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(AlertError::Status(response.status()));
+        }
+        Ok(())
+    }
+}
+
+/// Post-handler callback that alerts the webhook about dead-lettered steps.
+pub struct DeadLetterAlerter {
+    webhook: Arc<AlertWebhook>,
+}
+
+impl DeadLetterAlerter {
+    pub fn new(config: AlertWebhookConfig) -> Self {
+        Self {
+            webhook: Arc::new(AlertWebhook::new(config)),
+        }
+    }
+
+    /// Send an alert in the background, logging the outcome.
+    pub fn notify(&self, alert: DeadLetterAlert) -> JoinHandle<()> {
+        let webhook = self.webhook.clone();
+        tokio::spawn(async move {
+            match webhook.send(&alert).await {
+                Ok(()) => info!(
+                    "Sent dead-letter alert for step {} of task {}",
+                    alert.step_name, alert.task_uuid
+                ),
+                Err(e) => warn!(
+                    "Dead-letter alert for step {} of task {} not delivered ({}): {}",
+                    alert.step_name, alert.task_uuid, e, alert.error
+                ),
+            }
+        })
+    }
+}
+
+#[async_trait]
+impl PostHandlerCallback for DeadLetterAlerter {
+    async fn on_handler_complete(
+        &self,
+        step: &TaskSequenceStep,
+        result: &StepExecutionResult,
+        _worker_id: &str,
+    ) {
+        if let Some(alert) = DeadLetterAlert::from_step(step, result) {
+            self.notify(alert);
+        }
+    }
+
+    fn name(&self) -> &str {
+        "dead_letter_alerter"
+    }
+}
+
+/// Record a dead-lettered step in `failed_steps`.
+pub async fn record_failed_step(pool: &AppDb, alert: &DeadLetterAlert) -> sqlx::Result<()> {
+    sqlx::query(
+        "INSERT INTO failed_steps (task_uuid, step_name, category, error) VALUES ($1, $2, $3, $4)",
+    )
+    .bind(alert.task_uuid)
+    .bind(&alert.step_name)
+    .bind(&alert.category)
+    .bind(&alert.error)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// The most recent dead-lettered steps, newest first, optionally for one task.
+pub async fn recent_failed_steps(
+    pool: &AppDb,
+    task_uuid: Option<Uuid>,
+    limit: i64,
+) -> sqlx::Result<Vec<FailedStep>> {
+    sqlx::query_as(
+        r#"
+        SELECT * FROM failed_steps
+        WHERE $1::uuid IS NULL OR task_uuid = $1
+        ORDER BY occurred_at DESC, id DESC
+        LIMIT $2
+        "#,
+    )
+    .bind(task_uuid)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+/// Post-handler callback that records dead-lettered steps in `failed_steps`.
+///
+/// A failed write is logged and otherwise ignored; it never affects the step.
+pub struct DeadLetterCallback {
+    pool: AppDb,
+}
+
+impl DeadLetterCallback {
+    pub fn new(pool: AppDb) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl PostHandlerCallback for DeadLetterCallback {
+    async fn on_handler_complete(
+        &self,
+        step: &TaskSequenceStep,
+        result: &StepExecutionResult,
+        _worker_id: &str,
+    ) {
+        let Some(alert) = DeadLetterAlert::from_step(step, result) else {
+            return;
+        };
+        if let Err(e) = record_failed_step(&self.pool, &alert).await {
+            warn!(
+                "Failed to record dead-lettered step {} of task {}: {}",
+                alert.step_name, alert.task_uuid, e
+            );
+        }
+    }
+
+    fn name(&self) -> &str {
+        "dead_letter_callback"
+    }
+}
@@ -0,0 +1,50 @@
+//! Customer email normalization.
+//!
+//! Emails are keys: orders, service requests, and compliance checks are looked
+//! up by customer email, and user registration is idempotent per email. The
+//! create routes store [`normalize_email`]'s form so `User@Example.com` and
+//! ` user@example.com` belong to the same customer, after
+//! [`validate_email`] has rejected addresses that could never be delivered.
+
+use crate::error::ApiError;
+
+/// Trim surrounding whitespace and lowercase the address.
+///
+/// The whole address is lowercased, local part included: no mail provider
+/// this example deals with treats local parts case-sensitively.
+pub fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+/// Reject an address that is not shaped like `local@domain.tld`.
+///
+/// Expects the [`normalize_email`]ed form. This is a shape check, not RFC 5322:
+/// one `@`, a non-empty local part, a dotted domain, and no whitespace.
+/// `field` names the request field in the 422 response.
+pub fn validate_email(field: &str, email: &str) -> Result<(), ApiError> {
+    if email.is_empty() {
+        return Err(ApiError::validation(
+            field,
+            format!("{field} must not be blank"),
+        ));
+    }
+
+    let well_formed = match email.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !domain.contains('@')
+                && domain.split('.').count() > 1
+                && domain.split('.').all(|label| !label.is_empty())
+                && !email.chars().any(char::is_whitespace)
+        }
+        None => false,
+    };
+    if !well_formed {
+        return Err(ApiError::validation(
+            field,
+            format!("{field} must be an email address like user@example.com, got '{email}'"),
+        ));
+    }
+
+    Ok(())
+}
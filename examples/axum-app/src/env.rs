@@ -0,0 +1,44 @@
+//! Parsing helpers for optional environment variables.
+//!
+//! An unset variable reads as `Ok(None)`, so callers fall back to their
+//! default. A set but unparsable value is an error naming the variable, so a
+//! typo fails startup instead of being ignored.
+
+use std::fmt::Display;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// The trimmed value of `name` parsed as `T`.
+pub(crate) fn env_parse<T>(name: &str) -> Result<Option<T>, String>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    match std::env::var(name) {
+        Ok(raw) => raw
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|e| format!("Invalid {} '{}': {}", name, raw, e)),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Like [`env_parse`], but zero is rejected.
+pub(crate) fn env_nonzero<T>(name: &str) -> Result<Option<T>, String>
+where
+    T: FromStr + Default + PartialEq,
+    T::Err: Display,
+{
+    match env_parse::<T>(name)? {
+        Some(value) if value == T::default() => {
+            Err(format!("Invalid {} '0': expected at least 1", name))
+        }
+        value => Ok(value),
+    }
+}
+
+/// The value of `name` as a whole number of seconds.
+pub(crate) fn env_secs(name: &str) -> Result<Option<Duration>, String> {
+    Ok(env_parse(name)?.map(Duration::from_secs))
+}
@@ -0,0 +1,243 @@
+//! API error type for route handlers.
+//!
+//! Routes return `Result<_, ApiError>`, and every variant renders the same JSON
+//! envelope: an `error` object with a machine-readable `code` and a `message`,
+//! plus variant-specific fields. Validation failures name the offending field:
+//!
+//! ```json
+//! { "error": { "code": "validation_failed", "field": "shipping_address.zip", "message": "..." } }
+//! ```
+//!
+//! A missing row renders a 404 `not_found`, a malformed request body a 400
+//! `bad_request`, a missing or wrong admin token a 401 `unauthorized` with
+//! `WWW-Authenticate: Bearer`, and a failed orchestration call a 502
+//! `upstream_failed`.
+//! Database errors convert via `From<sqlx::Error>` and render a 500
+//! `database_error`; the underlying error is logged, not returned. Plain status
+//! codes also convert via `From` and render with a code derived from the
+//! status (e.g. `conflict` for 409).
+//!
+//! Exceeded tenant quotas render a 429 with a `quota_exceeded` body and a
+//! `Retry-After` header, and so do clients over the request rate limit, with a
+//! `rate_limited` body. Requests shed by a route's concurrency limit render a
+//! 503 with an `overloaded` body and `Retry-After: 1`. A create request whose
+//! workflow task orchestration did not accept renders a 502 (503 while
+//! orchestration is unreachable) with a `task_submission_failed` body naming
+//! the row, which is marked `failed`.
+
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use tracing::error;
+
+use crate::orchestration::OrchestrationError;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ApiError {
+    /// A request field failed validation (422 Unprocessable Entity).
+    #[error("{field}: {message}")]
+    Validation { field: String, message: String },
+
+    /// A tenant exceeded a quota or rate limit (429 Too Many Requests).
+    #[error("tenant {tenant_id} exceeded {quota} limit of {limit}")]
+    QuotaExceeded {
+        tenant_id: String,
+        /// Which limit was hit (`workflows_per_day` or `requests_per_minute`).
+        quota: &'static str,
+        limit: i64,
+        /// Seconds until the current window resets.
+        retry_after_secs: u64,
+    },
+
+    /// A client exceeded the request rate limit (429 Too Many Requests).
+    #[error("request rate limit exceeded")]
+    RateLimited {
+        /// Seconds until the client may send another request.
+        retry_after_secs: u64,
+    },
+
+    /// A route is at its concurrency limit (503 Service Unavailable).
+    #[error("too many concurrent requests to {route}")]
+    Overloaded { route: String },
+
+    /// Orchestration did not create a new row's workflow task (503 Service
+    /// Unavailable while orchestration is unreachable, 502 Bad Gateway otherwise).
+    #[error("task submission for row {id} failed: {message}")]
+    SubmissionFailed {
+        id: i32,
+        unavailable: bool,
+        message: String,
+    },
+
+    /// The requested row does not exist (404 Not Found).
+    #[error("not found")]
+    NotFound,
+
+    /// The request could not be read, e.g. malformed JSON (400 Bad Request).
+    #[error("bad request: {0}")]
+    BadRequest(String),
+
+    /// An admin route was called without the admin bearer token
+    /// (401 Unauthorized).
+    #[error("unauthorized")]
+    Unauthorized,
+
+    /// Orchestration failed to answer a call the route depends on
+    /// (502 Bad Gateway).
+    #[error("upstream request failed: {0}")]
+    Upstream(String),
+
+    /// A database query failed (500 Internal Server Error). The message is
+    /// for logs only; the response body does not include it.
+    #[error("database error: {0}")]
+    Db(String),
+
+    /// Any other HTTP status.
+    #[error("{0}")]
+    Status(StatusCode),
+}
+
+impl ApiError {
+    pub fn validation(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::Validation {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+
+    pub fn submission_failed(id: i32, error: &OrchestrationError) -> Self {
+        Self::SubmissionFailed {
+            id,
+            unavailable: error.is_outage() || matches!(error, OrchestrationError::CircuitOpen),
+            message: error.to_string(),
+        }
+    }
+
+    /// The response status.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::Validation { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::QuotaExceeded { .. } | Self::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+            Self::Overloaded { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            Self::SubmissionFailed { unavailable, .. } => {
+                if *unavailable {
+                    StatusCode::SERVICE_UNAVAILABLE
+                } else {
+                    StatusCode::BAD_GATEWAY
+                }
+            }
+            Self::NotFound => StatusCode::NOT_FOUND,
+            Self::BadRequest(_) => StatusCode::BAD_REQUEST,
+            Self::Unauthorized => StatusCode::UNAUTHORIZED,
+            Self::Upstream(_) => StatusCode::BAD_GATEWAY,
+            Self::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::Status(status) => *status,
+        }
+    }
+
+    /// The `{"error": {...}}` response body.
+    pub fn body(&self) -> serde_json::Value {
+        let error = match self {
+            Self::Validation { field, message } => serde_json::json!({
+                "code": "validation_failed",
+                "field": field,
+                "message": message,
+            }),
+            Self::QuotaExceeded {
+                tenant_id,
+                quota,
+                limit,
+                retry_after_secs,
+            } => serde_json::json!({
+                "code": "quota_exceeded",
+                "tenant_id": tenant_id,
+                "quota": quota,
+                "limit": limit,
+                "retry_after_secs": retry_after_secs,
+            }),
+            Self::RateLimited { retry_after_secs } => serde_json::json!({
+                "code": "rate_limited",
+                "retry_after_secs": retry_after_secs,
+                "message": "too many requests; retry after the Retry-After delay",
+            }),
+            Self::Overloaded { route } => serde_json::json!({
+                "code": "overloaded",
+                "route": route,
+                "message": "too many concurrent requests; retry shortly",
+            }),
+            Self::SubmissionFailed { id, message, .. } => serde_json::json!({
+                "code": "task_submission_failed",
+                "id": id,
+                "message": format!("workflow task could not be submitted: {message}"),
+            }),
+            Self::NotFound => code_and_message("not_found", "resource not found"),
+            Self::BadRequest(message) => code_and_message("bad_request", message),
+            Self::Unauthorized => {
+                code_and_message("unauthorized", "a valid admin bearer token is required")
+            }
+            Self::Upstream(message) => code_and_message("upstream_failed", message),
+            Self::Db(_) => code_and_message(
+                "database_error",
+                "the request could not be completed due to a database error",
+            ),
+            Self::Status(status) => {
+                let reason = status.canonical_reason().unwrap_or("error");
+                let code = reason.to_ascii_lowercase().replace([' ', '-'], "_");
+                code_and_message(&code, reason)
+            }
+        };
+        serde_json::json!({ "error": error })
+    }
+}
+
+impl From<StatusCode> for ApiError {
+    fn from(status: StatusCode) -> Self {
+        match status {
+            StatusCode::NOT_FOUND => Self::NotFound,
+            status => Self::Status(status),
+        }
+    }
+}
+
+impl From<sqlx::Error> for ApiError {
+    /// Logs the error, since `?` leaves no other trace of it.
+    fn from(e: sqlx::Error) -> Self {
+        error!("Database error: {}", e);
+        Self::Db(e.to_string())
+    }
+}
+
+fn code_and_message(code: &str, message: &str) -> serde_json::Value {
+    serde_json::json!({
+        "code": code,
+        "message": message,
+    })
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let mut response = (self.status(), Json(self.body())).into_response();
+        let retry_after = match &self {
+            Self::QuotaExceeded {
+                retry_after_secs, ..
+            }
+            | Self::RateLimited { retry_after_secs } => {
+                Some(header::HeaderValue::from(*retry_after_secs))
+            }
+            Self::Overloaded { .. } => Some(header::HeaderValue::from_static("1")),
+            _ => None,
+        };
+        if let Some(retry_after) = retry_after {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, retry_after);
+        }
+        if matches!(self, Self::Unauthorized) {
+            response.headers_mut().insert(
+                header::WWW_AUTHENTICATE,
+                header::HeaderValue::from_static("Bearer"),
+            );
+        }
+        response
+    }
+}
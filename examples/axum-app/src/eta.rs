@@ -0,0 +1,157 @@
+//! Estimated completion time for in-progress workflows.
+//!
+//! The status endpoints (`GET /orders/{id}`, `/analytics/{id}`,
+//! `/services/{id}`, `/compliance/{id}`) return an
+//! `estimated_completion_at` for rows that are still `processing`:
+//!
+//! ```text
+//! estimated_completion_at = now + average_duration * (1 - completion)
+//! ```
+//!
+//! - `average_duration` is the mean `updated_at - created_at` of the most
+//!   recently completed rows of the same domain table (one table per workflow
+//!   type), so it covers submission, queueing, and execution
+//! - `completion` is the task's `completion_percentage` from orchestration
+//!
+//! The estimate is `null` when the row is not processing, when fewer than
+//! [`MIN_HISTORY`] rows have completed, or when the task cannot be read.
+
+use std::time::Duration;
+
+use chrono::NaiveDateTime;
+use serde_json::Value;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::db::AppDb;
+use crate::orchestration::OrchestrationClient;
+
+/// Fewest completed rows an estimate is based on.
+pub const MIN_HISTORY: i64 = 3;
+
+/// Most recent completed rows averaged for an estimate.
+pub const HISTORY_WINDOW: i64 = 50;
+
+/// Statuses of rows whose workflow finished successfully.
+const COMPLETED_STATUSES: &[&str] = &["complete", "completed"];
+
+/// Average duration of a table's recently completed rows.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DurationHistory {
+    /// Mean `updated_at - created_at`.
+    pub average: Duration,
+    /// Rows the average was taken over.
+    pub samples: i64,
+    /// The database's current time, for anchoring an estimate.
+    pub now: NaiveDateTime,
+}
+
+/// Average the durations of the `window` most recently completed rows in
+/// `table`. Returns `None` when fewer than [`MIN_HISTORY`] rows have completed.
+pub async fn duration_history(
+    pool: &AppDb,
+    table: &str,
+    window: i64,
+) -> Result<Option<DurationHistory>, sqlx::Error> {
+    let statuses: Vec<String> = COMPLETED_STATUSES.iter().map(|s| s.to_string()).collect();
+    let (samples, average_secs, now): (i64, Option<f64>, NaiveDateTime) =
+        sqlx::query_as(&format!(
+            r#"
+            SELECT COUNT(*),
+                   EXTRACT(EPOCH FROM AVG(updated_at - created_at))::float8,
+                   LOCALTIMESTAMP
+            FROM (
+                SELECT created_at, updated_at FROM {table}
+                WHERE status = ANY($1)
+                ORDER BY updated_at DESC
+                LIMIT $2
+            ) recent
+            "#
+        ))
+        .bind(&statuses)
+        .bind(window)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(match average_secs {
+        Some(secs) if samples >= MIN_HISTORY => Some(DurationHistory {
+            average: Duration::from_secs_f64(secs.max(0.0)),
+            samples,
+            now,
+        }),
+        _ => None,
+    })
+}
+
+/// How far along a task is, from 0.0 to 1.0.
+///
+/// Reads `completion_percentage`, falling back to `completed_steps /
+/// total_steps` when orchestration omits it.
+pub fn completion_fraction(task: &Value) -> Option<f64> {
+    let fraction = match task["completion_percentage"].as_f64() {
+        Some(percentage) => percentage / 100.0,
+        None => {
+            let total = task["total_steps"].as_f64().filter(|total| *total > 0.0)?;
+            task["completed_steps"].as_f64()? / total
+        }
+    };
+    Some(fraction.clamp(0.0, 1.0))
+}
+
+/// Project the remaining share of `average` forward from `now`.
+pub fn estimate(now: NaiveDateTime, average: Duration, completion: f64) -> NaiveDateTime {
+    let remaining = average.mul_f64(1.0 - completion.clamp(0.0, 1.0));
+    now + chrono::Duration::from_std(remaining).unwrap_or_else(|_| chrono::Duration::zero())
+}
+
+/// Estimate when a row of `table` will complete, or `None` if it cannot be
+/// estimated. Lookup failures are logged, never returned: a missing estimate
+/// must not fail the status request.
+pub async fn estimated_completion_at(
+    pool: &AppDb,
+    orchestration: &OrchestrationClient,
+    table: &str,
+    status: &str,
+    task_uuid: Option<Uuid>,
+) -> Option<NaiveDateTime> {
+    let task_uuid = task_uuid.filter(|_| status == "processing")?;
+    let history = recent_history(pool, table).await?;
+
+    let task = match orchestration.get_task(task_uuid).await {
+        Ok(task) => task,
+        Err(e) => {
+            warn!("Failed to fetch task {} for completion estimate: {}", task_uuid, e);
+            return None;
+        }
+    };
+
+    let completion = completion_fraction(&task)?;
+    Some(estimate(history.now, history.average, completion))
+}
+
+/// [`estimated_completion_at`] for a row whose task the caller has already
+/// fetched, so orchestration is not asked twice.
+pub async fn estimated_completion_from_task(
+    pool: &AppDb,
+    table: &str,
+    status: &str,
+    task: &Value,
+) -> Option<NaiveDateTime> {
+    if status != "processing" {
+        return None;
+    }
+    let history = recent_history(pool, table).await?;
+    let completion = completion_fraction(task)?;
+    Some(estimate(history.now, history.average, completion))
+}
+
+/// [`duration_history`] over [`HISTORY_WINDOW`] rows, logging a failed read.
+async fn recent_history(pool: &AppDb, table: &str) -> Option<DurationHistory> {
+    match duration_history(pool, table, HISTORY_WINDOW).await {
+        Ok(history) => history,
+        Err(e) => {
+            warn!("Failed to read {} duration history: {}", table, e);
+            None
+        }
+    }
+}
@@ -0,0 +1,200 @@
+//! Request body extraction with configurable field aliases.
+//!
+//! Clients do not always agree on field names (`email` vs `customer_email`,
+//! `items` vs `cart_items`). [`AliasedJson`] renames aliased top-level body
+//! fields to their canonical names before deserializing, using the
+//! [`FieldAliases`] map installed by `create_app`. With no aliases configured
+//! it behaves like `axum::Json` and the strict schema applies.
+//!
+//! Aliases are scoped per endpoint because the same alias can mean different
+//! things: `email` is `customer_email` for orders but `user_email` for services.
+//!
+//! ## Configuration
+//!
+//! `FIELD_ALIASES` is a comma-separated list of `scope.alias=canonical` entries,
+//! plus the keyword `common` for the built-in set:
+//!
+//! ```text
+//! FIELD_ALIASES=common,orders.buyer_email=customer_email
+//! ```
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{FromRequest, Request};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::error::ApiError;
+use crate::models::{
+    CreateAnalyticsJobRequest, CreateComplianceCheckRequest, CreateOrderBatchRequest,
+    CreateOrderRequest, CreateServiceRequest, UpdateOrderRequest,
+};
+
+/// The built-in aliases enabled by `FIELD_ALIASES=common`, as
+/// `(scope, alias, canonical)`.
+pub const COMMON_ALIASES: &[(&str, &str, &str)] = &[
+    ("orders", "email", "customer_email"),
+    ("orders", "items", "cart_items"),
+    ("orders", "payment", "payment_token"),
+    ("orders", "address", "shipping_address"),
+    ("analytics", "name", "job_name"),
+    ("services", "email", "user_email"),
+    ("services", "name", "user_name"),
+    ("compliance", "email", "customer_email"),
+    ("compliance", "amount", "refund_amount"),
+];
+
+/// A request body type that accepts field aliases under a given scope.
+pub trait AliasScope {
+    /// Scope name used in `FIELD_ALIASES` entries (e.g. `orders`).
+    const SCOPE: &'static str;
+}
+
+impl AliasScope for CreateOrderRequest {
+    const SCOPE: &'static str = "orders";
+}
+
+/// The batch envelope has no aliases of its own; each order in it is
+/// aliased under `orders`.
+impl AliasScope for CreateOrderBatchRequest {
+    const SCOPE: &'static str = "order_batch";
+}
+
+impl AliasScope for UpdateOrderRequest {
+    const SCOPE: &'static str = "orders";
+}
+
+impl AliasScope for CreateAnalyticsJobRequest {
+    const SCOPE: &'static str = "analytics";
+}
+
+impl AliasScope for CreateServiceRequest {
+    const SCOPE: &'static str = "services";
+}
+
+impl AliasScope for CreateComplianceCheckRequest {
+    const SCOPE: &'static str = "compliance";
+}
+
+/// Per-scope map of `alias -> canonical` field names.
+#[derive(Debug, Clone, Default)]
+pub struct FieldAliases {
+    scopes: HashMap<String, HashMap<String, String>>,
+}
+
+impl FieldAliases {
+    /// The built-in [`COMMON_ALIASES`] set.
+    pub fn common() -> Self {
+        let mut aliases = Self::default();
+        for (scope, alias, canonical) in COMMON_ALIASES {
+            aliases.insert(scope, alias, canonical);
+        }
+        aliases
+    }
+
+    /// Parse a `FIELD_ALIASES` value. Malformed entries are rejected.
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let mut aliases = Self::default();
+        for entry in raw.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            if entry == "common" {
+                for (scope, alias, canonical) in COMMON_ALIASES {
+                    aliases.insert(scope, alias, canonical);
+                }
+                continue;
+            }
+
+            let parsed = entry.split_once('=').and_then(|(lhs, canonical)| {
+                let (scope, alias) = lhs.trim().split_once('.')?;
+                Some((scope.trim(), alias.trim(), canonical.trim()))
+            });
+            match parsed {
+                Some((scope, alias, canonical))
+                    if !scope.is_empty() && !alias.is_empty() && !canonical.is_empty() =>
+                {
+                    aliases.insert(scope, alias, canonical);
+                }
+                _ => {
+                    return Err(format!(
+                        "Invalid FIELD_ALIASES entry '{entry}', expected scope.alias=canonical"
+                    ))
+                }
+            }
+        }
+        Ok(aliases)
+    }
+
+    /// Read `FIELD_ALIASES`. Unset means no aliases (strict schema).
+    pub fn from_env() -> Result<Self, String> {
+        match std::env::var("FIELD_ALIASES") {
+            Ok(raw) => Self::parse(&raw),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    pub fn insert(&mut self, scope: &str, alias: &str, canonical: &str) {
+        self.scopes
+            .entry(scope.to_string())
+            .or_default()
+            .insert(alias.to_string(), canonical.to_string());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scopes.values().all(HashMap::is_empty)
+    }
+
+    /// Rename aliased top-level fields of `body` to their canonical names.
+    ///
+    /// An alias is ignored when the canonical field is also present, so a
+    /// client sending both gets the canonical value.
+    pub fn apply(&self, scope: &str, body: &mut Value) {
+        let (Some(aliases), Some(object)) = (self.scopes.get(scope), body.as_object_mut()) else {
+            return;
+        };
+
+        for (alias, canonical) in aliases {
+            if object.contains_key(canonical) {
+                continue;
+            }
+            if let Some(value) = object.remove(alias) {
+                object.insert(canonical.clone(), value);
+            }
+        }
+    }
+}
+
+/// JSON body extractor that applies [`FieldAliases`] before deserializing.
+///
+/// Malformed JSON is rejected with a 400 `bad_request`, and a body that does
+/// not match `T` with a 422 `validation_failed`.
+pub struct AliasedJson<T>(pub T);
+
+impl<S, T> FromRequest<S> for AliasedJson<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned + AliasScope,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let aliases = req.extensions().get::<Arc<FieldAliases>>().cloned();
+        let Json(mut body) = Json::<Value>::from_request(req, state)
+            .await
+            .map_err(|rejection| match rejection.status() {
+                StatusCode::BAD_REQUEST => ApiError::BadRequest(rejection.body_text()),
+                status => ApiError::Status(status),
+            })
+            .map_err(IntoResponse::into_response)?;
+
+        if let Some(aliases) = aliases {
+            aliases.apply(T::SCOPE, &mut body);
+        }
+
+        serde_json::from_value(body)
+            .map(AliasedJson)
+            .map_err(|e| ApiError::validation("body", e.to_string()).into_response())
+    }
+}
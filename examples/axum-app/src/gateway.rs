@@ -0,0 +1,127 @@
+//! Payment gateway boundary for the e-commerce and payments handlers.
+//!
+//! `process_payment` charges through a [`PaymentGateway`] and
+//! `process_gateway_refund` refunds through one. The handler registry holds
+//! the gateway (see
+//! [`crate::handler_registry::AxumHandlerRegistry::set_payment_gateway`]) and
+//! starts with the [`MockPaymentGateway`], which approves every request except
+//! these test inputs:
+//!
+//! | Input | Result |
+//! |-------|--------|
+//! | `payment_token: tok_test_declined` | Charge declined |
+//! | `payment_token: tok_test_insufficient_funds` | Charge declined |
+//! | `payment_token: tok_test_network_error` | Charge fails as retryable |
+//! | `payment_id` containing `pay_test_gateway_timeout` | Refund fails as retryable |
+//! | `payment_id` containing `pay_test_gateway_error` | Refund declined |
+
+use uuid::Uuid;
+
+/// A card charge.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChargeRequest<'a> {
+    pub payment_token: &'a str,
+    pub amount: f64,
+    pub currency: &'a str,
+    pub payment_method: &'a str,
+}
+
+/// An approved charge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChargeReceipt {
+    pub payment_id: String,
+    pub transaction_id: String,
+    pub authorization_code: String,
+}
+
+/// A refund of an earlier payment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RefundRequest<'a> {
+    pub payment_id: &'a str,
+    pub amount: f64,
+    pub payment_method: &'a str,
+}
+
+/// An approved refund.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RefundReceipt {
+    pub refund_id: String,
+    pub gateway_transaction_id: String,
+    pub authorization_code: String,
+}
+
+/// Why the gateway did not approve a request. The messages follow
+/// [`crate::retry::FailureCategory::classify`], so a step failing with an
+/// `Unavailable` error is retried and one failing with `Declined` is not.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum GatewayError {
+    /// The gateway refused the request; retrying will not help.
+    #[error("{0}")]
+    Declined(String),
+    /// The gateway could not be reached or did not answer in time.
+    #[error("{0} (retryable)")]
+    Unavailable(String),
+}
+
+/// Charges and refunds payments.
+pub trait PaymentGateway: Send + Sync {
+    fn charge(&self, request: &ChargeRequest<'_>) -> Result<ChargeReceipt, GatewayError>;
+
+    fn refund(&self, request: &RefundRequest<'_>) -> Result<RefundReceipt, GatewayError>;
+
+    /// Gateway name recorded in step results.
+    fn name(&self) -> &str;
+}
+
+/// Simulated gateway keyed on test tokens and payment ids (see the module docs).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MockPaymentGateway;
+
+impl PaymentGateway for MockPaymentGateway {
+    fn charge(&self, request: &ChargeRequest<'_>) -> Result<ChargeReceipt, GatewayError> {
+        match request.payment_token {
+            "tok_test_declined" => return Err(GatewayError::Declined("Card was declined".into())),
+            "tok_test_insufficient_funds" => {
+                return Err(GatewayError::Declined("Insufficient funds on card".into()))
+            }
+            "tok_test_network_error" => {
+                return Err(GatewayError::Unavailable(
+                    "Payment gateway unreachable".into(),
+                ))
+            }
+            _ => {}
+        }
+
+        Ok(ChargeReceipt {
+            payment_id: format!("pay_{}", short_id(12)),
+            transaction_id: format!("txn_{}", short_id(12)),
+            authorization_code: format!("AUTH{}", short_id(6).to_uppercase()),
+        })
+    }
+
+    fn refund(&self, request: &RefundRequest<'_>) -> Result<RefundReceipt, GatewayError> {
+        if request.payment_id.contains("pay_test_gateway_timeout") {
+            return Err(GatewayError::Unavailable("Gateway timeout".into()));
+        }
+        if request.payment_id.contains("pay_test_gateway_error") {
+            return Err(GatewayError::Declined(
+                "Gateway refund failed: Gateway error".into(),
+            ));
+        }
+
+        Ok(RefundReceipt {
+            refund_id: format!("rfnd_{}", short_id(12)),
+            gateway_transaction_id: format!("gtx_{}", short_id(12)),
+            authorization_code: format!("AUTH{}", short_id(6).to_uppercase()),
+        })
+    }
+
+    fn name(&self) -> &str {
+        "MockPaymentGateway"
+    }
+}
+
+/// The first `len` hex digits of a random UUID.
+fn short_id(len: usize) -> String {
+    Uuid::new_v4().simple().to_string()[..len].to_string()
+}
@@ -0,0 +1,83 @@
+//! Handler manifest: which step callables the registry exposes.
+//!
+//! By default the registry registers every built-in handler under its own
+//! callable. A manifest (YAML, path in `HANDLER_MANIFEST`) replaces that set
+//! with an explicit mapping from callable to built-in handler identifier, so
+//! handlers can be disabled (left out) or exposed under another callable
+//! without recompiling:
+//!
+//! ```yaml
+//! handlers:
+//!   ecommerce_validate_cart: ecommerce_validate_cart
+//!   ecommerce_process_payment: ecommerce_process_payment
+//!   # notify_customer is left out, so its steps find no handler
+//! ```
+//!
+//! A handler identifier is the callable the built-in handler registers under
+//! (see `config/handler_manifest.example.yaml` for the full list). Every
+//! identifier must name a built-in handler; an unknown one is an error at
+//! startup rather than a step that silently finds no handler.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::handler_policy::UnmatchedPattern;
+
+/// A parsed manifest: callable -> built-in handler identifier.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HandlerManifest {
+    #[serde(default)]
+    pub handlers: BTreeMap<String, String>,
+}
+
+/// Why a manifest could not be loaded or applied.
+#[derive(Debug, thiserror::Error)]
+pub enum HandlerManifestError {
+    #[error("failed to read handler manifest {path}: {source}")]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("invalid handler manifest: {0}")]
+    Parse(#[from] serde_yaml::Error),
+
+    /// A manifest entry names a handler that does not exist.
+    #[error("handler manifest maps {callable} to unknown handler {handler}")]
+    UnknownHandler { callable: String, handler: String },
+
+    /// A handler allowlist or denylist pattern matches no handler.
+    #[error(transparent)]
+    Policy(#[from] UnmatchedPattern),
+
+    /// `ENABLED_NAMESPACES` is empty or names an unknown namespace.
+    #[error("{0}")]
+    Namespaces(String),
+}
+
+impl HandlerManifest {
+    /// Parse a manifest from YAML.
+    pub fn parse(yaml: &str) -> Result<Self, HandlerManifestError> {
+        Ok(serde_yaml::from_str(yaml)?)
+    }
+
+    /// Read and parse a manifest file.
+    pub fn load(path: &Path) -> Result<Self, HandlerManifestError> {
+        let yaml = std::fs::read_to_string(path).map_err(|source| HandlerManifestError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::parse(&yaml)
+    }
+
+    /// Load the manifest named by `HANDLER_MANIFEST`, if set.
+    pub fn from_env() -> Result<Option<Self>, HandlerManifestError> {
+        match std::env::var("HANDLER_MANIFEST") {
+            Ok(path) if !path.trim().is_empty() => Self::load(Path::new(path.trim())).map(Some),
+            _ => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,94 @@
+//! Per-handler outcome counts.
+//!
+//! The worker's post-handler callbacks include a [`MetricsCallback`], which
+//! counts each handler's completed and failed steps and keeps the message of
+//! its most recent failure. `GET /debug/handlers` serves the snapshot, so
+//! operators can see which handlers are failing without scraping logs:
+//!
+//! ```json
+//! {"ecommerce_process_payment": {"completed": 12, "failed": 2,
+//!  "last_error": "Payment declined: insufficient funds"}}
+//! ```
+//!
+//! Counts are kept in memory and reset when the process restarts; for rates
+//! over time use `handler_executions_total` from `GET /metrics`.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use serde::Serialize;
+use tasker_shared::messaging::StepExecutionResult;
+use tasker_shared::types::base::TaskSequenceStep;
+use tasker_worker::worker::handlers::PostHandlerCallback;
+
+/// Outcomes recorded for one handler.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct HandlerCounts {
+    pub completed: u64,
+    pub failed: u64,
+    /// Error message of the most recent failure.
+    pub last_error: Option<String>,
+}
+
+/// Outcome counts keyed by handler name, shared between the worker's
+/// callback and the debug route.
+#[derive(Debug, Clone, Default)]
+pub struct HandlerMetrics {
+    counts: Arc<Mutex<HashMap<String, HandlerCounts>>>,
+}
+
+impl HandlerMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count one step result for `handler`.
+    pub fn record(&self, handler: &str, result: &StepExecutionResult) {
+        let mut counts = self.counts.lock().expect("handler metrics lock poisoned");
+        let entry = counts.entry(handler.to_string()).or_default();
+        if result.success {
+            entry.completed += 1;
+        } else {
+            entry.failed += 1;
+            entry.last_error = result.error.as_ref().map(|error| error.message.clone());
+        }
+    }
+
+    /// Current counts, sorted by handler name.
+    pub fn snapshot(&self) -> BTreeMap<String, HandlerCounts> {
+        let counts = self.counts.lock().expect("handler metrics lock poisoned");
+        counts
+            .iter()
+            .map(|(handler, counts)| (handler.clone(), counts.clone()))
+            .collect()
+    }
+}
+
+/// Post-handler callback recording every step result in [`HandlerMetrics`].
+pub struct MetricsCallback {
+    metrics: HandlerMetrics,
+}
+
+impl MetricsCallback {
+    pub fn new(metrics: HandlerMetrics) -> Self {
+        Self { metrics }
+    }
+}
+
+#[async_trait]
+impl PostHandlerCallback for MetricsCallback {
+    async fn on_handler_complete(
+        &self,
+        step: &TaskSequenceStep,
+        result: &StepExecutionResult,
+        _worker_id: &str,
+    ) {
+        self.metrics
+            .record(&step.step_definition.handler.callable, result);
+    }
+
+    fn name(&self) -> &str {
+        "metrics_callback"
+    }
+}
@@ -0,0 +1,153 @@
+//! Handler allowlist and denylist.
+//!
+//! In a locked-down environment some handlers must never run, e.g. anything
+//! that sends real notifications. A [`HandlerPolicy`] names the handlers the
+//! registry may run: with an allowlist only matching handlers run, and handlers
+//! matching the denylist never run (the denylist wins). A disallowed handler is
+//! not registered. Its callable answers with a permanent "handler disabled"
+//! failure instead, so the step fails fast with a clear message rather than
+//! executing or waiting for a handler that never appears.
+//!
+//! ## Configuration
+//!
+//! `HANDLER_ALLOWLIST` and `HANDLER_DENYLIST` are comma-separated handler
+//! identifiers (the callable a built-in handler registers under). `*` matches
+//! any run of characters:
+//!
+//! ```text
+//! HANDLER_DENYLIST=*notify_customer,*send_welcome_sequence
+//! ```
+//!
+//! Every pattern must match at least one built-in handler, so a typo is an
+//! error at startup rather than a handler that silently stays enabled.
+
+use std::collections::BTreeSet;
+
+use serde::Serialize;
+
+/// Message prefix of the failure returned by a disabled handler.
+pub const DISABLED_MESSAGE: &str = "Handler disabled by policy";
+
+/// Which handlers may run.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct HandlerPolicy {
+    /// Only handlers matching one of these run (`None` = all).
+    pub allow: Option<BTreeSet<String>>,
+    /// Handlers matching one of these never run.
+    pub deny: BTreeSet<String>,
+}
+
+/// A policy pattern that matches no built-in handler.
+#[derive(Debug, thiserror::Error)]
+#[error("{list} pattern '{pattern}' matches no handler")]
+pub struct UnmatchedPattern {
+    pub list: &'static str,
+    pub pattern: String,
+}
+
+impl HandlerPolicy {
+    /// Run every handler.
+    pub fn allow_all() -> Self {
+        Self::default()
+    }
+
+    /// Run only handlers matching `patterns`.
+    pub fn allowing<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allow = Some(patterns.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Never run handlers matching `patterns`.
+    pub fn denying<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.deny.extend(patterns.into_iter().map(Into::into));
+        self
+    }
+
+    /// Read `HANDLER_ALLOWLIST` and `HANDLER_DENYLIST` (unset = no restriction).
+    pub fn from_env() -> Self {
+        let mut policy = Self::default();
+        if let Some(allow) = std::env::var("HANDLER_ALLOWLIST").ok().and_then(|raw| parse_list(&raw)) {
+            policy = policy.allowing(allow);
+        }
+        if let Some(deny) = std::env::var("HANDLER_DENYLIST").ok().and_then(|raw| parse_list(&raw)) {
+            policy = policy.denying(deny);
+        }
+        policy
+    }
+
+    /// Whether the handler registered under `name` may run.
+    pub fn allows(&self, name: &str) -> bool {
+        let allowed = self
+            .allow
+            .as_ref()
+            .is_none_or(|allow| allow.iter().any(|pattern| glob_match(pattern, name)));
+        allowed && !self.deny.iter().any(|pattern| glob_match(pattern, name))
+    }
+
+    /// Whether the policy restricts anything.
+    pub fn is_restrictive(&self) -> bool {
+        self.allow.is_some() || !self.deny.is_empty()
+    }
+
+    /// Check that every pattern matches at least one of `handlers`.
+    pub fn check_patterns<'a>(
+        &self,
+        handlers: impl IntoIterator<Item = &'a str> + Clone,
+    ) -> Result<(), UnmatchedPattern> {
+        let lists = self
+            .allow
+            .iter()
+            .flatten()
+            .map(|pattern| ("HANDLER_ALLOWLIST", pattern))
+            .chain(self.deny.iter().map(|pattern| ("HANDLER_DENYLIST", pattern)));
+        for (list, pattern) in lists {
+            if !handlers.clone().into_iter().any(|name| glob_match(pattern, name)) {
+                return Err(UnmatchedPattern {
+                    list,
+                    pattern: pattern.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Split a comma-separated list, ignoring blank entries (`None` if empty).
+fn parse_list(raw: &str) -> Option<Vec<String>> {
+    let patterns: Vec<String> = raw
+        .split(',')
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(str::to_string)
+        .collect();
+    (!patterns.is_empty()).then_some(patterns)
+}
+
+/// Match `name` against `pattern`, where `*` matches any run of characters.
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = parts.collect();
+    let Some(last) = parts.pop() else {
+        // No '*': the whole name must match
+        return rest.is_empty();
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
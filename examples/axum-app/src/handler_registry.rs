@@ -3,45 +3,249 @@
 //! Bridges the plain function handlers in `handlers/` to the `StepHandler` trait
 //! required by the tasker-worker dispatch system. Each function is wrapped in a
 //! `FunctionHandler` that extracts context and dependency results from the
-//! `TaskSequenceStep` and calls the underlying function.
+//! `TaskSequenceStep` and calls the underlying function. A handler may be
+//! registered with a [`ResultNormalizer`] that rewrites its result before it is
+//! returned to the worker, and with a [`RetryPolicy`] that decides whether its
+//! failures are retried.
+//!
+//! The set of callables can be narrowed or remapped without recompiling with a
+//! [`HandlerManifest`] (see [`AxumHandlerRegistry::with_manifest`]), and a
+//! [`HandlerPolicy`] can disable handlers outright (see
+//! [`AxumHandlerRegistry::with_policy`]).
+//!
+//! Handlers that need the application database are registered with a
+//! [`DbHandlerFn`] alongside their pure function. Once the application pool is
+//! attached with [`AxumHandlerRegistry::set_db`], steps run the database
+//! version; without it (SQLite, tests, `simulate`) they run the pure function.
+//! `ecommerce_update_inventory` uses this to decrement `products.stock`, and
+//! the first three microservices steps to detect duplicate registrations and
+//! replay their results with the `users` table.
+//!
+//! Every step runs under a timeout ([`DEFAULT_HANDLER_TIMEOUT`], or
+//! `HANDLER_TIMEOUT_SECS`; `0` disables it). A step that exceeds it fails as
+//! retryable. The function handlers are synchronous, so they run on the
+//! blocking thread pool where the timeout can stop waiting for them; a
+//! timed-out function still runs to completion in the background, as a
+//! blocking thread cannot be interrupted.
+//!
+//! The e-commerce cart and inventory handlers read the product catalog the
+//! registry holds. It starts as the demo catalog; the application replaces it
+//! with the `products` table at startup via [`AxumHandlerRegistry::set_catalog`].
+//! The cart and shipping handlers likewise price orders with the registry's
+//! [`PricingConfig`], set from the environment via
+//! [`AxumHandlerRegistry::set_pricing`], and apply coupons from the `coupons`
+//! table ([`AxumHandlerRegistry::set_coupons`]). Without a database, the
+//! inventory handler reserves from, and decrements, the registry's
+//! per-warehouse stock ([`AxumHandlerRegistry::set_warehouse_stock`], the demo
+//! stock by default).
+//! The payment and refund handlers charge through the registry's [`PaymentGateway`], the [`MockPaymentGateway`] unless
+//! replaced with [`AxumHandlerRegistry::set_payment_gateway`]. Likewise, the
+//! confirmation, welcome, and refund notification handlers send through the
+//! registry's [`Notifier`] ([`AxumHandlerRegistry::set_notifier`]).
+//!
+//! A handler can also be registered for a specific template version with
+//! [`AxumHandlerRegistry::register_version`]. Steps of tasks created from that
+//! template version dispatch to it, so in-flight tasks keep the logic they
+//! started with while new tasks pick up the latest handler.
 
 use async_trait::async_trait;
+use futures::future::BoxFuture;
 use serde_json::Value;
-use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
-use std::time::Instant;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::future::Future;
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::{Duration, Instant};
 
 use tasker_shared::messaging::StepExecutionResult;
 use tasker_shared::types::base::TaskSequenceStep;
 use tasker_shared::TaskerResult;
 use tasker_worker::worker::handlers::{StepHandler, StepHandlerRegistry};
+use tracing::warn;
 
+use crate::db::AppDb;
+use crate::gateway::{MockPaymentGateway, PaymentGateway};
+use crate::handler_manifest::{HandlerManifest, HandlerManifestError};
+use crate::handler_policy::{HandlerPolicy, DISABLED_MESSAGE};
 use crate::handlers;
+use crate::handlers::ecommerce::{Coupon, CouponBook, PricingConfig, Product, ProductCatalog};
+use crate::metrics;
+use crate::normalize::ResultNormalizer;
+use crate::notifier::{LoggingNotifier, Notifier};
+use crate::retry::RetryPolicy;
+use crate::shutdown::InFlightSteps;
+use crate::warehouses::{demo_warehouse_stock, WarehouseStock};
 
 // ============================================================================
 // FunctionHandler: wraps a closure as a StepHandler
 // ============================================================================
 
-type HandlerFn = Box<dyn Fn(&Value, &HashMap<String, Value>) -> Result<Value, String> + Send + Sync>;
+type PureHandler = dyn Fn(&Value, &HashMap<String, Value>) -> Result<Value, String> + Send + Sync;
+type HandlerFn = Box<PureHandler>;
+
+/// Default time a step may run before it fails as timed out.
+pub const DEFAULT_HANDLER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A handler that works against the application database. It receives the
+/// pool, the task context, and the dependency results.
+pub type DbHandlerFn = Arc<
+    dyn Fn(AppDb, Value, HashMap<String, Value>) -> BoxFuture<'static, Result<Value, String>>
+        + Send
+        + Sync,
+>;
+
+/// Per-handler behavior declared at registration time.
+#[derive(Debug, Default)]
+struct HandlerOptions {
+    normalizer: Option<ResultNormalizer>,
+    retry: RetryPolicy,
+}
+
+impl HandlerOptions {
+    fn normalize(mut self, normalizer: ResultNormalizer) -> Self {
+        self.normalizer = Some(normalizer);
+        self
+    }
+
+    fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+}
+
+/// Registry state shared with every function handler.
+#[derive(Debug)]
+struct SharedState {
+    /// The application pool, once attached.
+    db: OnceLock<AppDb>,
+    /// How long a step may run (`None` = no limit).
+    timeout: RwLock<Option<Duration>>,
+    /// Steps currently executing, drained on shutdown.
+    in_flight: InFlightSteps,
+}
 
 struct FunctionHandler {
     handler_name: String,
-    handler_fn: HandlerFn,
+    handler_fn: Arc<PureHandler>,
+    /// The database version of the handler, run once a pool is attached.
+    db_fn: Option<DbHandlerFn>,
+    shared: Arc<SharedState>,
+    options: Arc<HandlerOptions>,
 }
 
 impl FunctionHandler {
-    fn new(name: impl Into<String>, f: HandlerFn) -> Self {
+    fn new(
+        name: impl Into<String>,
+        f: HandlerFn,
+        db_fn: Option<DbHandlerFn>,
+        shared: Arc<SharedState>,
+        options: Arc<HandlerOptions>,
+    ) -> Self {
         Self {
             handler_name: name.into(),
-            handler_fn: f,
+            handler_fn: Arc::from(f),
+            db_fn,
+            shared,
+            options,
         }
     }
+
+    /// Run the pure function and its result normalizer.
+    fn invoke(&self, context: &Value, dep_results: &HashMap<String, Value>) -> Result<Value, String> {
+        (self.handler_fn)(context, dep_results).and_then(|result| self.normalize(result))
+    }
+
+    /// Run the database version when a pool is attached, else the pure
+    /// function on the blocking pool, under the step timeout; then the
+    /// result normalizer.
+    async fn run(&self, context: &Value, dep_results: &HashMap<String, Value>) -> Result<Value, String> {
+        let timeout = *self.shared.timeout.read().expect("registry lock poisoned");
+        let (context, dep_results) = (context.clone(), dep_results.clone());
+        let result = match (&self.db_fn, self.shared.db.get()) {
+            (Some(db_fn), Some(pool)) => {
+                with_timeout(timeout, db_fn(pool.clone(), context, dep_results)).await
+            }
+            _ => {
+                let f = self.handler_fn.clone();
+                blocking_with_timeout(timeout, move || f(&context, &dep_results)).await
+            }
+        };
+        result.and_then(|result| self.normalize(result))
+    }
+
+    fn normalize(&self, result: Value) -> Result<Value, String> {
+        match &self.options.normalizer {
+            Some(normalizer) => normalizer.apply(result),
+            None => Ok(result),
+        }
+    }
+}
+
+/// Await a handler, failing it as retryable if it runs longer than `timeout`.
+pub async fn with_timeout(
+    timeout: Option<Duration>,
+    handler: impl Future<Output = Result<Value, String>>,
+) -> Result<Value, String> {
+    let Some(timeout) = timeout else {
+        return handler.await;
+    };
+    tokio::time::timeout(timeout, handler).await.unwrap_or_else(|_| {
+        Err(format!(
+            "Handler exceeded timeout of {:?} (retryable)",
+            timeout
+        ))
+    })
+}
+
+/// Run a synchronous handler on the blocking thread pool under `timeout`.
+///
+/// The timeout stops the wait, not the function: a timed-out handler keeps
+/// its blocking thread until it returns.
+pub async fn blocking_with_timeout(
+    timeout: Option<Duration>,
+    handler: impl FnOnce() -> Result<Value, String> + Send + 'static,
+) -> Result<Value, String> {
+    with_timeout(timeout, async {
+        tokio::task::spawn_blocking(handler)
+            .await
+            .unwrap_or_else(|e| Err(format!("Handler panicked: {}", e)))
+    })
+    .await
+}
+
+/// `HANDLER_TIMEOUT_SECS`, or [`DEFAULT_HANDLER_TIMEOUT`]; `0` means no limit.
+pub fn handler_timeout_from_env() -> Option<Duration> {
+    match std::env::var("HANDLER_TIMEOUT_SECS") {
+        Ok(raw) => match raw.trim().parse::<u64>() {
+            Ok(0) => None,
+            Ok(secs) => Some(Duration::from_secs(secs)),
+            Err(_) => {
+                warn!(
+                    "Ignoring invalid HANDLER_TIMEOUT_SECS '{}'; using {:?}",
+                    raw, DEFAULT_HANDLER_TIMEOUT
+                );
+                Some(DEFAULT_HANDLER_TIMEOUT)
+            }
+        },
+        Err(_) => Some(DEFAULT_HANDLER_TIMEOUT),
+    }
+}
+
+/// Await `f`, returning its output and how long it took in milliseconds.
+///
+/// Step results report this as their execution time, so it must wrap the
+/// handler call itself.
+pub async fn timed<F: Future>(f: F) -> (F::Output, i64) {
+    let start = Instant::now();
+    let output = f.await;
+    (output, start.elapsed().as_millis() as i64)
 }
 
 #[async_trait]
 impl StepHandler for FunctionHandler {
     async fn call(&self, step: &TaskSequenceStep) -> TaskerResult<StepExecutionResult> {
-        let start = Instant::now();
+        let _in_flight = self.shared.in_flight.start();
 
         // Extract task context (or empty object if missing)
         let context = step
@@ -58,23 +262,20 @@ impl StepHandler for FunctionHandler {
             .map(|(name, result)| (name.clone(), result.result.clone()))
             .collect();
 
-        let elapsed_ms = start.elapsed().as_millis() as i64;
+        let (outcome, elapsed_ms) = timed(self.run(&context, &dep_results)).await;
+        metrics::record_handler_execution(&self.handler_name, outcome.is_ok(), elapsed_ms);
 
-        match (self.handler_fn)(&context, &dep_results) {
+        match outcome {
             Ok(result) => Ok(StepExecutionResult::success(
                 step.workflow_step.workflow_step_uuid,
                 result,
                 elapsed_ms,
                 None,
             )),
-            Err(err) => Ok(StepExecutionResult::failure(
+            Err(err) => Ok(self.options.retry.failure_result(
                 step.workflow_step.workflow_step_uuid,
                 err,
-                None,
-                None,
-                false,
                 elapsed_ms,
-                None,
             )),
         }
     }
@@ -84,174 +285,825 @@ impl StepHandler for FunctionHandler {
     }
 }
 
+/// Copy the stock levels a reservation left in the database into the cached
+/// catalog, so later carts are stock-checked against them.
+fn record_stock_levels(catalog: &RwLock<ProductCatalog>, reservation: &Value) {
+    let Some(updated) = reservation["updated_products"].as_array() else {
+        return;
+    };
+    let mut catalog = catalog.write().expect("catalog lock poisoned");
+    for product in updated {
+        let (Some(sku), Some(stock)) = (product["sku"].as_str(), product["new_quantity"].as_i64())
+        else {
+            continue;
+        };
+        if let Some(cached) = catalog.values_mut().find(|p| p.sku == sku) {
+            cached.stock = stock;
+        }
+    }
+}
+
+// ============================================================================
+// Namespace filtering
+// ============================================================================
+
+/// Every namespace served by this example, in registration order.
+pub const ALL_NAMESPACES: &[&str] = &[
+    "ecommerce_rs",
+    "data_pipeline_rs",
+    "microservices_rs",
+    "customer_success_rs",
+    "payments_rs",
+];
+
+/// Parse a comma-separated namespace list such as `"payments_rs,ecommerce_rs"`.
+///
+/// Blank entries are ignored. Fails if the list names no namespace, or one
+/// that is not in [`ALL_NAMESPACES`], since either would leave the worker with
+/// no handlers for the steps it is sent.
+pub fn parse_namespace_list(raw: &str) -> Result<HashSet<String>, String> {
+    let namespaces: HashSet<String> = raw
+        .split(',')
+        .map(str::trim)
+        .filter(|ns| !ns.is_empty())
+        .map(str::to_string)
+        .collect();
+
+    if namespaces.is_empty() {
+        return Err("ENABLED_NAMESPACES names no namespace".to_string());
+    }
+    let mut unknown: Vec<&str> = namespaces
+        .iter()
+        .map(String::as_str)
+        .filter(|ns| !ALL_NAMESPACES.contains(ns))
+        .collect();
+    if !unknown.is_empty() {
+        unknown.sort_unstable();
+        return Err(format!(
+            "ENABLED_NAMESPACES has unknown namespaces {} (expected some of {})",
+            unknown.join(", "),
+            ALL_NAMESPACES.join(", ")
+        ));
+    }
+    Ok(namespaces)
+}
+
+/// Read the `ENABLED_NAMESPACES` env var. Unset means all namespaces.
+pub fn enabled_namespaces_from_env() -> Result<Option<HashSet<String>>, String> {
+    match std::env::var("ENABLED_NAMESPACES") {
+        Ok(raw) => parse_namespace_list(&raw).map(Some),
+        Err(_) => Ok(None),
+    }
+}
+
 // ============================================================================
 // AxumHandlerRegistry: StepHandlerRegistry for all example handlers
 // ============================================================================
 
+/// A template version such as `1.2.0`, ordered by its numeric components
+/// (so `1.10.0` sorts after `1.9.0`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TemplateVersion {
+    raw: String,
+    parts: Vec<u64>,
+}
+
+impl TemplateVersion {
+    fn parse(raw: &str) -> Self {
+        Self {
+            raw: raw.trim().to_string(),
+            parts: raw
+                .trim()
+                .split('.')
+                .map(|part| part.parse().unwrap_or(0))
+                .collect(),
+        }
+    }
+}
+
+impl Ord for TemplateVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.parts
+            .cmp(&other.parts)
+            .then_with(|| self.raw.cmp(&other.raw))
+    }
+}
+
+impl PartialOrd for TemplateVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Handlers for one callable, keyed by template version.
+type VersionedHandlers = BTreeMap<TemplateVersion, Arc<dyn StepHandler>>;
+
 pub struct AxumHandlerRegistry {
+    /// The latest implementation of each handler, keyed by callable.
     handlers: RwLock<HashMap<String, Arc<dyn StepHandler>>>,
+    /// Implementations pinned to a template version, keyed by callable.
+    versions: RwLock<HashMap<String, VersionedHandlers>>,
+    /// The function behind each registered function handler, for in-process
+    /// calls that have no `TaskSequenceStep` (see [`Self::call_function`]).
+    functions: RwLock<HashMap<String, Arc<FunctionHandler>>>,
+    /// Registration options (normalizer, retry policy), keyed by handler name.
+    options: RwLock<HashMap<String, Arc<HandlerOptions>>>,
+    /// Namespaces whose handlers are registered. `None` registers all of them.
+    enabled_namespaces: Option<HashSet<String>>,
+    /// Which handlers may run; the others are registered as disabled stubs.
+    policy: HandlerPolicy,
+    /// Handlers the policy disabled, by handler identifier.
+    disabled: RwLock<BTreeSet<String>>,
+    /// The product catalog, shared with the e-commerce handlers.
+    catalog: Arc<RwLock<ProductCatalog>>,
+    /// Tax and flat shipping pricing, shared with the e-commerce handlers.
+    pricing: Arc<RwLock<PricingConfig>>,
+    /// Discount codes the cart handler accepts.
+    coupons: Arc<RwLock<CouponBook>>,
+    /// Per-warehouse stock the inventory handler reserves from.
+    warehouses: Arc<RwLock<Vec<WarehouseStock>>>,
+    /// The gateway the payment and refund handlers charge through.
+    payment_gateway: Arc<RwLock<Arc<dyn PaymentGateway>>>,
+    /// The backend the customer message handlers send through.
+    notifier: Arc<RwLock<Arc<dyn Notifier>>>,
+    /// The application pool and step timeout, shared with every handler.
+    shared: Arc<SharedState>,
+}
+
+impl fmt::Debug for AxumHandlerRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut handlers = self.registered_handlers();
+        handlers.sort();
+        f.debug_struct("AxumHandlerRegistry")
+            .field("enabled_namespaces", &self.enabled_namespaces())
+            .field("handlers", &handlers)
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
+
+impl Default for AxumHandlerRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl AxumHandlerRegistry {
+    /// Registry with handlers for every namespace.
     pub fn new() -> Self {
+        Self::with_namespaces(None)
+    }
+
+    /// Registry restricted to the namespaces listed in `ENABLED_NAMESPACES`
+    /// (failing on an unknown or empty list),
+    /// exposing the handlers in the `HANDLER_MANIFEST` manifest if one is set,
+    /// disabling handlers per `HANDLER_ALLOWLIST` / `HANDLER_DENYLIST`, and
+    /// timing steps out after `HANDLER_TIMEOUT_SECS`.
+    pub fn from_env() -> Result<Self, HandlerManifestError> {
+        let registry = Self::with_policy(
+            enabled_namespaces_from_env().map_err(HandlerManifestError::Namespaces)?,
+            HandlerManifest::from_env()?.as_ref(),
+            HandlerPolicy::from_env(),
+        )?;
+        registry.set_handler_timeout(handler_timeout_from_env());
+        Ok(registry)
+    }
+
+    /// Registry restricted to `enabled_namespaces` that exposes only the
+    /// handlers listed in `manifest`, under the manifest's callables. `None`
+    /// exposes every built-in handler.
+    ///
+    /// Fails if the manifest names a handler that does not exist. Entries for
+    /// handlers in a disabled namespace are skipped.
+    pub fn with_manifest(
+        enabled_namespaces: Option<HashSet<String>>,
+        manifest: Option<&HandlerManifest>,
+    ) -> Result<Self, HandlerManifestError> {
+        Self::with_policy(enabled_namespaces, manifest, HandlerPolicy::allow_all())
+    }
+
+    /// Like [`Self::with_manifest`], with the handlers `policy` disallows
+    /// registered as disabled: their steps fail permanently with a
+    /// "handler disabled" message instead of running.
+    ///
+    /// Fails if a policy pattern matches no built-in handler.
+    pub fn with_policy(
+        enabled_namespaces: Option<HashSet<String>>,
+        manifest: Option<&HandlerManifest>,
+        policy: HandlerPolicy,
+    ) -> Result<Self, HandlerManifestError> {
+        if policy.is_restrictive() {
+            let known = Self::new().callables();
+            policy.check_patterns(known.iter().map(String::as_str))?;
+        }
+        let registry = Self::build(enabled_namespaces, policy);
+        if let Some(manifest) = manifest {
+            registry.apply_manifest(manifest)?;
+        }
+        Ok(registry)
+    }
+
+    /// Registry restricted to `enabled_namespaces` (`None` means all).
+    ///
+    /// Handlers belonging to any other namespace are never registered, so steps
+    /// routed to this instance for those namespaces find no handler.
+    pub fn with_namespaces(enabled_namespaces: Option<HashSet<String>>) -> Self {
+        Self::build(enabled_namespaces, HandlerPolicy::allow_all())
+    }
+
+    fn build(enabled_namespaces: Option<HashSet<String>>, policy: HandlerPolicy) -> Self {
         let registry = Self {
             handlers: RwLock::new(HashMap::new()),
+            versions: RwLock::new(HashMap::new()),
+            functions: RwLock::new(HashMap::new()),
+            options: RwLock::new(HashMap::new()),
+            enabled_namespaces,
+            policy,
+            disabled: RwLock::new(BTreeSet::new()),
+            catalog: Arc::new(RwLock::new(handlers::ecommerce::demo_catalog())),
+            pricing: Arc::new(RwLock::new(PricingConfig::default())),
+            coupons: Arc::new(RwLock::new(handlers::ecommerce::demo_coupons())),
+            warehouses: Arc::new(RwLock::new(demo_warehouse_stock())),
+            payment_gateway: Arc::new(RwLock::new(Arc::new(MockPaymentGateway))),
+            notifier: Arc::new(RwLock::new(Arc::new(LoggingNotifier))),
+            shared: Arc::new(SharedState {
+                db: OnceLock::new(),
+                timeout: RwLock::new(Some(DEFAULT_HANDLER_TIMEOUT)),
+                in_flight: InFlightSteps::new(),
+            }),
         };
         registry.register_all();
         registry
     }
 
-    /// Number of registered handlers (for logging at startup).
+    /// Number of registered handlers (for logging at startup). A handler
+    /// registered for several template versions counts once.
     pub fn handler_count(&self) -> usize {
-        self.handlers.read().expect("registry lock poisoned").len()
+        self.callables().len()
+    }
+
+    fn callables(&self) -> BTreeSet<String> {
+        let mut callables: BTreeSet<String> = self
+            .handlers
+            .read()
+            .expect("registry lock poisoned")
+            .keys()
+            .cloned()
+            .collect();
+        callables.extend(
+            self.versions
+                .read()
+                .expect("registry lock poisoned")
+                .keys()
+                .cloned(),
+        );
+        callables
+    }
+
+    /// Call a registered function handler directly with a task context and
+    /// dependency results, applying its result normalizer. Returns `None` if
+    /// no function handler is registered under `name`.
+    ///
+    /// Used to run workflows in-process without a worker (see `simulate`).
+    pub fn call_function(
+        &self,
+        name: &str,
+        context: &Value,
+        dep_results: &HashMap<String, Value>,
+    ) -> Option<Result<Value, String>> {
+        let function = self
+            .functions
+            .read()
+            .expect("registry lock poisoned")
+            .get(name)
+            .cloned()?;
+        Some(function.invoke(context, dep_results))
+    }
+
+    /// Register `handler` for steps of tasks created from template `version`
+    /// of its callable. Other versions keep dispatching to the latest handler.
+    pub fn register_version(&self, name: &str, version: &str, handler: Arc<dyn StepHandler>) {
+        self.versions
+            .write()
+            .expect("registry lock poisoned")
+            .entry(name.to_string())
+            .or_default()
+            .insert(TemplateVersion::parse(version), handler);
+    }
+
+    /// Template versions with a handler registered for `name`, oldest first.
+    pub fn handler_versions(&self, name: &str) -> Vec<String> {
+        self.versions
+            .read()
+            .expect("registry lock poisoned")
+            .get(name)
+            .map(|versions| versions.keys().map(|v| v.raw.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    /// The handler for `name` on a task created from `template_version`.
+    ///
+    /// Prefers the handler registered for that exact version, then the latest
+    /// (unversioned) handler, then the handler for the highest version.
+    pub fn handler_for(&self, name: &str, template_version: &str) -> Option<Arc<dyn StepHandler>> {
+        let versions = self.versions.read().expect("registry lock poisoned");
+        let pinned = versions.get(name);
+        let requested = TemplateVersion::parse(template_version);
+
+        if let Some(handler) = pinned.and_then(|v| v.get(&requested)) {
+            return Some(handler.clone());
+        }
+        if let Some(handler) = self
+            .handlers
+            .read()
+            .expect("registry lock poisoned")
+            .get(name)
+        {
+            return Some(handler.clone());
+        }
+        pinned
+            .and_then(|v| v.last_key_value())
+            .map(|(_, handler)| handler.clone())
+    }
+
+    /// The enabled namespaces, sorted in registration order (for logging at startup).
+    pub fn enabled_namespaces(&self) -> Vec<&'static str> {
+        ALL_NAMESPACES
+            .iter()
+            .copied()
+            .filter(|ns| self.namespace_enabled(ns))
+            .collect()
+    }
+
+    fn namespace_enabled(&self, namespace: &str) -> bool {
+        self.enabled_namespaces
+            .as_ref()
+            .is_none_or(|enabled| enabled.contains(namespace))
+    }
+
+    /// The active handler policy.
+    pub fn policy(&self) -> &HandlerPolicy {
+        &self.policy
+    }
+
+    /// Handlers the policy disabled, sorted by handler identifier.
+    pub fn disabled_handlers(&self) -> Vec<String> {
+        self.disabled
+            .read()
+            .expect("registry lock poisoned")
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Attach the application pool, switching handlers registered with a
+    /// [`DbHandlerFn`] to their database version. The first pool attached is
+    /// kept.
+    pub fn set_db(&self, pool: AppDb) {
+        let _ = self.shared.db.set(pool);
+    }
+
+    /// Whether an application pool is attached.
+    pub fn has_db(&self) -> bool {
+        self.shared.db.get().is_some()
+    }
+
+    /// Limit how long a step may run (`None` = no limit).
+    pub fn set_handler_timeout(&self, timeout: Option<Duration>) {
+        *self.shared.timeout.write().expect("registry lock poisoned") = timeout;
+    }
+
+    /// How long a step may run before it fails as timed out.
+    pub fn handler_timeout(&self) -> Option<Duration> {
+        *self.shared.timeout.read().expect("registry lock poisoned")
+    }
+
+    /// The steps this registry's handlers are executing right now.
+    pub fn in_flight_steps(&self) -> InFlightSteps {
+        self.shared.in_flight.clone()
+    }
+
+    /// Replace the product catalog the e-commerce handlers validate carts
+    /// against (e.g. with the rows of the `products` table).
+    pub fn set_catalog(&self, products: Vec<Product>) {
+        *self.catalog.write().expect("catalog lock poisoned") =
+            products.into_iter().map(|p| (p.id, p)).collect();
+    }
+
+    /// The current product catalog, ordered by id.
+    pub fn products(&self) -> Vec<Product> {
+        let mut products: Vec<Product> = self
+            .catalog
+            .read()
+            .expect("catalog lock poisoned")
+            .values()
+            .cloned()
+            .collect();
+        products.sort_by_key(|p| p.id);
+        products
+    }
+
+    /// Replace the tax and shipping pricing the e-commerce handlers use.
+    pub fn set_pricing(&self, pricing: PricingConfig) {
+        *self.pricing.write().expect("pricing lock poisoned") = pricing;
+    }
+
+    /// The current tax and shipping pricing.
+    pub fn pricing(&self) -> PricingConfig {
+        *self.pricing.read().expect("pricing lock poisoned")
+    }
+
+    /// Replace the discount codes the cart handler accepts (e.g. with the rows
+    /// of the `coupons` table).
+    pub fn set_coupons(&self, coupons: Vec<Coupon>) {
+        *self.coupons.write().expect("coupons lock poisoned") = coupons
+            .into_iter()
+            .map(|c| (c.code.to_uppercase(), c))
+            .collect();
+    }
+
+    /// Replace the per-warehouse stock the inventory handler reserves from.
+    pub fn set_warehouse_stock(&self, stock: Vec<WarehouseStock>) {
+        *self.warehouses.write().expect("warehouses lock poisoned") = stock;
+    }
+
+    /// Replace the gateway the payment and refund handlers charge through.
+    pub fn set_payment_gateway(&self, gateway: Arc<dyn PaymentGateway>) {
+        *self.payment_gateway.write().expect("gateway lock poisoned") = gateway;
+    }
+
+    /// The gateway the payment and refund handlers charge through.
+    pub fn payment_gateway(&self) -> Arc<dyn PaymentGateway> {
+        self.payment_gateway
+            .read()
+            .expect("gateway lock poisoned")
+            .clone()
+    }
+
+    /// Replace the backend the customer message handlers send through.
+    pub fn set_notifier(&self, notifier: Arc<dyn Notifier>) {
+        *self.notifier.write().expect("notifier lock poisoned") = notifier;
+    }
+
+    /// The backend the customer message handlers send through.
+    pub fn notifier(&self) -> Arc<dyn Notifier> {
+        self.notifier.read().expect("notifier lock poisoned").clone()
+    }
+
+    /// A separate registry with a copy of this one's catalog, pricing,
+    /// coupons and warehouse stock, the mock payment gateway, the logging
+    /// notifier, and no database. Nothing it runs touches this registry's
+    /// state or any external service (see `simulate`).
+    pub fn sandbox(&self) -> Self {
+        let sandbox = Self::build(self.enabled_namespaces.clone(), self.policy.clone());
+        *sandbox.catalog.write().expect("catalog lock poisoned") =
+            self.catalog.read().expect("catalog lock poisoned").clone();
+        sandbox.set_pricing(self.pricing());
+        *sandbox.coupons.write().expect("coupons lock poisoned") =
+            self.coupons.read().expect("coupons lock poisoned").clone();
+        sandbox.set_warehouse_stock(
+            self.warehouses
+                .read()
+                .expect("warehouses lock poisoned")
+                .clone(),
+        );
+        sandbox
+    }
+
+    fn handler_options(&self, name: &str) -> Option<Arc<HandlerOptions>> {
+        self.options
+            .read()
+            .expect("registry lock poisoned")
+            .get(name)
+            .cloned()
+    }
+
+    /// The result normalizer registered with a handler, if any.
+    pub fn result_normalizer(&self, name: &str) -> Option<ResultNormalizer> {
+        self.handler_options(name)?.normalizer.clone()
+    }
+
+    /// The retry policy a handler was registered with (`None` if the handler
+    /// is not registered).
+    pub fn retry_policy(&self, name: &str) -> Option<RetryPolicy> {
+        self.handler_options(name).map(|options| options.retry.clone())
     }
 
     fn register_fn(&self, name: &str, f: HandlerFn) {
-        let handler = Arc::new(FunctionHandler::new(name, f));
+        self.register_fn_with(name, f, HandlerOptions::default());
+    }
+
+    fn register_fn_with(&self, name: &str, f: HandlerFn, options: HandlerOptions) {
+        self.register_handler(name, f, None, options);
+    }
+
+    /// Register a handler with a database version, falling back to `f` while
+    /// no pool is attached.
+    fn register_db_fn(&self, name: &str, f: HandlerFn, db_fn: DbHandlerFn, options: HandlerOptions) {
+        self.register_handler(name, f, Some(db_fn), options);
+    }
+
+    fn register_handler(
+        &self,
+        name: &str,
+        f: HandlerFn,
+        db_fn: Option<DbHandlerFn>,
+        options: HandlerOptions,
+    ) {
+        let (f, db_fn, options) = if self.policy.allows(name) {
+            (f, db_fn, options)
+        } else {
+            // The real handler is never registered; its steps fail fast
+            self.disabled
+                .write()
+                .expect("registry lock poisoned")
+                .insert(name.to_string());
+            let message = format!("{}: {}", DISABLED_MESSAGE, name);
+            let disabled: HandlerFn = Box::new(move |_ctx, _deps| Err(message.clone()));
+            (disabled, None, HandlerOptions::default().retry(RetryPolicy::never()))
+        };
+        let options = Arc::new(options);
+        self.options
+            .write()
+            .expect("registry lock poisoned")
+            .insert(name.to_string(), options.clone());
+
+        let handler = Arc::new(FunctionHandler::new(name, f, db_fn, self.shared.clone(), options));
+        self.functions
+            .write()
+            .expect("registry lock poisoned")
+            .insert(name.to_string(), handler.clone());
         self.handlers
             .write()
             .expect("registry lock poisoned")
             .insert(name.to_string(), handler);
     }
 
+    /// Replace the built-in callables with the manifest's mapping.
+    fn apply_manifest(&self, manifest: &HandlerManifest) -> Result<(), HandlerManifestError> {
+        let known = if self.enabled_namespaces.is_none() {
+            self.callables()
+        } else {
+            Self::new().callables()
+        };
+        if let Some((callable, handler)) = manifest
+            .handlers
+            .iter()
+            .find(|(_, handler)| !known.contains(*handler))
+        {
+            return Err(HandlerManifestError::UnknownHandler {
+                callable: callable.clone(),
+                handler: handler.clone(),
+            });
+        }
+
+        fn remap<T: Clone>(map: &RwLock<HashMap<String, T>>, manifest: &HandlerManifest) {
+            let mut map = map.write().expect("registry lock poisoned");
+            let remapped = manifest
+                .handlers
+                .iter()
+                .filter_map(|(callable, handler)| {
+                    map.get(handler).map(|entry| (callable.clone(), entry.clone()))
+                })
+                .collect();
+            *map = remapped;
+        }
+        remap(&self.handlers, manifest);
+        remap(&self.functions, manifest);
+        remap(&self.options, manifest);
+        Ok(())
+    }
+
     fn register_all(&self) {
         // ================================================================
-        // E-commerce Order Processing (5 handlers)
+        // E-commerce Order Processing (6 handlers + optional reconcile)
         // ================================================================
-        self.register_fn(
-            "ecommerce_validate_cart",
-            Box::new(|ctx, _deps| handlers::ecommerce::validate_cart(ctx)),
-        );
-        self.register_fn(
-            "ecommerce_process_payment",
-            Box::new(|ctx, deps| handlers::ecommerce::process_payment(ctx, deps)),
-        );
-        self.register_fn(
-            "ecommerce_update_inventory",
-            Box::new(|_ctx, deps| handlers::ecommerce::update_inventory(deps)),
-        );
-        self.register_fn(
-            "ecommerce_create_order",
-            Box::new(|ctx, deps| handlers::ecommerce::create_order(ctx, deps)),
-        );
-        self.register_fn(
-            "ecommerce_send_confirmation",
-            Box::new(|ctx, deps| handlers::ecommerce::send_confirmation(ctx, deps)),
-        );
+        if self.namespace_enabled("ecommerce_rs") {
+            // Retry policies: cart and address failures are input errors and
+            // never retry. The template gives payment more attempts.
+            let catalog = self.catalog.clone();
+            let pricing = self.pricing.clone();
+            let coupons = self.coupons.clone();
+            self.register_fn_with(
+                "ecommerce_validate_cart",
+                Box::new(move |ctx, _deps| {
+                    let catalog = catalog.read().expect("catalog lock poisoned");
+                    let pricing = *pricing.read().expect("pricing lock poisoned");
+                    let coupons = coupons.read().expect("coupons lock poisoned");
+                    handlers::ecommerce::validate_cart_against(ctx, &catalog, &pricing, &coupons)
+                }),
+                HandlerOptions::default().retry(RetryPolicy::never()),
+            );
+            let pricing = self.pricing.clone();
+            self.register_fn_with(
+                "ecommerce_calculate_shipping",
+                Box::new(move |ctx, deps| {
+                    let pricing = *pricing.read().expect("pricing lock poisoned");
+                    handlers::ecommerce::calculate_shipping_with(ctx, deps, &pricing)
+                }),
+                HandlerOptions::default().retry(RetryPolicy::never()),
+            );
+            let gateway = self.payment_gateway.clone();
+            self.register_fn(
+                "ecommerce_process_payment",
+                Box::new(move |ctx, deps| {
+                    let gateway = gateway.read().expect("gateway lock poisoned").clone();
+                    handlers::ecommerce::process_payment_with(ctx, deps, gateway.as_ref())
+                }),
+            );
+            // With a database, reservations decrement products.stock
+            let warehouses = self.warehouses.clone();
+            let db_catalog = self.catalog.clone();
+            self.register_db_fn(
+                "ecommerce_update_inventory",
+                Box::new(move |_ctx, deps| {
+                    let mut warehouses = warehouses.write().expect("warehouses lock poisoned");
+                    handlers::ecommerce::update_inventory_against(deps, &mut warehouses)
+                }),
+                Arc::new(move |pool, _ctx, deps| {
+                    let catalog = db_catalog.clone();
+                    Box::pin(async move {
+                        let result = handlers::ecommerce::reserve_inventory(&pool, &deps).await?;
+                        record_stock_levels(&catalog, &result);
+                        Ok(result)
+                    })
+                }),
+                HandlerOptions::default(),
+            );
+            self.register_fn(
+                "ecommerce_create_order",
+                Box::new(handlers::ecommerce::create_order),
+            );
+            let notifier = self.notifier.clone();
+            self.register_fn(
+                "ecommerce_send_confirmation",
+                Box::new(move |ctx, deps| {
+                    let notifier = notifier.read().expect("notifier lock poisoned").clone();
+                    handlers::ecommerce::send_confirmation_with(ctx, deps, notifier.as_ref())
+                }),
+            );
+            self.register_fn(
+                "ecommerce_reconcile_order",
+                Box::new(|_ctx, deps| handlers::ecommerce::reconcile_order(deps)),
+            );
+        }
 
         // ================================================================
         // Data Pipeline Analytics (8 handlers)
         // ================================================================
-        self.register_fn(
-            "data_pipeline_extract_sales",
-            Box::new(|ctx, _deps| handlers::data_pipeline::extract_sales(ctx)),
-        );
-        self.register_fn(
-            "data_pipeline_extract_inventory",
-            Box::new(|ctx, _deps| handlers::data_pipeline::extract_inventory(ctx)),
-        );
-        self.register_fn(
-            "data_pipeline_extract_customers",
-            Box::new(|ctx, _deps| handlers::data_pipeline::extract_customers(ctx)),
-        );
-        self.register_fn(
-            "data_pipeline_transform_sales",
-            Box::new(|_ctx, deps| handlers::data_pipeline::transform_sales(deps)),
-        );
-        self.register_fn(
-            "data_pipeline_transform_inventory",
-            Box::new(|_ctx, deps| handlers::data_pipeline::transform_inventory(deps)),
-        );
-        self.register_fn(
-            "data_pipeline_transform_customers",
-            Box::new(|_ctx, deps| handlers::data_pipeline::transform_customers(deps)),
-        );
-        self.register_fn(
-            "data_pipeline_aggregate_metrics",
-            Box::new(|_ctx, deps| handlers::data_pipeline::aggregate_metrics(deps)),
-        );
-        self.register_fn(
-            "data_pipeline_generate_insights",
-            Box::new(|_ctx, deps| handlers::data_pipeline::generate_insights(deps)),
-        );
+        if self.namespace_enabled("data_pipeline_rs") {
+            self.register_fn(
+                "data_pipeline_extract_sales",
+                Box::new(|ctx, _deps| handlers::data_pipeline::extract_sales(ctx)),
+            );
+            self.register_fn(
+                "data_pipeline_extract_inventory",
+                Box::new(|ctx, _deps| handlers::data_pipeline::extract_inventory(ctx)),
+            );
+            self.register_fn(
+                "data_pipeline_extract_customers",
+                Box::new(|ctx, _deps| handlers::data_pipeline::extract_customers(ctx)),
+            );
+            self.register_fn(
+                "data_pipeline_transform_sales",
+                Box::new(handlers::data_pipeline::transform_sales),
+            );
+            self.register_fn(
+                "data_pipeline_transform_inventory",
+                Box::new(handlers::data_pipeline::transform_inventory),
+            );
+            self.register_fn(
+                "data_pipeline_transform_customers",
+                Box::new(handlers::data_pipeline::transform_customers),
+            );
+            self.register_fn(
+                "data_pipeline_aggregate_metrics",
+                Box::new(handlers::data_pipeline::aggregate_metrics),
+            );
+            self.register_fn(
+                "data_pipeline_generate_insights",
+                Box::new(|_ctx, deps| handlers::data_pipeline::generate_insights(deps)),
+            );
+        }
 
         // ================================================================
         // Microservices User Registration (5 handlers)
         // ================================================================
-        self.register_fn(
-            "microservices_create_user_account",
-            Box::new(|ctx, _deps| handlers::microservices::create_user_account(ctx)),
-        );
-        self.register_fn(
-            "microservices_setup_billing_profile",
-            Box::new(|ctx, deps| handlers::microservices::setup_billing_profile(ctx, deps)),
-        );
-        self.register_fn(
-            "microservices_initialize_preferences",
-            Box::new(|ctx, deps| handlers::microservices::initialize_preferences(ctx, deps)),
-        );
-        self.register_fn(
-            "microservices_send_welcome_sequence",
-            Box::new(|ctx, deps| handlers::microservices::send_welcome_sequence(ctx, deps)),
-        );
-        self.register_fn(
-            "microservices_update_user_status",
-            Box::new(|_ctx, deps| handlers::microservices::update_user_status(deps)),
-        );
+        if self.namespace_enabled("microservices_rs") {
+            // With a database, accounts and their billing profiles and
+            // preferences are stored in the users table
+            self.register_db_fn(
+                "microservices_create_user_account",
+                Box::new(|ctx, _deps| handlers::microservices::create_user_account(ctx)),
+                Arc::new(|pool, ctx, _deps| {
+                    Box::pin(async move {
+                        handlers::microservices::create_user_account_in_db(&pool, &ctx).await
+                    })
+                }),
+                HandlerOptions::default(),
+            );
+            self.register_db_fn(
+                "microservices_setup_billing_profile",
+                Box::new(handlers::microservices::setup_billing_profile),
+                Arc::new(|pool, _ctx, deps| {
+                    Box::pin(async move {
+                        handlers::microservices::setup_billing_profile_in_db(&pool, &deps).await
+                    })
+                }),
+                HandlerOptions::default(),
+            );
+            self.register_db_fn(
+                "microservices_initialize_preferences",
+                Box::new(handlers::microservices::initialize_preferences),
+                Arc::new(|pool, ctx, deps| {
+                    Box::pin(async move {
+                        handlers::microservices::initialize_preferences_in_db(&pool, &ctx, &deps)
+                            .await
+                    })
+                }),
+                HandlerOptions::default(),
+            );
+            // `total_messages` duplicates the `messages_sent` count; the
+            // per-message array is exposed only as `messages_sent_details`.
+            let notifier = self.notifier.clone();
+            self.register_fn_with(
+                "microservices_send_welcome_sequence",
+                Box::new(move |ctx, deps| {
+                    let notifier = notifier.read().expect("notifier lock poisoned").clone();
+                    handlers::microservices::send_welcome_sequence_with(ctx, deps, notifier.as_ref())
+                }),
+                HandlerOptions::default()
+                    .normalize(ResultNormalizer::new().strip("total_messages")),
+            );
+            self.register_fn(
+                "microservices_update_user_status",
+                Box::new(|_ctx, deps| handlers::microservices::update_user_status(deps)),
+            );
+        }
 
         // ================================================================
         // Customer Success - Process Refund (5 handlers)
         // ================================================================
-        self.register_fn(
-            "team_scaling_cs_validate_refund_request",
-            Box::new(|ctx, _deps| handlers::customer_success::validate_refund_request(ctx)),
-        );
-        self.register_fn(
-            "team_scaling_cs_check_refund_policy",
-            Box::new(|ctx, deps| handlers::customer_success::check_refund_policy(ctx, deps)),
-        );
-        self.register_fn(
-            "team_scaling_cs_get_manager_approval",
-            Box::new(|_ctx, deps| handlers::customer_success::get_manager_approval(deps)),
-        );
-        self.register_fn(
-            "team_scaling_cs_execute_refund_workflow",
-            Box::new(|_ctx, deps| handlers::customer_success::execute_refund_workflow(deps)),
-        );
-        self.register_fn(
-            "team_scaling_cs_update_ticket_status",
-            Box::new(|ctx, deps| handlers::customer_success::update_ticket_status(ctx, deps)),
-        );
+        if self.namespace_enabled("customer_success_rs") {
+            self.register_fn_with(
+                "team_scaling_cs_validate_refund_request",
+                Box::new(|ctx, _deps| handlers::customer_success::validate_refund_request(ctx)),
+                HandlerOptions::default().retry(RetryPolicy::never()),
+            );
+            self.register_fn(
+                "team_scaling_cs_check_refund_policy",
+                Box::new(handlers::customer_success::check_refund_policy),
+            );
+            self.register_fn(
+                "team_scaling_cs_get_manager_approval",
+                Box::new(|_ctx, deps| handlers::customer_success::get_manager_approval(deps)),
+            );
+            self.register_fn(
+                "team_scaling_cs_execute_refund_workflow",
+                Box::new(|_ctx, deps| handlers::customer_success::execute_refund_workflow(deps)),
+            );
+            self.register_fn(
+                "team_scaling_cs_update_ticket_status",
+                Box::new(handlers::customer_success::update_ticket_status),
+            );
+        }
 
         // ================================================================
         // Payments - Process Refund (4 handlers)
         // ================================================================
-        self.register_fn(
-            "team_scaling_payments_validate_eligibility",
-            Box::new(|ctx, _deps| handlers::payments::validate_payment_eligibility(ctx)),
-        );
-        self.register_fn(
-            "team_scaling_payments_process_gateway_refund",
-            Box::new(|_ctx, deps| handlers::payments::process_gateway_refund(deps)),
-        );
-        self.register_fn(
-            "team_scaling_payments_update_records",
-            Box::new(|_ctx, deps| handlers::payments::update_payment_records(deps)),
-        );
-        self.register_fn(
-            "team_scaling_payments_notify_customer",
-            Box::new(|ctx, deps| handlers::payments::notify_customer(ctx, deps)),
-        );
+        if self.namespace_enabled("payments_rs") {
+            self.register_fn(
+                "team_scaling_payments_validate_eligibility",
+                Box::new(|ctx, _deps| handlers::payments::validate_payment_eligibility(ctx)),
+            );
+            let gateway = self.payment_gateway.clone();
+            self.register_fn(
+                "team_scaling_payments_process_gateway_refund",
+                Box::new(move |_ctx, deps| {
+                    let gateway = gateway.read().expect("gateway lock poisoned").clone();
+                    handlers::payments::process_gateway_refund_with(deps, gateway.as_ref())
+                }),
+            );
+            self.register_fn(
+                "team_scaling_payments_update_records",
+                Box::new(|_ctx, deps| handlers::payments::update_payment_records(deps)),
+            );
+            let notifier = self.notifier.clone();
+            self.register_fn(
+                "team_scaling_payments_notify_customer",
+                Box::new(move |ctx, deps| {
+                    let notifier = notifier.read().expect("notifier lock poisoned").clone();
+                    handlers::payments::notify_customer_with(ctx, deps, notifier.as_ref())
+                }),
+            );
+        }
     }
 }
 
 #[async_trait]
 impl StepHandlerRegistry for AxumHandlerRegistry {
     async fn get(&self, step: &TaskSequenceStep) -> Option<Arc<dyn StepHandler>> {
-        let handlers = self.handlers.read().expect("registry lock poisoned");
-        handlers
-            .get(&step.step_definition.handler.callable)
-            .cloned()
+        self.handler_for(&step.step_definition.handler.callable, &step.task.task_version)
     }
 
     fn register(&self, name: &str, handler: Arc<dyn StepHandler>) {
@@ -266,14 +1118,14 @@ impl StepHandlerRegistry for AxumHandlerRegistry {
             .read()
             .expect("registry lock poisoned")
             .contains_key(name)
+            || self
+                .versions
+                .read()
+                .expect("registry lock poisoned")
+                .contains_key(name)
     }
 
     fn registered_handlers(&self) -> Vec<String> {
-        self.handlers
-            .read()
-            .expect("registry lock poisoned")
-            .keys()
-            .cloned()
-            .collect()
+        self.callables().into_iter().collect()
     }
 }
@@ -0,0 +1,167 @@
+//! Task context validation for the first step of a workflow.
+//!
+//! A first step deserializes the task context into its input type from
+//! [`crate::types`]. Deserializing directly stops at the first problem, so a
+//! caller fixing a bad context finds its mistakes one at a time.
+//! [`validate_context`] first checks the context against the input type's JSON
+//! Schema (required fields and the JSON type of each top-level field) and
+//! reports every problem in one error:
+//!
+//! ```text
+//! Invalid process refund input: customer_email is required; ticket_id is required;
+//! refund_amount must be a number, got string
+//! ```
+//!
+//! Handlers collect their own field checks the same way with [`ContextErrors`].
+
+use std::fmt::Display;
+
+use schemars::schema::{InstanceType, RootSchema, Schema, SingleOrVec};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+/// Problems found in a task context, reported together.
+#[derive(Debug, Default)]
+pub struct ContextErrors {
+    problems: Vec<String>,
+}
+
+impl ContextErrors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a problem, e.g. `"cart_items[0].quantity must be positive, got 0"`.
+    pub fn push(&mut self, problem: impl Display) {
+        self.problems.push(problem.to_string());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    /// `Ok` if nothing was recorded, else one error listing every problem,
+    /// prefixed `Invalid <input> input:`.
+    pub fn into_result(self, input: &str) -> Result<(), String> {
+        if self.problems.is_empty() {
+            Ok(())
+        } else {
+            Err(format!(
+                "Invalid {} input: {}",
+                input,
+                self.problems.join("; ")
+            ))
+        }
+    }
+}
+
+/// Deserialize `context` into `T`, reporting every missing required field and
+/// every top-level field of the wrong JSON type at once.
+///
+/// `input` names the input in the error, e.g. `"order processing"`.
+pub fn validate_context<T: DeserializeOwned + JsonSchema>(
+    context: &Value,
+    input: &str,
+) -> Result<T, String> {
+    let mut errors = ContextErrors::new();
+    check_schema(&schemars::schema_for!(T), context, &mut errors);
+    errors.into_result(input)?;
+
+    // Nested values are still checked by serde, one problem at a time
+    serde_json::from_value(context.clone()).map_err(|e| format!("Invalid {} input: {}", input, e))
+}
+
+fn check_schema(root: &RootSchema, context: &Value, errors: &mut ContextErrors) {
+    let Some(fields) = context.as_object() else {
+        errors.push(format!(
+            "context must be an object, got {}",
+            json_type(context)
+        ));
+        return;
+    };
+    let Some(object) = root.schema.object.as_ref() else {
+        return;
+    };
+
+    for field in &object.required {
+        if fields.get(field).is_none_or(Value::is_null) {
+            errors.push(format!("{} is required", field));
+        }
+    }
+
+    for (field, schema) in &object.properties {
+        let Some(value) = fields.get(field) else {
+            continue;
+        };
+        let Schema::Object(schema) = schema else {
+            continue;
+        };
+        // References and combinators (nested structs) are left to serde
+        let Some(instance_type) = &schema.instance_type else {
+            continue;
+        };
+        let allowed: &[InstanceType] = match instance_type {
+            SingleOrVec::Single(single) => std::slice::from_ref(single.as_ref()),
+            SingleOrVec::Vec(types) => types,
+        };
+        if !allowed.iter().any(|t| matches_type(value, t)) {
+            let expected = allowed
+                .iter()
+                .filter(|t| **t != InstanceType::Null)
+                .map(type_name)
+                .collect::<Vec<_>>()
+                .join(" or ");
+            errors.push(format!(
+                "{} must be {} {}, got {}",
+                field,
+                article(&expected),
+                expected,
+                json_type(value)
+            ));
+        }
+    }
+}
+
+fn matches_type(value: &Value, instance_type: &InstanceType) -> bool {
+    match instance_type {
+        InstanceType::Null => value.is_null(),
+        InstanceType::Boolean => value.is_boolean(),
+        InstanceType::Object => value.is_object(),
+        InstanceType::Array => value.is_array(),
+        InstanceType::Number => value.is_number(),
+        InstanceType::String => value.is_string(),
+        InstanceType::Integer => value.is_i64() || value.is_u64(),
+    }
+}
+
+fn type_name(instance_type: &InstanceType) -> &'static str {
+    match instance_type {
+        InstanceType::Null => "null",
+        InstanceType::Boolean => "boolean",
+        InstanceType::Object => "object",
+        InstanceType::Array => "array",
+        InstanceType::Number => "number",
+        InstanceType::String => "string",
+        InstanceType::Integer => "integer",
+    }
+}
+
+fn json_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn article(noun: &str) -> &'static str {
+    if noun.starts_with(['a', 'e', 'i', 'o', 'u']) {
+        "an"
+    } else {
+        "a"
+    }
+}
@@ -11,13 +11,22 @@
 //! 3. **team_scaling_cs_get_manager_approval**: Conditional manager approval
 //! 4. **team_scaling_cs_execute_refund_workflow**: Coordinate the refund execution
 //! 5. **team_scaling_cs_update_ticket_status**: Update the support ticket
+//!
+//! Refund amounts and limits are in dollars throughout: a request above
+//! [`MAX_SINGLE_REFUND`] fails validation, and the policy check compares the
+//! amount against the customer tier's limit (standard $100, gold $500,
+//! premium $1,000). An amount equal to a limit is allowed.
 
+use crate::handlers::context::{validate_context, ContextErrors};
 use crate::types::customer_success::*;
 use serde_json::Value;
 use std::collections::HashMap;
 use tracing::info;
 use uuid::Uuid;
 
+/// Largest refund accepted by `validate_refund_request`, in dollars.
+pub const MAX_SINGLE_REFUND: f64 = 10_000.0;
+
 // ============================================================================
 // Helper Functions
 // ============================================================================
@@ -33,6 +42,8 @@ fn determine_customer_tier(customer_id: &str) -> &'static str {
     }
 }
 
+/// Refund window in days, whether approval is required, and the maximum refund
+/// in dollars for `tier`.
 fn get_refund_policy(tier: &str) -> (i64, bool, f64) {
     match tier {
         "gold" => (60, false, 500.0),
@@ -47,8 +58,7 @@ fn get_refund_policy(tier: &str) -> (i64, bool, f64) {
 
 /// Validates the incoming refund request, checking for required fields and valid amounts.
 pub fn validate_refund_request(context: &Value) -> Result<Value, String> {
-    let input: ProcessRefundInput = serde_json::from_value(context.clone())
-        .map_err(|e| format!("Invalid process refund input: {}", e))?;
+    let input: ProcessRefundInput = validate_context(context, "process refund")?;
 
     let ticket_id = &input.ticket_id;
     let customer_id = &input.customer_id;
@@ -62,22 +72,26 @@ pub fn validate_refund_request(context: &Value) -> Result<Value, String> {
 
     let reason = input.refund_reason.as_deref().unwrap_or("No reason provided");
 
+    let mut errors = ContextErrors::new();
     if refund_amount <= 0.0 {
-        return Err("Refund amount must be positive".to_string());
-    }
-    if refund_amount > 10000.0 {
-        return Err(format!(
-            "Refund amount ${:.2} exceeds maximum single refund limit of $10,000",
+        errors.push(format!(
+            "refund_amount must be positive, got {:.2}",
             refund_amount
         ));
     }
-
+    if refund_amount > MAX_SINGLE_REFUND {
+        errors.push(format!(
+            "refund_amount ${:.2} exceeds maximum single refund limit of ${:.2}",
+            refund_amount, MAX_SINGLE_REFUND
+        ));
+    }
     if ticket_id.contains("ticket_closed") {
-        return Err("Cannot process refund for closed ticket".to_string());
+        errors.push("ticket_id refers to a closed ticket");
     }
     if ticket_id.contains("ticket_cancelled") {
-        return Err("Cannot process refund for cancelled ticket".to_string());
+        errors.push("ticket_id refers to a cancelled ticket");
     }
+    errors.into_result("process refund")?;
 
     let customer_tier = determine_customer_tier(customer_id);
     let payment_id = format!("pay_{}", &Uuid::new_v4().to_string().replace('-', "")[..12]);
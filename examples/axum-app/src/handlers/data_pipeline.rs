@@ -21,12 +21,30 @@
 //!
 //! **Insights Phase (depends on aggregate):**
 //! 8. data_pipeline_generate_insights
+//!
+//! The task context's `sources` array (`sales`, `inventory`, `customers`)
+//! selects the sources to process; a missing or empty array selects all three.
+//! The extract and transform steps of an unrequested source return a
+//! `{"source": ..., "skipped": true}` result, which `aggregate_metrics` leaves
+//! out.
+//!
+//! With `allow_partial: true` in the task context, a failing extract or
+//! transform step does not fail the task: it returns a
+//! `{"source": ..., "unavailable": true, "error": ...}` result, the transform
+//! of an unavailable extract passes it on, and `aggregate_metrics` aggregates
+//! the remaining sources into a result flagged `partial`.
+//!
+//! `extract_sales` keeps only the records dated within the context's
+//! `date_range` (inclusive). Without a `date_range` every record is extracted;
+//! a range whose `end_date` is before its `start_date` matches nothing.
 
 use crate::types::data_pipeline::*;
+use crate::warehouses::demo_warehouse_stock;
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::collections::HashMap;
-use tracing::info;
+use tracing::{info, warn};
 
 // ============================================================================
 // Sample Data Types
@@ -77,14 +95,18 @@ fn sample_sales() -> Vec<SalesRecord> {
     ]
 }
 
+/// The e-commerce warehouses' stock (see [`crate::warehouses`]).
 fn sample_inventory() -> Vec<InventoryRecord> {
-    vec![
-        InventoryRecord { product_id: "PROD-A".into(), sku: "SKU-A-001".into(), warehouse: "WH-01".into(), quantity_on_hand: 150, reorder_point: 50 },
-        InventoryRecord { product_id: "PROD-B".into(), sku: "SKU-B-002".into(), warehouse: "WH-01".into(), quantity_on_hand: 75, reorder_point: 25 },
-        InventoryRecord { product_id: "PROD-C".into(), sku: "SKU-C-003".into(), warehouse: "WH-02".into(), quantity_on_hand: 200, reorder_point: 100 },
-        InventoryRecord { product_id: "PROD-A".into(), sku: "SKU-A-001".into(), warehouse: "WH-02".into(), quantity_on_hand: 100, reorder_point: 50 },
-        InventoryRecord { product_id: "PROD-D".into(), sku: "SKU-D-004".into(), warehouse: "WH-01".into(), quantity_on_hand: 20, reorder_point: 30 },
-    ]
+    demo_warehouse_stock()
+        .into_iter()
+        .map(|stock| InventoryRecord {
+            product_id: format!("PROD-{}", stock.product_id),
+            sku: stock.sku,
+            warehouse: stock.warehouse,
+            quantity_on_hand: stock.on_hand,
+            reorder_point: stock.reorder_point,
+        })
+        .collect()
 }
 
 fn sample_customers() -> Vec<CustomerRecord> {
@@ -97,13 +119,135 @@ fn sample_customers() -> Vec<CustomerRecord> {
     ]
 }
 
+// ============================================================================
+// Source Selection
+// ============================================================================
+
+/// The sources the pipeline can extract.
+pub const SOURCES: [&str; 3] = ["sales", "inventory", "customers"];
+
+/// Whether the task context's `sources` selects `source`. A missing or empty
+/// `sources` array selects every source.
+fn source_requested(context: &Value, source: &str) -> bool {
+    match context.get("sources").and_then(Value::as_array) {
+        Some(sources) if !sources.is_empty() => {
+            sources.iter().any(|s| s.as_str() == Some(source))
+        }
+        _ => true,
+    }
+}
+
+/// Result of an extract or transform step for an unrequested source.
+fn skipped_result(source: &str) -> Value {
+    info!("Skipping {} (not in requested sources)", source);
+    json!({
+        "source": source,
+        "skipped": true,
+        "record_count": 0,
+        "records": [],
+    })
+}
+
+fn is_skipped(result: Option<&Value>) -> bool {
+    result.is_some_and(|r| r["skipped"] == true)
+}
+
+/// Whether the task context sets `allow_partial: true`.
+fn allows_partial(context: &Value) -> bool {
+    context
+        .get("allow_partial")
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+}
+
+/// Result of an extract or transform step whose source could not be produced.
+fn unavailable_result(source: &str, error: &str) -> Value {
+    json!({
+        "source": source,
+        "unavailable": true,
+        "error": error,
+        "record_count": 0,
+        "records": [],
+    })
+}
+
+fn is_unavailable(result: Option<&Value>) -> bool {
+    result.is_some_and(|r| r["unavailable"] == true)
+}
+
+/// In partial mode, report a failed extract or transform of `source` as an
+/// unavailable result so the pipeline continues without it; otherwise the
+/// step fails.
+fn unavailable_if_partial(
+    context: &Value,
+    source: &str,
+    result: Result<Value, String>,
+) -> Result<Value, String> {
+    match result {
+        Err(error) if allows_partial(context) => {
+            warn!("{} is unavailable (partial mode): {}", source, error);
+            Ok(unavailable_result(source, &error))
+        }
+        result => result,
+    }
+}
+
+/// The result to pass on for `source` when its upstream step was skipped or
+/// unavailable, if it was.
+fn passed_on(upstream: Option<&Value>, source: &str) -> Option<Value> {
+    if is_skipped(upstream) {
+        Some(skipped_result(source))
+    } else if is_unavailable(upstream) {
+        let error = upstream
+            .and_then(|r| r["error"].as_str())
+            .unwrap_or_default();
+        Some(unavailable_result(source, error))
+    } else {
+        None
+    }
+}
+
+/// The inclusive `(start_date, end_date)` bounds of the context's `date_range`;
+/// a missing bound is open.
+fn requested_date_range(context: &Value) -> Result<(Option<NaiveDate>, Option<NaiveDate>), String> {
+    let bound = |field: &str| {
+        context["date_range"][field]
+            .as_str()
+            .map(|raw| {
+                NaiveDate::parse_from_str(raw, "%Y-%m-%d").map_err(|_| {
+                    format!(
+                        "Invalid date_range.{} '{}': expected YYYY-MM-DD",
+                        field, raw
+                    )
+                })
+            })
+            .transpose()
+    };
+    Ok((bound("start_date")?, bound("end_date")?))
+}
+
 // ============================================================================
 // Extract Handlers (Parallel - No Dependencies)
 // ============================================================================
 
 /// Extracts sales records from simulated database.
-pub fn extract_sales(_context: &Value) -> Result<Value, String> {
-    let raw = sample_sales();
+pub fn extract_sales(context: &Value) -> Result<Value, String> {
+    if !source_requested(context, "sales") {
+        return Ok(skipped_result("sales"));
+    }
+    unavailable_if_partial(context, "sales", try_extract_sales(context))
+}
+
+fn try_extract_sales(context: &Value) -> Result<Value, String> {
+    let (start, end) = requested_date_range(context)?;
+    let raw: Vec<SalesRecord> = sample_sales()
+        .into_iter()
+        .filter(|r| {
+            let date = NaiveDate::parse_from_str(&r.date, "%Y-%m-%d").ok();
+            start.is_none_or(|start| date.is_some_and(|d| d >= start))
+                && end.is_none_or(|end| date.is_some_and(|d| d <= end))
+        })
+        .collect();
     let total_revenue: f64 = raw.iter().map(|r| r.total).sum();
     let total_quantity: i64 = raw.iter().map(|r| r.quantity).sum();
 
@@ -134,8 +278,8 @@ pub fn extract_sales(_context: &Value) -> Result<Value, String> {
         total_quantity,
         extracted_at: chrono::Utc::now().to_rfc3339(),
         date_range: ExtractSalesDataResultDateRange {
-            start_date: "2025-11-01".to_string(),
-            end_date: "2025-11-25".to_string(),
+            start_date: start.map_or_else(|| "2025-11-01".to_string(), |d| d.to_string()),
+            end_date: end.map_or_else(|| "2025-11-25".to_string(), |d| d.to_string()),
         },
         total_amount: Some(total_revenue),
     };
@@ -144,7 +288,14 @@ pub fn extract_sales(_context: &Value) -> Result<Value, String> {
 }
 
 /// Extracts inventory records from simulated warehouse system.
-pub fn extract_inventory(_context: &Value) -> Result<Value, String> {
+pub fn extract_inventory(context: &Value) -> Result<Value, String> {
+    if !source_requested(context, "inventory") {
+        return Ok(skipped_result("inventory"));
+    }
+    unavailable_if_partial(context, "inventory", try_extract_inventory())
+}
+
+fn try_extract_inventory() -> Result<Value, String> {
     let raw = sample_inventory();
     let total_on_hand: i64 = raw.iter().map(|r| r.quantity_on_hand).sum();
     let warehouses: Vec<String> = raw
@@ -187,7 +338,14 @@ pub fn extract_inventory(_context: &Value) -> Result<Value, String> {
 }
 
 /// Extracts customer records from simulated CRM.
-pub fn extract_customers(_context: &Value) -> Result<Value, String> {
+pub fn extract_customers(context: &Value) -> Result<Value, String> {
+    if !source_requested(context, "customers") {
+        return Ok(skipped_result("customers"));
+    }
+    unavailable_if_partial(context, "customers", try_extract_customers())
+}
+
+fn try_extract_customers() -> Result<Value, String> {
     let raw = sample_customers();
     let total_ltv: f64 = raw.iter().map(|r| r.lifetime_value).sum();
     let mut tier_counts: HashMap<String, i64> = HashMap::new();
@@ -231,7 +389,17 @@ pub fn extract_customers(_context: &Value) -> Result<Value, String> {
 // ============================================================================
 
 /// Transforms sales data into daily and product-level aggregations.
-pub fn transform_sales(dependency_results: &HashMap<String, Value>) -> Result<Value, String> {
+pub fn transform_sales(
+    context: &Value,
+    dependency_results: &HashMap<String, Value>,
+) -> Result<Value, String> {
+    if let Some(result) = passed_on(dependency_results.get("extract_sales_data"), "sales") {
+        return Ok(result);
+    }
+    unavailable_if_partial(context, "sales", try_transform_sales(dependency_results))
+}
+
+fn try_transform_sales(dependency_results: &HashMap<String, Value>) -> Result<Value, String> {
     let extract: ExtractSalesDataResult = dependency_results
         .get("extract_sales_data")
         .ok_or("Missing extract_sales_data dependency".to_string())
@@ -345,7 +513,24 @@ pub fn transform_sales(dependency_results: &HashMap<String, Value>) -> Result<Va
 }
 
 /// Transforms inventory data into warehouse and product summaries with reorder alerts.
-pub fn transform_inventory(dependency_results: &HashMap<String, Value>) -> Result<Value, String> {
+pub fn transform_inventory(
+    context: &Value,
+    dependency_results: &HashMap<String, Value>,
+) -> Result<Value, String> {
+    if let Some(result) = passed_on(
+        dependency_results.get("extract_inventory_data"),
+        "inventory",
+    ) {
+        return Ok(result);
+    }
+    unavailable_if_partial(
+        context,
+        "inventory",
+        try_transform_inventory(dependency_results),
+    )
+}
+
+fn try_transform_inventory(dependency_results: &HashMap<String, Value>) -> Result<Value, String> {
     let extract: ExtractInventoryDataResult = dependency_results
         .get("extract_inventory_data")
         .ok_or("Missing extract_inventory_data dependency".to_string())
@@ -439,7 +624,21 @@ pub fn transform_inventory(dependency_results: &HashMap<String, Value>) -> Resul
 }
 
 /// Transforms customer data into tier analysis and value segmentation.
-pub fn transform_customers(dependency_results: &HashMap<String, Value>) -> Result<Value, String> {
+pub fn transform_customers(
+    context: &Value,
+    dependency_results: &HashMap<String, Value>,
+) -> Result<Value, String> {
+    if let Some(result) = passed_on(dependency_results.get("extract_customer_data"), "customers") {
+        return Ok(result);
+    }
+    unavailable_if_partial(
+        context,
+        "customers",
+        try_transform_customers(dependency_results),
+    )
+}
+
+fn try_transform_customers(dependency_results: &HashMap<String, Value>) -> Result<Value, String> {
     let extract: ExtractCustomerDataResult = dependency_results
         .get("extract_customer_data")
         .ok_or("Missing extract_customer_data dependency".to_string())
@@ -529,82 +728,156 @@ pub fn transform_customers(dependency_results: &HashMap<String, Value>) -> Resul
 // Aggregate Metrics (DAG Convergence)
 // ============================================================================
 
-/// Combines metrics from all 3 transformed data sources into a unified view.
-pub fn aggregate_metrics(dependency_results: &HashMap<String, Value>) -> Result<Value, String> {
-    let sales: TransformSalesResult = dependency_results
-        .get("transform_sales")
-        .ok_or("Missing transform_sales dependency".to_string())
-        .and_then(|v| {
-            serde_json::from_value(v.clone())
-                .map_err(|e| format!("Failed to deserialize sales transform result: {}", e))
-        })?;
-
-    let inventory: TransformInventoryResult = dependency_results
-        .get("transform_inventory")
-        .ok_or("Missing transform_inventory dependency".to_string())
-        .and_then(|v| {
-            serde_json::from_value(v.clone())
-                .map_err(|e| format!("Failed to deserialize inventory transform result: {}", e))
-        })?;
-
-    let customers: TransformCustomersResult = dependency_results
-        .get("transform_customers")
-        .ok_or("Missing transform_customers dependency".to_string())
-        .and_then(|v| {
-            serde_json::from_value(v.clone())
-                .map_err(|e| format!("Failed to deserialize customer transform result: {}", e))
-        })?;
-
-    let total_inventory = inventory.total_quantity_on_hand.unwrap_or(0);
-    let reorder_alerts = inventory.reorder_alerts.unwrap_or(0);
-    let total_customers = customers.record_count;
-    let total_ltv = customers.total_lifetime_value.unwrap_or(0.0);
-
-    let revenue_per_customer = if total_customers > 0 {
-        (sales.total_revenue / total_customers as f64 * 100.0).round() / 100.0
-    } else {
-        0.0
-    };
-
-    let inventory_turnover = if total_inventory > 0 {
-        (sales.total_revenue / total_inventory as f64 * 10000.0).round() / 10000.0
-    } else {
-        0.0
-    };
+/// Combines metrics from the transformed data sources into a unified view.
+///
+/// Sources left out of the task's `sources` are skipped and do not make the
+/// result partial. By default every requested transform result is required.
+/// With `allow_partial: true` in the task context, unavailable (and missing)
+/// transform results are left out too: only the available sources are
+/// aggregated, `sources_included` and `data_sources` list just those, and the
+/// result is flagged `partial: true`. At least one source is always required.
+pub fn aggregate_metrics(
+    context: &Value,
+    dependency_results: &HashMap<String, Value>,
+) -> Result<Value, String> {
+    let allow_partial = allows_partial(context);
+
+    // An unrequested source may be missing whether or not partial results
+    // are allowed
+    let optional = |source: &str| allow_partial || !source_requested(context, source);
+    let sales: Option<TransformSalesResult> = transform_result(
+        dependency_results,
+        "transform_sales",
+        "sales",
+        optional("sales"),
+    )?;
+    let inventory: Option<TransformInventoryResult> = transform_result(
+        dependency_results,
+        "transform_inventory",
+        "inventory",
+        optional("inventory"),
+    )?;
+    let customers: Option<TransformCustomersResult> = transform_result(
+        dependency_results,
+        "transform_customers",
+        "customer",
+        optional("customers"),
+    )?;
+
+    let requested = SOURCES
+        .iter()
+        .filter(|source| source_requested(context, source))
+        .count();
+    let data_sources: Vec<String> = [
+        ("sales", sales.is_some()),
+        ("inventory", inventory.is_some()),
+        ("customers", customers.is_some()),
+    ]
+    .into_iter()
+    .filter(|(_, available)| *available)
+    .map(|(source, _)| source.to_string())
+    .collect();
+    if data_sources.is_empty() {
+        return Err("No transform results available to aggregate".to_string());
+    }
+    let partial = data_sources.len() < requested;
+
+    let total_revenue = sales.as_ref().map(|s| s.total_revenue);
+    let total_inventory = inventory
+        .as_ref()
+        .map(|i| i.total_quantity_on_hand.unwrap_or(0));
+    let reorder_alerts = inventory.as_ref().map(|i| i.reorder_alerts.unwrap_or(0));
+    let total_customers = customers.as_ref().map(|c| c.record_count);
+    let total_ltv = customers
+        .as_ref()
+        .map(|c| c.total_lifetime_value.unwrap_or(0.0));
+
+    // Cross-source ratios need both of their sources
+    let revenue_per_customer = total_revenue.zip(total_customers).map(|(revenue, customers)| {
+        if customers > 0 {
+            (revenue / customers as f64 * 100.0).round() / 100.0
+        } else {
+            0.0
+        }
+    });
+    let inventory_turnover = total_revenue.zip(total_inventory).map(|(revenue, inventory)| {
+        if inventory > 0 {
+            (revenue / inventory as f64 * 10000.0).round() / 10000.0
+        } else {
+            0.0
+        }
+    });
 
-    let total_records = sales.record_count + inventory.record_count + total_customers;
+    let total_records = sales.as_ref().map_or(0, |s| s.record_count)
+        + inventory.as_ref().map_or(0, |i| i.record_count)
+        + total_customers.unwrap_or(0);
 
     info!(
-        "Aggregated: revenue=${:.2}, inventory={}, customers={}, rev/customer=${:.2}",
-        sales.total_revenue, total_inventory, total_customers, revenue_per_customer
+        "Aggregated {} of {} sources: revenue={:?}, inventory={:?}, customers={:?}, rev/customer={:?}",
+        data_sources.len(),
+        requested,
+        total_revenue,
+        total_inventory,
+        total_customers,
+        revenue_per_customer
     );
 
     let result = AggregateMetricsResult {
         total_records_processed: total_records,
-        sources_included: 3,
+        sources_included: data_sources.len() as i64,
         aggregated_at: chrono::Utc::now().to_rfc3339(),
-        data_sources: vec![
-            "sales".to_string(),
-            "inventory".to_string(),
-            "customers".to_string(),
-        ],
-        aggregation_complete: true,
-        total_revenue: Some(sales.total_revenue),
-        total_customers: Some(total_customers),
-        total_customer_lifetime_value: Some(total_ltv),
-        sales_transactions: Some(sales.record_count),
-        total_inventory_quantity: Some(total_inventory),
-        inventory_reorder_alerts: Some(reorder_alerts),
-        revenue_per_customer: Some(revenue_per_customer),
-        inventory_turnover_indicator: Some(inventory_turnover),
-        sales_summary: sales.by_category,
-        inventory_summary: inventory.warehouse_summary,
+        data_sources,
+        aggregation_complete: !partial,
+        partial,
+        total_revenue,
+        total_customers,
+        total_customer_lifetime_value: total_ltv,
+        sales_transactions: sales.as_ref().map(|s| s.record_count),
+        total_inventory_quantity: total_inventory,
+        inventory_reorder_alerts: reorder_alerts,
+        revenue_per_customer,
+        inventory_turnover_indicator: inventory_turnover,
+        sales_summary: sales.and_then(|s| s.by_category),
+        inventory_summary: inventory.and_then(|i| i.warehouse_summary),
         traffic_summary: None,
     };
 
     serde_json::to_value(result).map_err(|e| format!("Failed to serialize result: {}", e))
 }
 
+/// Deserialize a transform step's result. A skipped result is `None`; an
+/// unavailable or missing result is an error unless `allow_partial` is set, in
+/// which case it is `None`.
+fn transform_result<T: serde::de::DeserializeOwned>(
+    dependency_results: &HashMap<String, Value>,
+    step_name: &str,
+    label: &str,
+    allow_partial: bool,
+) -> Result<Option<T>, String> {
+    match dependency_results.get(step_name) {
+        Some(v) if is_skipped(Some(v)) => Ok(None),
+        Some(v) if is_unavailable(Some(v)) => {
+            if allow_partial {
+                info!(
+                    "Aggregating without {} (unavailable: {})",
+                    step_name, v["error"]
+                );
+                Ok(None)
+            } else {
+                Err(format!("{} is unavailable: {}", step_name, v["error"]))
+            }
+        }
+        Some(v) => serde_json::from_value(v.clone())
+            .map(Some)
+            .map_err(|e| format!("Failed to deserialize {} transform result: {}", label, e)),
+        None if allow_partial => {
+            info!("Aggregating without {} (partial mode)", step_name);
+            Ok(None)
+        }
+        None => Err(format!("Missing {} dependency", step_name)),
+    }
+}
+
 // ============================================================================
 // Generate Insights
 // ============================================================================
@@ -1,17 +1,57 @@
 //! # E-commerce Order Processing Handlers
 //!
 //! Native Rust implementation of the e-commerce order processing workflow.
-//! Demonstrates a 5-step linear chain with dependency data passing.
+//! Demonstrates a 6-step linear chain with dependency data passing.
 //!
 //! ## Steps
 //!
-//! 1. **ecommerce_validate_cart**: Validate items, calc subtotal/tax(8%)/shipping/total
-//! 2. **ecommerce_process_payment**: Simulate payment gateway with test tokens
-//! 3. **ecommerce_update_inventory**: Create inventory reservations
-//! 4. **ecommerce_create_order**: Aggregate upstream data, generate order ID
-//! 5. **ecommerce_send_confirmation**: Simulate confirmation email
-
+//! 1. **ecommerce_validate_cart**: Validate items, calc subtotal/tax/shipping/total
+//! 2. **ecommerce_calculate_shipping**: Price shipping by destination zone, final total
+//! 3. **ecommerce_process_payment**: Simulate payment gateway with test tokens
+//! 4. **ecommerce_update_inventory**: Reserve each item from the warehouse with
+//!    the most on hand (decrementing `products.stock` instead when the
+//!    application database is attached)
+//! 5. **ecommerce_create_order**: Aggregate upstream data, generate order ID
+//! 6. **ecommerce_send_confirmation**: Simulate confirmation email
+//!
+//! `validate_cart`'s `shipping` is a flat, destination-unaware estimate. When a
+//! `calculate_shipping` result is present, later steps charge and record its
+//! shipping and total instead.
+//!
+//! The tax rate, flat shipping rate, and free-shipping threshold come from a
+//! [`PricingConfig`] (8%, $5.99, free over $100 by default). The flat rate and
+//! threshold also price `calculate_shipping`'s contiguous US zone.
+//!
+//! An optional `currency` in the context (one of
+//! [`crate::money::SUPPORTED_CURRENCIES`], USD by default) labels every amount
+//! the steps compute and sets their rounding: to the cent for USD, to the whole
+//! yen for JPY (see [`round_amount`]). Prices are not converted. An unsupported
+//! code fails cart validation.
+//!
+//! An optional `coupon_code` in the context applies a [`Coupon`] from the
+//! registry's [`CouponBook`] (the `coupons` table, or [`demo_coupons`]). The
+//! discount comes off the subtotal before tax and shipping are priced, and an
+//! unknown or expired code fails the step.
+//!
+//! Each step builds its result as the matching struct in
+//! [`crate::types::ecommerce`] (`ValidateCartResult`, `ProcessPaymentResult`,
+//! `UpdateInventoryResult`, `CreateOrderResult`, ...) and reads upstream
+//! results back with [`step_result`], so key names are checked at compile time
+//! and the JSON shape stays the one the template declares.
+//!
+//! `ecommerce_reconcile_order` is an optional final check that upstream step
+//! results agree with each other. It is registered but not part of the shipped
+//! template; append it after `send_confirmation` to enable it.
+
+use crate::db::AppDb;
+use crate::gateway::{ChargeRequest, MockPaymentGateway, PaymentGateway};
+use crate::handlers::context::{validate_context, ContextErrors};
+use crate::money::{amount_tolerance, round_amount, supported_currency, DEFAULT_CURRENCY};
+use crate::notifier::{send_blocking, LoggingNotifier, Notifier, OutboundMessage};
 use crate::types::ecommerce::*;
+use crate::warehouses::{demo_warehouse_stock, reserve, WarehouseStock};
+use chrono::NaiveDate;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
@@ -28,16 +68,177 @@ pub struct CartItem {
     pub quantity: i64,
 }
 
-#[derive(Debug, Clone)]
-struct Product {
-    id: i64,
-    name: String,
-    sku: String,
-    price: f64,
-    stock: i64,
+/// A catalog product, as served by `GET /products`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Product {
+    pub id: i64,
+    pub name: String,
+    pub sku: String,
+    pub price: f64,
+    pub stock: i64,
+}
+
+/// Products keyed by id.
+pub type ProductCatalog = HashMap<i64, Product>;
+
+/// Tax and flat shipping pricing.
+///
+/// | Variable | Default | Meaning |
+/// |----------|---------|---------|
+/// | `ECOMMERCE_TAX_RATE` | 0.08 | Tax as a fraction of the subtotal |
+/// | `ECOMMERCE_FREE_SHIPPING_THRESHOLD` | 100.00 | Subtotals above this ship free |
+/// | `ECOMMERCE_FLAT_SHIPPING` | 5.99 | Shipping below the threshold |
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct PricingConfig {
+    pub tax_rate: f64,
+    pub free_shipping_threshold: f64,
+    pub flat_shipping: f64,
+}
+
+impl Default for PricingConfig {
+    fn default() -> Self {
+        Self {
+            tax_rate: 0.08,
+            free_shipping_threshold: 100.0,
+            flat_shipping: 5.99,
+        }
+    }
+}
+
+impl PricingConfig {
+    /// Read the pricing variables; unset ones keep their defaults. The tax
+    /// rate must be between 0 and 1, and the amounts must not be negative.
+    pub fn from_env() -> Result<Self, String> {
+        let defaults = Self::default();
+        let pricing = Self {
+            tax_rate: env_f64("ECOMMERCE_TAX_RATE")?.unwrap_or(defaults.tax_rate),
+            free_shipping_threshold: env_f64("ECOMMERCE_FREE_SHIPPING_THRESHOLD")?
+                .unwrap_or(defaults.free_shipping_threshold),
+            flat_shipping: env_f64("ECOMMERCE_FLAT_SHIPPING")?.unwrap_or(defaults.flat_shipping),
+        };
+        if !(0.0..=1.0).contains(&pricing.tax_rate) {
+            return Err(format!(
+                "ECOMMERCE_TAX_RATE must be between 0 and 1, got {}",
+                pricing.tax_rate
+            ));
+        }
+        if pricing.free_shipping_threshold < 0.0 || pricing.flat_shipping < 0.0 {
+            return Err(
+                "ECOMMERCE_FREE_SHIPPING_THRESHOLD and ECOMMERCE_FLAT_SHIPPING must not be negative"
+                    .to_string(),
+            );
+        }
+        Ok(pricing)
+    }
+
+    /// Flat shipping for a cart with `subtotal`: free above the threshold.
+    pub fn shipping_for(&self, subtotal: f64) -> f64 {
+        if subtotal > self.free_shipping_threshold {
+            0.0
+        } else {
+            self.flat_shipping
+        }
+    }
 }
 
-fn get_product_catalog() -> HashMap<i64, Product> {
+/// How a coupon discounts the cart subtotal.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(tag = "kind", content = "amount", rename_all = "snake_case")]
+pub enum CouponDiscount {
+    /// A percentage of the subtotal, e.g. `10.0` for 10% off.
+    Percent(f64),
+    /// A fixed amount in the cart's currency, never more than the subtotal.
+    Fixed(f64),
+}
+
+impl CouponDiscount {
+    /// The discount on `subtotal`, rounded to `currency`'s minor unit.
+    pub fn amount_off(&self, subtotal: f64, currency: &str) -> f64 {
+        match *self {
+            Self::Percent(percent) => round_amount(subtotal * percent / 100.0, currency),
+            Self::Fixed(amount) => round_amount(amount.min(subtotal), currency),
+        }
+    }
+}
+
+/// A discount code carts may apply.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Coupon {
+    /// Upper-case code, e.g. `SAVE10`.
+    pub code: String,
+    pub discount: CouponDiscount,
+    /// Last day the code is valid (`None` = never expires).
+    pub expires_on: Option<NaiveDate>,
+}
+
+/// Coupons keyed by upper-case code.
+pub type CouponBook = HashMap<String, Coupon>;
+
+/// Look up `code` (case-insensitively) and check it has not expired on `today`.
+pub fn redeem_coupon<'a>(
+    coupons: &'a CouponBook,
+    code: &str,
+    today: NaiveDate,
+) -> Result<&'a Coupon, String> {
+    let code = code.trim().to_uppercase();
+    let coupon = coupons
+        .get(&code)
+        .ok_or_else(|| format!("Unknown coupon code '{}'", code))?;
+    match coupon.expires_on {
+        Some(expires_on) if expires_on < today => {
+            Err(format!("Coupon code '{}' expired on {}", code, expires_on))
+        }
+        _ => Ok(coupon),
+    }
+}
+
+/// The coupons seeded by `migrations/018_create_coupons.sql`.
+///
+/// Handlers use these until the application loads the `coupons` table (see
+/// [`crate::handler_registry::AxumHandlerRegistry::set_coupons`]).
+pub fn demo_coupons() -> CouponBook {
+    [
+        ("SAVE10", CouponDiscount::Percent(10.0), None),
+        ("FIVEOFF", CouponDiscount::Fixed(5.0), None),
+        (
+            "SPRING2020",
+            CouponDiscount::Percent(20.0),
+            NaiveDate::from_ymd_opt(2020, 5, 31),
+        ),
+    ]
+    .into_iter()
+    .map(|(code, discount, expires_on)| {
+        (
+            code.to_string(),
+            Coupon {
+                code: code.to_string(),
+                discount,
+                expires_on,
+            },
+        )
+    })
+    .collect()
+}
+
+fn env_f64(name: &str) -> Result<Option<f64>, String> {
+    match std::env::var(name) {
+        Ok(raw) => raw
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|value| value.is_finite())
+            .map(Some)
+            .ok_or_else(|| format!("Invalid {} '{}': expected a number", name, raw)),
+        Err(_) => Ok(None),
+    }
+}
+
+/// The five demo products seeded by `migrations/015_create_products.sql`.
+///
+/// Handlers use this catalog until the application loads the `products` table
+/// (see [`crate::handler_registry::AxumHandlerRegistry::set_catalog`]), e.g. on
+/// SQLite or when called without a database.
+pub fn demo_catalog() -> ProductCatalog {
     let mut catalog = HashMap::new();
     catalog.insert(
         1,
@@ -92,15 +293,57 @@ fn get_product_catalog() -> HashMap<i64, Product> {
     catalog
 }
 
+// ============================================================================
+// Step Results
+// ============================================================================
+
+/// Read an upstream step's result as its typed result struct.
+///
+/// The result types in [`crate::types::ecommerce`] are generated from the
+/// template's `result_schema`, so a key renamed on either side fails here (and
+/// in the schema consistency tests) rather than going unnoticed.
+pub fn step_result<T: DeserializeOwned>(
+    dependency_results: &HashMap<String, Value>,
+    step: &str,
+) -> Result<T, String> {
+    let value = dependency_results
+        .get(step)
+        .ok_or_else(|| format!("Missing {} dependency result", step))?;
+    serde_json::from_value(value.clone())
+        .map_err(|e| format!("Failed to deserialize {} result: {}", step, e))
+}
+
+/// Serialize a step's typed result into the JSON returned to the worker.
+fn step_output<T: Serialize>(result: &T) -> Result<Value, String> {
+    serde_json::to_value(result).map_err(|e| format!("Failed to serialize result: {}", e))
+}
+
 // ============================================================================
 // Step 1: Validate Cart
 // ============================================================================
 
-/// Validates cart items against the product catalog, checks stock availability,
-/// and calculates pricing including subtotal, tax (8%), shipping, and total.
+/// Validates cart items against the demo catalog and coupons with the default
+/// pricing; see [`validate_cart_against`].
 pub fn validate_cart(context: &Value) -> Result<Value, String> {
-    let input: OrderProcessingInput = serde_json::from_value(context.clone())
-        .map_err(|e| format!("Invalid order processing input: {}", e))?;
+    validate_cart_against(
+        context,
+        &demo_catalog(),
+        &PricingConfig::default(),
+        &demo_coupons(),
+    )
+}
+
+/// Validates cart items against the product catalog, checks stock availability,
+/// applies the context's `coupon_code`, and calculates pricing including
+/// subtotal, discount, tax, flat shipping, and total, rounded in the context's
+/// `currency`.
+pub fn validate_cart_against(
+    context: &Value,
+    catalog: &ProductCatalog,
+    pricing: &PricingConfig,
+    coupons: &CouponBook,
+) -> Result<Value, String> {
+    let input: OrderProcessingInput = validate_context(context, "order processing")?;
 
     let cart_items: Vec<CartItem> = input
         .cart_items
@@ -111,20 +354,40 @@ pub fn validate_cart(context: &Value) -> Result<Value, String> {
         })
         .collect();
 
+    // Report an unsupported currency, unknown products, and bad quantities
+    // together
+    let mut errors = ContextErrors::new();
+    let currency = supported_currency(input.currency.as_deref().unwrap_or(DEFAULT_CURRENCY))
+        .unwrap_or_else(|message| {
+            errors.push(message);
+            String::new()
+        });
     if cart_items.is_empty() {
-        return Err("Cart cannot be empty".to_string());
+        errors.push("cart_items must contain at least one item");
     }
+    for (index, cart_item) in cart_items.iter().enumerate() {
+        if !catalog.contains_key(&cart_item.product_id) {
+            errors.push(format!(
+                "cart_items[{index}].product_id {} is not in the catalog",
+                cart_item.product_id
+            ));
+        }
+        if cart_item.quantity <= 0 {
+            errors.push(format!(
+                "cart_items[{index}].quantity must be positive, got {}",
+                cart_item.quantity
+            ));
+        }
+    }
+    errors.into_result("order processing")?;
+    let round = |amount: f64| round_amount(amount, &currency);
 
-    let catalog = get_product_catalog();
     let mut validated_items = Vec::new();
     let mut subtotal = 0.0_f64;
     let mut item_count = 0_i64;
 
     for cart_item in &cart_items {
-        let product = catalog
-            .get(&cart_item.product_id)
-            .ok_or_else(|| format!("Product {} not found in catalog", cart_item.product_id))?;
-
+        let product = &catalog[&cart_item.product_id];
         if cart_item.quantity > product.stock {
             return Err(format!(
                 "Insufficient stock for {}: requested {}, available {}",
@@ -132,13 +395,6 @@ pub fn validate_cart(context: &Value) -> Result<Value, String> {
             ));
         }
 
-        if cart_item.quantity <= 0 {
-            return Err(format!(
-                "Invalid quantity {} for product {}",
-                cart_item.quantity, product.name
-            ));
-        }
-
         let line_total = product.price * cart_item.quantity as f64;
         subtotal += line_total;
         item_count += cart_item.quantity;
@@ -148,23 +404,42 @@ pub fn validate_cart(context: &Value) -> Result<Value, String> {
             name: product.name.clone(),
             quantity: cart_item.quantity,
             unit_price: product.price,
-            line_total: (line_total * 100.0).round() / 100.0,
+            line_total: round(line_total),
         });
     }
 
-    let tax_rate = 0.08;
-    let tax = (subtotal * tax_rate * 100.0).round() / 100.0;
-    let shipping = if subtotal > 100.0 { 0.0 } else { 5.99 };
-    let total = ((subtotal + tax + shipping) * 100.0).round() / 100.0;
+    let subtotal = round(subtotal);
+    let coupon = input
+        .coupon_code
+        .as_deref()
+        .map(|code| redeem_coupon(coupons, code, chrono::Utc::now().date_naive()))
+        .transpose()?;
+    let discount = coupon.map(|coupon| coupon.discount.amount_off(subtotal, &currency));
+
+    // Tax and shipping are priced on what the customer pays for the items
+    let discounted = round(subtotal - discount.unwrap_or(0.0));
+    let tax_rate = pricing.tax_rate;
+    let tax = round(discounted * tax_rate);
+    let shipping = round(pricing.shipping_for(discounted));
+    let total = round(discounted + tax + shipping);
 
     info!(
-        "Cart validated: {} items, subtotal=${:.2}, tax=${:.2}, shipping=${:.2}, total=${:.2}",
-        item_count, subtotal, tax, shipping, total
+        "Cart validated: {} items in {}, subtotal={:.2}, discount={:.2}, tax={:.2}, shipping={:.2}, total={:.2}",
+        item_count,
+        currency,
+        subtotal,
+        discount.unwrap_or(0.0),
+        tax,
+        shipping,
+        total
     );
 
     let result = ValidateCartResult {
         validated_items,
         subtotal,
+        discount,
+        coupon_code: coupon.map(|coupon| coupon.code.clone()),
+        currency: Some(currency.clone()),
         tax_rate,
         tax,
         shipping,
@@ -173,18 +448,198 @@ pub fn validate_cart(context: &Value) -> Result<Value, String> {
         validated_at: chrono::Utc::now().to_rfc3339(),
     };
 
-    serde_json::to_value(result).map_err(|e| format!("Failed to serialize result: {}", e))
+    step_output(&result)
 }
 
 // ============================================================================
-// Step 2: Process Payment
+// Step 2: Calculate Shipping
 // ============================================================================
 
-/// Simulates payment processing through a payment gateway.
-/// Supports test tokens for simulating various payment outcomes.
+/// A shipping zone: where it applies and how it is priced.
+struct ShippingZone {
+    name: &'static str,
+    /// ISO country codes served by this zone (empty = every other country).
+    countries: &'static [&'static str],
+    /// States/provinces served by this zone (empty = every state).
+    states: &'static [&'static str],
+    /// `None` prices the zone like `validate_cart`: the [`PricingConfig`]
+    /// flat rate, free above its threshold.
+    base_rate: Option<f64>,
+    international_surcharge: f64,
+}
+
+/// Shipping zones, most specific first. The last zone is the catch-all.
+const SHIPPING_ZONES: &[ShippingZone] = &[
+    ShippingZone {
+        name: "us_noncontiguous",
+        countries: &["US"],
+        states: &["AK", "HI", "PR"],
+        base_rate: Some(14.99),
+        international_surcharge: 0.0,
+    },
+    ShippingZone {
+        name: "us_contiguous",
+        countries: &["US"],
+        states: &[],
+        base_rate: None,
+        international_surcharge: 0.0,
+    },
+    ShippingZone {
+        name: "north_america",
+        countries: &["CA", "MX"],
+        states: &[],
+        base_rate: Some(12.99),
+        international_surcharge: 5.00,
+    },
+    ShippingZone {
+        name: "international",
+        countries: &[],
+        states: &[],
+        base_rate: Some(24.99),
+        international_surcharge: 10.00,
+    },
+];
+
+/// Find the shipping zone for a destination country and (optional) state.
+fn shipping_zone(country: &str, state: Option<&str>) -> &'static ShippingZone {
+    SHIPPING_ZONES
+        .iter()
+        .find(|zone| {
+            let country_matches = zone.countries.is_empty() || zone.countries.contains(&country);
+            let state_matches = zone.states.is_empty()
+                || state.is_some_and(|s| zone.states.contains(&s));
+            country_matches && state_matches
+        })
+        .unwrap_or(&SHIPPING_ZONES[SHIPPING_ZONES.len() - 1])
+}
+
+/// Normalize a country to an uppercase ISO code ("usa" -> "US").
+fn normalize_country(country: &str) -> String {
+    match country.trim().to_uppercase().as_str() {
+        "USA" | "UNITED STATES" => "US".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Prices shipping with the default pricing; see [`calculate_shipping_with`].
+pub fn calculate_shipping(
+    context: &Value,
+    dependency_results: &HashMap<String, Value>,
+) -> Result<Value, String> {
+    calculate_shipping_with(context, dependency_results, &PricingConfig::default())
+}
+
+/// Prices shipping for the destination in `shipping_address` and computes the
+/// total to charge (discounted subtotal + tax + shipping).
+///
+/// Orders without a shipping address are priced as contiguous US.
+pub fn calculate_shipping_with(
+    context: &Value,
+    dependency_results: &HashMap<String, Value>,
+    pricing: &PricingConfig,
+) -> Result<Value, String> {
+    let cart: ValidateCartResult = step_result(dependency_results, "validate_cart")?;
+
+    let address: Option<OrderProcessingInputShippingAddress> = context
+        .get("shipping_address")
+        .filter(|v| !v.is_null())
+        .map(|v| {
+            serde_json::from_value(v.clone())
+                .map_err(|e| format!("Invalid shipping_address: {}", e))
+        })
+        .transpose()?;
+
+    let country = address
+        .as_ref()
+        .map(|a| normalize_country(&a.country))
+        .unwrap_or_else(|| "US".to_string());
+    let state = address
+        .as_ref()
+        .and_then(|a| a.state.as_deref())
+        .map(|s| s.trim().to_uppercase());
+
+    let zone = shipping_zone(&country, state.as_deref());
+    let (base, free_shipping_applied) = match zone.base_rate {
+        Some(rate) => (rate, false),
+        None => {
+            let flat = pricing.shipping_for(discounted_subtotal(&cart));
+            (
+                flat,
+                discounted_subtotal(&cart) > pricing.free_shipping_threshold,
+            )
+        }
+    };
+    let currency = cart_currency(&cart);
+    let shipping = round_amount(base + zone.international_surcharge, currency);
+    let total = round_amount(discounted_subtotal(&cart) + cart.tax + shipping, currency);
+
+    info!(
+        "Shipping calculated: zone={} ({}), shipping={:.2} {}, total={:.2} {}",
+        zone.name, country, shipping, currency, total, currency
+    );
+
+    let result = CalculateShippingResult {
+        zone: zone.name.to_string(),
+        destination_country: country,
+        destination_state: state,
+        free_shipping_applied,
+        international_surcharge: zone.international_surcharge,
+        shipping,
+        total,
+        calculated_at: chrono::Utc::now().to_rfc3339(),
+    };
+
+    step_output(&result)
+}
+
+/// The cart subtotal after any coupon discount.
+fn discounted_subtotal(cart: &ValidateCartResult) -> f64 {
+    round_amount(
+        cart.subtotal - cart.discount.unwrap_or(0.0),
+        cart_currency(cart),
+    )
+}
+
+/// The currency the cart was priced in (USD for results from before carts
+/// recorded one).
+fn cart_currency(cart: &ValidateCartResult) -> &str {
+    cart.currency.as_deref().unwrap_or(DEFAULT_CURRENCY)
+}
+
+/// Shipping and total for the order: from `calculate_shipping` when it ran,
+/// otherwise `validate_cart`'s flat estimate.
+fn shipping_and_total(
+    dependency_results: &HashMap<String, Value>,
+    cart: &ValidateCartResult,
+) -> Result<(f64, f64), String> {
+    match dependency_results.get("calculate_shipping") {
+        Some(_) => {
+            let quote: CalculateShippingResult =
+                step_result(dependency_results, "calculate_shipping")?;
+            Ok((quote.shipping, quote.total))
+        }
+        None => Ok((cart.shipping, cart.total)),
+    }
+}
+
+// ============================================================================
+// Step 3: Process Payment
+// ============================================================================
+
+/// Charges the order total through the simulated gateway, whose test tokens
+/// produce the various payment outcomes; see [`process_payment_with`].
 pub fn process_payment(
     context: &Value,
     dependency_results: &HashMap<String, Value>,
+) -> Result<Value, String> {
+    process_payment_with(context, dependency_results, &MockPaymentGateway)
+}
+
+/// Charges the order total through `gateway`.
+pub fn process_payment_with(
+    context: &Value,
+    dependency_results: &HashMap<String, Value>,
+    gateway: &dyn PaymentGateway,
 ) -> Result<Value, String> {
     let token = context
         .get("payment_token")
@@ -196,85 +651,140 @@ pub fn process_payment(
         .and_then(|v| v.as_str())
         .unwrap_or("credit_card");
 
-    let cart: ValidateCartResult = dependency_results
-        .get("validate_cart")
-        .ok_or("Missing validate_cart dependency result".to_string())
-        .and_then(|v| {
-            serde_json::from_value(v.clone())
-                .map_err(|e| format!("Failed to deserialize cart result: {}", e))
-        })?;
+    let cart: ValidateCartResult = step_result(dependency_results, "validate_cart")?;
 
-    match token {
-        "tok_test_declined" => return Err("Card was declined".to_string()),
-        "tok_test_insufficient_funds" => return Err("Insufficient funds on card".to_string()),
-        "tok_test_network_error" => {
-            return Err("Payment gateway unreachable (retryable)".to_string())
-        }
-        _ => {}
-    }
+    let (_, amount) = shipping_and_total(dependency_results, &cart)?;
+    let currency = cart_currency(&cart);
 
-    let transaction_id = format!("txn_{}", &Uuid::new_v4().to_string().replace('-', "")[..12]);
-    let authorization_code = format!(
-        "AUTH{}",
-        &Uuid::new_v4().to_string().replace('-', "")[..6].to_uppercase()
-    );
-    let payment_id = format!("pay_{}", &Uuid::new_v4().to_string().replace('-', "")[..12]);
+    let receipt = gateway
+        .charge(&ChargeRequest {
+            payment_token: token,
+            amount,
+            currency,
+            payment_method: method,
+        })
+        .map_err(|e| e.to_string())?;
 
     info!(
-        "Payment processed: ${:.2} via {} (txn: {}, auth: {})",
-        cart.total, method, transaction_id, authorization_code
+        "Payment processed: {:.2} {} via {} (txn: {}, auth: {})",
+        amount, currency, method, receipt.transaction_id, receipt.authorization_code
     );
 
     let result = ProcessPaymentResult {
-        payment_id,
-        transaction_id,
+        payment_id: receipt.payment_id,
+        transaction_id: receipt.transaction_id,
         status: "completed".to_string(),
-        amount_charged: cart.total,
-        currency: "USD".to_string(),
+        amount_charged: amount,
+        currency: currency.to_string(),
         payment_method_type: method.to_string(),
-        authorization_code,
+        authorization_code: receipt.authorization_code,
         processed_at: chrono::Utc::now().to_rfc3339(),
         gateway_response: Some("approved".to_string()),
     };
 
-    serde_json::to_value(result).map_err(|e| format!("Failed to serialize result: {}", e))
+    step_output(&result)
 }
 
 // ============================================================================
-// Step 3: Update Inventory
+// Step 4: Update Inventory
 // ============================================================================
 
-/// Creates inventory reservations for each validated cart item.
+/// Creates inventory reservations against a fresh copy of the demo warehouse
+/// stock; see [`update_inventory_against`].
 pub fn update_inventory(dependency_results: &HashMap<String, Value>) -> Result<Value, String> {
-    let cart: ValidateCartResult = dependency_results
-        .get("validate_cart")
-        .ok_or("Missing validate_cart dependency result".to_string())
-        .and_then(|v| {
-            serde_json::from_value(v.clone())
-                .map_err(|e| format!("Failed to deserialize cart result: {}", e))
-        })?;
+    update_inventory_against(dependency_results, &mut demo_warehouse_stock())
+}
 
-    let mut updated_products = Vec::new();
-    let mut total_reserved = 0_i64;
-    let catalog = get_product_catalog();
+/// Creates inventory reservations for each validated cart item, each from the
+/// warehouse in `warehouses` with the most of it on hand, and decrements that
+/// warehouse's stock. The reported quantities are the chosen warehouse's.
+///
+/// Fails permanently if no single warehouse holds an item's full quantity, in
+/// which case no stock changes.
+pub fn update_inventory_against(
+    dependency_results: &HashMap<String, Value>,
+    warehouses: &mut Vec<WarehouseStock>,
+) -> Result<Value, String> {
+    let cart = validated_cart(dependency_results)?;
 
+    // Reserve against a copy so a short item leaves every level unchanged
+    let mut stock = warehouses.clone();
+    let mut updated_products = Vec::new();
     for item in &cart.validated_items {
-        let product = catalog.values().find(|p| p.sku == item.sku);
-        let previous_quantity = product.map(|p| p.stock).unwrap_or(100);
-        let product_id = product
-            .map(|p| format!("PROD-{}", p.id))
-            .unwrap_or_else(|| "PROD-0".to_string());
+        let Some(warehouse) = reserve(&mut stock, &item.sku, item.quantity) else {
+            return Err(format!(
+                "No warehouse has {} of {} in stock",
+                item.quantity, item.name
+            ));
+        };
 
         updated_products.push(UpdateInventoryResultUpdatedProducts {
-            product_id,
+            product_id: format!("PROD-{}", warehouse.product_id),
             sku: item.sku.clone(),
-            previous_quantity,
-            new_quantity: previous_quantity - item.quantity,
+            previous_quantity: warehouse.on_hand,
+            new_quantity: warehouse.on_hand - item.quantity,
             reserved: item.quantity,
+            warehouse: Some(warehouse.warehouse),
         });
-        total_reserved += item.quantity;
     }
 
+    let result = inventory_result(updated_products)?;
+    *warehouses = stock;
+    Ok(result)
+}
+
+/// Reserves inventory in the `products` table, decrementing each validated
+/// cart item's stock in one transaction. If any product is short, no stock
+/// changes and the step fails permanently. The table has no per-warehouse
+/// stock, so these reservations name no warehouse.
+pub async fn reserve_inventory(
+    pool: &AppDb,
+    dependency_results: &HashMap<String, Value>,
+) -> Result<Value, String> {
+    let cart = validated_cart(dependency_results)?;
+    let db_error = |e: sqlx::Error| format!("Inventory database error (retryable): {}", e);
+
+    let mut tx = pool.begin().await.map_err(db_error)?;
+    let mut updated_products = Vec::new();
+    for item in &cart.validated_items {
+        let reserved: Option<(i32, i32)> = sqlx::query_as(
+            "UPDATE products SET stock = stock - $1, updated_at = NOW() \
+             WHERE sku = $2 AND stock >= $1 RETURNING id, stock",
+        )
+        .bind(item.quantity)
+        .bind(&item.sku)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+        // Dropping the transaction rolls back earlier items
+        let (id, new_quantity) = reserved.ok_or_else(|| {
+            format!(
+                "Insufficient stock for {}: requested {}",
+                item.name, item.quantity
+            )
+        })?;
+        let new_quantity = i64::from(new_quantity);
+        updated_products.push(UpdateInventoryResultUpdatedProducts {
+            product_id: format!("PROD-{}", id),
+            sku: item.sku.clone(),
+            previous_quantity: new_quantity + item.quantity,
+            new_quantity,
+            reserved: item.quantity,
+            warehouse: None,
+        });
+    }
+    tx.commit().await.map_err(db_error)?;
+
+    inventory_result(updated_products)
+}
+
+fn validated_cart(dependency_results: &HashMap<String, Value>) -> Result<ValidateCartResult, String> {
+    step_result(dependency_results, "validate_cart")
+}
+
+fn inventory_result(updated_products: Vec<UpdateInventoryResultUpdatedProducts>) -> Result<Value, String> {
+    let total_reserved: i64 = updated_products.iter().map(|p| p.reserved).sum();
     let inventory_log_id = format!(
         "inv_{}",
         &Uuid::new_v4().to_string().replace('-', "")[..12]
@@ -294,11 +804,11 @@ pub fn update_inventory(dependency_results: &HashMap<String, Value>) -> Result<V
         inventory_changes: None,
     };
 
-    serde_json::to_value(result).map_err(|e| format!("Failed to serialize result: {}", e))
+    step_output(&result)
 }
 
 // ============================================================================
-// Step 4: Create Order
+// Step 5: Create Order
 // ============================================================================
 
 /// Aggregates data from cart validation, payment processing, and inventory reservation
@@ -312,29 +822,13 @@ pub fn create_order(
         .and_then(|v| v.as_str())
         .unwrap_or("unknown@example.com");
 
-    let cart: ValidateCartResult = dependency_results
-        .get("validate_cart")
-        .ok_or("Missing validate_cart dependency".to_string())
-        .and_then(|v| {
-            serde_json::from_value(v.clone())
-                .map_err(|e| format!("Failed to deserialize cart result: {}", e))
-        })?;
+    let cart: ValidateCartResult = step_result(dependency_results, "validate_cart")?;
 
-    let payment: ProcessPaymentResult = dependency_results
-        .get("process_payment")
-        .ok_or("Missing process_payment dependency".to_string())
-        .and_then(|v| {
-            serde_json::from_value(v.clone())
-                .map_err(|e| format!("Failed to deserialize payment result: {}", e))
-        })?;
+    let payment: ProcessPaymentResult = step_result(dependency_results, "process_payment")?;
 
-    let inventory: UpdateInventoryResult = dependency_results
-        .get("update_inventory")
-        .ok_or("Missing update_inventory dependency".to_string())
-        .and_then(|v| {
-            serde_json::from_value(v.clone())
-                .map_err(|e| format!("Failed to deserialize inventory result: {}", e))
-        })?;
+    let inventory: UpdateInventoryResult = step_result(dependency_results, "update_inventory")?;
+
+    let (shipping, total) = shipping_and_total(dependency_results, &cart)?;
 
     // Convert validated items to order items (same shape)
     let items: Vec<CreateOrderResultItems> = cart
@@ -358,9 +852,10 @@ pub fn create_order(
     let estimated_delivery =
         (chrono::Utc::now() + chrono::Duration::days(5)).format("%Y-%m-%d").to_string();
 
+    let currency = cart_currency(&cart).to_string();
     info!(
-        "Order created: {} for {} (total: ${:.2})",
-        order_id, customer_email, cart.total
+        "Order created: {} for {} (total: {:.2} {})",
+        order_id, customer_email, total, currency
     );
 
     let result = CreateOrderResult {
@@ -371,9 +866,10 @@ pub fn create_order(
         item_count: cart.item_count,
         subtotal: cart.subtotal,
         tax: cart.tax,
-        shipping: cart.shipping,
-        total: cart.total,
-        total_amount: cart.total,
+        shipping,
+        total,
+        total_amount: total,
+        currency: Some(currency),
         customer_email: customer_email.to_string(),
         payment_id: payment.payment_id,
         transaction_id: payment.transaction_id,
@@ -384,45 +880,55 @@ pub fn create_order(
         updated_products: None,
     };
 
-    serde_json::to_value(result).map_err(|e| format!("Failed to serialize result: {}", e))
+    step_output(&result)
 }
 
 // ============================================================================
-// Step 5: Send Confirmation
+// Step 6: Send Confirmation
 // ============================================================================
 
-/// Simulates sending an order confirmation email to the customer.
+/// Sends the order confirmation through the [`LoggingNotifier`]; see
+/// [`send_confirmation_with`].
 pub fn send_confirmation(
     context: &Value,
     dependency_results: &HashMap<String, Value>,
+) -> Result<Value, String> {
+    send_confirmation_with(context, dependency_results, &LoggingNotifier)
+}
+
+/// Sends an order confirmation email to the customer through `notifier`.
+pub fn send_confirmation_with(
+    context: &Value,
+    dependency_results: &HashMap<String, Value>,
+    notifier: &dyn Notifier,
 ) -> Result<Value, String> {
     let customer_email = context
         .get("customer_email")
         .and_then(|v| v.as_str())
         .unwrap_or("unknown@example.com");
 
-    let order: CreateOrderResult = dependency_results
-        .get("create_order")
-        .ok_or("Missing create_order dependency".to_string())
-        .and_then(|v| {
-            serde_json::from_value(v.clone())
-                .map_err(|e| format!("Failed to deserialize order result: {}", e))
-        })?;
+    let order: CreateOrderResult = step_result(dependency_results, "create_order")?;
 
-    let message_id = format!(
-        "email_{}",
-        &Uuid::new_v4().to_string().replace('-', "")[..12]
-    );
     let subject = format!("Order Confirmation - {}", order.order_id);
+    let receipt = send_blocking(
+        notifier,
+        OutboundMessage {
+            channel: "email".to_string(),
+            recipient: customer_email.to_string(),
+            template: "order_confirmation_v2".to_string(),
+            subject: subject.clone(),
+        },
+    )
+    .map_err(|e| e.to_string())?;
 
     info!(
         "Confirmation sent: {} to {} for order {}",
-        message_id, customer_email, order.order_id
+        receipt.message_id, customer_email, order.order_id
     );
 
     let result = SendConfirmationResult {
-        message_id,
-        status: "sent".to_string(),
+        message_id: receipt.message_id,
+        status: receipt.status,
         email_sent: true,
         recipient: customer_email.to_string(),
         subject,
@@ -433,5 +939,78 @@ pub fn send_confirmation(
         email_type: Some("transactional".to_string()),
     };
 
-    serde_json::to_value(result).map_err(|e| format!("Failed to serialize result: {}", e))
+    step_output(&result)
+}
+
+// ============================================================================
+// Reconcile Order (optional final step)
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReconcileOrderResult {
+    pub reconciled: bool,
+    pub cart_total: f64,
+    pub amount_charged: f64,
+    pub item_count: i64,
+    pub total_items_reserved: i64,
+    pub reconciled_at: String,
+}
+
+/// Cross-checks results from earlier steps for data-flow consistency:
+/// the payment must charge exactly the order total (the `calculate_shipping`
+/// total when that step ran, otherwise the cart total), and inventory must reserve
+/// exactly the number of items in the cart. All mismatches are reported together.
+pub fn reconcile_order(dependency_results: &HashMap<String, Value>) -> Result<Value, String> {
+    let cart: ValidateCartResult = step_result(dependency_results, "validate_cart")?;
+
+    let payment: ProcessPaymentResult = step_result(dependency_results, "process_payment")?;
+
+    let inventory: UpdateInventoryResult = step_result(dependency_results, "update_inventory")?;
+
+    let (_, expected_total) = shipping_and_total(dependency_results, &cart)?;
+    let total_source = if dependency_results.contains_key("calculate_shipping") {
+        "calculate_shipping.total"
+    } else {
+        "validate_cart.total"
+    };
+
+    let mut mismatches = Vec::new();
+
+    // Amounts agree to within half of the currency's minor unit
+    if (payment.amount_charged - expected_total).abs() > amount_tolerance(cart_currency(&cart)) {
+        mismatches.push(format!(
+            "process_payment.amount_charged ${:.2} != {} ${:.2}",
+            payment.amount_charged, total_source, expected_total
+        ));
+    }
+
+    if inventory.total_items_reserved != cart.item_count {
+        mismatches.push(format!(
+            "update_inventory.total_items_reserved {} != validate_cart.item_count {}",
+            inventory.total_items_reserved, cart.item_count
+        ));
+    }
+
+    if !mismatches.is_empty() {
+        return Err(format!(
+            "Order reconciliation failed: {}",
+            mismatches.join("; ")
+        ));
+    }
+
+    info!(
+        "Order reconciled: ${:.2} charged, {} items reserved",
+        payment.amount_charged, inventory.total_items_reserved
+    );
+
+    let result = ReconcileOrderResult {
+        reconciled: true,
+        cart_total: expected_total,
+        amount_charged: payment.amount_charged,
+        item_count: cart.item_count,
+        total_items_reserved: inventory.total_items_reserved,
+        reconciled_at: chrono::Utc::now().to_rfc3339(),
+    };
+
+    step_output(&result)
 }
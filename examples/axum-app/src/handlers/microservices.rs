@@ -10,7 +10,20 @@
 //! 3. **microservices_initialize_preferences**: Plan-based defaults [parallel]
 //! 4. **microservices_send_welcome_sequence**: Multi-channel welcome messages [convergence]
 //! 5. **microservices_update_user_status**: Activate user account
+//!
+//! When the worker has the application database, steps 1-3 run their `_in_db`
+//! versions, which make registration idempotent per email: accounts are
+//! stored in the `users` table, re-registering an existing user returns the
+//! original account marked `already_exists`, and steps 2 and 3 return the
+//! billing profile and preferences stored on the user's row the first time
+//! instead of creating new ones. This holds across restarts and workers.
+//!
+//! Without a database (SQLite, tests, `simulate`) the plain functions keep no
+//! state: every registration creates a new account.
 
+use crate::db::AppDb;
+use crate::email::normalize_email;
+use crate::notifier::{send_blocking, LoggingNotifier, Notifier, OutboundMessage};
 use crate::types::microservices::*;
 use serde_json::{json, Value};
 use std::collections::HashMap;
@@ -21,14 +34,67 @@ use uuid::Uuid;
 // Step 1: Create User Account
 // ============================================================================
 
-/// Validates the user email, checks for duplicates, and creates a new user account.
+/// Validates the user email and creates a new user account.
+///
+/// Without a database there is nothing to check duplicates against; see
+/// [`create_user_account_in_db`].
 pub fn create_user_account(context: &Value) -> Result<Value, String> {
+    let (input, email) = validated_registration(context)?;
+    let result = new_account(&input, &email);
+    log_created(&result);
+    serde_json::to_value(result).map_err(|e| format!("Failed to serialize result: {}", e))
+}
+
+/// Creates the user account in the `users` table, or returns the account
+/// already stored for the email, marked `already_exists`.
+///
+/// The unique email column makes check-and-insert atomic across workers.
+pub async fn create_user_account_in_db(pool: &AppDb, context: &Value) -> Result<Value, String> {
+    let (input, email) = validated_registration(context)?;
+    let db_error = |e: sqlx::Error| format!("User database error (retryable): {}", e);
+
+    let account = new_account(&input, &email);
+    let stored = serde_json::to_value(&account)
+        .map_err(|e| format!("Failed to serialize result: {}", e))?;
+    let inserted = sqlx::query(
+        "INSERT INTO users (user_id, email, account) VALUES ($1, $2, $3) \
+         ON CONFLICT (email) DO NOTHING",
+    )
+    .bind(&account.user_id)
+    .bind(&email)
+    .bind(&stored)
+    .execute(pool)
+    .await
+    .map_err(db_error)?
+    .rows_affected();
+    if inserted == 1 {
+        log_created(&account);
+        return Ok(stored);
+    }
+
+    let existing: Value = sqlx::query_scalar("SELECT account FROM users WHERE email = $1")
+        .bind(&email)
+        .fetch_one(pool)
+        .await
+        .map_err(db_error)?;
+    let mut existing: CreateUserAccountResult = serde_json::from_value(existing)
+        .map_err(|e| format!("Invalid stored account for {}: {}", email, e))?;
+    info!(
+        "User {} already exists as {} - returning idempotent success",
+        email, existing.user_id
+    );
+
+    existing.status = "already_exists".to_string();
+    serde_json::to_value(existing).map_err(|e| format!("Failed to serialize result: {}", e))
+}
+
+/// Parse the registration input and return it with the normalized email,
+/// rejecting malformed and blocked addresses.
+fn validated_registration(context: &Value) -> Result<(UserRegistrationInput, String), String> {
     let input: UserRegistrationInput = serde_json::from_value(context.clone())
         .map_err(|e| format!("Invalid user registration input: {}", e))?;
 
-    let email = &input.email;
-    let name = &input.full_name;
-
+    let email = normalize_email(&input.email);
     if !email.contains('@') || !email.contains('.') || email.len() < 5 {
         return Err(format!("Invalid email format: {}", email));
     }
@@ -37,44 +103,28 @@ pub fn create_user_account(context: &Value) -> Result<Value, String> {
         return Err(format!("Email domain is blocked: {}", email));
     }
 
-    let user_id = format!("usr_{}", &Uuid::new_v4().to_string().replace('-', "")[..12]);
-    let plan = input.plan.as_deref().unwrap_or("free");
-    let source = input.source.as_deref().unwrap_or("web");
-    let phone = input.phone.as_deref();
-
-    // Check for existing user (idempotency - simulated)
-    if email == "existing@example.com" {
-        info!(
-            "User {} already exists - returning idempotent success",
-            email
-        );
-        let result = CreateUserAccountResult {
-            user_id: "user_existing_001".to_string(),
-            email: email.to_string(),
-            status: "already_exists".to_string(),
-            created_at: "2025-01-01T00:00:00Z".to_string(),
-            name: Some(name.to_string()),
-            full_name: None,
-            phone: None,
-            plan: Some(plan.to_string()),
-            source: Some(source.to_string()),
-            username: None,
-            referral_code: None,
-            internal_id: None,
-            account_status: None,
-            email_verified: None,
-            verification_token: None,
-        };
-        return serde_json::to_value(result)
-            .map_err(|e| format!("Failed to serialize result: {}", e));
-    }
+    Ok((input, email))
+}
 
+fn log_created(account: &CreateUserAccountResult) {
     info!(
         "User account created: {} ({}) on {} plan, user_id={}",
-        name, email, plan, user_id
+        account.name.as_deref().unwrap_or_default(),
+        account.email,
+        account.plan.as_deref().unwrap_or_default(),
+        account.user_id
     );
+}
 
-    let result = CreateUserAccountResult {
+/// Build a new account with a fresh `user_id` for a validated registration.
+fn new_account(input: &UserRegistrationInput, email: &str) -> CreateUserAccountResult {
+    let name = &input.full_name;
+    let user_id = format!("usr_{}", &Uuid::new_v4().to_string().replace('-', "")[..12]);
+    let plan = input.plan.as_deref().unwrap_or("free");
+    let source = input.source.as_deref().unwrap_or("web");
+    let phone = input.phone.as_deref();
+
+    CreateUserAccountResult {
         user_id,
         email: email.to_string(),
         status: "created".to_string(),
@@ -93,9 +143,80 @@ pub fn create_user_account(context: &Value) -> Result<Value, String> {
             "vrf_{}",
             &Uuid::new_v4().to_string().replace('-', "")[..16]
         )),
-    };
+    }
+}
 
-    serde_json::to_value(result).map_err(|e| format!("Failed to serialize result: {}", e))
+// ============================================================================
+// Onboarding results stored per user (steps 2 and 3)
+// ============================================================================
+
+/// A `users` column holding one onboarding step's result.
+#[derive(Debug, Clone, Copy)]
+enum OnboardingResult {
+    Billing,
+    Preferences,
+}
+
+impl OnboardingResult {
+    fn column(self) -> &'static str {
+        match self {
+            Self::Billing => "billing",
+            Self::Preferences => "preferences",
+        }
+    }
+}
+
+/// The `create_user_account` result the onboarding steps depend on.
+fn registered_user(
+    dependency_results: &HashMap<String, Value>,
+) -> Result<CreateUserAccountResult, String> {
+    dependency_results
+        .get("create_user_account")
+        .ok_or("Missing create_user_account dependency".to_string())
+        .and_then(|v| {
+            serde_json::from_value(v.clone())
+                .map_err(|e| format!("Failed to deserialize user result: {}", e))
+        })
+}
+
+/// The result stored for a re-registered user, if any. New users have none.
+async fn stored_onboarding_result(
+    pool: &AppDb,
+    kind: OnboardingResult,
+    user: &CreateUserAccountResult,
+) -> Result<Option<Value>, String> {
+    if user.status != "already_exists" {
+        return Ok(None);
+    }
+    let query = format!("SELECT {} FROM users WHERE email = $1", kind.column());
+    let stored: Option<Option<Value>> = sqlx::query_scalar(&query)
+        .bind(normalize_email(&user.email))
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("User database error (retryable): {}", e))?;
+    Ok(stored.flatten())
+}
+
+/// Store `result` on the user's row unless one is already there, and return
+/// the stored result. A concurrent run of the same step keeps the first.
+async fn store_onboarding_result(
+    pool: &AppDb,
+    kind: OnboardingResult,
+    user: &CreateUserAccountResult,
+    result: Value,
+) -> Result<Value, String> {
+    let query = format!(
+        "UPDATE users SET {column} = COALESCE({column}, $2) WHERE email = $1 RETURNING {column}",
+        column = kind.column()
+    );
+    let stored: Option<Value> = sqlx::query_scalar(&query)
+        .bind(normalize_email(&user.email))
+        .bind(&result)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("User database error (retryable): {}", e))?;
+    // An account created without the database has no row to store on
+    Ok(stored.unwrap_or(result))
 }
 
 // ============================================================================
@@ -108,14 +229,26 @@ pub fn setup_billing_profile(
     context: &Value,
     dependency_results: &HashMap<String, Value>,
 ) -> Result<Value, String> {
-    let user: CreateUserAccountResult = dependency_results
-        .get("create_user_account")
-        .ok_or("Missing create_user_account dependency".to_string())
-        .and_then(|v| {
-            serde_json::from_value(v.clone())
-                .map_err(|e| format!("Failed to deserialize user result: {}", e))
-        })?;
+    new_billing_profile(registered_user(dependency_results)?)
+}
 
+/// Sets up the billing profile and stores it on the user's `users` row. A
+/// re-registered user gets the profile stored the first time.
+pub async fn setup_billing_profile_in_db(
+    pool: &AppDb,
+    dependency_results: &HashMap<String, Value>,
+) -> Result<Value, String> {
+    let user = registered_user(dependency_results)?;
+    let kind = OnboardingResult::Billing;
+    if let Some(existing) = stored_onboarding_result(pool, kind, &user).await? {
+        info!("Billing profile already set up for {}", user.user_id);
+        return Ok(existing);
+    }
+    let profile = new_billing_profile(user.clone())?;
+    store_onboarding_result(pool, kind, &user, profile).await
+}
+
+fn new_billing_profile(user: CreateUserAccountResult) -> Result<Value, String> {
     let plan = user.plan.as_deref().unwrap_or("free");
 
     let billing_id = format!(
@@ -221,14 +354,27 @@ pub fn initialize_preferences(
     context: &Value,
     dependency_results: &HashMap<String, Value>,
 ) -> Result<Value, String> {
-    let user: CreateUserAccountResult = dependency_results
-        .get("create_user_account")
-        .ok_or("Missing create_user_account dependency".to_string())
-        .and_then(|v| {
-            serde_json::from_value(v.clone())
-                .map_err(|e| format!("Failed to deserialize user result: {}", e))
-        })?;
+    new_preferences(context, registered_user(dependency_results)?)
+}
+
+/// Initializes user preferences and stores them on the user's `users` row. A
+/// re-registered user keeps the preferences stored the first time.
+pub async fn initialize_preferences_in_db(
+    pool: &AppDb,
+    context: &Value,
+    dependency_results: &HashMap<String, Value>,
+) -> Result<Value, String> {
+    let user = registered_user(dependency_results)?;
+    let kind = OnboardingResult::Preferences;
+    if let Some(existing) = stored_onboarding_result(pool, kind, &user).await? {
+        info!("Preferences already initialized for {}", user.user_id);
+        return Ok(existing);
+    }
+    let preferences = new_preferences(context, user.clone())?;
+    store_onboarding_result(pool, kind, &user, preferences).await
+}
 
+fn new_preferences(context: &Value, user: CreateUserAccountResult) -> Result<Value, String> {
     let plan = user.plan.as_deref().unwrap_or("free");
     let custom_prefs = context.get("preferences");
 
@@ -323,11 +469,22 @@ pub fn initialize_preferences(
 // Step 4: Send Welcome Sequence (convergence point)
 // ============================================================================
 
-/// Sends a multi-channel welcome sequence to the new user.
-#[expect(unused_variables, reason = "context available for future use")]
+/// Sends the welcome sequence through the [`LoggingNotifier`]; see
+/// [`send_welcome_sequence_with`].
 pub fn send_welcome_sequence(
     context: &Value,
     dependency_results: &HashMap<String, Value>,
+) -> Result<Value, String> {
+    send_welcome_sequence_with(context, dependency_results, &LoggingNotifier)
+}
+
+/// Sends a multi-channel welcome sequence to the new user through `notifier`.
+/// The step fails with the first message the notifier does not accept.
+#[expect(unused_variables, reason = "context available for future use")]
+pub fn send_welcome_sequence_with(
+    context: &Value,
+    dependency_results: &HashMap<String, Value>,
+    notifier: &dyn Notifier,
 ) -> Result<Value, String> {
     let user: CreateUserAccountResult = dependency_results
         .get("create_user_account")
@@ -372,31 +529,34 @@ pub fn send_welcome_sequence(
         _ => ("Welcome to Our Platform!", "Thanks for joining us"),
     };
 
-    let mut channels_used = Vec::new();
-    let mut messages_detail = Vec::new();
-
+    let mut sequence = Vec::new();
     if email_notifications_enabled {
-        channels_used.push("email".to_string());
-        messages_detail.push(SendWelcomeSequenceResultMessagesSentDetails {
-            channel: "email".to_string(),
-            template: "welcome_email".to_string(),
-            status: "sent".to_string(),
-        });
+        sequence.push(("email", "welcome_email", user.email.as_str()));
     }
-
-    channels_used.push("in_app".to_string());
-    messages_detail.push(SendWelcomeSequenceResultMessagesSentDetails {
-        channel: "in_app".to_string(),
-        template: "welcome_notification".to_string(),
-        status: "delivered".to_string(),
-    });
-
+    sequence.push(("in_app", "welcome_notification", user.user_id.as_str()));
     if plan == "enterprise" {
-        channels_used.push("sms".to_string());
+        sequence.push(("sms", "enterprise_welcome_sms", user.user_id.as_str()));
+    }
+
+    let mut channels_used = Vec::new();
+    let mut messages_detail = Vec::new();
+    for (channel, template, recipient) in sequence {
+        let receipt = send_blocking(
+            notifier,
+            OutboundMessage {
+                channel: channel.to_string(),
+                recipient: recipient.to_string(),
+                template: template.to_string(),
+                subject: subject.to_string(),
+            },
+        )
+        .map_err(|e| e.to_string())?;
+        channels_used.push(channel.to_string());
         messages_detail.push(SendWelcomeSequenceResultMessagesSentDetails {
-            channel: "sms".to_string(),
-            template: "enterprise_welcome_sms".to_string(),
-            status: "sent".to_string(),
+            channel: channel.to_string(),
+            template: template.to_string(),
+            title: Some(subject.to_string()),
+            status: receipt.status,
         });
     }
 
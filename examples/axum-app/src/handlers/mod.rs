@@ -7,9 +7,13 @@
 //! - `customer_success`: Customer success refund process (5 handlers)
 //! - `payments`: Payments refund process (4 handlers)
 //!
+//! `context` validates a workflow's task context, reporting every missing or
+//! invalid field at once.
+//!
 //! All handlers implement the `RustStepHandler` trait from tasker-worker and
 //! follow the same patterns as the handlers in tasker-core's workers/rust crate.
 
+pub mod context;
 pub mod customer_success;
 pub mod data_pipeline;
 pub mod ecommerce;
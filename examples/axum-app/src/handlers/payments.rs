@@ -11,6 +11,9 @@
 //! 3. **team_scaling_payments_update_records**: Update payment records
 //! 4. **team_scaling_payments_notify_customer**: Send refund notification to customer
 
+use crate::gateway::{MockPaymentGateway, PaymentGateway, RefundRequest};
+use crate::money;
+use crate::notifier::{send_blocking, LoggingNotifier, Notifier, OutboundMessage};
 use crate::types::payments::*;
 use chrono::Datelike;
 use serde_json::{json, Value};
@@ -18,6 +21,20 @@ use std::collections::HashMap;
 use tracing::info;
 use uuid::Uuid;
 
+/// The refund's notification address, from the task context.
+///
+/// A refund without a usable address fails instead of notifying a placeholder.
+fn refund_customer_email(input: &ProcessRefundInput) -> Result<&str, String> {
+    let email = input.customer_email.trim();
+    if email.is_empty() || !email.contains('@') {
+        return Err(format!(
+            "Invalid process refund input: customer_email '{}' is not an email address",
+            input.customer_email
+        ));
+    }
+    Ok(email)
+}
+
 // ============================================================================
 // Step 1: Validate Payment Eligibility
 // ============================================================================
@@ -35,7 +52,7 @@ pub fn validate_payment_eligibility(context: &Value) -> Result<Value, String> {
         .and_then(|v| v.as_str())
         .unwrap_or("credit_card");
 
-    let customer_email = input.customer_email.as_deref().unwrap_or("unknown@example.com");
+    let customer_email = refund_customer_email(&input)?;
 
     let order_ref = context
         .get("order_id")
@@ -45,6 +62,9 @@ pub fn validate_payment_eligibility(context: &Value) -> Result<Value, String> {
     if refund_amount <= 0.0 {
         return Err("Refund amount must be positive".to_string());
     }
+    let currency = input.currency.as_deref().unwrap_or(money::DEFAULT_CURRENCY);
+    money::check_precision(refund_amount, currency)
+        .map_err(|e| format!("Invalid refund amount: {}", e))?;
 
     if payment_id.contains("pay_test_insufficient") {
         return Err("Insufficient funds available for refund".to_string());
@@ -70,7 +90,9 @@ pub fn validate_payment_eligibility(context: &Value) -> Result<Value, String> {
         ));
     }
 
-    let original_amount = refund_amount + 1000.0;
+    // The route passes the local order's total; without one, the refund is
+    // taken to be a full refund of the original payment
+    let original_amount = input.original_amount.unwrap_or(refund_amount);
 
     if refund_amount > original_amount {
         return Err(format!(
@@ -116,9 +138,18 @@ pub fn validate_payment_eligibility(context: &Value) -> Result<Value, String> {
 // Step 2: Process Gateway Refund
 // ============================================================================
 
-/// Processes the refund through the payment gateway.
+/// Processes the refund through the simulated gateway; see
+/// [`process_gateway_refund_with`].
 pub fn process_gateway_refund(
     dependency_results: &HashMap<String, Value>,
+) -> Result<Value, String> {
+    process_gateway_refund_with(dependency_results, &MockPaymentGateway)
+}
+
+/// Processes the refund through `gateway`.
+pub fn process_gateway_refund_with(
+    dependency_results: &HashMap<String, Value>,
+    gateway: &dyn PaymentGateway,
 ) -> Result<Value, String> {
     let eligibility: ValidatePaymentEligibilityResult = dependency_results
         .get("validate_payment_eligibility")
@@ -137,19 +168,15 @@ pub fn process_gateway_refund(
         .as_deref()
         .unwrap_or("credit_card");
 
-    if eligibility.payment_id.contains("pay_test_gateway_timeout") {
-        return Err("Gateway timeout, will retry".to_string());
-    }
-    if eligibility.payment_id.contains("pay_test_gateway_error") {
-        return Err("Gateway refund failed: Gateway error".to_string());
-    }
-
-    let refund_id = format!(
-        "rfnd_{}",
-        &Uuid::new_v4().to_string().replace('-', "")[..12]
-    );
-    let gateway_transaction_id =
-        format!("gtx_{}", &Uuid::new_v4().to_string().replace('-', "")[..12]);
+    let receipt = gateway
+        .refund(&RefundRequest {
+            payment_id: &eligibility.payment_id,
+            amount: eligibility.refund_amount,
+            payment_method,
+        })
+        .map_err(|e| e.to_string())?;
+    let refund_id = receipt.refund_id;
+    let gateway_transaction_id = receipt.gateway_transaction_id;
     let gateway_txn_id = gateway_transaction_id.clone();
 
     let estimated_days: i64 = match payment_method {
@@ -174,14 +201,11 @@ pub fn process_gateway_refund(
         amount_processed: eligibility.refund_amount,
         gateway_status: "approved".to_string(),
         processed_at: now.to_rfc3339(),
-        authorization_code: Some(format!(
-            "AUTH{}",
-            &Uuid::new_v4().to_string().replace('-', "")[..6].to_uppercase()
-        )),
+        authorization_code: Some(receipt.authorization_code),
         currency: Some("USD".to_string()),
         estimated_arrival: Some(estimated_arrival),
         gateway: None,
-        gateway_provider: Some("MockPaymentGateway".to_string()),
+        gateway_provider: Some(gateway.name().to_string()),
         gateway_transaction_id: Some(gateway_transaction_id),
         gateway_txn_id: Some(gateway_txn_id),
         namespace: Some("payments_rs".to_string()),
@@ -288,10 +312,20 @@ pub fn update_payment_records(
 // Step 4: Notify Customer
 // ============================================================================
 
-/// Sends a refund notification to the customer.
+/// Sends the refund notification through the [`LoggingNotifier`]; see
+/// [`notify_customer_with`].
 pub fn notify_customer(
     context: &Value,
     dependency_results: &HashMap<String, Value>,
+) -> Result<Value, String> {
+    notify_customer_with(context, dependency_results, &LoggingNotifier)
+}
+
+/// Sends a refund notification to the customer through `notifier`.
+pub fn notify_customer_with(
+    context: &Value,
+    dependency_results: &HashMap<String, Value>,
+    notifier: &dyn Notifier,
 ) -> Result<Value, String> {
     let gateway: ProcessGatewayRefundResult = dependency_results
         .get("process_gateway_refund")
@@ -313,34 +347,35 @@ pub fn notify_customer(
         return Err("Refund must be processed before sending notification".to_string());
     }
 
-    let customer_email = context
-        .get("customer_email")
-        .and_then(|v| v.as_str())
-        .or(eligibility.customer_email.as_deref())
-        .unwrap_or("unknown@example.com");
-
-    if customer_email.contains("@test_bounce") {
-        return Err("Customer email bounced".to_string());
-    }
-    if customer_email.contains("@test_rate_limit") {
-        return Err("Email service rate limited, will retry".to_string());
-    }
+    let input: ProcessRefundInput = serde_json::from_value(context.clone())
+        .map_err(|e| format!("Invalid process refund input: {}", e))?;
+    let customer_email = refund_customer_email(&input)?;
 
     let refund_amount = gateway.refund_amount.unwrap_or(0.0);
     let order_ref = &eligibility.order_ref;
 
-    let message_id = format!("msg_{}", &Uuid::new_v4().to_string().replace('-', "")[..12]);
+    let subject = format!(
+        "Your refund of ${:.2} for order {} has been processed",
+        refund_amount, order_ref
+    );
+    let receipt = send_blocking(
+        notifier,
+        OutboundMessage {
+            channel: "email".to_string(),
+            recipient: customer_email.to_string(),
+            template: "refund_notification_v2".to_string(),
+            subject: subject.clone(),
+        },
+    )
+    .map_err(|e| e.to_string())?;
+    let message_id = receipt.message_id;
+
     let notification_id = format!(
         "notif_{}",
         &Uuid::new_v4().to_string().replace('-', "")[..12]
     );
     let now = chrono::Utc::now().to_rfc3339();
 
-    let subject = format!(
-        "Your refund of ${:.2} for order {} has been processed",
-        refund_amount, order_ref
-    );
-
     info!(
         "Customer notification sent: message_id={}, customer_email={}, refund_id={}",
         message_id, customer_email, gateway.refund_id
@@ -0,0 +1,48 @@
+//! The `Idempotency-Key` request header.
+//!
+//! A client retrying `POST /orders` after a network failure sends the same key
+//! again. The key is stored on the order, and a repeat returns the original
+//! order with 200 instead of creating a duplicate order and workflow. The
+//! original order is returned whatever the repeated request's body is.
+//!
+//! Keys are 1-[`MAX_IDEMPOTENCY_KEY_LEN`] visible ASCII characters; anything
+//! else is a 422 naming the header.
+
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+
+use crate::error::ApiError;
+
+/// Request header carrying the key.
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Longest accepted key (the width of `orders.idempotency_key`).
+pub const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
+/// The request's idempotency key, if it sent one.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IdempotencyKey(pub Option<String>);
+
+impl<S> FromRequestParts<S> for IdempotencyKey
+where
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Some(value) = parts.headers.get(IDEMPOTENCY_KEY_HEADER) else {
+            return Ok(Self(None));
+        };
+        let key = value
+            .to_str()
+            .map_err(|_| ApiError::validation(IDEMPOTENCY_KEY_HEADER, "must be visible ASCII"))?
+            .trim();
+        if key.is_empty() || key.len() > MAX_IDEMPOTENCY_KEY_LEN {
+            return Err(ApiError::validation(
+                IDEMPOTENCY_KEY_HEADER,
+                format!("must be 1-{} characters", MAX_IDEMPOTENCY_KEY_LEN),
+            ));
+        }
+        Ok(Self(Some(key.to_string())))
+    }
+}
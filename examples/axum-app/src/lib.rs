@@ -3,29 +3,143 @@
 //! Exposes the Axum router and modules so integration tests can create
 //! an in-process server without requiring `cargo run` in another terminal.
 
+pub mod admin_auth;
+pub mod archiver;
+pub mod attribution;
+pub mod callbacks;
+pub mod cancellation;
+pub mod catalog;
+pub mod circuit_breaker;
+pub mod concurrency;
+pub mod config;
+pub mod cors;
 pub mod db;
+pub mod dead_letter;
+pub mod email;
+pub mod env;
+pub mod error;
+pub mod eta;
+pub mod extract;
+pub mod gateway;
+pub mod handler_manifest;
+pub mod handler_metrics;
+pub mod handler_policy;
 pub mod handler_registry;
 pub mod handlers;
+pub mod idempotency;
+pub mod metrics;
 pub mod models;
+pub mod money;
+pub mod normalize;
+pub mod notifier;
+pub mod orchestration;
+pub mod pagination;
+pub mod quotas;
+pub mod rate_limit;
+pub mod reconciler;
+pub mod request_id;
+pub mod retry;
 pub mod routes;
+pub mod security_headers;
+pub mod shutdown;
+pub mod simulate;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod startup;
+pub mod step_results;
+pub mod submission;
+pub mod sweeper;
+pub mod tags;
+pub mod task_events;
+pub mod task_status;
+pub mod templates;
 pub mod types;
+pub mod warehouses;
+pub mod webhook_auth;
+pub mod worker_config;
 
-use axum::{Extension, Router};
+use std::sync::Arc;
+
+use axum::{middleware, Extension, Router};
 use sqlx::PgPool;
-use tower_http::cors::CorsLayer;
+use tower_http::compression::predicate::{NotForContentType, Predicate, SizeAbove};
+use tower_http::compression::CompressionLayer;
 use tower_http::trace::TraceLayer;
 
+pub use config::AppConfig;
+
 /// Build the Axum router with all route modules and middleware.
 ///
 /// The caller is responsible for providing a connected database pool.
 /// This function does NOT start a server or bootstrap the Tasker worker.
+///
+/// Uses the default [`AppConfig`] (strict request schemas); see
+/// [`create_app_with_config`] to customize it.
 pub fn create_app(app_db: PgPool) -> Router {
-    Router::new()
+    create_app_with_config(app_db, AppConfig::default())
+}
+
+/// Build the Axum router with an explicit [`AppConfig`].
+pub fn create_app_with_config(app_db: PgPool, config: AppConfig) -> Router {
+    let router = Router::new()
         .merge(routes::orders::router())
+        .merge(routes::order_monitor::router())
+        .merge(routes::products::router())
         .merge(routes::analytics::router())
         .merge(routes::services::router())
         .merge(routes::compliance::router())
+        .merge(routes::customers::router())
+        .merge(routes::metrics::router())
+        .merge(routes::health::router())
+        .merge(routes::simulate::router())
+        .merge(routes::admin::router())
+        .merge(routes::debug::router())
+        .merge(routes::tasks::router())
+        .merge(routes::webhooks::router());
+
+    #[cfg(feature = "test-util")]
+    let router = router.merge(routes::metrics::reset_router());
+
+    let router = if config.debug_endpoints {
+        router.merge(routes::debug::registry_router())
+    } else {
+        router
+    };
+
+    router
+        .layer(middleware::from_fn(quotas::enforce_quotas))
+        // Shed before quotas, so a shed request doesn't use a quota slot
+        .layer(middleware::from_fn(concurrency::limit_concurrency))
+        // Reject flooding clients before they take a concurrency permit
+        .layer(middleware::from_fn(rate_limit::rate_limit))
+        .layer(middleware::from_fn(metrics::track_http_metrics))
+        .layer(middleware::from_fn(security_headers::set_security_headers))
         .layer(Extension(app_db))
+        .layer(Extension(Arc::new(config.field_aliases)))
+        .layer(Extension(Arc::new(config.initiators)))
+        .layer(Extension(Arc::new(config.concurrency_limits)))
+        .layer(Extension(config.rate_limiter))
+        .layer(Extension(config.handler_registry))
+        .layer(Extension(config.handler_metrics))
+        .layer(Extension(config.orchestration))
+        .layer(Extension(config.task_events))
+        .layer(Extension(config.admin_auth))
+        .layer(Extension(config.webhook_auth))
+        .layer(Extension(Arc::new(config.security_headers)))
+        .layer(compression_layer(config.compression_min_bytes))
+        .layer(middleware::from_fn(request_id::propagate_request_id))
         .layer(TraceLayer::new_for_http())
-        .layer(CorsLayer::permissive())
+        .layer(config.cors.layer())
+}
+
+/// Compress responses (gzip or brotli, per `Accept-Encoding`) above `min_bytes`.
+///
+/// Images, gRPC, and server-sent event streams are never compressed.
+fn compression_layer(min_bytes: u16) -> CompressionLayer<impl Predicate> {
+    CompressionLayer::new().compress_when(
+        SizeAbove::new(min_bytes)
+            .and(NotForContentType::GRPC)
+            .and(NotForContentType::IMAGES)
+            .and(NotForContentType::SSE),
+    )
 }
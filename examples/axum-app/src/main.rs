@@ -5,7 +5,7 @@
 //!
 //! ## Workflow Patterns
 //!
-//! 1. **E-commerce Order Processing** (5 steps, linear chain)
+//! 1. **E-commerce Order Processing** (6 steps, linear chain)
 //! 2. **Data Pipeline Analytics** (8 steps, DAG with parallel extraction)
 //! 3. **Microservices User Registration** (5 steps, diamond pattern)
 //! 4. **Team Scaling with Namespace Isolation** (9 steps, 2 namespaces)
@@ -16,17 +16,42 @@
 //! - SQLx manages the application-specific database (domain models)
 //! - Tasker worker runs in the background for workflow step execution
 //! - Tasker client communicates with orchestration for task creation
+//!
+//! With the `sqlite` feature, an `APP_DATABASE_URL` starting with `sqlite:`
+//! serves the order routes from SQLite instead (see `example_axum_app::sqlite`).
 
+use std::net::SocketAddr;
+use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 
-use sqlx::postgres::PgPoolOptions;
-use tracing::info;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
 
-use example_axum_app::{create_app, db, handler_registry};
-use tasker_worker::worker::handlers::{HandlerDispatchConfig, HandlerDispatchService, NoOpCallback};
+use example_axum_app::archiver::{self, ArchiverConfig};
+use example_axum_app::catalog;
+use example_axum_app::callbacks::CallbackChain;
+use example_axum_app::dead_letter::{AlertWebhookConfig, DeadLetterAlerter, DeadLetterCallback};
+use example_axum_app::handler_metrics::MetricsCallback;
+use example_axum_app::handler_registry::{enabled_namespaces_from_env, AxumHandlerRegistry};
+#[cfg(feature = "sqlite")]
+use example_axum_app::rate_limit;
+use example_axum_app::reconciler::{self, ReconcilerConfig};
+#[cfg(feature = "sqlite")]
+use example_axum_app::security_headers::set_security_headers;
+use example_axum_app::shutdown::{self, ShutdownToken};
+#[cfg(feature = "sqlite")]
+use example_axum_app::sqlite;
+use example_axum_app::startup::{startup_checks, StartupConfig};
+use example_axum_app::step_results::{self, StepResultRecorder};
+use example_axum_app::sweeper::{self, SweeperConfig};
+use example_axum_app::worker_config;
+use example_axum_app::{create_app_with_config, db, AppConfig};
+use tasker_worker::worker::handlers::{
+    HandlerDispatchConfig, HandlerDispatchService, PostHandlerCallback,
+};
 
-#[tokio::main]
-async fn main() -> anyhow::Result<()> {
+fn main() -> anyhow::Result<()> {
     dotenvy::dotenv().ok();
 
     // Initialize tracing
@@ -39,60 +64,344 @@ async fn main() -> anyhow::Result<()> {
 
     info!("Starting Axum example application");
 
-    // Application database pool (for domain models: orders, analytics_jobs, etc.)
-    let app_db_url = db::pool_from_env();
-    let app_db = PgPoolOptions::new()
-        .max_connections(10)
-        .connect(&app_db_url)
-        .await?;
+    // Point the worker at its configuration and templates while the process
+    // is still single-threaded: both change environment variables
+    configure_poll_interval()?;
+    configure_template_path()?;
+
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?
+        .block_on(run())
+}
+
+async fn run() -> anyhow::Result<()> {
+
+    let app_config = AppConfig::from_env()?;
+    if !app_config.field_aliases.is_empty() {
+        info!("Accepting request body field aliases from FIELD_ALIASES");
+    }
+
+    #[cfg(feature = "sqlite")]
+    {
+        let app_db_url = db::pool_from_env();
+        if sqlite::is_sqlite_url(&app_db_url) {
+            return run_sqlite(&app_db_url, app_config).await;
+        }
+    }
+
+    // Application database pool (for domain models: orders, analytics_jobs, etc.),
+    // tuned by the APP_DB_* settings; runs the application migrations
+    let app_db = db::connect().await?;
+    info!("Connected to application database, migrations complete");
+
+    // Validate carts against the products and coupons tables rather than the
+    // demo catalog, and let database handlers (inventory reservations) use the pool
+    let products = catalog::load_products(&app_db).await?;
+    info!("Loaded {} products into the handler catalog", products.len());
+    app_config.handler_registry.set_catalog(products);
+    let coupons = catalog::load_coupons(&app_db).await?;
+    info!("Loaded {} coupons into the handler catalog", coupons.len());
+    app_config.handler_registry.set_coupons(coupons);
+    app_config.handler_registry.set_db(app_db.clone());
+
+    // Verify the database, orchestration, and template handlers before serving
+    let startup_config = StartupConfig::from_env();
+    let report = startup_checks(
+        &app_db,
+        &app_config.orchestration,
+        &app_config.handler_registry,
+        &startup_config.template_dir,
+    )
+    .await;
+    report.log();
+    report.enforce(startup_config.strict)?;
+
+    // Periodically fail (or resubmit) rows whose task was never submitted
+    let sweeper_config = SweeperConfig::from_env().map_err(anyhow::Error::msg)?;
+    if sweeper::spawn(app_db.clone(), app_config.orchestration.clone(), sweeper_config.clone())
+        .is_some()
+    {
+        info!(
+            "Stale row sweeper started (interval {:?}, max pending age {:?}, resubmit {})",
+            sweeper_config.interval, sweeper_config.max_pending_age, sweeper_config.resubmit
+        );
+    }
+
+    // Periodically sync processing rows with their task's status
+    let reconciler_config = ReconcilerConfig::from_env().map_err(anyhow::Error::msg)?;
+    if reconciler::spawn(
+        app_db.clone(),
+        app_config.orchestration.clone(),
+        reconciler_config.clone(),
+    )
+    .is_some()
+    {
+        info!(
+            "Reconciler started (interval {:?}, concurrency {}, max {} rows per run)",
+            reconciler_config.interval,
+            reconciler_config.concurrency,
+            reconciler_config.max_rows_per_run
+        );
+    }
+
+    // Periodically archive completed rows past the retention period
+    let archiver_config = ArchiverConfig::from_env().map_err(anyhow::Error::msg)?;
+    if archiver::spawn(app_db.clone(), archiver_config.clone()).is_some() {
+        info!(
+            "Archiver started (interval {:?}, retention {:?})",
+            archiver_config.interval, archiver_config.retention
+        );
+    }
+
+    // Per-handler outcome counts, served at /debug/handlers
+    let mut callbacks = CallbackChain::new().with(Arc::new(MetricsCallback::new(
+        app_config.handler_metrics.clone(),
+    )));
+    // PERSIST_STEP_RESULTS writes every step result to the step_results table
+    if step_results::persistence_enabled_from_env() {
+        info!("Persisting step results to the application database");
+        callbacks = callbacks.with(Arc::new(StepResultRecorder::new(app_db.clone())));
+    }
+    // Permanent failures are always recorded in the failed_steps table
+    callbacks = callbacks.with(Arc::new(DeadLetterCallback::new(app_db.clone())));
+    let callback: Arc<dyn PostHandlerCallback> = Arc::new(with_dead_letter_alerts(callbacks));
+    // AppConfig::from_env built the registry; the admin routes share it.
+    let shutdown = ShutdownToken::new();
+    let worker = start_worker(app_config.handler_registry.clone(), callback, &shutdown).await?;
+
+    if !app_config.admin_auth.is_enabled() {
+        warn!("ADMIN_TOKEN is not set; the /admin routes are unauthenticated");
+    }
+    if app_config.security_headers.is_enabled() {
+        info!("Adding security headers to every response");
+    }
+    if app_config.cors.is_permissive() {
+        warn!("CORS_ALLOWED_ORIGINS is not set; the CORS policy is permissive");
+    } else if let Some(origins) = app_config.cors.allowed_origins() {
+        info!("Allowing cross-origin requests from {:?}", origins);
+    }
+    if app_config.webhook_auth.is_enabled() {
+        info!("Accepting signed task webhooks at /webhooks/tasks");
+    }
+
+    // Build the Axum router with all route modules
+    let app = create_app_with_config(app_db, app_config);
+
+    serve(app, &shutdown).await?;
+    worker.stopped().await;
+    Ok(())
+}
+
+/// Add the dead-letter alerter when `DEAD_LETTER_WEBHOOK_URL` is set.
+fn with_dead_letter_alerts(callbacks: CallbackChain) -> CallbackChain {
+    match AlertWebhookConfig::from_env() {
+        Some(config) => {
+            info!("Sending dead-letter alerts to {}", config.url);
+            callbacks.with(Arc::new(DeadLetterAlerter::new(config)))
+        }
+        None => callbacks,
+    }
+}
 
-    info!("Connected to application database");
+/// The running Tasker worker.
+struct Worker<H> {
+    /// Must be kept alive while the app runs.
+    _handle: H,
+    /// The dispatch service task, which drains in-flight steps on shutdown.
+    dispatch: Option<JoinHandle<()>>,
+}
 
-    // Run application-specific migrations
-    sqlx::migrate!("./migrations").run(&app_db).await?;
-    info!("Application migrations complete");
+impl<H> Worker<H> {
+    /// Wait for the dispatch service to finish draining after shutdown.
+    async fn stopped(self) {
+        if let Some(dispatch) = self.dispatch {
+            if let Err(e) = dispatch.await {
+                warn!("Handler dispatch service ended abnormally: {}", e);
+            }
+        }
+    }
+}
 
-    // Bootstrap the Tasker worker in the background.
+/// Bootstrap the Tasker worker and start handler dispatch in the background.
+///
+/// The dispatch service runs until `shutdown` is triggered, then finishes the
+/// steps it is executing (up to `SHUTDOWN_DRAIN_TIMEOUT_SECS`) before stopping.
+async fn start_worker(
+    registry: Arc<AxumHandlerRegistry>,
+    callback: Arc<dyn PostHandlerCallback>,
+    shutdown: &ShutdownToken,
+) -> anyhow::Result<Worker<impl Sized>> {
     // Web and gRPC servers are disabled in config/worker.toml because
     // Axum provides its own HTTP server.
     let mut worker_handle = tasker_worker::WorkerBootstrap::bootstrap().await?;
     info!("Tasker worker bootstrapped");
 
-    // Create handler registry and wire it into the dispatch system.
+    // Wire the handler registry into the dispatch system.
     // WorkerBootstrap only creates infrastructure (channels, actors, DB pools).
     // The application is responsible for providing a StepHandlerRegistry so the
     // dispatch service can route steps to the correct handler functions.
-    let registry = Arc::new(handler_registry::AxumHandlerRegistry::new());
+    //
+    // ENABLED_NAMESPACES (comma-separated) restricts which namespaces get handlers.
+    // Queue subscription follows the templates under TASKER_TEMPLATE_PATH, which
+    // configure_template_path has narrowed to the same namespaces.
     info!(
-        "Handler registry initialized with {} handlers",
-        registry.handler_count()
+        "Handler registry initialized with {} handlers for namespaces: {}",
+        registry.handler_count(),
+        registry.enabled_namespaces().join(", ")
     );
+    match registry.handler_timeout() {
+        Some(timeout) => info!("Steps time out after {:?}", timeout),
+        None => info!("Step timeout disabled"),
+    }
+    let disabled = registry.disabled_handlers();
+    if !disabled.is_empty() {
+        info!("Handlers disabled by policy: {}", disabled.join(", "));
+    }
 
+    let mut dispatch = None;
     if let Some(dispatch_handles) = worker_handle.take_dispatch_handles() {
+        let in_flight = registry.in_flight_steps();
         let dispatch_config = HandlerDispatchConfig::default();
         let (dispatch_service, _capacity_checker) = HandlerDispatchService::with_callback(
             dispatch_handles.dispatch_receiver,
             dispatch_handles.completion_sender,
             registry,
             dispatch_config,
-            Arc::new(NoOpCallback),
+            callback,
         );
 
-        tokio::spawn(async move {
-            dispatch_service.run().await;
-        });
+        let shutdown = shutdown.clone();
+        let drain_timeout = shutdown::drain_timeout_from_env();
+        dispatch = Some(tokio::spawn(async move {
+            let report = shutdown::run_until_shutdown(
+                dispatch_service.run(),
+                &in_flight,
+                &shutdown,
+                drain_timeout,
+            )
+            .await;
+            log_drain(report, drain_timeout);
+        }));
         info!("Handler dispatch service started");
     }
 
-    // Build the Axum router with all route modules
-    let app = create_app(app_db);
+    Ok(Worker {
+        _handle: worker_handle,
+        dispatch,
+    })
+}
+
+fn log_drain(report: Option<shutdown::DrainReport>, drain_timeout: Duration) {
+    match report {
+        None => warn!("Handler dispatch service stopped"),
+        Some(report) if report.abandoned == 0 => info!(
+            "Handler dispatch service stopped after draining {} in-flight steps",
+            report.drained
+        ),
+        Some(report) => warn!(
+            "Handler dispatch service stopped after draining {} in-flight steps; \
+             {} still running after {:?} are left for orchestration to retry",
+            report.drained, report.abandoned, drain_timeout
+        ),
+    }
+}
 
-    // Bind and serve
+/// Apply `WORKER_POLL_INTERVAL_MS` to the worker configuration and log the
+/// effective poll interval.
+///
+/// The override is applied by pointing `TASKER_CONFIG_PATH` at a rewritten
+/// copy of the configuration, so it must run before the Tokio runtime starts
+/// any threads that could read the environment concurrently.
+fn configure_poll_interval() -> anyhow::Result<()> {
+    let poll_override = worker_config::poll_interval_override_from_env().map_err(anyhow::Error::msg)?;
+    let Ok(path) = std::env::var(worker_config::CONFIG_PATH_VAR) else {
+        if poll_override.is_some() {
+            anyhow::bail!("WORKER_POLL_INTERVAL_MS requires TASKER_CONFIG_PATH to be set");
+        }
+        return Ok(());
+    };
+
+    match poll_override {
+        Some(interval_ms) => {
+            let overridden = worker_config::write_overridden_config(Path::new(&path), interval_ms)?;
+            std::env::set_var(worker_config::CONFIG_PATH_VAR, &overridden);
+            info!(
+                "Worker poll interval {}ms (WORKER_POLL_INTERVAL_MS override of {})",
+                interval_ms, path
+            );
+        }
+        None => {
+            if let Some(interval_ms) = std::fs::read_to_string(&path)
+                .ok()
+                .and_then(|config| worker_config::poll_interval_ms(&config))
+            {
+                info!("Worker poll interval {}ms (from {})", interval_ms, path);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Point `TASKER_TEMPLATE_PATH` at a copy of the template directory holding
+/// only the `ENABLED_NAMESPACES` templates, so the worker subscribes to just
+/// those namespace queues. Like [`configure_poll_interval`], this must run
+/// before the Tokio runtime starts.
+fn configure_template_path() -> anyhow::Result<()> {
+    let Some(namespaces) = enabled_namespaces_from_env().map_err(anyhow::Error::msg)? else {
+        return Ok(());
+    };
+    let template_dir = StartupConfig::from_env().template_dir;
+    let filtered = worker_config::write_namespace_templates(&template_dir, &namespaces)?;
+    std::env::set_var(worker_config::TEMPLATE_PATH_VAR, &filtered);
+    info!(
+        "Worker templates limited to the enabled namespaces ({} filtered from {})",
+        filtered.display(),
+        template_dir.display()
+    );
+    Ok(())
+}
+
+/// Bind to `PORT` (default 3000) and serve the app until Ctrl-C or SIGTERM,
+/// which also triggers `shutdown`.
+async fn serve(app: axum::Router, shutdown: &ShutdownToken) -> anyhow::Result<()> {
     let port = std::env::var("PORT").unwrap_or_else(|_| "3000".to_string());
     let bind_addr = format!("0.0.0.0:{}", port);
     let listener = tokio::net::TcpListener::bind(&bind_addr).await?;
     info!("Listening on {}", bind_addr);
 
-    axum::serve(listener, app).await?;
+    // Peer addresses key the rate limiter for clients without an API key
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown::shutdown_signal(shutdown.clone()))
+    .await?;
+    info!("HTTP server stopped");
+    Ok(())
+}
+
+/// Serve the order routes from a SQLite application database (`sqlite` feature).
+///
+/// The Postgres-only background jobs and step result persistence are skipped.
+#[cfg(feature = "sqlite")]
+async fn run_sqlite(app_db_url: &str, app_config: AppConfig) -> anyhow::Result<()> {
+    let app_db = sqlite::connect(app_db_url).await?;
+    info!("Connected to SQLite application database (order routes only)");
+
+    let callback = Arc::new(with_dead_letter_alerts(CallbackChain::new()));
+    let shutdown = ShutdownToken::new();
+    let worker = start_worker(app_config.handler_registry.clone(), callback, &shutdown).await?;
+
+    let app = sqlite::create_app(app_db, app_config.orchestration.clone(), app_config.field_aliases)
+        .layer(axum::Extension(Arc::new(app_config.initiators)))
+        .layer(axum::Extension(app_config.handler_registry))
+        .layer(axum::middleware::from_fn(rate_limit::rate_limit))
+        .layer(axum::Extension(app_config.rate_limiter))
+        .layer(axum::middleware::from_fn(set_security_headers))
+        .layer(axum::Extension(Arc::new(app_config.security_headers)))
+        .layer(app_config.cors.layer());
+    serve(app, &shutdown).await?;
+    worker.stopped().await;
     Ok(())
 }
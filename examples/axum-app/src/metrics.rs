@@ -0,0 +1,308 @@
+//! In-process metrics registry with Prometheus text exposition.
+//!
+//! Counters and histograms are keyed by metric name plus a sorted label set and
+//! live in a single process-wide registry, so HTTP middleware and background
+//! components can record into the same place that `GET /metrics` renders.
+//!
+//! ## HTTP metrics
+//!
+//! - `http_requests_total{method, route, status}` - request count
+//! - `http_request_duration_seconds{method, route}` - latency histogram
+//!
+//! `route` is the matched route pattern (e.g. `/orders/{id}`), never the raw
+//! path, so ids do not explode label cardinality.
+//!
+//! ## Handler metrics
+//!
+//! - `handler_executions_total{handler, outcome}` - steps run by the worker,
+//!   with `outcome` `success` or `failure`
+//! - `handler_duration_seconds{handler}` - step execution time histogram
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+use axum::extract::{MatchedPath, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+
+/// Default latency buckets in seconds (the Prometheus client defaults).
+const DEFAULT_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Route label used for requests that did not match any route.
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+type Labels = Vec<(String, String)>;
+
+#[derive(Debug, Clone)]
+struct Histogram {
+    /// Cumulative count per bucket upper bound, parallel to `DEFAULT_BUCKETS`.
+    buckets: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn new() -> Self {
+        Self {
+            buckets: vec![0; DEFAULT_BUCKETS.len()],
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    fn observe(&mut self, value: f64) {
+        for (bucket, bound) in self.buckets.iter_mut().zip(DEFAULT_BUCKETS) {
+            if value <= *bound {
+                *bucket += 1;
+            }
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    counters: BTreeMap<String, BTreeMap<Labels, u64>>,
+    gauges: BTreeMap<String, BTreeMap<Labels, f64>>,
+    histograms: BTreeMap<String, BTreeMap<Labels, Histogram>>,
+}
+
+/// A registry of labelled counters, gauges, and histograms.
+#[derive(Debug, Default)]
+pub struct MetricsRegistry {
+    inner: Mutex<Inner>,
+}
+
+impl MetricsRegistry {
+    /// Increment a counter by one.
+    pub fn increment_counter(&self, name: &str, labels: &[(&str, &str)]) {
+        self.increment_counter_by(name, labels, 1);
+    }
+
+    /// Increment a counter by `amount`.
+    pub fn increment_counter_by(&self, name: &str, labels: &[(&str, &str)], amount: u64) {
+        let mut inner = self.inner.lock().expect("metrics lock poisoned");
+        *inner
+            .counters
+            .entry(name.to_string())
+            .or_default()
+            .entry(to_labels(labels))
+            .or_default() += amount;
+    }
+
+    /// Set a gauge to `value`.
+    pub fn set_gauge(&self, name: &str, labels: &[(&str, &str)], value: f64) {
+        let mut inner = self.inner.lock().expect("metrics lock poisoned");
+        inner
+            .gauges
+            .entry(name.to_string())
+            .or_default()
+            .insert(to_labels(labels), value);
+    }
+
+    /// Record one observation in a histogram.
+    pub fn observe_histogram(&self, name: &str, labels: &[(&str, &str)], value: f64) {
+        let mut inner = self.inner.lock().expect("metrics lock poisoned");
+        inner
+            .histograms
+            .entry(name.to_string())
+            .or_default()
+            .entry(to_labels(labels))
+            .or_insert_with(Histogram::new)
+            .observe(value);
+    }
+
+    /// Current value of a counter (0 if it has never been incremented).
+    pub fn counter_value(&self, name: &str, labels: &[(&str, &str)]) -> u64 {
+        let inner = self.inner.lock().expect("metrics lock poisoned");
+        inner
+            .counters
+            .get(name)
+            .and_then(|series| series.get(&to_labels(labels)))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Current value of a gauge (`None` if it has never been set).
+    pub fn gauge_value(&self, name: &str, labels: &[(&str, &str)]) -> Option<f64> {
+        let inner = self.inner.lock().expect("metrics lock poisoned");
+        inner
+            .gauges
+            .get(name)
+            .and_then(|series| series.get(&to_labels(labels)))
+            .copied()
+    }
+
+    /// Number of observations recorded in a histogram.
+    pub fn histogram_count(&self, name: &str, labels: &[(&str, &str)]) -> u64 {
+        let inner = self.inner.lock().expect("metrics lock poisoned");
+        inner
+            .histograms
+            .get(name)
+            .and_then(|series| series.get(&to_labels(labels)))
+            .map(|h| h.count)
+            .unwrap_or(0)
+    }
+
+    /// Drop every counter, gauge, and histogram series.
+    ///
+    /// Exposed over HTTP only with the `test-util` feature, so tests sharing
+    /// the process-wide registry can assert absolute counts.
+    pub fn reset(&self) {
+        let mut inner = self.inner.lock().expect("metrics lock poisoned");
+        inner.counters.clear();
+        inner.gauges.clear();
+        inner.histograms.clear();
+    }
+
+    /// Render all metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let inner = self.inner.lock().expect("metrics lock poisoned");
+        let mut out = String::new();
+
+        for (name, series) in &inner.counters {
+            let _ = writeln!(out, "# TYPE {name} counter");
+            for (labels, value) in series {
+                let _ = writeln!(out, "{name}{} {value}", format_labels(labels, None));
+            }
+        }
+
+        for (name, series) in &inner.gauges {
+            let _ = writeln!(out, "# TYPE {name} gauge");
+            for (labels, value) in series {
+                let _ = writeln!(out, "{name}{} {value}", format_labels(labels, None));
+            }
+        }
+
+        for (name, series) in &inner.histograms {
+            let _ = writeln!(out, "# TYPE {name} histogram");
+            for (labels, histogram) in series {
+                for (bound, count) in DEFAULT_BUCKETS.iter().zip(&histogram.buckets) {
+                    let le = bound.to_string();
+                    let _ = writeln!(
+                        out,
+                        "{name}_bucket{} {count}",
+                        format_labels(labels, Some(&le))
+                    );
+                }
+                let _ = writeln!(
+                    out,
+                    "{name}_bucket{} {}",
+                    format_labels(labels, Some("+Inf")),
+                    histogram.count
+                );
+                let _ = writeln!(
+                    out,
+                    "{name}_sum{} {}",
+                    format_labels(labels, None),
+                    histogram.sum
+                );
+                let _ = writeln!(
+                    out,
+                    "{name}_count{} {}",
+                    format_labels(labels, None),
+                    histogram.count
+                );
+            }
+        }
+
+        out
+    }
+}
+
+fn to_labels(labels: &[(&str, &str)]) -> Labels {
+    let mut labels: Labels = labels
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    labels.sort();
+    labels
+}
+
+fn format_labels(labels: &Labels, le: Option<&str>) -> String {
+    let mut parts: Vec<String> = labels
+        .iter()
+        .map(|(k, v)| format!("{k}=\"{}\"", escape_label_value(v)))
+        .collect();
+    if let Some(le) = le {
+        parts.push(format!("le=\"{le}\""));
+    }
+
+    if parts.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", parts.join(","))
+    }
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// The process-wide metrics registry.
+pub fn registry() -> &'static MetricsRegistry {
+    static REGISTRY: OnceLock<MetricsRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(MetricsRegistry::default)
+}
+
+// ============================================================================
+// HTTP middleware
+// ============================================================================
+
+/// Middleware recording request count and latency per matched route.
+///
+/// Must be applied with `Router::layer` so routing has already run and the
+/// `MatchedPath` extension is available.
+pub async fn track_http_metrics(req: Request, next: Next) -> Response {
+    let method = req.method().to_string();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| UNMATCHED_ROUTE.to_string());
+
+    let start = Instant::now();
+    let response = next.run(req).await;
+    let elapsed = start.elapsed().as_secs_f64();
+
+    let status = response.status().as_u16().to_string();
+    let metrics = registry();
+    metrics.increment_counter(
+        "http_requests_total",
+        &[("method", &method), ("route", &route), ("status", &status)],
+    );
+    metrics.observe_histogram(
+        "http_request_duration_seconds",
+        &[("method", &method), ("route", &route)],
+        elapsed,
+    );
+
+    response
+}
+
+// ============================================================================
+// Handler metrics
+// ============================================================================
+
+/// Record one step execution by `handler` and how long it took.
+pub fn record_handler_execution(handler: &str, success: bool, elapsed_ms: i64) {
+    let outcome = if success { "success" } else { "failure" };
+    let metrics = registry();
+    metrics.increment_counter(
+        "handler_executions_total",
+        &[("handler", handler), ("outcome", outcome)],
+    );
+    metrics.observe_histogram(
+        "handler_duration_seconds",
+        &[("handler", handler)],
+        elapsed_ms.max(0) as f64 / 1000.0,
+    );
+}
@@ -4,11 +4,18 @@
 //! the application's business entities. Each model includes a task_uuid field
 //! that links the domain record to its corresponding Tasker workflow task.
 
-use chrono::NaiveDateTime;
+use chrono::{NaiveDate, NaiveDateTime};
 use serde::{Deserialize, Serialize};
 use sqlx::types::BigDecimal;
 use uuid::Uuid;
 
+use crate::cancellation::TaskCancellation;
+use crate::email::validate_email;
+use crate::error::ApiError;
+use crate::money;
+use crate::tags::Tags;
+use crate::task_status::TaskStatus;
+
 // ============================================================================
 // Database Models (sqlx::FromRow)
 // ============================================================================
@@ -22,8 +29,17 @@ pub struct Order {
     pub total: BigDecimal,
     pub status: String,
     pub task_uuid: Option<Uuid>,
+    /// Why the row reached its current status (set when swept to `failed`).
+    pub status_reason: Option<String>,
+    pub tags: serde_json::Value,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    /// When the archiver archived the completed row (`None` = active).
+    pub archived_at: Option<NaiveDateTime>,
+    /// Where the order ships (`None` for orders created before it was stored).
+    pub shipping_address: Option<serde_json::Value>,
+    /// ISO 4217 code `total` is in.
+    pub currency: String,
 }
 
 /// An analytics pipeline job tracked in the application database.
@@ -34,10 +50,15 @@ pub struct AnalyticsJob {
     pub source_config: serde_json::Value,
     pub status: String,
     pub task_uuid: Option<Uuid>,
+    /// Why the row reached its current status (set when swept to `failed`).
+    pub status_reason: Option<String>,
+    pub tags: serde_json::Value,
     pub started_at: Option<NaiveDateTime>,
     pub completed_at: Option<NaiveDateTime>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    /// When the archiver archived the completed row (`None` = active).
+    pub archived_at: Option<NaiveDateTime>,
 }
 
 /// A microservices coordination request tracked in the application database.
@@ -49,8 +70,13 @@ pub struct ServiceRequest {
     pub payload: serde_json::Value,
     pub status: String,
     pub task_uuid: Option<Uuid>,
+    /// Why the row reached its current status (set when swept to `failed`).
+    pub status_reason: Option<String>,
+    pub tags: serde_json::Value,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    /// When the archiver archived the completed row (`None` = active).
+    pub archived_at: Option<NaiveDateTime>,
 }
 
 /// A compliance check request tracked in the application database.
@@ -60,11 +86,23 @@ pub struct ComplianceCheck {
     pub check_type: String,
     pub namespace: String,
     pub ticket_id: Option<String>,
+    /// Customer the refund is for (copied from the request payload).
+    pub customer_email: Option<String>,
     pub payload: serde_json::Value,
     pub status: String,
+    /// The customer success task.
     pub task_uuid: Option<Uuid>,
+    /// The payments task submitted alongside it.
+    pub payments_task_uuid: Option<Uuid>,
+    /// Why the row reached its current status (set when swept to `failed`).
+    pub status_reason: Option<String>,
+    pub tags: serde_json::Value,
+    /// The local order this refund refers to, if `order_id` matched one.
+    pub order_ref: Option<i32>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    /// When the archiver archived the completed row (`None` = active).
+    pub archived_at: Option<NaiveDateTime>,
 }
 
 // ============================================================================
@@ -78,6 +116,74 @@ pub struct CreateOrderRequest {
     pub cart_items: Vec<CartItemInput>,
     pub payment_token: String,
     pub shipping_address: ShippingAddress,
+    /// Discount code applied by the cart validation step; an unknown or
+    /// expired code fails the order's task.
+    #[serde(default)]
+    pub coupon_code: Option<String>,
+    /// ISO 4217 code the order is priced in; defaults to USD.
+    #[serde(default)]
+    pub currency: Option<String>,
+    #[serde(default)]
+    pub tags: Tags,
+}
+
+impl CreateOrderRequest {
+    /// The order currency, uppercased.
+    pub fn currency(&self) -> String {
+        self.currency
+            .as_deref()
+            .unwrap_or(money::DEFAULT_CURRENCY)
+            .trim()
+            .to_ascii_uppercase()
+    }
+
+    /// Check the customer email, the cart, the shipping address, and the
+    /// currency, in that order, and name the first offending field.
+    ///
+    /// The cart must hold at least one item and every quantity must be
+    /// positive; SKUs are resolved against the catalog separately. The
+    /// currency must be one of [`money::SUPPORTED_CURRENCIES`].
+    pub fn validate(&self) -> Result<(), ApiError> {
+        validate_email("customer_email", &self.customer_email)?;
+
+        if self.cart_items.is_empty() {
+            return Err(ApiError::validation(
+                "cart_items",
+                "cart_items must contain at least one item",
+            ));
+        }
+        for (index, item) in self.cart_items.iter().enumerate() {
+            if item.quantity <= 0 {
+                let field = format!("cart_items[{index}].quantity");
+                return Err(ApiError::validation(
+                    field.clone(),
+                    format!("{field} must be positive, got {}", item.quantity),
+                ));
+            }
+        }
+
+        self.shipping_address.validate()?;
+
+        money::supported_currency(&self.currency())
+            .map(|_| ())
+            .map_err(|message| ApiError::validation("currency", message))
+    }
+}
+
+/// Request body for `POST /orders/batch`.
+///
+/// Orders are kept as raw JSON and parsed one at a time, so an order that does
+/// not match [`CreateOrderRequest`] fails only its own entry.
+#[derive(Debug, Deserialize)]
+pub struct CreateOrderBatchRequest {
+    pub orders: Vec<serde_json::Value>,
+}
+
+/// Request body for `PATCH /orders/{id}`. Absent fields are left unchanged.
+#[derive(Debug, Deserialize)]
+pub struct UpdateOrderRequest {
+    pub shipping_address: Option<ShippingAddress>,
+    pub customer_email: Option<String>,
 }
 
 /// A single cart item in an order creation request.
@@ -90,13 +196,41 @@ pub struct CartItemInput {
 }
 
 /// Shipping address for an order.
+///
+/// Fields are optional at the serde level so a partial address reaches
+/// [`ShippingAddress::validate`], which names the missing field, instead of
+/// failing deserialization with a generic rejection.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ShippingAddress {
-    pub street: String,
-    pub city: String,
-    pub state: String,
-    pub zip: String,
-    pub country: String,
+    pub street: Option<String>,
+    pub city: Option<String>,
+    pub state: Option<String>,
+    pub zip: Option<String>,
+    pub country: Option<String>,
+}
+
+impl ShippingAddress {
+    /// Ensure every address field is present and non-blank.
+    pub fn validate(&self) -> Result<(), ApiError> {
+        let fields = [
+            ("street", &self.street),
+            ("city", &self.city),
+            ("state", &self.state),
+            ("zip", &self.zip),
+            ("country", &self.country),
+        ];
+
+        for (name, value) in fields {
+            if value.as_deref().is_none_or(|v| v.trim().is_empty()) {
+                return Err(ApiError::validation(
+                    format!("shipping_address.{name}"),
+                    format!("shipping_address.{name} is required"),
+                ));
+            }
+        }
+
+        Ok(())
+    }
 }
 
 /// Request body for creating a new analytics pipeline job.
@@ -105,21 +239,65 @@ pub struct CreateAnalyticsJobRequest {
     pub job_name: String,
     pub sources: Vec<String>,
     pub date_range: Option<DateRange>,
+    /// Aggregate the available sources if a transform result is missing,
+    /// instead of failing the aggregate step.
+    #[serde(default)]
+    pub allow_partial: bool,
+    /// Create a new job even if an identical one (same `job_name` and
+    /// `date_range`) already exists.
+    #[serde(default)]
+    pub force: bool,
+    #[serde(default)]
+    pub tags: Tags,
 }
 
 /// Date range filter for analytics jobs.
+///
+/// Dates are kept as the client's `YYYY-MM-DD` strings for the task context;
+/// [`DateRange::validate`] checks them before anything is stored.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DateRange {
     pub start_date: String,
     pub end_date: String,
 }
 
+impl DateRange {
+    /// Parse both dates and ensure the range is not inverted.
+    pub fn validate(&self) -> Result<(NaiveDate, NaiveDate), ApiError> {
+        let start = parse_date("date_range.start_date", &self.start_date)?;
+        let end = parse_date("date_range.end_date", &self.end_date)?;
+
+        if end < start {
+            return Err(ApiError::validation(
+                "date_range",
+                format!(
+                    "date_range.end_date ({}) is before date_range.start_date ({})",
+                    end, start
+                ),
+            ));
+        }
+
+        Ok((start, end))
+    }
+}
+
+fn parse_date(field: &str, value: &str) -> Result<NaiveDate, ApiError> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").map_err(|_| {
+        ApiError::validation(
+            field,
+            format!("{} must be a YYYY-MM-DD date, got '{}'", field, value),
+        )
+    })
+}
+
 /// Request body for creating a new service request (user registration).
 #[derive(Debug, Deserialize)]
 pub struct CreateServiceRequest {
     pub user_email: String,
     pub user_name: String,
     pub plan: Option<String>,
+    #[serde(default)]
+    pub tags: Tags,
 }
 
 /// Request body for creating a new compliance check (refund processing).
@@ -131,7 +309,28 @@ pub struct CreateComplianceCheckRequest {
     pub customer_email: String,
     pub order_id: String,
     pub refund_amount: f64,
+    /// ISO 4217 code for `refund_amount`; defaults to USD.
+    #[serde(default)]
+    pub currency: Option<String>,
     pub reason: String,
+    #[serde(default)]
+    pub tags: Tags,
+}
+
+impl CreateComplianceCheckRequest {
+    /// The refund currency, uppercased.
+    pub fn currency(&self) -> String {
+        self.currency
+            .as_deref()
+            .unwrap_or(money::DEFAULT_CURRENCY)
+            .to_ascii_uppercase()
+    }
+
+    /// Ensure `refund_amount` fits the currency's minor-unit precision.
+    pub fn validate_refund_amount(&self) -> Result<(), ApiError> {
+        money::check_precision(self.refund_amount, &self.currency())
+            .map_err(|message| ApiError::validation("refund_amount", message))
+    }
 }
 
 // ============================================================================
@@ -145,6 +344,28 @@ pub struct ApiResponse<T: Serialize> {
     pub message: String,
 }
 
+/// One page of a list endpoint.
+#[derive(Debug, Serialize)]
+pub struct PageResponse<T: Serialize> {
+    pub data: Vec<T>,
+    /// Pass as `?cursor=` to fetch the next page (`null` on the last page).
+    pub next_cursor: Option<String>,
+    /// Rows matching the filters across all pages, where the endpoint counts them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<i64>,
+    pub message: String,
+}
+
+/// A welcome message sent by a completed user registration.
+#[derive(Debug, Serialize)]
+pub struct WelcomeMessage {
+    pub channel: String,
+    /// Email subject or notification title.
+    pub title: Option<String>,
+    pub template: String,
+    pub status: String,
+}
+
 /// Response for a created order.
 #[derive(Debug, Serialize)]
 pub struct OrderResponse {
@@ -153,6 +374,25 @@ pub struct OrderResponse {
     pub status: String,
     pub task_uuid: Option<Uuid>,
     pub created_at: NaiveDateTime,
+    /// The `X-Request-Id` of the request, also in the task's context.
+    pub request_id: String,
+}
+
+/// The outcome of one order in a `POST /orders/batch` request.
+#[derive(Debug, Serialize)]
+pub struct BatchOrderResult {
+    /// Position of the order in the request's `orders` array.
+    pub index: usize,
+    /// Whether the order was created and its workflow task submitted.
+    pub success: bool,
+    /// Set once the order row exists, including when its task submission failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task_uuid: Option<Uuid>,
+    /// The `error` object a single `POST /orders` would have returned.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<serde_json::Value>,
 }
 
 /// Response for a created analytics job.
@@ -163,6 +403,8 @@ pub struct AnalyticsJobResponse {
     pub status: String,
     pub task_uuid: Option<Uuid>,
     pub created_at: NaiveDateTime,
+    /// The `X-Request-Id` of the request, also in the task's context.
+    pub request_id: String,
 }
 
 /// Response for a created service request.
@@ -174,6 +416,8 @@ pub struct ServiceRequestResponse {
     pub status: String,
     pub task_uuid: Option<Uuid>,
     pub created_at: NaiveDateTime,
+    /// The `X-Request-Id` of the request, also in the task's context.
+    pub request_id: String,
 }
 
 /// Response for a created compliance check.
@@ -185,5 +429,102 @@ pub struct ComplianceCheckResponse {
     pub status: String,
     pub task_uuid: Option<Uuid>,
     pub payments_task_uuid: Option<Uuid>,
+    /// Steps in the customer success task (`None` if it was not submitted).
+    pub cs_total_steps: Option<i64>,
+    /// Steps in the payments task (`None` if it was not submitted).
+    pub payments_total_steps: Option<i64>,
+    pub order_ref: Option<i32>,
     pub created_at: NaiveDateTime,
+    /// The `X-Request-Id` of the request, also in the task's context.
+    pub request_id: String,
+}
+
+/// Response after cancelling a compliance check's workflow tasks.
+#[derive(Debug, Serialize)]
+pub struct CancellationResponse {
+    pub id: i32,
+    /// The row's status after the cancellation.
+    pub status: String,
+    /// One result per namespace task.
+    pub tasks: Vec<TaskCancellation>,
+}
+
+/// A compliance check together with its correlated local order, if any.
+#[derive(Debug, Serialize)]
+pub struct ComplianceCheckDetail {
+    #[serde(flatten)]
+    pub check: ComplianceCheck,
+    pub order: Option<Order>,
+}
+
+/// A row from a status endpoint together with its estimated completion time
+/// (see [`crate::eta`]).
+#[derive(Debug, Serialize)]
+pub struct Estimated<T> {
+    #[serde(flatten)]
+    pub row: T,
+    /// `None` unless the row is processing and enough history exists.
+    pub estimated_completion_at: Option<NaiveDateTime>,
+}
+
+/// An analytics job with its estimated completion time and live task progress.
+#[derive(Debug, Serialize)]
+pub struct AnalyticsJobProgress {
+    #[serde(flatten)]
+    pub job: Estimated<AnalyticsJob>,
+    /// The pipeline task's status and the state of each of its steps. `None`
+    /// when no task was submitted or orchestration could not be reached.
+    pub task: Option<TaskStatus>,
+}
+
+/// A compliance check with its estimated completion time and the live status
+/// of both namespace tasks.
+#[derive(Debug, Serialize)]
+pub struct ComplianceCheckProgress {
+    #[serde(flatten)]
+    pub check: Estimated<ComplianceCheckDetail>,
+    /// The combined status of both tasks (see
+    /// [`crate::task_status::aggregate_status`]); `None` until both are known.
+    pub task_status: Option<String>,
+    /// The customer success task. `None` when it was not submitted or
+    /// orchestration could not be reached.
+    pub customer_success_task: Option<TaskStatus>,
+    /// The payments task, likewise.
+    pub payments_task: Option<TaskStatus>,
+}
+
+/// One workflow-backed row belonging to a customer, from any domain table.
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct CustomerWorkflow {
+    /// `order`, `service_request`, or `compliance_check`.
+    pub domain_type: String,
+    pub id: i32,
+    pub status: String,
+    pub task_uuid: Option<Uuid>,
+    pub created_at: NaiveDateTime,
+}
+
+/// A step result persisted locally by [`crate::step_results`].
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct StepResultRecord {
+    pub task_uuid: Uuid,
+    pub step_name: String,
+    pub namespace: String,
+    pub handler_name: String,
+    pub success: bool,
+    pub result: Option<serde_json::Value>,
+    pub error_message: Option<String>,
+    pub recorded_at: NaiveDateTime,
+}
+
+/// A dead-lettered step recorded by [`crate::dead_letter::DeadLetterCallback`].
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct FailedStep {
+    pub id: i64,
+    pub task_uuid: Uuid,
+    pub step_name: String,
+    /// The failure category (see [`crate::retry::FailureCategory`]).
+    pub category: String,
+    pub error: String,
+    pub occurred_at: NaiveDateTime,
 }
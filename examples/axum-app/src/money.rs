@@ -0,0 +1,77 @@
+//! Currency amount helpers.
+//!
+//! Amounts travel through the API and task contexts as `f64` in major units
+//! (dollars, not cents). [`check_precision`] rejects amounts finer than the
+//! currency's minor unit (e.g. `10.005` USD) so sub-cent values never reach
+//! the gateway fee math. Computed amounts are rounded with [`round_cents`], or
+//! with [`round_amount`] to the minor unit of a currency other than USD.
+//!
+//! Orders may be priced in any of [`SUPPORTED_CURRENCIES`]. Amounts are not
+//! converted between currencies; the currency only labels them and sets how
+//! they are rounded (JPY has no minor unit, so ¥1,234.56 rounds to ¥1,235).
+
+/// Currency assumed when a request does not name one.
+pub const DEFAULT_CURRENCY: &str = "USD";
+
+/// Currencies orders may be priced in.
+pub const SUPPORTED_CURRENCIES: &[&str] = &["USD", "EUR", "GBP", "CAD", "AUD", "JPY"];
+
+/// The upper-case code for `currency`, if it is one of
+/// [`SUPPORTED_CURRENCIES`].
+pub fn supported_currency(currency: &str) -> Result<String, String> {
+    let code = currency.trim().to_ascii_uppercase();
+    if SUPPORTED_CURRENCIES.contains(&code.as_str()) {
+        Ok(code)
+    } else {
+        Err(format!(
+            "Unsupported currency '{}': expected one of {}",
+            currency.trim(),
+            SUPPORTED_CURRENCIES.join(", ")
+        ))
+    }
+}
+
+/// Number of decimal places in a currency's minor unit (ISO 4217).
+///
+/// Currencies not listed here use two decimal places.
+pub fn minor_units(currency: &str) -> u32 {
+    match currency.to_ascii_uppercase().as_str() {
+        "JPY" | "KRW" | "VND" | "CLP" | "ISK" | "UGX" => 0,
+        "BHD" | "IQD" | "JOD" | "KWD" | "LYD" | "OMR" | "TND" => 3,
+        _ => 2,
+    }
+}
+
+/// Round `amount` to the nearest cent (half away from zero).
+pub fn round_cents(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
+/// Round `amount` to the minor unit of `currency` (half away from zero).
+pub fn round_amount(amount: f64, currency: &str) -> f64 {
+    let scale = 10f64.powi(minor_units(currency) as i32);
+    (amount * scale).round() / scale
+}
+
+/// Half of `currency`'s minor unit: the most two amounts rounded to it may
+/// differ by while still being equal.
+pub fn amount_tolerance(currency: &str) -> f64 {
+    0.5 / 10f64.powi(minor_units(currency) as i32)
+}
+
+/// Ensure `amount` has no more decimal places than `currency` allows.
+pub fn check_precision(amount: f64, currency: &str) -> Result<(), String> {
+    let places = minor_units(currency);
+    let scaled = amount * 10f64.powi(places as i32);
+    // Tolerate binary float noise (19.99 * 100 = 1998.9999999999998)
+    if (scaled - scaled.round()).abs() > 1e-6 {
+        return Err(format!(
+            "{} has more than {} decimal place{} for {}",
+            amount,
+            places,
+            if places == 1 { "" } else { "s" },
+            currency.to_ascii_uppercase()
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,101 @@
+//! Result normalization for step handlers.
+//!
+//! A [`ResultNormalizer`] is registered alongside a handler and rewrites the
+//! handler's JSON result before it is returned to the worker, so the step
+//! result schema seen by templates and downstream steps stays stable even when
+//! the handler's internal output changes. Operations run in a fixed order:
+//! renames, then strips, then coercions.
+
+use serde_json::Value;
+
+/// Target type for [`ResultNormalizer::coerce`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Coercion {
+    /// Integers pass through; whole floats and numeric strings are converted.
+    Integer,
+    /// Numbers and booleans are rendered as strings.
+    String,
+    /// `"true"`/`"false"` strings and 0/1 integers are converted.
+    Bool,
+}
+
+/// Declarative rewrite of a handler's top-level result keys.
+#[derive(Debug, Clone, Default)]
+pub struct ResultNormalizer {
+    renames: Vec<(String, String)>,
+    strips: Vec<String>,
+    coercions: Vec<(String, Coercion)>,
+}
+
+impl ResultNormalizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rename `from` to `to` (overwriting `to` if the handler also emits it).
+    pub fn rename(mut self, from: &str, to: &str) -> Self {
+        self.renames.push((from.to_string(), to.to_string()));
+        self
+    }
+
+    /// Remove `key` from the result.
+    pub fn strip(mut self, key: &str) -> Self {
+        self.strips.push(key.to_string());
+        self
+    }
+
+    /// Coerce `key` to the given type. Missing keys are left alone.
+    pub fn coerce(mut self, key: &str, target: Coercion) -> Self {
+        self.coercions.push((key.to_string(), target));
+        self
+    }
+
+    /// Apply the normalization. Non-object results and values that cannot be
+    /// coerced are errors, since they mean the handler broke its contract.
+    pub fn apply(&self, result: Value) -> Result<Value, String> {
+        let Value::Object(mut object) = result else {
+            return Err("Cannot normalize a non-object handler result".to_string());
+        };
+
+        for (from, to) in &self.renames {
+            if let Some(value) = object.remove(from) {
+                object.insert(to.clone(), value);
+            }
+        }
+
+        for key in &self.strips {
+            object.remove(key);
+        }
+
+        for (key, target) in &self.coercions {
+            if let Some(value) = object.get_mut(key) {
+                *value = coerce(value, *target)
+                    .ok_or_else(|| format!("Cannot coerce result field '{key}' ({value}) to {target:?}"))?;
+            }
+        }
+
+        Ok(Value::Object(object))
+    }
+}
+
+fn coerce(value: &Value, target: Coercion) -> Option<Value> {
+    match (target, value) {
+        (Coercion::Integer, Value::Number(n)) if n.is_i64() => Some(value.clone()),
+        (Coercion::Integer, Value::Number(n)) => n
+            .as_f64()
+            .filter(|f| f.fract() == 0.0)
+            .map(|f| Value::from(f as i64)),
+        (Coercion::Integer, Value::String(s)) => s.trim().parse::<i64>().ok().map(Value::from),
+        (Coercion::String, Value::String(_)) => Some(value.clone()),
+        (Coercion::String, Value::Number(n)) => Some(Value::String(n.to_string())),
+        (Coercion::String, Value::Bool(b)) => Some(Value::String(b.to_string())),
+        (Coercion::Bool, Value::Bool(_)) => Some(value.clone()),
+        (Coercion::Bool, Value::String(s)) => s.trim().parse::<bool>().ok().map(Value::Bool),
+        (Coercion::Bool, Value::Number(n)) => match n.as_i64() {
+            Some(0) => Some(Value::Bool(false)),
+            Some(1) => Some(Value::Bool(true)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
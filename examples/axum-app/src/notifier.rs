@@ -0,0 +1,121 @@
+//! Outbound customer messages.
+//!
+//! `send_confirmation` (e-commerce), `send_welcome_sequence` (microservices),
+//! and `notify_customer` (payments) deliver their messages through a
+//! [`Notifier`]. The handler registry holds the notifier (see
+//! [`crate::handler_registry::AxumHandlerRegistry::set_notifier`]) and starts
+//! with the [`LoggingNotifier`], which logs each message instead of sending
+//! it and simulates two delivery failures by recipient:
+//!
+//! | Recipient contains | Result |
+//! |--------------------|--------|
+//! | `@test_bounce` | Bounced: fails permanently |
+//! | `@test_rate_limit` | Rate limited: fails as retryable |
+
+use async_trait::async_trait;
+use serde::Serialize;
+use tracing::info;
+use uuid::Uuid;
+
+/// A message to one recipient on one channel.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OutboundMessage {
+    /// `email`, `sms`, or `in_app`.
+    pub channel: String,
+    /// Email address for `email`; the user id for `sms` and `in_app`.
+    pub recipient: String,
+    pub template: String,
+    pub subject: String,
+}
+
+/// A message the backend accepted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MessageReceipt {
+    pub message_id: String,
+    /// `sent`, or `delivered` once the backend confirms delivery.
+    pub status: String,
+}
+
+/// Why a message was not accepted. The messages follow
+/// [`crate::retry::FailureCategory::classify`], so a step failing with
+/// `Unavailable` is retried and one failing with `Rejected` is not.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum NotifyError {
+    /// The recipient cannot receive the message (e.g. the address bounced).
+    #[error("{0}")]
+    Rejected(String),
+    /// The backend is down or throttling; the message may go through later.
+    #[error("{0} (retryable)")]
+    Unavailable(String),
+}
+
+/// Delivers customer messages.
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    async fn send(&self, msg: OutboundMessage) -> Result<MessageReceipt, NotifyError>;
+
+    /// Backend name, for logging.
+    fn name(&self) -> &str;
+}
+
+/// Send `msg` from a synchronous handler.
+///
+/// Handlers run on the blocking thread pool (`spawn_blocking`), where the send
+/// is driven by the surrounding Tokio runtime, so notifiers that use Tokio I/O
+/// work and no runtime worker thread is blocked. Outside any runtime (e.g. in
+/// plain unit tests) the send runs on the current thread.
+///
+/// # Panics
+///
+/// If called from an async task, since blocking there would stall the
+/// runtime; run the handler with `spawn_blocking` instead.
+pub fn send_blocking(
+    notifier: &dyn Notifier,
+    msg: OutboundMessage,
+) -> Result<MessageReceipt, NotifyError> {
+    match tokio::runtime::Handle::try_current() {
+        Ok(runtime) => runtime.block_on(notifier.send(msg)),
+        Err(_) => futures::executor::block_on(notifier.send(msg)),
+    }
+}
+
+/// Logs messages instead of sending them (see the module docs).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LoggingNotifier;
+
+#[async_trait]
+impl Notifier for LoggingNotifier {
+    async fn send(&self, msg: OutboundMessage) -> Result<MessageReceipt, NotifyError> {
+        if msg.recipient.contains("@test_bounce") {
+            return Err(NotifyError::Rejected(format!(
+                "Customer {} bounced",
+                msg.channel
+            )));
+        }
+        if msg.recipient.contains("@test_rate_limit") {
+            return Err(NotifyError::Unavailable(format!(
+                "{} service rate limited",
+                msg.channel
+            )));
+        }
+
+        let message_id = format!("msg_{}", &Uuid::new_v4().simple().to_string()[..12]);
+        info!(
+            "{} message {} ({}) to {}: {}",
+            msg.channel, message_id, msg.template, msg.recipient, msg.subject
+        );
+        let status = if msg.channel == "in_app" {
+            "delivered"
+        } else {
+            "sent"
+        };
+        Ok(MessageReceipt {
+            message_id,
+            status: status.to_string(),
+        })
+    }
+
+    fn name(&self) -> &str {
+        "logging"
+    }
+}
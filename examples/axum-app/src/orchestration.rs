@@ -0,0 +1,543 @@
+//! Client for the Tasker orchestration REST API.
+//!
+//! Holds a single `reqwest::Client` and the base URL resolved from
+//! `ORCHESTRATION_URL`, so route handlers and background jobs can submit tasks
+//! without building a new HTTP client per call. `create_app` installs the
+//! client from [`AppConfig`](crate::config::AppConfig) as a request extension.
+//!
+//! Every request carries the `X-API-Key` header from `TASKER_API_KEY`
+//! (default [`DEFAULT_API_KEY`], matching the development orchestration
+//! config), so task creation works when orchestration requires auth.
+//!
+//! Submissions and reads have separate timeouts
+//! (`ORCHESTRATION_SUBMIT_TIMEOUT_SECS`, `ORCHESTRATION_READ_TIMEOUT_SECS`):
+//! a submission should fail fast, while a status read may legitimately wait
+//! longer.
+//!
+//! Failures are reported as an [`OrchestrationError`], which separates network
+//! failures, error statuses, and responses in an unexpected shape.
+//!
+//! A submission that could not connect or got a 5xx is retried up to
+//! `ORCHESTRATION_SUBMIT_ATTEMPTS` times in total (default 3), with
+//! exponential backoff from `ORCHESTRATION_SUBMIT_RETRY_BASE_MS` (default 100)
+//! and jitter. Timeouts after the request was sent are not retried, since
+//! orchestration may already have created the task, and neither are 4xx
+//! responses. Each retry increments `orchestration_submit_retries_total`.
+//!
+//! Calls go through a [`CircuitBreaker`]. After
+//! `ORCHESTRATION_BREAKER_THRESHOLD` consecutive outage failures (network
+//! errors and 5xx responses; default 5, `0` disables the breaker) calls fail
+//! fast with [`OrchestrationError::CircuitOpen`] for
+//! `ORCHESTRATION_BREAKER_COOLDOWN_SECS`; then one trial call decides whether
+//! the breaker closes again. Clients built
+//! with [`OrchestrationClient::from_env`] share one breaker, so an outage seen
+//! by one request is seen by all. The breaker is reported as the
+//! `orchestration_circuit_open` gauge, `orchestration_circuit_rejections_total`,
+//! and in `GET /admin/config`.
+
+use std::future::Future;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use reqwest::{Method, StatusCode};
+use serde::Serialize;
+use serde_json::Value;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::circuit_breaker::{CircuitBreaker, CircuitBreakerStatus};
+use crate::env::{env_nonzero, env_parse, env_secs};
+use crate::metrics;
+use crate::retry::jittered_delay;
+
+/// Default orchestration base URL when `ORCHESTRATION_URL` is unset.
+const DEFAULT_ORCHESTRATION_URL: &str = "http://localhost:8080";
+
+/// Default API key when `TASKER_API_KEY` is unset: the development key the
+/// docker-compose orchestration service accepts.
+pub const DEFAULT_API_KEY: &str = "test-api-key-full-access";
+
+/// Header carrying the API key on orchestration requests.
+pub const API_KEY_HEADER: &str = "X-API-Key";
+
+/// Default timeout for `POST /v1/tasks`.
+pub const DEFAULT_SUBMIT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Default timeout for task reads (`GET /v1/tasks/{uuid}`).
+pub const DEFAULT_READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default attempts for `POST /v1/tasks`, counting the first.
+pub const DEFAULT_SUBMIT_ATTEMPTS: u32 = 3;
+
+/// Default delay before the first submission retry; later retries double it.
+pub const DEFAULT_SUBMIT_RETRY_BASE: Duration = Duration::from_millis(100);
+
+/// Default consecutive outage failures that open the circuit breaker.
+pub const DEFAULT_BREAKER_THRESHOLD: u32 = 5;
+
+/// Default time the open circuit breaker rejects calls.
+pub const DEFAULT_BREAKER_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Longest response body (in characters) kept in an [`OrchestrationError`].
+const MAX_ERROR_BODY_CHARS: usize = 512;
+
+/// Why an orchestration call failed.
+#[derive(Debug, thiserror::Error)]
+pub enum OrchestrationError {
+    /// The request was not sent or the response not read (connection
+    /// refused, timeout, ...).
+    #[error("orchestration request failed: {0}")]
+    Request(#[from] reqwest::Error),
+
+    /// Orchestration answered with a non-success status.
+    #[error("orchestration returned {status}: {body}")]
+    Status { status: StatusCode, body: String },
+
+    /// Orchestration answered, but not in the expected shape - usually a
+    /// protocol or version mismatch (e.g. `id` instead of `task_uuid`).
+    #[error("malformed orchestration response ({status}): {reason}; body: {body}")]
+    MalformedResponse {
+        status: StatusCode,
+        reason: String,
+        /// The response body, truncated.
+        body: String,
+    },
+
+    /// The circuit breaker is open after repeated failures; no request was
+    /// sent.
+    #[error("orchestration circuit breaker is open")]
+    CircuitOpen,
+}
+
+impl OrchestrationError {
+    /// Whether the error suggests orchestration is down or unreachable (as
+    /// opposed to answering a bad request), so it counts towards opening the
+    /// circuit breaker.
+    pub fn is_outage(&self) -> bool {
+        match self {
+            Self::Request(_) => true,
+            Self::Status { status, .. } => status.is_server_error(),
+            Self::MalformedResponse { .. } | Self::CircuitOpen => false,
+        }
+    }
+
+    /// Whether a failed submission can safely be sent again: the connection
+    /// failed, or orchestration answered with a 5xx. A timeout once the
+    /// request was sent is not retryable, since the task may have been created
+    /// anyway.
+    pub fn is_retryable_submission(&self) -> bool {
+        match self {
+            Self::Request(e) => e.is_connect(),
+            Self::Status { status, .. } => status.is_server_error(),
+            Self::MalformedResponse { .. } | Self::CircuitOpen => false,
+        }
+    }
+
+    fn malformed(status: StatusCode, reason: impl Into<String>, body: &str) -> Self {
+        Self::MalformedResponse {
+            status,
+            reason: reason.into(),
+            body: truncate_body(body),
+        }
+    }
+}
+
+/// A task accepted by `POST /v1/tasks`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubmittedTask {
+    pub task_uuid: Uuid,
+    /// `step_count` from the creation response, if present.
+    pub total_steps: Option<i64>,
+}
+
+/// The client's effective settings, as reported by `GET /admin/config`.
+#[derive(Debug, Clone, Serialize)]
+pub struct OrchestrationSettings {
+    pub base_url: String,
+    pub submit_timeout_secs: u64,
+    pub read_timeout_secs: u64,
+    pub submit_attempts: u32,
+    pub submit_retry_base_ms: u64,
+    pub circuit_breaker: CircuitBreakerStatus,
+}
+
+#[derive(Debug, Clone)]
+pub struct OrchestrationClient {
+    http: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+    submit_timeout: Duration,
+    read_timeout: Duration,
+    submit_attempts: u32,
+    submit_retry_base: Duration,
+    breaker: Arc<CircuitBreaker>,
+}
+
+impl OrchestrationClient {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            api_key: None,
+            submit_timeout: DEFAULT_SUBMIT_TIMEOUT,
+            read_timeout: DEFAULT_READ_TIMEOUT,
+            submit_attempts: DEFAULT_SUBMIT_ATTEMPTS,
+            submit_retry_base: DEFAULT_SUBMIT_RETRY_BASE,
+            breaker: Arc::new(CircuitBreaker::new(
+                DEFAULT_BREAKER_THRESHOLD,
+                DEFAULT_BREAKER_COOLDOWN,
+            )),
+        }
+    }
+
+    /// Build a client for `ORCHESTRATION_URL` authenticated with
+    /// `TASKER_API_KEY`, keeping the default timeouts, retries, and breaker.
+    pub fn from_env_endpoint() -> Self {
+        Self::new(
+            std::env::var("ORCHESTRATION_URL")
+                .unwrap_or_else(|_| DEFAULT_ORCHESTRATION_URL.to_string()),
+        )
+        .with_api_key(
+            std::env::var("TASKER_API_KEY").unwrap_or_else(|_| DEFAULT_API_KEY.to_string()),
+        )
+    }
+
+    /// Build a client from the `ORCHESTRATION_URL`, `TASKER_API_KEY`,
+    /// `ORCHESTRATION_SUBMIT_TIMEOUT_SECS`, `ORCHESTRATION_READ_TIMEOUT_SECS`,
+    /// `ORCHESTRATION_SUBMIT_ATTEMPTS`, and `ORCHESTRATION_SUBMIT_RETRY_BASE_MS`
+    /// env vars, using the process-wide circuit breaker.
+    ///
+    /// Fails on an unparsable value or a zero timeout.
+    pub fn from_env() -> Result<Self, String> {
+        Ok(Self::from_env_endpoint()
+            .with_submit_timeout(
+                env_nonzero("ORCHESTRATION_SUBMIT_TIMEOUT_SECS")?
+                    .map(Duration::from_secs)
+                    .unwrap_or(DEFAULT_SUBMIT_TIMEOUT),
+            )
+            .with_read_timeout(
+                env_nonzero("ORCHESTRATION_READ_TIMEOUT_SECS")?
+                    .map(Duration::from_secs)
+                    .unwrap_or(DEFAULT_READ_TIMEOUT),
+            )
+            .with_submit_retries(
+                env_parse("ORCHESTRATION_SUBMIT_ATTEMPTS")?.unwrap_or(DEFAULT_SUBMIT_ATTEMPTS),
+                env_parse("ORCHESTRATION_SUBMIT_RETRY_BASE_MS")?
+                    .map(Duration::from_millis)
+                    .unwrap_or(DEFAULT_SUBMIT_RETRY_BASE),
+            )
+            .with_shared_breaker(shared_breaker()?))
+    }
+
+    /// Send `key` as the `X-API-Key` header on every request. An empty key
+    /// sends no header.
+    pub fn with_api_key(mut self, key: impl Into<String>) -> Self {
+        let key = key.into();
+        self.api_key = (!key.trim().is_empty()).then_some(key);
+        self
+    }
+
+    /// Timeout applied to task submissions.
+    pub fn with_submit_timeout(mut self, timeout: Duration) -> Self {
+        self.submit_timeout = timeout;
+        self
+    }
+
+    /// Timeout applied to task reads.
+    pub fn with_read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = timeout;
+        self
+    }
+
+    /// Make up to `attempts` submissions per task (at least one), waiting
+    /// about `base`, then twice that, and so on between them.
+    pub fn with_submit_retries(mut self, attempts: u32, base: Duration) -> Self {
+        self.submit_attempts = attempts.max(1);
+        self.submit_retry_base = base;
+        self
+    }
+
+    /// Use a circuit breaker of its own that opens after `failure_threshold`
+    /// consecutive outage failures and rejects calls for `cooldown`.
+    pub fn with_circuit_breaker(self, failure_threshold: u32, cooldown: Duration) -> Self {
+        self.with_shared_breaker(Arc::new(CircuitBreaker::new(failure_threshold, cooldown)))
+    }
+
+    fn with_shared_breaker(mut self, breaker: Arc<CircuitBreaker>) -> Self {
+        self.breaker = breaker;
+        self
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn circuit_breaker(&self) -> &CircuitBreaker {
+        &self.breaker
+    }
+
+    pub fn settings(&self) -> OrchestrationSettings {
+        OrchestrationSettings {
+            base_url: self.base_url.clone(),
+            submit_timeout_secs: self.submit_timeout.as_secs(),
+            read_timeout_secs: self.read_timeout.as_secs(),
+            submit_attempts: self.submit_attempts,
+            submit_retry_base_ms: self.submit_retry_base.as_millis() as u64,
+            circuit_breaker: self.breaker.status(),
+        }
+    }
+
+    /// Create a task via `POST /v1/tasks` and return its UUID.
+    pub async fn create_task(&self, payload: &Value) -> Result<Uuid, OrchestrationError> {
+        Ok(self.submit_task(payload).await?.task_uuid)
+    }
+
+    /// Create a task via `POST /v1/tasks`, keeping the step count from the
+    /// creation response when orchestration includes one.
+    ///
+    /// Retryable failures (see [`OrchestrationError::is_retryable_submission`])
+    /// are retried with backoff until the configured attempts are used up.
+    pub async fn submit_task(&self, payload: &Value) -> Result<SubmittedTask, OrchestrationError> {
+        let mut attempt = 1;
+        loop {
+            let result = self
+                .guarded(async {
+                    let response = self
+                        .request(Method::POST, format!("{}/v1/tasks", self.base_url))
+                        .timeout(self.submit_timeout)
+                        .json(payload)
+                        .send()
+                        .await?;
+
+                    parse_submission(response).await
+                })
+                .await;
+
+            match result {
+                Err(e) if attempt < self.submit_attempts && e.is_retryable_submission() => {
+                    let delay = jittered_delay(self.submit_retry_base, attempt);
+                    warn!(
+                        "Task submission attempt {}/{} failed, retrying in {:?}: {}",
+                        attempt, self.submit_attempts, delay, e
+                    );
+                    metrics::registry()
+                        .increment_counter("orchestration_submit_retries_total", &[]);
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Number of steps in a submitted task: from the creation response, or
+    /// from a follow-up `GET /v1/tasks/{uuid}` when the response omitted it.
+    pub async fn total_steps(&self, task: &SubmittedTask) -> Result<i64, OrchestrationError> {
+        if let Some(total_steps) = task.total_steps {
+            return Ok(total_steps);
+        }
+
+        let fetched = self.get_task(task.task_uuid).await?;
+        fetched["total_steps"].as_i64().ok_or_else(|| {
+            OrchestrationError::malformed(
+                StatusCode::OK,
+                "missing total_steps",
+                &fetched.to_string(),
+            )
+        })
+    }
+
+    /// Check orchestration's `GET /health`, failing fast on a stalled server.
+    pub async fn health(&self) -> Result<(), OrchestrationError> {
+        self.guarded(async {
+            let response = self
+                .request(Method::GET, format!("{}/health", self.base_url))
+                .timeout(self.submit_timeout)
+                .send()
+                .await?;
+
+            read_success(response).await?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Fetch a task via `GET /v1/tasks/{uuid}`.
+    pub async fn get_task(&self, task_uuid: Uuid) -> Result<Value, OrchestrationError> {
+        self.guarded(async {
+            let response = self
+                .request(Method::GET, format!("{}/v1/tasks/{}", self.base_url, task_uuid))
+                .timeout(self.read_timeout)
+                .send()
+                .await?;
+
+            let (status, text) = read_success(response).await?;
+            serde_json::from_str(&text).map_err(|e| {
+                OrchestrationError::malformed(status, format!("invalid JSON: {e}"), &text)
+            })
+        })
+        .await
+    }
+
+    /// Cancel a task via `DELETE /v1/tasks/{uuid}`.
+    pub async fn cancel_task(&self, task_uuid: Uuid) -> Result<(), OrchestrationError> {
+        self.guarded(async {
+            let response = self
+                .request(Method::DELETE, format!("{}/v1/tasks/{}", self.base_url, task_uuid))
+                .timeout(self.submit_timeout)
+                .send()
+                .await?;
+
+            read_success(response).await?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Retry a task's failed steps via `POST /v1/tasks/{uuid}/retry`.
+    ///
+    /// Orchestration answers 409 if the task has no failed steps to retry.
+    pub async fn retry_task(&self, task_uuid: Uuid) -> Result<(), OrchestrationError> {
+        self.guarded(async {
+            let response = self
+                .request(
+                    Method::POST,
+                    format!("{}/v1/tasks/{}/retry", self.base_url, task_uuid),
+                )
+                .timeout(self.submit_timeout)
+                .send()
+                .await?;
+
+            read_success(response).await?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Merge `context` into a task's context via `PATCH /v1/tasks/{uuid}`.
+    ///
+    /// Steps that have not run yet see the updated values; orchestration
+    /// answers 409 if the task can no longer be changed.
+    pub async fn update_task_context(
+        &self,
+        task_uuid: Uuid,
+        context: &Value,
+    ) -> Result<(), OrchestrationError> {
+        self.guarded(async {
+            let response = self
+                .request(Method::PATCH, format!("{}/v1/tasks/{}", self.base_url, task_uuid))
+                .timeout(self.submit_timeout)
+                .json(&serde_json::json!({ "context": context }))
+                .send()
+                .await?;
+
+            read_success(response).await?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Start a request, attaching the API key if one is configured.
+    fn request(&self, method: Method, url: String) -> reqwest::RequestBuilder {
+        let request = self.http.request(method, url);
+        match &self.api_key {
+            Some(key) => request.header(API_KEY_HEADER, key),
+            None => request,
+        }
+    }
+
+    /// Run a call through the circuit breaker.
+    async fn guarded<T>(
+        &self,
+        call: impl Future<Output = Result<T, OrchestrationError>>,
+    ) -> Result<T, OrchestrationError> {
+        if !self.breaker.allow() {
+            metrics::registry().increment_counter("orchestration_circuit_rejections_total", &[]);
+            return Err(OrchestrationError::CircuitOpen);
+        }
+
+        let result = call.await;
+        match &result {
+            Err(e) if e.is_outage() => {
+                if self.breaker.record_failure() {
+                    warn!(
+                        "Orchestration circuit breaker opened after {} consecutive failures; \
+                         failing fast for {:?}",
+                        self.breaker.failure_threshold(),
+                        self.breaker.cooldown()
+                    );
+                    metrics::registry().set_gauge("orchestration_circuit_open", &[], 1.0);
+                }
+            }
+            _ => {
+                if self.breaker.record_success() {
+                    info!("Orchestration circuit breaker closed");
+                    metrics::registry().set_gauge("orchestration_circuit_open", &[], 0.0);
+                }
+            }
+        }
+        result
+    }
+}
+
+/// The circuit breaker shared by clients built with
+/// [`OrchestrationClient::from_env`], configured from
+/// `ORCHESTRATION_BREAKER_THRESHOLD` (0 disables it) and
+/// `ORCHESTRATION_BREAKER_COOLDOWN_SECS`.
+fn shared_breaker() -> Result<Arc<CircuitBreaker>, String> {
+    static BREAKER: OnceLock<Arc<CircuitBreaker>> = OnceLock::new();
+    if let Some(breaker) = BREAKER.get() {
+        return Ok(breaker.clone());
+    }
+    let threshold =
+        env_parse::<u32>("ORCHESTRATION_BREAKER_THRESHOLD")?.unwrap_or(DEFAULT_BREAKER_THRESHOLD);
+    let breaker = Arc::new(CircuitBreaker::new(
+        if threshold == 0 { u32::MAX } else { threshold },
+        env_secs("ORCHESTRATION_BREAKER_COOLDOWN_SECS")?.unwrap_or(DEFAULT_BREAKER_COOLDOWN),
+    ));
+    Ok(BREAKER.get_or_init(|| breaker).clone())
+}
+
+/// Parse a `POST /v1/tasks` response into the created task.
+pub async fn parse_submission(
+    response: reqwest::Response,
+) -> Result<SubmittedTask, OrchestrationError> {
+    let (status, text) = read_success(response).await?;
+    let body: Value = serde_json::from_str(&text)
+        .map_err(|e| OrchestrationError::malformed(status, format!("invalid JSON: {e}"), &text))?;
+
+    let task_uuid = body["task_uuid"]
+        .as_str()
+        .ok_or_else(|| OrchestrationError::malformed(status, "missing task_uuid", &text))?;
+    let task_uuid = Uuid::parse_str(task_uuid).map_err(|e| {
+        OrchestrationError::malformed(status, format!("invalid task_uuid: {e}"), &text)
+    })?;
+
+    Ok(SubmittedTask {
+        task_uuid,
+        total_steps: body["step_count"].as_i64(),
+    })
+}
+
+/// Read a response body, turning a non-success status into an error.
+async fn read_success(
+    response: reqwest::Response,
+) -> Result<(StatusCode, String), OrchestrationError> {
+    let status = response.status();
+    let text = response.text().await?;
+    if !status.is_success() {
+        return Err(OrchestrationError::Status {
+            status,
+            body: truncate_body(&text),
+        });
+    }
+    Ok((status, text))
+}
+
+/// Shorten a response body for inclusion in an error.
+fn truncate_body(body: &str) -> String {
+    match body.char_indices().nth(MAX_ERROR_BODY_CHARS) {
+        Some((end, _)) => format!("{}...", &body[..end]),
+        None => body.to_string(),
+    }
+}
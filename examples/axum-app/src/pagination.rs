@@ -0,0 +1,143 @@
+//! Cursor pagination for the list endpoints.
+//!
+//! List endpoints return rows newest first, ordered by `(created_at, id)`.
+//! Each page carries a `next_cursor`: an opaque token encoding the last row's
+//! `(created_at, id)`. Passing it back as `?cursor=` continues strictly after
+//! that row, so rows inserted while a client is paging never shift the
+//! following pages (unlike offsets, which skip or repeat rows under concurrent
+//! inserts and make the database scan every skipped row).
+//!
+//! `?limit=` sets the page size (default [`DEFAULT_PAGE_SIZE`]); a larger
+//! limit than [`MAX_PAGE_SIZE`] is clamped to it.
+//! `?offset=` is still accepted for existing clients but is deprecated, and
+//! cannot be combined with `?cursor=`.
+
+use std::collections::HashMap;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::NaiveDateTime;
+
+use crate::error::ApiError;
+
+/// Page size when `?limit=` is absent.
+pub const DEFAULT_PAGE_SIZE: i64 = 20;
+
+/// Largest page a list endpoint returns.
+pub const MAX_PAGE_SIZE: i64 = 100;
+
+pub const CURSOR_PARAM: &str = "cursor";
+pub const LIMIT_PARAM: &str = "limit";
+/// Deprecated: use [`CURSOR_PARAM`].
+pub const OFFSET_PARAM: &str = "offset";
+
+/// Timestamp format inside a cursor (microsecond precision round-trips).
+const CURSOR_TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.f";
+
+/// The position of a row in `created_at DESC, id DESC` order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cursor {
+    pub created_at: NaiveDateTime,
+    pub id: i32,
+}
+
+impl Cursor {
+    pub fn new(created_at: NaiveDateTime, id: i32) -> Self {
+        Self { created_at, id }
+    }
+
+    /// Encode as an opaque URL-safe token.
+    pub fn encode(&self) -> String {
+        let raw = format!("{}|{}", self.created_at.format(CURSOR_TIME_FORMAT), self.id);
+        URL_SAFE_NO_PAD.encode(raw)
+    }
+
+    /// Decode a token produced by [`Cursor::encode`].
+    pub fn decode(token: &str) -> Option<Self> {
+        let raw = String::from_utf8(URL_SAFE_NO_PAD.decode(token.trim()).ok()?).ok()?;
+        let (created_at, id) = raw.split_once('|')?;
+        Some(Self {
+            created_at: NaiveDateTime::parse_from_str(created_at, CURSOR_TIME_FORMAT).ok()?,
+            id: id.parse().ok()?,
+        })
+    }
+}
+
+/// Paging parameters of a list request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageParams {
+    /// Return rows strictly after this position.
+    pub cursor: Option<Cursor>,
+    /// Deprecated offset (0 unless `?offset=` was passed).
+    pub offset: i64,
+    pub limit: i64,
+}
+
+impl PageParams {
+    /// Parse `?cursor=`, `?limit=` and `?offset=`.
+    pub fn from_query(query: &HashMap<String, String>) -> Result<Self, ApiError> {
+        let cursor = match query.get(CURSOR_PARAM) {
+            None => None,
+            Some(token) => Some(
+                Cursor::decode(token)
+                    .ok_or_else(|| ApiError::validation(CURSOR_PARAM, "cursor is not a valid page cursor"))?,
+            ),
+        };
+        let limit = match query.get(LIMIT_PARAM) {
+            None => DEFAULT_PAGE_SIZE,
+            Some(raw) => raw
+                .trim()
+                .parse::<i64>()
+                .ok()
+                .filter(|limit| *limit >= 1)
+                .map(|limit| limit.min(MAX_PAGE_SIZE))
+                .ok_or_else(|| ApiError::validation(LIMIT_PARAM, "limit must be a positive integer"))?,
+        };
+        let offset = match query.get(OFFSET_PARAM) {
+            None => 0,
+            Some(raw) => raw
+                .trim()
+                .parse::<i64>()
+                .ok()
+                .filter(|offset| *offset >= 0)
+                .ok_or_else(|| ApiError::validation(OFFSET_PARAM, "offset must be a non-negative integer"))?,
+        };
+        if cursor.is_some() && offset > 0 {
+            return Err(ApiError::validation(
+                OFFSET_PARAM,
+                "offset cannot be combined with cursor",
+            ));
+        }
+        Ok(Self {
+            cursor,
+            offset,
+            limit,
+        })
+    }
+
+    /// `created_at` of the cursor row, bound as the `(created_at, id) <` bound.
+    pub fn cursor_created_at(&self) -> Option<NaiveDateTime> {
+        self.cursor.map(|c| c.created_at)
+    }
+
+    /// `id` of the cursor row.
+    pub fn cursor_id(&self) -> Option<i32> {
+        self.cursor.map(|c| c.id)
+    }
+
+    /// Rows to fetch: one more than the page, to tell whether another follows.
+    pub fn fetch_limit(&self) -> i64 {
+        self.limit + 1
+    }
+
+    /// Trim the extra row fetched by [`PageParams::fetch_limit`] and return the
+    /// page with the cursor of the page's last row, if more rows follow.
+    pub fn finish<T>(&self, mut rows: Vec<T>, cursor: impl Fn(&T) -> Cursor) -> (Vec<T>, Option<String>) {
+        if rows.len() as i64 <= self.limit {
+            return (rows, None);
+        }
+        rows.truncate(self.limit as usize);
+        let next = rows.last().map(|row| cursor(row).encode());
+        (rows, next)
+    }
+}
@@ -0,0 +1,399 @@
+//! Per-tenant quotas and rate limits for workflow submissions.
+//!
+//! Requests name their tenant with the `X-Tenant-Id` header. A tenant with a
+//! row in `tenant_quotas` is limited to `max_workflows_per_day` submissions per
+//! day and `max_requests_per_minute` submissions per minute (`NULL` means
+//! unlimited). Tenants without a row, and requests without the header, are not
+//! limited, so single-tenant use is unaffected.
+//!
+//! Only the routes that submit a workflow task count (see
+//! [`SUBMISSION_ROUTES`]). Usage is counted in `tenant_usage` with an atomic
+//! conditional upsert before the route runs; a request that does not create
+//! anything (a 422, or a deduplicated analytics job) gives its slot back.
+//!
+//! `POST /orders/batch` takes one daily slot per order in the batch, so a batch
+//! larger than what is left of the day's quota is rejected whole. The route
+//! reports how many workflows it submitted ([`SubmittedWorkflows`]), and the
+//! slots of orders that were not created go back. The per-minute limit counts
+//! the batch as one request.
+//!
+//! A request over a limit gets a 429 with the quota details and `Retry-After`.
+//! Successful submissions under a daily quota carry `X-Quota-Remaining`. Each
+//! rejection increments `tenant_quota_rejections_total{quota}`.
+
+use std::sync::Arc;
+
+use axum::body::{to_bytes, Body};
+use axum::extract::{MatchedPath, Request};
+use axum::http::{HeaderValue, Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+use tracing::{error, warn};
+
+use crate::db::AppDb;
+use crate::error::ApiError;
+use crate::extract::{AliasScope, FieldAliases};
+use crate::metrics;
+use crate::models::CreateOrderBatchRequest;
+use crate::routes::orders::MAX_BATCH_ORDERS;
+
+/// Request header naming the tenant.
+pub const TENANT_HEADER: &str = "x-tenant-id";
+
+/// Response header with the submissions left in the tenant's daily quota.
+pub const QUOTA_REMAINING_HEADER: &str = "x-quota-remaining";
+
+/// `POST` routes that submit a workflow task and count against quotas.
+pub const SUBMISSION_ROUTES: &[&str] = &[
+    "/orders",
+    "/orders/async",
+    "/orders/batch",
+    "/analytics",
+    "/services/register",
+    "/compliance/refund",
+];
+
+/// Longest accepted tenant id (the `tenant_id` column width).
+const MAX_TENANT_ID_LEN: usize = 100;
+
+/// Largest batch body read to count its orders (axum's default body limit).
+const MAX_BATCH_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+/// Response extension set by a route that submits several workflows: how many
+/// it submitted. Daily slots taken beyond that are given back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SubmittedWorkflows(pub usize);
+
+/// A tenant's configured limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::FromRow)]
+pub struct TenantQuota {
+    pub max_workflows_per_day: Option<i32>,
+    pub max_requests_per_minute: Option<i32>,
+}
+
+/// A counting window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Window {
+    Day,
+    Minute,
+}
+
+impl Window {
+    /// `date_trunc` field, also stored as `tenant_usage.window_kind`.
+    fn kind(self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Minute => "minute",
+        }
+    }
+
+    /// Name of the limit reported in a 429 body.
+    fn quota(self) -> &'static str {
+        match self {
+            Self::Day => "workflows_per_day",
+            Self::Minute => "requests_per_minute",
+        }
+    }
+}
+
+/// Look up a tenant's limits (`None` = unlimited).
+pub async fn quota_for(pool: &AppDb, tenant_id: &str) -> sqlx::Result<Option<TenantQuota>> {
+    sqlx::query_as(
+        "SELECT max_workflows_per_day, max_requests_per_minute \
+         FROM tenant_quotas WHERE tenant_id = $1",
+    )
+    .bind(tenant_id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Take `slots` slots in the tenant's current window. Returns the usage after
+/// the increment, or `None` when fewer than `slots` are left under `limit`.
+async fn try_consume(
+    pool: &AppDb,
+    tenant_id: &str,
+    window: Window,
+    limit: i32,
+    slots: i32,
+) -> sqlx::Result<Option<i32>> {
+    if slots > limit {
+        return Ok(None);
+    }
+
+    sqlx::query_scalar(
+        r#"
+        INSERT INTO tenant_usage (tenant_id, window_kind, window_start, used)
+        VALUES ($1, $2, date_trunc($2, NOW()), $4)
+        ON CONFLICT (tenant_id, window_kind, window_start)
+        DO UPDATE SET used = tenant_usage.used + $4
+        WHERE tenant_usage.used + $4 <= $3
+        RETURNING used
+        "#,
+    )
+    .bind(tenant_id)
+    .bind(window.kind())
+    .bind(limit)
+    .bind(slots)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Give back `slots` slots taken by [`try_consume`].
+async fn release(pool: &AppDb, tenant_id: &str, window: Window, slots: i32) -> sqlx::Result<()> {
+    sqlx::query(
+        "UPDATE tenant_usage SET used = GREATEST(used - $3, 0) \
+         WHERE tenant_id = $1 AND window_kind = $2 \
+           AND window_start = date_trunc($2, NOW()) AND used > 0",
+    )
+    .bind(tenant_id)
+    .bind(window.kind())
+    .bind(slots)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Seconds until the current window ends.
+async fn retry_after_secs(pool: &AppDb, window: Window) -> sqlx::Result<u64> {
+    let secs: i64 = sqlx::query_scalar(
+        "SELECT CEIL(EXTRACT(EPOCH FROM \
+           date_trunc($1, NOW()) + ('1 ' || $1)::interval - NOW()))::bigint",
+    )
+    .bind(window.kind())
+    .fetch_one(pool)
+    .await?;
+    Ok(secs.max(1) as u64)
+}
+
+/// Slots taken for one request, released again if the request fails.
+struct Permit {
+    tenant_id: String,
+    taken: Vec<(Window, i32)>,
+    /// Submissions left today, if the tenant has a daily quota.
+    remaining: Option<i64>,
+}
+
+impl Permit {
+    async fn release(&self, pool: &AppDb) {
+        for (window, slots) in &self.taken {
+            self.release_slots(pool, *window, *slots).await;
+        }
+    }
+
+    /// Give back the daily slots of workflows that were not submitted, when
+    /// the request took more than `submitted`.
+    async fn release_unsubmitted(&mut self, pool: &AppDb, submitted: usize) {
+        let submitted = i32::try_from(submitted).unwrap_or(i32::MAX);
+        let Some((_, slots)) = self.taken.iter().find(|(window, _)| *window == Window::Day) else {
+            return;
+        };
+        let unused = slots - submitted;
+        if unused > 0 {
+            self.release_slots(pool, Window::Day, unused).await;
+            self.remaining = self
+                .remaining
+                .map(|remaining| remaining + i64::from(unused));
+        }
+    }
+
+    async fn release_slots(&self, pool: &AppDb, window: Window, slots: i32) {
+        if let Err(e) = release(pool, &self.tenant_id, window, slots).await {
+            error!(
+                "Failed to release {} quota for tenant {}: {}",
+                window.kind(),
+                self.tenant_id,
+                e
+            );
+        }
+    }
+}
+
+/// Take `workflows` daily slots and one per-minute slot, in every window the
+/// tenant is limited in. On a limit, slots already taken are released and the
+/// 429 error is returned.
+async fn acquire(
+    pool: &AppDb,
+    tenant_id: &str,
+    quota: TenantQuota,
+    workflows: i32,
+) -> Result<Permit, ApiError> {
+    let mut permit = Permit {
+        tenant_id: tenant_id.to_string(),
+        taken: Vec::new(),
+        remaining: None,
+    };
+    let limits = [
+        (Window::Day, quota.max_workflows_per_day, workflows),
+        (Window::Minute, quota.max_requests_per_minute, 1),
+    ];
+
+    for (window, limit, slots) in limits {
+        let Some(limit) = limit else { continue };
+        match try_consume(pool, tenant_id, window, limit, slots)
+            .await
+            .map_err(db_error)?
+        {
+            Some(used) => {
+                permit.taken.push((window, slots));
+                if window == Window::Day {
+                    permit.remaining = Some(i64::from(limit - used));
+                }
+            }
+            None => {
+                permit.release(pool).await;
+                warn!("Tenant {} exceeded {} limit of {}", tenant_id, window.quota(), limit);
+                metrics::registry().increment_counter(
+                    "tenant_quota_rejections_total",
+                    &[("quota", window.quota())],
+                );
+                return Err(ApiError::QuotaExceeded {
+                    tenant_id: tenant_id.to_string(),
+                    quota: window.quota(),
+                    limit: i64::from(limit),
+                    retry_after_secs: retry_after_secs(pool, window).await.map_err(db_error)?,
+                });
+            }
+        }
+    }
+
+    Ok(permit)
+}
+
+fn db_error(e: sqlx::Error) -> ApiError {
+    error!("Failed to check tenant quota: {}", e);
+    ApiError::Db(e.to_string())
+}
+
+/// The request's tenant id, if it names one. An empty or oversized header is
+/// a 422.
+fn tenant_id(req: &Request) -> Result<Option<String>, ApiError> {
+    let Some(value) = req.headers().get(TENANT_HEADER) else {
+        return Ok(None);
+    };
+    let tenant_id = value.to_str().map(str::trim).unwrap_or_default();
+    if tenant_id.is_empty() || tenant_id.len() > MAX_TENANT_ID_LEN {
+        return Err(ApiError::validation(
+            "X-Tenant-Id",
+            format!("tenant id must be 1-{} visible ASCII characters", MAX_TENANT_ID_LEN),
+        ));
+    }
+    Ok(Some(tenant_id.to_string()))
+}
+
+/// Middleware enforcing tenant quotas on [`SUBMISSION_ROUTES`].
+///
+/// Must be applied with `Router::layer` so `MatchedPath` is available, and
+/// inside the `Extension(AppDb)` layer.
+pub async fn enforce_quotas(req: Request, next: Next) -> Response {
+    let is_submission = req.method() == Method::POST
+        && req
+            .extensions()
+            .get::<MatchedPath>()
+            .is_some_and(|path| SUBMISSION_ROUTES.contains(&path.as_str()));
+    if !is_submission {
+        return next.run(req).await;
+    }
+
+    let tenant_id = match tenant_id(&req) {
+        Ok(Some(tenant_id)) => tenant_id,
+        Ok(None) => return next.run(req).await,
+        Err(e) => return e.into_response(),
+    };
+    let Some(pool) = req.extensions().get::<AppDb>().cloned() else {
+        error!("Quota middleware is missing the database pool extension");
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+
+    let quota = match quota_for(&pool, &tenant_id).await {
+        Ok(Some(quota)) => quota,
+        Ok(None) => return next.run(req).await,
+        Err(e) => return db_error(e).into_response(),
+    };
+    let (req, workflows) = match workflows_requested(req).await {
+        Ok(counted) => counted,
+        Err(e) => return e.into_response(),
+    };
+    let mut permit = match acquire(&pool, &tenant_id, quota, workflows).await {
+        Ok(permit) => permit,
+        Err(e) => {
+            let mut response = e.into_response();
+            if quota.max_workflows_per_day.is_some() {
+                let remaining = remaining_header(&pool, &tenant_id, quota).await;
+                response.headers_mut().insert(QUOTA_REMAINING_HEADER, remaining);
+            }
+            return response;
+        }
+    };
+
+    let mut response = next.run(req).await;
+    if !matches!(response.status(), StatusCode::CREATED | StatusCode::ACCEPTED) {
+        // Nothing was submitted (an error, or an existing analytics job
+        // returned with 200); the slot goes back to the tenant
+        permit.release(&pool).await;
+        return response;
+    }
+    if let Some(SubmittedWorkflows(submitted)) = response.extensions().get().copied() {
+        permit.release_unsubmitted(&pool, submitted).await;
+    }
+    if let Some(remaining) = permit.remaining {
+        response
+            .headers_mut()
+            .insert(QUOTA_REMAINING_HEADER, HeaderValue::from(remaining.max(0)));
+    }
+    response
+}
+
+/// The number of workflows a submission asks for: the orders in an
+/// `/orders/batch` body (with field aliases applied, as the route does), one
+/// for any other route. The body is buffered to count them and put back.
+///
+/// A body that cannot be counted is charged one slot and left for the route
+/// to reject. A batch over [`MAX_BATCH_ORDERS`] is charged that many, as the
+/// route rejects it anyway.
+async fn workflows_requested(req: Request) -> Result<(Request, i32), ApiError> {
+    #[derive(Deserialize)]
+    struct BatchOrders {
+        orders: Vec<IgnoredAny>,
+    }
+
+    let is_batch = req
+        .extensions()
+        .get::<MatchedPath>()
+        .is_some_and(|path| path.as_str() == "/orders/batch");
+    if !is_batch {
+        return Ok((req, 1));
+    }
+
+    let (parts, body) = req.into_parts();
+    let bytes = to_bytes(body, MAX_BATCH_BODY_BYTES)
+        .await
+        .map_err(|_| ApiError::from(StatusCode::PAYLOAD_TOO_LARGE))?;
+    let orders = serde_json::from_slice::<serde_json::Value>(&bytes)
+        .ok()
+        .and_then(|mut body| {
+            if let Some(aliases) = parts.extensions.get::<Arc<FieldAliases>>() {
+                aliases.apply(CreateOrderBatchRequest::SCOPE, &mut body);
+            }
+            serde_json::from_value::<BatchOrders>(body).ok()
+        })
+        .map_or(1, |batch| batch.orders.len().clamp(1, MAX_BATCH_ORDERS));
+    Ok((Request::from_parts(parts, Body::from(bytes)), orders as i32))
+}
+
+/// `X-Quota-Remaining` for a rejected request: what is left of today's quota.
+async fn remaining_header(pool: &AppDb, tenant_id: &str, quota: TenantQuota) -> HeaderValue {
+    let used: i32 = sqlx::query_scalar(
+        "SELECT used FROM tenant_usage \
+         WHERE tenant_id = $1 AND window_kind = 'day' AND window_start = date_trunc('day', NOW())",
+    )
+    .bind(tenant_id)
+    .fetch_optional(pool)
+    .await
+    .ok()
+    .flatten()
+    .unwrap_or(0);
+    let limit = quota.max_workflows_per_day.unwrap_or(0);
+    HeaderValue::from((limit - used).max(0))
+}
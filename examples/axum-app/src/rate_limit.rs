@@ -0,0 +1,226 @@
+//! Per-client request rate limiting.
+//!
+//! Every client gets a token bucket holding up to `burst` requests, refilled at
+//! `per_second` requests per second. A request finding its bucket empty is
+//! rejected with a 429 and a `Retry-After` header (seconds until a token is
+//! available) before it reaches the database or orchestration. This bounds
+//! each client's request rate, unlike concurrency limits, which bound the work
+//! in flight across all clients, and tenant quotas, which bound submissions.
+//!
+//! Clients are keyed by their `X-API-Key` header when it is one of the keys
+//! in `RATE_LIMIT_API_KEYS`, and otherwise by their peer IP address (the server
+//! must be run with `into_make_service_with_connect_info::<SocketAddr>()`, as
+//! `main.rs` does). An unknown key is ignored, so a client cannot get a fresh
+//! bucket by sending a new key with each request. Health probes and metric
+//! scrapes ([`EXEMPT_PATHS`]) are never limited.
+//!
+//! | Variable | Default | Meaning |
+//! |----------|---------|---------|
+//! | `RATE_LIMIT_PER_SECOND` | unset (no limit) | Requests per second each client regains |
+//! | `RATE_LIMIT_BURST` | `RATE_LIMIT_PER_SECOND`, rounded up | Requests a client may send at once |
+//! | `RATE_LIMIT_API_KEYS` | unset (key by IP only) | Comma-separated API keys that get their own bucket |
+//!
+//! Each rejected request increments `requests_rate_limited_total{source}`,
+//! where `source` is `api_key` or `ip`.
+
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::extract::{ConnectInfo, Request};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+
+use crate::error::ApiError;
+use crate::metrics;
+
+/// Header identifying an API client.
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Paths served without a rate limit.
+pub const EXEMPT_PATHS: &[&str] = &["/health", "/readiness", "/metrics"];
+
+/// Buckets tracked before full ones are dropped.
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+/// A token bucket's refill rate and capacity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    pub per_second: f64,
+    pub burst: u32,
+}
+
+/// Per-client rate limiter.
+///
+/// Clones share their buckets, so every router built from one limiter counts
+/// against the same clients.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimiter {
+    limit: Option<RateLimit>,
+    /// API keys that are bucketed by key rather than by IP.
+    api_keys: Arc<HashSet<String>>,
+    buckets: Arc<Mutex<HashMap<String, Bucket>>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    /// No limit.
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Allow each client `burst` requests at once, regaining `per_second`
+    /// requests per second.
+    pub fn new(per_second: f64, burst: u32) -> Result<Self, String> {
+        if !per_second.is_finite() || per_second <= 0.0 {
+            return Err(format!(
+                "rate limit must be a positive number of requests per second, got {}",
+                per_second
+            ));
+        }
+        if burst == 0 {
+            return Err("rate limit burst must be at least 1".to_string());
+        }
+        Ok(Self {
+            limit: Some(RateLimit { per_second, burst }),
+            api_keys: Arc::default(),
+            buckets: Arc::default(),
+        })
+    }
+
+    /// Give each of `keys` its own bucket. Requests with any other
+    /// `X-API-Key` are keyed by IP.
+    pub fn with_api_keys<I, K>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = K>,
+        K: Into<String>,
+    {
+        self.api_keys = Arc::new(
+            keys.into_iter()
+                .map(Into::into)
+                .filter(|key| !key.is_empty())
+                .collect(),
+        );
+        self
+    }
+
+    /// Read `RATE_LIMIT_PER_SECOND`, `RATE_LIMIT_BURST` and
+    /// `RATE_LIMIT_API_KEYS` (unset rate = no limit).
+    pub fn from_env() -> Result<Self, String> {
+        let Ok(raw) = std::env::var("RATE_LIMIT_PER_SECOND") else {
+            return Ok(Self::disabled());
+        };
+        let per_second: f64 = raw
+            .trim()
+            .parse()
+            .map_err(|_| format!("Invalid RATE_LIMIT_PER_SECOND '{}': expected a number", raw))?;
+        let burst = match std::env::var("RATE_LIMIT_BURST") {
+            Ok(raw) => raw.trim().parse().map_err(|_| {
+                format!(
+                    "Invalid RATE_LIMIT_BURST '{}': expected a positive integer",
+                    raw
+                )
+            })?,
+            Err(_) => per_second.ceil().max(1.0) as u32,
+        };
+        let api_keys = std::env::var("RATE_LIMIT_API_KEYS").unwrap_or_default();
+        Ok(Self::new(per_second, burst)?.with_api_keys(api_keys.split(',').map(str::trim)))
+    }
+
+    /// The configured limit, if any.
+    pub fn limit(&self) -> Option<RateLimit> {
+        self.limit
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.limit.is_some()
+    }
+
+    /// Take a token from `client`'s bucket, or return how long until one is
+    /// available.
+    pub fn check(&self, client: &str) -> Result<(), Duration> {
+        let Some(limit) = self.limit else {
+            return Ok(());
+        };
+        let now = Instant::now();
+        let capacity = f64::from(limit.burst);
+        let refill = |bucket: &Bucket| {
+            let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+            (bucket.tokens + elapsed * limit.per_second).min(capacity)
+        };
+
+        let mut buckets = self.buckets.lock().expect("rate limit lock poisoned");
+        if buckets.len() >= MAX_TRACKED_CLIENTS && !buckets.contains_key(client) {
+            // A full bucket is the same as no bucket
+            buckets.retain(|_, bucket| refill(bucket) < capacity);
+        }
+        let bucket = buckets.entry(client.to_string()).or_insert(Bucket {
+            tokens: capacity,
+            updated: now,
+        });
+        bucket.tokens = refill(bucket);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - bucket.tokens) / limit.per_second,
+            ))
+        }
+    }
+}
+
+/// Middleware rejecting a client's requests with a 429 once its bucket is
+/// empty.
+///
+/// Reads the [`RateLimiter`] extension installed by `create_app`; without it,
+/// nothing is limited.
+pub async fn rate_limit(req: Request, next: Next) -> Response {
+    if EXEMPT_PATHS.contains(&req.uri().path()) {
+        return next.run(req).await;
+    }
+    let Some(limiter) = req
+        .extensions()
+        .get::<RateLimiter>()
+        .filter(|limiter| limiter.is_enabled())
+    else {
+        return next.run(req).await;
+    };
+
+    let (source, client) = client_key(limiter, &req);
+    if let Err(wait) = limiter.check(&client) {
+        metrics::registry().increment_counter("requests_rate_limited_total", &[("source", source)]);
+        return ApiError::RateLimited {
+            retry_after_secs: wait.as_secs_f64().ceil().max(1.0) as u64,
+        }
+        .into_response();
+    }
+    next.run(req).await
+}
+
+/// The bucket key for a request: its API key if `limiter` knows it, else its
+/// peer IP address.
+fn client_key(limiter: &RateLimiter, req: &Request) -> (&'static str, String) {
+    if let Some(key) = req
+        .headers()
+        .get(API_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|key| limiter.api_keys.contains(*key))
+    {
+        return ("api_key", format!("api_key:{}", key));
+    }
+    let ip = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    ("ip", format!("ip:{}", ip))
+}
@@ -0,0 +1,314 @@
+//! Periodic reconciliation of domain row status with orchestration.
+//!
+//! A row moves to `processing` once its task is created, and nothing in the
+//! request path updates it afterwards. The reconciler fetches the task of each
+//! `processing` row from orchestration and, once the task has finished, sets
+//! the row to `completed` or `failed` (with a `status_reason`).
+//!
+//! Fetches run with bounded concurrency, and each run processes at most a
+//! fixed number of rows, least recently reconciled first, so a large backlog
+//! neither floods orchestration nor makes a single run unbounded.
+//!
+//! ## Configuration
+//!
+//! | Env var | Default | Meaning |
+//! |---------|---------|---------|
+//! | `RECONCILER_INTERVAL_SECS` | 30 | Time between runs (0 disables the reconciler) |
+//! | `RECONCILER_MIN_AGE_SECS` | 30 | Rows updated more recently than this are skipped |
+//! | `RECONCILER_CONCURRENCY` | 8 | Orchestration fetches in flight at once |
+//! | `RECONCILER_MAX_ROWS_PER_RUN` | 200 | Rows checked per run |
+//!
+//! Each checked row increments `domain_rows_reconciled_total{table, outcome}`
+//! (`outcome` is `updated`, `unchanged`, or `error`), and each run increments
+//! `reconciler_runs_total`.
+
+use std::time::Duration;
+
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::db::AppDb;
+use crate::env::{env_nonzero, env_secs};
+use crate::metrics;
+use crate::orchestration::{OrchestrationClient, OrchestrationError};
+
+/// Domain tables whose rows are reconciled.
+const RECONCILED_TABLES: &[&str] = &[
+    "orders",
+    "analytics_jobs",
+    "service_requests",
+    "compliance_checks",
+];
+
+#[derive(Debug, Clone)]
+pub struct ReconcilerConfig {
+    /// Time between runs.
+    pub interval: Duration,
+    /// Rows updated more recently than this are left for a later run.
+    pub min_age: Duration,
+    /// Maximum orchestration fetches in flight at once.
+    pub concurrency: usize,
+    /// Maximum rows checked per run.
+    pub max_rows_per_run: i64,
+}
+
+impl Default for ReconcilerConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(30),
+            min_age: Duration::from_secs(30),
+            concurrency: 8,
+            max_rows_per_run: 200,
+        }
+    }
+}
+
+impl ReconcilerConfig {
+    /// Read the reconciler configuration from the environment.
+    pub fn from_env() -> Result<Self, String> {
+        let defaults = Self::default();
+        Ok(Self {
+            interval: env_secs("RECONCILER_INTERVAL_SECS")?.unwrap_or(defaults.interval),
+            min_age: env_secs("RECONCILER_MIN_AGE_SECS")?.unwrap_or(defaults.min_age),
+            concurrency: env_nonzero("RECONCILER_CONCURRENCY")?.unwrap_or(defaults.concurrency),
+            max_rows_per_run: env_nonzero("RECONCILER_MAX_ROWS_PER_RUN")?
+                .unwrap_or(defaults.max_rows_per_run),
+        })
+    }
+}
+
+/// Outcome of a single reconciliation run.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ReconcileReport {
+    /// Rows whose task was fetched (or attempted).
+    pub checked: u64,
+    /// Rows moved to `completed` or `failed`.
+    pub updated: u64,
+    /// Rows whose task could not be fetched.
+    pub errors: u64,
+}
+
+/// A row with a task to check.
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct ProcessingRow {
+    domain_table: String,
+    id: i32,
+    task_uuid: Uuid,
+}
+
+/// The domain status for a finished task status (`None` while still running).
+pub fn domain_status(task_status: &str) -> Option<&'static str> {
+    match task_status {
+        "complete" | "resolved_manually" => Some("completed"),
+        "error" | "cancelled" => Some("failed"),
+        _ => None,
+    }
+}
+
+/// Run one reconciliation pass across every domain table.
+pub async fn reconcile_once(
+    pool: &AppDb,
+    client: &OrchestrationClient,
+    config: &ReconcilerConfig,
+) -> Result<ReconcileReport, sqlx::Error> {
+    let candidates = RECONCILED_TABLES
+        .iter()
+        .map(|table| {
+            format!(
+                "SELECT '{table}' AS domain_table, id, task_uuid, reconciled_at, updated_at \
+                 FROM {table} \
+                 WHERE status = 'processing' AND task_uuid IS NOT NULL \
+                   AND updated_at < NOW() - ($1::bigint * INTERVAL '1 second')"
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" UNION ALL ");
+
+    let rows: Vec<ProcessingRow> = sqlx::query_as(&format!(
+        "SELECT domain_table, id, task_uuid FROM ({candidates}) candidates \
+         ORDER BY reconciled_at NULLS FIRST, updated_at, id \
+         LIMIT $2"
+    ))
+    .bind(config.min_age.as_secs() as i64)
+    .bind(config.max_rows_per_run)
+    .fetch_all(pool)
+    .await?;
+
+    let fetched: Vec<(ProcessingRow, Result<serde_json::Value, OrchestrationError>)> =
+        stream::iter(rows)
+            .map(|row| async move {
+                let task = client.get_task(row.task_uuid).await;
+                (row, task)
+            })
+            .buffer_unordered(config.concurrency.max(1))
+            .collect()
+            .await;
+
+    let mut report = ReconcileReport::default();
+    for (row, task) in fetched {
+        report.checked += 1;
+        let table = row.domain_table.as_str();
+
+        let outcome = match task {
+            Ok(task) => {
+                let task_status = task["status"].as_str().unwrap_or_default();
+                match domain_status(task_status) {
+                    Some(status) => {
+                        if apply_status(pool, &row, status, task_status).await? {
+                            report.updated += 1;
+                            "updated"
+                        } else {
+                            "unchanged"
+                        }
+                    }
+                    None => {
+                        mark_reconciled(pool, &row).await?;
+                        "unchanged"
+                    }
+                }
+            }
+            Err(e) => {
+                warn!(
+                    "Reconciler could not fetch task {} for {} {}: {}",
+                    row.task_uuid, table, row.id, e
+                );
+                mark_reconciled(pool, &row).await?;
+                report.errors += 1;
+                "error"
+            }
+        };
+
+        metrics::registry().increment_counter(
+            "domain_rows_reconciled_total",
+            &[("table", table), ("outcome", outcome)],
+        );
+    }
+
+    metrics::registry().increment_counter("reconciler_runs_total", &[]);
+    Ok(report)
+}
+
+/// A domain row updated from a pushed task status (see [`apply_task_status`]).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TaskStatusUpdate {
+    pub domain_table: String,
+    pub id: i32,
+    /// The row's new status, or `None` when the task is still running or the
+    /// row had already left `processing`.
+    pub status: Option<&'static str>,
+}
+
+/// Apply a task status reported by orchestration (rather than fetched) to the
+/// domain row that submitted the task. Returns `None` when no row has the task.
+pub async fn apply_task_status(
+    pool: &AppDb,
+    task_uuid: Uuid,
+    task_status: &str,
+) -> Result<Option<TaskStatusUpdate>, sqlx::Error> {
+    let lookup = RECONCILED_TABLES
+        .iter()
+        .map(|table| {
+            format!(
+                "SELECT '{table}' AS domain_table, id, task_uuid FROM {table} WHERE task_uuid = $1"
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" UNION ALL ");
+    let row: Option<ProcessingRow> = sqlx::query_as(&format!("{lookup} LIMIT 1"))
+        .bind(task_uuid)
+        .fetch_optional(pool)
+        .await?;
+    let Some(row) = row else {
+        return Ok(None);
+    };
+
+    let status = match domain_status(task_status) {
+        Some(status) if apply_status(pool, &row, status, task_status).await? => Some(status),
+        _ => None,
+    };
+    Ok(Some(TaskStatusUpdate {
+        domain_table: row.domain_table,
+        id: row.id,
+        status,
+    }))
+}
+
+/// Move a row to its final status. Returns whether the row was updated (it may
+/// have left `processing` since it was selected).
+async fn apply_status(
+    pool: &AppDb,
+    row: &ProcessingRow,
+    status: &str,
+    task_status: &str,
+) -> Result<bool, sqlx::Error> {
+    let table = row.domain_table.as_str();
+    let reason = (status == "failed").then(|| format!("Workflow task ended with status '{task_status}'"));
+    // Analytics jobs also record when they finished
+    let completed_at = if table == "analytics_jobs" {
+        ", completed_at = NOW()"
+    } else {
+        ""
+    };
+
+    let updated = sqlx::query(&format!(
+        "UPDATE {table} SET status = $1, status_reason = $2, reconciled_at = NOW(), \
+         updated_at = NOW(){completed_at} \
+         WHERE id = $3 AND status = 'processing'"
+    ))
+    .bind(status)
+    .bind(&reason)
+    .bind(row.id)
+    .execute(pool)
+    .await?;
+
+    if updated.rows_affected() > 0 {
+        info!(
+            "Reconciler marked {} {} {} (task {} is {})",
+            table, row.id, status, row.task_uuid, task_status
+        );
+    }
+    Ok(updated.rows_affected() > 0)
+}
+
+/// Record that a row was checked, so later runs prefer other rows.
+async fn mark_reconciled(pool: &AppDb, row: &ProcessingRow) -> Result<(), sqlx::Error> {
+    sqlx::query(&format!(
+        "UPDATE {} SET reconciled_at = NOW() WHERE id = $1",
+        row.domain_table
+    ))
+    .bind(row.id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Spawn the reconciler loop. Returns `None` when the interval is zero (disabled).
+pub fn spawn(
+    pool: AppDb,
+    client: OrchestrationClient,
+    config: ReconcilerConfig,
+) -> Option<JoinHandle<()>> {
+    if config.interval.is_zero() {
+        return None;
+    }
+
+    Some(tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(config.interval);
+        loop {
+            ticker.tick().await;
+            match reconcile_once(&pool, &client, &config).await {
+                Ok(report) if report.checked > 0 => {
+                    info!(
+                        "Reconciler: {} rows checked, {} updated, {} errors",
+                        report.checked, report.updated, report.errors
+                    );
+                }
+                Ok(_) => {}
+                Err(e) => error!("Reconciler run failed: {}", e),
+            }
+        }
+    }))
+}
@@ -0,0 +1,83 @@
+//! Request ids correlating HTTP requests with the tasks they create.
+//!
+//! [`propagate_request_id`] takes the id from the request's `X-Request-Id`
+//! header, or generates a UUID when the header is missing or unusable, stores
+//! it as a [`RequestId`] extension, and echoes it in the response's
+//! `X-Request-Id`. The create routes put it in their task's context as
+//! `request_id` and in their response, so a failed workflow can be traced
+//! back to the request that started it.
+
+use std::convert::Infallible;
+
+use axum::extract::{FromRequestParts, Request};
+use axum::http::request::Parts;
+use axum::http::HeaderValue;
+use axum::middleware::Next;
+use axum::response::Response;
+use uuid::Uuid;
+
+/// Request and response header carrying the id.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longest client-supplied id that is kept; longer ones are replaced.
+pub const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// The current request's id.
+///
+/// As an extractor, reads the extension set by [`propagate_request_id`], or
+/// generates a fresh id when the middleware is not installed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    pub fn generate() -> Self {
+        Self(Uuid::new_v4().to_string())
+    }
+
+    /// A client-supplied id, if it is 1-[`MAX_REQUEST_ID_LEN`] visible ASCII
+    /// characters.
+    pub fn parse(value: &HeaderValue) -> Option<Self> {
+        let id = value.to_str().ok()?.trim();
+        let valid = !id.is_empty()
+            && id.len() <= MAX_REQUEST_ID_LEN
+            && id.bytes().all(|b| b.is_ascii_graphic());
+        valid.then(|| Self(id.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<S> FromRequestParts<S> for RequestId
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts
+            .extensions
+            .get::<RequestId>()
+            .cloned()
+            .unwrap_or_else(Self::generate))
+    }
+}
+
+/// Middleware assigning every request a [`RequestId`] and echoing it in the
+/// response's `X-Request-Id` header.
+pub async fn propagate_request_id(mut req: Request, next: Next) -> Response {
+    let request_id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(RequestId::parse)
+        .unwrap_or_else(RequestId::generate);
+    let header = HeaderValue::from_str(request_id.as_str()).ok();
+    req.extensions_mut().insert(request_id);
+
+    let mut response = next.run(req).await;
+    if let Some(header) = header {
+        response.headers_mut().insert(REQUEST_ID_HEADER, header);
+    }
+    response
+}
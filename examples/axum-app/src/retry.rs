@@ -0,0 +1,123 @@
+//! Per-handler retry policies.
+//!
+//! A [`RetryPolicy`] is declared when a handler is registered and decides, for
+//! each failure, whether orchestration should retry the step. How often and
+//! how soon a retryable step is retried is up to orchestration, from the
+//! step's `retry` block in its task template (`max_attempts`, `backoff`); the
+//! policy only classifies. The policy travels with the failed
+//! [`StepExecutionResult`] so the decision is visible in step logs.
+//!
+//! Handlers report failures as plain strings. By convention, transient failures
+//! say so in the message ("(retryable)", "will retry"); everything else is
+//! treated as permanent.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use rand::Rng;
+use serde::Serialize;
+use serde_json::Value;
+use tasker_shared::messaging::StepExecutionResult;
+use uuid::Uuid;
+
+/// Broad failure classes a policy can choose to retry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureCategory {
+    /// Temporary conditions: timeouts, locks, rate limits, pending approvals.
+    Transient,
+    /// Everything else: bad input, declined payments, broken contracts.
+    Permanent,
+}
+
+impl FailureCategory {
+    /// Classify a handler error message.
+    pub fn classify(message: &str) -> Self {
+        let message = message.to_ascii_lowercase();
+        if message.contains("(retryable)") || message.contains("will retry") {
+            Self::Transient
+        } else {
+            Self::Permanent
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Transient => "transient",
+            Self::Permanent => "permanent",
+        }
+    }
+}
+
+/// Delay before retry number `attempt` (1-based): `base * 2^(attempt - 1)`,
+/// jittered down to as little as half so concurrent retries spread out.
+pub fn jittered_delay(base: Duration, attempt: u32) -> Duration {
+    let full = base.saturating_mul(1 << (attempt.max(1) - 1).min(16));
+    let half = full / 2;
+    let jitter_range = (full - half).as_millis() as u64;
+    let jitter = rand::thread_rng().gen_range(0..=jitter_range);
+    half + Duration::from_millis(jitter)
+}
+
+/// Which of a handler's failures are retried.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RetryPolicy {
+    pub retryable: Vec<FailureCategory>,
+}
+
+impl Default for RetryPolicy {
+    /// Retry transient failures only.
+    fn default() -> Self {
+        Self {
+            retryable: vec![FailureCategory::Transient],
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries (e.g. for input validation steps).
+    pub fn never() -> Self {
+        Self {
+            retryable: Vec::new(),
+        }
+    }
+
+    /// Replace the set of retryable failure categories.
+    pub fn retry_on(mut self, categories: &[FailureCategory]) -> Self {
+        self.retryable = categories.to_vec();
+        self
+    }
+
+    /// Whether a failure of this category should be retried at all.
+    pub fn is_retryable(&self, category: FailureCategory) -> bool {
+        self.retryable.contains(&category)
+    }
+
+    /// Build the failure result for a handler error, carrying the retry
+    /// decision, the failure category, and the policy itself.
+    pub fn failure_result(
+        &self,
+        step_uuid: Uuid,
+        message: String,
+        elapsed_ms: i64,
+    ) -> StepExecutionResult {
+        let category = FailureCategory::classify(&message);
+        let context = HashMap::from([
+            ("failure_category".to_string(), Value::from(category.as_str())),
+            (
+                "retry_policy".to_string(),
+                serde_json::to_value(self).unwrap_or(Value::Null),
+            ),
+        ]);
+
+        StepExecutionResult::failure(
+            step_uuid,
+            message,
+            None,
+            Some(category.as_str().to_string()),
+            self.is_retryable(category),
+            elapsed_ms,
+            Some(context),
+        )
+    }
+}
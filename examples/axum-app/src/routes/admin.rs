@@ -0,0 +1,212 @@
+//! Admin routes.
+//!
+//! GET  /admin/config               - Effective runtime configuration (orchestration client
+//!                                     and circuit breaker state, enabled namespaces)
+//! GET  /admin/handlers             - Registered handlers and the active allow/deny policy
+//! GET  /admin/tasks/:uuid/results  - Step results persisted locally for a task
+//! POST /admin/validate-template     - Check a template's callables against the registry
+//! POST /admin/reconcile             - Reconcile stale rows with orchestration now
+//!                                     (?status=processing&older_than_secs=60&limit=200)
+//!
+//! All admin routes require `Authorization: Bearer $ADMIN_TOKEN` when
+//! `ADMIN_TOKEN` is set (see [`crate::admin_auth`]).
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::{Path, Query};
+use axum::http::StatusCode;
+use axum::routing::{get, post};
+use axum::{middleware, Extension, Json, Router};
+use serde::{Deserialize, Serialize};
+use tasker_worker::worker::handlers::StepHandlerRegistry;
+use tracing::error;
+
+use crate::admin_auth::require_admin;
+use crate::db::AppDb;
+use crate::error::ApiError;
+use crate::handler_policy::HandlerPolicy;
+use crate::handler_registry::AxumHandlerRegistry;
+use crate::models::{ApiResponse, StepResultRecord};
+use crate::orchestration::{OrchestrationClient, OrchestrationSettings};
+use crate::reconciler::{reconcile_once, ReconcileReport, ReconcilerConfig};
+use crate::routes::tasks::parse_task_uuid;
+use crate::step_results;
+use crate::templates::{template_callables, template_coverage, TemplateCoverage};
+
+/// Build the admin router.
+pub fn router() -> Router {
+    Router::new()
+        .route("/admin/config", get(get_config))
+        .route("/admin/handlers", get(get_handlers))
+        .route("/admin/tasks/{uuid}/results", get(get_task_results))
+        .route("/admin/validate-template", post(validate_template))
+        .route("/admin/reconcile", post(reconcile))
+        .route_layer(middleware::from_fn(require_admin))
+}
+
+/// Most rows a single `POST /admin/reconcile` may check.
+const MAX_RECONCILE_ROWS: i64 = 1000;
+
+#[derive(Debug, Deserialize)]
+struct ReconcileParams {
+    status: Option<String>,
+    older_than_secs: Option<u64>,
+    limit: Option<i64>,
+}
+
+/// The effective runtime configuration.
+#[derive(Debug, Serialize)]
+pub struct AdminConfig {
+    pub orchestration: OrchestrationSettings,
+    pub enabled_namespaces: Vec<&'static str>,
+}
+
+/// Report the orchestration client settings (including the live circuit
+/// breaker state) and the namespaces handlers are registered for.
+async fn get_config(
+    Extension(registry): Extension<Arc<AxumHandlerRegistry>>,
+    Extension(client): Extension<OrchestrationClient>,
+) -> Json<ApiResponse<AdminConfig>> {
+    Json(ApiResponse {
+        data: AdminConfig {
+            orchestration: client.settings(),
+            enabled_namespaces: registry.enabled_namespaces(),
+        },
+        message: "Current configuration".to_string(),
+    })
+}
+
+/// The registry's handlers and the policy that disabled some of them.
+#[derive(Debug, Serialize)]
+pub struct HandlerReport {
+    pub policy: HandlerPolicy,
+    /// Callables with a handler (including disabled stubs).
+    pub registered: Vec<String>,
+    /// Handlers the policy disabled; their steps fail with "handler disabled".
+    pub disabled: Vec<String>,
+}
+
+/// Report the registered handlers and the active allowlist/denylist.
+async fn get_handlers(
+    Extension(registry): Extension<Arc<AxumHandlerRegistry>>,
+) -> Json<ApiResponse<HandlerReport>> {
+    let report = HandlerReport {
+        policy: registry.policy().clone(),
+        registered: registry.registered_handlers(),
+        disabled: registry.disabled_handlers(),
+    };
+    Json(ApiResponse {
+        message: format!(
+            "{} handlers registered, {} disabled by policy",
+            report.registered.len(),
+            report.disabled.len()
+        ),
+        data: report,
+    })
+}
+
+/// List the step results recorded for a task.
+///
+/// Results are only recorded when `PERSIST_STEP_RESULTS` is enabled on the
+/// worker; a task with no recorded results is a 404.
+async fn get_task_results(
+    Extension(pool): Extension<AppDb>,
+    Path(task_uuid): Path<String>,
+) -> Result<Json<ApiResponse<Vec<StepResultRecord>>>, ApiError> {
+    let task_uuid = parse_task_uuid(&task_uuid)?;
+    let results = step_results::for_task(&pool, task_uuid).await?;
+
+    if results.is_empty() {
+        return Err(ApiError::NotFound);
+    }
+
+    Ok(Json(ApiResponse {
+        message: format!("{} step results for task {}", results.len(), task_uuid),
+        data: results,
+    }))
+}
+
+/// Report which of a template's step callables have registered handlers.
+///
+/// The body is a task template in YAML or JSON (JSON is valid YAML). Nothing is
+/// submitted to orchestration.
+async fn validate_template(
+    Extension(registry): Extension<Arc<AxumHandlerRegistry>>,
+    body: String,
+) -> Result<Json<ApiResponse<TemplateCoverage>>, ApiError> {
+    let template: serde_json::Value = serde_yaml::from_str(&body)
+        .map_err(|e| ApiError::validation("template", format!("invalid YAML or JSON: {e}")))?;
+    let callables = template_callables(&template)?;
+    let coverage = template_coverage(registry.as_ref(), &callables);
+
+    let name = template["name"].as_str().unwrap_or("template");
+    let message = if coverage.missing.is_empty() {
+        format!("All {} callables in {} have registered handlers", callables.len(), name)
+    } else {
+        format!(
+            "{} callables in {} have no registered handler",
+            coverage.missing.len(),
+            name
+        )
+    };
+
+    Ok(Json(ApiResponse {
+        message,
+        data: coverage,
+    }))
+}
+
+/// Run one reconciliation pass now, without waiting for the periodic reconciler.
+///
+/// Only `processing` rows are reconciled (the default `status`). Rows updated
+/// within `older_than_secs` (default `RECONCILER_MIN_AGE_SECS`) are skipped, and
+/// at most `limit` rows (default `RECONCILER_MAX_ROWS_PER_RUN`, capped at
+/// [`MAX_RECONCILE_ROWS`]) are checked.
+async fn reconcile(
+    Extension(pool): Extension<AppDb>,
+    Extension(client): Extension<OrchestrationClient>,
+    Query(params): Query<ReconcileParams>,
+) -> Result<Json<ApiResponse<ReconcileReport>>, ApiError> {
+    if let Some(status) = params.status.as_deref() {
+        if status != "processing" {
+            return Err(ApiError::validation(
+                "status",
+                format!("only 'processing' rows can be reconciled, got '{status}'"),
+            ));
+        }
+    }
+
+    let defaults = ReconcilerConfig::from_env().map_err(|e| {
+        error!("Invalid reconciler configuration: {}", e);
+        ApiError::from(StatusCode::INTERNAL_SERVER_ERROR)
+    })?;
+    let max_rows_per_run = match params.limit {
+        None => defaults.max_rows_per_run.min(MAX_RECONCILE_ROWS),
+        Some(limit) if (1..=MAX_RECONCILE_ROWS).contains(&limit) => limit,
+        Some(_) => {
+            return Err(ApiError::validation(
+                "limit",
+                format!("must be between 1 and {MAX_RECONCILE_ROWS}"),
+            ))
+        }
+    };
+    let config = ReconcilerConfig {
+        min_age: params
+            .older_than_secs
+            .map(Duration::from_secs)
+            .unwrap_or(defaults.min_age),
+        max_rows_per_run,
+        ..defaults
+    };
+
+    let report = reconcile_once(&pool, &client, &config).await?;
+
+    Ok(Json(ApiResponse {
+        message: format!(
+            "{} rows checked, {} updated, {} errors",
+            report.checked, report.updated, report.errors
+        ),
+        data: report,
+    }))
+}
@@ -1,53 +1,132 @@
 //! Data pipeline analytics routes.
 //!
+//! GET  /analytics     - List analytics jobs (filter with ?tag.<key>=<value>, ?include_archived=true, page with ?cursor=)
 //! POST /analytics     - Create a new analytics pipeline job
-//! GET  /analytics/:id - Retrieve an analytics job by ID
+//! GET  /analytics/:id - Retrieve an analytics job by ID (with its estimated completion time
+//!                       and live task status and steps)
+//! GET  /analytics/:id/task - Report the job's workflow task status and step states
+//! DELETE /analytics/:id - Cancel an analytics job and its workflow task
 
-use axum::extract::Path;
+use std::collections::HashMap;
+
+use axum::extract::{Path, Query};
 use axum::http::StatusCode;
-use axum::routing::{get, post};
+use axum::routing::get;
 use axum::{Extension, Json, Router};
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
+use crate::archiver::include_archived;
+use crate::attribution::TaskAttribution;
+use crate::cancellation::cancel_row;
 use crate::db::AppDb;
-use crate::models::{AnalyticsJob, AnalyticsJobResponse, ApiResponse, CreateAnalyticsJobRequest};
+use crate::error::ApiError;
+use crate::eta::estimated_completion_from_task;
+use crate::extract::AliasedJson;
+use crate::models::{
+    AnalyticsJob, AnalyticsJobProgress, AnalyticsJobResponse, ApiResponse,
+    CreateAnalyticsJobRequest, Estimated, PageResponse,
+};
+use crate::orchestration::OrchestrationClient;
+use crate::pagination::{Cursor, PageParams};
+use crate::request_id::RequestId;
+use crate::submission::fail_submission;
+use crate::tags::{tag_filter, validate_tags};
+use crate::task_status::{row_task_status, TaskStatus};
 
 /// Build the analytics router.
 pub fn router() -> Router {
     Router::new()
-        .route("/analytics", post(create_analytics_job))
-        .route("/analytics/{id}", get(get_analytics_job))
+        .route("/analytics", get(list_analytics_jobs).post(create_analytics_job))
+        .route(
+            "/analytics/{id}",
+            get(get_analytics_job).delete(cancel_analytics_job),
+        )
+        .route("/analytics/{id}/task", get(get_analytics_job_task))
 }
 
 /// Create a new analytics pipeline job and submit a data pipeline workflow to Tasker.
 ///
 /// The data pipeline workflow extracts data from 3 parallel sources (sales, inventory,
 /// customers), transforms each, aggregates metrics, and generates business insights.
+///
+/// Submissions are deduplicated on `job_name` and `date_range`: if an active
+/// (not failed, cancelled, or archived) job with the same key exists, it is
+/// returned with 200 and no task is submitted. `force: true` always creates a
+/// new job.
 async fn create_analytics_job(
     Extension(pool): Extension<AppDb>,
-    Json(req): Json<CreateAnalyticsJobRequest>,
-) -> Result<(StatusCode, Json<ApiResponse<AnalyticsJobResponse>>), StatusCode> {
+    Extension(client): Extension<OrchestrationClient>,
+    attribution: TaskAttribution,
+    request_id: RequestId,
+    AliasedJson(req): AliasedJson<CreateAnalyticsJobRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<AnalyticsJobResponse>>), ApiError> {
+    validate_tags(&req.tags)?;
+    let dates = req.date_range.as_ref().map(|r| r.validate()).transpose()?;
+    let dedup_key = match dates {
+        Some((start, end)) => format!("{}|{}|{}", req.job_name, start, end),
+        None => format!("{}||", req.job_name),
+    };
+
     let source_config = serde_json::json!({
         "sources": req.sources,
         "date_range": req.date_range,
     });
 
+    let mut tx = pool.begin().await?;
+
+    // Serialize submissions with the same key so concurrent duplicates see
+    // each other's rows
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+        .bind(&dedup_key)
+        .execute(&mut *tx)
+        .await?;
+
+    if !req.force {
+        let existing: Option<AnalyticsJob> = sqlx::query_as(
+            "SELECT * FROM analytics_jobs \
+             WHERE dedup_key = $1 \
+               AND status NOT IN ('failed', 'cancelled', 'partially_cancelled') \
+               AND archived_at IS NULL \
+             ORDER BY created_at DESC, id DESC LIMIT 1",
+        )
+        .bind(&dedup_key)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if let Some(job) = existing {
+            info!("Analytics job {} already exists for {}", job.id, req.job_name);
+            return Ok((
+                StatusCode::OK,
+                Json(ApiResponse {
+                    data: AnalyticsJobResponse {
+                        id: job.id,
+                        job_name: job.job_name,
+                        status: job.status,
+                        task_uuid: job.task_uuid,
+                        created_at: job.created_at,
+                        request_id: request_id.0,
+                    },
+                    message: "Analytics job already exists; pass force to re-run".to_string(),
+                }),
+            ));
+        }
+    }
+
     // Insert analytics job into application database
     let job: AnalyticsJob = sqlx::query_as(
         r#"
-        INSERT INTO analytics_jobs (job_name, source_config, status, started_at)
-        VALUES ($1, $2, 'pending', NOW())
+        INSERT INTO analytics_jobs (job_name, source_config, status, started_at, tags, dedup_key)
+        VALUES ($1, $2, 'pending', NOW(), $3, $4)
         RETURNING *
         "#,
     )
     .bind(&req.job_name)
     .bind(&source_config)
-    .fetch_one(&pool)
-    .await
-    .map_err(|e| {
-        error!("Failed to insert analytics job: {}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    .bind(sqlx::types::Json(&req.tags))
+    .bind(&dedup_key)
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
 
     info!("Analytics job {} created: {}", job.id, req.job_name);
 
@@ -56,47 +135,46 @@ async fn create_analytics_job(
         "name": "analytics_pipeline",
         "namespace": "data_pipeline_rs",
         "version": "1.0.0",
-        "initiator": "axum-example-app",
-        "source_system": "example-axum",
+        "initiator": attribution.initiator,
+        "source_system": attribution.source_system,
         "reason": format!("Analytics pipeline job: {}", req.job_name),
         "context": {
             "job_name": req.job_name,
             "sources": req.sources,
             "date_range": req.date_range,
-            "app_job_id": job.id
+            "allow_partial": req.allow_partial,
+            "tags": req.tags,
+            "app_job_id": job.id,
+            "request_id": request_id.as_str()
         }
     });
 
-    // Submit task to Tasker orchestration
-    let task_uuid = match submit_task_to_orchestration(&task_payload).await {
-        Ok(uuid) => Some(uuid),
+    // Submit task to Tasker orchestration. A failed job no longer blocks
+    // resubmitting the same key.
+    let task_uuid = match client.create_task(&task_payload).await {
+        Ok(uuid) => uuid,
         Err(e) => {
             error!("Failed to submit analytics task to orchestration: {}", e);
-            None
+            return Err(fail_submission(&pool, "analytics_jobs", job.id, &e).await);
         }
     };
 
     // Update job with task UUID
-    if let Some(ref uuid) = task_uuid {
-        let _ = sqlx::query(
-            "UPDATE analytics_jobs SET task_uuid = $1, status = 'processing' WHERE id = $2",
-        )
-        .bind(uuid)
-        .bind(job.id)
-        .execute(&pool)
-        .await;
-    }
+    let _ = sqlx::query(
+        "UPDATE analytics_jobs SET task_uuid = $1, status = 'processing' WHERE id = $2",
+    )
+    .bind(task_uuid)
+    .bind(job.id)
+    .execute(&pool)
+    .await;
 
     let response = AnalyticsJobResponse {
         id: job.id,
         job_name: job.job_name,
-        status: if task_uuid.is_some() {
-            "processing".to_string()
-        } else {
-            "pending".to_string()
-        },
-        task_uuid,
+        status: "processing".to_string(),
+        task_uuid: Some(task_uuid),
         created_at: job.created_at,
+        request_id: request_id.0,
     };
 
     Ok((
@@ -108,51 +186,115 @@ async fn create_analytics_job(
     ))
 }
 
-/// Retrieve an analytics job by ID.
+/// List analytics jobs, newest first, optionally filtered by `?tag.<key>=<value>`.
+/// Archived rows are excluded unless `?include_archived=true`. Pages with
+/// `?cursor=` and `?limit=` (see [`crate::pagination`]).
+async fn list_analytics_jobs(
+    Extension(pool): Extension<AppDb>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Result<Json<PageResponse<AnalyticsJob>>, ApiError> {
+    let filter = tag_filter(&query)?;
+    let include_archived = include_archived(&query)?;
+    let page = PageParams::from_query(&query)?;
+
+    let rows: Vec<AnalyticsJob> = sqlx::query_as(
+        "SELECT * FROM analytics_jobs WHERE tags @> $1 AND ($3 OR archived_at IS NULL) \
+         AND ($4::timestamp IS NULL OR (created_at, id) < ($4, $5)) \
+         ORDER BY created_at DESC, id DESC LIMIT $2 OFFSET $6",
+    )
+    .bind(&filter)
+    .bind(page.fetch_limit())
+    .bind(include_archived)
+    .bind(page.cursor_created_at())
+    .bind(page.cursor_id())
+    .bind(page.offset)
+    .fetch_all(&pool)
+    .await?;
+    let (rows, next_cursor) = page.finish(rows, |row| Cursor::new(row.created_at, row.id));
+
+    Ok(Json(PageResponse {
+        message: format!("{} analytics jobs found", rows.len()),
+        data: rows,
+        next_cursor,
+        total: None,
+    }))
+}
+
+/// Retrieve an analytics job by ID, with its pipeline task's live status.
+///
+/// The task is fetched once and serves both `task` (status, completion, and
+/// the state of each of the 8 steps) and `estimated_completion_at`. If
+/// orchestration cannot be reached, both are `null` and the stored row is
+/// still returned.
 async fn get_analytics_job(
     Extension(pool): Extension<AppDb>,
+    Extension(client): Extension<OrchestrationClient>,
     Path(id): Path<i32>,
-) -> Result<Json<ApiResponse<AnalyticsJob>>, StatusCode> {
+) -> Result<Json<ApiResponse<AnalyticsJobProgress>>, ApiError> {
     let job: AnalyticsJob = sqlx::query_as("SELECT * FROM analytics_jobs WHERE id = $1")
         .bind(id)
         .fetch_optional(&pool)
-        .await
-        .map_err(|e| {
-            error!("Failed to query analytics job: {}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?
-        .ok_or(StatusCode::NOT_FOUND)?;
+        .await?
+        .ok_or(ApiError::NotFound)?;
+
+    let task = match job.task_uuid {
+        Some(task_uuid) => match client.get_task(task_uuid).await {
+            Ok(task) => Some((task_uuid, task)),
+            Err(e) => {
+                warn!(
+                    "Failed to fetch task {} for analytics job {}: {}",
+                    task_uuid, id, e
+                );
+                None
+            }
+        },
+        None => None,
+    };
+    let estimated_completion_at = match &task {
+        Some((_, task)) => {
+            estimated_completion_from_task(&pool, "analytics_jobs", &job.status, task).await
+        }
+        None => None,
+    };
 
     Ok(Json(ApiResponse {
-        data: job,
+        data: AnalyticsJobProgress {
+            job: Estimated {
+                row: job,
+                estimated_completion_at,
+            },
+            task: task.map(|(task_uuid, task)| TaskStatus::from_task(task_uuid, &task)),
+        },
         message: "Analytics job retrieved".to_string(),
     }))
 }
 
-/// Submit a task to the Tasker orchestration REST API and return the task UUID.
-async fn submit_task_to_orchestration(
-    payload: &serde_json::Value,
-) -> anyhow::Result<uuid::Uuid> {
-    let orchestration_url =
-        std::env::var("ORCHESTRATION_URL").unwrap_or_else(|_| "http://localhost:8080".to_string());
-
-    let client = reqwest::Client::new();
-    let response = client
-        .post(format!("{}/v1/tasks", orchestration_url))
-        .json(payload)
-        .send()
-        .await?;
+/// Cancel an analytics job, and its workflow task if one was submitted (see
+/// [`cancel_row`]).
+async fn cancel_analytics_job(
+    Extension(pool): Extension<AppDb>,
+    Extension(client): Extension<OrchestrationClient>,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<AnalyticsJob>>, ApiError> {
+    let job: AnalyticsJob = cancel_row(&pool, &client, "analytics_jobs", id).await?;
 
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().await.unwrap_or_default();
-        anyhow::bail!("Orchestration returned {}: {}", status, body);
-    }
+    info!("Analytics job {} cancelled", job.id);
 
-    let body: serde_json::Value = response.json().await?;
-    let task_uuid_str = body["task_uuid"]
-        .as_str()
-        .ok_or_else(|| anyhow::anyhow!("Missing task_uuid in orchestration response"))?;
-    let task_uuid = uuid::Uuid::parse_str(task_uuid_str)?;
-    Ok(task_uuid)
+    Ok(Json(ApiResponse {
+        data: job,
+        message: "Analytics job cancelled".to_string(),
+    }))
+}
+
+/// Report the workflow task of an analytics job (see [`crate::task_status`]).
+async fn get_analytics_job_task(
+    Extension(pool): Extension<AppDb>,
+    Extension(client): Extension<OrchestrationClient>,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<TaskStatus>>, ApiError> {
+    let task = row_task_status(&pool, &client, "analytics_jobs", id).await?;
+    Ok(Json(ApiResponse {
+        data: task,
+        message: "Task status retrieved".to_string(),
+    }))
 }
@@ -1,24 +1,47 @@
 //! Team scaling with namespace isolation routes (compliance/refund processing).
 //!
+//! GET  /compliance        - List compliance checks (filter with ?tag.<key>=<value>, ?include_archived=true, page with ?cursor=)
 //! POST /compliance/refund - Create a refund processing workflow across two namespaces
-//! GET  /compliance/:id    - Retrieve a compliance check by ID
+//! GET  /compliance/:id    - Retrieve a compliance check (and its correlated order) by ID,
+//!                           with its estimated completion time and the live status of
+//!                           both namespace tasks
+//! POST /compliance/:id/cancel - Cancel both namespace tasks of a compliance check
 
-use axum::extract::Path;
+use std::collections::HashMap;
+
+use axum::extract::{Path, Query};
 use axum::http::StatusCode;
 use axum::routing::{get, post};
 use axum::{Extension, Json, Router};
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
+use crate::archiver::include_archived;
+use crate::attribution::TaskAttribution;
+use crate::cancellation::{cancel_all, cancelled_status, WorkflowTask, CANCELLABLE_STATUSES};
 use crate::db::AppDb;
+use crate::email::{normalize_email, validate_email};
+use crate::eta::estimated_completion_from_task;
+use crate::error::ApiError;
+use crate::extract::AliasedJson;
 use crate::models::{
-    ApiResponse, ComplianceCheck, ComplianceCheckResponse, CreateComplianceCheckRequest,
+    ApiResponse, CancellationResponse, ComplianceCheck, ComplianceCheckDetail,
+    ComplianceCheckProgress, ComplianceCheckResponse, CreateComplianceCheckRequest, Estimated,
+    Order, PageResponse,
 };
+use crate::orchestration::{OrchestrationClient, OrchestrationError};
+use crate::pagination::{Cursor, PageParams};
+use crate::request_id::RequestId;
+use crate::submission::fail_submission;
+use crate::tags::{tag_filter, validate_tags};
+use crate::task_status::{aggregate_status, TaskStatus};
 
 /// Build the compliance router.
 pub fn router() -> Router {
     Router::new()
+        .route("/compliance", get(list_compliance_checks))
         .route("/compliance/refund", post(create_refund_check))
         .route("/compliance/{id}", get(get_compliance_check))
+        .route("/compliance/{id}/cancel", post(cancel_compliance_check))
 }
 
 /// Create a refund processing compliance check spanning two namespaces.
@@ -30,33 +53,57 @@ pub fn router() -> Router {
 ///   update records, notify customer
 async fn create_refund_check(
     Extension(pool): Extension<AppDb>,
-    Json(req): Json<CreateComplianceCheckRequest>,
-) -> Result<(StatusCode, Json<ApiResponse<ComplianceCheckResponse>>), StatusCode> {
+    Extension(client): Extension<OrchestrationClient>,
+    attribution: TaskAttribution,
+    request_id: RequestId,
+    AliasedJson(mut req): AliasedJson<CreateComplianceCheckRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<ComplianceCheckResponse>>), ApiError> {
+    req.customer_email = normalize_email(&req.customer_email);
+    validate_email("customer_email", &req.customer_email)?;
+    validate_tags(&req.tags)?;
+    req.validate_refund_amount()?;
+
+    // Correlate with a local order when the order_id refers to one
+    let local_order = resolve_local_order(&pool, &req.order_id).await?;
+    // A local order's total caps the refund; other orders are checked by the
+    // payments team's handlers
+    let original_amount = local_order.map(|order| order.total);
+    if let Some(total) = original_amount {
+        if req.refund_amount > total {
+            return Err(ApiError::BadRequest(format!(
+                "refund_amount {:.2} exceeds the order total {:.2}",
+                req.refund_amount, total
+            )));
+        }
+    }
+    let order_ref = local_order.map(|order| order.id);
+
     let payload = serde_json::json!({
         "customer_email": req.customer_email,
         "order_id": req.order_id,
         "refund_amount": req.refund_amount,
+        "currency": req.currency(),
         "reason": req.reason,
     });
 
     // Insert compliance check into application database
     let check: ComplianceCheck = sqlx::query_as(
         r#"
-        INSERT INTO compliance_checks (check_type, namespace, ticket_id, payload, status)
-        VALUES ($1, $2, $3, $4, 'pending')
+        INSERT INTO compliance_checks
+            (check_type, namespace, ticket_id, customer_email, payload, status, order_ref, tags)
+        VALUES ($1, $2, $3, $4, $5, 'pending', $6, $7)
         RETURNING *
         "#,
     )
     .bind(&req.check_type)
     .bind(&req.namespace)
     .bind(&req.ticket_id)
+    .bind(&req.customer_email)
     .bind(&payload)
+    .bind(order_ref)
+    .bind(sqlx::types::Json(&req.tags))
     .fetch_one(&pool)
-    .await
-    .map_err(|e| {
-        error!("Failed to insert compliance check: {}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    .await?;
 
     info!(
         "Compliance check {} created: {} in namespace {}",
@@ -74,8 +121,8 @@ async fn create_refund_check(
         "name": "process_refund",
         "namespace": "customer_success_rs",
         "version": "1.0.0",
-        "initiator": "axum-example-app",
-        "source_system": "example-axum",
+        "initiator": attribution.initiator,
+        "source_system": attribution.source_system,
         "reason": format!("Refund request: {} - {}", req.order_id, req.reason),
         "context": {
             "ticket_id": req.ticket_id.as_deref().unwrap_or("TICKET-000"),
@@ -84,79 +131,91 @@ async fn create_refund_check(
             "order_id": req.order_id,
             "refund_amount": req.refund_amount,
             "reason": req.reason,
-            "app_compliance_check_id": check.id
+            "tags": req.tags,
+            "app_compliance_check_id": check.id,
+            "request_id": request_id.as_str()
         }
     });
 
     // Payments context must include:
     //   - payment_id (required by validate_payment_eligibility - source handler contract)
     //   - refund_amount (required by validate_payment_eligibility)
+    //   - currency (read by validate_payment_eligibility for amount precision)
     //   - customer_email (read by notify_customer from context)
+    //   - original_amount (the local order's total, when there is one)
     let payment_id = format!("pay_{}", req.order_id.replace('-', ""));
-    let payments_task_payload = serde_json::json!({
+    let mut payments_task_payload = serde_json::json!({
         "name": "process_refund",
         "namespace": "payments_rs",
         "version": "1.0.0",
-        "initiator": "axum-example-app",
-        "source_system": "example-axum",
+        "initiator": attribution.initiator,
+        "source_system": attribution.source_system,
         "reason": format!("Payment refund: {} - ${:.2}", req.order_id, req.refund_amount),
         "context": {
             "payment_id": payment_id,
             "order_id": req.order_id,
             "customer_email": req.customer_email,
             "refund_amount": req.refund_amount,
+            "currency": req.currency(),
             "payment_method": "original_method",
             "reason": req.reason,
-            "app_compliance_check_id": check.id
+            "tags": req.tags,
+            "app_compliance_check_id": check.id,
+            "request_id": request_id.as_str()
         }
     });
 
-    // Submit both tasks to orchestration (customer success + payments)
-    let cs_task_uuid = match submit_task_to_orchestration(&cs_task_payload).await {
-        Ok(uuid) => Some(uuid),
-        Err(e) => {
-            error!(
-                "Failed to submit customer success task to orchestration: {}",
-                e
-            );
-            None
-        }
-    };
+    if let Some(total) = original_amount {
+        payments_task_payload["context"]["original_amount"] = serde_json::json!(total);
+        payments_task_payload["context"]["partial_refund"] =
+            serde_json::json!(req.refund_amount < total);
+    }
 
-    let payments_task_uuid = match submit_task_to_orchestration(&payments_task_payload).await {
-        Ok(uuid) => Some(uuid),
-        Err(e) => {
-            error!("Failed to submit payments task to orchestration: {}", e);
-            None
-        }
-    };
+    // Submit both tasks to orchestration (customer success + payments)
+    let cs_task = submit_with_step_count(&client, &cs_task_payload, "customer success").await;
+    let payments_task =
+        submit_with_step_count(&client, &payments_task_payload, "payments").await;
+    let ((cs_task_uuid, cs_total_steps), (payments_task_uuid, payments_total_steps)) =
+        match (cs_task, payments_task) {
+            (Ok(cs_task), Ok(payments_task)) => (cs_task, payments_task),
+            (Err(e), other) | (other, Err(e)) => {
+                // Don't leave the other half of the refund running on its own
+                if let Ok((task_uuid, _)) = other {
+                    if let Err(cancel_error) = client.cancel_task(task_uuid).await {
+                        warn!(
+                            "Failed to cancel task {} of compliance check {}: {}",
+                            task_uuid, check.id, cancel_error
+                        );
+                    }
+                }
+                return Err(fail_submission(&pool, "compliance_checks", check.id, &e).await);
+            }
+        };
 
-    // Use the customer success task UUID as the primary reference
-    let task_uuid = cs_task_uuid;
-
-    // Update compliance check with task UUID
-    if let Some(ref uuid) = task_uuid {
-        let _ = sqlx::query(
-            "UPDATE compliance_checks SET task_uuid = $1, status = 'processing' WHERE id = $2",
-        )
-        .bind(uuid)
-        .bind(check.id)
-        .execute(&pool)
-        .await;
-    }
+    // Update compliance check with both task UUIDs; the customer success task
+    // is the primary reference
+    let _ = sqlx::query(
+        "UPDATE compliance_checks SET task_uuid = $1, payments_task_uuid = $2, \
+         status = 'processing' WHERE id = $3",
+    )
+    .bind(cs_task_uuid)
+    .bind(payments_task_uuid)
+    .bind(check.id)
+    .execute(&pool)
+    .await;
 
     let response = ComplianceCheckResponse {
         id: check.id,
         check_type: check.check_type,
         namespace: check.namespace,
-        status: if task_uuid.is_some() {
-            "processing".to_string()
-        } else {
-            "pending".to_string()
-        },
-        task_uuid,
-        payments_task_uuid,
+        status: "processing".to_string(),
+        task_uuid: Some(cs_task_uuid),
+        payments_task_uuid: Some(payments_task_uuid),
+        cs_total_steps,
+        payments_total_steps,
+        order_ref: check.order_ref,
         created_at: check.created_at,
+        request_id: request_id.0,
     };
 
     Ok((
@@ -168,51 +227,250 @@ async fn create_refund_check(
     ))
 }
 
-/// Retrieve a compliance check by ID.
+/// List compliance checks, newest first, optionally filtered by `?tag.<key>=<value>`.
+/// Archived rows are excluded unless `?include_archived=true`. Pages with
+/// `?cursor=` and `?limit=` (see [`crate::pagination`]).
+async fn list_compliance_checks(
+    Extension(pool): Extension<AppDb>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Result<Json<PageResponse<ComplianceCheck>>, ApiError> {
+    let filter = tag_filter(&query)?;
+    let include_archived = include_archived(&query)?;
+    let page = PageParams::from_query(&query)?;
+
+    let rows: Vec<ComplianceCheck> = sqlx::query_as(
+        "SELECT * FROM compliance_checks WHERE tags @> $1 AND ($3 OR archived_at IS NULL) \
+         AND ($4::timestamp IS NULL OR (created_at, id) < ($4, $5)) \
+         ORDER BY created_at DESC, id DESC LIMIT $2 OFFSET $6",
+    )
+    .bind(&filter)
+    .bind(page.fetch_limit())
+    .bind(include_archived)
+    .bind(page.cursor_created_at())
+    .bind(page.cursor_id())
+    .bind(page.offset)
+    .fetch_all(&pool)
+    .await?;
+    let (rows, next_cursor) = page.finish(rows, |row| Cursor::new(row.created_at, row.id));
+
+    Ok(Json(PageResponse {
+        message: format!("{} compliance checks found", rows.len()),
+        data: rows,
+        next_cursor,
+        total: None,
+    }))
+}
+
+/// Retrieve a compliance check by ID, including the correlated local order if any.
+///
+/// Both namespace tasks are fetched from orchestration and reported with their
+/// combined `task_status`. A task that cannot be fetched is `null`, and the
+/// stored row is still returned.
 async fn get_compliance_check(
     Extension(pool): Extension<AppDb>,
+    Extension(client): Extension<OrchestrationClient>,
     Path(id): Path<i32>,
-) -> Result<Json<ApiResponse<ComplianceCheck>>, StatusCode> {
+) -> Result<Json<ApiResponse<ComplianceCheckProgress>>, ApiError> {
     let check: ComplianceCheck = sqlx::query_as("SELECT * FROM compliance_checks WHERE id = $1")
         .bind(id)
         .fetch_optional(&pool)
-        .await
-        .map_err(|e| {
-            error!("Failed to query compliance check: {}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?
-        .ok_or(StatusCode::NOT_FOUND)?;
+        .await?
+        .ok_or(ApiError::NotFound)?;
+
+    let order: Option<Order> = match check.order_ref {
+        Some(order_id) => {
+            sqlx::query_as("SELECT * FROM orders WHERE id = $1")
+                .bind(order_id)
+                .fetch_optional(&pool)
+                .await?
+        }
+        None => None,
+    };
+
+    let (cs_task, payments_task) = tokio::join!(
+        fetch_task(&client, id, "customer success", check.task_uuid),
+        fetch_task(&client, id, "payments", check.payments_task_uuid),
+    );
+    // The customer success task drives the estimate, as it does for the row
+    let estimated_completion_at = match &cs_task {
+        Some((_, task)) => {
+            estimated_completion_from_task(&pool, "compliance_checks", &check.status, task).await
+        }
+        None => None,
+    };
+
+    let cs_task = cs_task.map(|(task_uuid, task)| TaskStatus::from_task(task_uuid, &task));
+    let payments_task =
+        payments_task.map(|(task_uuid, task)| TaskStatus::from_task(task_uuid, &task));
+    // A task that was submitted but could not be fetched leaves the aggregate unknown
+    let statuses: Vec<Option<&str>> = [
+        (check.task_uuid, &cs_task),
+        (check.payments_task_uuid, &payments_task),
+    ]
+    .into_iter()
+    .filter(|(task_uuid, _)| task_uuid.is_some())
+    .map(|(_, task)| task.as_ref().and_then(|task| task.status.as_deref()))
+    .collect();
+    let task_status = aggregate_status(&statuses);
 
     Ok(Json(ApiResponse {
-        data: check,
+        data: ComplianceCheckProgress {
+            check: Estimated {
+                row: ComplianceCheckDetail { check, order },
+                estimated_completion_at,
+            },
+            task_status,
+            customer_success_task: cs_task,
+            payments_task,
+        },
         message: "Compliance check retrieved".to_string(),
     }))
 }
 
-/// Submit a task to the Tasker orchestration REST API and return the task UUID.
-async fn submit_task_to_orchestration(
-    payload: &serde_json::Value,
-) -> anyhow::Result<uuid::Uuid> {
-    let orchestration_url =
-        std::env::var("ORCHESTRATION_URL").unwrap_or_else(|_| "http://localhost:8080".to_string());
-
-    let client = reqwest::Client::new();
-    let response = client
-        .post(format!("{}/v1/tasks", orchestration_url))
-        .json(payload)
-        .send()
-        .await?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().await.unwrap_or_default();
-        anyhow::bail!("Orchestration returned {}: {}", status, body);
+/// Fetch one namespace task of compliance check `id`; `None` if it was not
+/// submitted or could not be fetched (logged).
+async fn fetch_task(
+    client: &OrchestrationClient,
+    id: i32,
+    label: &str,
+    task_uuid: Option<uuid::Uuid>,
+) -> Option<(uuid::Uuid, serde_json::Value)> {
+    let task_uuid = task_uuid?;
+    match client.get_task(task_uuid).await {
+        Ok(task) => Some((task_uuid, task)),
+        Err(e) => {
+            warn!(
+                "Failed to fetch {} task {} for compliance check {}: {}",
+                label, task_uuid, id, e
+            );
+            None
+        }
     }
+}
+
+/// Cancel the customer success and payments tasks of a compliance check.
+///
+/// Reports a result per namespace. The check becomes `cancelled` only if every
+/// task was cancelled, `partially_cancelled` if some were, and is left as is if
+/// none were. A check that already finished is a 409.
+async fn cancel_compliance_check(
+    Extension(pool): Extension<AppDb>,
+    Extension(client): Extension<OrchestrationClient>,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<CancellationResponse>>, ApiError> {
+    let row: Option<(String, Option<uuid::Uuid>, Option<uuid::Uuid>)> = sqlx::query_as(
+        "SELECT status, task_uuid, payments_task_uuid FROM compliance_checks WHERE id = $1",
+    )
+    .bind(id)
+    .fetch_optional(&pool)
+    .await?;
+    let (status, cs_task_uuid, payments_task_uuid) = row.ok_or(ApiError::NotFound)?;
+    if !CANCELLABLE_STATUSES.contains(&status.as_str()) {
+        return Err(StatusCode::CONFLICT.into());
+    }
+
+    let tasks: Vec<WorkflowTask> = [
+        ("customer_success_rs", cs_task_uuid),
+        ("payments_rs", payments_task_uuid),
+    ]
+    .into_iter()
+    .filter_map(|(namespace, task_uuid)| Some(WorkflowTask { namespace, task_uuid: task_uuid? }))
+    .collect();
+    let results = cancel_all(&client, &tasks).await;
+
+    let status = match cancelled_status(&results) {
+        Some(new_status) => {
+            // The reconciler may have finished the row while we were cancelling
+            let updated: Option<String> = sqlx::query_scalar(
+                "UPDATE compliance_checks SET status = $1, updated_at = NOW() \
+                 WHERE id = $2 AND status = ANY($3) RETURNING status",
+            )
+            .bind(new_status)
+            .bind(id)
+            .bind(CANCELLABLE_STATUSES)
+            .fetch_optional(&pool)
+            .await?;
+            match updated {
+                Some(status) => status,
+                None => current_status(&pool, id).await?,
+            }
+        }
+        None => status,
+    };
+
+    let cancelled = results.iter().filter(|r| r.cancelled).count();
+    info!(
+        "Compliance check {} is {} ({} of {} tasks cancelled)",
+        id,
+        status,
+        cancelled,
+        results.len()
+    );
+
+    Ok(Json(ApiResponse {
+        message: format!("{} of {} tasks cancelled", cancelled, results.len()),
+        data: CancellationResponse {
+            id,
+            status,
+            tasks: results,
+        },
+    }))
+}
+
+async fn current_status(pool: &AppDb, id: i32) -> Result<String, ApiError> {
+    Ok(
+        sqlx::query_scalar("SELECT status FROM compliance_checks WHERE id = $1")
+            .bind(id)
+            .fetch_one(pool)
+            .await?,
+    )
+}
+
+/// The local order a refund refers to.
+#[derive(Debug, Clone, Copy, sqlx::FromRow)]
+struct LocalOrder {
+    id: i32,
+    total: f64,
+}
+
+/// Resolve a refund request's `order_id` to a local order.
+///
+/// Refunds may reference orders from other systems (e.g. `ORD-20251115-ABC123`),
+/// so an order_id that is not numeric or does not match a row yields `None`.
+async fn resolve_local_order(
+    pool: &AppDb,
+    order_id: &str,
+) -> Result<Option<LocalOrder>, sqlx::Error> {
+    let Ok(id) = order_id.trim().parse::<i32>() else {
+        return Ok(None);
+    };
+
+    sqlx::query_as("SELECT id, total::float8 AS total FROM orders WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Submit a task and look up its step count.
+///
+/// The step count is `None` if the task was created but its step count could
+/// not be read, which is logged rather than failing the submission.
+async fn submit_with_step_count(
+    orchestration: &OrchestrationClient,
+    payload: &serde_json::Value,
+    label: &str,
+) -> Result<(uuid::Uuid, Option<i64>), OrchestrationError> {
+    let task = orchestration.submit_task(payload).await.inspect_err(|e| {
+        error!("Failed to submit {} task to orchestration: {}", label, e);
+    })?;
+
+    let total_steps = match orchestration.total_steps(&task).await {
+        Ok(total_steps) => Some(total_steps),
+        Err(e) => {
+            warn!("Failed to read step count for {} task {}: {}", label, task.task_uuid, e);
+            None
+        }
+    };
 
-    let body: serde_json::Value = response.json().await?;
-    let task_uuid_str = body["task_uuid"]
-        .as_str()
-        .ok_or_else(|| anyhow::anyhow!("Missing task_uuid in orchestration response"))?;
-    let task_uuid = uuid::Uuid::parse_str(task_uuid_str)?;
-    Ok(task_uuid)
+    Ok((task.task_uuid, total_steps))
 }
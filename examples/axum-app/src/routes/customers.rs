@@ -0,0 +1,54 @@
+//! Cross-domain customer routes.
+//!
+//! GET /customers/:email/workflows - List every workflow-backed row for a customer
+
+use axum::extract::Path;
+use axum::routing::get;
+use axum::{Extension, Json, Router};
+
+use crate::db::AppDb;
+use crate::email::normalize_email;
+use crate::error::ApiError;
+use crate::models::{ApiResponse, CustomerWorkflow};
+
+/// Maximum rows returned by the workflows endpoint.
+const LIST_LIMIT: i64 = 100;
+
+/// Build the customers router.
+pub fn router() -> Router {
+    Router::new().route("/customers/{email}/workflows", get(list_customer_workflows))
+}
+
+/// List orders, service requests, and compliance checks for a customer, newest first.
+///
+/// Analytics jobs are not tied to a customer and are never included.
+async fn list_customer_workflows(
+    Extension(pool): Extension<AppDb>,
+    Path(email): Path<String>,
+) -> Result<Json<ApiResponse<Vec<CustomerWorkflow>>>, ApiError> {
+    // Rows are stored under the normalized email
+    let email = normalize_email(&email);
+    let rows: Vec<CustomerWorkflow> = sqlx::query_as(
+        r#"
+        SELECT 'order' AS domain_type, id, status, task_uuid, created_at
+        FROM orders WHERE customer_email = $1
+        UNION ALL
+        SELECT 'service_request' AS domain_type, id, status, task_uuid, created_at
+        FROM service_requests WHERE user_email = $1
+        UNION ALL
+        SELECT 'compliance_check' AS domain_type, id, status, task_uuid, created_at
+        FROM compliance_checks WHERE customer_email = $1
+        ORDER BY created_at DESC, domain_type, id DESC
+        LIMIT $2
+        "#,
+    )
+    .bind(&email)
+    .bind(LIST_LIMIT)
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(Json(ApiResponse {
+        message: format!("{} workflows found for {}", rows.len(), email),
+        data: rows,
+    }))
+}
@@ -0,0 +1,108 @@
+//! Debug routes.
+//!
+//! GET /debug/handlers      - Completed and failed step counts, and the last
+//!                            error, per handler (see [`crate::handler_metrics`])
+//! GET /debug/failed-steps  - Recently dead-lettered steps, newest first
+//!                            (see [`crate::dead_letter`])
+//! GET /debug/registry      - Names and count of the registered handlers
+//!                            (only served with `DEBUG_ENDPOINTS=true`)
+//!
+//! Like the admin routes, these require `Authorization: Bearer $ADMIN_TOKEN`
+//! when `ADMIN_TOKEN` is set.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use axum::extract::Query;
+use axum::routing::get;
+use axum::{middleware, Extension, Json, Router};
+use serde::{Deserialize, Serialize};
+use tasker_worker::worker::handlers::StepHandlerRegistry;
+use uuid::Uuid;
+
+use crate::admin_auth::require_admin;
+use crate::db::AppDb;
+use crate::dead_letter::{recent_failed_steps, MAX_FAILED_STEPS};
+use crate::error::ApiError;
+use crate::handler_metrics::{HandlerCounts, HandlerMetrics};
+use crate::handler_registry::AxumHandlerRegistry;
+use crate::models::{ApiResponse, FailedStep};
+
+/// Read the `DEBUG_ENDPOINTS` env var (`true` serves [`registry_router`]).
+pub fn debug_endpoints_from_env() -> bool {
+    std::env::var("DEBUG_ENDPOINTS")
+        .map(|v| v.trim().eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+/// Build the debug router.
+pub fn router() -> Router {
+    Router::new()
+        .route("/debug/handlers", get(get_handler_metrics))
+        .route("/debug/failed-steps", get(get_failed_steps))
+        .route_layer(middleware::from_fn(require_admin))
+}
+
+/// Build the router for `GET /debug/registry`, merged only when
+/// `DEBUG_ENDPOINTS` is enabled.
+pub fn registry_router() -> Router {
+    Router::new()
+        .route("/debug/registry", get(get_registry))
+        .route_layer(middleware::from_fn(require_admin))
+}
+
+/// Outcome counts for every handler that has run since startup.
+async fn get_handler_metrics(
+    Extension(metrics): Extension<HandlerMetrics>,
+) -> Json<ApiResponse<BTreeMap<String, HandlerCounts>>> {
+    let snapshot = metrics.snapshot();
+    let message = format!("{} handlers have run", snapshot.len());
+    Json(ApiResponse {
+        data: snapshot,
+        message,
+    })
+}
+
+#[derive(Debug, Deserialize)]
+struct FailedStepsParams {
+    task_uuid: Option<Uuid>,
+}
+
+/// The most recent dead-lettered steps (at most [`MAX_FAILED_STEPS`]),
+/// optionally only those of `task_uuid`.
+async fn get_failed_steps(
+    Extension(pool): Extension<AppDb>,
+    Query(params): Query<FailedStepsParams>,
+) -> Result<Json<ApiResponse<Vec<FailedStep>>>, ApiError> {
+    let steps = recent_failed_steps(&pool, params.task_uuid, MAX_FAILED_STEPS).await?;
+
+    Ok(Json(ApiResponse {
+        message: format!("{} failed steps", steps.len()),
+        data: steps,
+    }))
+}
+
+/// The handlers a deployment registered.
+#[derive(Debug, Serialize)]
+pub struct RegistryReport {
+    /// Registered handler names, sorted.
+    pub handlers: Vec<String>,
+    pub count: usize,
+}
+
+/// List the registered handlers, to check a deployment registered exactly the
+/// ones its templates call.
+async fn get_registry(
+    Extension(registry): Extension<Arc<AxumHandlerRegistry>>,
+) -> Json<ApiResponse<RegistryReport>> {
+    let mut handlers = registry.registered_handlers();
+    handlers.sort();
+    let report = RegistryReport {
+        count: handlers.len(),
+        handlers,
+    };
+    Json(ApiResponse {
+        message: format!("{} handlers registered", report.count),
+        data: report,
+    })
+}
@@ -0,0 +1,91 @@
+//! Liveness and readiness probes for load balancers and Kubernetes.
+//!
+//! GET /health    - 200 whenever the app is serving requests
+//! GET /readiness - 200 when the app database and orchestration both answer,
+//!                  otherwise 503 naming the dependencies that failed
+
+use std::collections::BTreeMap;
+
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Extension, Json, Router};
+use serde::Serialize;
+use serde_json::json;
+
+use crate::db::AppDb;
+use crate::orchestration::OrchestrationClient;
+
+/// Build the health router.
+pub fn router() -> Router {
+    Router::new()
+        .route("/health", get(health))
+        .route("/readiness", get(readiness))
+}
+
+/// The outcome of one dependency check.
+#[derive(Debug, Serialize)]
+pub struct DependencyCheck {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl DependencyCheck {
+    fn from_result<E: ToString>(result: Result<(), E>) -> Self {
+        match result {
+            Ok(()) => Self { ok: true, error: None },
+            Err(e) => Self {
+                ok: false,
+                error: Some(e.to_string()),
+            },
+        }
+    }
+}
+
+/// Readiness report: overall status, the failed dependencies, and each check.
+#[derive(Debug, Serialize)]
+pub struct Readiness {
+    pub status: &'static str,
+    pub failed: Vec<&'static str>,
+    pub checks: BTreeMap<&'static str, DependencyCheck>,
+}
+
+/// The app is up and serving.
+async fn health() -> Json<serde_json::Value> {
+    Json(json!({ "status": "ok" }))
+}
+
+/// Check the app database (`SELECT 1`) and orchestration (`GET /health`).
+async fn readiness(
+    Extension(pool): Extension<AppDb>,
+    Extension(client): Extension<OrchestrationClient>,
+) -> (StatusCode, Json<Readiness>) {
+    let (database, orchestration) = tokio::join!(
+        sqlx::query("SELECT 1").execute(&pool),
+        client.health(),
+    );
+    let checks = BTreeMap::from([
+        ("database", DependencyCheck::from_result(database.map(|_| ()))),
+        ("orchestration", DependencyCheck::from_result(orchestration)),
+    ]);
+
+    let failed: Vec<&'static str> = checks
+        .iter()
+        .filter(|(_, check)| !check.ok)
+        .map(|(name, _)| *name)
+        .collect();
+    let (status, label) = if failed.is_empty() {
+        (StatusCode::OK, "ready")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "not_ready")
+    };
+
+    (
+        status,
+        Json(Readiness {
+            status: label,
+            failed,
+            checks,
+        }),
+    )
+}
@@ -0,0 +1,40 @@
+//! Metrics exposition route.
+//!
+//! GET  /metrics               - Prometheus text format for all recorded metrics
+//! POST /admin/metrics/reset   - Clear all metrics (`test-util` feature only)
+
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+
+use crate::metrics;
+
+/// Build the metrics router.
+pub fn router() -> Router {
+    Router::new().route("/metrics", get(render_metrics))
+}
+
+/// Render the process-wide metrics registry.
+async fn render_metrics() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        metrics::registry().render(),
+    )
+}
+
+/// Build the test-only metrics reset router.
+///
+/// Not for production: clearing the registry discards data scrapers have not
+/// collected yet. Only compiled with the `test-util` feature.
+#[cfg(feature = "test-util")]
+pub fn reset_router() -> Router {
+    Router::new().route("/admin/metrics/reset", axum::routing::post(reset_metrics))
+}
+
+/// Clear the process-wide metrics registry.
+#[cfg(feature = "test-util")]
+async fn reset_metrics() -> axum::http::StatusCode {
+    metrics::registry().reset();
+    axum::http::StatusCode::NO_CONTENT
+}
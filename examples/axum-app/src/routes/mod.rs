@@ -5,8 +5,29 @@
 //! - `analytics`: Data pipeline analytics (Blog Post 2)
 //! - `services`: Microservices user registration (Blog Post 3)
 //! - `compliance`: Team scaling with namespace isolation (Blog Post 4)
+//!
+//! `products` lists the catalog orders can reference, `customers` lists a
+//! customer's workflows across the domain tables,
+//! `metrics` exposes HTTP request metrics in the Prometheus text format,
+//! `health` serves liveness and readiness probes,
+//! `order_monitor` streams order status changes over a WebSocket,
+//! `simulate` runs a workflow's handlers in-process without orchestration,
+//! `admin` serves operational views such as locally persisted step results,
+//! `debug` reports per-handler outcome counts and the registered handlers,
+//! `tasks` fetches and cancels orchestration tasks by UUID, and `webhooks`
+//! receives task state changes pushed by orchestration.
 
+pub mod admin;
 pub mod analytics;
 pub mod compliance;
+pub mod customers;
+pub mod debug;
+pub mod health;
+pub mod metrics;
+pub mod order_monitor;
 pub mod orders;
+pub mod products;
 pub mod services;
+pub mod simulate;
+pub mod tasks;
+pub mod webhooks;
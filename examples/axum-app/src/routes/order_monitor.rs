@@ -0,0 +1,196 @@
+//! Live order monitoring over WebSocket.
+//!
+//! GET /ws/orders - Upgrade to a WebSocket that streams status updates for
+//!                  the orders a client subscribes to
+//!
+//! Clients send JSON messages to change their subscription:
+//!
+//! ```json
+//! {"action": "subscribe", "order_ids": [1, 2, 3]}
+//! {"action": "unsubscribe", "order_ids": [2]}
+//! ```
+//!
+//! The server answers each with the full subscription (`{"type":
+//! "subscribed", "order_ids": [...]}`), then sends an `order_status` message
+//! for each newly subscribed order and again whenever its status changes.
+//! Unknown ids are reported once in a `not_found` message and dropped.
+//!
+//! Updates come from polling the `orders` table, which the stale-row sweeper
+//! and the status reconciler keep in sync with the orders' tasks. A
+//! connection's subscriptions are discarded when it closes.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::response::Response;
+use axum::routing::get;
+use axum::{Extension, Router};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use tokio::time::MissedTickBehavior;
+use tracing::{debug, error};
+use uuid::Uuid;
+
+use crate::db::AppDb;
+
+/// How often subscribed orders are re-read.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Most orders one connection may watch.
+const MAX_SUBSCRIPTIONS: usize = 500;
+
+/// Build the order monitor router.
+pub fn router() -> Router {
+    Router::new().route("/ws/orders", get(monitor_orders))
+}
+
+/// A subscription change sent by the client.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum ClientMessage {
+    Subscribe { order_ids: Vec<i32> },
+    Unsubscribe { order_ids: Vec<i32> },
+}
+
+/// The current state of a watched order.
+#[derive(Debug, Clone, PartialEq, Serialize, sqlx::FromRow)]
+struct OrderStatus {
+    order_id: i32,
+    status: String,
+    task_uuid: Option<Uuid>,
+    status_reason: Option<String>,
+    updated_at: NaiveDateTime,
+}
+
+/// A message pushed to the client.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ServerMessage {
+    Subscribed { order_ids: Vec<i32> },
+    OrderStatus(OrderStatus),
+    NotFound { order_ids: Vec<i32> },
+    Error { message: String },
+}
+
+async fn monitor_orders(ws: WebSocketUpgrade, Extension(pool): Extension<AppDb>) -> Response {
+    ws.on_upgrade(move |socket| monitor(socket, pool))
+}
+
+/// Watched orders and the last status sent for each (`None` until the first).
+type Subscriptions = BTreeMap<i32, Option<OrderStatus>>;
+
+/// Serve one connection until the client disconnects or a send fails.
+async fn monitor(mut socket: WebSocket, pool: AppDb) {
+    let mut subscriptions = Subscriptions::new();
+    let mut ticker = tokio::time::interval(POLL_INTERVAL);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+    loop {
+        let outgoing = tokio::select! {
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Text(text))) => {
+                    let mut outgoing = apply_client_message(&mut subscriptions, &text);
+                    if !subscriptions.is_empty() {
+                        outgoing.extend(poll(&pool, &mut subscriptions).await);
+                    }
+                    outgoing
+                }
+                // Pings are answered by axum; binary frames are ignored
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => continue,
+            },
+            _ = ticker.tick(), if !subscriptions.is_empty() => {
+                poll(&pool, &mut subscriptions).await
+            }
+        };
+
+        for message in outgoing {
+            let text = serde_json::to_string(&message).unwrap_or_default();
+            if socket.send(Message::Text(text.into())).await.is_err() {
+                debug!("Order monitor client went away");
+                return;
+            }
+        }
+    }
+    debug!("Order monitor connection closed ({} subscriptions)", subscriptions.len());
+}
+
+/// Apply a subscribe/unsubscribe message, returning the reply.
+fn apply_client_message(subscriptions: &mut Subscriptions, text: &str) -> Vec<ServerMessage> {
+    let message: ClientMessage = match serde_json::from_str(text) {
+        Ok(message) => message,
+        Err(e) => {
+            return vec![ServerMessage::Error {
+                message: format!("invalid message: {}", e),
+            }]
+        }
+    };
+
+    match message {
+        ClientMessage::Subscribe { order_ids } => {
+            let new: BTreeSet<i32> = order_ids
+                .into_iter()
+                .filter(|id| !subscriptions.contains_key(id))
+                .collect();
+            if subscriptions.len() + new.len() > MAX_SUBSCRIPTIONS {
+                return vec![ServerMessage::Error {
+                    message: format!(
+                        "at most {} orders can be watched per connection",
+                        MAX_SUBSCRIPTIONS
+                    ),
+                }];
+            }
+            subscriptions.extend(new.into_iter().map(|id| (id, None)));
+        }
+        ClientMessage::Unsubscribe { order_ids } => {
+            for id in order_ids {
+                subscriptions.remove(&id);
+            }
+        }
+    }
+
+    vec![ServerMessage::Subscribed {
+        order_ids: subscriptions.keys().copied().collect(),
+    }]
+}
+
+/// Re-read the watched orders and return a message for every change.
+async fn poll(pool: &AppDb, subscriptions: &mut Subscriptions) -> Vec<ServerMessage> {
+    let ids: Vec<i32> = subscriptions.keys().copied().collect();
+    let rows: Vec<OrderStatus> = match sqlx::query_as(
+        "SELECT id AS order_id, status, task_uuid, status_reason, updated_at \
+         FROM orders WHERE id = ANY($1)",
+    )
+    .bind(&ids)
+    .fetch_all(pool)
+    .await
+    {
+        Ok(rows) => rows,
+        Err(e) => {
+            // Keep the connection; the next tick retries
+            error!("Failed to poll monitored orders: {}", e);
+            return Vec::new();
+        }
+    };
+
+    let mut messages = Vec::new();
+    let mut seen = BTreeSet::new();
+    for row in rows {
+        seen.insert(row.order_id);
+        let last = subscriptions.entry(row.order_id).or_default();
+        if last.as_ref() != Some(&row) {
+            *last = Some(row.clone());
+            messages.push(ServerMessage::OrderStatus(row));
+        }
+    }
+
+    let missing: Vec<i32> = ids.into_iter().filter(|id| !seen.contains(id)).collect();
+    if !missing.is_empty() {
+        for id in &missing {
+            subscriptions.remove(id);
+        }
+        messages.push(ServerMessage::NotFound { order_ids: missing });
+    }
+    messages
+}
@@ -1,117 +1,182 @@
 //! E-commerce order processing routes.
 //!
+//! GET  /orders     - List orders with a total count (filter with ?status=, ?tag.<key>=<value>, ?include_archived=true, page with ?cursor=)
 //! POST /orders     - Create a new order and kick off the e-commerce workflow
-//! GET  /orders/:id - Retrieve an order by ID (includes task status)
+//! POST /orders/batch - Create up to 100 orders in one request, reporting each order's outcome
+//! GET  /orders/:id - Retrieve an order by ID (with its estimated completion time)
+//! PATCH /orders/:id - Correct the shipping address or email before the workflow uses it
+//! DELETE /orders/:id - Cancel an order and its workflow task
+//! POST /orders/:id/retry - Retry the failed steps of the order's workflow task
+//! GET  /orders/:id/task - Report the order's workflow task status and step states
+//! GET  /orders/:id/events - Stream the order's task progress as Server-Sent Events
 
-use axum::extract::Path;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use axum::extract::{Path, Query};
 use axum::http::StatusCode;
+use axum::response::sse::{Event, KeepAlive, Sse};
 use axum::routing::{get, post};
 use axum::{Extension, Json, Router};
+use futures::future::join_all;
+use futures::Stream;
 use tracing::{error, info};
 
+use crate::archiver::include_archived;
+use crate::attribution::TaskAttribution;
+use crate::cancellation::cancel_row;
 use crate::db::AppDb;
-use crate::models::{ApiResponse, CreateOrderRequest, Order, OrderResponse};
+use crate::email::{normalize_email, validate_email};
+use crate::eta::estimated_completion_at;
+use crate::error::ApiError;
+use crate::extract::{AliasScope, AliasedJson, FieldAliases};
+use crate::idempotency::IdempotencyKey;
+use crate::catalog::product_ids_for_skus;
+use crate::models::{
+    ApiResponse, BatchOrderResult, CartItemInput, CreateOrderBatchRequest, CreateOrderRequest,
+    Estimated, Order, OrderResponse, PageResponse, UpdateOrderRequest,
+};
+use crate::money::round_amount;
+use crate::orchestration::{OrchestrationClient, OrchestrationError};
+use crate::pagination::{Cursor, PageParams};
+use crate::quotas::SubmittedWorkflows;
+use crate::request_id::RequestId;
+use crate::submission::fail_submission;
+use crate::tags::{tag_filter, validate_tags};
+use crate::task_events::{task_events, TaskEventsConfig};
+use crate::task_status::{row_task_status, row_task_uuid, TaskStatus, RETRYABLE_TASK_STATUSES};
+
+/// Order statuses that can still be updated: awaiting submission, or running.
+/// A `queued` order's submission is in flight and could miss the change.
+const UPDATABLE_STATUSES: &[&str] = &["pending", "processing"];
+
+/// Index of the first e-commerce step that reads each updatable context field
+/// (`calculate_shipping` and `create_order`). A field can be changed while
+/// fewer steps than this have completed.
+const SHIPPING_ADDRESS_STEP: i64 = 1;
+const CUSTOMER_EMAIL_STEP: i64 = 4;
+
+/// Most orders accepted by one `POST /orders/batch`.
+pub const MAX_BATCH_ORDERS: usize = 100;
 
 /// Build the orders router.
 pub fn router() -> Router {
     Router::new()
-        .route("/orders", post(create_order))
+        .route("/orders", get(list_orders).post(create_order))
         .route("/orders/async", post(create_order_async))
-        .route("/orders/{id}", get(get_order))
+        .route("/orders/batch", post(create_order_batch))
+        .route("/orders/{id}", get(get_order).patch(update_order).delete(cancel_order))
+        .route("/orders/{id}/retry", post(retry_order))
+        .route("/orders/{id}/task", get(get_order_task))
+        .route("/orders/{id}/events", get(stream_order_events))
 }
 
 /// Create a new order and submit an e-commerce workflow task to Tasker.
 ///
-/// 1. Insert an order record with status=pending into the app database
-/// 2. Create a Tasker task via the orchestration REST API
-/// 3. Update the order with the returned task UUID
-/// 4. Return the order response
+/// 1. Validate the customer email, cart, and shipping address, and resolve
+///    cart SKUs (422 naming the offending field, 400 for an unknown SKU)
+/// 2. Insert an order record with status=pending into the app database; if the
+///    request's `Idempotency-Key` is already stored, return that order with 200
+/// 3. Create a Tasker task via the orchestration REST API; if that fails, mark
+///    the order failed and return 502 (503 while orchestration is down)
+/// 4. Update the order with the returned task UUID
+/// 5. Return the order response
 async fn create_order(
     Extension(pool): Extension<AppDb>,
-    Json(req): Json<CreateOrderRequest>,
-) -> Result<(StatusCode, Json<ApiResponse<OrderResponse>>), StatusCode> {
+    Extension(client): Extension<OrchestrationClient>,
+    attribution: TaskAttribution,
+    request_id: RequestId,
+    IdempotencyKey(idempotency_key): IdempotencyKey,
+    AliasedJson(mut req): AliasedJson<CreateOrderRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<OrderResponse>>), ApiError> {
+    req.customer_email = normalize_email(&req.customer_email);
+    req.validate()?;
+    validate_tags(&req.tags)?;
+    let cart_items = resolve_cart_items(&pool, &req.cart_items).await?;
+
     // Calculate total from cart items
-    let total: f64 = req
-        .cart_items
-        .iter()
-        .map(|item| item.unit_price * item.quantity as f64)
-        .sum();
+    let total = order_total(&req.cart_items, &req.currency());
     let items_json = serde_json::to_value(&req.cart_items).unwrap_or_default();
 
-    // Insert order into application database
-    let order: Order = sqlx::query_as(
+    // Insert order into application database, unless its idempotency key is
+    // already taken
+    let inserted: Option<Order> = sqlx::query_as(
         r#"
-        INSERT INTO orders (customer_email, items, total, status)
-        VALUES ($1, $2, $3, 'pending')
+        INSERT INTO orders (customer_email, items, total, status, tags, shipping_address, idempotency_key, currency)
+        VALUES ($1, $2, $3, 'pending', $4, $5, $6, $7)
+        ON CONFLICT (idempotency_key) DO NOTHING
         RETURNING *
         "#,
     )
     .bind(&req.customer_email)
     .bind(&items_json)
     .bind(total)
-    .fetch_one(&pool)
-    .await
-    .map_err(|e| {
-        error!("Failed to insert order: {}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    .bind(sqlx::types::Json(&req.tags))
+    .bind(sqlx::types::Json(&req.shipping_address))
+    .bind(&idempotency_key)
+    .bind(req.currency())
+    .fetch_optional(&pool)
+    .await?;
+
+    let Some(order) = inserted else {
+        let order: Order = sqlx::query_as("SELECT * FROM orders WHERE idempotency_key = $1")
+            .bind(&idempotency_key)
+            .fetch_one(&pool)
+            .await?;
+        info!("Order {} already exists for its idempotency key", order.id);
+        return Ok((
+            StatusCode::OK,
+            Json(ApiResponse {
+                data: OrderResponse {
+                    id: order.id,
+                    customer_email: order.customer_email,
+                    status: order.status,
+                    task_uuid: order.task_uuid,
+                    created_at: order.created_at,
+                    request_id: request_id.0,
+                },
+                message: "Order already exists for this Idempotency-Key".to_string(),
+            }),
+        ));
+    };
 
     info!("Order {} created for {}", order.id, req.customer_email);
 
     // Build the Tasker task request for e-commerce order processing.
-    // We use the orchestration REST API directly via reqwest.
-    let task_payload = serde_json::json!({
-        "name": "ecommerce_order_processing",
-        "namespace": "ecommerce_rs",
-        "version": "1.0.0",
-        "initiator": "axum-example-app",
-        "source_system": "example-axum",
-        "reason": "E-commerce order placed via Axum API",
-        "context": {
-            "cart_items": req.cart_items.iter().map(|item| {
-                serde_json::json!({
-                    "product_id": item.sku.parse::<i64>().unwrap_or(1),
-                    "quantity": item.quantity
-                })
-            }).collect::<Vec<_>>(),
-            "customer_email": req.customer_email,
-            "customer_name": req.customer_email.split('@').next().unwrap_or("Customer"),
-            "payment_method": "credit_card",
-            "payment_token": req.payment_token,
-            "payment_amount": total,
-            "shipping_address": req.shipping_address,
-            "app_order_id": order.id
-        }
-    });
+    // Submitted through the orchestration REST API.
+    let task_payload = order_task_payload(
+        &req,
+        &cart_items,
+        total,
+        order.id,
+        &attribution,
+        &request_id,
+    );
 
     // Submit task to Tasker orchestration
-    let task_uuid = match submit_task_to_orchestration(&task_payload).await {
-        Ok(uuid) => Some(uuid),
+    let task_uuid = match client.create_task(&task_payload).await {
+        Ok(uuid) => uuid,
         Err(e) => {
             error!("Failed to submit task to orchestration: {}", e);
-            None
+            return Err(fail_submission(&pool, "orders", order.id, &e).await);
         }
     };
 
     // Update order with task UUID and status
-    if let Some(ref uuid) = task_uuid {
-        let _ = sqlx::query("UPDATE orders SET task_uuid = $1, status = 'processing' WHERE id = $2")
-            .bind(uuid)
-            .bind(order.id)
-            .execute(&pool)
-            .await;
-    }
+    let _ = sqlx::query("UPDATE orders SET task_uuid = $1, status = 'processing' WHERE id = $2")
+        .bind(task_uuid)
+        .bind(order.id)
+        .execute(&pool)
+        .await;
 
     let response = OrderResponse {
         id: order.id,
         customer_email: order.customer_email,
-        status: if task_uuid.is_some() {
-            "processing".to_string()
-        } else {
-            "pending".to_string()
-        },
-        task_uuid,
+        status: "processing".to_string(),
+        task_uuid: Some(task_uuid),
         created_at: order.created_at,
+        request_id: request_id.0,
     };
 
     Ok((
@@ -129,74 +194,80 @@ async fn create_order(
 /// Tasker workflow and updates the order record asynchronously.
 async fn create_order_async(
     Extension(pool): Extension<AppDb>,
-    Json(req): Json<CreateOrderRequest>,
-) -> Result<(StatusCode, Json<ApiResponse<OrderResponse>>), StatusCode> {
-    let total: f64 = req
-        .cart_items
-        .iter()
-        .map(|item| item.unit_price * item.quantity as f64)
-        .sum();
+    Extension(client): Extension<OrchestrationClient>,
+    attribution: TaskAttribution,
+    request_id: RequestId,
+    AliasedJson(mut req): AliasedJson<CreateOrderRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<OrderResponse>>), ApiError> {
+    req.customer_email = normalize_email(&req.customer_email);
+    req.validate()?;
+    validate_tags(&req.tags)?;
+    let cart_items = resolve_cart_items(&pool, &req.cart_items).await?;
+
+    let total = order_total(&req.cart_items, &req.currency());
     let items_json = serde_json::to_value(&req.cart_items).unwrap_or_default();
 
     let order: Order = sqlx::query_as(
         r#"
-        INSERT INTO orders (customer_email, items, total, status)
-        VALUES ($1, $2, $3, 'queued')
+        INSERT INTO orders (customer_email, items, total, status, tags, shipping_address, currency)
+        VALUES ($1, $2, $3, 'queued', $4, $5, $6)
         RETURNING *
         "#,
     )
     .bind(&req.customer_email)
     .bind(&items_json)
     .bind(total)
+    .bind(sqlx::types::Json(&req.tags))
+    .bind(sqlx::types::Json(&req.shipping_address))
+    .bind(req.currency())
     .fetch_one(&pool)
-    .await
-    .map_err(|e| {
-        error!("Failed to insert order: {}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    .await?;
 
     let order_id = order.id;
-    let customer_email = req.customer_email.clone();
 
     // Build the task payload before moving into spawn
-    let task_payload = serde_json::json!({
-        "name": "ecommerce_order_processing",
-        "namespace": "ecommerce_rs",
-        "version": "1.0.0",
-        "initiator": "axum-example-app",
-        "source_system": "example-axum",
-        "reason": format!("E-commerce order #{} (async)", order_id),
-        "context": {
-            "cart_items": req.cart_items.iter().map(|item| {
-                serde_json::json!({
-                    "product_id": item.sku.parse::<i64>().unwrap_or(1),
-                    "quantity": item.quantity
-                })
-            }).collect::<Vec<_>>(),
-            "customer_email": customer_email,
-            "payment_method": "credit_card",
-            "payment_token": req.payment_token,
-            "payment_amount": total,
-            "shipping_address": req.shipping_address,
-            "app_order_id": order_id
-        }
-    });
+    let mut task_payload = order_task_payload(
+        &req,
+        &cart_items,
+        total,
+        order_id,
+        &attribution,
+        &request_id,
+    );
+    task_payload["reason"] = serde_json::json!(format!("E-commerce order #{} (async)", order_id));
 
     let bg_pool = pool.clone();
     tokio::spawn(async move {
-        match submit_task_to_orchestration(&task_payload).await {
+        match client.create_task(&task_payload).await {
             Ok(uuid) => {
-                let _ = sqlx::query(
+                let updated = sqlx::query(
                     "UPDATE orders SET task_uuid = $1, status = 'processing' WHERE id = $2",
                 )
                 .bind(uuid)
                 .bind(order_id)
                 .execute(&bg_pool)
                 .await;
-                info!("Background: created task {} for order {}", uuid, order_id);
+                match updated {
+                    Ok(_) => info!("Background: created task {} for order {}", uuid, order_id),
+                    Err(e) => error!(
+                        "Background: created task {} but failed to record it on order {}: {}",
+                        uuid, order_id, e
+                    ),
+                }
             }
             Err(e) => {
                 error!("Background: failed to create task for order {}: {}", order_id, e);
+                let stored = sqlx::query("UPDATE orders SET task_request = $1 WHERE id = $2")
+                    .bind(&task_payload)
+                    .bind(order_id)
+                    .execute(&bg_pool)
+                    .await;
+                if let Err(e) = stored {
+                    error!(
+                        "Background: failed to store the task request for order {}: {}",
+                        order_id, e
+                    );
+                }
             }
         }
     });
@@ -207,6 +278,7 @@ async fn create_order_async(
         status: "queued".to_string(),
         task_uuid: None,
         created_at: order.created_at,
+        request_id: request_id.0,
     };
 
     Ok((
@@ -218,51 +290,539 @@ async fn create_order_async(
     ))
 }
 
+/// Create several orders in one request.
+///
+/// 1. Reject an empty batch (422) or one over [`MAX_BATCH_ORDERS`] (400)
+/// 2. Check each order like a `POST /orders` body; an order that fails is
+///    reported by its index and the rest go ahead
+/// 3. Insert the valid orders with status=pending in one transaction
+/// 4. Submit their workflow tasks concurrently; an order whose submission
+///    fails is marked failed, as in `POST /orders`
+/// 5. Return every order's result in request order: 201 if any order was
+///    created, 200 if none was
+///
+/// The response carries [`SubmittedWorkflows`], so a tenant's daily quota is
+/// only charged for the orders that were created. `Idempotency-Key` is not
+/// supported for batches.
+async fn create_order_batch(
+    Extension(pool): Extension<AppDb>,
+    Extension(client): Extension<OrchestrationClient>,
+    Extension(aliases): Extension<Arc<FieldAliases>>,
+    attribution: TaskAttribution,
+    request_id: RequestId,
+    AliasedJson(batch): AliasedJson<CreateOrderBatchRequest>,
+) -> Result<
+    (
+        StatusCode,
+        Extension<SubmittedWorkflows>,
+        Json<ApiResponse<Vec<BatchOrderResult>>>,
+    ),
+    ApiError,
+> {
+    let count = batch.orders.len();
+    if count == 0 {
+        return Err(ApiError::validation(
+            "orders",
+            "orders must contain at least one order",
+        ));
+    }
+    if count > MAX_BATCH_ORDERS {
+        return Err(ApiError::BadRequest(format!(
+            "a batch holds at most {} orders, got {}",
+            MAX_BATCH_ORDERS, count
+        )));
+    }
+
+    let mut results = Vec::with_capacity(count);
+    let mut valid = Vec::with_capacity(count);
+    for (index, mut body) in batch.orders.into_iter().enumerate() {
+        aliases.apply(CreateOrderRequest::SCOPE, &mut body);
+        match parse_batch_order(body) {
+            Ok(req) => valid.push((index, req)),
+            Err(e) => results.push(batch_failure(index, None, &e)),
+        }
+    }
+
+    // Resolve every SKU in the batch with one query
+    let skus: Vec<String> = valid
+        .iter()
+        .flat_map(|(_, req)| req.cart_items.iter().map(|item| item.sku.clone()))
+        .collect();
+    let product_ids = product_ids_for_skus(&pool, &skus).await?;
+    let mut resolved = Vec::with_capacity(valid.len());
+    for (index, req) in valid {
+        match cart_items_context(&req.cart_items, &product_ids) {
+            Ok(cart_items) => resolved.push((index, req, cart_items)),
+            Err(e) => results.push(batch_failure(index, None, &e)),
+        }
+    }
+
+    let mut tx = pool.begin().await?;
+    let mut inserted = Vec::with_capacity(resolved.len());
+    for (index, req, cart_items) in resolved {
+        let total = order_total(&req.cart_items, &req.currency());
+        let items_json = serde_json::to_value(&req.cart_items).unwrap_or_default();
+        let order: Order = sqlx::query_as(
+            r#"
+            INSERT INTO orders (customer_email, items, total, status, tags, shipping_address, currency)
+            VALUES ($1, $2, $3, 'pending', $4, $5, $6)
+            RETURNING *
+            "#,
+        )
+        .bind(&req.customer_email)
+        .bind(&items_json)
+        .bind(total)
+        .bind(sqlx::types::Json(&req.tags))
+        .bind(sqlx::types::Json(&req.shipping_address))
+        .bind(req.currency())
+        .fetch_one(&mut *tx)
+        .await?;
+        let task_payload = order_task_payload(
+            &req,
+            &cart_items,
+            total,
+            order.id,
+            &attribution,
+            &request_id,
+        );
+        inserted.push((index, order.id, task_payload));
+    }
+    tx.commit().await?;
+    info!(
+        "Order batch: inserted {} of {} orders",
+        inserted.len(),
+        count
+    );
+
+    let submissions = inserted.iter().map(|(index, order_id, task_payload)| {
+        let (pool, client) = (&pool, &client);
+        async move {
+            match client.create_task(task_payload).await {
+                Ok(task_uuid) => {
+                    let _ = sqlx::query(
+                        "UPDATE orders SET task_uuid = $1, status = 'processing' WHERE id = $2",
+                    )
+                    .bind(task_uuid)
+                    .bind(order_id)
+                    .execute(pool)
+                    .await;
+                    BatchOrderResult {
+                        index: *index,
+                        success: true,
+                        id: Some(*order_id),
+                        task_uuid: Some(task_uuid),
+                        error: None,
+                    }
+                }
+                Err(e) => {
+                    error!("Failed to submit task for order {}: {}", order_id, e);
+                    let error = fail_submission(pool, "orders", *order_id, &e).await;
+                    batch_failure(*index, Some(*order_id), &error)
+                }
+            }
+        }
+    });
+    results.extend(join_all(submissions).await);
+    results.sort_by_key(|result| result.index);
+
+    let created = results.iter().filter(|result| result.success).count();
+    let status = if created > 0 {
+        StatusCode::CREATED
+    } else {
+        StatusCode::OK
+    };
+    Ok((
+        status,
+        Extension(SubmittedWorkflows(created)),
+        Json(ApiResponse {
+            message: format!("{} of {} orders created", created, count),
+            data: results,
+        }),
+    ))
+}
+
+/// Parse and check one order of a batch, as `create_order` does.
+fn parse_batch_order(body: serde_json::Value) -> Result<CreateOrderRequest, ApiError> {
+    let mut req: CreateOrderRequest =
+        serde_json::from_value(body).map_err(|e| ApiError::validation("body", e.to_string()))?;
+    req.customer_email = normalize_email(&req.customer_email);
+    req.validate()?;
+    validate_tags(&req.tags)?;
+    Ok(req)
+}
+
+/// The result for a batch order that was rejected, or whose task submission
+/// failed after its row (`id`) was inserted.
+fn batch_failure(index: usize, id: Option<i32>, error: &ApiError) -> BatchOrderResult {
+    BatchOrderResult {
+        index,
+        success: false,
+        id,
+        task_uuid: None,
+        error: Some(error.body()["error"].clone()),
+    }
+}
+
+/// List orders, newest first, optionally filtered by `?status=` and
+/// `?tag.<key>=<value>`. Archived rows are excluded unless
+/// `?include_archived=true`. Pages with `?cursor=` (or `?offset=`) and
+/// `?limit=` (see [`crate::pagination`]); `total` counts every matching order.
+async fn list_orders(
+    Extension(pool): Extension<AppDb>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Result<Json<PageResponse<Order>>, ApiError> {
+    let filter = tag_filter(&query)?;
+    let include_archived = include_archived(&query)?;
+    let page = PageParams::from_query(&query)?;
+    let status = query.get("status").map(|s| s.trim()).filter(|s| !s.is_empty());
+
+    let rows: Vec<Order> = sqlx::query_as(
+        "SELECT * FROM orders WHERE tags @> $1 AND ($3 OR archived_at IS NULL) \
+         AND ($7::text IS NULL OR status = $7) \
+         AND ($4::timestamp IS NULL OR (created_at, id) < ($4, $5)) \
+         ORDER BY created_at DESC, id DESC LIMIT $2 OFFSET $6",
+    )
+    .bind(&filter)
+    .bind(page.fetch_limit())
+    .bind(include_archived)
+    .bind(page.cursor_created_at())
+    .bind(page.cursor_id())
+    .bind(page.offset)
+    .bind(status)
+    .fetch_all(&pool)
+    .await?;
+    let (rows, next_cursor) = page.finish(rows, |row| Cursor::new(row.created_at, row.id));
+
+    let total: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM orders WHERE tags @> $1 AND ($2 OR archived_at IS NULL) \
+         AND ($3::text IS NULL OR status = $3)",
+    )
+    .bind(&filter)
+    .bind(include_archived)
+    .bind(status)
+    .fetch_one(&pool)
+    .await?;
+
+    Ok(Json(PageResponse {
+        message: format!("{} of {} orders", rows.len(), total),
+        data: rows,
+        next_cursor,
+        total: Some(total),
+    }))
+}
+
 /// Retrieve an order by ID.
 async fn get_order(
     Extension(pool): Extension<AppDb>,
+    Extension(client): Extension<OrchestrationClient>,
     Path(id): Path<i32>,
-) -> Result<Json<ApiResponse<Order>>, StatusCode> {
+) -> Result<Json<ApiResponse<Estimated<Order>>>, ApiError> {
     let order: Order = sqlx::query_as("SELECT * FROM orders WHERE id = $1")
         .bind(id)
         .fetch_optional(&pool)
-        .await
-        .map_err(|e| {
-            error!("Failed to query order: {}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?
-        .ok_or(StatusCode::NOT_FOUND)?;
+        .await?
+        .ok_or(ApiError::NotFound)?;
+
+    let estimated_completion_at = estimated_completion_at(
+        &pool,
+        &client,
+        "orders",
+        &order.status,
+        order.task_uuid,
+    )
+    .await;
 
     Ok(Json(ApiResponse {
-        data: order,
+        data: Estimated {
+            row: order,
+            estimated_completion_at,
+        },
         message: "Order retrieved".to_string(),
     }))
 }
 
-/// Submit a task to the Tasker orchestration REST API and return the task UUID.
-async fn submit_task_to_orchestration(
-    payload: &serde_json::Value,
-) -> anyhow::Result<uuid::Uuid> {
-    let orchestration_url =
-        std::env::var("ORCHESTRATION_URL").unwrap_or_else(|_| "http://localhost:8080".to_string());
-
-    let client = reqwest::Client::new();
-    let response = client
-        .post(format!("{}/v1/tasks", orchestration_url))
-        .json(payload)
-        .send()
-        .await?;
+/// Update an order's shipping address and/or customer email.
+///
+/// A running order's task context is updated first, then the row; 409 if the
+/// order finished, or its workflow already ran the first step reading a changed
+/// field. An order still awaiting submission only has its row (and the saved
+/// task request the sweeper resubmits) updated.
+async fn update_order(
+    Extension(pool): Extension<AppDb>,
+    Extension(client): Extension<OrchestrationClient>,
+    Path(id): Path<i32>,
+    AliasedJson(req): AliasedJson<UpdateOrderRequest>,
+) -> Result<Json<ApiResponse<Order>>, ApiError> {
+    let mut context = serde_json::Map::new();
+    let mut first_step = i64::MAX;
+    if let Some(address) = &req.shipping_address {
+        address.validate()?;
+        context.insert("shipping_address".to_string(), serde_json::json!(address));
+        first_step = first_step.min(SHIPPING_ADDRESS_STEP);
+    }
+    if let Some(email) = &req.customer_email {
+        let email = normalize_email(email);
+        validate_email("customer_email", &email)?;
+        context.insert("customer_email".to_string(), serde_json::json!(email));
+        first_step = first_step.min(CUSTOMER_EMAIL_STEP);
+    }
+    if context.is_empty() {
+        return Err(ApiError::validation(
+            "body",
+            "provide shipping_address and/or customer_email",
+        ));
+    }
+    let context = serde_json::Value::Object(context);
 
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().await.unwrap_or_default();
-        anyhow::bail!("Orchestration returned {}: {}", status, body);
+    let order: Order = sqlx::query_as("SELECT * FROM orders WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+    if !UPDATABLE_STATUSES.contains(&order.status.as_str()) {
+        return Err(StatusCode::CONFLICT.into());
+    }
+
+    if let Some(task_uuid) = order.task_uuid {
+        let task = client.get_task(task_uuid).await.map_err(|e| {
+            error!("Failed to fetch task {} for order {}: {}", task_uuid, id, e);
+            ApiError::Upstream(e.to_string())
+        })?;
+        // Unknown progress is treated as too late
+        if completed_steps(&task).is_none_or(|completed| completed >= first_step) {
+            return Err(StatusCode::CONFLICT.into());
+        }
+        client
+            .update_task_context(task_uuid, &context)
+            .await
+            .map_err(|e| match e {
+                OrchestrationError::Status { status, .. } if status == StatusCode::CONFLICT => {
+                    ApiError::from(StatusCode::CONFLICT)
+                }
+                e => {
+                    error!("Failed to update task {} for order {}: {}", task_uuid, id, e);
+                    ApiError::Upstream(e.to_string())
+                }
+            })?;
     }
 
-    let body: serde_json::Value = response.json().await?;
-    let task_uuid_str = body["task_uuid"]
-        .as_str()
-        .ok_or_else(|| anyhow::anyhow!("Missing task_uuid in orchestration response"))?;
-    let task_uuid = uuid::Uuid::parse_str(task_uuid_str)?;
-    Ok(task_uuid)
+    // Conditional on the row being unchanged since it was read, so a
+    // concurrent submission or status change is a 409 rather than lost
+    let order: Order = sqlx::query_as(
+        r#"
+        UPDATE orders SET
+            shipping_address = COALESCE($2, shipping_address),
+            customer_email = COALESCE($3, customer_email),
+            task_request = CASE
+                WHEN task_request IS NULL THEN NULL
+                ELSE jsonb_set(task_request, '{context}', COALESCE(task_request->'context', '{}') || $4)
+            END,
+            updated_at = NOW()
+        WHERE id = $1 AND status = $5 AND task_uuid IS NOT DISTINCT FROM $6
+        RETURNING *
+        "#,
+    )
+    .bind(id)
+    .bind(context.get("shipping_address"))
+    .bind(context["customer_email"].as_str())
+    .bind(&context)
+    .bind(&order.status)
+    .bind(order.task_uuid)
+    .fetch_optional(&pool)
+    .await?
+    .ok_or(StatusCode::CONFLICT)?;
+
+    info!("Order {} updated", order.id);
+
+    Ok(Json(ApiResponse {
+        data: order,
+        message: "Order updated".to_string(),
+    }))
+}
+
+/// Report the workflow task of an order (see [`crate::task_status`]).
+async fn get_order_task(
+    Extension(pool): Extension<AppDb>,
+    Extension(client): Extension<OrchestrationClient>,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<TaskStatus>>, ApiError> {
+    let task = row_task_status(&pool, &client, "orders", id).await?;
+    Ok(Json(ApiResponse {
+        data: task,
+        message: "Task status retrieved".to_string(),
+    }))
+}
+
+/// Stream the progress of an order's task (see [`crate::task_events`]).
+async fn stream_order_events(
+    Extension(pool): Extension<AppDb>,
+    Extension(client): Extension<OrchestrationClient>,
+    Extension(config): Extension<TaskEventsConfig>,
+    Path(id): Path<i32>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    let task_uuid = row_task_uuid(&pool, "orders", id).await?;
+    Ok(Sse::new(task_events(client, task_uuid, config)).keep_alive(KeepAlive::default()))
+}
+
+/// Cancel an order, and its workflow task if one was submitted (see
+/// [`cancel_row`]).
+async fn cancel_order(
+    Extension(pool): Extension<AppDb>,
+    Extension(client): Extension<OrchestrationClient>,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<Order>>, ApiError> {
+    let order: Order = cancel_row(&pool, &client, "orders", id).await?;
+
+    info!("Order {} cancelled", order.id);
+
+    Ok(Json(ApiResponse {
+        data: order,
+        message: "Order cancelled".to_string(),
+    }))
+}
+
+/// Retry the failed steps of an order's workflow task and move the order back
+/// to `processing`.
+///
+/// Only a task in a failure state ([`RETRYABLE_TASK_STATUSES`]) is retried; a
+/// task that completed or is still running is a 409, as is a retry
+/// orchestration refuses. The order is only moved back from `failed` or
+/// `processing`, so one cancelled or completed meanwhile is a 409 too. 404 if
+/// there is no such order or it has no task.
+async fn retry_order(
+    Extension(pool): Extension<AppDb>,
+    Extension(client): Extension<OrchestrationClient>,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<Order>>, ApiError> {
+    let task = row_task_status(&pool, &client, "orders", id).await?;
+    let failed = task
+        .status
+        .as_deref()
+        .is_some_and(|status| RETRYABLE_TASK_STATUSES.contains(&status));
+    if !failed {
+        return Err(StatusCode::CONFLICT.into());
+    }
+
+    let task_uuid = task.task_uuid;
+    client.retry_task(task_uuid).await.map_err(|e| match e {
+        OrchestrationError::Status { status, .. } if status == StatusCode::CONFLICT => {
+            ApiError::from(StatusCode::CONFLICT)
+        }
+        e => {
+            error!("Failed to retry task {} for order {}: {}", task_uuid, id, e);
+            ApiError::Upstream(e.to_string())
+        }
+    })?;
+
+    let order: Order = sqlx::query_as(
+        r#"
+        UPDATE orders SET status = 'processing', status_reason = NULL, updated_at = NOW()
+        WHERE id = $1 AND status IN ('failed', 'processing')
+        RETURNING *
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(&pool)
+    .await?
+    .ok_or_else(|| ApiError::from(StatusCode::CONFLICT))?;
+
+    info!("Order {} retrying task {}", order.id, task_uuid);
+
+    Ok(Json(ApiResponse {
+        data: order,
+        message: "Order retry requested".to_string(),
+    }))
+}
+
+/// Completed step count of a fetched task: `completed_steps`, or derived from
+/// `completion_percentage` and `total_steps`.
+fn completed_steps(task: &serde_json::Value) -> Option<i64> {
+    task["completed_steps"].as_i64().or_else(|| {
+        let percentage = task["completion_percentage"].as_f64()?;
+        let total = task["total_steps"].as_f64()?;
+        Some((percentage / 100.0 * total).round() as i64)
+    })
+}
+
+/// Resolve each cart item's SKU to a catalog product id and build the
+/// `cart_items` task context. An unknown SKU is a 400 naming the item.
+async fn resolve_cart_items(
+    pool: &AppDb,
+    items: &[CartItemInput],
+) -> Result<Vec<serde_json::Value>, ApiError> {
+    let skus: Vec<String> = items.iter().map(|item| item.sku.clone()).collect();
+    let product_ids = product_ids_for_skus(pool, &skus).await?;
+
+    cart_items_context(items, &product_ids)
+}
+
+/// Build the `cart_items` task context from resolved product ids. An item
+/// whose SKU has no product id is a 400 naming the item and the SKU.
+pub(crate) fn cart_items_context(
+    items: &[CartItemInput],
+    product_ids: &HashMap<String, i64>,
+) -> Result<Vec<serde_json::Value>, ApiError> {
+    items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let product_id = product_ids.get(&item.sku).ok_or_else(|| {
+                ApiError::BadRequest(format!(
+                    "cart_items[{}].sku: unknown SKU '{}'",
+                    i, item.sku
+                ))
+            })?;
+            Ok(serde_json::json!({
+                "product_id": product_id,
+                "quantity": item.quantity
+            }))
+        })
+        .collect()
+}
+
+/// Order total: the sum of unit price times quantity over the cart, rounded to
+/// `currency`'s minor unit.
+pub(crate) fn order_total(items: &[CartItemInput], currency: &str) -> f64 {
+    let total: f64 = items
+        .iter()
+        .map(|item| item.unit_price * item.quantity as f64)
+        .sum();
+    round_amount(total, currency)
+}
+
+/// The `ecommerce_order_processing` task request for a newly created order.
+pub(crate) fn order_task_payload(
+    req: &CreateOrderRequest,
+    cart_items: &[serde_json::Value],
+    total: f64,
+    order_id: i32,
+    attribution: &TaskAttribution,
+    request_id: &RequestId,
+) -> serde_json::Value {
+    let mut payload = serde_json::json!({
+        "name": "ecommerce_order_processing",
+        "namespace": "ecommerce_rs",
+        "version": "1.0.0",
+        "initiator": attribution.initiator,
+        "source_system": attribution.source_system,
+        "reason": "E-commerce order placed via Axum API",
+        "context": {
+            "cart_items": cart_items,
+            "customer_email": req.customer_email,
+            "customer_name": req.customer_email.split('@').next().unwrap_or("Customer"),
+            "payment_method": "credit_card",
+            "payment_token": req.payment_token,
+            "payment_amount": total,
+            "currency": req.currency(),
+            "shipping_address": req.shipping_address,
+            "tags": req.tags,
+            "app_order_id": order_id,
+            "request_id": request_id.as_str()
+        }
+    });
+    // Applied by the cart validation step
+    if let Some(code) = &req.coupon_code {
+        payload["context"]["coupon_code"] = serde_json::json!(code);
+    }
+    payload
 }
@@ -0,0 +1,28 @@
+//! Product catalog routes.
+//!
+//! GET /products - List the products (and SKUs) orders can reference
+
+use std::sync::Arc;
+
+use axum::routing::get;
+use axum::{Extension, Json, Router};
+
+use crate::handler_registry::AxumHandlerRegistry;
+use crate::handlers::ecommerce::Product;
+use crate::models::ApiResponse;
+
+/// Build the products router.
+pub fn router() -> Router {
+    Router::new().route("/products", get(list_products))
+}
+
+/// List the catalog the e-commerce handlers validate carts against, by id.
+async fn list_products(
+    Extension(registry): Extension<Arc<AxumHandlerRegistry>>,
+) -> Json<ApiResponse<Vec<Product>>> {
+    let products = registry.products();
+    Json(ApiResponse {
+        message: format!("{} products found", products.len()),
+        data: products,
+    })
+}
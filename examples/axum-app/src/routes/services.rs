@@ -1,22 +1,55 @@
 //! Microservices user registration routes.
 //!
-//! POST /services/register - Create a user registration workflow
-//! GET  /services/:id      - Retrieve a service request by ID
+//! GET  /services              - List service requests (filter with ?tag.<key>=<value>, ?include_archived=true, page with ?cursor=)
+//! POST /services/register     - Create a user registration workflow
+//! GET  /services/:id          - Retrieve a service request by ID (with its estimated completion time)
+//! GET  /services/:id/messages - Welcome messages sent by a completed registration
+//! GET  /services/:id/task     - Report the registration's workflow task status and step states
+//! DELETE /services/:id         - Cancel a service request and its workflow task
 
-use axum::extract::Path;
+use std::collections::HashMap;
+
+use axum::extract::{Path, Query};
 use axum::http::StatusCode;
 use axum::routing::{get, post};
 use axum::{Extension, Json, Router};
 use tracing::{error, info};
 
+use crate::archiver::include_archived;
+use crate::attribution::TaskAttribution;
+use crate::cancellation::cancel_row;
 use crate::db::AppDb;
-use crate::models::{ApiResponse, CreateServiceRequest, ServiceRequest, ServiceRequestResponse};
+use crate::email::{normalize_email, validate_email};
+use crate::eta::estimated_completion_at;
+use crate::error::ApiError;
+use crate::extract::AliasedJson;
+use crate::models::{
+    ApiResponse, CreateServiceRequest, Estimated, PageResponse, ServiceRequest,
+    ServiceRequestResponse, WelcomeMessage,
+};
+use crate::orchestration::OrchestrationClient;
+use crate::pagination::{Cursor, PageParams};
+use crate::request_id::RequestId;
+use crate::step_results;
+use crate::submission::fail_submission;
+use crate::tags::{tag_filter, validate_tags};
+use crate::task_status::{fetch_task, row_task_status, step_results, TaskStatus};
+use crate::types::microservices::SendWelcomeSequenceResult;
+
+/// Step whose result lists the welcome messages.
+const WELCOME_STEP: &str = "send_welcome_sequence";
 
 /// Build the services router.
 pub fn router() -> Router {
     Router::new()
+        .route("/services", get(list_service_requests))
         .route("/services/register", post(create_registration))
-        .route("/services/{id}", get(get_service_request))
+        .route(
+            "/services/{id}",
+            get(get_service_request).delete(cancel_service_request),
+        )
+        .route("/services/{id}/task", get(get_service_request_task))
+        .route("/services/{id}/messages", get(get_welcome_messages))
 }
 
 /// Create a user registration service request and submit a microservices workflow to Tasker.
@@ -25,8 +58,15 @@ pub fn router() -> Router {
 /// CreateUser -> (SetupBilling || InitPreferences) -> SendWelcome -> UpdateStatus
 async fn create_registration(
     Extension(pool): Extension<AppDb>,
-    Json(req): Json<CreateServiceRequest>,
-) -> Result<(StatusCode, Json<ApiResponse<ServiceRequestResponse>>), StatusCode> {
+    Extension(client): Extension<OrchestrationClient>,
+    attribution: TaskAttribution,
+    request_id: RequestId,
+    AliasedJson(mut req): AliasedJson<CreateServiceRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<ServiceRequestResponse>>), ApiError> {
+    req.user_email = normalize_email(&req.user_email);
+    validate_email("user_email", &req.user_email)?;
+    validate_tags(&req.tags)?;
+
     let payload = serde_json::json!({
         "user_email": req.user_email,
         "user_name": req.user_name,
@@ -36,19 +76,16 @@ async fn create_registration(
     // Insert service request into application database
     let service_req: ServiceRequest = sqlx::query_as(
         r#"
-        INSERT INTO service_requests (service_type, user_email, payload, status)
-        VALUES ('user_registration', $1, $2, 'pending')
+        INSERT INTO service_requests (service_type, user_email, payload, status, tags)
+        VALUES ('user_registration', $1, $2, 'pending', $3)
         RETURNING *
         "#,
     )
     .bind(&req.user_email)
     .bind(&payload)
+    .bind(sqlx::types::Json(&req.tags))
     .fetch_one(&pool)
-    .await
-    .map_err(|e| {
-        error!("Failed to insert service request: {}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    .await?;
 
     info!(
         "Service request {} created for user registration: {}",
@@ -62,49 +99,46 @@ async fn create_registration(
         "name": "user_registration",
         "namespace": "microservices_rs",
         "version": "1.0.0",
-        "initiator": "axum-example-app",
-        "source_system": "example-axum",
+        "initiator": attribution.initiator,
+        "source_system": attribution.source_system,
         "reason": format!("User registration for {}", req.user_email),
         "context": {
             "email": req.user_email,
             "full_name": req.user_name,
             "plan": req.plan.as_deref().unwrap_or("free"),
             "source": "axum-example-app",
-            "app_service_request_id": service_req.id
+            "tags": req.tags,
+            "app_service_request_id": service_req.id,
+            "request_id": request_id.as_str()
         }
     });
 
     // Submit task to Tasker orchestration
-    let task_uuid = match submit_task_to_orchestration(&task_payload).await {
-        Ok(uuid) => Some(uuid),
+    let task_uuid = match client.create_task(&task_payload).await {
+        Ok(uuid) => uuid,
         Err(e) => {
             error!("Failed to submit registration task to orchestration: {}", e);
-            None
+            return Err(fail_submission(&pool, "service_requests", service_req.id, &e).await);
         }
     };
 
     // Update service request with task UUID
-    if let Some(ref uuid) = task_uuid {
-        let _ = sqlx::query(
-            "UPDATE service_requests SET task_uuid = $1, status = 'processing' WHERE id = $2",
-        )
-        .bind(uuid)
-        .bind(service_req.id)
-        .execute(&pool)
-        .await;
-    }
+    let _ = sqlx::query(
+        "UPDATE service_requests SET task_uuid = $1, status = 'processing' WHERE id = $2",
+    )
+    .bind(task_uuid)
+    .bind(service_req.id)
+    .execute(&pool)
+    .await;
 
     let response = ServiceRequestResponse {
         id: service_req.id,
         service_type: service_req.service_type,
         user_email: service_req.user_email,
-        status: if task_uuid.is_some() {
-            "processing".to_string()
-        } else {
-            "pending".to_string()
-        },
-        task_uuid,
+        status: "processing".to_string(),
+        task_uuid: Some(task_uuid),
         created_at: service_req.created_at,
+        request_id: request_id.0,
     };
 
     Ok((
@@ -116,52 +150,147 @@ async fn create_registration(
     ))
 }
 
+/// List service requests, newest first, optionally filtered by `?tag.<key>=<value>`.
+/// Archived rows are excluded unless `?include_archived=true`. Pages with
+/// `?cursor=` and `?limit=` (see [`crate::pagination`]).
+async fn list_service_requests(
+    Extension(pool): Extension<AppDb>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Result<Json<PageResponse<ServiceRequest>>, ApiError> {
+    let filter = tag_filter(&query)?;
+    let include_archived = include_archived(&query)?;
+    let page = PageParams::from_query(&query)?;
+
+    let rows: Vec<ServiceRequest> = sqlx::query_as(
+        "SELECT * FROM service_requests WHERE tags @> $1 AND ($3 OR archived_at IS NULL) \
+         AND ($4::timestamp IS NULL OR (created_at, id) < ($4, $5)) \
+         ORDER BY created_at DESC, id DESC LIMIT $2 OFFSET $6",
+    )
+    .bind(&filter)
+    .bind(page.fetch_limit())
+    .bind(include_archived)
+    .bind(page.cursor_created_at())
+    .bind(page.cursor_id())
+    .bind(page.offset)
+    .fetch_all(&pool)
+    .await?;
+    let (rows, next_cursor) = page.finish(rows, |row| Cursor::new(row.created_at, row.id));
+
+    Ok(Json(PageResponse {
+        message: format!("{} service requests found", rows.len()),
+        data: rows,
+        next_cursor,
+        total: None,
+    }))
+}
+
 /// Retrieve a service request by ID.
 async fn get_service_request(
     Extension(pool): Extension<AppDb>,
+    Extension(client): Extension<OrchestrationClient>,
     Path(id): Path<i32>,
-) -> Result<Json<ApiResponse<ServiceRequest>>, StatusCode> {
+) -> Result<Json<ApiResponse<Estimated<ServiceRequest>>>, ApiError> {
     let service_req: ServiceRequest =
         sqlx::query_as("SELECT * FROM service_requests WHERE id = $1")
             .bind(id)
             .fetch_optional(&pool)
-            .await
-            .map_err(|e| {
-                error!("Failed to query service request: {}", e);
-                StatusCode::INTERNAL_SERVER_ERROR
-            })?
-            .ok_or(StatusCode::NOT_FOUND)?;
+            .await?
+            .ok_or(ApiError::NotFound)?;
+
+    let estimated_completion_at = estimated_completion_at(
+        &pool,
+        &client,
+        "service_requests",
+        &service_req.status,
+        service_req.task_uuid,
+    )
+    .await;
 
     Ok(Json(ApiResponse {
-        data: service_req,
+        data: Estimated {
+            row: service_req,
+            estimated_completion_at,
+        },
         message: "Service request retrieved".to_string(),
     }))
 }
 
-/// Submit a task to the Tasker orchestration REST API and return the task UUID.
-async fn submit_task_to_orchestration(
-    payload: &serde_json::Value,
-) -> anyhow::Result<uuid::Uuid> {
-    let orchestration_url =
-        std::env::var("ORCHESTRATION_URL").unwrap_or_else(|_| "http://localhost:8080".to_string());
-
-    let client = reqwest::Client::new();
-    let response = client
-        .post(format!("{}/v1/tasks", orchestration_url))
-        .json(payload)
-        .send()
-        .await?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().await.unwrap_or_default();
-        anyhow::bail!("Orchestration returned {}: {}", status, body);
+/// Cancel a service request, and its workflow task if one was submitted (see
+/// [`cancel_row`]).
+async fn cancel_service_request(
+    Extension(pool): Extension<AppDb>,
+    Extension(client): Extension<OrchestrationClient>,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<ServiceRequest>>, ApiError> {
+    let request: ServiceRequest = cancel_row(&pool, &client, "service_requests", id).await?;
+
+    info!("Service request {} cancelled", request.id);
+
+    Ok(Json(ApiResponse {
+        data: request,
+        message: "Service request cancelled".to_string(),
+    }))
+}
+
+/// Report the workflow task of a service request (see [`crate::task_status`]).
+async fn get_service_request_task(
+    Extension(pool): Extension<AppDb>,
+    Extension(client): Extension<OrchestrationClient>,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<TaskStatus>>, ApiError> {
+    let task = row_task_status(&pool, &client, "service_requests", id).await?;
+    Ok(Json(ApiResponse {
+        data: task,
+        message: "Task status retrieved".to_string(),
+    }))
+}
+
+/// List the welcome messages the registration's `send_welcome_sequence` step sent.
+///
+/// Read from the locally persisted step result when the worker records them
+/// (`PERSIST_STEP_RESULTS`), and otherwise from the step's results in the task
+/// fetched from orchestration. Returns 425 Too Early until the registration
+/// has completed, and 404 if neither has a result.
+async fn get_welcome_messages(
+    Extension(pool): Extension<AppDb>,
+    Extension(client): Extension<OrchestrationClient>,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<Vec<WelcomeMessage>>>, ApiError> {
+    let service_req: ServiceRequest =
+        sqlx::query_as("SELECT * FROM service_requests WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&pool)
+            .await?
+            .ok_or(ApiError::NotFound)?;
+
+    if !matches!(service_req.status.as_str(), "complete" | "completed") {
+        return Err(StatusCode::TOO_EARLY.into());
     }
+    let task_uuid = service_req.task_uuid.ok_or(ApiError::NotFound)?;
 
-    let body: serde_json::Value = response.json().await?;
-    let task_uuid_str = body["task_uuid"]
-        .as_str()
-        .ok_or_else(|| anyhow::anyhow!("Missing task_uuid in orchestration response"))?;
-    let task_uuid = uuid::Uuid::parse_str(task_uuid_str)?;
-    Ok(task_uuid)
+    let record = step_results::for_step(&pool, task_uuid, WELCOME_STEP).await?;
+    let result = match record {
+        Some(record) => record.result,
+        None => step_results(&fetch_task(&client, task_uuid).await?, WELCOME_STEP),
+    };
+    let result: SendWelcomeSequenceResult = result
+        .and_then(|value| serde_json::from_value(value).ok())
+        .ok_or(ApiError::NotFound)?;
+
+    let messages: Vec<WelcomeMessage> = result
+        .messages_sent_details
+        .unwrap_or_default()
+        .into_iter()
+        .map(|message| WelcomeMessage {
+            channel: message.channel,
+            title: message.title,
+            template: message.template,
+            status: message.status,
+        })
+        .collect();
+
+    Ok(Json(ApiResponse {
+        message: format!("{} welcome messages sent", messages.len()),
+        data: messages,
+    }))
 }
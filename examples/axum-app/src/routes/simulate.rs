@@ -0,0 +1,72 @@
+//! Workflow simulation routes.
+//!
+//! GET  /simulate            - List the workflows that can be simulated
+//! POST /simulate/:workflow  - Run a workflow's handlers in-process (no orchestration)
+//!
+//! Simulation requires the admin token (see `admin_auth`) and runs against a
+//! [`AxumHandlerRegistry::sandbox`] of the app's registry, so it never charges
+//! the configured payment gateway, sends real messages, or changes shared
+//! handler state.
+
+use std::sync::Arc;
+
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::middleware;
+use axum::routing::{get, post};
+use axum::{Extension, Json, Router};
+
+use crate::admin_auth::require_admin;
+use crate::error::ApiError;
+use crate::handler_registry::AxumHandlerRegistry;
+use crate::models::ApiResponse;
+use crate::simulate::{simulate, workflow_names, workflow_template, SimulationResult};
+
+/// Build the simulation router.
+pub fn router() -> Router {
+    Router::new()
+        .route("/simulate", get(list_workflows))
+        .merge(
+            Router::new()
+                .route("/simulate/{workflow}", post(simulate_workflow))
+                .route_layer(middleware::from_fn(require_admin)),
+        )
+}
+
+/// List the workflow names accepted by `POST /simulate/{workflow}`.
+async fn list_workflows() -> Json<ApiResponse<Vec<&'static str>>> {
+    let names = workflow_names();
+    Json(ApiResponse {
+        message: format!("{} workflows can be simulated", names.len()),
+        data: names,
+    })
+}
+
+/// Run every step of a workflow in dependency order with the request body as
+/// the task context, and return each step's result.
+///
+/// The handlers run on the blocking thread pool against a sandbox registry.
+/// Nothing is stored or submitted. A failing handler is reported in the
+/// response (`status: "error"`) rather than as an HTTP error.
+async fn simulate_workflow(
+    Extension(registry): Extension<Arc<AxumHandlerRegistry>>,
+    Path(workflow): Path<String>,
+    Json(context): Json<serde_json::Value>,
+) -> Result<Json<ApiResponse<SimulationResult>>, ApiError> {
+    let template = workflow_template(&workflow).ok_or(ApiError::NotFound)?;
+    let sandbox = registry.sandbox();
+    let name = workflow.clone();
+    let simulation =
+        tokio::task::spawn_blocking(move || simulate(&sandbox, &name, &template, &context))
+            .await
+            .map_err(|_| ApiError::from(StatusCode::INTERNAL_SERVER_ERROR))??;
+
+    let message = match &simulation.failed_step {
+        None => format!("Simulated {} steps of {}", simulation.steps.len(), workflow),
+        Some(step) => format!("Simulation of {} failed at {}", workflow, step),
+    };
+    Ok(Json(ApiResponse {
+        message,
+        data: simulation,
+    }))
+}
@@ -0,0 +1,104 @@
+//! Task routes: orchestration tasks by UUID, without a domain row.
+//!
+//! GET  /tasks/:uuid        - Fetch a task from orchestration, as orchestration returns it
+//! POST /tasks/:uuid/cancel - Cancel a task
+//!
+//! For clients that hold a task UUID but not the domain id. Requests go through
+//! the shared [`OrchestrationClient`], so no orchestration credentials are
+//! needed. A malformed UUID is a 400 and a task orchestration does not know is a
+//! 404. Cancelling here leaves the domain row as it is; prefer the domain cancel
+//! routes (e.g. `DELETE /orders/:id`) when the row is known.
+//!
+//! Cancelling can stop any task the app's API key reaches, not only the app's
+//! own, so like the admin routes it requires `Authorization: Bearer
+//! $ADMIN_TOKEN` when `ADMIN_TOKEN` is set.
+
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::routing::{get, post};
+use axum::{middleware, Extension, Json, Router};
+use serde::Serialize;
+use serde_json::Value;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::admin_auth::require_admin;
+use crate::error::ApiError;
+use crate::models::ApiResponse;
+use crate::orchestration::{OrchestrationClient, OrchestrationError};
+
+/// Build the tasks router.
+pub fn router() -> Router {
+    let cancel = Router::new()
+        .route("/tasks/{uuid}/cancel", post(cancel_task))
+        .route_layer(middleware::from_fn(require_admin));
+    Router::new()
+        .route("/tasks/{uuid}", get(get_task))
+        .merge(cancel)
+}
+
+/// A task cancelled through `POST /tasks/{uuid}/cancel`.
+#[derive(Debug, Serialize)]
+pub struct CancelledTask {
+    pub task_uuid: Uuid,
+    pub cancelled: bool,
+}
+
+/// Fetch a task from orchestration.
+async fn get_task(
+    Extension(client): Extension<OrchestrationClient>,
+    Path(uuid): Path<String>,
+) -> Result<Json<ApiResponse<Value>>, ApiError> {
+    let task_uuid = parse_task_uuid(&uuid)?;
+    let task = client
+        .get_task(task_uuid)
+        .await
+        .map_err(|e| task_error(task_uuid, "fetch", e))?;
+
+    Ok(Json(ApiResponse {
+        data: task,
+        message: "Task retrieved".to_string(),
+    }))
+}
+
+/// Cancel a task in orchestration. A task that can no longer be cancelled is
+/// a 409.
+async fn cancel_task(
+    Extension(client): Extension<OrchestrationClient>,
+    Path(uuid): Path<String>,
+) -> Result<Json<ApiResponse<CancelledTask>>, ApiError> {
+    let task_uuid = parse_task_uuid(&uuid)?;
+    client
+        .cancel_task(task_uuid)
+        .await
+        .map_err(|e| task_error(task_uuid, "cancel", e))?;
+    info!("Cancelled task {}", task_uuid);
+
+    Ok(Json(ApiResponse {
+        data: CancelledTask {
+            task_uuid,
+            cancelled: true,
+        },
+        message: format!("Task {} cancelled", task_uuid),
+    }))
+}
+
+/// Parse the `{uuid}` path segment; 400 when it is not a UUID.
+pub(crate) fn parse_task_uuid(raw: &str) -> Result<Uuid, ApiError> {
+    Uuid::parse_str(raw).map_err(|_| ApiError::BadRequest(format!("invalid task UUID '{}'", raw)))
+}
+
+/// Pass orchestration's 404 and 409 through; anything else is a 502.
+fn task_error(task_uuid: Uuid, action: &str, e: OrchestrationError) -> ApiError {
+    match e {
+        OrchestrationError::Status { status, .. }
+            if status == StatusCode::NOT_FOUND || status == StatusCode::CONFLICT =>
+        {
+            ApiError::from(status)
+        }
+        e => {
+            error!("Failed to {} task {}: {}", action, task_uuid, e);
+            ApiError::Upstream(e.to_string())
+        }
+    }
+}
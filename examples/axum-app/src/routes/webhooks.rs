@@ -0,0 +1,74 @@
+//! Webhook routes: task state changes pushed by orchestration.
+//!
+//! POST /webhooks/tasks  - Apply a task's new status to the domain row that
+//!                         submitted it (`{task_uuid, status, completion_percentage}`)
+//!
+//! This is the push counterpart to the status reconciler, which polls
+//! orchestration. Requests must be signed with `WEBHOOK_SECRET`
+//! (see [`crate::webhook_auth`]); unsigned or mis-signed requests are a 401.
+
+use axum::body::Bytes;
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use axum::{Extension, Json, Router};
+use serde::Deserialize;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::db::AppDb;
+use crate::error::ApiError;
+use crate::models::ApiResponse;
+use crate::reconciler::{apply_task_status, TaskStatusUpdate};
+use crate::webhook_auth::{WebhookAuth, SIGNATURE_HEADER};
+
+/// Build the webhooks router.
+pub fn router() -> Router {
+    Router::new().route("/webhooks/tasks", post(task_state_changed))
+}
+
+/// A task state change.
+#[derive(Debug, Deserialize)]
+pub struct TaskWebhook {
+    pub task_uuid: Uuid,
+    /// The task's orchestration status, e.g. `complete` or `error`.
+    pub status: String,
+    pub completion_percentage: Option<f64>,
+}
+
+/// Verify the signature, then move the task's domain row to its final status
+/// once the task has finished.
+async fn task_state_changed(
+    Extension(pool): Extension<AppDb>,
+    Extension(auth): Extension<WebhookAuth>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<ApiResponse<TaskStatusUpdate>>, ApiError> {
+    let signature = headers
+        .get(SIGNATURE_HEADER)
+        .and_then(|value| value.to_str().ok());
+    if !auth.verify(&body, signature) {
+        warn!("Rejected task webhook with a missing or invalid signature");
+        return Err(ApiError::Status(StatusCode::UNAUTHORIZED));
+    }
+
+    let webhook: TaskWebhook = serde_json::from_slice(&body)
+        .map_err(|e| ApiError::BadRequest(format!("Invalid task webhook: {}", e)))?;
+    info!(
+        "Task webhook: task {} is {} ({}% complete)",
+        webhook.task_uuid,
+        webhook.status,
+        webhook.completion_percentage.unwrap_or_default()
+    );
+
+    let update = apply_task_status(&pool, webhook.task_uuid, &webhook.status)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+    let message = match update.status {
+        Some(status) => format!("{} {} marked {}", update.domain_table, update.id, status),
+        None => format!("{} {} unchanged", update.domain_table, update.id),
+    };
+    Ok(Json(ApiResponse {
+        data: update,
+        message,
+    }))
+}
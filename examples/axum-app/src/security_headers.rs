@@ -0,0 +1,118 @@
+//! Baseline security response headers.
+//!
+//! The example is permissive by default, which suits local development. For a
+//! production deployment, [`SecurityHeaders`] adds to every response:
+//!
+//! - `X-Content-Type-Options: nosniff`
+//! - `X-Frame-Options: DENY`
+//! - `Strict-Transport-Security: max-age=<secs>; includeSubDomains`
+//! - `Content-Security-Policy` (default [`DEFAULT_CONTENT_SECURITY_POLICY`],
+//!   which allows nothing, as the app serves only JSON)
+//!
+//! A header a handler already set is left alone.
+//!
+//! ## Configuration
+//!
+//! `SECURITY_HEADERS=true|false` turns the headers on or off. When it is unset
+//! they are on only with `TASKER_ENV=production`. `HSTS_MAX_AGE_SECS` sets the
+//! HSTS lifetime (0 omits the header) and `CONTENT_SECURITY_POLICY` replaces the
+//! policy, e.g. to allow the assets of an API documentation UI.
+
+use std::sync::Arc;
+
+use axum::extract::Request;
+use axum::http::header::{self, HeaderName, HeaderValue};
+use axum::middleware::Next;
+use axum::response::Response;
+
+/// Default `Strict-Transport-Security` max-age: one year.
+pub const DEFAULT_HSTS_MAX_AGE_SECS: u64 = 365 * 24 * 3600;
+
+/// Default policy: no content may load and the app may not be framed.
+pub const DEFAULT_CONTENT_SECURITY_POLICY: &str = "default-src 'none'; frame-ancestors 'none'";
+
+/// Security headers added to every response, installed as a request extension.
+#[derive(Debug, Clone, Default)]
+pub struct SecurityHeaders {
+    headers: Vec<(HeaderName, HeaderValue)>,
+}
+
+impl SecurityHeaders {
+    /// Add no headers (the default).
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Add the headers with the default HSTS lifetime and policy.
+    pub fn enabled() -> Self {
+        Self::with_settings(DEFAULT_HSTS_MAX_AGE_SECS, DEFAULT_CONTENT_SECURITY_POLICY)
+            .expect("default security headers are valid")
+    }
+
+    /// Add the headers with an HSTS lifetime (0 = no HSTS header) and a
+    /// Content-Security-Policy.
+    pub fn with_settings(hsts_max_age_secs: u64, content_security_policy: &str) -> Result<Self, String> {
+        let mut headers = vec![
+            (header::X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff")),
+            (header::X_FRAME_OPTIONS, HeaderValue::from_static("DENY")),
+        ];
+        if hsts_max_age_secs > 0 {
+            let hsts = format!("max-age={}; includeSubDomains", hsts_max_age_secs);
+            headers.push((
+                header::STRICT_TRANSPORT_SECURITY,
+                HeaderValue::from_str(&hsts).expect("HSTS value is ASCII"),
+            ));
+        }
+        let csp = HeaderValue::from_str(content_security_policy.trim())
+            .map_err(|_| format!("invalid Content-Security-Policy '{}'", content_security_policy))?;
+        headers.push((header::CONTENT_SECURITY_POLICY, csp));
+        Ok(Self { headers })
+    }
+
+    /// Read `SECURITY_HEADERS`, `HSTS_MAX_AGE_SECS`, and `CONTENT_SECURITY_POLICY`.
+    pub fn from_env() -> Result<Self, String> {
+        let enabled = match std::env::var("SECURITY_HEADERS") {
+            Ok(raw) => raw
+                .trim()
+                .parse::<bool>()
+                .map_err(|_| format!("SECURITY_HEADERS must be true or false, got '{}'", raw))?,
+            Err(_) => std::env::var("TASKER_ENV").is_ok_and(|env| env == "production"),
+        };
+        if !enabled {
+            return Ok(Self::disabled());
+        }
+
+        let hsts_max_age_secs = match std::env::var("HSTS_MAX_AGE_SECS") {
+            Ok(raw) => raw
+                .trim()
+                .parse::<u64>()
+                .map_err(|e| format!("invalid HSTS_MAX_AGE_SECS '{}': {}", raw, e))?,
+            Err(_) => DEFAULT_HSTS_MAX_AGE_SECS,
+        };
+        let csp = std::env::var("CONTENT_SECURITY_POLICY")
+            .unwrap_or_else(|_| DEFAULT_CONTENT_SECURITY_POLICY.to_string());
+        Self::with_settings(hsts_max_age_secs, &csp)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.headers.is_empty()
+    }
+}
+
+/// Middleware adding the configured security headers to every response.
+///
+/// Reads the `Arc<SecurityHeaders>` extension installed by `create_app`;
+/// without it, responses are unchanged.
+pub async fn set_security_headers(req: Request, next: Next) -> Response {
+    let security = req.extensions().get::<Arc<SecurityHeaders>>().cloned();
+    let mut response = next.run(req).await;
+    if let Some(security) = security {
+        let headers = response.headers_mut();
+        for (name, value) in &security.headers {
+            if !headers.contains_key(name) {
+                headers.insert(name.clone(), value.clone());
+            }
+        }
+    }
+    response
+}
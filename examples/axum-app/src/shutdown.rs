@@ -0,0 +1,200 @@
+//! Graceful shutdown.
+//!
+//! On Ctrl-C or SIGTERM the HTTP server stops accepting connections and
+//! finishes the requests it has, and the handler dispatch service is told to
+//! stop through a [`ShutdownToken`]. The service keeps running until the steps
+//! already executing have finished (so their results are still reported to
+//! orchestration), up to `SHUTDOWN_DRAIN_TIMEOUT_SECS` (default 30), and is then
+//! stopped. Steps still running after the timeout are retried by orchestration
+//! like any other interrupted step.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{watch, Notify};
+use tracing::{info, warn};
+
+/// How long to wait for in-flight steps when none is configured.
+pub const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// `SHUTDOWN_DRAIN_TIMEOUT_SECS`, or [`DEFAULT_DRAIN_TIMEOUT`].
+pub fn drain_timeout_from_env() -> Duration {
+    match std::env::var("SHUTDOWN_DRAIN_TIMEOUT_SECS") {
+        Ok(raw) => match raw.trim().parse::<u64>() {
+            Ok(secs) => Duration::from_secs(secs),
+            Err(_) => {
+                warn!(
+                    "Ignoring invalid SHUTDOWN_DRAIN_TIMEOUT_SECS '{}'; using {:?}",
+                    raw, DEFAULT_DRAIN_TIMEOUT
+                );
+                DEFAULT_DRAIN_TIMEOUT
+            }
+        },
+        Err(_) => DEFAULT_DRAIN_TIMEOUT,
+    }
+}
+
+/// Tells background services to stop. Clones share the same state.
+#[derive(Debug, Clone)]
+pub struct ShutdownToken {
+    sender: Arc<watch::Sender<bool>>,
+}
+
+impl Default for ShutdownToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShutdownToken {
+    pub fn new() -> Self {
+        let (sender, _) = watch::channel(false);
+        Self {
+            sender: Arc::new(sender),
+        }
+    }
+
+    /// Start shutting down; every waiter on [`Self::triggered`] wakes up.
+    pub fn trigger(&self) {
+        self.sender.send_replace(true);
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        *self.sender.borrow()
+    }
+
+    /// Resolve once [`Self::trigger`] has been called.
+    pub async fn triggered(&self) {
+        let mut receiver = self.sender.subscribe();
+        // The sender lives in `self`, so the channel cannot close
+        let _ = receiver.wait_for(|triggered| *triggered).await;
+    }
+}
+
+/// Resolve on Ctrl-C or (on Unix) SIGTERM, then trigger `token`.
+pub async fn shutdown_signal(token: ShutdownToken) {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            warn!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                warn!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => info!("Received Ctrl-C, shutting down"),
+        _ = terminate => info!("Received SIGTERM, shutting down"),
+        _ = token.triggered() => {}
+    }
+    token.trigger();
+}
+
+/// Counts the steps currently executing. Clones share the same count.
+#[derive(Debug, Clone, Default)]
+pub struct InFlightSteps {
+    inner: Arc<InFlightInner>,
+}
+
+#[derive(Debug, Default)]
+struct InFlightInner {
+    count: AtomicUsize,
+    idle: Notify,
+}
+
+impl InFlightSteps {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a step as running until the returned guard is dropped.
+    pub fn start(&self) -> InFlightGuard {
+        self.inner.count.fetch_add(1, Ordering::SeqCst);
+        InFlightGuard {
+            inner: self.inner.clone(),
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.inner.count.load(Ordering::SeqCst)
+    }
+
+    /// Wait until no step is running, for at most `timeout`. Returns the steps
+    /// still running when it stopped waiting (0 = all finished).
+    pub async fn wait_until_idle(&self, timeout: Duration) -> usize {
+        let idle = async {
+            loop {
+                let notified = self.inner.idle.notified();
+                if self.count() == 0 {
+                    return;
+                }
+                notified.await;
+            }
+        };
+        let _ = tokio::time::timeout(timeout, idle).await;
+        self.count()
+    }
+}
+
+/// Marks one running step; see [`InFlightSteps::start`].
+#[derive(Debug)]
+pub struct InFlightGuard {
+    inner: Arc<InFlightInner>,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        if self.inner.count.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.inner.idle.notify_waiters();
+        }
+    }
+}
+
+/// Steps a shutdown waited for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DrainReport {
+    /// Steps that finished after shutdown began.
+    pub drained: usize,
+    /// Steps still running when the drain timeout expired.
+    pub abandoned: usize,
+}
+
+/// Drive `service` (the dispatch loop) until `shutdown` is triggered, then
+/// keep driving it while the in-flight steps finish, for at most
+/// `drain_timeout`. Returns `None` when the service stopped on its own.
+pub async fn run_until_shutdown<F: Future<Output = ()>>(
+    service: F,
+    in_flight: &InFlightSteps,
+    shutdown: &ShutdownToken,
+    drain_timeout: Duration,
+) -> Option<DrainReport> {
+    tokio::pin!(service);
+    tokio::select! {
+        _ = &mut service => return None,
+        _ = shutdown.triggered() => {}
+    }
+
+    let running = in_flight.count();
+    tokio::select! {
+        _ = &mut service => None,
+        abandoned = in_flight.wait_until_idle(drain_timeout) => Some(DrainReport {
+            drained: running.saturating_sub(abandoned),
+            abandoned,
+        }),
+    }
+}
@@ -0,0 +1,224 @@
+//! In-process workflow simulation.
+//!
+//! Runs every step of a workflow template through the registered handler
+//! functions in dependency order, without orchestration or a worker. Each step
+//! receives the results of all of its ancestors as dependency results, as it
+//! would from Tasker, so the handler logic can be demonstrated (and tested)
+//! end-to-end with no infrastructure.
+//!
+//! Handlers run with their real logic against whatever registry they are given;
+//! the HTTP route passes a [`AxumHandlerRegistry::sandbox`], which has no
+//! database, the mock payment gateway and the logging notifier.
+
+use std::collections::{HashMap, HashSet};
+use std::time::Instant;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::ApiError;
+use crate::handler_registry::AxumHandlerRegistry;
+use crate::templates::{missing_handlers, template_callables};
+
+/// Simulatable workflows, as `(name, template)`. The name is the template's
+/// namespace without the `_rs` suffix.
+const WORKFLOWS: &[(&str, &str)] = &[
+    (
+        "ecommerce",
+        include_str!("../config/templates/ecommerce_order_processing.yaml"),
+    ),
+    (
+        "data_pipeline",
+        include_str!("../config/templates/data_pipeline_analytics_pipeline.yaml"),
+    ),
+    (
+        "microservices",
+        include_str!("../config/templates/microservices_user_registration.yaml"),
+    ),
+    (
+        "customer_success",
+        include_str!("../config/templates/customer_success_process_refund.yaml"),
+    ),
+    (
+        "payments",
+        include_str!("../config/templates/payments_process_refund.yaml"),
+    ),
+];
+
+/// Names of the workflows [`simulate`] accepts.
+pub fn workflow_names() -> Vec<&'static str> {
+    WORKFLOWS.iter().map(|(name, _)| *name).collect()
+}
+
+/// The parsed template for a workflow name, if there is one.
+pub fn workflow_template(name: &str) -> Option<Value> {
+    let (_, yaml) = WORKFLOWS.iter().find(|(workflow, _)| *workflow == name)?;
+    Some(serde_yaml::from_str(yaml).expect("bundled templates are valid YAML"))
+}
+
+/// A template step, reduced to what simulation needs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedStep {
+    pub name: String,
+    pub callable: String,
+    pub dependencies: Vec<String>,
+}
+
+/// Template steps in an order where every step follows its dependencies.
+/// Steps that could run in parallel keep their template order.
+pub fn execution_order(template: &Value) -> Result<Vec<PlannedStep>, ApiError> {
+    let callables = template_callables(template)?;
+    let steps: Vec<PlannedStep> = template["steps"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .zip(callables)
+        .map(|(step, callable)| PlannedStep {
+            name: step["name"].as_str().unwrap_or_default().to_string(),
+            callable,
+            dependencies: step["dependencies"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect(),
+        })
+        .collect();
+
+    let names: HashSet<&str> = steps.iter().map(|s| s.name.as_str()).collect();
+    for (i, step) in steps.iter().enumerate() {
+        if let Some(unknown) = step.dependencies.iter().find(|d| !names.contains(d.as_str())) {
+            return Err(ApiError::validation(
+                format!("steps[{i}].dependencies"),
+                format!("unknown step '{unknown}'"),
+            ));
+        }
+    }
+
+    let mut ordered: Vec<PlannedStep> = Vec::with_capacity(steps.len());
+    let mut done: HashSet<String> = HashSet::new();
+    while ordered.len() < steps.len() {
+        let ready: Vec<&PlannedStep> = steps
+            .iter()
+            .filter(|s| !done.contains(&s.name))
+            .filter(|s| s.dependencies.iter().all(|d| done.contains(d)))
+            .collect();
+        if ready.is_empty() {
+            return Err(ApiError::validation("steps", "step dependencies form a cycle"));
+        }
+        for step in ready {
+            done.insert(step.name.clone());
+            ordered.push(step.clone());
+        }
+    }
+    Ok(ordered)
+}
+
+/// One executed step.
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulatedStep {
+    pub name: String,
+    pub handler: String,
+    pub elapsed_ms: u64,
+    pub result: Value,
+}
+
+/// The outcome of a simulated workflow.
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulationResult {
+    pub workflow: String,
+    /// `complete` when every step succeeded, `error` when a step failed.
+    pub status: String,
+    /// Steps that ran successfully, in execution order.
+    pub steps: Vec<SimulatedStep>,
+    /// The last step's result (the workflow's final output when complete).
+    pub final_result: Option<Value>,
+    /// The step that failed, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failed_step: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Run a workflow template's steps in-process against `context`.
+///
+/// A handler failure stops the simulation and is reported in the result; a
+/// template that names an unregistered handler is a 422 before anything runs.
+pub fn simulate(
+    registry: &AxumHandlerRegistry,
+    workflow: &str,
+    template: &Value,
+    context: &Value,
+) -> Result<SimulationResult, ApiError> {
+    let plan = execution_order(template)?;
+    let missing = missing_handlers(registry, plan.iter().map(|s| s.callable.as_str()));
+    if !missing.is_empty() {
+        return Err(ApiError::validation(
+            "workflow",
+            format!("no handler registered for {}", missing.join(", ")),
+        ));
+    }
+
+    let dependencies: HashMap<&str, &[String]> = plan
+        .iter()
+        .map(|s| (s.name.as_str(), s.dependencies.as_slice()))
+        .collect();
+    let mut results: HashMap<String, Value> = HashMap::new();
+    let mut simulation = SimulationResult {
+        workflow: workflow.to_string(),
+        status: "complete".to_string(),
+        steps: Vec::with_capacity(plan.len()),
+        final_result: None,
+        failed_step: None,
+        error: None,
+    };
+
+    for step in &plan {
+        let dep_results: HashMap<String, Value> = ancestors(&step.name, &dependencies)
+            .into_iter()
+            .filter_map(|name| results.get(name).map(|r| (name.to_string(), r.clone())))
+            .collect();
+
+        let start = Instant::now();
+        let outcome = registry
+            .call_function(&step.callable, context, &dep_results)
+            .unwrap_or_else(|| Err(format!("Handler {} is not a function handler", step.callable)));
+        let elapsed_ms = start.elapsed().as_millis() as u64;
+
+        match outcome {
+            Ok(result) => {
+                results.insert(step.name.clone(), result.clone());
+                simulation.final_result = Some(result.clone());
+                simulation.steps.push(SimulatedStep {
+                    name: step.name.clone(),
+                    handler: step.callable.clone(),
+                    elapsed_ms,
+                    result,
+                });
+            }
+            Err(e) => {
+                simulation.status = "error".to_string();
+                simulation.failed_step = Some(step.name.clone());
+                simulation.error = Some(e);
+                break;
+            }
+        }
+    }
+
+    Ok(simulation)
+}
+
+/// Every step `name` depends on, directly or transitively.
+fn ancestors<'a>(name: &str, dependencies: &HashMap<&str, &'a [String]>) -> HashSet<&'a str> {
+    let mut seen = HashSet::new();
+    let mut pending: Vec<&str> = vec![name];
+    while let Some(current) = pending.pop() {
+        for dep in dependencies.get(current).copied().unwrap_or_default() {
+            if seen.insert(dep.as_str()) {
+                pending.push(dep.as_str());
+            }
+        }
+    }
+    seen
+}
@@ -0,0 +1,320 @@
+//! SQLite backend for the application database (`sqlite` feature).
+//!
+//! Lets the example run against an in-memory (or file) SQLite database for a
+//! quick local try, without a Postgres instance for the domain tables. Only
+//! order storage is supported: the router built by [`create_app`] serves
+//! `POST /orders`, `GET /orders`, `GET /orders/{id}`, and `GET /products`.
+//! Analytics, services, compliance, and the background jobs (sweeper,
+//! reconciler, archiver) remain Postgres-only, and the Tasker worker and
+//! orchestration still need their own Postgres database.
+//!
+//! Schema lives in `migrations_sqlite/`. SQLite has no `DECIMAL` or `UUID`
+//! type, so `total` is stored as a decimal string and `task_uuid` as a
+//! hyphenated string; rows are converted to the shared [`Order`] model on read.
+//!
+//! Enable with `cargo run --features sqlite` and
+//! `APP_DATABASE_URL=sqlite::memory:` (or `sqlite://demo.db?mode=rwc`).
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{middleware, Extension, Json, Router};
+use chrono::NaiveDateTime;
+use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
+use sqlx::types::{BigDecimal, Json as SqlJson};
+use tower_http::trace::TraceLayer;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::attribution::TaskAttribution;
+use crate::email::normalize_email;
+use crate::error::ApiError;
+use crate::extract::{AliasedJson, FieldAliases};
+use crate::metrics;
+use crate::models::{ApiResponse, CreateOrderRequest, Order, OrderResponse};
+use crate::orchestration::OrchestrationClient;
+use crate::request_id::{propagate_request_id, RequestId};
+use crate::routes::orders::{cart_items_context, order_task_payload, order_total};
+use crate::tags::validate_tags;
+
+/// Type alias for the SQLite application database pool.
+pub type SqliteDb = SqlitePool;
+
+/// Maximum rows returned by the list endpoint.
+const LIST_LIMIT: i64 = 100;
+
+/// Whether a database URL selects the SQLite backend.
+pub fn is_sqlite_url(url: &str) -> bool {
+    url.starts_with("sqlite:")
+}
+
+/// Connect to a SQLite database and run the SQLite migrations.
+///
+/// An in-memory database exists per connection, so `sqlite::memory:` URLs get
+/// a single-connection pool that keeps the data alive for the pool's lifetime.
+pub async fn connect(url: &str) -> Result<SqliteDb, sqlx::Error> {
+    let max_connections = if url.contains(":memory:") { 1 } else { 5 };
+    let pool = SqlitePoolOptions::new()
+        .max_connections(max_connections)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect(url)
+        .await?;
+
+    sqlx::migrate!("./migrations_sqlite").run(&pool).await?;
+    Ok(pool)
+}
+
+/// Connect to a fresh in-memory database.
+pub async fn connect_in_memory() -> Result<SqliteDb, sqlx::Error> {
+    connect("sqlite::memory:").await
+}
+
+/// An `orders` row as stored in SQLite.
+#[derive(Debug, sqlx::FromRow)]
+struct OrderRow {
+    id: i64,
+    customer_email: String,
+    items: SqlJson<serde_json::Value>,
+    total: String,
+    status: String,
+    task_uuid: Option<String>,
+    status_reason: Option<String>,
+    tags: SqlJson<serde_json::Value>,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+    archived_at: Option<NaiveDateTime>,
+    shipping_address: Option<SqlJson<serde_json::Value>>,
+    currency: String,
+}
+
+impl TryFrom<OrderRow> for Order {
+    type Error = sqlx::Error;
+
+    fn try_from(row: OrderRow) -> Result<Self, Self::Error> {
+        let decode = |e: Box<dyn std::error::Error + Send + Sync>| sqlx::Error::Decode(e);
+        Ok(Order {
+            id: i32::try_from(row.id).map_err(|e| decode(e.into()))?,
+            customer_email: row.customer_email,
+            items: row.items.0,
+            total: BigDecimal::from_str(&row.total).map_err(|e| decode(e.into()))?,
+            status: row.status,
+            task_uuid: row
+                .task_uuid
+                .as_deref()
+                .map(Uuid::parse_str)
+                .transpose()
+                .map_err(|e| decode(e.into()))?,
+            status_reason: row.status_reason,
+            tags: row.tags.0,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+            archived_at: row.archived_at,
+            shipping_address: row.shipping_address.map(|address| address.0),
+            currency: row.currency,
+        })
+    }
+}
+
+/// Look up the product id for each SKU. SKUs with no mapping are absent from
+/// the returned map.
+pub async fn product_ids_for_skus(
+    pool: &SqliteDb,
+    skus: &[String],
+) -> sqlx::Result<HashMap<String, i64>> {
+    // SQLite has no array binds; pass the SKUs as a JSON array instead
+    let rows: Vec<(String, i64)> = sqlx::query_as(
+        "SELECT sku, product_id FROM skus WHERE sku IN (SELECT value FROM json_each($1))",
+    )
+    .bind(SqlJson(skus))
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().collect())
+}
+
+/// Insert a `pending` order.
+pub async fn insert_order(
+    pool: &SqliteDb,
+    req: &CreateOrderRequest,
+    total: f64,
+) -> sqlx::Result<Order> {
+    let row: OrderRow = sqlx::query_as(
+        r#"
+        INSERT INTO orders (customer_email, items, total, status, tags, shipping_address, currency)
+        VALUES ($1, $2, $3, 'pending', $4, $5, $6)
+        RETURNING *
+        "#,
+    )
+    .bind(&req.customer_email)
+    .bind(SqlJson(&req.cart_items))
+    .bind(format!("{total:.2}"))
+    .bind(SqlJson(&req.tags))
+    .bind(SqlJson(&req.shipping_address))
+    .bind(req.currency())
+    .fetch_one(pool)
+    .await?;
+
+    row.try_into()
+}
+
+/// Fetch an order by id.
+pub async fn get_order(pool: &SqliteDb, id: i32) -> sqlx::Result<Option<Order>> {
+    let row: Option<OrderRow> = sqlx::query_as("SELECT * FROM orders WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+    row.map(Order::try_from).transpose()
+}
+
+/// List unarchived orders, newest first.
+pub async fn list_orders(pool: &SqliteDb) -> sqlx::Result<Vec<Order>> {
+    let rows: Vec<OrderRow> = sqlx::query_as(
+        "SELECT * FROM orders WHERE archived_at IS NULL \
+         ORDER BY created_at DESC, id DESC LIMIT $1",
+    )
+    .bind(LIST_LIMIT)
+    .fetch_all(pool)
+    .await?;
+
+    rows.into_iter().map(Order::try_from).collect()
+}
+
+/// Build the order-only router backed by SQLite.
+pub fn create_app(
+    db: SqliteDb,
+    client: OrchestrationClient,
+    field_aliases: FieldAliases,
+) -> Router {
+    Router::new()
+        .route("/orders", get(list_orders_route).post(create_order))
+        .route("/orders/{id}", get(get_order_route))
+        .merge(crate::routes::products::router())
+        .merge(crate::routes::metrics::router())
+        .layer(middleware::from_fn(metrics::track_http_metrics))
+        .layer(Extension(db))
+        .layer(Extension(client))
+        .layer(Extension(Arc::new(field_aliases)))
+        .layer(middleware::from_fn(propagate_request_id))
+        .layer(TraceLayer::new_for_http())
+}
+
+/// Create an order and submit its e-commerce workflow task.
+///
+/// Same request and response as the Postgres `POST /orders`. A failed
+/// submission marks the order `failed` (see [`crate::submission`]).
+async fn create_order(
+    Extension(pool): Extension<SqliteDb>,
+    Extension(client): Extension<OrchestrationClient>,
+    attribution: TaskAttribution,
+    request_id: RequestId,
+    AliasedJson(mut req): AliasedJson<CreateOrderRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<OrderResponse>>), ApiError> {
+    req.customer_email = normalize_email(&req.customer_email);
+    req.validate()?;
+    validate_tags(&req.tags)?;
+
+    let skus: Vec<String> = req.cart_items.iter().map(|item| item.sku.clone()).collect();
+    let product_ids = product_ids_for_skus(&pool, &skus).await.map_err(|e| {
+        error!("Failed to resolve SKUs: {}", e);
+        ApiError::Db(e.to_string())
+    })?;
+    let cart_items = cart_items_context(&req.cart_items, &product_ids)?;
+
+    let total = order_total(&req.cart_items, &req.currency());
+    let order = insert_order(&pool, &req, total).await.map_err(|e| {
+        error!("Failed to insert order: {}", e);
+        ApiError::Db(e.to_string())
+    })?;
+
+    info!("Order {} created for {} (sqlite)", order.id, req.customer_email);
+
+    let task_payload = order_task_payload(
+        &req,
+        &cart_items,
+        total,
+        order.id,
+        &attribution,
+        &request_id,
+    );
+    let task_uuid = match client.create_task(&task_payload).await {
+        Ok(uuid) => uuid,
+        Err(e) => {
+            error!("Failed to submit task to orchestration: {}", e);
+            let _ = sqlx::query(
+                "UPDATE orders SET status = 'failed', status_reason = $1, \
+                 updated_at = CURRENT_TIMESTAMP WHERE id = $2",
+            )
+            .bind(format!("Task submission failed: {e}"))
+            .bind(order.id)
+            .execute(&pool)
+            .await;
+            return Err(ApiError::submission_failed(order.id, &e));
+        }
+    };
+
+    let _ = sqlx::query(
+        "UPDATE orders SET task_uuid = $1, status = 'processing', \
+         updated_at = CURRENT_TIMESTAMP WHERE id = $2",
+    )
+    .bind(task_uuid.to_string())
+    .bind(order.id)
+    .execute(&pool)
+    .await;
+
+    let response = OrderResponse {
+        id: order.id,
+        customer_email: order.customer_email,
+        status: "processing".to_string(),
+        task_uuid: Some(task_uuid),
+        created_at: order.created_at,
+        request_id: request_id.0,
+    };
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse {
+            data: response,
+            message: "Order created successfully".to_string(),
+        }),
+    ))
+}
+
+/// List unarchived orders.
+async fn list_orders_route(
+    Extension(pool): Extension<SqliteDb>,
+) -> Result<Json<ApiResponse<Vec<Order>>>, ApiError> {
+    let rows = list_orders(&pool).await.map_err(|e| {
+        error!("Failed to list orders: {}", e);
+        ApiError::Db(e.to_string())
+    })?;
+
+    Ok(Json(ApiResponse {
+        message: format!("{} orders found", rows.len()),
+        data: rows,
+    }))
+}
+
+/// Retrieve an order by ID.
+async fn get_order_route(
+    Extension(pool): Extension<SqliteDb>,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<Order>>, ApiError> {
+    let order = get_order(&pool, id)
+        .await
+        .map_err(|e| {
+            error!("Failed to query order: {}", e);
+            ApiError::Db(e.to_string())
+        })?
+        .ok_or(ApiError::NotFound)?;
+
+    Ok(Json(ApiResponse {
+        data: order,
+        message: "Order retrieved".to_string(),
+    }))
+}
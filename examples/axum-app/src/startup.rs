@@ -0,0 +1,203 @@
+//! Startup self-check.
+//!
+//! [`startup_checks`] verifies the app's dependencies before it starts
+//! serving, so a misconfiguration shows up in the startup log rather than on
+//! the first request:
+//!
+//! - `database`: the application pool answers `SELECT 1`
+//! - `orchestration`: `GET {ORCHESTRATION_URL}/health` succeeds
+//! - `handlers`: every step callable in the templates under
+//!   `TASKER_TEMPLATE_PATH` has a registered handler
+//!
+//! Failures are logged as warnings. With `STRICT_STARTUP=true` any failure
+//! stops the app instead.
+
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+use tracing::{info, warn};
+
+use crate::db::AppDb;
+use crate::handler_registry::AxumHandlerRegistry;
+use crate::orchestration::OrchestrationClient;
+use crate::templates::{missing_handlers, template_callables};
+
+/// Template directory when `TASKER_TEMPLATE_PATH` is unset.
+const DEFAULT_TEMPLATE_PATH: &str = "config/templates";
+
+/// Startup check settings.
+#[derive(Debug, Clone)]
+pub struct StartupConfig {
+    /// Fail startup when any check fails.
+    pub strict: bool,
+    /// Directory of the task templates the worker loads.
+    pub template_dir: PathBuf,
+}
+
+impl StartupConfig {
+    /// Read `STRICT_STARTUP` and `TASKER_TEMPLATE_PATH`.
+    pub fn from_env() -> Self {
+        Self {
+            strict: std::env::var("STRICT_STARTUP")
+                .map(|v| v.trim().eq_ignore_ascii_case("true"))
+                .unwrap_or(false),
+            template_dir: std::env::var("TASKER_TEMPLATE_PATH")
+                .unwrap_or_else(|_| DEFAULT_TEMPLATE_PATH.to_string())
+                .into(),
+        }
+    }
+}
+
+/// The outcome of one dependency check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StartupCheck {
+    pub name: &'static str,
+    pub ok: bool,
+    /// What was verified, or why the check failed.
+    pub detail: String,
+}
+
+impl StartupCheck {
+    fn from_result(name: &'static str, result: Result<String, String>) -> Self {
+        let (ok, detail) = match result {
+            Ok(detail) => (true, detail),
+            Err(detail) => (false, detail),
+        };
+        Self { name, ok, detail }
+    }
+}
+
+/// The outcome of all startup checks.
+#[derive(Debug, Clone)]
+pub struct StartupReport {
+    pub checks: Vec<StartupCheck>,
+}
+
+impl StartupReport {
+    /// Checks that failed.
+    pub fn failures(&self) -> Vec<&StartupCheck> {
+        self.checks.iter().filter(|check| !check.ok).collect()
+    }
+
+    /// Whether every check passed.
+    pub fn is_ready(&self) -> bool {
+        self.checks.iter().all(|check| check.ok)
+    }
+
+    /// Log one line per check and a summary.
+    pub fn log(&self) {
+        for check in &self.checks {
+            if check.ok {
+                info!("Startup check {}: ok ({})", check.name, check.detail);
+            } else {
+                warn!("Startup check {}: FAILED ({})", check.name, check.detail);
+            }
+        }
+        let failed = self.failures().len();
+        if failed == 0 {
+            info!("All {} startup checks passed", self.checks.len());
+        } else {
+            warn!("{} of {} startup checks failed", failed, self.checks.len());
+        }
+    }
+
+    /// Turn failures into an error in strict mode; otherwise always `Ok`.
+    pub fn enforce(&self, strict: bool) -> anyhow::Result<()> {
+        if !strict || self.is_ready() {
+            return Ok(());
+        }
+        let failed: Vec<String> = self
+            .failures()
+            .iter()
+            .map(|check| format!("{}: {}", check.name, check.detail))
+            .collect();
+        anyhow::bail!("startup checks failed (STRICT_STARTUP=true): {}", failed.join("; "))
+    }
+}
+
+/// Run every startup check. Checks never panic or short-circuit; each failure
+/// is recorded in the report.
+pub async fn startup_checks(
+    pool: &AppDb,
+    orchestration: &OrchestrationClient,
+    registry: &AxumHandlerRegistry,
+    template_dir: &Path,
+) -> StartupReport {
+    let database = sqlx::query("SELECT 1")
+        .execute(pool)
+        .await
+        .map(|_| "SELECT 1 succeeded".to_string())
+        .map_err(|e| e.to_string());
+
+    let orchestration_health = orchestration
+        .health()
+        .await
+        .map(|()| format!("{}/health is up", orchestration.base_url()))
+        .map_err(|e| e.to_string());
+
+    StartupReport {
+        checks: vec![
+            StartupCheck::from_result("database", database),
+            StartupCheck::from_result("orchestration", orchestration_health),
+            StartupCheck::from_result("handlers", check_template_handlers(registry, template_dir)),
+        ],
+    }
+}
+
+/// Ensure every callable in the templates under `template_dir` is registered.
+fn check_template_handlers(
+    registry: &AxumHandlerRegistry,
+    template_dir: &Path,
+) -> Result<String, String> {
+    let missing = missing_template_handlers(registry, template_dir)?;
+    if !missing.is_empty() {
+        return Err(format!("no handler registered for {}", missing.join(", ")));
+    }
+    Ok(format!(
+        "every step callable in {} has a handler",
+        template_dir.display()
+    ))
+}
+
+/// Callables referenced by the templates under `template_dir` that `registry`
+/// has no handler for, sorted and de-duplicated.
+///
+/// Errors if a template cannot be read or has a step without a callable.
+fn missing_template_handlers(
+    registry: &AxumHandlerRegistry,
+    template_dir: &Path,
+) -> Result<Vec<String>, String> {
+    let callables = load_template_callables(template_dir)?;
+    Ok(missing_handlers(
+        registry,
+        callables.iter().map(String::as_str),
+    ))
+}
+
+/// The callables of the steps of the YAML templates under `template_dir`, in
+/// file and step order.
+fn load_template_callables(template_dir: &Path) -> Result<Vec<String>, String> {
+    let entries = std::fs::read_dir(template_dir)
+        .map_err(|e| format!("cannot read {}: {}", template_dir.display(), e))?;
+
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext == "yaml" || ext == "yml")
+        })
+        .collect();
+    paths.sort();
+
+    let mut callables = Vec::new();
+    for path in &paths {
+        let template: Value = std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|yaml| serde_yaml::from_str(&yaml).map_err(|e| e.to_string()))
+            .map_err(|e| format!("cannot load {}: {}", path.display(), e))?;
+        let template_callables = template_callables(&template)
+            .map_err(|e| format!("invalid template {}: {}", path.display(), e))?;
+        callables.extend(template_callables);
+    }
+    Ok(callables)
+}
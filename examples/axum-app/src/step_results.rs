@@ -0,0 +1,135 @@
+//! Local persistence of step results.
+//!
+//! When `PERSIST_STEP_RESULTS` is enabled, the worker's post-handler callback
+//! is a [`StepResultRecorder`], which writes every step's result JSON to the
+//! `step_results` table keyed by `(task_uuid, step_name)`. Results are then
+//! queryable with SQL (or `GET /admin/tasks/{uuid}/results`) without calling
+//! orchestration.
+//!
+//! Persistence is off by default: it costs one write per step execution.
+
+use async_trait::async_trait;
+use tasker_shared::messaging::StepExecutionResult;
+use tasker_shared::types::base::TaskSequenceStep;
+use tasker_worker::worker::handlers::PostHandlerCallback;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::db::AppDb;
+use crate::models::StepResultRecord;
+
+/// Read the `PERSIST_STEP_RESULTS` env var (`1`/`true`/`yes` enables it).
+pub fn persistence_enabled_from_env() -> bool {
+    std::env::var("PERSIST_STEP_RESULTS")
+        .map(|v| matches!(v.as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+/// Where a result came from.
+#[derive(Debug, Clone, Copy)]
+pub struct StepKey<'a> {
+    pub task_uuid: Uuid,
+    pub step_name: &'a str,
+    pub namespace: &'a str,
+    pub handler_name: &'a str,
+}
+
+/// Insert or overwrite the result for one step.
+pub async fn record(
+    pool: &AppDb,
+    key: StepKey<'_>,
+    result: &StepExecutionResult,
+) -> sqlx::Result<()> {
+    let value = result.success.then(|| result.result.clone());
+    let error_message = result.error.as_ref().map(|e| e.message.clone());
+
+    sqlx::query(
+        r#"
+        INSERT INTO step_results
+            (task_uuid, step_name, namespace, handler_name, success, result, error_message)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT (task_uuid, step_name) DO UPDATE SET
+            namespace = EXCLUDED.namespace,
+            handler_name = EXCLUDED.handler_name,
+            success = EXCLUDED.success,
+            result = EXCLUDED.result,
+            error_message = EXCLUDED.error_message,
+            recorded_at = NOW()
+        "#,
+    )
+    .bind(key.task_uuid)
+    .bind(key.step_name)
+    .bind(key.namespace)
+    .bind(key.handler_name)
+    .bind(result.success)
+    .bind(value)
+    .bind(error_message)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// All persisted results for a task, in the order they were recorded.
+pub async fn for_task(pool: &AppDb, task_uuid: Uuid) -> sqlx::Result<Vec<StepResultRecord>> {
+    sqlx::query_as(
+        "SELECT * FROM step_results WHERE task_uuid = $1 ORDER BY recorded_at, step_name",
+    )
+    .bind(task_uuid)
+    .fetch_all(pool)
+    .await
+}
+
+/// The persisted result of one step of a task, if recorded.
+pub async fn for_step(
+    pool: &AppDb,
+    task_uuid: Uuid,
+    step_name: &str,
+) -> sqlx::Result<Option<StepResultRecord>> {
+    sqlx::query_as("SELECT * FROM step_results WHERE task_uuid = $1 AND step_name = $2")
+        .bind(task_uuid)
+        .bind(step_name)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Post-handler callback that persists each step result.
+///
+/// A failed write is logged and otherwise ignored; it never affects the step.
+pub struct StepResultRecorder {
+    pool: AppDb,
+}
+
+impl StepResultRecorder {
+    pub fn new(pool: AppDb) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl PostHandlerCallback for StepResultRecorder {
+    async fn on_handler_complete(
+        &self,
+        step: &TaskSequenceStep,
+        result: &StepExecutionResult,
+        _worker_id: &str,
+    ) {
+        let key = StepKey {
+            task_uuid: step.task.task.task_uuid,
+            step_name: &step.workflow_step.name,
+            namespace: &step.task.namespace_name,
+            handler_name: &step.step_definition.handler.callable,
+        };
+
+        if let Err(e) = record(&self.pool, key, result).await {
+            warn!(
+                "Failed to persist result for step {} of task {}: {}",
+                key.step_name, key.task_uuid, e
+            );
+        }
+    }
+
+    fn name(&self) -> &str {
+        "step_result_recorder"
+    }
+}
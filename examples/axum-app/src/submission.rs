@@ -0,0 +1,35 @@
+//! Failed task submissions in the create routes.
+//!
+//! A create route inserts its domain row before submitting the workflow task.
+//! If orchestration rejects the submission or cannot be reached, the row is
+//! marked `failed` with a `status_reason` rather than left `pending` without a
+//! workflow, and the client gets an [`ApiError::SubmissionFailed`] naming it.
+
+use tracing::error;
+
+use crate::db::AppDb;
+use crate::error::ApiError;
+use crate::orchestration::OrchestrationError;
+
+/// Mark row `id` of `table` failed after its task submission failed, and
+/// return the error for the client.
+pub async fn fail_submission(
+    pool: &AppDb,
+    table: &str,
+    id: i32,
+    submission_error: &OrchestrationError,
+) -> ApiError {
+    let reason = format!("Task submission failed: {submission_error}");
+    let updated = sqlx::query(&format!(
+        "UPDATE {table} SET status = 'failed', status_reason = $1, updated_at = NOW() WHERE id = $2"
+    ))
+    .bind(&reason)
+    .bind(id)
+    .execute(pool)
+    .await;
+    if let Err(e) = updated {
+        error!("Failed to mark {} {} failed: {}", table, id, e);
+    }
+
+    ApiError::submission_failed(id, submission_error)
+}
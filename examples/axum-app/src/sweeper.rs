@@ -0,0 +1,226 @@
+//! Periodic sweeper for stale domain rows.
+//!
+//! The synchronous create routes mark a row `failed` themselves when its task
+//! submission fails (see [`crate::submission`]), so two kinds of row are left
+//! without a `task_uuid`:
+//!
+//! - an async order whose background submission failed, which stays `queued`
+//!   and keeps the failed request in `task_request`;
+//! - a row whose create request was interrupted before submission, which stays
+//!   `pending` and has no stored request.
+//!
+//! The sweeper finds such rows older than a configurable age and marks them
+//! `failed` with a `status_reason`. When resubmission is enabled, async orders
+//! get one resubmission of their stored `task_request` first; other rows have
+//! nothing to resubmit and are failed directly. If the row changed while the
+//! resubmission was in flight (it was cancelled, the background submission
+//! finished, or another sweeper got there first), the new task is cancelled so
+//! no workflow runs without a row tracking it.
+//!
+//! ## Configuration
+//!
+//! | Env var | Default | Meaning |
+//! |---------|---------|---------|
+//! | `SWEEPER_INTERVAL_SECS` | 60 | Time between sweeps (0 disables the sweeper) |
+//! | `SWEEPER_MAX_PENDING_AGE_SECS` | 900 | Age after which a row is stale |
+//! | `SWEEPER_RESUBMIT` | false | Attempt one resubmission before failing |
+//!
+//! Each swept row increments `domain_rows_swept_total{table, outcome}`.
+
+use std::time::Duration;
+
+use serde_json::Value;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+use crate::db::AppDb;
+use crate::env::env_secs;
+use crate::metrics;
+use crate::orchestration::OrchestrationClient;
+
+/// Domain tables that hold a task-backed row.
+const SWEPT_TABLES: &[&str] = &[
+    "orders",
+    "analytics_jobs",
+    "service_requests",
+    "compliance_checks",
+];
+
+#[derive(Debug, Clone)]
+pub struct SweeperConfig {
+    /// Time between sweeps.
+    pub interval: Duration,
+    /// Rows older than this without a task are considered stale.
+    pub max_pending_age: Duration,
+    /// Attempt one resubmission of the stored task request before failing.
+    pub resubmit: bool,
+}
+
+impl Default for SweeperConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(60),
+            max_pending_age: Duration::from_secs(900),
+            resubmit: false,
+        }
+    }
+}
+
+impl SweeperConfig {
+    /// Read the sweeper configuration from the environment.
+    pub fn from_env() -> Result<Self, String> {
+        let defaults = Self::default();
+        Ok(Self {
+            interval: env_secs("SWEEPER_INTERVAL_SECS")?.unwrap_or(defaults.interval),
+            max_pending_age: env_secs("SWEEPER_MAX_PENDING_AGE_SECS")?
+                .unwrap_or(defaults.max_pending_age),
+            resubmit: std::env::var("SWEEPER_RESUBMIT")
+                .map(|v| matches!(v.as_str(), "1" | "true" | "yes"))
+                .unwrap_or(defaults.resubmit),
+        })
+    }
+}
+
+/// Outcome of a single sweep across all domain tables.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SweepReport {
+    /// Rows marked `failed`.
+    pub failed: u64,
+    /// Rows whose task was successfully resubmitted.
+    pub resubmitted: u64,
+    /// Resubmitted tasks cancelled because their row changed in the meantime.
+    pub cancelled: u64,
+}
+
+/// Run one sweep over every domain table.
+pub async fn sweep_once(
+    pool: &AppDb,
+    client: &OrchestrationClient,
+    config: &SweeperConfig,
+) -> Result<SweepReport, sqlx::Error> {
+    let mut report = SweepReport::default();
+    for table in SWEPT_TABLES {
+        sweep_table(pool, client, config, table, &mut report).await?;
+    }
+    Ok(report)
+}
+
+async fn sweep_table(
+    pool: &AppDb,
+    client: &OrchestrationClient,
+    config: &SweeperConfig,
+    table: &'static str,
+    report: &mut SweepReport,
+) -> Result<(), sqlx::Error> {
+    let max_age_secs = config.max_pending_age.as_secs() as i64;
+    let stale: Vec<(i32, Option<Value>)> = sqlx::query_as(&format!(
+        r#"
+        SELECT id, task_request FROM {table}
+        WHERE status IN ('pending', 'queued')
+          AND task_uuid IS NULL
+          AND created_at < NOW() - ($1::bigint * INTERVAL '1 second')
+        ORDER BY id
+        "#
+    ))
+    .bind(max_age_secs)
+    .fetch_all(pool)
+    .await?;
+
+    for (id, task_request) in stale {
+        let mut reason = format!(
+            "No workflow task was created within {}s",
+            config.max_pending_age.as_secs()
+        );
+
+        if config.resubmit {
+            match task_request {
+                Some(payload) => match client.create_task(&payload).await {
+                    Ok(task_uuid) => {
+                        let updated = sqlx::query(&format!(
+                            "UPDATE {table} SET task_uuid = $1, status = 'processing', \
+                             status_reason = NULL, updated_at = NOW() \
+                             WHERE id = $2 AND status IN ('pending', 'queued') \
+                             AND task_uuid IS NULL"
+                        ))
+                        .bind(task_uuid)
+                        .bind(id)
+                        .execute(pool)
+                        .await?;
+                        if updated.rows_affected() > 0 {
+                            info!("Sweeper resubmitted {} {} as task {}", table, id, task_uuid);
+                            report.resubmitted += 1;
+                            record(table, "resubmitted");
+                        } else {
+                            warn!(
+                                "Sweeper resubmitted {} {} as task {}, but the row changed meanwhile; cancelling the task",
+                                table, id, task_uuid
+                            );
+                            if let Err(e) = client.cancel_task(task_uuid).await {
+                                error!("Failed to cancel untracked task {}: {}", task_uuid, e);
+                            }
+                            report.cancelled += 1;
+                            record(table, "cancelled");
+                        }
+                        continue;
+                    }
+                    Err(e) => {
+                        warn!("Sweeper resubmission failed for {} {}: {}", table, id, e);
+                        reason = format!("{reason}; resubmission failed: {e}");
+                    }
+                },
+                None => reason.push_str("; no stored task request to resubmit"),
+            }
+        }
+
+        let updated = sqlx::query(&format!(
+            "UPDATE {table} SET status = 'failed', status_reason = $1, updated_at = NOW() \
+             WHERE id = $2 AND status IN ('pending', 'queued') AND task_uuid IS NULL"
+        ))
+        .bind(&reason)
+        .bind(id)
+        .execute(pool)
+        .await?;
+        if updated.rows_affected() > 0 {
+            info!("Sweeper marked {} {} failed: {}", table, id, reason);
+            report.failed += 1;
+            record(table, "failed");
+        }
+    }
+
+    Ok(())
+}
+
+fn record(table: &str, outcome: &str) {
+    metrics::registry().increment_counter(
+        "domain_rows_swept_total",
+        &[("table", table), ("outcome", outcome)],
+    );
+}
+
+/// Spawn the sweeper loop. Returns `None` when the interval is zero (disabled).
+pub fn spawn(
+    pool: AppDb,
+    client: OrchestrationClient,
+    config: SweeperConfig,
+) -> Option<JoinHandle<()>> {
+    if config.interval.is_zero() {
+        return None;
+    }
+
+    Some(tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(config.interval);
+        loop {
+            ticker.tick().await;
+            match sweep_once(&pool, &client, &config).await {
+                Ok(report) if report != SweepReport::default() => {
+                    info!(
+                        "Sweeper: {} rows failed, {} resubmitted, {} resubmissions cancelled",
+                        report.failed, report.resubmitted, report.cancelled
+                    );
+                }
+                Ok(_) => {}
+                Err(e) => error!("Sweeper run failed: {}", e),
+            }
+        }
+    }))
+}
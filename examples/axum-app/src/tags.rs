@@ -0,0 +1,46 @@
+//! Workflow tags: free-form string labels attached to domain rows.
+//!
+//! Create requests accept an optional `tags` object which is stored in the
+//! row's `tags` JSONB column and forwarded in the task context. List endpoints
+//! filter on tags with `?tag.<key>=<value>` query parameters; multiple tag
+//! parameters must all match.
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::error::ApiError;
+
+/// Tags attached to a workflow, e.g. `{"campaign": "black_friday"}`.
+pub type Tags = HashMap<String, String>;
+
+/// Query parameter prefix for tag filters.
+const TAG_PARAM_PREFIX: &str = "tag.";
+
+/// Reject tags with blank keys.
+pub fn validate_tags(tags: &Tags) -> Result<(), ApiError> {
+    if tags.keys().any(|key| key.trim().is_empty()) {
+        return Err(ApiError::validation("tags", "tag keys must not be empty"));
+    }
+    Ok(())
+}
+
+/// Build a JSONB containment filter from `tag.<key>=<value>` query parameters.
+///
+/// Returns `{}` when no tag parameters are present, which matches every row
+/// under `tags @> $1`.
+pub fn tag_filter(query: &HashMap<String, String>) -> Result<Value, ApiError> {
+    let mut filter = serde_json::Map::new();
+    for (param, value) in query {
+        if let Some(key) = param.strip_prefix(TAG_PARAM_PREFIX) {
+            if key.trim().is_empty() {
+                return Err(ApiError::validation(
+                    param.clone(),
+                    "tag filter must name a key, e.g. tag.campaign=black_friday",
+                ));
+            }
+            filter.insert(key.to_string(), Value::String(value.clone()));
+        }
+    }
+    Ok(Value::Object(filter))
+}
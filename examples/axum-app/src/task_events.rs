@@ -0,0 +1,153 @@
+//! Task progress as a Server-Sent Events stream.
+//!
+//! `GET /orders/{id}/events` polls the order's task in orchestration and sends
+//! a `progress` event whenever its `status` or `completion_percentage`
+//! changes:
+//!
+//! ```text
+//! event: progress
+//! data: {"task_uuid":"...","status":"steps_in_process","completion_percentage":40.0}
+//! ```
+//!
+//! The first poll always produces an event. The stream ends after the event
+//! for a terminal status ([`TERMINAL_STATUSES`]), or with an `error` event if
+//! orchestration no longer knows the task. Other fetch failures are logged and
+//! retried on the next poll.
+//!
+//! | Variable | Default | Meaning |
+//! |----------|---------|---------|
+//! | `TASK_EVENTS_POLL_INTERVAL_MS` | 1000 | Time between task polls |
+
+use std::convert::Infallible;
+use std::time::Duration;
+
+use axum::http::StatusCode;
+use axum::response::sse::Event;
+use futures::stream::{self, Stream};
+use serde::Serialize;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::orchestration::{OrchestrationClient, OrchestrationError};
+
+/// Task statuses after which the task no longer changes.
+pub const TERMINAL_STATUSES: &[&str] = &["complete", "error", "cancelled"];
+
+/// Default time between task polls.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Task event stream settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TaskEventsConfig {
+    pub poll_interval: Duration,
+}
+
+impl Default for TaskEventsConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+}
+
+impl TaskEventsConfig {
+    /// Read `TASK_EVENTS_POLL_INTERVAL_MS`, which must be a positive integer.
+    pub fn from_env() -> Result<Self, String> {
+        match std::env::var("TASK_EVENTS_POLL_INTERVAL_MS") {
+            Ok(raw) => match raw.trim().parse::<u64>() {
+                Ok(ms) if ms > 0 => Ok(Self {
+                    poll_interval: Duration::from_millis(ms),
+                }),
+                _ => Err(format!(
+                    "Invalid TASK_EVENTS_POLL_INTERVAL_MS '{}': expected a positive integer",
+                    raw
+                )),
+            },
+            Err(_) => Ok(Self::default()),
+        }
+    }
+}
+
+/// The fields of a task whose changes produce an event.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TaskProgress {
+    pub task_uuid: Uuid,
+    pub status: Option<String>,
+    pub completion_percentage: Option<f64>,
+}
+
+impl TaskProgress {
+    fn is_terminal(&self) -> bool {
+        self.status
+            .as_deref()
+            .is_some_and(|status| TERMINAL_STATUSES.contains(&status))
+    }
+}
+
+struct PollState {
+    client: OrchestrationClient,
+    task_uuid: Uuid,
+    poll_interval: Duration,
+    last: Option<TaskProgress>,
+    polled: bool,
+    done: bool,
+}
+
+/// Stream `progress` events for `task_uuid` until it reaches a terminal status.
+pub fn task_events(
+    client: OrchestrationClient,
+    task_uuid: Uuid,
+    config: TaskEventsConfig,
+) -> impl Stream<Item = Result<Event, Infallible>> {
+    let state = PollState {
+        client,
+        task_uuid,
+        poll_interval: config.poll_interval,
+        last: None,
+        polled: false,
+        done: false,
+    };
+
+    stream::unfold(state, |mut state| async move {
+        if state.done {
+            return None;
+        }
+        loop {
+            if state.polled {
+                tokio::time::sleep(state.poll_interval).await;
+            }
+            state.polled = true;
+
+            let task = match state.client.get_task(state.task_uuid).await {
+                Ok(task) => task,
+                Err(OrchestrationError::Status { status, .. }) if status == StatusCode::NOT_FOUND => {
+                    state.done = true;
+                    let event = Event::default()
+                        .event("error")
+                        .data(format!("task {} not found", state.task_uuid));
+                    return Some((Ok(event), state));
+                }
+                Err(e) => {
+                    warn!("Failed to poll task {} for events: {}", state.task_uuid, e);
+                    continue;
+                }
+            };
+
+            let progress = TaskProgress {
+                task_uuid: state.task_uuid,
+                status: task["status"].as_str().map(str::to_string),
+                completion_percentage: task["completion_percentage"].as_f64(),
+            };
+            if state.last.as_ref() == Some(&progress) {
+                continue;
+            }
+
+            state.done = progress.is_terminal();
+            let event = Event::default()
+                .event("progress")
+                .data(serde_json::to_string(&progress).unwrap_or_default());
+            state.last = Some(progress);
+            return Some((Ok(event), state));
+        }
+    })
+}
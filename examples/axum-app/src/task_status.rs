@@ -0,0 +1,149 @@
+//! Task status for a domain row, proxied from orchestration.
+//!
+//! `GET /orders/{id}/task`, `/analytics/{id}/task`, and `/services/{id}/task`
+//! look up the row's `task_uuid`, fetch the task with the shared
+//! [`OrchestrationClient`], and return a trimmed view of it, so clients can
+//! follow a workflow without orchestration credentials.
+//!
+//! Returns 404 for an unknown row, a row whose task has not been submitted, or
+//! a task orchestration does not know, and 502 if orchestration cannot be read.
+
+use axum::http::StatusCode;
+use serde::Serialize;
+use serde_json::Value;
+use tracing::error;
+use uuid::Uuid;
+
+use crate::db::AppDb;
+use crate::error::ApiError;
+use crate::orchestration::{OrchestrationClient, OrchestrationError};
+
+/// Task statuses whose failed steps can be retried.
+pub const RETRYABLE_TASK_STATUSES: &[&str] = &["error", "blocked_by_failures"];
+
+/// The workflow state of one step.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StepState {
+    pub name: String,
+    pub current_state: Option<String>,
+    pub attempts: Option<i64>,
+}
+
+/// The parts of an orchestration task a client polls for.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TaskStatus {
+    pub task_uuid: Uuid,
+    pub status: Option<String>,
+    pub completion_percentage: Option<f64>,
+    pub steps: Vec<StepState>,
+}
+
+impl TaskStatus {
+    /// Trim a task fetched with `GET /v1/tasks/{uuid}`. Missing fields are
+    /// `null` rather than an error, since the shape varies across versions.
+    pub fn from_task(task_uuid: Uuid, task: &Value) -> Self {
+        let steps = task["steps"]
+            .as_array()
+            .map(|steps| {
+                steps
+                    .iter()
+                    .map(|step| StepState {
+                        name: step["name"].as_str().unwrap_or_default().to_string(),
+                        current_state: step["current_state"].as_str().map(str::to_string),
+                        attempts: step["attempts"].as_i64(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            task_uuid,
+            status: task["status"].as_str().map(str::to_string),
+            completion_percentage: task["completion_percentage"].as_f64(),
+            steps,
+        }
+    }
+}
+
+/// The `results` of step `name` in a task fetched with `GET /v1/tasks/{uuid}`,
+/// if the step has reported any.
+pub fn step_results(task: &Value, name: &str) -> Option<Value> {
+    task["steps"]
+        .as_array()?
+        .iter()
+        .find(|step| step["name"].as_str() == Some(name))
+        .map(|step| step["results"].clone())
+        .filter(|results| !results.is_null())
+}
+
+/// The combined status of a workflow spread over several tasks, e.g. the two
+/// namespace tasks of a compliance check:
+///
+/// - `error` if any task errored
+/// - `complete` once every task is complete
+/// - `cancelled` if any task was cancelled
+/// - otherwise the status of the first unfinished task
+///
+/// `None` if there are no tasks, or a task's status is unknown and no task
+/// errored.
+pub fn aggregate_status(statuses: &[Option<&str>]) -> Option<String> {
+    if statuses.is_empty() {
+        return None;
+    }
+    if statuses.contains(&Some("error")) {
+        return Some("error".to_string());
+    }
+    let statuses: Vec<&str> = statuses.iter().copied().collect::<Option<_>>()?;
+    if statuses.iter().all(|status| *status == "complete") {
+        Some("complete".to_string())
+    } else if statuses.contains(&"cancelled") {
+        Some("cancelled".to_string())
+    } else {
+        statuses
+            .into_iter()
+            .find(|status| *status != "complete")
+            .map(str::to_string)
+    }
+}
+
+/// The task behind row `id` of `table`; 404 if there is no such row or its
+/// task has not been submitted.
+pub async fn row_task_uuid(pool: &AppDb, table: &str, id: i32) -> Result<Uuid, ApiError> {
+    let task_uuid: Option<Uuid> =
+        sqlx::query_scalar(&format!("SELECT task_uuid FROM {table} WHERE id = $1"))
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| {
+                error!("Failed to query {} {}: {}", table, id, e);
+                ApiError::Db(e.to_string())
+            })?
+            .ok_or(ApiError::NotFound)?;
+    task_uuid.ok_or(ApiError::NotFound)
+}
+
+/// Fetch the status of the task behind row `id` of `table`.
+pub async fn row_task_status(
+    pool: &AppDb,
+    client: &OrchestrationClient,
+    table: &str,
+    id: i32,
+) -> Result<TaskStatus, ApiError> {
+    let task_uuid = row_task_uuid(pool, table, id).await?;
+    let task = fetch_task(client, task_uuid).await?;
+    Ok(TaskStatus::from_task(task_uuid, &task))
+}
+
+/// Fetch a task from orchestration; 404 if orchestration does not know it and
+/// 502 if it cannot be read.
+pub async fn fetch_task(client: &OrchestrationClient, task_uuid: Uuid) -> Result<Value, ApiError> {
+    client.get_task(task_uuid).await.map_err(|e| match e {
+        OrchestrationError::Status { status, .. } if status == StatusCode::NOT_FOUND => {
+            ApiError::NotFound
+        }
+        e => {
+            error!("Failed to fetch task {}: {}", task_uuid, e);
+            ApiError::Upstream(e.to_string())
+        }
+    })
+}
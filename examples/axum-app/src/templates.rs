@@ -0,0 +1,95 @@
+//! Workflow template inspection.
+//!
+//! Compares the step callables a task template references against a handler
+//! registry, so a template that names an unregistered handler is caught before
+//! any task is submitted rather than surfacing as a step failure.
+
+use std::collections::BTreeSet;
+
+use serde::Serialize;
+use serde_json::Value;
+use tasker_worker::worker::handlers::StepHandlerRegistry;
+
+use crate::error::ApiError;
+
+/// The `handler.callable` of every step in a template, in step order.
+///
+/// Errors name the first step that is missing a callable.
+pub fn template_callables(template: &Value) -> Result<Vec<String>, ApiError> {
+    let steps = template
+        .get("steps")
+        .and_then(Value::as_array)
+        .ok_or_else(|| ApiError::validation("steps", "template must have a steps array"))?;
+
+    steps
+        .iter()
+        .enumerate()
+        .map(|(i, step)| {
+            step.pointer("/handler/callable")
+                .and_then(Value::as_str)
+                .filter(|callable| !callable.trim().is_empty())
+                .map(str::to_string)
+                .ok_or_else(|| {
+                    ApiError::validation(
+                        format!("steps[{i}].handler.callable"),
+                        "every step must name a handler callable",
+                    )
+                })
+        })
+        .collect()
+}
+
+/// Callables with no registered handler, sorted and de-duplicated.
+pub fn missing_handlers<'a, R>(
+    registry: &R,
+    callables: impl IntoIterator<Item = &'a str>,
+) -> Vec<String>
+where
+    R: StepHandlerRegistry + ?Sized,
+{
+    callables
+        .into_iter()
+        .filter(|callable| !registry.handler_available(callable))
+        .map(str::to_string)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// How a template's callables line up with a registry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TemplateCoverage {
+    /// Template callables that have a registered handler.
+    pub registered: Vec<String>,
+    /// Template callables with no registered handler.
+    pub missing: Vec<String>,
+    /// Registered handlers the template does not reference.
+    pub unused: Vec<String>,
+}
+
+/// Compare a template's callables against a registry. All lists are sorted.
+pub fn template_coverage<R>(registry: &R, callables: &[String]) -> TemplateCoverage
+where
+    R: StepHandlerRegistry + ?Sized,
+{
+    let referenced: BTreeSet<&str> = callables.iter().map(String::as_str).collect();
+    let missing = missing_handlers(registry, referenced.iter().copied());
+    let registered = referenced
+        .iter()
+        .filter(|callable| !missing.iter().any(|m| m == *callable))
+        .map(|callable| callable.to_string())
+        .collect();
+
+    let mut unused: Vec<String> = registry
+        .registered_handlers()
+        .into_iter()
+        .filter(|handler| !referenced.contains(handler.as_str()))
+        .collect();
+    unused.sort();
+
+    TemplateCoverage {
+        registered,
+        missing,
+        unused,
+    }
+}
@@ -56,9 +56,26 @@ pub mod ecommerce {
         pub quantity: i64,
     }
 
+    #[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+    pub struct OrderProcessingInputShippingAddress {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub city: Option<String>,
+        pub country: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub state: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub street: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub zip: Option<String>,
+    }
+
     #[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
     pub struct OrderProcessingInput {
         pub cart_items: Vec<OrderProcessingInputCartItems>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub coupon_code: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub currency: Option<String>,
         pub customer_email: String,
         #[serde(default, skip_serializing_if = "Option::is_none")]
         pub customer_name: Option<String>,
@@ -67,6 +84,8 @@ pub mod ecommerce {
         #[serde(default, skip_serializing_if = "Option::is_none")]
         pub payment_method: Option<String>,
         pub payment_token: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub shipping_address: Option<OrderProcessingInputShippingAddress>,
     }
 
     // -- Result types (from result_schema) --
@@ -82,6 +101,13 @@ pub mod ecommerce {
 
     #[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
     pub struct ValidateCartResult {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub coupon_code: Option<String>,
+        /// Allowed values: USD, EUR, GBP, CAD, AUD, JPY
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub currency: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub discount: Option<f64>,
         pub item_count: i64,
         pub shipping: f64,
         pub subtotal: f64,
@@ -92,11 +118,24 @@ pub mod ecommerce {
         pub validated_items: Vec<ValidateCartResultValidatedItems>,
     }
 
+    #[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+    pub struct CalculateShippingResult {
+        pub calculated_at: String,
+        pub destination_country: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub destination_state: Option<String>,
+        pub free_shipping_applied: bool,
+        pub international_surcharge: f64,
+        pub shipping: f64,
+        pub total: f64,
+        pub zone: String,
+    }
+
     #[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
     pub struct ProcessPaymentResult {
         pub amount_charged: f64,
         pub authorization_code: String,
-        /// Allowed values: USD
+        /// Allowed values: USD, EUR, GBP, CAD, AUD, JPY
         pub currency: String,
         #[serde(default, skip_serializing_if = "Option::is_none")]
         pub gateway_response: Option<String>,
@@ -115,6 +154,8 @@ pub mod ecommerce {
         pub product_id: String,
         pub reserved: i64,
         pub sku: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub warehouse: Option<String>,
     }
 
     #[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
@@ -140,6 +181,9 @@ pub mod ecommerce {
     pub struct CreateOrderResult {
         pub authorization_code: String,
         pub created_at: String,
+        /// Allowed values: USD, EUR, GBP, CAD, AUD, JPY
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub currency: Option<String>,
         pub customer_email: String,
         pub estimated_delivery: String,
         pub inventory_log_id: String,
@@ -198,10 +242,14 @@ pub mod data_pipeline {
 
     #[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
     pub struct AnalyticsPipelineInput {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub allow_partial: Option<bool>,
         #[serde(default, skip_serializing_if = "Option::is_none")]
         pub date_range: Option<AnalyticsPipelineInputDateRange>,
         #[serde(default, skip_serializing_if = "Option::is_none")]
         pub pipeline_id: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub sources: Option<Vec<String>>,
     }
 
     // -- Result types (from result_schema) --
@@ -369,6 +417,7 @@ pub mod data_pipeline {
         pub inventory_summary: Option<serde_json::Value>,
         #[serde(default, skip_serializing_if = "Option::is_none")]
         pub inventory_turnover_indicator: Option<f64>,
+        pub partial: bool,
         #[serde(default, skip_serializing_if = "Option::is_none")]
         pub revenue_per_customer: Option<f64>,
         #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -547,6 +596,8 @@ pub mod microservices {
         pub channel: String,
         pub status: String,
         pub template: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub title: Option<String>,
     }
 
     #[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
@@ -561,7 +612,10 @@ pub mod microservices {
         pub sent_at: String,
         pub sequence_id: String,
         pub status: String,
+        /// Stripped by the registry's result normalizer, so it is not part of
+        /// the template's `result_schema`.
         #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[schemars(skip)]
         pub total_messages: Option<i64>,
         pub user_id: String,
         #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -811,8 +865,16 @@ pub mod payments {
     pub struct ProcessRefundInput {
         #[serde(default, skip_serializing_if = "Option::is_none")]
         pub correlation_id: Option<String>,
+        /// ISO 4217 code for `refund_amount` (defaults to USD)
         #[serde(default, skip_serializing_if = "Option::is_none")]
-        pub customer_email: Option<String>,
+        pub currency: Option<String>,
+        /// The one address refund notifications go to; handlers read it from
+        /// here rather than from step results
+        pub customer_email: String,
+        /// Total of the original order; `refund_amount` may not exceed it
+        /// (defaults to `refund_amount`)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub original_amount: Option<f64>,
         #[serde(default, skip_serializing_if = "Option::is_none")]
         pub partial_refund: Option<bool>,
         pub payment_id: String,
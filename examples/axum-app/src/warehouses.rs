@@ -0,0 +1,80 @@
+//! Per-warehouse stock of the demo products.
+//!
+//! The catalog's stock is spread over two warehouses, `WH-01` and `WH-02`.
+//! [`demo_warehouse_stock`] is the one source of those levels: the e-commerce
+//! inventory step reserves each product from a warehouse picked with
+//! [`choose_warehouse`], decrementing that warehouse's stock ([`reserve`]),
+//! and the analytics pipeline's inventory extract reports the same rows.
+
+use serde::Serialize;
+
+/// Units of one product on hand at one warehouse.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WarehouseStock {
+    pub warehouse: String,
+    /// Catalog product id.
+    pub product_id: i64,
+    pub sku: String,
+    pub on_hand: i64,
+    /// Stock level at or below which the warehouse should reorder.
+    pub reorder_point: i64,
+}
+
+/// Stock of the five demo products, summing to their catalog stock.
+///
+/// Handlers use these levels until the registry is given others (see
+/// [`crate::handler_registry::AxumHandlerRegistry::set_warehouse_stock`]).
+pub fn demo_warehouse_stock() -> Vec<WarehouseStock> {
+    [
+        ("WH-01", 1, "WGT-A-001", 60, 20),
+        ("WH-02", 1, "WGT-A-001", 40, 20),
+        ("WH-01", 2, "WGT-B-002", 20, 10),
+        ("WH-02", 2, "WGT-B-002", 30, 10),
+        ("WH-01", 3, "WGT-C-003", 25, 10),
+        ("WH-01", 4, "GDG-X-004", 10, 15),
+        ("WH-02", 4, "GDG-X-004", 20, 15),
+        ("WH-02", 5, "GDG-Y-005", 15, 5),
+    ]
+    .into_iter()
+    .map(
+        |(warehouse, product_id, sku, on_hand, reorder_point)| WarehouseStock {
+            warehouse: warehouse.to_string(),
+            product_id,
+            sku: sku.to_string(),
+            on_hand,
+            reorder_point,
+        },
+    )
+    .collect()
+}
+
+/// The warehouse to reserve `quantity` units of `sku` from: of those holding
+/// at least `quantity`, the one with the most on hand (ties go to the first
+/// by name). `None` if no single warehouse can cover the quantity.
+pub fn choose_warehouse<'a>(
+    stock: &'a [WarehouseStock],
+    sku: &str,
+    quantity: i64,
+) -> Option<&'a WarehouseStock> {
+    stock
+        .iter()
+        .filter(|s| s.sku == sku && s.on_hand >= quantity)
+        .min_by(|a, b| {
+            b.on_hand
+                .cmp(&a.on_hand)
+                .then_with(|| a.warehouse.cmp(&b.warehouse))
+        })
+}
+
+/// Reserve `quantity` units of `sku` from the warehouse [`choose_warehouse`]
+/// picks, decrementing its `on_hand`. Returns that warehouse's stock as it
+/// was before the reservation, or `None` (changing nothing) if no single
+/// warehouse can cover the quantity.
+pub fn reserve(stock: &mut [WarehouseStock], sku: &str, quantity: i64) -> Option<WarehouseStock> {
+    let chosen = choose_warehouse(stock, sku, quantity)?.clone();
+    let row = stock
+        .iter_mut()
+        .find(|s| s.warehouse == chosen.warehouse && s.sku == chosen.sku)?;
+    row.on_hand -= quantity;
+    Some(chosen)
+}
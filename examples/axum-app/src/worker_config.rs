@@ -0,0 +1,151 @@
+//! Worker poll interval override and namespace template filtering.
+//!
+//! `WorkerBootstrap` reads its configuration from the TOML file named by
+//! `TASKER_CONFIG_PATH`, whose poll intervals are tuned for production. For
+//! demos, `WORKER_POLL_INTERVAL_MS` overrides them without editing the file:
+//! the bootstrap is pointed at a copy of the configuration with every poll
+//! interval in [`POLL_INTERVAL_KEYS`] replaced, so steps are picked up as
+//! soon as they are enqueued. The file is parsed and rewritten with the `toml`
+//! crate.
+//!
+//! ```text
+//! WORKER_POLL_INTERVAL_MS=50
+//! ```
+//!
+//! The worker subscribes to the namespace queues of the templates under
+//! `TASKER_TEMPLATE_PATH`. When `ENABLED_NAMESPACES` narrows the handlers, the
+//! bootstrap is likewise pointed at a copy of that directory holding only the
+//! enabled namespaces' templates (see [`write_namespace_templates`]), so the
+//! instance never receives steps it has no handlers for.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use toml::{Table, Value};
+
+/// Environment variable naming the worker configuration file.
+pub const CONFIG_PATH_VAR: &str = "TASKER_CONFIG_PATH";
+
+/// Environment variable naming the task template directory.
+pub const TEMPLATE_PATH_VAR: &str = "TASKER_TEMPLATE_PATH";
+
+/// Poll interval settings (`section`, `key`, both in milliseconds) replaced by
+/// the override.
+pub const POLL_INTERVAL_KEYS: &[(&str, &str)] = &[
+    ("common.queues.pgmq", "poll_interval_ms"),
+    ("worker.event_systems.worker.metadata.fallback_poller", "polling_interval_ms"),
+];
+
+/// Read `WORKER_POLL_INTERVAL_MS` (unset = use the configuration file).
+pub fn poll_interval_override_from_env() -> Result<Option<u64>, String> {
+    match std::env::var("WORKER_POLL_INTERVAL_MS") {
+        Ok(raw) if !raw.trim().is_empty() => match raw.trim().parse::<u64>() {
+            Ok(ms) if ms > 0 => Ok(Some(ms)),
+            _ => Err(format!(
+                "WORKER_POLL_INTERVAL_MS must be a positive number of milliseconds, got '{}'",
+                raw
+            )),
+        },
+        _ => Ok(None),
+    }
+}
+
+/// The queue poll interval set in `config`, in milliseconds.
+pub fn poll_interval_ms(config: &str) -> Option<u64> {
+    let table: Table = config.parse().ok()?;
+    let (section, key) = POLL_INTERVAL_KEYS[0];
+    section_table(&table, section)?
+        .get(key)?
+        .as_integer()
+        .and_then(|ms| u64::try_from(ms).ok())
+}
+
+/// Replace every poll interval setting in `config` with `interval_ms` and
+/// return the re-serialized configuration. Comments and formatting are not
+/// preserved; every other setting is.
+///
+/// Fails if `config` is not valid TOML or has none of the settings, since the
+/// override would silently do nothing.
+pub fn with_poll_interval(config: &str, interval_ms: u64) -> Result<String, String> {
+    let mut table: Table = config
+        .parse()
+        .map_err(|e| format!("worker configuration is not valid TOML: {}", e))?;
+    let interval = i64::try_from(interval_ms)
+        .map_err(|_| format!("poll interval {}ms is too large", interval_ms))?;
+    let mut replaced = 0;
+    for (section, key) in POLL_INTERVAL_KEYS {
+        if let Some(value) = section_table_mut(&mut table, section).and_then(|t| t.get_mut(*key)) {
+            *value = Value::Integer(interval);
+            replaced += 1;
+        }
+    }
+    if replaced == 0 {
+        return Err("worker configuration has no poll interval settings".to_string());
+    }
+    toml::to_string(&table).map_err(|e| format!("failed to write worker configuration: {}", e))
+}
+
+/// Write a copy of the configuration at `path` with the poll intervals
+/// overridden, returning the copy's path.
+pub fn write_overridden_config(path: &Path, interval_ms: u64) -> anyhow::Result<PathBuf> {
+    let config = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("failed to read worker config {}: {}", path.display(), e))?;
+    let overridden = with_poll_interval(&config, interval_ms).map_err(anyhow::Error::msg)?;
+    let target = std::env::temp_dir().join(format!("tasker-worker-{}.toml", std::process::id()));
+    std::fs::write(&target, overridden)?;
+    Ok(target)
+}
+
+/// Copy the YAML templates in `dir` whose `namespace_name` is one of
+/// `namespaces` to a new directory, returning its path.
+///
+/// Fails if no template matches, since the worker would subscribe to no queue.
+pub fn write_namespace_templates(
+    dir: &Path,
+    namespaces: &HashSet<String>,
+) -> anyhow::Result<PathBuf> {
+    let entries = std::fs::read_dir(dir)
+        .map_err(|e| anyhow::anyhow!("failed to read templates {}: {}", dir.display(), e))?;
+    let target = std::env::temp_dir().join(format!("tasker-templates-{}", std::process::id()));
+    if target.exists() {
+        std::fs::remove_dir_all(&target)?;
+    }
+    std::fs::create_dir_all(&target)?;
+
+    let mut copied = 0;
+    for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+        if !path.extension().is_some_and(|ext| ext == "yaml" || ext == "yml") {
+            continue;
+        }
+        let yaml = std::fs::read_to_string(&path)?;
+        let template: serde_yaml::Value = serde_yaml::from_str(&yaml)
+            .map_err(|e| anyhow::anyhow!("invalid template {}: {}", path.display(), e))?;
+        let enabled = template["namespace_name"]
+            .as_str()
+            .is_some_and(|namespace| namespaces.contains(namespace));
+        if enabled {
+            if let Some(name) = path.file_name() {
+                std::fs::write(target.join(name), yaml)?;
+                copied += 1;
+            }
+        }
+    }
+    if copied == 0 {
+        anyhow::bail!("{} has no templates for the enabled namespaces", dir.display());
+    }
+    Ok(target)
+}
+
+/// The table at dotted path `section`.
+fn section_table<'a>(table: &'a Table, section: &str) -> Option<&'a Table> {
+    section
+        .split('.')
+        .try_fold(table, |table, name| table.get(name)?.as_table())
+}
+
+/// The table at dotted path `section`, mutably.
+fn section_table_mut<'a>(table: &'a mut Table, section: &str) -> Option<&'a mut Table> {
+    section
+        .split('.')
+        .try_fold(table, |table, name| table.get_mut(name)?.as_table_mut())
+}
@@ -0,0 +1,326 @@
+//! Admin route tests that need no database: template validation against the
+//! handler registry, the configuration report, the debug handler registry
+//! listing, and admin token checks.
+//!
+//! The app is served with a lazily-connected pool, so no database, worker, or
+//! orchestration services are needed.
+//!
+//! Run: cargo test --test admin
+
+mod common;
+
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+
+use axum::routing::get;
+use axum::{middleware, Router};
+
+use example_axum_app::admin_auth::{require_admin, AdminAuth};
+use example_axum_app::handler_policy::HandlerPolicy;
+use example_axum_app::handler_registry::AxumHandlerRegistry;
+use example_axum_app::AppConfig;
+
+use common::{spawn_app, spawn_app_with_config};
+
+async fn validate_template(base_url: &str, body: String) -> (u16, Value) {
+    let res = reqwest::Client::new()
+        .post(format!("{}/admin/validate-template", base_url))
+        .body(body)
+        .send()
+        .await
+        .expect("Failed to send request");
+    let status = res.status().as_u16();
+    (status, res.json().await.unwrap_or(Value::Null))
+}
+
+// ---------------------------------------------------------------------------
+// POST /admin/validate-template
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn validate_template_flags_unregistered_step() {
+    let base_url = spawn_app().await;
+    let template = r#"
+name: refund_with_audit
+namespace_name: payments_rs
+steps:
+  - name: validate_payment_eligibility
+    handler:
+      callable: team_scaling_payments_validate_eligibility
+  - name: audit_refund
+    handler:
+      callable: team_scaling_payments_audit_refund
+    dependencies: [validate_payment_eligibility]
+"#;
+
+    let (status, body) = validate_template(&base_url, template.to_string()).await;
+    assert_eq!(status, 200);
+    assert_eq!(
+        body["data"]["missing"],
+        json!(["team_scaling_payments_audit_refund"])
+    );
+    assert_eq!(
+        body["data"]["registered"],
+        json!(["team_scaling_payments_validate_eligibility"])
+    );
+
+    let unused = body["data"]["unused"].as_array().unwrap();
+    assert!(unused.contains(&json!("team_scaling_payments_notify_customer")));
+    assert!(!unused.contains(&json!("team_scaling_payments_validate_eligibility")));
+}
+
+#[tokio::test]
+async fn validate_template_accepts_shipped_json_and_yaml_templates() {
+    let base_url = spawn_app().await;
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/config/templates/ecommerce_order_processing.yaml");
+    let yaml = std::fs::read_to_string(path).expect("Failed to read template");
+
+    let (status, body) = validate_template(&base_url, yaml.clone()).await;
+    assert_eq!(status, 200);
+    assert_eq!(body["data"]["missing"], json!([]));
+    assert!(body["data"]["registered"]
+        .as_array()
+        .unwrap()
+        .contains(&json!("ecommerce_calculate_shipping")));
+
+    // The same template as JSON
+    let as_json: Value = serde_yaml::from_str(&yaml).unwrap();
+    let (status, body) = validate_template(&base_url, as_json.to_string()).await;
+    assert_eq!(status, 200);
+    assert_eq!(body["data"]["missing"], json!([]));
+}
+
+#[tokio::test]
+async fn validate_template_rejects_step_without_callable() {
+    let base_url = spawn_app().await;
+
+    let (status, body) = validate_template(
+        &base_url,
+        json!({"name": "broken", "steps": [{"name": "a", "handler": {}}]}).to_string(),
+    )
+    .await;
+    assert_eq!(status, 422);
+    assert_eq!(body["error"]["field"], "steps[0].handler.callable");
+
+    let (status, body) = validate_template(&base_url, "steps: [unclosed".to_string()).await;
+    assert_eq!(status, 422);
+    assert_eq!(body["error"]["field"], "template");
+}
+
+// ---------------------------------------------------------------------------
+// GET /admin/config
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn config_reports_orchestration_circuit_breaker() {
+    let base_url = spawn_app().await;
+
+    let res = reqwest::get(format!("{}/admin/config", base_url))
+        .await
+        .expect("Failed to send request");
+    assert_eq!(res.status(), 200);
+    let body: Value = res.json().await.unwrap();
+
+    let orchestration = &body["data"]["orchestration"];
+    assert!(orchestration["base_url"].is_string());
+    assert_eq!(orchestration["circuit_breaker"]["state"], "closed");
+    assert_eq!(orchestration["circuit_breaker"]["failure_threshold"], 5);
+    assert_eq!(orchestration["circuit_breaker"]["cooldown_secs"], 30);
+    assert!(body["data"]["enabled_namespaces"]
+        .as_array()
+        .unwrap()
+        .contains(&json!("ecommerce_rs")));
+}
+
+
+// ---------------------------------------------------------------------------
+// GET /admin/handlers
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn handlers_report_the_active_policy() {
+    let registry = AxumHandlerRegistry::with_policy(
+        None,
+        None,
+        HandlerPolicy::allow_all().denying(["*notify_customer"]),
+    )
+    .unwrap();
+    let base_url = spawn_app_with_config(AppConfig {
+        handler_registry: Arc::new(registry),
+        ..AppConfig::default()
+    })
+    .await;
+
+    let res = reqwest::get(format!("{}/admin/handlers", base_url))
+        .await
+        .expect("Failed to send request");
+    assert_eq!(res.status(), 200);
+    let body: Value = res.json().await.unwrap();
+
+    assert_eq!(body["data"]["policy"]["allow"], Value::Null);
+    assert_eq!(body["data"]["policy"]["deny"], json!(["*notify_customer"]));
+    assert_eq!(
+        body["data"]["disabled"],
+        json!(["team_scaling_payments_notify_customer"])
+    );
+    assert_eq!(body["data"]["registered"].as_array().unwrap().len(), 29);
+}
+
+// ---------------------------------------------------------------------------
+// Admin token
+// ---------------------------------------------------------------------------
+
+// ---------------------------------------------------------------------------
+// GET /debug/registry
+// ---------------------------------------------------------------------------
+
+/// Every handler the shipped templates (and the optional reconcile step) call.
+const KNOWN_HANDLERS: &[&str] = &[
+    "data_pipeline_aggregate_metrics",
+    "data_pipeline_extract_customers",
+    "data_pipeline_extract_inventory",
+    "data_pipeline_extract_sales",
+    "data_pipeline_generate_insights",
+    "data_pipeline_transform_customers",
+    "data_pipeline_transform_inventory",
+    "data_pipeline_transform_sales",
+    "ecommerce_calculate_shipping",
+    "ecommerce_create_order",
+    "ecommerce_process_payment",
+    "ecommerce_reconcile_order",
+    "ecommerce_send_confirmation",
+    "ecommerce_update_inventory",
+    "ecommerce_validate_cart",
+    "microservices_create_user_account",
+    "microservices_initialize_preferences",
+    "microservices_send_welcome_sequence",
+    "microservices_setup_billing_profile",
+    "microservices_update_user_status",
+    "team_scaling_cs_check_refund_policy",
+    "team_scaling_cs_execute_refund_workflow",
+    "team_scaling_cs_get_manager_approval",
+    "team_scaling_cs_update_ticket_status",
+    "team_scaling_cs_validate_refund_request",
+    "team_scaling_payments_notify_customer",
+    "team_scaling_payments_process_gateway_refund",
+    "team_scaling_payments_update_records",
+    "team_scaling_payments_validate_eligibility",
+];
+
+#[tokio::test]
+async fn debug_registry_lists_every_registered_handler() {
+    let base_url = spawn_app_with_config(AppConfig {
+        debug_endpoints: true,
+        ..Default::default()
+    })
+    .await;
+
+    let res = reqwest::get(format!("{}/debug/registry", base_url))
+        .await
+        .expect("Failed to send request");
+    assert_eq!(res.status(), 200);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["data"]["count"], KNOWN_HANDLERS.len());
+    assert_eq!(body["data"]["handlers"], json!(KNOWN_HANDLERS));
+}
+
+#[tokio::test]
+async fn debug_registry_is_off_by_default() {
+    let base_url = spawn_app().await;
+    let res = reqwest::get(format!("{}/debug/registry", base_url))
+        .await
+        .expect("Failed to send request");
+    assert_eq!(res.status(), 404);
+}
+
+#[tokio::test]
+async fn admin_routes_require_the_configured_token() {
+    let base_url = spawn_app_with_config(AppConfig {
+        admin_auth: AdminAuth::new(Some("s3cret".to_string())),
+        ..AppConfig::default()
+    })
+    .await;
+    let client = reqwest::Client::new();
+
+    for authorization in [None, Some("Bearer wrong"), Some("s3cret")] {
+        for request in [
+            client.get(format!("{}/admin/config", base_url)),
+            client.post(format!("{}/admin/reconcile", base_url)),
+        ] {
+            let request = match authorization {
+                Some(value) => request.header("Authorization", value),
+                None => request,
+            };
+            let res = request.send().await.expect("Failed to send request");
+            assert_eq!(res.status(), 401, "Authorization: {authorization:?}");
+            assert_eq!(res.headers()["www-authenticate"], "Bearer");
+            let body: Value = res.json().await.expect("Expected JSON error body");
+            assert_eq!(body["error"]["code"], "unauthorized");
+        }
+    }
+
+    let res = client
+        .get(format!("{}/admin/config", base_url))
+        .bearer_auth("s3cret")
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert_eq!(res.status(), 200);
+
+    // Authorized, then rejected by validation before touching the database
+    let res = client
+        .post(format!("{}/admin/reconcile?status=complete", base_url))
+        .bearer_auth("s3cret")
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert_eq!(res.status(), 422);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["error"]["field"], "status");
+}
+
+#[tokio::test]
+async fn admin_routes_fail_closed_without_the_auth_extension() {
+    let app = Router::new()
+        .route("/admin/config", get(|| async { "open" }))
+        .route_layer(middleware::from_fn(require_admin));
+    let base_url = common::serve(app).await;
+
+    let res = reqwest::get(format!("{}/admin/config", base_url))
+        .await
+        .expect("Failed to send request");
+    assert_eq!(res.status(), 500);
+    let body: Value = res.json().await.expect("Expected JSON error body");
+    assert_eq!(body["error"]["code"], "internal_server_error");
+}
+
+#[tokio::test]
+async fn reconcile_limit_is_bounded() {
+    let base_url = spawn_app().await;
+
+    for limit in ["0", "1001"] {
+        let res = reqwest::Client::new()
+            .post(format!("{}/admin/reconcile?limit={}", base_url, limit))
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(res.status(), 422, "limit={limit}");
+        let body: Value = res.json().await.unwrap();
+        assert_eq!(body["error"]["field"], "limit");
+    }
+}
+
+#[tokio::test]
+async fn task_results_reject_a_malformed_uuid() {
+    let base_url = spawn_app().await;
+
+    let res = reqwest::Client::new()
+        .get(format!("{}/admin/tasks/not-a-uuid/results", base_url))
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert_eq!(res.status(), 400);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["error"]["code"], "bad_request");
+}
@@ -0,0 +1,47 @@
+//! Helpers shared by the test binaries.
+//!
+//! Each binary uses only some of them.
+#![allow(dead_code)]
+
+use std::net::SocketAddr;
+
+use axum::Router;
+
+use example_axum_app::{create_app_with_config, AppConfig};
+
+/// A pool to the app database that connects on first use, so tests that never
+/// touch the database run without one.
+pub fn lazy_pool() -> sqlx::PgPool {
+    sqlx::postgres::PgPoolOptions::new()
+        .connect_lazy(&example_axum_app::db::pool_from_env())
+        .expect("Failed to create lazy pool")
+}
+
+/// Serve `app` on a random local port and return its base URL.
+pub async fn serve(app: Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind test listener");
+    let addr = listener.local_addr().expect("Failed to get local address");
+
+    tokio::spawn(async move {
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await
+        .expect("Test server failed");
+    });
+
+    format!("http://127.0.0.1:{}", addr.port())
+}
+
+/// Serve the app built with `config` over a [`lazy_pool`].
+pub async fn spawn_app_with_config(config: AppConfig) -> String {
+    serve(create_app_with_config(lazy_pool(), config)).await
+}
+
+/// Serve the app with the default configuration over a [`lazy_pool`].
+pub async fn spawn_app() -> String {
+    spawn_app_with_config(AppConfig::default()).await
+}
@@ -0,0 +1,109 @@
+//! Concurrency limit tests.
+//!
+//! The limit middleware is served in front of a slow stand-in for
+//! `POST /orders`, so requests stay in flight long enough to saturate the
+//! route. No database, worker, or orchestration services are needed.
+//!
+//! Run: cargo test --test concurrency
+
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::http::StatusCode;
+use axum::routing::post;
+use axum::{middleware, Extension, Router};
+use serde_json::Value;
+
+use example_axum_app::concurrency::{limit_concurrency, ConcurrencyLimits};
+
+/// Serve a slow `POST /orders` behind `limits` and return its base URL.
+async fn spawn_app(limits: ConcurrencyLimits) -> String {
+    let app = Router::new()
+        .route(
+            "/orders",
+            post(|| async {
+                tokio::time::sleep(Duration::from_millis(300)).await;
+                StatusCode::CREATED
+            }),
+        )
+        .layer(middleware::from_fn(limit_concurrency))
+        .layer(Extension(Arc::new(limits)));
+
+    common::serve(app).await
+}
+
+#[tokio::test]
+async fn requests_over_the_limit_get_503() {
+    let limits = ConcurrencyLimits::new().with_limit("/orders", 2).unwrap();
+    let base_url = spawn_app(limits).await;
+    let client = reqwest::Client::new();
+
+    let responses = futures::future::join_all((0..6).map(|_| {
+        client
+            .post(format!("{}/orders", base_url))
+            .send()
+    }))
+    .await;
+
+    let mut created = 0;
+    let mut shed = 0;
+    for res in responses {
+        let res = res.expect("Failed to send request");
+        match res.status().as_u16() {
+            201 => created += 1,
+            503 => {
+                shed += 1;
+                assert_eq!(res.headers()["retry-after"], "1");
+                let body: Value = res.json().await.unwrap();
+                assert_eq!(body["error"]["code"], "overloaded");
+                assert_eq!(body["error"]["route"], "/orders");
+            }
+            other => panic!("Unexpected status {other}"),
+        }
+    }
+    assert_eq!(created, 2);
+    assert_eq!(shed, 4);
+
+    // Permits are released once the in-flight requests finish
+    let res = client
+        .post(format!("{}/orders", base_url))
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert_eq!(res.status(), 201);
+}
+
+#[tokio::test]
+async fn unlimited_routes_are_not_shed() {
+    let base_url = spawn_app(ConcurrencyLimits::new()).await;
+    let client = reqwest::Client::new();
+
+    let responses = futures::future::join_all((0..6).map(|_| {
+        client
+            .post(format!("{}/orders", base_url))
+            .send()
+    }))
+    .await;
+    for res in responses {
+        assert_eq!(res.expect("Failed to send request").status(), 201);
+    }
+}
+
+#[test]
+fn concurrency_limits_parse() {
+    let limits = ConcurrencyLimits::parse("*=64, /analytics=8").unwrap();
+    assert_eq!(limits.limit("/orders"), Some(64));
+    assert_eq!(limits.limit("/compliance/refund"), Some(64));
+    assert_eq!(limits.limit("/analytics"), Some(8));
+
+    let limits = ConcurrencyLimits::parse("*=4,/orders=0").unwrap();
+    assert_eq!(limits.limit("/orders"), None);
+    assert_eq!(limits.limit("/orders/async"), Some(4));
+
+    assert!(ConcurrencyLimits::parse("").unwrap().is_empty());
+    assert!(ConcurrencyLimits::parse("/orders").is_err());
+    assert!(ConcurrencyLimits::parse("/orders=many").is_err());
+    assert!(ConcurrencyLimits::parse("/products=4").is_err());
+}
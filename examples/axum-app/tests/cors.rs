@@ -0,0 +1,95 @@
+//! CORS policy tests.
+//!
+//! Preflight requests are answered by the CORS layer, so the app is served with
+//! a lazily-connected pool and no database or orchestration is needed.
+//!
+//! Run: cargo test --test cors
+
+mod common;
+
+use example_axum_app::cors::CorsConfig;
+use example_axum_app::AppConfig;
+
+/// Serve the app with `cors` on a random local port and return its base URL.
+async fn spawn_app(cors: CorsConfig) -> String {
+    common::spawn_app_with_config(AppConfig {
+        cors,
+        ..Default::default()
+    })
+    .await
+}
+
+/// Send a preflight for `POST /orders` with an `Idempotency-Key` from `origin`.
+async fn preflight(base_url: &str, origin: &str) -> reqwest::Response {
+    reqwest::Client::new()
+        .request(reqwest::Method::OPTIONS, format!("{}/orders", base_url))
+        .header("origin", origin)
+        .header("access-control-request-method", "POST")
+        .header(
+            "access-control-request-headers",
+            "content-type,idempotency-key",
+        )
+        .send()
+        .await
+        .expect("Failed to send request")
+}
+
+#[test]
+fn origin_lists_are_parsed() {
+    let cors = CorsConfig::from_origins(" https://shop.example.com, https://admin.example.com/ ,")
+        .unwrap();
+    assert!(!cors.is_permissive());
+    let origins: Vec<&str> = cors
+        .allowed_origins()
+        .unwrap()
+        .iter()
+        .map(|origin| origin.to_str().unwrap())
+        .collect();
+    assert_eq!(
+        origins,
+        ["https://shop.example.com", "https://admin.example.com"]
+    );
+
+    let any = CorsConfig::from_origins("*").unwrap();
+    assert!(!any.is_permissive());
+    assert!(any.allowed_origins().is_none());
+
+    for invalid in ["", " , ", "*,https://shop.example.com", "shop.example.com"] {
+        assert!(CorsConfig::from_origins(invalid).is_err(), "{invalid:?}");
+    }
+}
+
+#[tokio::test]
+async fn listed_origins_pass_preflight() {
+    let base_url = spawn_app(CorsConfig::from_origins("https://shop.example.com").unwrap()).await;
+
+    let res = preflight(&base_url, "https://shop.example.com").await;
+    assert_eq!(res.status(), 200);
+    let headers = res.headers();
+    assert_eq!(
+        headers["access-control-allow-origin"],
+        "https://shop.example.com"
+    );
+    let allowed_headers = headers["access-control-allow-headers"].to_str().unwrap();
+    assert!(
+        allowed_headers.contains("idempotency-key"),
+        "{allowed_headers}"
+    );
+    assert!(allowed_headers.contains("x-api-key"), "{allowed_headers}");
+}
+
+#[tokio::test]
+async fn unlisted_origins_are_not_allowed() {
+    let base_url = spawn_app(CorsConfig::from_origins("https://shop.example.com").unwrap()).await;
+
+    let res = preflight(&base_url, "https://evil.example.com").await;
+    assert!(res.headers().get("access-control-allow-origin").is_none());
+}
+
+#[tokio::test]
+async fn permissive_policy_allows_any_origin() {
+    let base_url = spawn_app(CorsConfig::Permissive).await;
+
+    let res = preflight(&base_url, "https://evil.example.com").await;
+    assert_eq!(res.headers()["access-control-allow-origin"], "*");
+}
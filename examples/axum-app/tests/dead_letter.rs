@@ -0,0 +1,232 @@
+//! Dead-letter alert tests against a mock alert webhook.
+//!
+//! No database, worker, or orchestration services are needed.
+//!
+//! Run: cargo test --test dead_letter
+
+use std::time::{Duration, Instant};
+
+use serde_json::json;
+use tasker_shared::messaging::StepExecutionResult;
+use tasker_shared::types::base::{
+    Task, TaskForOrchestration, TaskSequenceStep, WorkflowStepWithName,
+};
+use uuid::Uuid;
+use wiremock::matchers::{body_json, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use example_axum_app::dead_letter::{
+    AlertError, AlertWebhook, AlertWebhookConfig, DeadLetterAlert, DeadLetterAlerter,
+};
+use example_axum_app::retry::RetryPolicy;
+
+/// Webhook settings for `server` without the production delays.
+fn test_config(server: &MockServer) -> AlertWebhookConfig {
+    AlertWebhookConfig {
+        retry_delay: Duration::from_millis(10),
+        ..AlertWebhookConfig::new(format!("{}/alerts", server.uri()))
+    }
+}
+
+fn declined_payment() -> StepExecutionResult {
+    RetryPolicy::default().failure_result(
+        Uuid::new_v4(),
+        "Payment declined: insufficient funds".to_string(),
+        3,
+    )
+}
+
+/// Attempt `attempts` of `max_attempts` of the `process_payment` step.
+fn payment_step(task_uuid: Uuid, attempts: i32, max_attempts: i32) -> TaskSequenceStep {
+    TaskSequenceStep {
+        task: TaskForOrchestration {
+            task: Task {
+                task_uuid,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        workflow_step: WorkflowStepWithName {
+            task_uuid,
+            name: "process_payment".to_string(),
+            attempts: Some(attempts),
+            max_attempts: Some(max_attempts),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+fn sample_alert() -> DeadLetterAlert {
+    DeadLetterAlert {
+        task_uuid: Uuid::new_v4(),
+        step_name: "process_payment".to_string(),
+        category: "permanent".to_string(),
+        error: "Payment declined: insufficient funds".to_string(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Dead-letter detection
+// ---------------------------------------------------------------------------
+
+#[test]
+fn only_non_retryable_failures_dead_letter() {
+    let task_uuid = Uuid::new_v4();
+
+    let alert =
+        DeadLetterAlert::from_result(task_uuid, "process_payment", &declined_payment(), false)
+            .expect("A permanent failure should dead-letter");
+    assert_eq!(alert.task_uuid, task_uuid);
+    assert_eq!(alert.step_name, "process_payment");
+    assert_eq!(alert.category, "permanent");
+    assert_eq!(alert.error, "Payment declined: insufficient funds");
+
+    let transient = RetryPolicy::default().failure_result(
+        Uuid::new_v4(),
+        "Gateway timeout (retryable)".to_string(),
+        3,
+    );
+    assert_eq!(
+        DeadLetterAlert::from_result(task_uuid, "process_payment", &transient, false),
+        None
+    );
+
+    let success = StepExecutionResult::success(Uuid::new_v4(), json!({"ok": true}), 3, None);
+    assert_eq!(
+        DeadLetterAlert::from_result(task_uuid, "process_payment", &success, true),
+        None
+    );
+}
+
+#[test]
+fn retryable_failures_dead_letter_once_retries_are_exhausted() {
+    let task_uuid = Uuid::new_v4();
+    let transient = RetryPolicy::default().failure_result(
+        Uuid::new_v4(),
+        "Gateway timeout (retryable)".to_string(),
+        3,
+    );
+
+    assert_eq!(DeadLetterAlert::from_step(&payment_step(task_uuid, 2, 3), &transient), None);
+
+    let alert = DeadLetterAlert::from_step(&payment_step(task_uuid, 3, 3), &transient)
+        .expect("The last failed attempt should dead-letter");
+    assert_eq!(alert.task_uuid, task_uuid);
+    assert_eq!(alert.step_name, "process_payment");
+    assert_eq!(alert.category, "transient");
+    assert_eq!(alert.error, "Gateway timeout (retryable)");
+}
+
+// ---------------------------------------------------------------------------
+// Delivery
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn dead_lettered_step_fires_alert() {
+    let server = MockServer::start().await;
+    let task_uuid = Uuid::new_v4();
+    Mock::given(method("POST"))
+        .and(path("/alerts"))
+        .and(body_json(json!({
+            "task_uuid": task_uuid,
+            "step_name": "process_payment",
+            "category": "permanent",
+            "error": "Payment declined: insufficient funds"
+        })))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let alerter = DeadLetterAlerter::new(test_config(&server));
+    let alert =
+        DeadLetterAlert::from_result(task_uuid, "process_payment", &declined_payment(), false)
+            .expect("A permanent failure should dead-letter");
+    alerter.notify(alert).await.expect("Alert task panicked");
+
+    server.verify().await;
+}
+
+#[tokio::test]
+async fn failed_delivery_is_retried() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/alerts"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(2)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/alerts"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    let webhook = AlertWebhook::new(test_config(&server));
+    webhook.send(&sample_alert()).await.expect("Third attempt should succeed");
+
+    assert_eq!(server.received_requests().await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn breaker_skips_alerts_while_endpoint_is_down() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/alerts"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+
+    let webhook = AlertWebhook::new(AlertWebhookConfig {
+        max_attempts: 1,
+        failure_threshold: 2,
+        ..test_config(&server)
+    });
+
+    for _ in 0..2 {
+        let err = webhook.send(&sample_alert()).await.unwrap_err();
+        assert!(matches!(err, AlertError::Status(status) if status == 500), "got {err:?}");
+    }
+
+    // The breaker is open: no request is made
+    let start = Instant::now();
+    let err = webhook.send(&sample_alert()).await.unwrap_err();
+    assert!(matches!(err, AlertError::CircuitOpen), "got {err:?}");
+    assert!(start.elapsed() < Duration::from_millis(100));
+    assert_eq!(server.received_requests().await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn breaker_closes_after_successful_trial() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/alerts"))
+        .respond_with(ResponseTemplate::new(500))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/alerts"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    let webhook = AlertWebhook::new(AlertWebhookConfig {
+        max_attempts: 1,
+        failure_threshold: 1,
+        cooldown: Duration::from_millis(50),
+        ..test_config(&server)
+    });
+
+    assert!(webhook.send(&sample_alert()).await.is_err());
+    assert!(matches!(
+        webhook.send(&sample_alert()).await,
+        Err(AlertError::CircuitOpen)
+    ));
+
+    // After the cooldown a trial alert goes through and closes the breaker
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    webhook.send(&sample_alert()).await.expect("Trial alert should succeed");
+    webhook.send(&sample_alert()).await.expect("Breaker should be closed");
+}
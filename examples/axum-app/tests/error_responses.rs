@@ -0,0 +1,92 @@
+//! Error envelope tests: every route error renders `{"error": {"code", "message"}}`.
+//!
+//! The app is served with a lazily-connected pool pointing at a port nothing
+//! listens on, so queries fail fast and no database or orchestration is needed.
+//!
+//! Run: cargo test --test error_responses
+
+mod common;
+
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+/// Nothing listens on the discard port, so connections are refused at once.
+const UNREACHABLE: &str = "127.0.0.1:9";
+
+/// Serve the app on a random local port and return its base URL.
+async fn spawn_app() -> String {
+    let pool = sqlx::postgres::PgPoolOptions::new()
+        .acquire_timeout(Duration::from_secs(1))
+        .connect_lazy(&format!(
+            "postgresql://tasker:tasker@{UNREACHABLE}/example_axum"
+        ))
+        .expect("Failed to create lazy pool");
+    common::serve(example_axum_app::create_app(pool)).await
+}
+
+/// Assert the response status and return the `error` object of its body.
+async fn error_body(res: reqwest::Response, status: u16) -> Value {
+    assert_eq!(res.status(), status);
+    let body: Value = res.json().await.expect("Expected JSON error body");
+    assert!(body["error"]["message"].is_string(), "{body}");
+    body["error"].clone()
+}
+
+#[tokio::test]
+async fn malformed_json_returns_400() {
+    let base_url = spawn_app().await;
+
+    let res = reqwest::Client::new()
+        .post(format!("{}/orders", base_url))
+        .header("content-type", "application/json")
+        .body(r#"{"customer_email": "a@example.com","#)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    let error = error_body(res, 400).await;
+    assert_eq!(error["code"], "bad_request");
+}
+
+#[tokio::test]
+async fn unknown_workflow_returns_404() {
+    let base_url = spawn_app().await;
+
+    let res = reqwest::Client::new()
+        .post(format!("{}/simulate/no_such_workflow", base_url))
+        .json(&json!({}))
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    let error = error_body(res, 404).await;
+    assert_eq!(error["code"], "not_found");
+}
+
+#[tokio::test]
+async fn database_failure_returns_500_without_details() {
+    let base_url = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    for path in [
+        "/orders",
+        "/orders/1",
+        "/analytics/1",
+        "/services",
+        "/compliance/1",
+    ] {
+        let res = client
+            .get(format!("{}{}", base_url, path))
+            .send()
+            .await
+            .expect("Failed to send request");
+
+        let error = error_body(res, 500).await;
+        assert_eq!(error["code"], "database_error", "{path}");
+        assert!(
+            !error["message"].as_str().unwrap().contains(UNREACHABLE),
+            "{path} leaked the database error: {error}"
+        );
+    }
+}
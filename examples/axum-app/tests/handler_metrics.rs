@@ -0,0 +1,107 @@
+//! Per-handler outcome count tests.
+//!
+//! Results are recorded directly, as the worker's metrics callback would, and
+//! read back both from the snapshot and from `GET /debug/handlers`. No
+//! database, worker, or orchestration services are needed.
+//!
+//! Run: cargo test --test handler_metrics
+
+mod common;
+
+use serde_json::{json, Value};
+use tasker_shared::messaging::StepExecutionResult;
+use uuid::Uuid;
+
+use example_axum_app::handler_metrics::{HandlerCounts, HandlerMetrics};
+use example_axum_app::retry::RetryPolicy;
+use example_axum_app::AppConfig;
+
+fn completed() -> StepExecutionResult {
+    StepExecutionResult::success(Uuid::new_v4(), json!({"ok": true}), 3, None)
+}
+
+fn failed(message: &str) -> StepExecutionResult {
+    RetryPolicy::default().failure_result(Uuid::new_v4(), message.to_string(), 3)
+}
+
+/// Serve the app with `metrics` on a random local port and return its base URL.
+async fn spawn_app(metrics: HandlerMetrics) -> String {
+    common::spawn_app_with_config(AppConfig {
+        handler_metrics: metrics,
+        ..Default::default()
+    })
+    .await
+}
+
+#[test]
+fn outcomes_are_counted_per_handler() {
+    let metrics = HandlerMetrics::new();
+    metrics.record("ecommerce_process_payment", &completed());
+    metrics.record("ecommerce_process_payment", &failed("Gateway timeout"));
+    metrics.record(
+        "ecommerce_process_payment",
+        &failed("Payment declined: insufficient funds"),
+    );
+    metrics.record("ecommerce_validate_cart", &completed());
+
+    let snapshot = metrics.snapshot();
+    assert_eq!(snapshot.len(), 2);
+    assert_eq!(
+        snapshot["ecommerce_process_payment"],
+        HandlerCounts {
+            completed: 1,
+            failed: 2,
+            last_error: Some("Payment declined: insufficient funds".to_string()),
+        }
+    );
+    assert_eq!(
+        snapshot["ecommerce_validate_cart"],
+        HandlerCounts {
+            completed: 1,
+            failed: 0,
+            last_error: None,
+        }
+    );
+}
+
+#[test]
+fn last_error_survives_later_successes() {
+    let metrics = HandlerMetrics::new();
+    metrics.record("analytics_extract_data", &failed("Connection refused"));
+    metrics.record("analytics_extract_data", &completed());
+
+    let counts = &metrics.snapshot()["analytics_extract_data"];
+    assert_eq!(counts.completed, 1);
+    assert_eq!(counts.failed, 1);
+    assert_eq!(counts.last_error.as_deref(), Some("Connection refused"));
+}
+
+#[tokio::test]
+async fn debug_handlers_serves_the_shared_counts() {
+    let metrics = HandlerMetrics::new();
+    let base_url = spawn_app(metrics.clone()).await;
+
+    // Recorded after the app was built: the route reads the live counts
+    metrics.record("ecommerce_process_payment", &completed());
+    metrics.record(
+        "ecommerce_process_payment",
+        &failed("Payment declined: insufficient funds"),
+    );
+
+    let res = reqwest::get(format!("{}/debug/handlers", base_url))
+        .await
+        .expect("Failed to send request");
+    assert_eq!(res.status(), 200);
+
+    let body: Value = res.json().await.expect("Expected JSON body");
+    assert_eq!(
+        body["data"],
+        json!({
+            "ecommerce_process_payment": {
+                "completed": 1,
+                "failed": 1,
+                "last_error": "Payment declined: insufficient funds",
+            }
+        })
+    );
+}
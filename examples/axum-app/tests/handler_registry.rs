@@ -0,0 +1,626 @@
+//! Handler registry tests: namespace filtering, handler lookup, retry policies,
+//! payment gateways, versioned handlers, handler manifests, and allow/deny
+//! policies.
+//!
+//! These tests exercise `AxumHandlerRegistry` directly and need no database
+//! or orchestration services.
+//!
+//! Run: cargo test --test handler_registry
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+
+use example_axum_app::gateway::{
+    ChargeReceipt, ChargeRequest, GatewayError, PaymentGateway, RefundReceipt, RefundRequest,
+};
+use example_axum_app::handler_manifest::{HandlerManifest, HandlerManifestError};
+use example_axum_app::handler_policy::{glob_match, HandlerPolicy, DISABLED_MESSAGE};
+use example_axum_app::handler_registry::{
+    blocking_with_timeout, parse_namespace_list, timed, AxumHandlerRegistry,
+    DEFAULT_HANDLER_TIMEOUT,
+};
+use example_axum_app::handlers;
+use example_axum_app::handlers::ecommerce::Product;
+use example_axum_app::retry::{FailureCategory, RetryPolicy};
+use example_axum_app::warehouses::WarehouseStock;
+use tasker_shared::messaging::StepExecutionResult;
+use tasker_shared::types::base::TaskSequenceStep;
+use tasker_shared::TaskerResult;
+use tasker_worker::worker::handlers::{StepHandler, StepHandlerRegistry};
+
+// ---------------------------------------------------------------------------
+// Namespace filtering
+// ---------------------------------------------------------------------------
+
+#[test]
+fn default_registry_exposes_all_namespaces() {
+    let registry = AxumHandlerRegistry::new();
+
+    assert_eq!(registry.handler_count(), 29);
+    assert_eq!(
+        registry.enabled_namespaces(),
+        vec![
+            "ecommerce_rs",
+            "data_pipeline_rs",
+            "microservices_rs",
+            "customer_success_rs",
+            "payments_rs",
+        ]
+    );
+}
+
+#[test]
+fn restricted_registry_only_exposes_enabled_handlers() {
+    let registry =
+        AxumHandlerRegistry::with_namespaces(Some(HashSet::from(["payments_rs".to_string()])));
+
+    let mut handlers = registry.registered_handlers();
+    handlers.sort();
+    assert_eq!(
+        handlers,
+        vec![
+            "team_scaling_payments_notify_customer",
+            "team_scaling_payments_process_gateway_refund",
+            "team_scaling_payments_update_records",
+            "team_scaling_payments_validate_eligibility",
+        ]
+    );
+    assert_eq!(registry.enabled_namespaces(), vec!["payments_rs"]);
+
+    assert!(registry.handler_available("team_scaling_payments_update_records"));
+    assert!(!registry.handler_available("ecommerce_validate_cart"));
+    assert!(!registry.handler_available("team_scaling_cs_validate_refund_request"));
+}
+
+#[test]
+fn parse_namespace_list_trims_and_ignores_blanks() {
+    assert_eq!(
+        parse_namespace_list(" payments_rs, ecommerce_rs ,,"),
+        Ok(HashSet::from([
+            "payments_rs".to_string(),
+            "ecommerce_rs".to_string()
+        ]))
+    );
+}
+
+#[test]
+fn parse_namespace_list_rejects_unknown_and_empty_lists() {
+    let err = parse_namespace_list("payments_rs,paymnets_rs").unwrap_err();
+    assert!(err.contains("unknown namespaces paymnets_rs"), "{err}");
+    assert!(parse_namespace_list("").is_err());
+    assert!(parse_namespace_list(" , ").is_err());
+}
+
+// ---------------------------------------------------------------------------
+// Product catalog
+// ---------------------------------------------------------------------------
+
+#[test]
+fn cart_handlers_use_the_loaded_catalog() {
+    let registry = AxumHandlerRegistry::new();
+    let context = json!({
+        "cart_items": [{"product_id": 7, "quantity": 2}],
+        "customer_email": "catalog@example.com",
+        "payment_token": "tok_test_success"
+    });
+    // Product 7 is not in the demo catalog
+    assert!(registry
+        .call_function("ecommerce_validate_cart", &context, &HashMap::new())
+        .unwrap()
+        .is_err());
+
+    registry.set_catalog(vec![Product {
+        id: 7,
+        name: "Sprocket".into(),
+        sku: "SPR-7".into(),
+        price: 12.5,
+        stock: 3,
+    }]);
+    assert_eq!(registry.products().len(), 1);
+    registry.set_warehouse_stock(vec![WarehouseStock {
+        warehouse: "WH-03".into(),
+        product_id: 7,
+        sku: "SPR-7".into(),
+        on_hand: 3,
+        reorder_point: 1,
+    }]);
+
+    let cart = registry
+        .call_function("ecommerce_validate_cart", &context, &HashMap::new())
+        .unwrap()
+        .unwrap();
+    assert_eq!(cart["subtotal"], 25.0);
+    assert_eq!(cart["validated_items"][0]["sku"], "SPR-7");
+
+    let deps = HashMap::from([("validate_cart".to_string(), cart)]);
+    let inventory = registry
+        .call_function("ecommerce_update_inventory", &context, &deps)
+        .unwrap()
+        .unwrap();
+    assert_eq!(inventory["updated_products"][0]["product_id"], "PROD-7");
+    assert_eq!(inventory["updated_products"][0]["previous_quantity"], 3);
+    assert_eq!(inventory["updated_products"][0]["new_quantity"], 1);
+    assert_eq!(inventory["updated_products"][0]["warehouse"], "WH-03");
+
+    // The reservation came out of the registry's warehouse stock
+    let err = registry
+        .call_function("ecommerce_update_inventory", &context, &deps)
+        .unwrap()
+        .unwrap_err();
+    assert!(err.contains("No warehouse has 2 of Sprocket"), "{err}");
+
+    // Stock is checked against the loaded catalog too
+    let too_many = json!({
+        "cart_items": [{"product_id": 7, "quantity": 4}],
+        "customer_email": "catalog@example.com",
+        "payment_token": "tok_test_success"
+    });
+    let err = registry
+        .call_function("ecommerce_validate_cart", &too_many, &HashMap::new())
+        .unwrap()
+        .unwrap_err();
+    assert!(err.contains("Insufficient stock for Sprocket"), "{err}");
+}
+
+// ---------------------------------------------------------------------------
+// Handler timing
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn handler_timing_covers_the_handler_call() {
+    let (result, elapsed_ms) = timed(async {
+        tokio::time::sleep(std::time::Duration::from_millis(25)).await;
+        handlers::ecommerce::validate_cart(&json!({
+            "cart_items": [{"product_id": 1, "quantity": 1}],
+            "customer_email": "timing@example.com",
+            "payment_token": "tok_test_success"
+        }))
+    })
+    .await;
+    assert!(result.is_ok());
+    assert!(elapsed_ms >= 25, "elapsed_ms was {elapsed_ms}");
+}
+
+#[tokio::test]
+async fn slow_handler_times_out_as_retryable() {
+    let timeout = Some(std::time::Duration::from_millis(50));
+    let slow = || {
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        Ok(json!({"done": true}))
+    };
+
+    let (result, elapsed_ms) = timed(blocking_with_timeout(timeout, slow)).await;
+    let err = result.unwrap_err();
+    assert!(err.contains("Handler exceeded timeout"), "{err}");
+    assert_eq!(FailureCategory::classify(&err), FailureCategory::Transient);
+    // The wait ends at the timeout, not when the handler finishes
+    assert!(elapsed_ms < 400, "elapsed_ms was {elapsed_ms}");
+
+    // A handler within the limit, or with no limit, returns its result
+    let fast = || Ok(json!({"done": true}));
+    assert_eq!(blocking_with_timeout(timeout, fast).await.unwrap()["done"], true);
+    assert_eq!(blocking_with_timeout(None, slow).await.unwrap()["done"], true);
+
+    let registry = AxumHandlerRegistry::new();
+    assert_eq!(registry.handler_timeout(), Some(DEFAULT_HANDLER_TIMEOUT));
+}
+
+// ---------------------------------------------------------------------------
+// Retry policies
+// ---------------------------------------------------------------------------
+
+#[test]
+fn declared_retry_policy_reaches_failure_result() {
+    let registry = AxumHandlerRegistry::new();
+    let policy = registry
+        .retry_policy("ecommerce_process_payment")
+        .expect("process_payment is registered");
+    assert_eq!(policy, RetryPolicy::default());
+
+    let payment_error = |token: &str| {
+        let context = json!({
+            "cart_items": [{"product_id": 1, "quantity": 1}],
+            "customer_email": "retry@example.com",
+            "payment_token": token
+        });
+        let cart = handlers::ecommerce::validate_cart(&context).unwrap();
+        let deps = HashMap::from([("validate_cart".to_string(), cart)]);
+        handlers::ecommerce::process_payment(&context, &deps).unwrap_err()
+    };
+
+    let err = payment_error("tok_test_network_error");
+    assert_eq!(FailureCategory::classify(&err), FailureCategory::Transient);
+    let result = policy.failure_result(uuid::Uuid::new_v4(), err, 3);
+    assert!(!result.success);
+    assert!(result.metadata.retryable, "Gateway errors are transient");
+
+    let serialized = serde_json::to_value(&result).unwrap();
+    let carried = find_key(&serialized, "retry_policy").expect("retry_policy in result");
+    assert_eq!(carried["retryable"], json!(["transient"]));
+    assert_eq!(find_key(&serialized, "failure_category"), Some(&json!("transient")));
+
+    // A declined card is permanent, so the same policy does not retry it
+    let err = payment_error("tok_test_declined");
+    assert_eq!(FailureCategory::classify(&err), FailureCategory::Permanent);
+    let declined = policy.failure_result(uuid::Uuid::new_v4(), err, 3);
+    assert!(!declined.metadata.retryable);
+}
+
+#[test]
+fn retry_policies_default_and_never() {
+    let registry = AxumHandlerRegistry::new();
+
+    assert_eq!(
+        registry.retry_policy("ecommerce_update_inventory"),
+        Some(RetryPolicy::default())
+    );
+    assert_eq!(
+        registry.retry_policy("ecommerce_validate_cart"),
+        Some(RetryPolicy::never())
+    );
+    assert_eq!(registry.retry_policy("no_such_handler"), None);
+
+    let never = RetryPolicy::never();
+    assert!(!never.is_retryable(FailureCategory::Transient));
+    assert!(RetryPolicy::default().is_retryable(FailureCategory::classify(
+        "Ticket locked by another agent, will retry"
+    )));
+}
+
+#[test]
+fn templates_declare_retry_attempts_and_backoff() {
+    // Orchestration retries from the template, so that is where the steps
+    // that need more room than the rest get it
+    let step_retry = |template: &str, step: &str| {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("config/templates")
+            .join(template);
+        let yaml: Value = serde_yaml::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        yaml["steps"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|s| s["name"] == step)
+            .map(|s| s["retry"].clone())
+            .unwrap()
+    };
+
+    let payment = step_retry("ecommerce_order_processing.yaml", "process_payment");
+    assert_eq!(payment["max_attempts"], 5);
+    assert_eq!(payment["backoff_base_ms"], 2000);
+    assert_eq!(payment["max_backoff_ms"], 60000);
+
+    let approval = step_retry("customer_success_process_refund.yaml", "get_manager_approval");
+    assert_eq!(approval["max_attempts"], 10);
+    assert_eq!(approval["backoff_base_ms"], approval["max_backoff_ms"]);
+
+    let refund = step_retry("payments_process_refund.yaml", "process_gateway_refund");
+    assert_eq!(refund["max_attempts"], 5);
+}
+
+// ---------------------------------------------------------------------------
+// Payment gateway
+// ---------------------------------------------------------------------------
+
+/// A gateway that fails every charge and refund with `error`.
+struct FailingGateway {
+    error: GatewayError,
+}
+
+impl PaymentGateway for FailingGateway {
+    fn charge(&self, _request: &ChargeRequest<'_>) -> Result<ChargeReceipt, GatewayError> {
+        Err(self.error.clone())
+    }
+
+    fn refund(&self, _request: &RefundRequest<'_>) -> Result<RefundReceipt, GatewayError> {
+        Err(self.error.clone())
+    }
+
+    fn name(&self) -> &str {
+        "FailingGateway"
+    }
+}
+
+/// Run the payment and refund steps through `registry` with inputs the mock
+/// gateway approves.
+fn charge_and_refund(
+    registry: &AxumHandlerRegistry,
+) -> (Result<Value, String>, Result<Value, String>) {
+    let order = json!({
+        "cart_items": [{"product_id": 1, "quantity": 1}],
+        "customer_email": "gateway@example.com",
+        "payment_token": "tok_test_success"
+    });
+    let cart = registry
+        .call_function("ecommerce_validate_cart", &order, &HashMap::new())
+        .unwrap()
+        .unwrap();
+    let charge = registry
+        .call_function(
+            "ecommerce_process_payment",
+            &order,
+            &HashMap::from([("validate_cart".to_string(), cart)]),
+        )
+        .unwrap();
+
+    let refund = json!({
+        "payment_id": "pay_gateway_test",
+        "refund_amount": 25.0,
+        "customer_email": "gateway@example.com"
+    });
+    let eligibility = registry
+        .call_function(
+            "team_scaling_payments_validate_eligibility",
+            &refund,
+            &HashMap::new(),
+        )
+        .unwrap()
+        .unwrap();
+    let refunded = registry
+        .call_function(
+            "team_scaling_payments_process_gateway_refund",
+            &refund,
+            &HashMap::from([("validate_payment_eligibility".to_string(), eligibility)]),
+        )
+        .unwrap();
+    (charge, refunded)
+}
+
+#[test]
+fn payment_handlers_use_the_registry_gateway() {
+    let registry = AxumHandlerRegistry::new();
+    assert_eq!(registry.payment_gateway().name(), "MockPaymentGateway");
+    let (charge, refund) = charge_and_refund(&registry);
+    assert_eq!(charge.unwrap()["gateway_response"], "approved");
+    assert_eq!(refund.unwrap()["gateway_provider"], "MockPaymentGateway");
+
+    // An unreachable gateway fails both steps as retryable
+    registry.set_payment_gateway(Arc::new(FailingGateway {
+        error: GatewayError::Unavailable("Gateway connection reset".into()),
+    }));
+    let (charge, refund) = charge_and_refund(&registry);
+    for err in [charge.unwrap_err(), refund.unwrap_err()] {
+        assert!(err.starts_with("Gateway connection reset"), "{err}");
+        assert_eq!(FailureCategory::classify(&err), FailureCategory::Transient);
+    }
+
+    // A declined request is permanent
+    registry.set_payment_gateway(Arc::new(FailingGateway {
+        error: GatewayError::Declined("Card reported stolen".into()),
+    }));
+    let (charge, refund) = charge_and_refund(&registry);
+    for err in [charge.unwrap_err(), refund.unwrap_err()] {
+        assert_eq!(err, "Card reported stolen");
+        assert_eq!(FailureCategory::classify(&err), FailureCategory::Permanent);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Versioned handlers
+// ---------------------------------------------------------------------------
+
+/// A handler that only reports its name, to tell implementations apart.
+struct NamedHandler(&'static str);
+
+#[async_trait::async_trait]
+impl StepHandler for NamedHandler {
+    async fn call(&self, _step: &TaskSequenceStep) -> TaskerResult<StepExecutionResult> {
+        unreachable!("only dispatch is tested")
+    }
+
+    fn name(&self) -> &str {
+        self.0
+    }
+}
+
+#[test]
+fn versioned_handlers_dispatch_by_template_version() {
+    let registry = AxumHandlerRegistry::new();
+    registry.register_version("ecommerce_validate_cart", "1.0.0", Arc::new(NamedHandler("cart_v1")));
+    registry.register_version("ecommerce_validate_cart", "1.1.0", Arc::new(NamedHandler("cart_v1_1")));
+
+    let dispatched = |version: &str| {
+        registry
+            .handler_for("ecommerce_validate_cart", version)
+            .map(|h| h.name().to_string())
+    };
+    assert_eq!(dispatched("1.0.0").as_deref(), Some("cart_v1"));
+    assert_eq!(dispatched("1.1.0").as_deref(), Some("cart_v1_1"));
+    // Unpinned versions use the latest (unversioned) handler
+    assert_eq!(dispatched("2.0.0").as_deref(), Some("ecommerce_validate_cart"));
+
+    assert_eq!(
+        registry.handler_versions("ecommerce_validate_cart"),
+        vec!["1.0.0", "1.1.0"]
+    );
+    // Versions do not add handlers to the count
+    assert_eq!(registry.handler_count(), 29);
+}
+
+#[test]
+fn version_only_handler_falls_back_to_highest_version() {
+    let registry = AxumHandlerRegistry::new();
+    registry.register_version("pinned_only", "1.9.0", Arc::new(NamedHandler("v1_9")));
+    registry.register_version("pinned_only", "1.10.0", Arc::new(NamedHandler("v1_10")));
+
+    assert!(registry.handler_available("pinned_only"));
+    assert!(registry.registered_handlers().contains(&"pinned_only".to_string()));
+    assert_eq!(registry.handler_for("pinned_only", "1.9.0").unwrap().name(), "v1_9");
+    assert_eq!(registry.handler_for("pinned_only", "3.0.0").unwrap().name(), "v1_10");
+    assert!(registry.handler_for("no_such_handler", "1.0.0").is_none());
+}
+
+// ---------------------------------------------------------------------------
+// Handler manifest
+// ---------------------------------------------------------------------------
+
+#[test]
+fn example_manifest_matches_the_built_in_set() {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("config/handler_manifest.example.yaml");
+    let manifest = HandlerManifest::load(&path).unwrap();
+    let registry = AxumHandlerRegistry::with_manifest(None, Some(&manifest)).unwrap();
+
+    let mut expected = AxumHandlerRegistry::new().registered_handlers();
+    expected.sort();
+    assert_eq!(registry.registered_handlers(), expected);
+}
+
+#[test]
+fn manifest_disables_and_remaps_handlers() {
+    let manifest = HandlerManifest::parse(
+        r#"
+handlers:
+  team_scaling_payments_validate_eligibility: team_scaling_payments_validate_eligibility
+  team_scaling_payments_process_gateway_refund: team_scaling_payments_process_gateway_refund
+  team_scaling_payments_update_records: team_scaling_payments_update_records
+  # notify_customer disabled; cart validation served under another callable
+  legacy_validate_cart: ecommerce_validate_cart
+"#,
+    )
+    .unwrap();
+    let registry = AxumHandlerRegistry::with_manifest(None, Some(&manifest)).unwrap();
+
+    assert_eq!(registry.handler_count(), 4);
+    assert!(!registry.handler_available("team_scaling_payments_notify_customer"));
+    assert!(!registry.handler_available("ecommerce_validate_cart"));
+    assert!(registry.handler_available("legacy_validate_cart"));
+
+    // The remapped callable keeps the handler's function and options
+    let err = registry
+        .call_function("legacy_validate_cart", &json!({}), &HashMap::new())
+        .expect("remapped function handler")
+        .unwrap_err();
+    assert!(err.contains("Invalid order processing input"), "{err}");
+    assert_eq!(
+        registry.retry_policy("legacy_validate_cart").unwrap(),
+        RetryPolicy::never()
+    );
+
+    // Entries for a disabled namespace are skipped rather than rejected
+    let payments_only = AxumHandlerRegistry::with_manifest(
+        Some(HashSet::from(["payments_rs".to_string()])),
+        Some(&manifest),
+    )
+    .unwrap();
+    assert_eq!(payments_only.handler_count(), 3);
+    assert!(!payments_only.handler_available("legacy_validate_cart"));
+}
+
+#[test]
+fn manifest_rejects_unknown_handlers() {
+    let manifest = HandlerManifest::parse(
+        "handlers:\n  ecommerce_validate_cart: ecommerce_validate_basket\n",
+    )
+    .unwrap();
+    let err = AxumHandlerRegistry::with_manifest(None, Some(&manifest)).unwrap_err();
+    assert!(matches!(
+        &err,
+        HandlerManifestError::UnknownHandler { callable, handler }
+            if callable == "ecommerce_validate_cart" && handler == "ecommerce_validate_basket"
+    ));
+
+    assert!(HandlerManifest::parse("handlerz: {}").is_err());
+}
+
+// ---------------------------------------------------------------------------
+// Handler policy
+// ---------------------------------------------------------------------------
+
+#[test]
+fn denied_handler_fails_with_disabled_message() {
+    let registry = AxumHandlerRegistry::with_policy(
+        None,
+        None,
+        HandlerPolicy::allow_all().denying(["*notify_customer"]),
+    )
+    .unwrap();
+
+    assert_eq!(
+        registry.disabled_handlers(),
+        vec!["team_scaling_payments_notify_customer"]
+    );
+    // The callable still answers, so the step fails fast instead of waiting
+    assert!(registry.handler_available("team_scaling_payments_notify_customer"));
+
+    // A context the real handler would send a notification for
+    let context = json!({
+        "payment_id": "pay_policy_test",
+        "refund_amount": 10.0,
+        "customer_email": "policy@example.com"
+    });
+    let eligibility = handlers::payments::validate_payment_eligibility(&context).unwrap();
+    let gateway = handlers::payments::process_gateway_refund(&HashMap::from([(
+        "validate_payment_eligibility".to_string(),
+        eligibility.clone(),
+    )]))
+    .unwrap();
+    let deps = HashMap::from([
+        ("validate_payment_eligibility".to_string(), eligibility),
+        ("process_gateway_refund".to_string(), gateway),
+    ]);
+    assert!(handlers::payments::notify_customer(&context, &deps).is_ok());
+
+    let err = registry
+        .call_function("team_scaling_payments_notify_customer", &context, &deps)
+        .expect("disabled stub is registered")
+        .unwrap_err();
+    assert_eq!(
+        err,
+        format!("{DISABLED_MESSAGE}: team_scaling_payments_notify_customer")
+    );
+    assert_eq!(
+        registry.retry_policy("team_scaling_payments_notify_customer").unwrap(),
+        RetryPolicy::never()
+    );
+
+    // Other handlers are untouched
+    assert!(registry
+        .call_function("team_scaling_payments_validate_eligibility", &context, &HashMap::new())
+        .unwrap()
+        .is_ok());
+}
+
+#[test]
+fn allowlist_disables_everything_else_and_denylist_wins() {
+    let policy = HandlerPolicy::allow_all()
+        .allowing(["team_scaling_payments_*"])
+        .denying(["team_scaling_payments_notify_customer"]);
+    let registry = AxumHandlerRegistry::with_policy(None, None, policy).unwrap();
+
+    let disabled = registry.disabled_handlers();
+    assert_eq!(disabled.len(), 29 - 3);
+    assert!(disabled.contains(&"ecommerce_validate_cart".to_string()));
+    assert!(disabled.contains(&"team_scaling_payments_notify_customer".to_string()));
+    assert!(!disabled.contains(&"team_scaling_payments_update_records".to_string()));
+}
+
+#[test]
+fn policy_patterns_must_match_a_handler() {
+    let err = AxumHandlerRegistry::with_policy(
+        None,
+        None,
+        HandlerPolicy::allow_all().denying(["notify_customer"]),
+    )
+    .unwrap_err();
+    assert!(matches!(err, HandlerManifestError::Policy(_)), "{err}");
+
+    assert!(glob_match("*notify_customer", "team_scaling_payments_notify_customer"));
+    assert!(glob_match("ecommerce_*_cart", "ecommerce_validate_cart"));
+    assert!(glob_match("ecommerce_validate_cart", "ecommerce_validate_cart"));
+    assert!(!glob_match("ecommerce_*", "payments_ecommerce_x"));
+    assert!(!glob_match("a*bb", "ab"));
+}
+
+/// Depth-first search for `key` anywhere in a JSON document.
+fn find_key<'a>(value: &'a Value, key: &str) -> Option<&'a Value> {
+    match value {
+        Value::Object(map) => map
+            .get(key)
+            .or_else(|| map.values().find_map(|v| find_key(v, key))),
+        Value::Array(items) => items.iter().find_map(|v| find_key(v, key)),
+        _ => None,
+    }
+}
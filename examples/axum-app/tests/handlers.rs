@@ -0,0 +1,1158 @@
+//! Handler unit tests: call step handler functions directly with crafted
+//! context and dependency results.
+//!
+//! No database, worker, or orchestration services are needed.
+//!
+//! Run: cargo test --test handlers
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use serde_json::{json, Value};
+
+use example_axum_app::handler_registry::AxumHandlerRegistry;
+use example_axum_app::handlers;
+use example_axum_app::normalize::{Coercion, ResultNormalizer};
+use example_axum_app::notifier::{MessageReceipt, Notifier, NotifyError, OutboundMessage};
+use example_axum_app::retry::FailureCategory;
+use example_axum_app::warehouses::{demo_warehouse_stock, WarehouseStock};
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+fn deps(results: &[(&str, Value)]) -> HashMap<String, Value> {
+    results
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.clone()))
+        .collect()
+}
+
+/// Run the first three ecommerce steps for a small cart and return their results.
+fn ecommerce_upstream_results() -> HashMap<String, Value> {
+    let context = json!({
+        "cart_items": [
+            {"product_id": 1, "quantity": 2},
+            {"product_id": 2, "quantity": 1}
+        ],
+        "customer_email": "handler-test@example.com",
+        "payment_token": "tok_test_success"
+    });
+
+    let cart = handlers::ecommerce::validate_cart(&context).expect("validate_cart failed");
+    let with_cart = deps(&[("validate_cart", cart.clone())]);
+    let payment =
+        handlers::ecommerce::process_payment(&context, &with_cart).expect("process_payment failed");
+    let inventory =
+        handlers::ecommerce::update_inventory(&with_cart).expect("update_inventory failed");
+
+    deps(&[
+        ("validate_cart", cart),
+        ("process_payment", payment),
+        ("update_inventory", inventory),
+    ])
+}
+
+// ---------------------------------------------------------------------------
+// E-commerce: reconcile_order
+// ---------------------------------------------------------------------------
+
+#[test]
+fn reconcile_order_accepts_consistent_results() {
+    let results = ecommerce_upstream_results();
+
+    let reconciled =
+        handlers::ecommerce::reconcile_order(&results).expect("Expected reconciliation to pass");
+
+    assert_eq!(reconciled["reconciled"], true);
+    assert_eq!(reconciled["item_count"], 3);
+    assert_eq!(reconciled["total_items_reserved"], 3);
+}
+
+#[test]
+fn reconcile_order_reports_every_mismatch() {
+    let mut results = ecommerce_upstream_results();
+    results.get_mut("process_payment").unwrap()["amount_charged"] = json!(1.00);
+    results.get_mut("update_inventory").unwrap()["total_items_reserved"] = json!(7);
+
+    let err = handlers::ecommerce::reconcile_order(&results)
+        .expect_err("Expected reconciliation to fail");
+
+    assert!(err.starts_with("Order reconciliation failed"), "{err}");
+    assert!(err.contains("process_payment.amount_charged $1.00"), "{err}");
+    assert!(err.contains("update_inventory.total_items_reserved 7"), "{err}");
+    assert!(err.contains("validate_cart.item_count 3"), "{err}");
+}
+
+#[test]
+fn reconcile_order_requires_upstream_results() {
+    let mut results = ecommerce_upstream_results();
+    results.remove("update_inventory");
+
+    let err = handlers::ecommerce::reconcile_order(&results).unwrap_err();
+    assert!(err.contains("Missing update_inventory dependency"));
+}
+
+// ---------------------------------------------------------------------------
+// E-commerce: typed step results
+// ---------------------------------------------------------------------------
+
+/// Read `step`'s result as `T` and serialize it back, which must reproduce the
+/// handler's JSON exactly (no key dropped, renamed, or added).
+fn assert_round_trips<T>(results: &HashMap<String, Value>, step: &str)
+where
+    T: serde::de::DeserializeOwned + serde::Serialize,
+{
+    let typed: T = handlers::ecommerce::step_result(results, step)
+        .unwrap_or_else(|e| panic!("{step}: {e}"));
+    assert_eq!(serde_json::to_value(&typed).unwrap(), results[step], "{step}");
+}
+
+#[test]
+fn ecommerce_results_round_trip_through_their_types() {
+    use example_axum_app::types::ecommerce::*;
+
+    let context = json!({
+        "cart_items": [{"product_id": 1, "quantity": 2}, {"product_id": 3, "quantity": 1}],
+        "customer_email": "typed@example.com",
+        "payment_token": "tok_test_success",
+        "shipping_address": {
+            "street": "1 Main St", "city": "Portland", "state": "OR", "zip": "97201", "country": "US"
+        }
+    });
+    let mut results = HashMap::new();
+    let cart = handlers::ecommerce::validate_cart(&context).unwrap();
+    results.insert("validate_cart".to_string(), cart);
+    let shipping = handlers::ecommerce::calculate_shipping(&context, &results).unwrap();
+    results.insert("calculate_shipping".to_string(), shipping);
+    let payment = handlers::ecommerce::process_payment(&context, &results).unwrap();
+    results.insert("process_payment".to_string(), payment);
+    let inventory = handlers::ecommerce::update_inventory(&results).unwrap();
+    results.insert("update_inventory".to_string(), inventory);
+    let order = handlers::ecommerce::create_order(&context, &results).unwrap();
+    results.insert("create_order".to_string(), order);
+    let confirmation = handlers::ecommerce::send_confirmation(&context, &results).unwrap();
+    results.insert("send_confirmation".to_string(), confirmation);
+
+    assert_round_trips::<ValidateCartResult>(&results, "validate_cart");
+    assert_round_trips::<CalculateShippingResult>(&results, "calculate_shipping");
+    assert_round_trips::<ProcessPaymentResult>(&results, "process_payment");
+    assert_round_trips::<UpdateInventoryResult>(&results, "update_inventory");
+    assert_round_trips::<CreateOrderResult>(&results, "create_order");
+    assert_round_trips::<SendConfirmationResult>(&results, "send_confirmation");
+
+    // A result missing a required key is rejected, naming the step
+    let mut drifted = results.clone();
+    drifted
+        .get_mut("process_payment")
+        .unwrap()
+        .as_object_mut()
+        .unwrap()
+        .remove("payment_id");
+    let err = handlers::ecommerce::create_order(&context, &drifted).unwrap_err();
+    assert!(err.contains("Failed to deserialize process_payment result"), "{err}");
+    assert!(err.contains("payment_id"), "{err}");
+}
+
+// ---------------------------------------------------------------------------
+// Task context validation
+// ---------------------------------------------------------------------------
+
+#[test]
+fn cart_validation_reports_every_missing_field_at_once() {
+    let err = handlers::ecommerce::validate_cart(&json!({
+        "cart_items": [{"product_id": 1, "quantity": 1}]
+    }))
+    .unwrap_err();
+
+    assert!(err.starts_with("Invalid order processing input: "), "{err}");
+    assert!(err.contains("customer_email is required"), "{err}");
+    assert!(err.contains("payment_token is required"), "{err}");
+}
+
+#[test]
+fn cart_validation_reports_every_bad_item_at_once() {
+    let err = handlers::ecommerce::validate_cart(&json!({
+        "cart_items": [
+            {"product_id": 1, "quantity": 0},
+            {"product_id": 999, "quantity": 1}
+        ],
+        "customer_email": "cart@example.com",
+        "payment_token": "tok_test_success"
+    }))
+    .unwrap_err();
+
+    assert!(
+        err.contains("cart_items[0].quantity must be positive, got 0"),
+        "{err}"
+    );
+    assert!(
+        err.contains("cart_items[1].product_id 999 is not in the catalog"),
+        "{err}"
+    );
+}
+
+#[test]
+fn refund_validation_reports_missing_and_mistyped_fields_together() {
+    let err = handlers::customer_success::validate_refund_request(&json!({
+        "refund_amount": "49.99"
+    }))
+    .unwrap_err();
+
+    assert!(err.starts_with("Invalid process refund input: "), "{err}");
+    for field in ["ticket_id", "customer_id", "customer_email"] {
+        assert!(err.contains(&format!("{field} is required")), "{err}");
+    }
+    assert!(
+        err.contains("refund_amount must be a number, got string"),
+        "{err}"
+    );
+}
+
+#[test]
+fn refund_validation_reports_amount_and_ticket_problems_together() {
+    let err = handlers::customer_success::validate_refund_request(&json!({
+        "ticket_id": "ticket_closed_42",
+        "customer_id": "cust_standard",
+        "customer_email": "refund@example.com",
+        "refund_amount": -5.0
+    }))
+    .unwrap_err();
+
+    assert!(err.contains("refund_amount must be positive"), "{err}");
+    assert!(err.contains("ticket_id refers to a closed ticket"), "{err}");
+}
+
+// ---------------------------------------------------------------------------
+// E-commerce: calculate_shipping
+// ---------------------------------------------------------------------------
+
+fn quote_shipping(address: Value) -> Value {
+    let context = json!({
+        "cart_items": [{"product_id": 1, "quantity": 1}],
+        "customer_email": "shipping@example.com",
+        "payment_token": "tok_test_success",
+        "shipping_address": address
+    });
+    let cart = handlers::ecommerce::validate_cart(&context).unwrap();
+    handlers::ecommerce::calculate_shipping(&context, &deps(&[("validate_cart", cart)])).unwrap()
+}
+
+#[test]
+fn calculate_shipping_prices_domestic_and_international_differently() {
+    let domestic = quote_shipping(json!({"state": "OR", "country": "US"}));
+    let international = quote_shipping(json!({"city": "Berlin", "country": "DE"}));
+
+    assert_eq!(domestic["zone"], "us_contiguous");
+    assert_eq!(domestic["international_surcharge"], 0.0);
+    assert_eq!(domestic["shipping"], 5.99);
+
+    assert_eq!(international["zone"], "international");
+    assert_eq!(international["international_surcharge"], 10.0);
+    assert_eq!(international["shipping"], 34.99);
+
+    let difference = international["total"].as_f64().unwrap() - domestic["total"].as_f64().unwrap();
+    assert!((difference - 29.0).abs() < 0.005, "{difference}");
+
+    let hawaii = quote_shipping(json!({"state": "hi", "country": "usa"}));
+    assert_eq!(hawaii["zone"], "us_noncontiguous");
+    assert_eq!(hawaii["destination_state"], "HI");
+}
+
+#[test]
+fn payment_and_order_use_calculated_shipping_total() {
+    let context = json!({
+        "cart_items": [{"product_id": 1, "quantity": 1}],
+        "customer_email": "shipping@example.com",
+        "payment_token": "tok_test_success",
+        "shipping_address": {"country": "CA"}
+    });
+    let cart = handlers::ecommerce::validate_cart(&context).unwrap();
+    let with_cart = deps(&[("validate_cart", cart.clone())]);
+    let shipping = handlers::ecommerce::calculate_shipping(&context, &with_cart).unwrap();
+    assert_ne!(shipping["total"], cart["total"]);
+
+    let mut results = deps(&[("validate_cart", cart), ("calculate_shipping", shipping.clone())]);
+    let payment = handlers::ecommerce::process_payment(&context, &results).unwrap();
+    assert_eq!(payment["amount_charged"], shipping["total"]);
+    let inventory = handlers::ecommerce::update_inventory(&results).unwrap();
+    results.insert("process_payment".into(), payment);
+    results.insert("update_inventory".into(), inventory);
+
+    let order = handlers::ecommerce::create_order(&context, &results).unwrap();
+    assert_eq!(order["shipping"], shipping["shipping"]);
+    assert_eq!(order["total_amount"], shipping["total"]);
+    assert!(handlers::ecommerce::reconcile_order(&results).is_ok());
+}
+
+#[test]
+fn pricing_config_sets_tax_and_free_shipping() {
+    use handlers::ecommerce::PricingConfig;
+
+    let context = json!({
+        "cart_items": [{"product_id": 1, "quantity": 1}],
+        "customer_email": "pricing@example.com",
+        "payment_token": "tok_test_success",
+        "shipping_address": {"state": "OR", "country": "US"}
+    });
+    let catalog = handlers::ecommerce::demo_catalog();
+    let coupons = handlers::ecommerce::demo_coupons();
+
+    // 0% tax: the total is the subtotal plus shipping
+    let no_tax = PricingConfig {
+        tax_rate: 0.0,
+        ..PricingConfig::default()
+    };
+    let cart =
+        handlers::ecommerce::validate_cart_against(&context, &catalog, &no_tax, &coupons).unwrap();
+    assert_eq!(cart["tax"], 0.0);
+    assert_eq!(cart["shipping"], 5.99);
+    assert_eq!(cart["total"], 35.98);
+
+    // $0 threshold: every cart ships free, quoted and validated alike
+    let free = PricingConfig {
+        free_shipping_threshold: 0.0,
+        ..PricingConfig::default()
+    };
+    let cart =
+        handlers::ecommerce::validate_cart_against(&context, &catalog, &free, &coupons).unwrap();
+    assert_eq!(cart["tax"], 2.4);
+    assert_eq!(cart["shipping"], 0.0);
+    assert_eq!(cart["total"], 32.39);
+    let quote = handlers::ecommerce::calculate_shipping_with(
+        &context,
+        &deps(&[("validate_cart", cart.clone())]),
+        &free,
+    )
+    .unwrap();
+    assert_eq!(quote["shipping"], 0.0);
+    assert_eq!(quote["free_shipping_applied"], true);
+    assert_eq!(quote["total"], cart["total"]);
+
+    // The registry's handlers price with the configuration it was given
+    let registry = AxumHandlerRegistry::new();
+    assert_eq!(registry.pricing(), PricingConfig::default());
+    registry.set_pricing(no_tax);
+    let cart = registry
+        .call_function("ecommerce_validate_cart", &context, &HashMap::new())
+        .unwrap()
+        .unwrap();
+    assert_eq!(cart["tax"], 0.0);
+    assert_eq!(cart["total"], 35.98);
+}
+
+// ---------------------------------------------------------------------------
+// E-commerce: coupons
+// ---------------------------------------------------------------------------
+
+fn cart_with_coupon(quantity: i64, coupon_code: &str) -> Value {
+    json!({
+        "cart_items": [{"product_id": 1, "quantity": quantity}],
+        "customer_email": "coupon@example.com",
+        "payment_token": "tok_test_success",
+        "coupon_code": coupon_code
+    })
+}
+
+#[test]
+fn percentage_coupon_discounts_the_subtotal_before_tax() {
+    // 4 x $29.99 = $119.96, 10% off = $12.00
+    let cart = handlers::ecommerce::validate_cart(&cart_with_coupon(4, "save10")).unwrap();
+    assert_eq!(cart["coupon_code"], "SAVE10");
+    assert_eq!(cart["subtotal"], 119.96);
+    assert_eq!(cart["discount"], 12.0);
+    // 8% tax on $107.96, and still over the free shipping threshold
+    assert_eq!(cart["tax"], 8.64);
+    assert_eq!(cart["shipping"], 0.0);
+    assert_eq!(cart["total"], 116.6);
+}
+
+#[test]
+fn fixed_coupon_discounts_the_subtotal_and_the_shipping_quote() {
+    let context = cart_with_coupon(1, "FIVEOFF");
+    let cart = handlers::ecommerce::validate_cart(&context).unwrap();
+    assert_eq!(cart["subtotal"], 29.99);
+    assert_eq!(cart["discount"], 5.0);
+    // 8% tax on $24.99, plus flat shipping
+    assert_eq!(cart["tax"], 2.0);
+    assert_eq!(cart["shipping"], 5.99);
+    assert_eq!(cart["total"], 32.98);
+
+    let quote = handlers::ecommerce::calculate_shipping(
+        &context,
+        &deps(&[("validate_cart", cart.clone())]),
+    )
+    .unwrap();
+    assert_eq!(quote["total"], cart["total"]);
+
+    // Carts without a coupon report no discount
+    let plain = handlers::ecommerce::validate_cart(&json!({
+        "cart_items": [{"product_id": 1, "quantity": 1}],
+        "customer_email": "coupon@example.com",
+        "payment_token": "tok_test_success"
+    }))
+    .unwrap();
+    assert!(plain.get("discount").is_none());
+    assert_eq!(plain["total"], 38.38);
+}
+
+#[test]
+fn unknown_and_expired_coupons_fail_cart_validation() {
+    let err = handlers::ecommerce::validate_cart(&cart_with_coupon(1, "BOGUS")).unwrap_err();
+    assert_eq!(err, "Unknown coupon code 'BOGUS'");
+
+    let err = handlers::ecommerce::validate_cart(&cart_with_coupon(1, "SPRING2020")).unwrap_err();
+    assert_eq!(err, "Coupon code 'SPRING2020' expired on 2020-05-31");
+    assert_eq!(FailureCategory::classify(&err), FailureCategory::Permanent);
+}
+
+// ---------------------------------------------------------------------------
+// E-commerce: currencies
+// ---------------------------------------------------------------------------
+
+fn cart_in(currency: &str, shipping_address: Value) -> Value {
+    json!({
+        "cart_items": [{"product_id": 1, "quantity": 3}],
+        "customer_email": "currency@example.com",
+        "payment_token": "tok_test_success",
+        "currency": currency,
+        "shipping_address": shipping_address
+    })
+}
+
+#[test]
+fn usd_carts_round_to_the_cent_and_jpy_carts_to_the_yen() {
+    let address = json!({"state": "OR", "country": "US"});
+
+    // 3 x 29.99 = 89.97, 8% tax = 7.1976, flat shipping 5.99
+    let usd = handlers::ecommerce::validate_cart(&cart_in("usd", address.clone())).unwrap();
+    assert_eq!(usd["currency"], "USD");
+    assert_eq!(usd["subtotal"], 89.97);
+    assert_eq!(usd["tax"], 7.2);
+    assert_eq!(usd["shipping"], 5.99);
+    assert_eq!(usd["total"], 103.16);
+
+    // The same amounts in yen have no minor unit
+    let jpy = handlers::ecommerce::validate_cart(&cart_in("JPY", address)).unwrap();
+    assert_eq!(jpy["currency"], "JPY");
+    assert_eq!(jpy["validated_items"][0]["line_total"], 90.0);
+    assert_eq!(jpy["subtotal"], 90.0);
+    assert_eq!(jpy["tax"], 7.0);
+    assert_eq!(jpy["shipping"], 6.0);
+    assert_eq!(jpy["total"], 103.0);
+}
+
+#[test]
+fn jpy_order_is_charged_and_recorded_in_yen() {
+    let context = cart_in("JPY", json!({"country": "CA"}));
+    let cart = handlers::ecommerce::validate_cart(&context).unwrap();
+    let shipping = handlers::ecommerce::calculate_shipping(
+        &context,
+        &deps(&[("validate_cart", cart.clone())]),
+    )
+    .unwrap();
+    // 12.99 + 5.00 surcharge
+    assert_eq!(shipping["shipping"], 18.0);
+    assert_eq!(shipping["total"], 115.0);
+
+    let mut results = deps(&[("validate_cart", cart), ("calculate_shipping", shipping)]);
+    let payment = handlers::ecommerce::process_payment(&context, &results).unwrap();
+    assert_eq!(payment["currency"], "JPY");
+    assert_eq!(payment["amount_charged"], 115.0);
+    let inventory = handlers::ecommerce::update_inventory(&results).unwrap();
+    results.insert("process_payment".into(), payment);
+    results.insert("update_inventory".into(), inventory);
+
+    let order = handlers::ecommerce::create_order(&context, &results).unwrap();
+    assert_eq!(order["currency"], "JPY");
+    assert_eq!(order["total"], 115.0);
+    assert!(handlers::ecommerce::reconcile_order(&results).is_ok());
+
+    // Carts without a currency are priced in USD
+    let mut context = context;
+    context.as_object_mut().unwrap().remove("currency");
+    let cart = handlers::ecommerce::validate_cart(&context).unwrap();
+    assert_eq!(cart["currency"], "USD");
+    assert_eq!(cart["total"], 103.16);
+}
+
+#[test]
+fn unsupported_currency_fails_cart_validation() {
+    let err = handlers::ecommerce::validate_cart(&cart_in("XYZ", Value::Null)).unwrap_err();
+    assert!(err.contains("Unsupported currency 'XYZ'"), "{err}");
+    assert_eq!(FailureCategory::classify(&err), FailureCategory::Permanent);
+}
+
+// ---------------------------------------------------------------------------
+// E-commerce: warehouse reservations
+// ---------------------------------------------------------------------------
+
+/// Widget A (product 1) stock split 30/45 between two warehouses.
+fn widget_a_stock() -> Vec<WarehouseStock> {
+    let stock = |warehouse: &str, on_hand| WarehouseStock {
+        warehouse: warehouse.to_string(),
+        product_id: 1,
+        sku: "WGT-A-001".to_string(),
+        on_hand,
+        reorder_point: 10,
+    };
+    vec![stock("WH-01", 30), stock("WH-02", 45)]
+}
+
+/// Reserve `quantity` of Widget A from `warehouses`.
+fn reserve_widget_a_from(
+    warehouses: &mut Vec<WarehouseStock>,
+    quantity: i64,
+) -> Result<Value, String> {
+    let context = json!({
+        "cart_items": [{"product_id": 1, "quantity": quantity}],
+        "customer_email": "warehouse@example.com",
+        "payment_token": "tok_test_success"
+    });
+    let cart = handlers::ecommerce::validate_cart(&context).expect("validate_cart failed");
+    handlers::ecommerce::update_inventory_against(&deps(&[("validate_cart", cart)]), warehouses)
+}
+
+/// Reserve `quantity` of Widget A from a fresh [`widget_a_stock`].
+fn reserve_widget_a(quantity: i64) -> Result<Value, String> {
+    reserve_widget_a_from(&mut widget_a_stock(), quantity)
+}
+
+#[test]
+fn inventory_reserves_from_the_warehouse_with_the_most_on_hand() {
+    let inventory = reserve_widget_a(10).unwrap();
+    assert_eq!(inventory["updated_products"][0]["warehouse"], "WH-02");
+    assert_eq!(inventory["updated_products"][0]["reserved"], 10);
+
+    // Only WH-02 can cover 45 units
+    let inventory = reserve_widget_a(45).unwrap();
+    assert_eq!(inventory["updated_products"][0]["warehouse"], "WH-02");
+}
+
+#[test]
+fn inventory_reports_and_decrements_the_chosen_warehouse() {
+    let mut warehouses = widget_a_stock();
+    let inventory = reserve_widget_a_from(&mut warehouses, 20).unwrap();
+    let reserved = &inventory["updated_products"][0];
+    assert_eq!(reserved["product_id"], "PROD-1");
+    assert_eq!(reserved["previous_quantity"], 45);
+    assert_eq!(reserved["new_quantity"], 25);
+    assert_eq!(warehouses[1].on_hand, 25);
+
+    // WH-01 now has the most on hand
+    let inventory = reserve_widget_a_from(&mut warehouses, 5).unwrap();
+    assert_eq!(inventory["updated_products"][0]["warehouse"], "WH-01");
+    assert_eq!(inventory["updated_products"][0]["previous_quantity"], 30);
+    assert_eq!(warehouses[0].on_hand, 25);
+
+    // A failed reservation leaves the stock as it was
+    assert!(reserve_widget_a_from(&mut warehouses, 26).is_err());
+    assert_eq!(warehouses[0].on_hand, 25);
+    assert_eq!(warehouses[1].on_hand, 25);
+}
+
+#[test]
+fn inventory_fails_when_no_single_warehouse_covers_the_quantity() {
+    // 75 units are on hand in total, but not in any one warehouse
+    let err = reserve_widget_a(50).unwrap_err();
+    assert_eq!(err, "No warehouse has 50 of Widget A in stock");
+    assert_eq!(FailureCategory::classify(&err), FailureCategory::Permanent);
+}
+
+#[test]
+fn demo_warehouse_stock_matches_the_demo_catalog() {
+    let catalog = handlers::ecommerce::demo_catalog();
+    for product in catalog.values() {
+        let on_hand: i64 = demo_warehouse_stock()
+            .iter()
+            .filter(|s| s.product_id == product.id && s.sku == product.sku)
+            .map(|s| s.on_hand)
+            .sum();
+        assert_eq!(on_hand, product.stock, "{}", product.sku);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Data pipeline: partial aggregation
+// ---------------------------------------------------------------------------
+
+/// Run the extract and transform steps for the sales and customer branches only
+/// (as if the inventory branch had failed).
+fn pipeline_results_without_inventory() -> HashMap<String, Value> {
+    use handlers::data_pipeline as dp;
+
+    let context = json!({});
+    let sales = dp::extract_sales(&context).unwrap();
+    let customers = dp::extract_customers(&context).unwrap();
+    deps(&[
+        (
+            "transform_sales",
+            dp::transform_sales(&context, &deps(&[("extract_sales_data", sales)])).unwrap(),
+        ),
+        (
+            "transform_customers",
+            dp::transform_customers(&context, &deps(&[("extract_customer_data", customers)]))
+                .unwrap(),
+        ),
+    ])
+}
+
+#[test]
+fn aggregate_metrics_is_strict_by_default() {
+    let results = pipeline_results_without_inventory();
+
+    let err = handlers::data_pipeline::aggregate_metrics(&json!({}), &results).unwrap_err();
+    assert!(err.contains("transform_inventory"), "{err}");
+}
+
+#[test]
+fn aggregate_metrics_tolerates_missing_sources_when_partial() {
+    let results = pipeline_results_without_inventory();
+    let context = json!({"allow_partial": true});
+
+    let aggregate = handlers::data_pipeline::aggregate_metrics(&context, &results).unwrap();
+    assert_eq!(aggregate["partial"], true);
+    assert_eq!(aggregate["aggregation_complete"], false);
+    assert_eq!(aggregate["sources_included"], 2);
+    assert_eq!(aggregate["data_sources"], json!(["sales", "customers"]));
+    assert!(aggregate["total_revenue"].as_f64().unwrap() > 0.0);
+    assert!(aggregate["revenue_per_customer"].is_number());
+    // Inventory-derived metrics are absent rather than zero
+    assert!(aggregate.get("total_inventory_quantity").is_none());
+    assert!(aggregate.get("inventory_turnover_indicator").is_none());
+
+    // generate_insights still accepts the partial aggregate
+    let insights =
+        handlers::data_pipeline::generate_insights(&deps(&[("aggregate_metrics", aggregate)]));
+    assert!(insights.is_ok(), "{insights:?}");
+
+    // With every source missing there is nothing to aggregate
+    let err = handlers::data_pipeline::aggregate_metrics(&context, &HashMap::new()).unwrap_err();
+    assert!(err.contains("No transform results"), "{err}");
+}
+
+#[test]
+fn failed_source_is_reported_unavailable_when_partial() {
+    use handlers::data_pipeline as dp;
+
+    // The sales extract fails on the bad date; the other branches succeed
+    let context = json!({
+        "allow_partial": true,
+        "date_range": {"start_date": "not-a-date"}
+    });
+    let sales = dp::extract_sales(&context).unwrap();
+    assert_eq!(sales["unavailable"], true);
+    let error = sales["error"].as_str().unwrap();
+    assert!(error.contains("date_range.start_date"), "{error}");
+
+    let transformed_sales =
+        dp::transform_sales(&context, &deps(&[("extract_sales_data", sales)])).unwrap();
+    assert_eq!(transformed_sales["unavailable"], true);
+    let inventory = dp::extract_inventory(&context).unwrap();
+    let customers = dp::extract_customers(&context).unwrap();
+    let results = deps(&[
+        ("transform_sales", transformed_sales),
+        (
+            "transform_inventory",
+            dp::transform_inventory(&context, &deps(&[("extract_inventory_data", inventory)]))
+                .unwrap(),
+        ),
+        (
+            "transform_customers",
+            dp::transform_customers(&context, &deps(&[("extract_customer_data", customers)]))
+                .unwrap(),
+        ),
+    ]);
+
+    let aggregate = dp::aggregate_metrics(&context, &results).unwrap();
+    assert_eq!(aggregate["partial"], true);
+    assert_eq!(aggregate["data_sources"], json!(["inventory", "customers"]));
+    assert!(aggregate.get("total_revenue").is_none());
+
+    // Without partial mode the failure stops the pipeline
+    let strict = json!({"date_range": {"start_date": "not-a-date"}});
+    assert!(dp::extract_sales(&strict).is_err());
+    let err = dp::aggregate_metrics(&strict, &results).unwrap_err();
+    assert!(err.contains("transform_sales is unavailable"), "{err}");
+}
+
+// ---------------------------------------------------------------------------
+// Data pipeline: date range
+// ---------------------------------------------------------------------------
+
+fn extract_sales_in(date_range: Value) -> Value {
+    handlers::data_pipeline::extract_sales(&json!({"date_range": date_range})).unwrap()
+}
+
+#[test]
+fn extract_sales_keeps_records_in_the_date_range() {
+    let window = extract_sales_in(json!({"start_date": "2025-11-05", "end_date": "2025-11-15"}));
+    assert_eq!(window["record_count"], 3);
+    let dates: Vec<&str> = window["records"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["date"].as_str().unwrap())
+        .collect();
+    assert_eq!(dates, ["2025-11-05", "2025-11-10", "2025-11-15"]);
+    let total = window["total_amount"].as_f64().unwrap();
+    assert!((total - 1999.85).abs() < 0.005, "{total}");
+    assert_eq!(window["date_range"]["start_date"], "2025-11-05");
+    assert_eq!(window["date_range"]["end_date"], "2025-11-15");
+
+    // The transform aggregates only the extracted window
+    let transformed = handlers::data_pipeline::transform_sales(
+        &json!({}),
+        &deps(&[("extract_sales_data", window)]),
+    )
+    .unwrap();
+    assert_eq!(transformed["record_count"], 3);
+}
+
+#[test]
+fn extract_sales_outside_or_inverted_range_is_empty() {
+    for range in [
+        json!({"start_date": "2024-01-01", "end_date": "2024-12-31"}),
+        json!({"start_date": "2025-11-25", "end_date": "2025-11-01"}),
+    ] {
+        let extract = extract_sales_in(range.clone());
+        assert_eq!(extract["record_count"], 0, "{range}");
+        assert_eq!(extract["total_amount"], 0.0, "{range}");
+    }
+
+    let err = handlers::data_pipeline::extract_sales(&json!({
+        "date_range": {"start_date": "11/01/2025", "end_date": "2025-11-30"}
+    }))
+    .unwrap_err();
+    assert!(err.contains("date_range.start_date"), "{err}");
+}
+
+#[test]
+fn extract_sales_without_a_date_range_returns_everything() {
+    let all = handlers::data_pipeline::extract_sales(&json!({})).unwrap();
+    assert_eq!(all["record_count"], 7);
+    let total = all["total_amount"].as_f64().unwrap();
+    assert!((total - 3849.68).abs() < 0.005, "{total}");
+
+    // A single bound leaves the other end open
+    let from = extract_sales_in(json!({"start_date": "2025-11-20"}));
+    assert_eq!(from["record_count"], 2);
+}
+
+// ---------------------------------------------------------------------------
+// Microservices: registration without a database
+// ---------------------------------------------------------------------------
+
+#[test]
+fn registration_without_a_database_keeps_no_state() {
+    let local = format!("stateless-{}", uuid::Uuid::new_v4().simple());
+    let context = json!({"email": format!("  {local}@Example.com "), "full_name": "No State"});
+
+    // Duplicates are only detected against the users table
+    let first = handlers::microservices::create_user_account(&context).unwrap();
+    let second = handlers::microservices::create_user_account(&context).unwrap();
+    assert_eq!(first["email"], format!("{local}@example.com"));
+    assert_eq!(second["status"], "created");
+    assert_ne!(second["user_id"], first["user_id"]);
+
+    let with_user = deps(&[("create_user_account", first)]);
+    let billing = handlers::microservices::setup_billing_profile(&context, &with_user).unwrap();
+    let again = handlers::microservices::setup_billing_profile(&context, &with_user).unwrap();
+    assert_ne!(again["billing_id"], billing["billing_id"]);
+}
+
+// ---------------------------------------------------------------------------
+// Payments
+// ---------------------------------------------------------------------------
+
+fn refund_context(refund_amount: f64, currency: Option<&str>) -> Value {
+    let mut context = json!({
+        "payment_id": "pay_precision_test",
+        "refund_amount": refund_amount,
+        "customer_email": "refund@example.com"
+    });
+    if let Some(currency) = currency {
+        context["currency"] = json!(currency);
+    }
+    context
+}
+
+#[test]
+fn payment_eligibility_rejects_sub_cent_amounts() {
+    let err = handlers::payments::validate_payment_eligibility(&refund_context(10.005, None))
+        .unwrap_err();
+    assert!(err.contains("more than 2 decimal places"), "{err}");
+
+    let result =
+        handlers::payments::validate_payment_eligibility(&refund_context(10.00, None)).unwrap();
+    assert_eq!(result["refund_amount"], 10.0);
+    assert!(handlers::payments::validate_payment_eligibility(&refund_context(19.99, None)).is_ok());
+}
+
+#[test]
+fn payment_eligibility_checks_the_original_amount() {
+    let mut context = refund_context(20.00, None);
+    context["original_amount"] = json!(50.00);
+    let result = handlers::payments::validate_payment_eligibility(&context).unwrap();
+    assert_eq!(result["original_amount"], 50.0);
+    assert_eq!(result["refund_percentage"], 40.0);
+
+    context["refund_amount"] = json!(50.01);
+    let err = handlers::payments::validate_payment_eligibility(&context).unwrap_err();
+    assert_eq!(err, "Refund $50.01 exceeds original transaction amount $50.00");
+
+    // Without an original amount the refund is a full refund
+    let result =
+        handlers::payments::validate_payment_eligibility(&refund_context(20.00, None)).unwrap();
+    assert_eq!(result["original_amount"], 20.0);
+    assert_eq!(result["refund_percentage"], 100.0);
+}
+
+#[test]
+fn payment_eligibility_uses_the_currency_minor_unit() {
+    assert!(
+        handlers::payments::validate_payment_eligibility(&refund_context(10.5, Some("JPY")))
+            .is_err()
+    );
+    assert!(
+        handlers::payments::validate_payment_eligibility(&refund_context(1500.0, Some("JPY")))
+            .is_ok()
+    );
+    assert!(
+        handlers::payments::validate_payment_eligibility(&refund_context(10.005, Some("KWD")))
+            .is_ok()
+    );
+}
+
+#[test]
+fn refund_without_customer_email_fails_validation() {
+    let mut context = refund_context(10.00, None);
+    context.as_object_mut().unwrap().remove("customer_email");
+    let err = handlers::payments::validate_payment_eligibility(&context).unwrap_err();
+    assert!(err.contains("customer_email"), "{err}");
+
+    context["customer_email"] = json!("  ");
+    let err = handlers::payments::validate_payment_eligibility(&context).unwrap_err();
+    assert!(err.contains("customer_email"), "{err}");
+
+    // The notification step reads the context too, and never falls back to a
+    // placeholder address
+    let eligibility =
+        handlers::payments::validate_payment_eligibility(&refund_context(10.00, None)).unwrap();
+    let gateway = handlers::payments::process_gateway_refund(&HashMap::from([(
+        "validate_payment_eligibility".to_string(),
+        eligibility.clone(),
+    )]))
+    .unwrap();
+    let deps = HashMap::from([
+        ("validate_payment_eligibility".to_string(), eligibility),
+        ("process_gateway_refund".to_string(), gateway),
+    ]);
+    let err = handlers::payments::notify_customer(&context, &deps).unwrap_err();
+    assert!(err.contains("customer_email"), "{err}");
+
+    let sent = handlers::payments::notify_customer(&refund_context(10.00, None), &deps).unwrap();
+    assert_eq!(sent["recipient"], "refund@example.com");
+}
+
+// ---------------------------------------------------------------------------
+// Notifications
+// ---------------------------------------------------------------------------
+
+/// Records every message and fails the recipients it was scripted to fail.
+#[derive(Default)]
+struct ScriptedNotifier {
+    failures: HashMap<String, NotifyError>,
+    sent: Mutex<Vec<OutboundMessage>>,
+}
+
+#[async_trait::async_trait]
+impl Notifier for ScriptedNotifier {
+    async fn send(&self, msg: OutboundMessage) -> Result<MessageReceipt, NotifyError> {
+        if let Some(error) = self.failures.get(&msg.recipient) {
+            return Err(error.clone());
+        }
+        let message_id = format!("scripted_{}", self.sent.lock().unwrap().len());
+        self.sent.lock().unwrap().push(msg);
+        Ok(MessageReceipt {
+            message_id,
+            status: "sent".to_string(),
+        })
+    }
+
+    fn name(&self) -> &str {
+        "scripted"
+    }
+}
+
+/// The results `notify_customer` depends on, for a refund the mock gateway approves.
+fn refund_notification_deps() -> HashMap<String, Value> {
+    let eligibility =
+        handlers::payments::validate_payment_eligibility(&refund_context(10.00, None)).unwrap();
+    let gateway = handlers::payments::process_gateway_refund(&deps(&[(
+        "validate_payment_eligibility",
+        eligibility.clone(),
+    )]))
+    .unwrap();
+    deps(&[
+        ("validate_payment_eligibility", eligibility),
+        ("process_gateway_refund", gateway),
+    ])
+}
+
+#[test]
+fn bounced_address_fails_permanently_and_rate_limit_is_retryable() {
+    let deps = refund_notification_deps();
+    let notify = |email: &str| {
+        let mut context = refund_context(10.00, None);
+        context["customer_email"] = json!(email);
+        handlers::payments::notify_customer(&context, &deps)
+    };
+
+    let bounced = notify("refund@test_bounce.example").unwrap_err();
+    assert_eq!(bounced, "Customer email bounced");
+    assert_eq!(FailureCategory::classify(&bounced), FailureCategory::Permanent);
+
+    let throttled = notify("refund@test_rate_limit.example").unwrap_err();
+    assert_eq!(FailureCategory::classify(&throttled), FailureCategory::Transient);
+
+    assert!(notify("refund@example.com").is_ok());
+}
+
+#[test]
+fn message_handlers_send_through_the_registry_notifier() {
+    let notifier = Arc::new(ScriptedNotifier {
+        failures: HashMap::from([
+            (
+                "bounce@example.com".to_string(),
+                NotifyError::Rejected("Mailbox does not exist".into()),
+            ),
+            (
+                "busy@example.com".to_string(),
+                NotifyError::Unavailable("Too many requests".into()),
+            ),
+        ]),
+        ..Default::default()
+    });
+    let registry = AxumHandlerRegistry::new();
+    registry.set_notifier(notifier.clone());
+
+    // Order confirmation
+    let mut order_results = ecommerce_upstream_results();
+    let order_context = json!({"customer_email": "buyer@example.com"});
+    let order = handlers::ecommerce::create_order(&order_context, &order_results).unwrap();
+    order_results.insert("create_order".into(), order);
+    let confirmation = registry
+        .call_function("ecommerce_send_confirmation", &order_context, &order_results)
+        .unwrap()
+        .unwrap();
+    assert_eq!(confirmation["message_id"], "scripted_0");
+
+    // Welcome sequence: email, in-app, and (enterprise) SMS
+    let user_context = json!({
+        "email": "welcome-notifier@example.com",
+        "full_name": "Notifier Test",
+        "plan": "enterprise"
+    });
+    let user = handlers::microservices::create_user_account(&user_context).unwrap();
+    let with_user = deps(&[("create_user_account", user.clone())]);
+    let user_results = deps(&[
+        (
+            "setup_billing_profile",
+            handlers::microservices::setup_billing_profile(&user_context, &with_user).unwrap(),
+        ),
+        (
+            "initialize_preferences",
+            handlers::microservices::initialize_preferences(&user_context, &with_user).unwrap(),
+        ),
+        ("create_user_account", user),
+    ]);
+    registry
+        .call_function("microservices_send_welcome_sequence", &user_context, &user_results)
+        .unwrap()
+        .unwrap();
+
+    // Refund notification
+    let refund_results = refund_notification_deps();
+    registry
+        .call_function(
+            "team_scaling_payments_notify_customer",
+            &refund_context(10.00, None),
+            &refund_results,
+        )
+        .unwrap()
+        .unwrap();
+
+    let templates: Vec<String> = notifier
+        .sent
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|m| format!("{}:{}", m.channel, m.template))
+        .collect();
+    assert_eq!(
+        templates,
+        [
+            "email:order_confirmation_v2",
+            "email:welcome_email",
+            "in_app:welcome_notification",
+            "sms:enterprise_welcome_sms",
+            "email:refund_notification_v2",
+        ]
+    );
+
+    // Delivery failures keep their retry category
+    let confirm = |email: &str| {
+        registry
+            .call_function(
+                "ecommerce_send_confirmation",
+                &json!({"customer_email": email}),
+                &order_results,
+            )
+            .unwrap()
+            .unwrap_err()
+    };
+    let bounced = confirm("bounce@example.com");
+    assert_eq!(bounced, "Mailbox does not exist");
+    assert_eq!(FailureCategory::classify(&bounced), FailureCategory::Permanent);
+    let busy = confirm("busy@example.com");
+    assert_eq!(FailureCategory::classify(&busy), FailureCategory::Transient);
+}
+
+// ---------------------------------------------------------------------------
+// Customer success: refund limits
+// ---------------------------------------------------------------------------
+
+fn cs_refund_context(customer_id: &str, refund_amount: f64) -> Value {
+    json!({
+        "ticket_id": "ticket_limits",
+        "customer_id": customer_id,
+        "customer_email": "limits@example.com",
+        "refund_amount": refund_amount
+    })
+}
+
+fn check_cs_refund_policy(customer_id: &str, refund_amount: f64) -> Result<Value, String> {
+    let context = cs_refund_context(customer_id, refund_amount);
+    let validation = handlers::customer_success::validate_refund_request(&context)?;
+    handlers::customer_success::check_refund_policy(
+        &context,
+        &deps(&[("validate_refund_request", validation)]),
+    )
+}
+
+#[test]
+fn refund_policy_limits_are_in_dollars_and_inclusive() {
+    for (customer_id, tier, limit) in [
+        ("cust_standard", "standard", 100.0),
+        ("cust_gold", "gold", 500.0),
+        ("cust_vip", "premium", 1000.0),
+    ] {
+        let at_limit = check_cs_refund_policy(customer_id, limit).unwrap();
+        assert_eq!(at_limit["customer_tier"], tier);
+        assert_eq!(at_limit["max_allowed_amount"], limit);
+
+        let err = check_cs_refund_policy(customer_id, limit + 0.01).unwrap_err();
+        assert!(err.contains("exceeds policy limit"), "{tier}: {err}");
+        assert!(err.contains(&format!("(max: ${limit:.2})")), "{tier}: {err}");
+    }
+
+    // A $60 standard refund is under the $100 limit, routed to a director
+    let standard = check_cs_refund_policy("cust_standard", 60.0).unwrap();
+    assert_eq!(standard["approval_path"], "director");
+}
+
+#[test]
+fn refund_validation_caps_a_single_refund_in_dollars() {
+    let max = handlers::customer_success::MAX_SINGLE_REFUND;
+    assert_eq!(max, 10_000.0);
+    let validation =
+        handlers::customer_success::validate_refund_request(&cs_refund_context("cust_vip", max))
+            .unwrap();
+    assert_eq!(validation["amount"], max);
+
+    let err = handlers::customer_success::validate_refund_request(&cs_refund_context(
+        "cust_vip",
+        max + 0.01,
+    ))
+    .unwrap_err();
+    assert!(err.contains("limit of $10000.00"), "{err}");
+    assert!(check_cs_refund_policy("cust_vip", 0.0).is_err());
+}
+
+// ---------------------------------------------------------------------------
+// Result normalization
+// ---------------------------------------------------------------------------
+
+#[test]
+fn send_welcome_sequence_result_is_normalized() {
+    let context = json!({
+        "email": "normalize@example.com",
+        "full_name": "Normalize Test",
+        "plan": "enterprise"
+    });
+    let user = handlers::microservices::create_user_account(&context).unwrap();
+    let with_user = deps(&[("create_user_account", user.clone())]);
+    let billing = handlers::microservices::setup_billing_profile(&context, &with_user).unwrap();
+    let preferences = handlers::microservices::initialize_preferences(&context, &with_user).unwrap();
+    let upstream = deps(&[
+        ("create_user_account", user),
+        ("setup_billing_profile", billing),
+        ("initialize_preferences", preferences),
+    ]);
+
+    let raw = handlers::microservices::send_welcome_sequence(&context, &upstream).unwrap();
+    assert!(raw.get("total_messages").is_some(), "Handler emits the redundant count");
+
+    let registry = AxumHandlerRegistry::new();
+    let normalizer = registry
+        .result_normalizer("microservices_send_welcome_sequence")
+        .expect("Expected a normalizer for send_welcome_sequence");
+    let normalized = normalizer.apply(raw).unwrap();
+
+    let mut keys: Vec<&str> = normalized
+        .as_object()
+        .unwrap()
+        .keys()
+        .map(String::as_str)
+        .collect();
+    keys.sort();
+    assert_eq!(
+        keys,
+        vec![
+            "channels_used",
+            "messages_sent",
+            "messages_sent_details",
+            "plan",
+            "sent_at",
+            "sequence_id",
+            "status",
+            "user_id",
+        ]
+    );
+    assert_eq!(
+        normalized["messages_sent"].as_i64(),
+        Some(normalized["messages_sent_details"].as_array().unwrap().len() as i64)
+    );
+
+    assert!(registry.result_normalizer("microservices_update_user_status").is_none());
+}
+
+#[test]
+fn result_normalizer_renames_strips_and_coerces() {
+    let normalizer = ResultNormalizer::new()
+        .rename("count", "messages_sent")
+        .strip("debug")
+        .coerce("messages_sent", Coercion::Integer)
+        .coerce("ok", Coercion::Bool)
+        .coerce("code", Coercion::String);
+
+    let normalized = normalizer
+        .apply(json!({"count": "3", "debug": {"x": 1}, "ok": "true", "code": 42}))
+        .unwrap();
+    assert_eq!(normalized, json!({"messages_sent": 3, "ok": true, "code": "42"}));
+
+    let err = normalizer.apply(json!({"count": "three"})).unwrap_err();
+    assert!(err.contains("messages_sent"), "{err}");
+    assert!(normalizer.apply(json!([1, 2])).is_err());
+}
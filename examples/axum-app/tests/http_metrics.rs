@@ -0,0 +1,120 @@
+//! HTTP metrics tests: request counters, latency histograms, and `/metrics`.
+//!
+//! The app is served on a random port with a lazily-connected pool, and the
+//! requests used here are rejected before any query runs, so no database or
+//! orchestration services are needed.
+//!
+//! Run: cargo test --test http_metrics
+
+mod common;
+
+use example_axum_app::metrics;
+
+use common::spawn_app;
+
+#[tokio::test]
+async fn request_counters_use_matched_route() {
+    let base_url = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    let labels = [
+        ("method", "GET"),
+        ("route", "/orders/{id}"),
+        ("status", "400"),
+    ];
+    let before = metrics::registry().counter_value("http_requests_total", &labels);
+    let latency_before = metrics::registry().histogram_count(
+        "http_request_duration_seconds",
+        &[("method", "GET"), ("route", "/orders/{id}")],
+    );
+
+    // Non-numeric ids are rejected by the Path extractor with 400
+    for id in ["abc", "def"] {
+        let res = client
+            .get(format!("{}/orders/{}", base_url, id))
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(res.status(), 400);
+    }
+
+    assert_eq!(
+        metrics::registry().counter_value("http_requests_total", &labels),
+        before + 2
+    );
+    assert_eq!(
+        metrics::registry().histogram_count(
+            "http_request_duration_seconds",
+            &[("method", "GET"), ("route", "/orders/{id}")],
+        ),
+        latency_before + 2
+    );
+
+    let res = client
+        .get(format!("{}/metrics", base_url))
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert_eq!(res.status(), 200);
+    let body = res.text().await.unwrap();
+
+    assert!(body.contains("# TYPE http_requests_total counter"));
+    assert!(body.contains("# TYPE http_request_duration_seconds histogram"));
+    assert!(body.contains(r#"http_requests_total{method="GET",route="/orders/{id}",status="400"}"#));
+    // Raw paths never become label values
+    assert!(!body.contains("/orders/abc"));
+}
+
+#[tokio::test]
+async fn unmatched_requests_share_one_route_label() {
+    let base_url = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    let labels = [("method", "GET"), ("route", "unmatched"), ("status", "404")];
+    let before = metrics::registry().counter_value("http_requests_total", &labels);
+
+    for path in ["/no-such-route/1", "/no-such-route/2"] {
+        let res = client
+            .get(format!("{}{}", base_url, path))
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(res.status(), 404);
+    }
+
+    assert_eq!(
+        metrics::registry().counter_value("http_requests_total", &labels),
+        before + 2
+    );
+}
+
+#[tokio::test]
+async fn handler_executions_are_exposed() {
+    let base_url = spawn_app().await;
+    let success = [("handler", "metrics_test_handler"), ("outcome", "success")];
+    let failure = [("handler", "metrics_test_handler"), ("outcome", "failure")];
+
+    metrics::record_handler_execution("metrics_test_handler", true, 12);
+    metrics::record_handler_execution("metrics_test_handler", true, 30);
+    metrics::record_handler_execution("metrics_test_handler", false, 5);
+
+    let registry = metrics::registry();
+    assert_eq!(registry.counter_value("handler_executions_total", &success), 2);
+    assert_eq!(registry.counter_value("handler_executions_total", &failure), 1);
+    assert_eq!(
+        registry.histogram_count("handler_duration_seconds", &[("handler", "metrics_test_handler")]),
+        3
+    );
+
+    let body = reqwest::get(format!("{}/metrics", base_url))
+        .await
+        .expect("Failed to scrape metrics")
+        .text()
+        .await
+        .unwrap();
+    assert!(body.contains("# TYPE handler_executions_total counter"));
+    assert!(body.contains(
+        r#"handler_executions_total{handler="metrics_test_handler",outcome="success"} 2"#
+    ));
+    assert!(body.contains("# TYPE handler_duration_seconds histogram"));
+}
@@ -16,8 +16,12 @@
 //! cd examples/axum-app && cargo nextest run
 //! ```
 
+mod common;
+
 #[cfg(test)]
 mod tests {
+    use example_axum_app::handler_metrics::{HandlerMetrics, MetricsCallback};
+    use example_axum_app::task_events::TERMINAL_STATUSES;
     use serde_json::json;
     use std::sync::{Arc, OnceLock};
 
@@ -45,23 +49,17 @@ mod tests {
                     .expect("Failed to build tokio runtime for test server");
 
                 rt.block_on(async {
-                    let db_url = example_axum_app::db::pool_from_env();
-                    let pool = sqlx::postgres::PgPoolOptions::new()
-                        .max_connections(5)
-                        .connect(&db_url)
+                    let pool = example_axum_app::db::connect()
                         .await
                         .expect("Failed to connect to app database");
 
-                    sqlx::migrate!("./migrations")
-                        .run(&pool)
-                        .await
-                        .expect("Failed to run migrations");
-
                     // Bootstrap the Tasker worker: register templates with
                     // orchestration and start the handler dispatch pipeline.
+                    // The metrics callback feeds the app's /debug/handlers.
+                    let handler_metrics = HandlerMetrics::new();
                     {
                         use tasker_worker::worker::handlers::{
-                            HandlerDispatchConfig, HandlerDispatchService, NoOpCallback,
+                            HandlerDispatchConfig, HandlerDispatchService,
                         };
 
                         let mut worker_handle = tasker_worker::WorkerBootstrap::bootstrap()
@@ -79,7 +77,7 @@ mod tests {
                                     dispatch_handles.completion_sender,
                                     registry,
                                     dispatch_config,
-                                    Arc::new(NoOpCallback),
+                                    Arc::new(MetricsCallback::new(handler_metrics.clone())),
                                 );
 
                             tokio::spawn(async move {
@@ -90,7 +88,13 @@ mod tests {
                         eprintln!("Tasker worker bootstrapped");
                     }
 
-                    let app = example_axum_app::create_app(pool);
+                    let app = example_axum_app::create_app_with_config(
+                        pool,
+                        example_axum_app::AppConfig {
+                            handler_metrics,
+                            ..Default::default()
+                        },
+                    );
 
                     // Bind to port 0 for OS-assigned random port
                     let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
@@ -114,6 +118,27 @@ mod tests {
         })
     }
 
+    /// Connect to the app database directly (for tests that seed or inspect rows).
+    async fn app_pool() -> sqlx::PgPool {
+        dotenvy::dotenv().ok();
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .max_connections(2)
+            .connect(&example_axum_app::db::pool_from_env())
+            .await
+            .expect("Failed to connect to app database");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("Failed to run migrations");
+        pool
+    }
+
+    /// Serve an app built with a custom config on a random port (no worker).
+    async fn spawn_app_with_config(config: example_axum_app::AppConfig) -> String {
+        crate::common::serve(example_axum_app::create_app_with_config(app_pool().await, config))
+            .await
+    }
+
     fn orchestration_url() -> String {
         std::env::var("ORCHESTRATION_URL").unwrap_or_else(|_| "http://localhost:8080".to_string())
     }
@@ -123,9 +148,6 @@ mod tests {
             .unwrap_or_else(|_| "test-api-key-full-access".to_string())
     }
 
-    /// Terminal statuses that indicate a task is definitively done.
-    const TERMINAL_STATUSES: &[&str] = &["complete", "error", "cancelled"];
-
     /// Failure statuses that may still transition (grace period before accepting).
     const FAILURE_STATUSES: &[&str] = &["blocked_by_failures"];
 
@@ -243,7 +265,7 @@ mod tests {
             .json(&json!({
                 "customer_email": "async-test@example.com",
                 "cart_items": [
-                    {"sku": "A1", "name": "Async Widget", "quantity": 1, "unit_price": 24.99}
+                    {"sku": "WGT-A-001", "name": "Async Widget", "quantity": 1, "unit_price": 24.99}
                 ],
                 "payment_token": "tok_test_async",
                 "shipping_address": {
@@ -265,6 +287,108 @@ mod tests {
         assert_eq!(body["data"]["status"].as_str().unwrap(), "queued");
     }
 
+    #[tokio::test]
+    async fn test_create_order_with_field_aliases() {
+        let config = example_axum_app::AppConfig {
+            field_aliases: example_axum_app::extract::FieldAliases::common(),
+            ..Default::default()
+        };
+        let url = spawn_app_with_config(config).await;
+
+        let client = reqwest::Client::new();
+        let res = client
+            .post(format!("{}/orders", url))
+            .json(&json!({
+                "email": "alias-test@example.com",
+                "items": [
+                    {"sku": "1", "name": "Widget A", "quantity": 1, "unit_price": 29.99}
+                ],
+                "payment_token": "tok_test_success",
+                "shipping_address": {
+                    "street": "5 Alias Ln",
+                    "city": "Anytown",
+                    "state": "CA",
+                    "zip": "90210",
+                    "country": "US"
+                }
+            }))
+            .send()
+            .await
+            .expect("Failed to send request");
+
+        assert_eq!(res.status(), 201, "Expected aliased fields to be accepted");
+        let body: serde_json::Value = res.json().await.unwrap();
+        assert_eq!(
+            body["data"]["customer_email"].as_str(),
+            Some("alias-test@example.com")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_order_batch_reports_each_order() {
+        let order = |email: &str, quantity: i64| {
+            json!({
+                "customer_email": email,
+                "cart_items": [
+                    {"sku": "1", "name": "Widget A", "quantity": quantity, "unit_price": 29.99}
+                ],
+                "payment_token": "tok_test_success",
+                "shipping_address": {
+                    "street": "9 Batch Rd",
+                    "city": "Anytown",
+                    "state": "CA",
+                    "zip": "90210",
+                    "country": "US"
+                }
+            })
+        };
+
+        let client = reqwest::Client::new();
+        let res = client
+            .post(format!("{}/orders/batch", base_url()))
+            .json(&json!({
+                "orders": [
+                    order("batch-1@example.com", 1),
+                    order("batch-2@example.com", 0),
+                    order("batch-3@example.com", 3)
+                ]
+            }))
+            .send()
+            .await
+            .expect("Failed to send request");
+
+        assert_eq!(res.status(), 201, "Expected 201 when any order is created");
+        let body: serde_json::Value = res.json().await.unwrap();
+        assert_eq!(body["message"], "2 of 3 orders created");
+        let results = body["data"].as_array().unwrap();
+        assert_eq!(results.len(), 3);
+
+        for i in [0, 2] {
+            assert_eq!(results[i]["index"], i);
+            assert_eq!(results[i]["success"], true, "{}", results[i]);
+            assert!(results[i]["id"].is_number());
+            assert!(results[i]["task_uuid"].is_string());
+            assert!(results[i].get("error").is_none());
+        }
+
+        assert_eq!(results[1]["index"], 1);
+        assert_eq!(results[1]["success"], false);
+        assert!(results[1].get("id").is_none(), "Rejected orders are not inserted");
+        assert_eq!(results[1]["error"]["code"], "validation_failed");
+        assert_eq!(results[1]["error"]["field"], "cart_items[0].quantity");
+
+        let pool = app_pool().await;
+        let id = results[2]["id"].as_i64().unwrap() as i32;
+        let (email, status): (String, String) =
+            sqlx::query_as("SELECT customer_email, status FROM orders WHERE id = $1")
+                .bind(id)
+                .fetch_one(&pool)
+                .await
+                .expect("Batch order should be stored");
+        assert_eq!(email, "batch-3@example.com");
+        assert_eq!(status, "processing");
+    }
+
     #[tokio::test]
     async fn test_get_order_not_found() {
         let client = reqwest::Client::new();
@@ -284,6 +408,7 @@ mod tests {
             .post(format!("{}/analytics", base_url()))
             .json(&json!({
                 "job_name": "monthly_report_q4",
+                "force": true,
                 "sources": ["sales", "inventory", "customers"],
                 "date_range": {
                     "start_date": "2025-10-01",
@@ -347,7 +472,7 @@ mod tests {
             .json(&json!({
                 "customer_email": "completion-test@example.com",
                 "cart_items": [
-                    {"sku": "C1", "name": "Completion Widget", "quantity": 1, "unit_price": 19.99}
+                    {"sku": "WGT-A-001", "name": "Completion Widget", "quantity": 1, "unit_price": 19.99}
                 ],
                 "payment_token": "tok_test_completion",
                 "shipping_address": {
@@ -391,9 +516,79 @@ mod tests {
             .expect("Expected validate_cart step");
         assert!(validate_step["attempts"].as_i64().unwrap() >= 1);
 
+        // The worker counted the handler executions
+        let metrics = client
+            .get(format!("{}/metrics", base_url()))
+            .send()
+            .await
+            .expect("Failed to scrape metrics")
+            .text()
+            .await
+            .unwrap();
+        assert!(
+            metrics.contains(
+                r#"handler_executions_total{handler="ecommerce_validate_cart",outcome="success"}"#
+            ),
+            "handler metrics missing from /metrics"
+        );
+
         println!("  E-commerce task (sync): {} ({}/5 steps complete)", status, completed);
     }
 
+    #[tokio::test]
+    async fn test_completed_order_task_is_reconciled_into_the_order_row() {
+        let client = reqwest::Client::new();
+        let res = client
+            .post(format!("{}/orders", base_url()))
+            .json(&json!({
+                "customer_email": "reconcile-e2e@example.com",
+                "cart_items": [
+                    {"sku": "WGT-A-001", "name": "Reconciled Widget", "quantity": 1, "unit_price": 19.99}
+                ],
+                "payment_token": "tok_test_completion",
+                "shipping_address": {
+                    "street": "1 Test Ln",
+                    "city": "Testville",
+                    "state": "OR",
+                    "zip": "97201",
+                    "country": "US"
+                }
+            }))
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(res.status(), 201);
+        let body: serde_json::Value = res.json().await.unwrap();
+        let order_id = body["data"]["id"].as_i64().expect("Expected order ID");
+        let task_uuid = body["data"]["task_uuid"]
+            .as_str()
+            .expect("Expected task_uuid in response");
+        assert_eq!(body["data"]["status"], "processing");
+
+        let task = wait_for_task_completion(&client, task_uuid).await;
+        assert_eq!(task["status"].as_str(), Some("complete"));
+
+        // Run a reconciler pass now rather than waiting for the interval
+        let res = client
+            .post(format!(
+                "{}/admin/reconcile?older_than_secs=0&limit=1000",
+                base_url()
+            ))
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(res.status(), 200);
+
+        let res = client
+            .get(format!("{}/orders/{}", base_url(), order_id))
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(res.status(), 200);
+        let body: serde_json::Value = res.json().await.unwrap();
+        assert_eq!(body["data"]["status"], "completed");
+    }
+
     #[tokio::test]
     async fn test_ecommerce_order_async_dispatches_and_processes() {
         let client = reqwest::Client::new();
@@ -402,7 +597,7 @@ mod tests {
             .json(&json!({
                 "customer_email": "async-completion@example.com",
                 "cart_items": [
-                    {"sku": "A1", "name": "Async Widget", "quantity": 1, "unit_price": 24.99}
+                    {"sku": "WGT-A-001", "name": "Async Widget", "quantity": 1, "unit_price": 24.99}
                 ],
                 "payment_token": "tok_test_async",
                 "shipping_address": {
@@ -473,6 +668,7 @@ mod tests {
             .json(&json!({
                 "job_name": "completion_test_pipeline",
                 "sources": ["sales", "inventory", "customers"],
+                "force": true,
                 "date_range": {
                     "start_date": "2026-01-01",
                     "end_date": "2026-01-07"
@@ -532,6 +728,20 @@ mod tests {
             .count();
         assert_eq!(completed, 8, "Expected all 8 steps to complete, got {}", completed);
 
+        // The job's own endpoint reports the same step breakdown
+        let job: serde_json::Value = client
+            .get(format!("{}/analytics/{}", base_url(), body["data"]["id"]))
+            .send()
+            .await
+            .expect("Failed to fetch analytics job")
+            .json()
+            .await
+            .unwrap();
+        let job_steps = job["data"]["task"]["steps"]
+            .as_array()
+            .expect("Expected steps array on the analytics job");
+        assert_eq!(job_steps.len(), 8);
+
         println!(
             "  Analytics task: {} ({}/8 steps complete)",
             status, completed
@@ -756,6 +966,29 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_refund_creation_reports_both_step_counts() {
+        let res = reqwest::Client::new()
+            .post(format!("{}/compliance/refund", base_url()))
+            .json(&json!({
+                "check_type": "refund",
+                "namespace": "customer_success_rs",
+                "ticket_id": "TICKET-STEPS",
+                "customer_email": "step-counts@example.com",
+                "order_id": "ORD-20260101-STEPS1",
+                "refund_amount": 12.00,
+                "reason": "Step count test"
+            }))
+            .send()
+            .await
+            .expect("Failed to send request");
+
+        assert_eq!(res.status(), 201);
+        let body: serde_json::Value = res.json().await.unwrap();
+        assert_eq!(body["data"]["cs_total_steps"], 5);
+        assert_eq!(body["data"]["payments_total_steps"], 4);
+    }
+
     #[tokio::test]
     async fn test_create_refund_compliance_check() {
         let client = reqwest::Client::new();
@@ -787,4 +1020,3360 @@ mod tests {
             "customer_success_rs"
         );
     }
+
+    #[tokio::test]
+    async fn test_compliance_check_correlates_local_order() {
+        let client = reqwest::Client::new();
+
+        let res = client
+            .post(format!("{}/orders", base_url()))
+            .json(&json!({
+                "customer_email": "refund-correlation@example.com",
+                "cart_items": [
+                    {"sku": "1", "name": "Widget A", "quantity": 1, "unit_price": 29.99}
+                ],
+                "payment_token": "tok_test_success",
+                "shipping_address": {
+                    "street": "1 Refund Rd",
+                    "city": "Anytown",
+                    "state": "CA",
+                    "zip": "90210",
+                    "country": "US"
+                }
+            }))
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(res.status(), 201);
+        let body: serde_json::Value = res.json().await.unwrap();
+        let order_id = body["data"]["id"].as_i64().expect("Expected order ID");
+
+        let res = client
+            .post(format!("{}/compliance/refund", base_url()))
+            .json(&json!({
+                "check_type": "refund",
+                "namespace": "customer_success_rs",
+                "ticket_id": "TICKET-CORR-1",
+                "customer_email": "refund-correlation@example.com",
+                "order_id": order_id.to_string(),
+                "refund_amount": 29.99,
+                "reason": "Correlation test - matched order"
+            }))
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(res.status(), 201);
+        let body: serde_json::Value = res.json().await.unwrap();
+        assert_eq!(body["data"]["order_ref"].as_i64(), Some(order_id));
+        let check_id = body["data"]["id"].as_i64().unwrap();
+
+        let res = client
+            .get(format!("{}/compliance/{}", base_url(), check_id))
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(res.status(), 200);
+        let body: serde_json::Value = res.json().await.unwrap();
+        assert_eq!(body["data"]["order_ref"].as_i64(), Some(order_id));
+        assert_eq!(body["data"]["order"]["id"].as_i64(), Some(order_id));
+        assert_eq!(
+            body["data"]["order"]["customer_email"].as_str(),
+            Some("refund-correlation@example.com")
+        );
+    }
+
+    /// Create a local order of two $25.00 widgets and return its id.
+    async fn create_fifty_dollar_order(client: &reqwest::Client, email: &str) -> i64 {
+        let res = client
+            .post(format!("{}/orders", base_url()))
+            .json(&json!({
+                "customer_email": email,
+                "cart_items": [
+                    {"sku": "1", "name": "Widget A", "quantity": 2, "unit_price": 25.00}
+                ],
+                "payment_token": "tok_test_success",
+                "shipping_address": {
+                    "street": "1 Refund Rd",
+                    "city": "Anytown",
+                    "state": "CA",
+                    "zip": "90210",
+                    "country": "US"
+                }
+            }))
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(res.status(), 201);
+        let body: serde_json::Value = res.json().await.unwrap();
+        body["data"]["id"].as_i64().expect("Expected order ID")
+    }
+
+    #[tokio::test]
+    async fn test_full_refund_of_local_order_is_accepted() {
+        let client = reqwest::Client::new();
+        let order_id = create_fifty_dollar_order(&client, "full-refund@example.com").await;
+
+        let res = client
+            .post(format!("{}/compliance/refund", base_url()))
+            .json(&json!({
+                "check_type": "refund",
+                "namespace": "customer_success_rs",
+                "ticket_id": "TICKET-FULL-1",
+                "customer_email": "full-refund@example.com",
+                "order_id": order_id.to_string(),
+                "refund_amount": 50.00,
+                "reason": "Full refund of the order total"
+            }))
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(res.status(), 201);
+        let body: serde_json::Value = res.json().await.unwrap();
+        assert_eq!(body["data"]["order_ref"].as_i64(), Some(order_id));
+    }
+
+    #[tokio::test]
+    async fn test_refund_over_local_order_total_returns_400() {
+        let client = reqwest::Client::new();
+        let order_id = create_fifty_dollar_order(&client, "over-refund@example.com").await;
+
+        let res = client
+            .post(format!("{}/compliance/refund", base_url()))
+            .json(&json!({
+                "check_type": "refund",
+                "namespace": "customer_success_rs",
+                "ticket_id": "TICKET-OVER-1",
+                "customer_email": "over-refund@example.com",
+                "order_id": order_id.to_string(),
+                "refund_amount": 50.01,
+                "reason": "Refund more than was paid"
+            }))
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(res.status(), 400);
+        let body: serde_json::Value = res.json().await.unwrap();
+        assert_eq!(body["error"]["code"], "bad_request");
+        assert_eq!(
+            body["error"]["message"],
+            "refund_amount 50.01 exceeds the order total 50.00"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_compliance_check_without_local_order() {
+        let client = reqwest::Client::new();
+
+        // Neither an external order number nor an unknown numeric ID should
+        // correlate, and neither should fail the request.
+        for order_id in ["ORD-20251115-EXT001", "999999"] {
+            let res = client
+                .post(format!("{}/compliance/refund", base_url()))
+                .json(&json!({
+                    "check_type": "refund",
+                    "namespace": "customer_success_rs",
+                    "ticket_id": "TICKET-CORR-2",
+                    "customer_email": "external-order@example.com",
+                    "order_id": order_id,
+                    "refund_amount": 10.00,
+                    "reason": "Correlation test - unmatched order"
+                }))
+                .send()
+                .await
+                .expect("Failed to send request");
+            assert_eq!(res.status(), 201);
+            let body: serde_json::Value = res.json().await.unwrap();
+            assert!(body["data"]["order_ref"].is_null());
+            let check_id = body["data"]["id"].as_i64().unwrap();
+
+            let res = client
+                .get(format!("{}/compliance/{}", base_url(), check_id))
+                .send()
+                .await
+                .expect("Failed to send request");
+            assert_eq!(res.status(), 200);
+            let body: serde_json::Value = res.json().await.unwrap();
+            assert!(body["data"]["order_ref"].is_null());
+            assert!(body["data"]["order"].is_null());
+        }
+    }
+
+    // -----------------------------------------------------------------------
+    // Stale Row Sweeper
+    // -----------------------------------------------------------------------
+
+    /// Insert an order that has been `pending` with no task for `age_secs`.
+    async fn seed_pending_order(pool: &sqlx::PgPool, email: &str, age_secs: i64) -> i32 {
+        sqlx::query_scalar(
+            r#"
+            INSERT INTO orders (customer_email, items, total, status, task_request, created_at)
+            VALUES ($1, '[]', 10.00, 'pending', $2, NOW() - ($3::bigint * INTERVAL '1 second'))
+            RETURNING id
+            "#,
+        )
+        .bind(email)
+        .bind(json!({"name": "ecommerce_order_processing", "namespace": "ecommerce_rs"}))
+        .bind(age_secs)
+        .fetch_one(pool)
+        .await
+        .expect("Failed to seed order")
+    }
+
+    async fn order_status(pool: &sqlx::PgPool, id: i32) -> (String, Option<String>) {
+        sqlx::query_as("SELECT status, status_reason FROM orders WHERE id = $1")
+            .bind(id)
+            .fetch_one(pool)
+            .await
+            .expect("Failed to read order status")
+    }
+
+    #[tokio::test]
+    async fn test_sweeper_fails_stale_pending_rows() {
+        use example_axum_app::orchestration::OrchestrationClient;
+        use example_axum_app::sweeper::{sweep_once, SweeperConfig};
+
+        let pool = app_pool().await;
+        let stale_id = seed_pending_order(&pool, "stale-sweep@example.com", 7200).await;
+        let fresh_id = seed_pending_order(&pool, "fresh-sweep@example.com", 0).await;
+
+        let config = SweeperConfig {
+            max_pending_age: std::time::Duration::from_secs(3600),
+            resubmit: false,
+            ..SweeperConfig::default()
+        };
+        let report = sweep_once(&pool, &OrchestrationClient::new(orchestration_url()), &config)
+            .await
+            .expect("Sweep failed");
+        assert!(report.failed >= 1);
+        assert_eq!(report.resubmitted, 0);
+
+        let (status, reason) = order_status(&pool, stale_id).await;
+        assert_eq!(status, "failed");
+        assert!(reason.is_some_and(|r| r.contains("No workflow task was created")));
+
+        let (status, reason) = order_status(&pool, fresh_id).await;
+        assert_eq!(status, "pending", "Fresh pending rows must not be swept");
+        assert!(reason.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_sweeper_fails_row_when_resubmission_fails() {
+        use example_axum_app::orchestration::OrchestrationClient;
+        use example_axum_app::sweeper::{sweep_once, SweeperConfig};
+
+        let pool = app_pool().await;
+        let stale_id = seed_pending_order(&pool, "resubmit-sweep@example.com", 7200).await;
+
+        // Nothing listens on the discard port, so the resubmission attempt fails
+        let config = SweeperConfig {
+            max_pending_age: std::time::Duration::from_secs(3600),
+            resubmit: true,
+            ..SweeperConfig::default()
+        };
+        sweep_once(&pool, &OrchestrationClient::new("http://127.0.0.1:9"), &config)
+            .await
+            .expect("Sweep failed");
+
+        let (status, reason) = order_status(&pool, stale_id).await;
+        assert_eq!(status, "failed");
+        assert!(reason.is_some_and(|r| r.contains("resubmission failed")));
+    }
+
+    #[tokio::test]
+    async fn test_sweeper_cancels_resubmission_when_the_row_changed() {
+        use example_axum_app::orchestration::OrchestrationClient;
+        use example_axum_app::sweeper::{sweep_once, SweeperConfig};
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        const TASK_UUID: &str = "0191e0a4-7b3c-7d2e-9f10-5ee9e4c0ffee";
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/tasks"))
+            .respond_with(
+                ResponseTemplate::new(201)
+                    .set_body_json(json!({"task_uuid": TASK_UUID}))
+                    .set_delay(std::time::Duration::from_millis(500)),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path(format!("/v1/tasks/{TASK_UUID}")))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .expect(1..)
+            .mount(&server)
+            .await;
+
+        let pool = app_pool().await;
+        let stale_id = seed_pending_order(&pool, "race-sweep@example.com", 7200).await;
+
+        let config = SweeperConfig {
+            max_pending_age: std::time::Duration::from_secs(3600),
+            resubmit: true,
+            ..SweeperConfig::default()
+        };
+        let client = OrchestrationClient::new(server.uri());
+        let sweep = {
+            let pool = pool.clone();
+            tokio::spawn(async move { sweep_once(&pool, &client, &config).await })
+        };
+
+        // Cancel the order while its resubmission is in flight
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        sqlx::query("UPDATE orders SET status = 'cancelled' WHERE id = $1")
+            .bind(stale_id)
+            .execute(&pool)
+            .await
+            .expect("Failed to cancel order");
+
+        let report = sweep.await.unwrap().expect("Sweep failed");
+        assert!(report.cancelled >= 1, "{report:?}");
+
+        let (status, _) = order_status(&pool, stale_id).await;
+        assert_eq!(status, "cancelled", "The sweeper must not revive a cancelled row");
+        let task_uuid: Option<uuid::Uuid> =
+            sqlx::query_scalar("SELECT task_uuid FROM orders WHERE id = $1")
+                .bind(stale_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert!(task_uuid.is_none());
+    }
+
+    // -----------------------------------------------------------------------
+    // Tags
+    // -----------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn test_order_tags_are_stored_and_filterable() {
+        let client = reqwest::Client::new();
+        // Unique per run so earlier runs' rows never match the filter
+        let campaign = format!("campaign-{}", uuid::Uuid::new_v4());
+
+        let mut ids = Vec::new();
+        for region in ["us-west", "us-east"] {
+            let res = client
+                .post(format!("{}/orders", base_url()))
+                .json(&json!({
+                    "customer_email": "tagged@example.com",
+                    "cart_items": [
+                        {"sku": "1", "name": "Widget A", "quantity": 1, "unit_price": 29.99}
+                    ],
+                    "payment_token": "tok_test_success",
+                    "shipping_address": {
+                        "street": "9 Tag St",
+                        "city": "Anytown",
+                        "state": "CA",
+                        "zip": "90210",
+                        "country": "US"
+                    },
+                    "tags": {"campaign": campaign, "region": region}
+                }))
+                .send()
+                .await
+                .expect("Failed to send request");
+            assert_eq!(res.status(), 201);
+            let body: serde_json::Value = res.json().await.unwrap();
+            ids.push(body["data"]["id"].as_i64().unwrap());
+        }
+
+        // Tags are stored on the row
+        let res = client
+            .get(format!("{}/orders/{}", base_url(), ids[0]))
+            .send()
+            .await
+            .expect("Failed to send request");
+        let body: serde_json::Value = res.json().await.unwrap();
+        assert_eq!(body["data"]["tags"]["campaign"], campaign.as_str());
+        assert_eq!(body["data"]["tags"]["region"], "us-west");
+
+        let list_ids = |body: serde_json::Value| -> Vec<i64> {
+            body["data"]
+                .as_array()
+                .expect("Expected data array")
+                .iter()
+                .map(|o| o["id"].as_i64().unwrap())
+                .collect()
+        };
+
+        // One tag matches both orders
+        let res = client
+            .get(format!("{}/orders", base_url()))
+            .query(&[("tag.campaign", campaign.as_str())])
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(res.status(), 200);
+        let mut found = list_ids(res.json().await.unwrap());
+        found.sort();
+        assert_eq!(found, ids);
+
+        // Multiple tags must all match
+        let res = client
+            .get(format!("{}/orders", base_url()))
+            .query(&[("tag.campaign", campaign.as_str()), ("tag.region", "us-east")])
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(list_ids(res.json().await.unwrap()), vec![ids[1]]);
+
+        let res = client
+            .get(format!("{}/orders", base_url()))
+            .query(&[("tag.campaign", campaign.as_str()), ("tag.region", "eu-central")])
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert!(list_ids(res.json().await.unwrap()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_analytics_job_tags_are_filterable() {
+        let client = reqwest::Client::new();
+        let campaign = format!("campaign-{}", uuid::Uuid::new_v4());
+
+        let res = client
+            .post(format!("{}/analytics", base_url()))
+            .json(&json!({
+                "job_name": "tagged_report",
+                "sources": ["sales"],
+                "force": true,
+                "tags": {"campaign": campaign}
+            }))
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(res.status(), 201);
+        let body: serde_json::Value = res.json().await.unwrap();
+        let job_id = body["data"]["id"].as_i64().unwrap();
+
+        let res = client
+            .get(format!("{}/analytics", base_url()))
+            .query(&[("tag.campaign", campaign.as_str())])
+            .send()
+            .await
+            .expect("Failed to send request");
+        let body: serde_json::Value = res.json().await.unwrap();
+        let jobs = body["data"].as_array().unwrap();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0]["id"].as_i64(), Some(job_id));
+        assert_eq!(jobs[0]["tags"]["campaign"], campaign.as_str());
+    }
+
+    // -----------------------------------------------------------------------
+    // Customer Workflows
+    // -----------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn test_customer_workflows_span_domains() {
+        let pool = app_pool().await;
+        let email = format!("workflows-{}@example.com", uuid::Uuid::new_v4());
+
+        let order_id = seed_pending_order(&pool, &email, 30).await;
+        let service_id: i32 = sqlx::query_scalar(
+            r#"
+            INSERT INTO service_requests (service_type, user_email, status, created_at)
+            VALUES ('user_registration', $1, 'processing', NOW() - INTERVAL '20 seconds')
+            RETURNING id
+            "#,
+        )
+        .bind(&email)
+        .fetch_one(&pool)
+        .await
+        .expect("Failed to seed service request");
+        let check_id: i32 = sqlx::query_scalar(
+            r#"
+            INSERT INTO compliance_checks (check_type, namespace, customer_email, status, created_at)
+            VALUES ('refund', 'customer_success_rs', $1, 'pending', NOW() - INTERVAL '10 seconds')
+            RETURNING id
+            "#,
+        )
+        .bind(&email)
+        .fetch_one(&pool)
+        .await
+        .expect("Failed to seed compliance check");
+        // Another customer's row must not leak in
+        seed_pending_order(&pool, "someone-else@example.com", 0).await;
+
+        let base_url = spawn_app_with_config(example_axum_app::AppConfig::default()).await;
+        let res = reqwest::Client::new()
+            .get(format!("{}/customers/{}/workflows", base_url, email))
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(res.status(), 200);
+
+        let body: serde_json::Value = res.json().await.unwrap();
+        let workflows: Vec<(String, i64)> = body["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|w| (w["domain_type"].as_str().unwrap().to_string(), w["id"].as_i64().unwrap()))
+            .collect();
+        assert_eq!(
+            workflows,
+            vec![
+                ("compliance_check".to_string(), check_id as i64),
+                ("service_request".to_string(), service_id as i64),
+                ("order".to_string(), order_id as i64),
+            ]
+        );
+        assert_eq!(body["data"][1]["status"], "processing");
+    }
+
+    // -----------------------------------------------------------------------
+    // SKU Resolution
+    // -----------------------------------------------------------------------
+
+    fn order_with_sku(sku: &str) -> serde_json::Value {
+        json!({
+            "customer_email": "sku@example.com",
+            "cart_items": [
+                {"sku": "WGT-A-001", "name": "Widget A", "quantity": 1, "unit_price": 29.99},
+                {"sku": sku, "name": "Mystery item", "quantity": 1, "unit_price": 9.99}
+            ],
+            "payment_token": "tok_test_success",
+            "shipping_address": {
+                "street": "123 Main St",
+                "city": "Anytown",
+                "state": "CA",
+                "zip": "90210",
+                "country": "US"
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_alphanumeric_sku_resolves_to_product() {
+        use example_axum_app::catalog::product_ids_for_skus;
+
+        let pool = app_pool().await;
+        let sku = format!("SKU-{}", uuid::Uuid::new_v4().simple());
+        sqlx::query("INSERT INTO skus (sku, product_id) VALUES ($1, 3)")
+            .bind(&sku)
+            .execute(&pool)
+            .await
+            .expect("Failed to seed SKU");
+
+        let resolved = product_ids_for_skus(&pool, &[sku.clone(), "NOPE-404".to_string()])
+            .await
+            .expect("Failed to resolve SKUs");
+        assert_eq!(resolved.get(&sku), Some(&3));
+        assert!(!resolved.contains_key("NOPE-404"));
+
+        let base_url = spawn_app_with_config(example_axum_app::AppConfig::default()).await;
+        let res = reqwest::Client::new()
+            .post(format!("{}/orders", base_url))
+            .json(&order_with_sku(&sku))
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(res.status(), 201);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_sku_returns_400() {
+        let base_url = spawn_app_with_config(example_axum_app::AppConfig::default()).await;
+        let client = reqwest::Client::new();
+
+        for path in ["/orders", "/orders/async"] {
+            let res = client
+                .post(format!("{}{}", base_url, path))
+                .json(&order_with_sku("NOPE-404"))
+                .send()
+                .await
+                .expect("Failed to send request");
+            assert_eq!(res.status(), 400, "{path}");
+
+            let body: serde_json::Value = res.json().await.unwrap();
+            assert_eq!(body["error"]["code"], "bad_request");
+            assert_eq!(
+                body["error"]["message"],
+                "cart_items[1].sku: unknown SKU 'NOPE-404'"
+            );
+        }
+    }
+
+    // -----------------------------------------------------------------------
+    // Response Compression
+    // -----------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn test_large_list_responses_are_compressed() {
+        let pool = app_pool().await;
+        let campaign = format!("compress-{}", uuid::Uuid::new_v4());
+        for _ in 0..20 {
+            sqlx::query(
+                r#"
+                INSERT INTO orders (customer_email, items, total, status, tags)
+                VALUES ('compress@example.com', '[{"sku": "WGT-A-001", "quantity": 1}]', 29.99, 'pending', $1)
+                "#,
+            )
+            .bind(json!({"campaign": campaign}))
+            .execute(&pool)
+            .await
+            .expect("Failed to seed order");
+        }
+
+        let base_url = spawn_app_with_config(example_axum_app::AppConfig::default()).await;
+        let client = reqwest::Client::new();
+
+        let res = client
+            .get(format!("{}/orders", base_url))
+            .query(&[("tag.campaign", campaign.as_str())])
+            .header("Accept-Encoding", "gzip")
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(res.status(), 200);
+        assert_eq!(
+            res.headers().get("content-encoding").map(|v| v.to_str().unwrap()),
+            Some("gzip")
+        );
+
+        // An empty list is below the threshold and is sent as-is
+        let res = client
+            .get(format!("{}/orders", base_url))
+            .query(&[("tag.campaign", "no-such-campaign")])
+            .header("Accept-Encoding", "gzip")
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(res.status(), 200);
+        assert!(res.headers().get("content-encoding").is_none());
+        let body: serde_json::Value = res.json().await.unwrap();
+        assert_eq!(body["data"], json!([]));
+    }
+
+    // -----------------------------------------------------------------------
+    // Step Result Persistence
+    // -----------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn test_completed_workflow_step_results_are_queryable() {
+        use example_axum_app::handlers::ecommerce;
+        use example_axum_app::step_results::{self, StepKey};
+        use serde_json::Value;
+        use std::collections::HashMap;
+        use tasker_shared::messaging::StepExecutionResult;
+
+        type StepFn = fn(&Value, &HashMap<String, Value>) -> Result<Value, String>;
+
+        let pool = app_pool().await;
+        let task_uuid = uuid::Uuid::new_v4();
+        let context = json!({
+            "cart_items": [{"product_id": 1, "quantity": 2}],
+            "customer_email": "step-results@example.com",
+            "payment_token": "tok_test_success",
+            "shipping_address": {"state": "OR", "country": "US"}
+        });
+
+        // Run the ecommerce chain, recording each result the way the worker
+        // callback does
+        let steps: Vec<(&str, StepFn)> = vec![
+            ("validate_cart", |ctx, _| ecommerce::validate_cart(ctx)),
+            ("calculate_shipping", ecommerce::calculate_shipping),
+            ("process_payment", ecommerce::process_payment),
+            ("update_inventory", |_, deps| ecommerce::update_inventory(deps)),
+            ("create_order", ecommerce::create_order),
+            ("send_confirmation", ecommerce::send_confirmation),
+        ];
+        let mut results = HashMap::new();
+        for (step_name, handler) in steps {
+            let output = handler(&context, &results).expect("Handler failed");
+            let key = StepKey {
+                task_uuid,
+                step_name,
+                namespace: "ecommerce_rs",
+                handler_name: &format!("ecommerce_{step_name}"),
+            };
+            let result = StepExecutionResult::success(uuid::Uuid::new_v4(), output.clone(), 1, None);
+            step_results::record(&pool, key, &result)
+                .await
+                .expect("Failed to record step result");
+            results.insert(step_name.to_string(), output);
+        }
+
+        let base_url = spawn_app_with_config(example_axum_app::AppConfig::default()).await;
+        let client = reqwest::Client::new();
+        let res = client
+            .get(format!("{}/admin/tasks/{}/results", base_url, task_uuid))
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(res.status(), 200);
+
+        let body: serde_json::Value = res.json().await.unwrap();
+        let recorded = body["data"].as_array().unwrap();
+        assert_eq!(recorded.len(), 6);
+        let order = recorded
+            .iter()
+            .find(|r| r["step_name"] == "create_order")
+            .expect("create_order result recorded");
+        assert_eq!(order["success"], true);
+        assert_eq!(order["handler_name"], "ecommerce_create_order");
+        assert_eq!(order["result"]["order_id"], results["create_order"]["order_id"]);
+
+        // Also queryable directly with SQL
+        let total: f64 = sqlx::query_scalar(
+            "SELECT (result->>'total')::float8 FROM step_results WHERE task_uuid = $1 AND step_name = 'calculate_shipping'",
+        )
+        .bind(task_uuid)
+        .fetch_one(&pool)
+        .await
+        .expect("Failed to query step_results");
+        assert_eq!(json!(total), results["calculate_shipping"]["total"]);
+
+        let res = client
+            .get(format!("{}/admin/tasks/{}/results", base_url, uuid::Uuid::new_v4()))
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(res.status(), 404);
+    }
+
+    // -----------------------------------------------------------------------
+    // Dead-lettered steps
+    // -----------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn test_declined_payment_is_recorded_in_failed_steps() {
+        use example_axum_app::dead_letter::DeadLetterCallback;
+        use example_axum_app::handlers::ecommerce;
+        use example_axum_app::retry::RetryPolicy;
+        use std::collections::HashMap;
+        use tasker_shared::types::base::{
+            Task, TaskForOrchestration, TaskSequenceStep, WorkflowStepWithName,
+        };
+        use tasker_worker::worker::handlers::PostHandlerCallback;
+
+        let pool = app_pool().await;
+        let task_uuid = uuid::Uuid::new_v4();
+        let context = json!({
+            "cart_items": [{"product_id": 1, "quantity": 1}],
+            "customer_email": "declined@example.com",
+            "payment_token": "tok_test_declined"
+        });
+
+        // Fail the payment step on its first attempt and hand the result to
+        // the worker's dead-letter callback
+        let cart = ecommerce::validate_cart(&context).expect("Handler failed");
+        let deps = HashMap::from([("validate_cart".to_string(), cart)]);
+        let err =
+            ecommerce::process_payment(&context, &deps).expect_err("Payment should be declined");
+        let result = RetryPolicy::default().failure_result(uuid::Uuid::new_v4(), err.clone(), 1);
+        let step = TaskSequenceStep {
+            task: TaskForOrchestration {
+                task: Task {
+                    task_uuid,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            workflow_step: WorkflowStepWithName {
+                task_uuid,
+                name: "process_payment".to_string(),
+                attempts: Some(1),
+                max_attempts: Some(5),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        DeadLetterCallback::new(pool.clone())
+            .on_handler_complete(&step, &result, "test-worker")
+            .await;
+
+        let (step_name, category, error): (String, String, String) = sqlx::query_as(
+            "SELECT step_name, category, error FROM failed_steps WHERE task_uuid = $1",
+        )
+        .bind(task_uuid)
+        .fetch_one(&pool)
+        .await
+        .expect("Expected a failed_steps row");
+        assert_eq!(step_name, "process_payment");
+        assert_eq!(category, "permanent");
+        assert_eq!(error, err);
+
+        let base_url = spawn_app_with_config(example_axum_app::AppConfig::default()).await;
+        let res = reqwest::Client::new()
+            .get(format!(
+                "{}/debug/failed-steps?task_uuid={}",
+                base_url, task_uuid
+            ))
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(res.status(), 200);
+        let body: serde_json::Value = res.json().await.unwrap();
+        let steps = body["data"].as_array().unwrap();
+        assert_eq!(steps.len(), 1);
+        assert_eq!(steps[0]["task_uuid"], task_uuid.to_string());
+        assert_eq!(steps[0]["step_name"], "process_payment");
+    }
+
+    // -----------------------------------------------------------------------
+    // Archival
+    // -----------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn test_archived_orders_excluded_by_default() {
+        use example_axum_app::archiver::{archive_once, ArchiverConfig};
+
+        let pool = app_pool().await;
+        let batch = uuid::Uuid::new_v4().to_string();
+        let seed = |status: &'static str, age_days: i64| {
+            let pool = pool.clone();
+            let batch = batch.clone();
+            async move {
+                sqlx::query_scalar::<_, i32>(
+                    r#"
+                    INSERT INTO orders (customer_email, items, total, status, tags, updated_at)
+                    VALUES ('archive@example.com', '[]', 10.00, $1, $2, NOW() - ($3::bigint * INTERVAL '1 day'))
+                    RETURNING id
+                    "#,
+                )
+                .bind(status)
+                .bind(json!({"archive_batch": batch}))
+                .bind(age_days)
+                .fetch_one(&pool)
+                .await
+                .expect("Failed to seed order")
+            }
+        };
+        let old_complete = seed("complete", 10).await;
+        let recent_complete = seed("complete", 0).await;
+        let old_processing = seed("processing", 10).await;
+
+        let config = ArchiverConfig {
+            retention: std::time::Duration::from_secs(24 * 3600),
+            ..Default::default()
+        };
+        assert!(archive_once(&pool, &config).await.expect("Archiver failed") >= 1);
+
+        let base_url = spawn_app_with_config(example_axum_app::AppConfig::default()).await;
+        let client = reqwest::Client::new();
+        let list = |include_archived: Option<&'static str>| {
+            let mut url = format!("{}/orders?tag.archive_batch={}", base_url, batch);
+            if let Some(flag) = include_archived {
+                url.push_str(&format!("&include_archived={flag}"));
+            }
+            client.get(url).send()
+        };
+        let ids = |body: serde_json::Value| -> Vec<i64> {
+            body["data"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|o| o["id"].as_i64().unwrap())
+                .collect()
+        };
+
+        let res = list(None).await.expect("Failed to send request");
+        assert_eq!(res.status(), 200);
+        let mut active = ids(res.json().await.unwrap());
+        active.sort();
+        assert_eq!(active, vec![recent_complete as i64, old_processing as i64]);
+
+        let res = list(Some("true")).await.expect("Failed to send request");
+        assert_eq!(res.status(), 200);
+        let body: serde_json::Value = res.json().await.unwrap();
+        let archived = body["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|o| o["id"] == old_complete)
+            .expect("Archived order included with the flag");
+        assert!(archived["archived_at"].is_string());
+        assert_eq!(ids(body).len(), 3);
+
+        let res = list(Some("maybe")).await.expect("Failed to send request");
+        assert_eq!(res.status(), 422);
+    }
+
+    // -----------------------------------------------------------------------
+    // Status Reconciler
+    // -----------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn test_signed_task_webhook_completes_the_order() {
+        use example_axum_app::webhook_auth::{WebhookAuth, SIGNATURE_HEADER};
+
+        let pool = app_pool().await;
+        let task_uuid = uuid::Uuid::new_v4();
+        let id: i32 = sqlx::query_scalar(
+            "INSERT INTO orders (customer_email, items, total, status, task_uuid) \
+             VALUES ('webhook@example.com', '[]', 10.00, 'processing', $1) RETURNING id",
+        )
+        .bind(task_uuid)
+        .fetch_one(&pool)
+        .await
+        .expect("Failed to seed order");
+
+        let auth = WebhookAuth::new(Some("integration-webhook-secret".to_string()));
+        let base_url = spawn_app_with_config(example_axum_app::AppConfig {
+            webhook_auth: auth.clone(),
+            ..Default::default()
+        })
+        .await;
+        let client = reqwest::Client::new();
+        let send = |status: &str, signature: Option<String>| {
+            let body = serde_json::to_vec(&json!({
+                "task_uuid": task_uuid,
+                "status": status,
+                "completion_percentage": 100.0
+            }))
+            .unwrap();
+            let signature = signature.unwrap_or_else(|| auth.sign(&body).unwrap());
+            client
+                .post(format!("{}/webhooks/tasks", base_url))
+                .header("content-type", "application/json")
+                .header(SIGNATURE_HEADER, signature)
+                .body(body)
+                .send()
+        };
+
+        // A bad signature changes nothing
+        let res = send("complete", Some("sha256=00".to_string()))
+            .await
+            .expect("Failed to send webhook");
+        assert_eq!(res.status(), 401);
+        assert_eq!(order_status(&pool, id).await.0, "processing");
+
+        let res = send("complete", None).await.expect("Failed to send webhook");
+        assert_eq!(res.status(), 200);
+        let body: serde_json::Value = res.json().await.unwrap();
+        assert_eq!(body["data"]["domain_table"], "orders");
+        assert_eq!(body["data"]["id"], id);
+        assert_eq!(body["data"]["status"], "completed");
+        assert_eq!(order_status(&pool, id).await.0, "completed");
+
+        // A repeated delivery leaves the finished row alone
+        let res = send("error", None).await.expect("Failed to send webhook");
+        assert_eq!(res.status(), 200);
+        let body: serde_json::Value = res.json().await.unwrap();
+        assert!(body["data"]["status"].is_null());
+        assert_eq!(order_status(&pool, id).await.0, "completed");
+    }
+
+    #[tokio::test]
+    async fn test_reconciler_processes_batch_larger_than_concurrency() {
+        use example_axum_app::orchestration::OrchestrationClient;
+        use example_axum_app::reconciler::{reconcile_once, ReconcilerConfig};
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let pool = app_pool().await;
+        let server = MockServer::start().await;
+
+        // Oldest first; None means orchestration fails the fetch
+        let task_statuses = [
+            Some("complete"),
+            Some("error"),
+            Some("complete"),
+            Some("steps_in_process"),
+            None,
+            Some("complete"),
+            Some("cancelled"),
+        ];
+        let mut rows = Vec::new();
+        for (i, task_status) in task_statuses.iter().enumerate() {
+            let task_uuid = uuid::Uuid::new_v4();
+            // Far older than any other row, so these are selected first
+            let id: i32 = sqlx::query_scalar(
+                r#"
+                INSERT INTO orders (customer_email, items, total, status, task_uuid, created_at, updated_at)
+                VALUES ('reconcile@example.com', '[]', 10.00, 'processing', $1,
+                        NOW() - INTERVAL '100 years', NOW() - INTERVAL '100 years' + ($2::bigint * INTERVAL '1 minute'))
+                RETURNING id
+                "#,
+            )
+            .bind(task_uuid)
+            .bind(i as i64)
+            .fetch_one(&pool)
+            .await
+            .expect("Failed to seed order");
+
+            let response = match task_status {
+                Some(status) => ResponseTemplate::new(200)
+                    .set_body_json(json!({"task_uuid": task_uuid, "status": status})),
+                None => ResponseTemplate::new(500),
+            };
+            Mock::given(method("GET"))
+                .and(path(format!("/v1/tasks/{task_uuid}")))
+                .respond_with(response)
+                .mount(&server)
+                .await;
+            rows.push(id);
+        }
+
+        let client = OrchestrationClient::new(server.uri());
+        let config = ReconcilerConfig {
+            concurrency: 2,
+            max_rows_per_run: 5,
+            ..Default::default()
+        };
+
+        // The per-run cap leaves the two newest rows for the next run
+        let first = reconcile_once(&pool, &client, &config).await.expect("Reconciler failed");
+        assert_eq!(first.checked, 5);
+        let checked: Vec<bool> = sqlx::query_scalar(
+            "SELECT reconciled_at IS NOT NULL FROM orders WHERE id = ANY($1) ORDER BY id",
+        )
+        .bind(&rows)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(checked, vec![true, true, true, true, true, false, false]);
+
+        let second = reconcile_once(&pool, &client, &config).await.expect("Reconciler failed");
+        assert!(second.checked >= 2);
+
+        let mut statuses = Vec::new();
+        for id in &rows {
+            statuses.push(order_status(&pool, *id).await);
+        }
+        let expected = [
+            "completed",
+            "failed",
+            "completed",
+            "processing",
+            "processing",
+            "completed",
+            "failed",
+        ];
+        assert_eq!(
+            statuses.iter().map(|(s, _)| s.as_str()).collect::<Vec<_>>(),
+            expected
+        );
+        assert_eq!(
+            statuses[6].1.as_deref(),
+            Some("Workflow task ended with status 'cancelled'")
+        );
+        assert_eq!(first.updated + second.updated, 5);
+        assert!(first.errors + second.errors >= 1);
+
+        sqlx::query("DELETE FROM orders WHERE id = ANY($1)")
+            .bind(&rows)
+            .execute(&pool)
+            .await
+            .expect("Failed to clean up orders");
+    }
+
+    // -----------------------------------------------------------------------
+    // Tenant Quotas
+    // -----------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn test_tenant_quota_is_exhausted_and_rejects_submissions() {
+        let pool = app_pool().await;
+        let tenant = format!("tenant-{}", uuid::Uuid::new_v4().simple());
+        sqlx::query(
+            "INSERT INTO tenant_quotas (tenant_id, max_workflows_per_day) VALUES ($1, 2)",
+        )
+        .bind(&tenant)
+        .execute(&pool)
+        .await
+        .expect("Failed to seed tenant quota");
+
+        let base_url = spawn_app_with_config(example_axum_app::AppConfig::default()).await;
+        let client = reqwest::Client::new();
+        let submit = |body: serde_json::Value| {
+            client
+                .post(format!("{}/analytics", base_url))
+                .header("X-Tenant-Id", &tenant)
+                .json(&body)
+                .send()
+        };
+        let job = json!({"job_name": "quota_test", "sources": ["sales"], "force": true});
+
+        // A rejected request does not use up the quota
+        let invalid = submit(json!({
+            "job_name": "quota_test",
+            "sources": ["sales"],
+            "date_range": {"start_date": "not-a-date", "end_date": "2025-01-01"}
+        }))
+        .await
+        .unwrap();
+        assert_eq!(invalid.status(), 422);
+
+        let mut remaining = Vec::new();
+        for _ in 0..2 {
+            let res = submit(job.clone()).await.unwrap();
+            assert_eq!(res.status(), 201);
+            remaining.push(res.headers()["x-quota-remaining"].to_str().unwrap().to_string());
+        }
+        assert_eq!(remaining, vec!["1", "0"]);
+
+        for _ in 0..2 {
+            let res = submit(job.clone()).await.unwrap();
+            assert_eq!(res.status(), 429);
+            assert_eq!(res.headers()["x-quota-remaining"], "0");
+            assert!(res.headers().contains_key("retry-after"));
+            let body: serde_json::Value = res.json().await.unwrap();
+            assert_eq!(body["error"]["code"], "quota_exceeded");
+            assert_eq!(body["error"]["tenant_id"], tenant.as_str());
+            assert_eq!(body["error"]["quota"], "workflows_per_day");
+            assert_eq!(body["error"]["limit"], 2);
+        }
+
+        // Other tenants and untagged requests are unaffected
+        let res = client
+            .post(format!("{}/analytics", base_url))
+            .json(&job)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 201);
+        assert!(!res.headers().contains_key("x-quota-remaining"));
+
+        let created: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM analytics_jobs WHERE job_name = 'quota_test'")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert!(created >= 3);
+        sqlx::query("DELETE FROM analytics_jobs WHERE job_name = 'quota_test'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM tenant_usage WHERE tenant_id = $1")
+            .bind(&tenant)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM tenant_quotas WHERE tenant_id = $1")
+            .bind(&tenant)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_tenant_quota_charges_each_order_of_a_batch() {
+        let pool = app_pool().await;
+        let tenant = format!("tenant-{}", uuid::Uuid::new_v4().simple());
+        sqlx::query(
+            "INSERT INTO tenant_quotas (tenant_id, max_workflows_per_day) VALUES ($1, 3)",
+        )
+        .bind(&tenant)
+        .execute(&pool)
+        .await
+        .expect("Failed to seed tenant quota");
+
+        let base_url = spawn_app_with_config(example_axum_app::AppConfig::default()).await;
+        let client = reqwest::Client::new();
+        let order = |quantity: i64| {
+            json!({
+                "customer_email": "quota-batch@example.com",
+                "cart_items": [
+                    {"sku": "1", "name": "Widget A", "quantity": quantity, "unit_price": 29.99}
+                ],
+                "payment_token": "tok_test_success",
+                "shipping_address": {
+                    "street": "9 Batch Rd",
+                    "city": "Anytown",
+                    "state": "CA",
+                    "zip": "90210",
+                    "country": "US"
+                }
+            })
+        };
+        let submit = |orders: Vec<serde_json::Value>| {
+            client
+                .post(format!("{}/orders/batch", base_url))
+                .header("X-Tenant-Id", &tenant)
+                .json(&json!({ "orders": orders }))
+                .send()
+        };
+
+        // A batch larger than the quota is rejected whole
+        let res = submit(vec![order(1); 4]).await.unwrap();
+        assert_eq!(res.status(), 429);
+        assert_eq!(res.headers()["x-quota-remaining"], "3");
+
+        // Only the orders that were created are charged
+        let res = submit(vec![order(1), order(0)]).await.unwrap();
+        assert_eq!(res.status(), 201);
+        assert_eq!(res.headers()["x-quota-remaining"], "2");
+
+        let res = submit(vec![order(1); 3]).await.unwrap();
+        assert_eq!(res.status(), 429);
+
+        let res = submit(vec![order(1); 2]).await.unwrap();
+        assert_eq!(res.status(), 201);
+        assert_eq!(res.headers()["x-quota-remaining"], "0");
+
+        sqlx::query("DELETE FROM tenant_usage WHERE tenant_id = $1")
+            .bind(&tenant)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM tenant_quotas WHERE tenant_id = $1")
+            .bind(&tenant)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    // -----------------------------------------------------------------------
+    // Analytics Deduplication
+    // -----------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn test_duplicate_analytics_job_returns_existing_unless_forced() {
+        let pool = app_pool().await;
+        let job_name = format!("dedup-{}", uuid::Uuid::new_v4().simple());
+        let job = json!({
+            "job_name": job_name,
+            "sources": ["sales"],
+            "date_range": {"start_date": "2025-10-01", "end_date": "2025-12-31"}
+        });
+
+        let client = reqwest::Client::new();
+        let submit = |body: serde_json::Value| {
+            client.post(format!("{}/analytics", base_url())).json(&body).send()
+        };
+        // Each created job submits exactly one task
+        let job_count = || {
+            sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM analytics_jobs WHERE job_name = $1")
+                .bind(&job_name)
+                .fetch_one(&pool)
+        };
+
+        let first = submit(job.clone()).await.unwrap();
+        assert_eq!(first.status(), 201);
+        let first: serde_json::Value = first.json().await.unwrap();
+
+        let second = submit(job.clone()).await.unwrap();
+        assert_eq!(second.status(), 200);
+        let second: serde_json::Value = second.json().await.unwrap();
+        assert_eq!(second["data"]["id"], first["data"]["id"]);
+        assert_eq!(second["data"]["task_uuid"], first["data"]["task_uuid"]);
+        assert_eq!(job_count().await.unwrap(), 1);
+
+        // A different date range is a different job
+        let mut other_range = job.clone();
+        other_range["date_range"]["end_date"] = json!("2025-11-30");
+        assert_eq!(submit(other_range).await.unwrap().status(), 201);
+
+        let mut forced = job.clone();
+        forced["force"] = json!(true);
+        let forced = submit(forced).await.unwrap();
+        assert_eq!(forced.status(), 201);
+        let forced: serde_json::Value = forced.json().await.unwrap();
+        assert_ne!(forced["data"]["id"], first["data"]["id"]);
+        assert_eq!(job_count().await.unwrap(), 3);
+
+        // Later duplicates resolve to the newest run
+        let again: serde_json::Value = submit(job.clone()).await.unwrap().json().await.unwrap();
+        assert_eq!(again["data"]["id"], forced["data"]["id"]);
+
+        // Cancelled jobs are not duplicates
+        sqlx::query("UPDATE analytics_jobs SET status = 'cancelled' WHERE job_name = $1")
+            .bind(&job_name)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(submit(job.clone()).await.unwrap().status(), 201);
+        sqlx::query(
+            "UPDATE analytics_jobs SET status = 'partially_cancelled' \
+             WHERE job_name = $1 AND status <> 'cancelled'",
+        )
+        .bind(&job_name)
+        .execute(&pool)
+        .await
+        .unwrap();
+        assert_eq!(submit(job).await.unwrap().status(), 201);
+        assert_eq!(job_count().await.unwrap(), 5);
+
+        sqlx::query("DELETE FROM analytics_jobs WHERE job_name = $1")
+            .bind(&job_name)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    // -----------------------------------------------------------------------
+    // Order Monitor WebSocket
+    // -----------------------------------------------------------------------
+
+    type MonitorSocket =
+        tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+    async fn monitor_send(socket: &mut MonitorSocket, message: serde_json::Value) {
+        use futures::SinkExt;
+        socket
+            .send(tokio_tungstenite::tungstenite::Message::Text(message.to_string().into()))
+            .await
+            .expect("Failed to send WebSocket message");
+    }
+
+    async fn monitor_recv(socket: &mut MonitorSocket) -> serde_json::Value {
+        use futures::StreamExt;
+        let message = tokio::time::timeout(std::time::Duration::from_secs(10), socket.next())
+            .await
+            .expect("Timed out waiting for a WebSocket message")
+            .expect("WebSocket closed")
+            .expect("WebSocket error");
+        let text = message.into_text().expect("Expected a text message");
+        serde_json::from_str(&text).expect("Expected a JSON message")
+    }
+
+    #[tokio::test]
+    async fn test_order_monitor_pushes_status_updates() {
+        let pool = app_pool().await;
+        let email = format!("monitor-{}@example.com", uuid::Uuid::new_v4().simple());
+        let order_id = seed_pending_order(&pool, &email, 0).await;
+
+        let base_url = spawn_app_with_config(example_axum_app::AppConfig::default()).await;
+        let ws_url = format!("{}/ws/orders", base_url.replacen("http", "ws", 1));
+        let (mut socket, _) = tokio_tungstenite::connect_async(ws_url)
+            .await
+            .expect("Failed to open WebSocket");
+
+        // Subscribing replies with the subscription and the current status
+        let missing_id = i32::MAX;
+        monitor_send(
+            &mut socket,
+            json!({"action": "subscribe", "order_ids": [order_id, missing_id]}),
+        )
+        .await;
+        let subscribed = monitor_recv(&mut socket).await;
+        assert_eq!(subscribed["type"], "subscribed");
+        assert_eq!(subscribed["order_ids"], json!([order_id, missing_id]));
+
+        let initial = monitor_recv(&mut socket).await;
+        assert_eq!(initial["type"], "order_status");
+        assert_eq!(initial["order_id"], order_id);
+        assert_eq!(initial["status"], "pending");
+        let not_found = monitor_recv(&mut socket).await;
+        assert_eq!(not_found["type"], "not_found");
+        assert_eq!(not_found["order_ids"], json!([missing_id]));
+
+        // A status change is pushed on the next poll
+        sqlx::query("UPDATE orders SET status = 'complete', updated_at = NOW() WHERE id = $1")
+            .bind(order_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let update = monitor_recv(&mut socket).await;
+        assert_eq!(update["type"], "order_status");
+        assert_eq!(update["order_id"], order_id);
+        assert_eq!(update["status"], "complete");
+
+        monitor_send(&mut socket, json!({"action": "unsubscribe", "order_ids": [order_id]})).await;
+        let subscribed = monitor_recv(&mut socket).await;
+        assert_eq!(subscribed["order_ids"], json!([]));
+
+        socket.close(None).await.unwrap();
+        sqlx::query("DELETE FROM orders WHERE id = $1")
+            .bind(order_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    // -----------------------------------------------------------------------
+    // Completion Estimates
+    // -----------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn test_estimated_completion_from_historical_durations() {
+        use example_axum_app::eta::{estimated_completion_at, HISTORY_WINDOW};
+        use example_axum_app::orchestration::OrchestrationClient;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let pool = app_pool().await;
+
+        // A full window of completed orders that each took 120s, finished in
+        // the future so they are the most recent history
+        sqlx::query(
+            r#"
+            INSERT INTO orders (customer_email, items, total, status, created_at, updated_at)
+            SELECT 'eta-history@example.com', '[]', 10.00, 'completed',
+                   NOW() + INTERVAL '1 day' - INTERVAL '120 seconds', NOW() + INTERVAL '1 day'
+            FROM generate_series(1, $1)
+            "#,
+        )
+        .bind(HISTORY_WINDOW as i32)
+        .execute(&pool)
+        .await
+        .expect("Failed to seed order history");
+
+        let server = MockServer::start().await;
+        let task_uuid = uuid::Uuid::new_v4();
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/tasks/{task_uuid}")))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "task_uuid": task_uuid,
+                "status": "steps_in_process",
+                "completion_percentage": 25.0
+            })))
+            .mount(&server)
+            .await;
+        let client = OrchestrationClient::new(server.uri());
+
+        let now: chrono::NaiveDateTime = sqlx::query_scalar("SELECT LOCALTIMESTAMP")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let eta = estimated_completion_at(&pool, &client, "orders", "processing", Some(task_uuid))
+            .await
+            .expect("Expected an estimate for a processing order");
+
+        // 75% of the 120s average remains
+        let remaining = (eta - now).num_seconds();
+        assert!(
+            (85..=95).contains(&remaining),
+            "Expected about 90s remaining, got {}s",
+            remaining
+        );
+
+        // Rows that are not processing get no estimate
+        let finished =
+            estimated_completion_at(&pool, &client, "orders", "completed", Some(task_uuid)).await;
+        assert_eq!(finished, None);
+
+        sqlx::query("DELETE FROM orders WHERE customer_email = 'eta-history@example.com'")
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    // -----------------------------------------------------------------------
+    // Email Normalization
+    // -----------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn test_differently_cased_emails_resolve_to_same_customer() {
+        let pool = app_pool().await;
+        let client = reqwest::Client::new();
+        let local = format!("casing-{}", uuid::Uuid::new_v4().simple());
+
+        let mut ids = Vec::new();
+        for email in [format!("{local}@Example.com"), format!(" {}@EXAMPLE.COM ", local.to_uppercase())] {
+            let res = client
+                .post(format!("{}/services/register", base_url()))
+                .json(&json!({
+                    "user_email": email,
+                    "user_name": "Case User",
+                    "plan": "free"
+                }))
+                .send()
+                .await
+                .expect("Failed to send request");
+            assert_eq!(res.status(), 201);
+            let body: serde_json::Value = res.json().await.unwrap();
+            assert_eq!(body["data"]["user_email"], format!("{local}@example.com"));
+            ids.push(body["data"]["id"].as_i64().unwrap() as i32);
+        }
+
+        let stored: Vec<String> =
+            sqlx::query_scalar("SELECT DISTINCT user_email FROM service_requests WHERE id = ANY($1)")
+                .bind(&ids)
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(stored, vec![format!("{local}@example.com")]);
+
+        // Lookups normalize the email too
+        let res = client
+            .get(format!("{}/customers/{}@EXAMPLE.com/workflows", base_url(), local.to_uppercase()))
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(res.status(), 200);
+        let body: serde_json::Value = res.json().await.unwrap();
+        assert_eq!(body["data"].as_array().unwrap().len(), 2);
+    }
+
+    // -----------------------------------------------------------------------
+    // Admin-triggered reconciliation
+    // -----------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn test_admin_reconcile_updates_stale_rows() {
+        use example_axum_app::admin_auth::AdminAuth;
+        use example_axum_app::orchestration::OrchestrationClient;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let pool = app_pool().await;
+        let server = MockServer::start().await;
+
+        let mut rows = Vec::new();
+        for task_status in ["complete", "error"] {
+            let task_uuid = uuid::Uuid::new_v4();
+            // Older than any other row, so these are selected first
+            let id: i32 = sqlx::query_scalar(
+                r#"
+                INSERT INTO orders (customer_email, items, total, status, task_uuid, created_at, updated_at)
+                VALUES ('admin-reconcile@example.com', '[]', 10.00, 'processing', $1,
+                        NOW() - INTERVAL '200 years', NOW() - INTERVAL '200 years')
+                RETURNING id
+                "#,
+            )
+            .bind(task_uuid)
+            .fetch_one(&pool)
+            .await
+            .expect("Failed to seed order");
+
+            Mock::given(method("GET"))
+                .and(path(format!("/v1/tasks/{task_uuid}")))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .set_body_json(json!({"task_uuid": task_uuid, "status": task_status})),
+                )
+                .mount(&server)
+                .await;
+            rows.push(id);
+        }
+
+        let base = spawn_app_with_config(example_axum_app::AppConfig {
+            orchestration: OrchestrationClient::new(server.uri()),
+            admin_auth: AdminAuth::new(Some("admin-secret".to_string())),
+            ..Default::default()
+        })
+        .await;
+
+        let res = reqwest::Client::new()
+            .post(format!(
+                "{base}/admin/reconcile?status=processing&older_than_secs=60&limit=2"
+            ))
+            .bearer_auth("admin-secret")
+            .send()
+            .await
+            .expect("Failed to reconcile");
+        assert_eq!(res.status(), 200);
+        let body: serde_json::Value = res.json().await.unwrap();
+        assert_eq!(body["data"], json!({"checked": 2, "updated": 2, "errors": 0}));
+
+        assert_eq!(order_status(&pool, rows[0]).await.0, "completed");
+        assert_eq!(order_status(&pool, rows[1]).await.0, "failed");
+    }
+
+    // -----------------------------------------------------------------------
+    // Compliance cancellation
+    // -----------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn test_compliance_cancel_reports_partial_cancellation() {
+        use example_axum_app::orchestration::OrchestrationClient;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let pool = app_pool().await;
+        let server = MockServer::start().await;
+        let cs_task_uuid = uuid::Uuid::new_v4();
+        let payments_task_uuid = uuid::Uuid::new_v4();
+
+        let id: i32 = sqlx::query_scalar(
+            r#"
+            INSERT INTO compliance_checks
+                (check_type, namespace, customer_email, status, task_uuid, payments_task_uuid)
+            VALUES ('refund_processing', 'customer_success_rs', 'cancel@example.com',
+                    'processing', $1, $2)
+            RETURNING id
+            "#,
+        )
+        .bind(cs_task_uuid)
+        .bind(payments_task_uuid)
+        .fetch_one(&pool)
+        .await
+        .expect("Failed to seed compliance check");
+
+        Mock::given(method("DELETE"))
+            .and(path(format!("/v1/tasks/{cs_task_uuid}")))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path(format!("/v1/tasks/{payments_task_uuid}")))
+            .respond_with(ResponseTemplate::new(500).set_body_string("payments unavailable"))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path(format!("/v1/tasks/{payments_task_uuid}")))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let base = spawn_app_with_config(example_axum_app::AppConfig {
+            orchestration: OrchestrationClient::new(server.uri()),
+            ..Default::default()
+        })
+        .await;
+        let client = reqwest::Client::new();
+
+        let res = client
+            .post(format!("{base}/compliance/{id}/cancel"))
+            .send()
+            .await
+            .expect("Failed to cancel");
+        assert_eq!(res.status(), 200);
+        let body: serde_json::Value = res.json().await.unwrap();
+        assert_eq!(body["data"]["status"], "partially_cancelled");
+        let tasks = body["data"]["tasks"].as_array().unwrap();
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0]["namespace"], "customer_success_rs");
+        assert_eq!(tasks[0]["cancelled"], true);
+        assert_eq!(tasks[1]["namespace"], "payments_rs");
+        assert_eq!(tasks[1]["cancelled"], false);
+        assert!(tasks[1]["error"].as_str().unwrap().contains("500"));
+
+        let status: String = sqlx::query_scalar("SELECT status FROM compliance_checks WHERE id = $1")
+            .bind(id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(status, "partially_cancelled");
+
+        // Retrying cancels the rest; a finished check can't be cancelled again
+        let res = client
+            .post(format!("{base}/compliance/{id}/cancel"))
+            .send()
+            .await
+            .expect("Failed to cancel");
+        let body: serde_json::Value = res.json().await.unwrap();
+        assert_eq!(body["data"]["status"], "cancelled");
+
+        let res = client
+            .post(format!("{base}/compliance/{id}/cancel"))
+            .send()
+            .await
+            .expect("Failed to cancel");
+        assert_eq!(res.status(), 409);
+        server.verify().await;
+    }
+
+    // -----------------------------------------------------------------------
+    // Cursor pagination
+    // -----------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn test_cursor_pagination_has_no_gaps_or_duplicates() {
+        let pool = app_pool().await;
+        let batch = uuid::Uuid::new_v4().to_string();
+        let seed = |age_secs: i64| {
+            let pool = pool.clone();
+            let batch = batch.clone();
+            async move {
+                sqlx::query_scalar::<_, i32>(
+                    r#"
+                    INSERT INTO orders (customer_email, items, total, status, tags, created_at)
+                    VALUES ('pages@example.com', '[]', 10.00, 'pending', $1,
+                            date_trunc('second', NOW()) - ($2::bigint * INTERVAL '1 second'))
+                    RETURNING id
+                    "#,
+                )
+                .bind(json!({"page_batch": batch}))
+                .bind(age_secs)
+                .fetch_one(&pool)
+                .await
+                .expect("Failed to seed order")
+            }
+        };
+        // Several rows share a created_at, so the id breaks ties
+        let mut seeded = Vec::new();
+        for age_secs in [30, 20, 20, 20, 10, 10, 0] {
+            seeded.push((age_secs, seed(age_secs).await as i64));
+        }
+        seeded.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+        let expected: Vec<i64> = seeded.iter().map(|(_, id)| *id).collect();
+
+        let base_url = spawn_app_with_config(example_axum_app::AppConfig::default()).await;
+        let client = reqwest::Client::new();
+        let mut cursor: Option<String> = None;
+        let mut paged = Vec::new();
+        loop {
+            let mut query = vec![("tag.page_batch", batch.clone()), ("limit", "3".to_string())];
+            if let Some(cursor) = &cursor {
+                query.push(("cursor", cursor.clone()));
+            }
+            let res = client
+                .get(format!("{}/orders", base_url))
+                .query(&query)
+                .send()
+                .await
+                .expect("Failed to send request");
+            assert_eq!(res.status(), 200);
+            let body: serde_json::Value = res.json().await.unwrap();
+            let page = body["data"].as_array().unwrap();
+            assert!(page.len() <= 3);
+            paged.extend(page.iter().map(|o| o["id"].as_i64().unwrap()));
+
+            // A row inserted mid-pagination is newer than the cursor and never shifts later pages
+            if cursor.is_none() {
+                seed(-60).await;
+            }
+            match body["next_cursor"].as_str() {
+                Some(next) => cursor = Some(next.to_string()),
+                None => break,
+            }
+        }
+        assert_eq!(paged, expected);
+
+        // Deprecated offset paging still works
+        let res = client
+            .get(format!("{}/orders", base_url))
+            .query(&[("tag.page_batch", batch.as_str()), ("limit", "2"), ("offset", "1")])
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(res.status(), 200);
+        let body: serde_json::Value = res.json().await.unwrap();
+        assert_eq!(body["data"].as_array().unwrap().len(), 2);
+
+        let res = client
+            .get(format!("{}/orders", base_url))
+            .query(&[("cursor", "not-a-cursor")])
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(res.status(), 422);
+        let body: serde_json::Value = res.json().await.unwrap();
+        assert_eq!(body["error"]["field"], "cursor");
+    }
+
+    // -----------------------------------------------------------------------
+    // Welcome messages
+    // -----------------------------------------------------------------------
+
+    /// The `send_welcome_sequence` result of a registration with `context`,
+    /// with its upstream steps run first. The handlers run on the blocking
+    /// pool, as they do under the worker.
+    async fn welcome_sequence_result(context: serde_json::Value) -> serde_json::Value {
+        use example_axum_app::handlers::microservices;
+        use std::collections::HashMap;
+
+        tokio::task::spawn_blocking(move || {
+            let user = microservices::create_user_account(&context).expect("Handler failed");
+            let mut deps = HashMap::from([("create_user_account".to_string(), user)]);
+            let billing = microservices::setup_billing_profile(&context, &deps).expect("Handler failed");
+            let preferences =
+                microservices::initialize_preferences(&context, &deps).expect("Handler failed");
+            deps.insert("setup_billing_profile".to_string(), billing);
+            deps.insert("initialize_preferences".to_string(), preferences);
+            microservices::send_welcome_sequence(&context, &deps).expect("Handler failed")
+        })
+        .await
+        .expect("Handler panicked")
+    }
+
+    #[tokio::test]
+    async fn test_completed_registration_lists_welcome_messages() {
+        use example_axum_app::step_results::{self, StepKey};
+        use tasker_shared::messaging::StepExecutionResult;
+
+        let pool = app_pool().await;
+        let task_uuid = uuid::Uuid::new_v4();
+        let email = format!("welcome-{}@example.com", uuid::Uuid::new_v4().simple());
+        let context = json!({"email": email, "full_name": "Welcome User", "plan": "enterprise"});
+
+        // Run the registration up to the welcome sequence and record its
+        // result the way the worker callback does
+        let welcome = welcome_sequence_result(context).await;
+        let key = StepKey {
+            task_uuid,
+            step_name: "send_welcome_sequence",
+            namespace: "microservices_rs",
+            handler_name: "microservices_send_welcome_sequence",
+        };
+        let result = StepExecutionResult::success(uuid::Uuid::new_v4(), welcome, 1, None);
+        step_results::record(&pool, key, &result)
+            .await
+            .expect("Failed to record step result");
+
+        let seed = |status: &'static str| {
+            let pool = pool.clone();
+            let email = email.clone();
+            async move {
+                sqlx::query_scalar::<_, i32>(
+                    r#"
+                    INSERT INTO service_requests (service_type, user_email, payload, status, task_uuid)
+                    VALUES ('user_registration', $1, '{}', $2, $3)
+                    RETURNING id
+                    "#,
+                )
+                .bind(email)
+                .bind(status)
+                .bind(task_uuid)
+                .fetch_one(&pool)
+                .await
+                .expect("Failed to seed service request")
+            }
+        };
+        let completed = seed("completed").await;
+        let processing = seed("processing").await;
+
+        let base_url = spawn_app_with_config(example_axum_app::AppConfig::default()).await;
+        let client = reqwest::Client::new();
+        let res = client
+            .get(format!("{}/services/{}/messages", base_url, completed))
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(res.status(), 200);
+        let body: serde_json::Value = res.json().await.unwrap();
+        let messages = body["data"].as_array().unwrap();
+        let channels: Vec<&str> = messages.iter().map(|m| m["channel"].as_str().unwrap()).collect();
+        assert_eq!(channels, vec!["email", "in_app", "sms"]);
+        assert!(messages.iter().all(|m| m["title"] == "Welcome to Enterprise!"));
+        assert_eq!(messages[0]["status"], "sent");
+        assert_eq!(messages[1]["status"], "delivered");
+
+        let res = client
+            .get(format!("{}/services/{}/messages", base_url, processing))
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(res.status(), 425);
+
+        let res = client
+            .get(format!("{}/services/{}/messages", base_url, i32::MAX))
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(res.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_welcome_messages_fall_back_to_the_orchestration_step_result() {
+        use example_axum_app::orchestration::OrchestrationClient;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let pool = app_pool().await;
+        let server = MockServer::start().await;
+        let task_uuid = uuid::Uuid::new_v4();
+        let context = json!({"email": "fallback@example.com", "full_name": "Fallback User", "plan": "pro"});
+        let welcome = welcome_sequence_result(context).await;
+
+        // No local step result: the messages come from the task's step results
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/tasks/{task_uuid}")))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "task_uuid": task_uuid,
+                "status": "complete",
+                "steps": [
+                    {"name": "create_user_account", "current_state": "complete", "results": {}},
+                    {"name": "send_welcome_sequence", "current_state": "complete", "results": welcome}
+                ]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let completed: i32 = sqlx::query_scalar(
+            r#"
+            INSERT INTO service_requests (service_type, user_email, payload, status, task_uuid)
+            VALUES ('user_registration', 'fallback@example.com', '{}', 'completed', $1)
+            RETURNING id
+            "#,
+        )
+        .bind(task_uuid)
+        .fetch_one(&pool)
+        .await
+        .expect("Failed to seed service request");
+
+        let base_url = spawn_app_with_config(example_axum_app::AppConfig {
+            orchestration: OrchestrationClient::new(server.uri()),
+            ..Default::default()
+        })
+        .await;
+        let res = reqwest::Client::new()
+            .get(format!("{}/services/{}/messages", base_url, completed))
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(res.status(), 200);
+        let body: serde_json::Value = res.json().await.unwrap();
+        let channels: Vec<&str> = body["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m["channel"].as_str().unwrap())
+            .collect();
+        assert_eq!(channels, vec!["email", "in_app"]);
+        server.verify().await;
+    }
+
+    // -----------------------------------------------------------------------
+    // Order updates
+    // -----------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn test_patch_order_updates_shipping_address_before_it_is_used() {
+        use example_axum_app::orchestration::OrchestrationClient;
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let pool = app_pool().await;
+        let server = MockServer::start().await;
+        let seed = |status: &'static str, task_uuid: Option<uuid::Uuid>| {
+            let pool = pool.clone();
+            async move {
+                sqlx::query_scalar::<_, i32>(
+                    r#"
+                    INSERT INTO orders (customer_email, items, total, status, task_uuid, shipping_address)
+                    VALUES ('patch@example.com', '[]', 10.00, $1, $2, $3)
+                    RETURNING id
+                    "#,
+                )
+                .bind(status)
+                .bind(task_uuid)
+                .bind(json!({"street": "1 Old Rd", "city": "Oldtown", "state": "CA", "zip": "90210", "country": "US"}))
+                .fetch_one(&pool)
+                .await
+                .expect("Failed to seed order")
+            }
+        };
+        let new_address = json!({
+            "street": "9 New Ave",
+            "city": "Newville",
+            "state": "OR",
+            "zip": "97201",
+            "country": "US"
+        });
+
+        // A freshly submitted task: no step has run yet
+        let fresh_task = uuid::Uuid::new_v4();
+        let fresh = seed("processing", Some(fresh_task)).await;
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/tasks/{fresh_task}")))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "task_uuid": fresh_task, "status": "pending", "total_steps": 6, "completed_steps": 0
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("PATCH"))
+            .and(path(format!("/v1/tasks/{fresh_task}")))
+            .and(body_partial_json(json!({"context": {"shipping_address": {"city": "Newville"}}})))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        // A task that has already priced shipping
+        let shipped_task = uuid::Uuid::new_v4();
+        let shipped = seed("processing", Some(shipped_task)).await;
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/tasks/{shipped_task}")))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "task_uuid": shipped_task, "status": "in_progress", "total_steps": 6, "completed_steps": 2
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("PATCH"))
+            .and(path(format!("/v1/tasks/{shipped_task}")))
+            .and(body_partial_json(json!({"context": {"customer_email": "fixed@example.com"}})))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let base = spawn_app_with_config(example_axum_app::AppConfig {
+            orchestration: OrchestrationClient::new(server.uri()),
+            ..Default::default()
+        })
+        .await;
+        let client = reqwest::Client::new();
+        let patch = |id: i32, body: serde_json::Value| {
+            client.patch(format!("{base}/orders/{id}")).json(&body).send()
+        };
+
+        let res = patch(fresh, json!({"shipping_address": new_address})).await.unwrap();
+        assert_eq!(res.status(), 200);
+        let body: serde_json::Value = res.json().await.unwrap();
+        assert_eq!(body["data"]["shipping_address"], new_address);
+        assert_eq!(body["data"]["customer_email"], "patch@example.com");
+
+        // Shipping was priced with the old address, but the email is not used yet
+        let res = patch(shipped, json!({"shipping_address": new_address})).await.unwrap();
+        assert_eq!(res.status(), 409);
+        let res = patch(shipped, json!({"customer_email": " Fixed@Example.com "})).await.unwrap();
+        assert_eq!(res.status(), 200);
+        let stored: (String, serde_json::Value) =
+            sqlx::query_as("SELECT customer_email, shipping_address FROM orders WHERE id = $1")
+                .bind(shipped)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(stored.0, "fixed@example.com");
+        assert_eq!(stored.1["city"], "Oldtown");
+
+        // An order awaiting resubmission: the saved task request is updated too
+        let pending = seed_pending_order(&pool, "patch@example.com", 0).await;
+        let res = patch(pending, json!({"shipping_address": new_address})).await.unwrap();
+        assert_eq!(res.status(), 200);
+        let task_request: serde_json::Value =
+            sqlx::query_scalar("SELECT task_request FROM orders WHERE id = $1")
+                .bind(pending)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(task_request["context"]["shipping_address"], new_address);
+        assert_eq!(task_request["name"], "ecommerce_order_processing");
+
+        let complete = seed("complete", None).await;
+        let res = patch(complete, json!({"shipping_address": new_address})).await.unwrap();
+        assert_eq!(res.status(), 409);
+
+        let res = patch(fresh, json!({"shipping_address": {"city": "Nowhere"}})).await.unwrap();
+        assert_eq!(res.status(), 422);
+        let res = patch(fresh, json!({})).await.unwrap();
+        assert_eq!(res.status(), 422);
+        let res = patch(i32::MAX, json!({"customer_email": "x@example.com"})).await.unwrap();
+        assert_eq!(res.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_compliance_check_get_returns_both_task_uuids() {
+        let pool = app_pool().await;
+        let cs_task_uuid = uuid::Uuid::new_v4();
+        let payments_task_uuid = uuid::Uuid::new_v4();
+
+        let id: i32 = sqlx::query_scalar(
+            r#"
+            INSERT INTO compliance_checks
+                (check_type, namespace, customer_email, status, task_uuid, payments_task_uuid)
+            VALUES ('refund_processing', 'customer_success_rs', 'both-uuids@example.com',
+                    'completed', $1, $2)
+            RETURNING id
+            "#,
+        )
+        .bind(cs_task_uuid)
+        .bind(payments_task_uuid)
+        .fetch_one(&pool)
+        .await
+        .expect("Failed to seed compliance check");
+
+        let base = spawn_app_with_config(example_axum_app::AppConfig::default()).await;
+        let body: serde_json::Value = reqwest::get(format!("{base}/compliance/{id}"))
+            .await
+            .expect("Failed to get compliance check")
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(body["data"]["task_uuid"], cs_task_uuid.to_string());
+        assert_eq!(body["data"]["payments_task_uuid"], payments_task_uuid.to_string());
+    }
+
+    #[tokio::test]
+    async fn test_compliance_check_get_reports_both_namespace_task_statuses() {
+        use example_axum_app::orchestration::OrchestrationClient;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let pool = app_pool().await;
+        let cs_task_uuid = uuid::Uuid::new_v4();
+        let payments_task_uuid = uuid::Uuid::new_v4();
+        let id: i32 = sqlx::query_scalar(
+            r#"
+            INSERT INTO compliance_checks
+                (check_type, namespace, customer_email, status, task_uuid, payments_task_uuid)
+            VALUES ('refund_processing', 'customer_success_rs', 'both-statuses@example.com',
+                    'processing', $1, $2)
+            RETURNING id
+            "#,
+        )
+        .bind(cs_task_uuid)
+        .bind(payments_task_uuid)
+        .fetch_one(&pool)
+        .await
+        .expect("Failed to seed compliance check");
+
+        // Orchestration after the refund workflow ran in both namespaces
+        let get_check = |cs_status: &'static str, payments_status: &'static str| async move {
+            let server = MockServer::start().await;
+            for (task_uuid, status, step) in [
+                (cs_task_uuid, cs_status, "update_ticket_status"),
+                (payments_task_uuid, payments_status, "notify_customer"),
+            ] {
+                Mock::given(method("GET"))
+                    .and(path(format!("/v1/tasks/{task_uuid}")))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                        "task_uuid": task_uuid,
+                        "status": status,
+                        "completion_percentage": 100.0,
+                        "steps": [{"name": step, "current_state": status, "attempts": 1}]
+                    })))
+                    .expect(1)
+                    .mount(&server)
+                    .await;
+            }
+            let base = spawn_app_with_config(example_axum_app::AppConfig {
+                orchestration: OrchestrationClient::new(server.uri()),
+                ..Default::default()
+            })
+            .await;
+            let res = reqwest::get(format!("{base}/compliance/{id}"))
+                .await
+                .expect("Failed to get compliance check");
+            assert_eq!(res.status(), 200);
+            server.verify().await;
+            res.json::<serde_json::Value>().await.unwrap()
+        };
+
+        let body = get_check("complete", "complete").await;
+        let data = &body["data"];
+        assert_eq!(data["task_status"], "complete");
+        assert_eq!(
+            data["customer_success_task"]["task_uuid"],
+            cs_task_uuid.to_string()
+        );
+        assert_eq!(data["customer_success_task"]["status"], "complete");
+        assert_eq!(
+            data["customer_success_task"]["steps"][0]["name"],
+            "update_ticket_status"
+        );
+        assert_eq!(
+            data["payments_task"]["task_uuid"],
+            payments_task_uuid.to_string()
+        );
+        assert_eq!(data["payments_task"]["status"], "complete");
+
+        // Either namespace failing fails the whole check
+        let body = get_check("complete", "error").await;
+        assert_eq!(body["data"]["task_status"], "error");
+        assert_eq!(body["data"]["customer_success_task"]["status"], "complete");
+        assert_eq!(body["data"]["payments_task"]["status"], "error");
+
+        // Orchestration unreachable: the stored row alone
+        let base = spawn_app_with_config(example_axum_app::AppConfig {
+            orchestration: OrchestrationClient::new("http://127.0.0.1:9"),
+            ..Default::default()
+        })
+        .await;
+        let body: serde_json::Value = reqwest::get(format!("{base}/compliance/{id}"))
+            .await
+            .expect("Failed to get compliance check")
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(body["data"]["id"], id);
+        assert!(body["data"]["task_status"].is_null());
+        assert!(body["data"]["customer_success_task"].is_null());
+        assert!(body["data"]["payments_task"].is_null());
+
+        sqlx::query("DELETE FROM compliance_checks WHERE id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_products_table_is_seeded_with_the_demo_catalog() {
+        let pool = app_pool().await;
+        // Other tests add their own products; compare the seeded ids
+        let products: Vec<_> = example_axum_app::catalog::load_products(&pool)
+            .await
+            .expect("Failed to load products")
+            .into_iter()
+            .filter(|p| p.id <= 5)
+            .collect();
+
+        let mut demo: Vec<_> = example_axum_app::handlers::ecommerce::demo_catalog()
+            .into_values()
+            .collect();
+        demo.sort_by_key(|p| p.id);
+        let summary = |p: &example_axum_app::handlers::ecommerce::Product| {
+            (p.id, p.name.clone(), p.sku.clone(), p.price)
+        };
+        assert_eq!(
+            products.iter().map(summary).collect::<Vec<_>>(),
+            demo.iter().map(summary).collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_inventory_reservation_decrements_product_stock() {
+        use example_axum_app::handlers::ecommerce::reserve_inventory;
+
+        let pool = app_pool().await;
+        let sku = format!("TST-{}", &uuid::Uuid::new_v4().simple().to_string()[..12]);
+        let id: i32 = sqlx::query_scalar(
+            "INSERT INTO products (id, name, sku, price, stock) \
+             VALUES ((SELECT COALESCE(MAX(id), 0) + 1000 FROM products), 'Test Part', $1, 5.00, 5) \
+             RETURNING id",
+        )
+        .bind(&sku)
+        .fetch_one(&pool)
+        .await
+        .expect("Failed to seed product");
+
+        let cart = |quantity: i64| {
+            std::collections::HashMap::from([(
+                "validate_cart".to_string(),
+                json!({
+                    "validated_items": [{
+                        "sku": sku, "name": "Test Part", "quantity": quantity,
+                        "unit_price": 5.0, "line_total": 5.0 * quantity as f64
+                    }],
+                    "subtotal": 5.0 * quantity as f64, "tax_rate": 0.08, "tax": 0.0,
+                    "shipping": 5.99, "total": 0.0, "item_count": quantity,
+                    "validated_at": "2026-01-01T00:00:00Z"
+                }),
+            )])
+        };
+        let stock = || async {
+            sqlx::query_scalar::<_, i32>("SELECT stock FROM products WHERE id = $1")
+                .bind(id)
+                .fetch_one(&pool)
+                .await
+                .unwrap()
+        };
+
+        let result = reserve_inventory(&pool, &cart(2)).await.expect("Reservation failed");
+        assert_eq!(result["updated_products"][0]["product_id"], format!("PROD-{id}"));
+        assert_eq!(result["updated_products"][0]["previous_quantity"], 5);
+        assert_eq!(result["updated_products"][0]["new_quantity"], 3);
+        assert_eq!(stock().await, 3);
+
+        // Over-reserving fails without touching stock
+        let err = reserve_inventory(&pool, &cart(4)).await.unwrap_err();
+        assert!(err.contains("Insufficient stock for Test Part"), "{err}");
+        assert_eq!(stock().await, 3);
+
+        sqlx::query("DELETE FROM products WHERE id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_registration_returns_the_stored_user() {
+        use example_axum_app::handlers::microservices::{
+            create_user_account_in_db, initialize_preferences_in_db, setup_billing_profile_in_db,
+        };
+        use std::collections::HashMap;
+
+        let pool = app_pool().await;
+        let email = format!("dup-{}@example.com", uuid::Uuid::new_v4().simple());
+        let context = json!({"email": email, "full_name": "Dup User", "plan": "pro"});
+
+        // The first three registration steps, as the worker runs them
+        let register = |context: serde_json::Value| {
+            let pool = pool.clone();
+            async move {
+                let user = create_user_account_in_db(&pool, &context)
+                    .await
+                    .expect("Registration failed");
+                let deps = HashMap::from([("create_user_account".to_string(), user.clone())]);
+                let billing = setup_billing_profile_in_db(&pool, &deps)
+                    .await
+                    .expect("Billing setup failed");
+                let preferences = initialize_preferences_in_db(&pool, &context, &deps)
+                    .await
+                    .expect("Preferences setup failed");
+                (user, billing, preferences)
+            }
+        };
+
+        let (first, first_billing, first_prefs) = register(context).await;
+        assert_eq!(first["status"], "created");
+
+        // Same email, different casing and details: the stored account wins,
+        // and the billing profile and preferences are the ones stored with it
+        let again = json!({"email": email.to_uppercase(), "full_name": "Someone Else"});
+        let (second, second_billing, second_prefs) = register(again).await;
+        assert_eq!(second["status"], "already_exists");
+        assert_eq!(second["user_id"], first["user_id"]);
+        assert_eq!(second["name"], "Dup User");
+        assert_eq!(second_billing["billing_id"], first_billing["billing_id"]);
+        assert_eq!(second_prefs["preferences_id"], first_prefs["preferences_id"]);
+
+        let rows: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users WHERE email = $1")
+            .bind(&email)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(rows, 1);
+
+        sqlx::query("DELETE FROM users WHERE email = $1")
+            .bind(&email)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    // -----------------------------------------------------------------------
+    // Health and readiness
+    // -----------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn test_health_and_readiness_report_dependencies() {
+        use example_axum_app::orchestration::OrchestrationClient;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let healthy = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/health"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&healthy)
+            .await;
+        let base = spawn_app_with_config(example_axum_app::AppConfig {
+            orchestration: OrchestrationClient::new(healthy.uri()),
+            ..Default::default()
+        })
+        .await;
+
+        let res = reqwest::get(format!("{base}/health")).await.unwrap();
+        assert_eq!(res.status(), 200);
+
+        let res = reqwest::get(format!("{base}/readiness")).await.unwrap();
+        assert_eq!(res.status(), 200);
+        let body: serde_json::Value = res.json().await.unwrap();
+        assert_eq!(body["status"], "ready");
+        assert_eq!(body["failed"], json!([]));
+        assert_eq!(body["checks"]["database"]["ok"], true);
+
+        // Orchestration down: not ready, and the body says why
+        let down = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/health"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&down)
+            .await;
+        let base = spawn_app_with_config(example_axum_app::AppConfig {
+            orchestration: OrchestrationClient::new(down.uri()),
+            ..Default::default()
+        })
+        .await;
+
+        let res = reqwest::get(format!("{base}/health")).await.unwrap();
+        assert_eq!(res.status(), 200);
+        let res = reqwest::get(format!("{base}/readiness")).await.unwrap();
+        assert_eq!(res.status(), 503);
+        let body: serde_json::Value = res.json().await.unwrap();
+        assert_eq!(body["status"], "not_ready");
+        assert_eq!(body["failed"], json!(["orchestration"]));
+        assert_eq!(body["checks"]["database"]["ok"], true);
+        assert!(body["checks"]["orchestration"]["error"]
+            .as_str()
+            .unwrap()
+            .contains("503"));
+    }
+
+    #[tokio::test]
+    async fn test_order_list_defaults_filters_and_clamps_limit() {
+        let pool = app_pool().await;
+        let batch = uuid::Uuid::new_v4().to_string();
+        sqlx::query(
+            r#"
+            INSERT INTO orders (customer_email, items, total, status, tags)
+            SELECT 'list@example.com', '[]', 10.00,
+                   CASE WHEN n <= 3 THEN 'completed' ELSE 'pending' END, $1
+            FROM generate_series(1, 105) AS n
+            "#,
+        )
+        .bind(json!({"list_batch": batch}))
+        .execute(&pool)
+        .await
+        .expect("Failed to seed orders");
+
+        let base_url = spawn_app_with_config(example_axum_app::AppConfig::default()).await;
+        let client = reqwest::Client::new();
+        let list = |extra: Vec<(&'static str, &'static str)>| {
+            let mut query = vec![("tag.list_batch", batch.clone())];
+            query.extend(extra.into_iter().map(|(k, v)| (k, v.to_string())));
+            let request = client.get(format!("{}/orders", base_url)).query(&query);
+            async move {
+                let res = request.send().await.expect("Failed to send request");
+                assert_eq!(res.status(), 200);
+                res.json::<serde_json::Value>().await.unwrap()
+            }
+        };
+
+        // Default page: 20 rows, newest first, with the total across pages
+        let body = list(vec![]).await;
+        assert_eq!(body["data"].as_array().unwrap().len(), 20);
+        assert_eq!(body["total"], 105);
+        assert!(body["next_cursor"].is_string());
+        let created: Vec<&str> = body["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|o| o["created_at"].as_str().unwrap())
+            .collect();
+        assert!(created.windows(2).all(|pair| pair[0] >= pair[1]));
+
+        // Status filter applies to the rows and the total
+        let body = list(vec![("status", "completed")]).await;
+        let rows = body["data"].as_array().unwrap();
+        assert_eq!(rows.len(), 3);
+        assert!(rows.iter().all(|o| o["status"] == "completed"));
+        assert_eq!(body["total"], 3);
+        assert!(body["next_cursor"].is_null());
+
+        // A limit above the maximum is clamped
+        let body = list(vec![("limit", "500")]).await;
+        assert_eq!(body["data"].as_array().unwrap().len(), 100);
+        assert_eq!(body["total"], 105);
+
+        // Offsets page through the rest
+        let body = list(vec![("limit", "100"), ("offset", "100")]).await;
+        assert_eq!(body["data"].as_array().unwrap().len(), 5);
+    }
+
+    // -----------------------------------------------------------------------
+    // Order cancellation
+    // -----------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn test_order_cancel_cancels_the_task_or_just_the_row() {
+        use example_axum_app::orchestration::OrchestrationClient;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let pool = app_pool().await;
+        let server = MockServer::start().await;
+        let task_uuid = uuid::Uuid::new_v4();
+        let seed = |status: &'static str, task_uuid: Option<uuid::Uuid>| {
+            let pool = pool.clone();
+            async move {
+                sqlx::query_scalar::<_, i32>(
+                    r#"
+                    INSERT INTO orders (customer_email, items, total, status, task_uuid)
+                    VALUES ('cancel@example.com', '[]', 10.00, $1, $2)
+                    RETURNING id
+                    "#,
+                )
+                .bind(status)
+                .bind(task_uuid)
+                .fetch_one(&pool)
+                .await
+                .expect("Failed to seed order")
+            }
+        };
+        let running = seed("processing", Some(task_uuid)).await;
+        let unsubmitted = seed("pending", None).await;
+        let finished = seed("completed", Some(uuid::Uuid::new_v4())).await;
+
+        Mock::given(method("DELETE"))
+            .and(path(format!("/v1/tasks/{task_uuid}")))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let base = spawn_app_with_config(example_axum_app::AppConfig {
+            orchestration: OrchestrationClient::new(server.uri()),
+            ..Default::default()
+        })
+        .await;
+        let client = reqwest::Client::new();
+        let cancel = |id: i32| client.delete(format!("{base}/orders/{id}")).send();
+
+        // A running order cancels its task, then the row
+        let res = cancel(running).await.expect("Failed to cancel");
+        assert_eq!(res.status(), 200);
+        let body: serde_json::Value = res.json().await.unwrap();
+        assert_eq!(body["data"]["status"], "cancelled");
+
+        // An order without a task is only cancelled locally
+        let res = cancel(unsubmitted).await.expect("Failed to cancel");
+        assert_eq!(res.status(), 200);
+        let status: String = sqlx::query_scalar("SELECT status FROM orders WHERE id = $1")
+            .bind(unsubmitted)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(status, "cancelled");
+
+        // Finished (or already cancelled) orders are a 409; unknown ones a 404
+        assert_eq!(cancel(finished).await.unwrap().status(), 409);
+        assert_eq!(cancel(running).await.unwrap().status(), 409);
+        assert_eq!(cancel(i32::MAX).await.unwrap().status(), 404);
+        server.verify().await;
+    }
+
+    // -----------------------------------------------------------------------
+    // Order retry
+    // -----------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn test_order_retry_only_retries_failed_tasks() {
+        use example_axum_app::orchestration::OrchestrationClient;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let pool = app_pool().await;
+        let server = MockServer::start().await;
+        let seed = |status: &'static str, task_status: Option<&'static str>| {
+            let pool = pool.clone();
+            let server = &server;
+            async move {
+                let task_uuid = task_status.map(|_| uuid::Uuid::new_v4());
+                if let (Some(task_uuid), Some(task_status)) = (task_uuid, task_status) {
+                    Mock::given(method("GET"))
+                        .and(path(format!("/v1/tasks/{task_uuid}")))
+                        .respond_with(ResponseTemplate::new(200).set_body_json(
+                            json!({"task_uuid": task_uuid, "status": task_status, "steps": []}),
+                        ))
+                        .mount(server)
+                        .await;
+                }
+                let id = sqlx::query_scalar::<_, i32>(
+                    r#"
+                    INSERT INTO orders (customer_email, items, total, status, status_reason, task_uuid)
+                    VALUES ('retry@example.com', '[]', 10.00, $1, 'payment declined', $2)
+                    RETURNING id
+                    "#,
+                )
+                .bind(status)
+                .bind(task_uuid)
+                .fetch_one(&pool)
+                .await
+                .expect("Failed to seed order");
+                (id, task_uuid)
+            }
+        };
+        let (failed, failed_task) = seed("failed", Some("error")).await;
+        let (complete, _) = seed("completed", Some("complete")).await;
+        let (running, _) = seed("processing", Some("steps_in_process")).await;
+        let (unsubmitted, _) = seed("pending", None).await;
+        let (cancelled, cancelled_task) = seed("cancelled", Some("error")).await;
+
+        for task_uuid in [failed_task, cancelled_task] {
+            Mock::given(method("POST"))
+                .and(path(format!("/v1/tasks/{}/retry", task_uuid.unwrap())))
+                .respond_with(ResponseTemplate::new(200))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let base = spawn_app_with_config(example_axum_app::AppConfig {
+            orchestration: OrchestrationClient::new(server.uri()),
+            ..Default::default()
+        })
+        .await;
+        let client = reqwest::Client::new();
+        let retry = |id: i32| client.post(format!("{base}/orders/{id}/retry")).send();
+
+        // A complete or still running task is a 409, and the order is unchanged
+        for id in [complete, running] {
+            let res = retry(id).await.expect("Failed to retry");
+            assert_eq!(res.status(), 409);
+            let body: serde_json::Value = res.json().await.unwrap();
+            assert_eq!(body["error"]["code"], "conflict");
+        }
+        let status: String = sqlx::query_scalar("SELECT status FROM orders WHERE id = $1")
+            .bind(complete)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(status, "completed");
+
+        // A failed task is retried and the order is processing again
+        let res = retry(failed).await.expect("Failed to retry");
+        assert_eq!(res.status(), 200);
+        let body: serde_json::Value = res.json().await.unwrap();
+        assert_eq!(body["data"]["status"], "processing");
+        assert_eq!(body["data"]["status_reason"], serde_json::Value::Null);
+
+        // An order that is no longer failed or processing is not moved back
+        let res = retry(cancelled).await.expect("Failed to retry");
+        assert_eq!(res.status(), 409);
+        let status: String = sqlx::query_scalar("SELECT status FROM orders WHERE id = $1")
+            .bind(cancelled)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(status, "cancelled");
+
+        // Orders without a task, and unknown orders, are a 404
+        assert_eq!(retry(unsubmitted).await.unwrap().status(), 404);
+        assert_eq!(retry(i32::MAX).await.unwrap().status(), 404);
+        server.verify().await;
+    }
+
+    /// Seed a running and an unsubmitted row with `insert` (binding status and
+    /// task UUID), then check `DELETE {base}/{route}/{id}` cancels the running
+    /// row's task and the row, and only the row when there is no task.
+    async fn assert_cancel_route(route: &str, insert: &'static str) {
+        use example_axum_app::orchestration::OrchestrationClient;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let pool = app_pool().await;
+        let server = MockServer::start().await;
+        let task_uuid = uuid::Uuid::new_v4();
+        let seed = |status: &'static str, task_uuid: Option<uuid::Uuid>| {
+            let pool = pool.clone();
+            async move {
+                sqlx::query_scalar::<_, i32>(insert)
+                    .bind(status)
+                    .bind(task_uuid)
+                    .fetch_one(&pool)
+                    .await
+                    .expect("Failed to seed row")
+            }
+        };
+        let running = seed("processing", Some(task_uuid)).await;
+        let unsubmitted = seed("pending", None).await;
+
+        Mock::given(method("DELETE"))
+            .and(path(format!("/v1/tasks/{task_uuid}")))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let base = spawn_app_with_config(example_axum_app::AppConfig {
+            orchestration: OrchestrationClient::new(server.uri()),
+            ..Default::default()
+        })
+        .await;
+        let client = reqwest::Client::new();
+        let cancel = |id: i32| client.delete(format!("{base}/{route}/{id}")).send();
+
+        // A running row cancels its task, then the row
+        let res = cancel(running).await.expect("Failed to cancel");
+        assert_eq!(res.status(), 200, "{route}");
+        let body: serde_json::Value = res.json().await.unwrap();
+        assert_eq!(body["data"]["status"], "cancelled", "{route}");
+
+        // A row without a task is only cancelled locally
+        let res = cancel(unsubmitted).await.expect("Failed to cancel");
+        assert_eq!(res.status(), 200, "{route}");
+        let body: serde_json::Value = res.json().await.unwrap();
+        assert_eq!(body["data"]["id"], unsubmitted, "{route}");
+        assert_eq!(body["data"]["status"], "cancelled", "{route}");
+
+        // Cancelled rows are a 409; unknown ones a 404
+        assert_eq!(cancel(running).await.unwrap().status(), 409, "{route}");
+        assert_eq!(cancel(i32::MAX).await.unwrap().status(), 404, "{route}");
+        server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn test_analytics_job_cancel_cancels_the_task_or_just_the_row() {
+        assert_cancel_route(
+            "analytics",
+            "INSERT INTO analytics_jobs (job_name, status, task_uuid) \
+             VALUES ('cancel_test', $1, $2) RETURNING id",
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_service_request_cancel_cancels_the_task_or_just_the_row() {
+        assert_cancel_route(
+            "services",
+            "INSERT INTO service_requests (service_type, user_email, status, task_uuid) \
+             VALUES ('user_registration', 'cancel@example.com', $1, $2) RETURNING id",
+        )
+        .await;
+    }
+
+    // -----------------------------------------------------------------------
+    // Task status proxy
+    // -----------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn test_task_status_is_proxied_for_each_domain_row() {
+        use example_axum_app::orchestration::OrchestrationClient;
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let pool = app_pool().await;
+        let server = MockServer::start().await;
+        let task_uuid = uuid::Uuid::new_v4();
+        let seed = |sql: &'static str, task_uuid: Option<uuid::Uuid>| {
+            let pool = pool.clone();
+            async move {
+                sqlx::query_scalar::<_, i32>(sql)
+                    .bind(task_uuid)
+                    .fetch_one(&pool)
+                    .await
+                    .expect("Failed to seed row")
+            }
+        };
+        let order = seed(
+            "INSERT INTO orders (customer_email, status, task_uuid) \
+             VALUES ('task@example.com', 'processing', $1) RETURNING id",
+            Some(task_uuid),
+        )
+        .await;
+        let unsubmitted = seed(
+            "INSERT INTO orders (customer_email, status, task_uuid) \
+             VALUES ('task@example.com', 'pending', $1) RETURNING id",
+            None,
+        )
+        .await;
+        let job = seed(
+            "INSERT INTO analytics_jobs (job_name, status, task_uuid) \
+             VALUES ('task_status', 'processing', $1) RETURNING id",
+            Some(task_uuid),
+        )
+        .await;
+        let registration = seed(
+            "INSERT INTO service_requests (service_type, user_email, status, task_uuid) \
+             VALUES ('user_registration', 'task@example.com', 'processing', $1) RETURNING id",
+            Some(task_uuid),
+        )
+        .await;
+
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/tasks/{task_uuid}")))
+            .and(header("X-API-Key", "proxy-key"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "task_uuid": task_uuid,
+                "status": "steps_in_process",
+                "completion_percentage": 40.0,
+                "context": {"payment_token": "tok_secret"},
+                "steps": [
+                    {"name": "validate_cart", "current_state": "complete", "attempts": 1, "results": {}},
+                    {"name": "process_payment", "current_state": "in_progress", "attempts": 2}
+                ]
+            })))
+            .expect(3)
+            .mount(&server)
+            .await;
+
+        let base = spawn_app_with_config(example_axum_app::AppConfig {
+            orchestration: OrchestrationClient::new(server.uri()).with_api_key("proxy-key"),
+            ..Default::default()
+        })
+        .await;
+        let client = reqwest::Client::new();
+
+        for route in [
+            format!("orders/{order}"),
+            format!("analytics/{job}"),
+            format!("services/{registration}"),
+        ] {
+            let res = client
+                .get(format!("{base}/{route}/task"))
+                .send()
+                .await
+                .expect("Failed to fetch task status");
+            assert_eq!(res.status(), 200, "{route}");
+            let body: serde_json::Value = res.json().await.unwrap();
+            assert_eq!(
+                body["data"],
+                json!({
+                    "task_uuid": task_uuid,
+                    "status": "steps_in_process",
+                    "completion_percentage": 40.0,
+                    "steps": [
+                        {"name": "validate_cart", "current_state": "complete", "attempts": 1},
+                        {"name": "process_payment", "current_state": "in_progress", "attempts": 2}
+                    ]
+                })
+            );
+        }
+
+        // No task yet, or no such order
+        for id in [unsubmitted, i32::MAX] {
+            let res = client
+                .get(format!("{base}/orders/{id}/task"))
+                .send()
+                .await
+                .expect("Failed to fetch task status");
+            assert_eq!(res.status(), 404);
+        }
+        server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn test_analytics_job_includes_live_task_steps() {
+        use example_axum_app::orchestration::OrchestrationClient;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let pool = app_pool().await;
+        let server = MockServer::start().await;
+        let task_uuid = uuid::Uuid::new_v4();
+        let job: i32 = sqlx::query_scalar(
+            "INSERT INTO analytics_jobs (job_name, status, task_uuid) \
+             VALUES ('live_steps', 'processing', $1) RETURNING id",
+        )
+        .bind(task_uuid)
+        .fetch_one(&pool)
+        .await
+        .expect("Failed to seed analytics job");
+
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/tasks/{task_uuid}")))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "task_uuid": task_uuid,
+                "status": "steps_in_process",
+                "completion_percentage": 25.0,
+                "steps": [
+                    {"name": "extract_sales_data", "current_state": "complete", "attempts": 1},
+                    {"name": "extract_inventory_data", "current_state": "complete", "attempts": 1},
+                    {"name": "extract_customer_data", "current_state": "in_progress", "attempts": 1}
+                ]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let get_job = |orchestration: OrchestrationClient| async move {
+            let base = spawn_app_with_config(example_axum_app::AppConfig {
+                orchestration,
+                ..Default::default()
+            })
+            .await;
+            let res = reqwest::Client::new()
+                .get(format!("{base}/analytics/{job}"))
+                .send()
+                .await
+                .expect("Failed to fetch analytics job");
+            assert_eq!(res.status(), 200);
+            res.json::<serde_json::Value>().await.unwrap()
+        };
+
+        // One task fetch serves both the steps and the estimate
+        let body = get_job(OrchestrationClient::new(server.uri())).await;
+        assert_eq!(body["data"]["status"], "processing");
+        let task = &body["data"]["task"];
+        assert_eq!(task["status"], "steps_in_process");
+        assert_eq!(task["completion_percentage"], 25.0);
+        let complete: Vec<&str> = task["steps"]
+            .as_array()
+            .expect("Expected steps array")
+            .iter()
+            .filter(|step| step["current_state"] == "complete")
+            .map(|step| step["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(complete, ["extract_sales_data", "extract_inventory_data"]);
+        server.verify().await;
+
+        // Orchestration unreachable: the stored row alone
+        let body = get_job(OrchestrationClient::new("http://127.0.0.1:9")).await;
+        assert_eq!(body["data"]["id"], job);
+        assert!(body["data"]["task"].is_null());
+        assert!(body["data"]["estimated_completion_at"].is_null());
+
+        sqlx::query("DELETE FROM analytics_jobs WHERE id = $1")
+            .bind(job)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    // -----------------------------------------------------------------------
+    // Task progress events
+    // -----------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn test_order_events_stream_progress_until_complete() {
+        use example_axum_app::orchestration::OrchestrationClient;
+        use example_axum_app::task_events::TaskEventsConfig;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let pool = app_pool().await;
+        let server = MockServer::start().await;
+        let task_uuid = uuid::Uuid::new_v4();
+        let order: i32 = sqlx::query_scalar(
+            "INSERT INTO orders (customer_email, status, task_uuid) \
+             VALUES ('events@example.com', 'processing', $1) RETURNING id",
+        )
+        .bind(task_uuid)
+        .fetch_one(&pool)
+        .await
+        .expect("Failed to seed order");
+
+        // Two unchanged polls at 50%, then the task completes
+        let task = |status: &str, percentage: f64| {
+            ResponseTemplate::new(200).set_body_json(json!({
+                "task_uuid": task_uuid,
+                "status": status,
+                "completion_percentage": percentage
+            }))
+        };
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/tasks/{task_uuid}")))
+            .respond_with(task("steps_in_process", 50.0))
+            .up_to_n_times(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/tasks/{task_uuid}")))
+            .respond_with(task("complete", 100.0))
+            .mount(&server)
+            .await;
+
+        let base = spawn_app_with_config(example_axum_app::AppConfig {
+            orchestration: OrchestrationClient::new(server.uri()),
+            task_events: TaskEventsConfig {
+                poll_interval: std::time::Duration::from_millis(10),
+            },
+            ..Default::default()
+        })
+        .await;
+
+        let res = reqwest::Client::new()
+            .get(format!("{base}/orders/{order}/events"))
+            .timeout(std::time::Duration::from_secs(10))
+            .send()
+            .await
+            .expect("Failed to subscribe");
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.headers()["content-type"], "text/event-stream");
+
+        // The stream closes itself after the terminal event
+        let body = res.text().await.expect("Stream did not close");
+        let events: Vec<serde_json::Value> = body
+            .lines()
+            .filter_map(|line| line.strip_prefix("data: "))
+            .map(|data| serde_json::from_str(data).unwrap())
+            .collect();
+        assert_eq!(events.len(), 2, "{body}");
+        assert_eq!(events[0]["status"], "steps_in_process");
+        assert_eq!(events[1]["status"], "complete");
+        assert_eq!(events[1]["completion_percentage"], 100.0);
+        assert!(body.contains("event: progress"));
+
+        let res = reqwest::Client::new()
+            .get(format!("{base}/orders/{}/events", i32::MAX))
+            .send()
+            .await
+            .expect("Failed to subscribe");
+        assert_eq!(res.status(), 404);
+    }
+
+    // -----------------------------------------------------------------------
+    // Failed task submissions
+    // -----------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn test_failed_submission_marks_the_row_failed() {
+        use example_axum_app::orchestration::OrchestrationClient;
+
+        let pool = app_pool().await;
+        let base = spawn_app_with_config(example_axum_app::AppConfig {
+            orchestration: OrchestrationClient::new("http://127.0.0.1:9"),
+            ..Default::default()
+        })
+        .await;
+        let client = reqwest::Client::new();
+        let email = format!("{}@unreachable.example.com", uuid::Uuid::new_v4());
+
+        let creates = [
+            (
+                "orders",
+                "orders",
+                json!({
+                    "customer_email": email,
+                    "cart_items": [
+                        {"sku": "WGT-A-001", "name": "Widget", "quantity": 1, "unit_price": 29.99}
+                    ],
+                    "payment_token": "tok_test_success",
+                    "shipping_address": {
+                        "street": "1 Down St", "city": "Portland", "state": "OR",
+                        "zip": "97201", "country": "US"
+                    }
+                }),
+            ),
+            (
+                "analytics",
+                "analytics_jobs",
+                json!({"job_name": format!("unreachable-{email}"), "sources": ["sales"]}),
+            ),
+            (
+                "services/register",
+                "service_requests",
+                json!({"user_email": email, "user_name": "Down"}),
+            ),
+            (
+                "compliance/refund",
+                "compliance_checks",
+                json!({
+                    "check_type": "refund_processing",
+                    "namespace": "customer_success_rs",
+                    "customer_email": email,
+                    "order_id": "ORD-DOWN",
+                    "refund_amount": 10.00,
+                    "reason": "orchestration down"
+                }),
+            ),
+        ];
+
+        for (route, table, body) in creates {
+            let res = client
+                .post(format!("{base}/{route}"))
+                .json(&body)
+                .send()
+                .await
+                .expect("Failed to send request");
+            assert_eq!(res.status(), 503, "{route}");
+            let body: serde_json::Value = res.json().await.unwrap();
+            assert_eq!(body["error"]["code"], "task_submission_failed");
+            let id = body["error"]["id"].as_i64().expect("Expected the row id") as i32;
+
+            let (status, reason, task_uuid): (String, Option<String>, Option<uuid::Uuid>) =
+                sqlx::query_as(&format!(
+                    "SELECT status, status_reason, task_uuid FROM {table} WHERE id = $1"
+                ))
+                .bind(id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+            assert_eq!(status, "failed", "{table}");
+            assert!(reason.unwrap().starts_with("Task submission failed"));
+            assert!(task_uuid.is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_client_request_id_reaches_the_order_response_and_task_context() {
+        use example_axum_app::orchestration::OrchestrationClient;
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/tasks"))
+            .and(body_partial_json(json!({"context": {"request_id": "req-order-42"}})))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                "task_uuid": uuid::Uuid::new_v4(), "total_steps": 5
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let base = spawn_app_with_config(example_axum_app::AppConfig {
+            orchestration: OrchestrationClient::new(server.uri()),
+            ..Default::default()
+        })
+        .await;
+
+        let res = reqwest::Client::new()
+            .post(format!("{base}/orders"))
+            .header("X-Request-Id", "req-order-42")
+            .json(&json!({
+                "customer_email": "request-id@example.com",
+                "cart_items": [
+                    {"sku": "1", "name": "Widget A", "quantity": 1, "unit_price": 29.99}
+                ],
+                "payment_token": "tok_test_success",
+                "shipping_address": {
+                    "street": "1 Trace St",
+                    "city": "Anytown",
+                    "state": "CA",
+                    "zip": "90210",
+                    "country": "US"
+                }
+            }))
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(res.status(), 201);
+        assert_eq!(res.headers()["x-request-id"], "req-order-42");
+        let body: serde_json::Value = res.json().await.unwrap();
+        assert_eq!(body["data"]["request_id"], "req-order-42");
+        server.verify().await;
+
+        // Without the header, an id is generated and still echoed
+        let res = reqwest::Client::new()
+            .get(format!("{base}/health"))
+            .send()
+            .await
+            .expect("Failed to send request");
+        let generated = res.headers()["x-request-id"].to_str().unwrap();
+        assert!(uuid::Uuid::parse_str(generated).is_ok(), "{generated}");
+    }
+
+    #[tokio::test]
+    async fn test_order_coupon_code_reaches_the_task_context() {
+        use example_axum_app::orchestration::OrchestrationClient;
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        // The seeded coupons are the ones handlers use without a database
+        let mut seeded = example_axum_app::catalog::load_coupons(&app_pool().await)
+            .await
+            .expect("Failed to load coupons");
+        seeded.retain(|c| ["SAVE10", "FIVEOFF", "SPRING2020"].contains(&c.code.as_str()));
+        let demo = example_axum_app::handlers::ecommerce::demo_coupons();
+        assert_eq!(seeded.len(), demo.len());
+        for coupon in &seeded {
+            assert_eq!(Some(coupon), demo.get(&coupon.code));
+        }
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/tasks"))
+            .and(body_partial_json(json!({"context": {"coupon_code": "SAVE10"}})))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                "task_uuid": uuid::Uuid::new_v4(), "total_steps": 6
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let base = spawn_app_with_config(example_axum_app::AppConfig {
+            orchestration: OrchestrationClient::new(server.uri()),
+            ..Default::default()
+        })
+        .await;
+        let mut order = order_with_sku("WGT-B-002");
+        order["coupon_code"] = json!("SAVE10");
+        let res = reqwest::Client::new()
+            .post(format!("{base}/orders"))
+            .json(&order)
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(res.status(), 201);
+        server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn test_order_currency_is_stored_and_reaches_the_task_context() {
+        use example_axum_app::orchestration::OrchestrationClient;
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        // 29.99 + 9.99 = 39.98, rounded to the yen
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/tasks"))
+            .and(body_partial_json(json!({
+                "context": {"currency": "JPY", "payment_amount": 40.0}
+            })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                "task_uuid": uuid::Uuid::new_v4(), "total_steps": 6
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let base = spawn_app_with_config(example_axum_app::AppConfig {
+            orchestration: OrchestrationClient::new(server.uri()),
+            ..Default::default()
+        })
+        .await;
+        let mut order = order_with_sku("WGT-B-002");
+        order["currency"] = json!("jpy");
+        let res = reqwest::Client::new()
+            .post(format!("{base}/orders"))
+            .json(&order)
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(res.status(), 201);
+        server.verify().await;
+
+        let body: serde_json::Value = res.json().await.unwrap();
+        let id = body["data"]["id"].as_i64().unwrap();
+        let fetched: serde_json::Value = reqwest::get(format!("{base}/orders/{id}"))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(fetched["data"]["currency"], "JPY");
+
+        let (total,): (f64,) = sqlx::query_as("SELECT total::float8 FROM orders WHERE id = $1")
+            .bind(id as i32)
+            .fetch_one(&app_pool().await)
+            .await
+            .unwrap();
+        assert_eq!(total, 40.0);
+    }
+
+    #[tokio::test]
+    async fn test_refund_submission_failure_cancels_the_other_task() {
+        use example_axum_app::orchestration::OrchestrationClient;
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let pool = app_pool().await;
+        let server = MockServer::start().await;
+        let cs_task_uuid = uuid::Uuid::new_v4();
+
+        Mock::given(method("POST"))
+            .and(path("/v1/tasks"))
+            .and(body_partial_json(json!({"namespace": "customer_success_rs"})))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                "task_uuid": cs_task_uuid, "total_steps": 5
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v1/tasks"))
+            .and(body_partial_json(json!({"namespace": "payments_rs"})))
+            .respond_with(ResponseTemplate::new(400).set_body_string("unknown template"))
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path(format!("/v1/tasks/{cs_task_uuid}")))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let base = spawn_app_with_config(example_axum_app::AppConfig {
+            orchestration: OrchestrationClient::new(server.uri()),
+            ..Default::default()
+        })
+        .await;
+
+        let res = reqwest::Client::new()
+            .post(format!("{base}/compliance/refund"))
+            .json(&json!({
+                "check_type": "refund_processing",
+                "namespace": "customer_success_rs",
+                "customer_email": "half@example.com",
+                "order_id": "ORD-HALF",
+                "refund_amount": 10.00,
+                "reason": "payments rejects the task"
+            }))
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(res.status(), 502);
+        let body: serde_json::Value = res.json().await.unwrap();
+        assert!(body["error"]["message"].as_str().unwrap().contains("400"));
+        let id = body["error"]["id"].as_i64().unwrap() as i32;
+
+        let status: String = sqlx::query_scalar("SELECT status FROM compliance_checks WHERE id = $1")
+            .bind(id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(status, "failed");
+        server.verify().await;
+    }
+
+    // -----------------------------------------------------------------------
+    // Idempotency keys
+    // -----------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn test_repeated_idempotency_key_returns_the_original_order() {
+        use example_axum_app::orchestration::OrchestrationClient;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let pool = app_pool().await;
+        let server = MockServer::start().await;
+        let task_uuid = uuid::Uuid::new_v4();
+        Mock::given(method("POST"))
+            .and(path("/v1/tasks"))
+            .respond_with(
+                ResponseTemplate::new(201).set_body_json(json!({"task_uuid": task_uuid})),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let base = spawn_app_with_config(example_axum_app::AppConfig {
+            orchestration: OrchestrationClient::new(server.uri()),
+            ..Default::default()
+        })
+        .await;
+        let client = reqwest::Client::new();
+        let key = uuid::Uuid::new_v4().to_string();
+        let create = || {
+            client
+                .post(format!("{base}/orders"))
+                .header("Idempotency-Key", &key)
+                .json(&order_with_sku("WGT-A-001"))
+                .send()
+        };
+
+        let res = create().await.expect("Failed to create order");
+        assert_eq!(res.status(), 201);
+        let first: serde_json::Value = res.json().await.unwrap();
+
+        // The retry returns the same order without submitting another task
+        let res = create().await.expect("Failed to retry order");
+        assert_eq!(res.status(), 200);
+        let second: serde_json::Value = res.json().await.unwrap();
+        assert_eq!(second["data"]["id"], first["data"]["id"]);
+        assert_eq!(second["data"]["task_uuid"], task_uuid.to_string());
+        assert_eq!(second["data"]["task_uuid"], first["data"]["task_uuid"]);
+
+        let rows: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM orders WHERE idempotency_key = $1")
+            .bind(&key)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(rows, 1);
+        server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn test_db_connect_applies_pool_settings_from_env() {
+        dotenvy::dotenv().ok();
+        std::env::set_var("APP_DB_MAX_CONNECTIONS", "3");
+        std::env::set_var("APP_DB_ACQUIRE_TIMEOUT", "7");
+        std::env::set_var("APP_DB_IDLE_TIMEOUT", "0");
+        let pool = example_axum_app::db::connect().await;
+        for var in [
+            "APP_DB_MAX_CONNECTIONS",
+            "APP_DB_ACQUIRE_TIMEOUT",
+            "APP_DB_IDLE_TIMEOUT",
+        ] {
+            std::env::remove_var(var);
+        }
+
+        let pool = pool.expect("Failed to connect with custom pool settings");
+        let options = pool.options();
+        assert_eq!(options.get_max_connections(), 3);
+        assert_eq!(
+            options.get_acquire_timeout(),
+            std::time::Duration::from_secs(7)
+        );
+        assert_eq!(options.get_idle_timeout(), None);
+
+        // Migrations ran, so the domain tables are there
+        let orders: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM orders")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert!(orders >= 0);
+
+        std::env::set_var("APP_DB_MAX_CONNECTIONS", "none");
+        let err = example_axum_app::db::connect().await.unwrap_err();
+        std::env::remove_var("APP_DB_MAX_CONNECTIONS");
+        assert!(err.to_string().contains("APP_DB_MAX_CONNECTIONS"), "{err}");
+    }
+
+    #[test]
+    fn test_invalid_background_job_settings_are_rejected() {
+        use example_axum_app::archiver::ArchiverConfig;
+        use example_axum_app::orchestration::OrchestrationClient;
+        use example_axum_app::reconciler::ReconcilerConfig;
+        use example_axum_app::sweeper::SweeperConfig;
+
+        std::env::set_var("SWEEPER_INTERVAL_SECS", "1m");
+        let err = SweeperConfig::from_env().unwrap_err();
+        std::env::remove_var("SWEEPER_INTERVAL_SECS");
+        assert!(err.contains("SWEEPER_INTERVAL_SECS '1m'"), "{err}");
+
+        std::env::set_var("ARCHIVE_RETENTION_SECS", "-1");
+        let err = ArchiverConfig::from_env().unwrap_err();
+        std::env::remove_var("ARCHIVE_RETENTION_SECS");
+        assert!(err.contains("ARCHIVE_RETENTION_SECS"), "{err}");
+
+        std::env::set_var("RECONCILER_CONCURRENCY", "0");
+        let err = ReconcilerConfig::from_env().unwrap_err();
+        std::env::remove_var("RECONCILER_CONCURRENCY");
+        assert!(err.contains("RECONCILER_CONCURRENCY '0'"), "{err}");
+
+        std::env::set_var("ORCHESTRATION_READ_TIMEOUT_SECS", "0");
+        let err = OrchestrationClient::from_env().unwrap_err();
+        std::env::remove_var("ORCHESTRATION_READ_TIMEOUT_SECS");
+        assert!(err.contains("ORCHESTRATION_READ_TIMEOUT_SECS '0'"), "{err}");
+
+        // A zero interval still disables a job
+        std::env::set_var("SWEEPER_INTERVAL_SECS", "0");
+        let config = SweeperConfig::from_env();
+        std::env::remove_var("SWEEPER_INTERVAL_SECS");
+        assert!(config.unwrap().interval.is_zero());
+    }
 }
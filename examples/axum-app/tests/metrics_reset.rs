@@ -0,0 +1,42 @@
+//! Metrics reset tests: `POST /admin/metrics/reset` (test-util feature only).
+//!
+//! Kept in its own test binary because resetting the process-wide registry
+//! would race the delta-based assertions in `http_metrics`.
+//!
+//! Run: cargo test --features test-util --test metrics_reset
+
+#![cfg(feature = "test-util")]
+
+mod common;
+
+use example_axum_app::metrics;
+
+use common::spawn_app;
+
+#[tokio::test]
+async fn reset_clears_counters() {
+    let base_url = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    let labels = [("table", "orders"), ("outcome", "failed")];
+    metrics::registry().increment_counter("domain_rows_swept_total", &labels);
+    metrics::registry().increment_counter("domain_rows_swept_total", &labels);
+    assert_eq!(
+        metrics::registry().counter_value("domain_rows_swept_total", &labels),
+        2
+    );
+
+    let res = client
+        .post(format!("{}/admin/metrics/reset", base_url))
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert_eq!(res.status(), 204);
+
+    assert_eq!(
+        metrics::registry().counter_value("domain_rows_swept_total", &labels),
+        0
+    );
+    let rendered = metrics::registry().render();
+    assert!(!rendered.contains("domain_rows_swept_total"), "{rendered}");
+}
@@ -0,0 +1,416 @@
+//! Orchestration client tests against a mock orchestration server.
+//!
+//! No database, worker, or orchestration services are needed.
+//!
+//! Run: cargo test --test orchestration
+
+use std::time::{Duration, Instant};
+
+use serde_json::json;
+use wiremock::matchers::{header, method, path, path_regex};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use example_axum_app::circuit_breaker::CircuitState;
+use example_axum_app::metrics;
+use example_axum_app::orchestration::{OrchestrationClient, OrchestrationError};
+
+const TASK_UUID: &str = "0191e0a4-7b3c-7d2e-9f10-123456789abc";
+
+// ---------------------------------------------------------------------------
+// Timeouts
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn submission_respects_submit_timeout() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/tasks"))
+        .respond_with(
+            ResponseTemplate::new(201)
+                .set_body_json(json!({"task_uuid": TASK_UUID}))
+                .set_delay(Duration::from_secs(5)),
+        )
+        .mount(&server)
+        .await;
+
+    // The read timeout is generous; only the submit timeout applies here
+    let client = OrchestrationClient::new(server.uri())
+        .with_submit_timeout(Duration::from_millis(200))
+        .with_read_timeout(Duration::from_secs(30));
+
+    let start = Instant::now();
+    let err = client
+        .create_task(&json!({"name": "slow"}))
+        .await
+        .expect_err("Expected the submission to time out");
+    assert!(start.elapsed() < Duration::from_secs(2), "took {:?}", start.elapsed());
+
+    assert!(
+        matches!(&err, OrchestrationError::Request(e) if e.is_timeout()),
+        "Expected a timeout error, got: {err}"
+    );
+}
+
+#[tokio::test]
+async fn reads_use_read_timeout() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path_regex(r"^/v1/tasks/[0-9a-f-]+$"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({"task_uuid": TASK_UUID, "status": "complete"}))
+                .set_delay(Duration::from_millis(500)),
+        )
+        .mount(&server)
+        .await;
+
+    // A read slower than the submit timeout still succeeds
+    let client = OrchestrationClient::new(server.uri())
+        .with_submit_timeout(Duration::from_millis(100))
+        .with_read_timeout(Duration::from_secs(5));
+
+    let task = client
+        .get_task(TASK_UUID.parse().unwrap())
+        .await
+        .expect("Expected the read to succeed");
+    assert_eq!(task["status"], "complete");
+
+    let impatient = client.with_read_timeout(Duration::from_millis(100));
+    assert!(impatient.get_task(TASK_UUID.parse().unwrap()).await.is_err());
+}
+
+// ---------------------------------------------------------------------------
+// Authentication
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn requests_carry_the_api_key() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/tasks"))
+        .and(header("X-API-Key", "secret-key"))
+        .respond_with(ResponseTemplate::new(201).set_body_json(json!({"task_uuid": TASK_UUID})))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("/v1/tasks/{TASK_UUID}")))
+        .and(header("X-API-Key", "secret-key"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"status": "pending"})))
+        .expect(1)
+        .mount(&server)
+        .await;
+    // Anything without the key is rejected
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(401))
+        .mount(&server)
+        .await;
+
+    let client = OrchestrationClient::new(server.uri()).with_api_key("secret-key");
+    let task_uuid = client.create_task(&json!({"name": "process_refund"})).await.unwrap();
+    client.get_task(task_uuid).await.unwrap();
+
+    let err = OrchestrationClient::new(server.uri())
+        .create_task(&json!({"name": "process_refund"}))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, OrchestrationError::Status { status, .. } if status == 401),
+        "got {err:?}"
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Step counts
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn step_count_comes_from_creation_response() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/tasks"))
+        .respond_with(
+            ResponseTemplate::new(201).set_body_json(json!({"task_uuid": TASK_UUID, "step_count": 5})),
+        )
+        .mount(&server)
+        .await;
+    // No follow-up fetch is needed
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let client = OrchestrationClient::new(server.uri());
+    let task = client.submit_task(&json!({"name": "process_refund"})).await.unwrap();
+    assert_eq!(task.total_steps, Some(5));
+    assert_eq!(client.total_steps(&task).await.unwrap(), 5);
+}
+
+#[tokio::test]
+async fn step_count_falls_back_to_task_fetch() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/tasks"))
+        .respond_with(ResponseTemplate::new(201).set_body_json(json!({"task_uuid": TASK_UUID})))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("/v1/tasks/{TASK_UUID}")))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(json!({"task_uuid": TASK_UUID, "total_steps": 4})),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = OrchestrationClient::new(server.uri());
+    let task = client.submit_task(&json!({"name": "process_refund"})).await.unwrap();
+    assert_eq!(task.total_steps, None);
+    assert_eq!(client.total_steps(&task).await.unwrap(), 4);
+}
+
+// ---------------------------------------------------------------------------
+// Malformed responses
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn response_without_task_uuid_is_malformed() {
+    let server = MockServer::start().await;
+    // e.g. an orchestration version that returns `id` instead of `task_uuid`
+    Mock::given(method("POST"))
+        .and(path("/v1/tasks"))
+        .respond_with(
+            ResponseTemplate::new(201).set_body_json(json!({"id": TASK_UUID, "status": "pending"})),
+        )
+        .mount(&server)
+        .await;
+
+    let err = OrchestrationClient::new(server.uri())
+        .create_task(&json!({"name": "renamed_field"}))
+        .await
+        .expect_err("Expected a malformed response error");
+
+    match &err {
+        OrchestrationError::MalformedResponse { status, reason, body } => {
+            assert_eq!(status.as_u16(), 201);
+            assert_eq!(reason, "missing task_uuid");
+            assert!(body.contains(r#""id":"0191e0a4"#), "{body}");
+        }
+        other => panic!("Expected MalformedResponse, got: {other:?}"),
+    }
+    let message = err.to_string();
+    assert!(message.contains("201"), "{message}");
+    assert!(message.contains(TASK_UUID), "{message}");
+}
+
+#[tokio::test]
+async fn malformed_response_body_is_truncated() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/tasks"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("x".repeat(10_000)))
+        .mount(&server)
+        .await;
+
+    let err = OrchestrationClient::new(server.uri())
+        .create_task(&json!({"name": "huge"}))
+        .await
+        .unwrap_err();
+    let OrchestrationError::MalformedResponse { reason, body, .. } = err else {
+        panic!("Expected MalformedResponse");
+    };
+    assert!(reason.starts_with("invalid JSON"), "{reason}");
+    assert!(body.len() < 1_000, "body was {} bytes", body.len());
+
+    // An error status is not a protocol mismatch
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(503).set_body_string("unavailable"))
+        .mount(&server)
+        .await;
+    let err = OrchestrationClient::new(server.uri())
+        .create_task(&json!({"name": "down"}))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, OrchestrationError::Status { status, .. } if status.as_u16() == 503),
+        "{err}"
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Submission retries
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn flaky_submission_is_retried_until_it_succeeds() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/tasks"))
+        .respond_with(ResponseTemplate::new(503).set_body_string("warming up"))
+        .up_to_n_times(2)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/tasks"))
+        .respond_with(ResponseTemplate::new(201).set_body_json(json!({"task_uuid": TASK_UUID})))
+        .mount(&server)
+        .await;
+
+    let client = OrchestrationClient::new(server.uri())
+        .with_submit_retries(3, Duration::from_millis(10));
+
+    let task_uuid = client
+        .create_task(&json!({"name": "flaky"}))
+        .await
+        .expect("Expected the third attempt to succeed");
+    assert_eq!(task_uuid.to_string(), TASK_UUID);
+    assert_eq!(server.received_requests().await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn submission_gives_up_after_the_configured_attempts() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/tasks"))
+        .respond_with(ResponseTemplate::new(502))
+        .mount(&server)
+        .await;
+
+    let client = OrchestrationClient::new(server.uri())
+        .with_submit_retries(4, Duration::from_millis(10));
+
+    let retries = metrics::registry().counter_value("orchestration_submit_retries_total", &[]);
+    let err = client.create_task(&json!({})).await.unwrap_err();
+    assert!(
+        matches!(err, OrchestrationError::Status { status, .. } if status == 502),
+        "got {err:?}"
+    );
+    assert_eq!(server.received_requests().await.unwrap().len(), 4);
+    assert!(
+        metrics::registry().counter_value("orchestration_submit_retries_total", &[])
+            >= retries + 3
+    );
+}
+
+#[tokio::test]
+async fn client_errors_are_not_retried() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/tasks"))
+        .respond_with(ResponseTemplate::new(422).set_body_string("unknown template"))
+        .mount(&server)
+        .await;
+
+    let client = OrchestrationClient::new(server.uri())
+        .with_submit_retries(3, Duration::from_millis(10));
+
+    let err = client.create_task(&json!({})).await.unwrap_err();
+    assert!(matches!(err, OrchestrationError::Status { .. }), "got {err:?}");
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn refused_connections_are_retried() {
+    // Nothing listens on the discard port, so every attempt is refused
+    let client = OrchestrationClient::new("http://127.0.0.1:9")
+        .with_submit_retries(3, Duration::from_millis(50));
+
+    let start = Instant::now();
+    let err = client.create_task(&json!({})).await.unwrap_err();
+    assert!(
+        matches!(&err, OrchestrationError::Request(e) if e.is_connect()),
+        "got {err:?}"
+    );
+    // Two backoffs: at least 25ms + 50ms with jitter
+    assert!(start.elapsed() >= Duration::from_millis(75), "took {:?}", start.elapsed());
+}
+
+// ---------------------------------------------------------------------------
+// Circuit breaker
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn breaker_opens_after_consecutive_failures_and_fails_fast() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/tasks"))
+        .respond_with(ResponseTemplate::new(503).set_delay(Duration::from_millis(200)))
+        .mount(&server)
+        .await;
+
+    // One request per call, so each call is one breaker failure
+    let client = OrchestrationClient::new(server.uri())
+        .with_submit_retries(1, Duration::ZERO)
+        .with_circuit_breaker(3, Duration::from_secs(60));
+
+    for _ in 0..3 {
+        let err = client.create_task(&json!({})).await.unwrap_err();
+        assert!(matches!(err, OrchestrationError::Status { .. }), "got {err:?}");
+    }
+    assert_eq!(client.circuit_breaker().state(), CircuitState::Open);
+
+    let rejections = metrics::registry().counter_value("orchestration_circuit_rejections_total", &[]);
+    let start = Instant::now();
+    let err = client.create_task(&json!({})).await.unwrap_err();
+    assert!(matches!(err, OrchestrationError::CircuitOpen), "got {err:?}");
+    assert!(start.elapsed() < Duration::from_millis(50), "took {:?}", start.elapsed());
+
+    // Reads are short-circuited too, and nothing reached the server
+    let err = client.get_task(TASK_UUID.parse().unwrap()).await.unwrap_err();
+    assert!(matches!(err, OrchestrationError::CircuitOpen), "got {err:?}");
+    assert_eq!(server.received_requests().await.unwrap().len(), 3);
+    assert!(
+        metrics::registry().counter_value("orchestration_circuit_rejections_total", &[])
+            >= rejections + 2
+    );
+}
+
+#[tokio::test]
+async fn breaker_half_opens_after_cooldown_and_closes_on_success() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/health"))
+        .respond_with(ResponseTemplate::new(500))
+        .up_to_n_times(2)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/health"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    let client = OrchestrationClient::new(server.uri())
+        .with_circuit_breaker(2, Duration::from_millis(100));
+
+    assert!(client.health().await.is_err());
+    assert!(client.health().await.is_err());
+    assert!(matches!(client.health().await, Err(OrchestrationError::CircuitOpen)));
+
+    tokio::time::sleep(Duration::from_millis(120)).await;
+    assert_eq!(client.circuit_breaker().state(), CircuitState::HalfOpen);
+    client.health().await.expect("Trial call should reach orchestration");
+    assert_eq!(client.circuit_breaker().state(), CircuitState::Closed);
+}
+
+#[tokio::test]
+async fn client_errors_do_not_trip_the_breaker() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/tasks"))
+        .respond_with(ResponseTemplate::new(400).set_body_string("unknown template"))
+        .mount(&server)
+        .await;
+
+    let client = OrchestrationClient::new(server.uri())
+        .with_circuit_breaker(1, Duration::from_secs(60));
+
+    // Orchestration is up and answering; a bad request is not an outage
+    for _ in 0..3 {
+        let err = client.create_task(&json!({})).await.unwrap_err();
+        assert!(matches!(err, OrchestrationError::Status { .. }), "got {err:?}");
+    }
+    assert_eq!(client.circuit_breaker().state(), CircuitState::Closed);
+}
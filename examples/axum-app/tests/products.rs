@@ -0,0 +1,48 @@
+//! Product catalog route tests.
+//!
+//! The app is served with a lazily-connected pool, so no database, worker, or
+//! orchestration services are needed.
+//!
+//! Run: cargo test --test products
+
+mod common;
+
+use serde_json::Value;
+
+use common::spawn_app;
+
+#[tokio::test]
+async fn lists_the_default_catalog_with_prices() {
+    let base_url = spawn_app().await;
+    let res = reqwest::get(format!("{}/products", base_url))
+        .await
+        .expect("Failed to list products");
+    assert_eq!(res.status(), 200);
+
+    let body: Value = res.json().await.unwrap();
+    let products = body["data"].as_array().expect("data should be an array");
+    let listed: Vec<(i64, &str, &str, f64)> = products
+        .iter()
+        .map(|p| {
+            (
+                p["id"].as_i64().unwrap(),
+                p["name"].as_str().unwrap(),
+                p["sku"].as_str().unwrap(),
+                p["price"].as_f64().unwrap(),
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        listed,
+        vec![
+            (1, "Widget A", "WGT-A-001", 29.99),
+            (2, "Widget B", "WGT-B-002", 49.99),
+            (3, "Widget C", "WGT-C-003", 99.99),
+            (4, "Gadget X", "GDG-X-004", 149.99),
+            (5, "Gadget Y", "GDG-Y-005", 199.99),
+        ]
+    );
+    assert!(products.iter().all(|p| p["stock"].as_i64().unwrap() > 0));
+    assert_eq!(body["message"], "5 products found");
+}
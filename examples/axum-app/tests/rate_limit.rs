@@ -0,0 +1,117 @@
+//! Rate limit tests.
+//!
+//! Requests are rejected by the rate limit middleware before any handler
+//! touches the database, so the app is served with a lazily-connected pool and
+//! an invalid order body (a 422 while under the limit). No database or
+//! orchestration is needed.
+//!
+//! Run: cargo test --test rate_limit
+
+mod common;
+
+use serde_json::{json, Value};
+
+use example_axum_app::rate_limit::RateLimiter;
+use example_axum_app::AppConfig;
+
+/// Serve the app with `rate_limiter` on a random local port, with peer
+/// addresses available, and return its base URL.
+async fn spawn_app(rate_limiter: RateLimiter) -> String {
+    common::spawn_app_with_config(AppConfig {
+        rate_limiter,
+        ..Default::default()
+    })
+    .await
+}
+
+async fn post_order(base_url: &str, api_key: Option<&str>) -> reqwest::Response {
+    let mut request = reqwest::Client::new()
+        .post(format!("{}/orders", base_url))
+        .json(&json!({"customer_email": "flood@example.com"}));
+    if let Some(api_key) = api_key {
+        request = request.header("X-API-Key", api_key);
+    }
+    request.send().await.expect("Failed to send request")
+}
+
+#[tokio::test]
+async fn flooding_post_orders_returns_429_with_retry_after() {
+    // 3 requests at once, then one every 10 seconds
+    let base_url = spawn_app(RateLimiter::new(0.1, 3).unwrap()).await;
+
+    let mut statuses = Vec::new();
+    let mut limited = None;
+    for _ in 0..10 {
+        let res = post_order(&base_url, None).await;
+        statuses.push(res.status().as_u16());
+        if res.status() == 429 {
+            limited = Some(res);
+            break;
+        }
+    }
+    assert_eq!(statuses, [422, 422, 422, 429]);
+
+    let res = limited.expect("Expected a 429");
+    let retry_after: u64 = res.headers()["retry-after"]
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!((1..=10).contains(&retry_after), "{retry_after}");
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["error"]["code"], "rate_limited");
+    assert_eq!(body["error"]["retry_after_secs"], retry_after);
+
+    // Health probes are not limited
+    let res = reqwest::get(format!("{}/health", base_url)).await.unwrap();
+    assert_ne!(res.status(), 429);
+}
+
+#[tokio::test]
+async fn each_api_key_has_its_own_bucket() {
+    let limiter = RateLimiter::new(0.1, 1)
+        .unwrap()
+        .with_api_keys(["key-a", "key-b"]);
+    let base_url = spawn_app(limiter).await;
+
+    assert_eq!(post_order(&base_url, Some("key-a")).await.status(), 422);
+    assert_eq!(post_order(&base_url, Some("key-a")).await.status(), 429);
+
+    // Another key, and the caller's IP, still have their requests
+    assert_eq!(post_order(&base_url, Some("key-b")).await.status(), 422);
+    assert_eq!(post_order(&base_url, None).await.status(), 422);
+    assert_eq!(post_order(&base_url, None).await.status(), 429);
+}
+
+#[tokio::test]
+async fn unknown_api_keys_share_the_ip_bucket() {
+    let limiter = RateLimiter::new(0.1, 2).unwrap().with_api_keys(["key-a"]);
+    let base_url = spawn_app(limiter).await;
+
+    // A new key per request does not get a new bucket
+    assert_eq!(post_order(&base_url, Some("random-1")).await.status(), 422);
+    assert_eq!(post_order(&base_url, Some("random-2")).await.status(), 422);
+    assert_eq!(post_order(&base_url, Some("random-3")).await.status(), 429);
+    assert_eq!(post_order(&base_url, None).await.status(), 429);
+
+    assert_eq!(post_order(&base_url, Some("key-a")).await.status(), 422);
+}
+
+#[tokio::test]
+async fn disabled_limiter_never_limits() {
+    let base_url = spawn_app(RateLimiter::disabled()).await;
+    for _ in 0..20 {
+        assert_eq!(post_order(&base_url, None).await.status(), 422);
+    }
+}
+
+#[test]
+fn limiter_rejects_invalid_settings() {
+    assert!(RateLimiter::new(0.0, 1).is_err());
+    assert!(RateLimiter::new(f64::NAN, 1).is_err());
+    assert!(RateLimiter::new(5.0, 0).is_err());
+
+    let limiter = RateLimiter::new(2.5, 4).unwrap();
+    let limit = limiter.limit().unwrap();
+    assert_eq!((limit.per_second, limit.burst), (2.5, 4));
+}
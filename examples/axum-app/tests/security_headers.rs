@@ -0,0 +1,67 @@
+//! Security header tests.
+//!
+//! The app is served with a lazily-connected pool and the headers are checked
+//! on `GET /products`, which needs no database or orchestration.
+//!
+//! Run: cargo test --test security_headers
+
+mod common;
+
+use example_axum_app::security_headers::{SecurityHeaders, DEFAULT_CONTENT_SECURITY_POLICY};
+use example_axum_app::AppConfig;
+
+/// Serve the app with `security_headers` and return its base URL.
+async fn spawn_app(security_headers: SecurityHeaders) -> String {
+    common::spawn_app_with_config(AppConfig {
+        security_headers,
+        ..Default::default()
+    })
+    .await
+}
+
+#[tokio::test]
+async fn enabled_headers_are_set_on_responses() {
+    let base_url = spawn_app(SecurityHeaders::enabled()).await;
+    let client = reqwest::Client::new();
+
+    for path in ["/products", "/no-such-route"] {
+        let res = client
+            .get(format!("{}{}", base_url, path))
+            .send()
+            .await
+            .expect("Failed to send request");
+        let headers = res.headers();
+        assert_eq!(headers["x-content-type-options"], "nosniff", "{path}");
+        assert_eq!(headers["x-frame-options"], "DENY", "{path}");
+        assert_eq!(
+            headers["strict-transport-security"],
+            "max-age=31536000; includeSubDomains",
+            "{path}"
+        );
+        assert_eq!(headers["content-security-policy"], DEFAULT_CONTENT_SECURITY_POLICY, "{path}");
+    }
+}
+
+#[tokio::test]
+async fn headers_are_not_set_by_default() {
+    let base_url = spawn_app(SecurityHeaders::disabled()).await;
+    let res = reqwest::get(format!("{}/products", base_url))
+        .await
+        .expect("Failed to send request");
+    assert_eq!(res.status(), 200);
+    assert!(res.headers().get("x-frame-options").is_none());
+    assert!(res.headers().get("content-security-policy").is_none());
+}
+
+#[tokio::test]
+async fn zero_hsts_max_age_omits_the_header() {
+    let headers = SecurityHeaders::with_settings(0, "default-src 'self'").unwrap();
+    let base_url = spawn_app(headers).await;
+    let res = reqwest::get(format!("{}/products", base_url))
+        .await
+        .expect("Failed to send request");
+    assert!(res.headers().get("strict-transport-security").is_none());
+    assert_eq!(res.headers()["content-security-policy"], "default-src 'self'");
+
+    assert!(SecurityHeaders::with_settings(0, "default-src\n'self'").is_err());
+}
@@ -0,0 +1,128 @@
+//! Graceful shutdown tests: in-flight steps are drained before dispatch stops.
+//!
+//! A pending future stands in for the dispatch service, so no worker,
+//! database, or orchestration services are needed.
+//!
+//! Run: cargo test --test shutdown
+
+use std::time::Duration;
+
+use example_axum_app::handler_registry::AxumHandlerRegistry;
+use example_axum_app::shutdown::{run_until_shutdown, DrainReport, InFlightSteps, ShutdownToken};
+
+#[tokio::test]
+async fn wait_until_idle_returns_once_steps_finish() {
+    let in_flight = InFlightSteps::new();
+    let first = in_flight.start();
+    let second = in_flight.start();
+    assert_eq!(in_flight.count(), 2);
+
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        drop(first);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        drop(second);
+    });
+
+    assert_eq!(in_flight.wait_until_idle(Duration::from_secs(5)).await, 0);
+    assert_eq!(in_flight.count(), 0);
+}
+
+#[tokio::test]
+async fn wait_until_idle_gives_up_after_the_timeout() {
+    let in_flight = InFlightSteps::new();
+    let _stuck = in_flight.start();
+
+    assert_eq!(
+        in_flight.wait_until_idle(Duration::from_millis(20)).await,
+        1
+    );
+}
+
+#[tokio::test]
+async fn shutdown_drains_in_flight_steps_before_stopping() {
+    let in_flight = InFlightSteps::new();
+    let shutdown = ShutdownToken::new();
+    let step = in_flight.start();
+
+    let drain = tokio::spawn({
+        let in_flight = in_flight.clone();
+        let shutdown = shutdown.clone();
+        async move {
+            run_until_shutdown(
+                std::future::pending(),
+                &in_flight,
+                &shutdown,
+                Duration::from_secs(5),
+            )
+            .await
+        }
+    });
+
+    shutdown.trigger();
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert!(
+        !drain.is_finished(),
+        "dispatch stopped with a step still running"
+    );
+
+    drop(step);
+    let report = drain.await.unwrap();
+    assert_eq!(
+        report,
+        Some(DrainReport {
+            drained: 1,
+            abandoned: 0
+        })
+    );
+}
+
+#[tokio::test]
+async fn shutdown_abandons_steps_still_running_after_the_timeout() {
+    let in_flight = InFlightSteps::new();
+    let shutdown = ShutdownToken::new();
+    let _stuck = in_flight.start();
+    shutdown.trigger();
+
+    let report = run_until_shutdown(
+        std::future::pending(),
+        &in_flight,
+        &shutdown,
+        Duration::from_millis(20),
+    )
+    .await;
+    assert_eq!(
+        report,
+        Some(DrainReport {
+            drained: 0,
+            abandoned: 1
+        })
+    );
+}
+
+#[tokio::test]
+async fn service_that_stops_on_its_own_reports_no_drain() {
+    let shutdown = ShutdownToken::new();
+
+    let report = run_until_shutdown(
+        async {},
+        &InFlightSteps::new(),
+        &shutdown,
+        Duration::from_secs(5),
+    )
+    .await;
+    assert_eq!(report, None);
+    assert!(!shutdown.is_triggered());
+}
+
+#[tokio::test]
+async fn registry_handlers_count_as_in_flight() {
+    let registry = AxumHandlerRegistry::new();
+    let in_flight = registry.in_flight_steps();
+
+    // The registry and its handlers share one count
+    let step = in_flight.start();
+    assert_eq!(registry.in_flight_steps().count(), 1);
+    drop(step);
+    assert_eq!(registry.in_flight_steps().count(), 0);
+}
@@ -0,0 +1,265 @@
+//! Workflow simulation tests: `POST /simulate/{workflow}` runs the handlers
+//! in-process.
+//!
+//! The app is served with a lazily-connected pool, so no database, worker, or
+//! orchestration services are needed.
+//!
+//! Run: cargo test --test simulate
+
+mod common;
+
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+
+use example_axum_app::admin_auth::AdminAuth;
+use example_axum_app::gateway::{
+    ChargeReceipt, ChargeRequest, GatewayError, PaymentGateway, RefundReceipt, RefundRequest,
+};
+use example_axum_app::handler_registry::AxumHandlerRegistry;
+use example_axum_app::simulate::{execution_order, simulate, workflow_names, workflow_template};
+use example_axum_app::AppConfig;
+
+const ADMIN_TOKEN: &str = "s3cret";
+
+/// Serve the app with `registry`, requiring [`ADMIN_TOKEN`] for admin routes,
+/// on a random local port and return its base URL.
+async fn spawn_app_with_registry(registry: AxumHandlerRegistry) -> String {
+    common::spawn_app_with_config(AppConfig {
+        handler_registry: Arc::new(registry),
+        admin_auth: AdminAuth::new(Some(ADMIN_TOKEN.to_string())),
+        ..Default::default()
+    })
+    .await
+}
+
+async fn spawn_app() -> String {
+    spawn_app_with_registry(AxumHandlerRegistry::new()).await
+}
+
+/// A gateway that must never be reached from a simulation.
+struct UnreachableGateway;
+
+impl PaymentGateway for UnreachableGateway {
+    fn charge(&self, _: &ChargeRequest<'_>) -> Result<ChargeReceipt, GatewayError> {
+        panic!("simulation charged the configured gateway")
+    }
+
+    fn refund(&self, _: &RefundRequest<'_>) -> Result<RefundReceipt, GatewayError> {
+        panic!("simulation refunded through the configured gateway")
+    }
+
+    fn name(&self) -> &str {
+        "unreachable"
+    }
+}
+
+async fn simulate_http(base_url: &str, workflow: &str, context: Value) -> (u16, Value) {
+    let res = reqwest::Client::new()
+        .post(format!("{}/simulate/{}", base_url, workflow))
+        .bearer_auth(ADMIN_TOKEN)
+        .json(&context)
+        .send()
+        .await
+        .expect("Failed to send request");
+    let status = res.status().as_u16();
+    (status, res.json().await.unwrap_or(Value::Null))
+}
+
+fn order_context(payment_token: &str) -> Value {
+    json!({
+        "cart_items": [
+            {"product_id": 1, "quantity": 2},
+            {"product_id": 2, "quantity": 1}
+        ],
+        "customer_email": "simulate@example.com",
+        "payment_token": payment_token,
+        "shipping_address": {
+            "street": "123 Main St", "city": "Portland", "state": "OR",
+            "zip": "97201", "country": "US"
+        }
+    })
+}
+
+#[tokio::test]
+async fn simulated_order_is_confirmed_with_correct_totals() {
+    let base_url = spawn_app().await;
+    let (status, body) =
+        simulate_http(&base_url, "ecommerce", order_context("tok_test_success")).await;
+    assert_eq!(status, 200, "{body}");
+
+    let simulation = &body["data"];
+    assert_eq!(simulation["status"], "complete");
+    let steps: Vec<&str> = simulation["steps"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        steps,
+        vec![
+            "validate_cart",
+            "calculate_shipping",
+            "process_payment",
+            "update_inventory",
+            "create_order",
+            "send_confirmation"
+        ]
+    );
+
+    // 2 x Widget A ($29.99) + 1 x Widget B ($49.99), 8% tax, free shipping over $100
+    let order = &simulation["steps"][4]["result"];
+    assert_eq!(order["status"], "confirmed");
+    assert_eq!(order["subtotal"], 109.97);
+    assert_eq!(order["tax"], 8.8);
+    assert_eq!(order["shipping"], 0.0);
+    assert_eq!(order["total"], 118.77);
+    assert_eq!(order["customer_email"], "simulate@example.com");
+
+    assert_eq!(simulation["final_result"]["status"], "sent");
+    assert_eq!(simulation["final_result"]["recipient"], "simulate@example.com");
+}
+
+#[tokio::test]
+async fn failing_handler_stops_the_simulation() {
+    let base_url = spawn_app().await;
+    let (status, body) =
+        simulate_http(&base_url, "ecommerce", order_context("tok_test_declined")).await;
+    assert_eq!(status, 200);
+
+    let simulation = &body["data"];
+    assert_eq!(simulation["status"], "error");
+    assert_eq!(simulation["failed_step"], "process_payment");
+    assert_eq!(simulation["steps"].as_array().unwrap().len(), 2);
+
+    let (status, _) = simulate_http(&base_url, "no_such_workflow", json!({})).await;
+    assert_eq!(status, 404);
+}
+
+#[tokio::test]
+async fn simulation_requires_the_admin_token() {
+    let base_url = spawn_app().await;
+    let res = reqwest::Client::new()
+        .post(format!("{}/simulate/ecommerce", base_url))
+        .json(&order_context("tok_test_success"))
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert_eq!(res.status().as_u16(), 401);
+
+    let res = reqwest::Client::new()
+        .get(format!("{}/simulate", base_url))
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert_eq!(res.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn simulation_runs_against_a_sandbox_registry() {
+    let registry = AxumHandlerRegistry::new();
+    registry.set_payment_gateway(Arc::new(UnreachableGateway));
+    let base_url = spawn_app_with_registry(registry).await;
+
+    let (status, body) =
+        simulate_http(&base_url, "ecommerce", order_context("tok_test_success")).await;
+    assert_eq!(status, 200, "{body}");
+    assert_eq!(body["data"]["status"], "complete");
+}
+
+#[test]
+fn sandbox_copies_the_catalog_without_sharing_it() {
+    let registry = AxumHandlerRegistry::new();
+    let mut products = registry.products();
+    products.truncate(1);
+    registry.set_catalog(products);
+
+    let sandbox = registry.sandbox();
+    assert_eq!(sandbox.products().len(), 1);
+    sandbox.set_catalog(Vec::new());
+    assert_eq!(registry.products().len(), 1);
+}
+
+#[test]
+fn every_workflow_has_a_valid_execution_order() {
+    let registry = AxumHandlerRegistry::new();
+    assert_eq!(
+        workflow_names(),
+        vec!["ecommerce", "data_pipeline", "microservices", "customer_success", "payments"]
+    );
+
+    for name in workflow_names() {
+        let template = workflow_template(name).unwrap();
+        let plan = execution_order(&template).unwrap();
+        for (i, step) in plan.iter().enumerate() {
+            for dep in &step.dependencies {
+                assert!(
+                    plan[..i].iter().any(|s| &s.name == dep),
+                    "{name}: {} runs before its dependency {dep}",
+                    step.name
+                );
+            }
+        }
+        let callables: Vec<&str> = plan.iter().map(|s| s.callable.as_str()).collect();
+        assert!(
+            callables.iter().all(|c| registry.call_function(c, &json!({}), &Default::default()).is_some()),
+            "{name}: every step should have a function handler"
+        );
+    }
+}
+
+#[test]
+fn data_pipeline_simulation_aggregates_all_sources() {
+    let registry = AxumHandlerRegistry::new();
+    let template = workflow_template("data_pipeline").unwrap();
+
+    let simulation = simulate(&registry, "data_pipeline", &template, &json!({})).unwrap();
+    assert_eq!(simulation.status, "complete", "{:?}", simulation.error);
+    assert_eq!(simulation.steps.len(), 8);
+
+    let aggregate = simulation
+        .steps
+        .iter()
+        .find(|s| s.name == "aggregate_metrics")
+        .unwrap();
+    assert_eq!(aggregate.result["sources_included"], 3);
+    assert_eq!(aggregate.result["partial"], false);
+}
+
+#[test]
+fn data_pipeline_simulation_skips_unrequested_sources() {
+    let registry = AxumHandlerRegistry::new();
+    let template = workflow_template("data_pipeline").unwrap();
+    let context = json!({"job_name": "sales_only", "sources": ["sales"]});
+
+    let simulation = simulate(&registry, "data_pipeline", &template, &context).unwrap();
+    assert_eq!(simulation.status, "complete", "{:?}", simulation.error);
+    let step = |name: &str| {
+        &simulation
+            .steps
+            .iter()
+            .find(|s| s.name == name)
+            .unwrap_or_else(|| panic!("{name} ran"))
+            .result
+    };
+
+    assert!(step("extract_sales_data")["record_count"].as_i64().unwrap() > 0);
+    assert!(step("extract_sales_data").get("skipped").is_none());
+    for name in [
+        "extract_inventory_data",
+        "extract_customer_data",
+        "transform_inventory",
+        "transform_customers",
+    ] {
+        assert_eq!(step(name)["skipped"], true, "{name}");
+        assert_eq!(step(name)["record_count"], 0, "{name}");
+    }
+
+    let aggregate = step("aggregate_metrics");
+    assert_eq!(aggregate["data_sources"], json!(["sales"]));
+    assert_eq!(aggregate["sources_included"], 1);
+    // Leaving sources out on purpose is not a degraded result
+    assert_eq!(aggregate["partial"], false);
+    assert!(aggregate.get("total_customers").is_none());
+}
@@ -0,0 +1,198 @@
+//! SQLite backend smoke tests (sqlite feature only).
+//!
+//! Runs the order routes against an in-memory SQLite database and a mock
+//! orchestration server. No Postgres, worker, or orchestration services are
+//! needed.
+//!
+//! Run: cargo test --features sqlite --test sqlite
+
+#![cfg(feature = "sqlite")]
+
+mod common;
+
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+use wiremock::matchers::{body_partial_json, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use example_axum_app::attribution::InitiatorAllowlist;
+use example_axum_app::extract::FieldAliases;
+use example_axum_app::orchestration::OrchestrationClient;
+use example_axum_app::sqlite;
+
+const TASK_UUID: &str = "0191e0a4-7b3c-7d2e-9f10-123456789abc";
+
+/// Serve the SQLite app on a random local port and return its base URL.
+async fn spawn_app(orchestration_url: String) -> String {
+    spawn_app_with_initiators(orchestration_url, InitiatorAllowlist::default()).await
+}
+
+async fn spawn_app_with_initiators(
+    orchestration_url: String,
+    initiators: InitiatorAllowlist,
+) -> String {
+    let db = sqlite::connect_in_memory()
+        .await
+        .expect("Failed to open in-memory SQLite database");
+    let app = sqlite::create_app(
+        db,
+        OrchestrationClient::new(orchestration_url),
+        FieldAliases::default(),
+    )
+    .layer(axum::Extension(Arc::new(initiators)));
+
+    common::serve(app).await
+}
+
+fn order_body(sku: &str) -> Value {
+    json!({
+        "customer_email": "sqlite@example.com",
+        "cart_items": [
+            {"sku": sku, "name": "Widget A", "quantity": 2, "unit_price": 29.99}
+        ],
+        "payment_token": "tok_test_success",
+        "shipping_address": {
+            "street": "123 Main", "city": "Portland", "state": "OR",
+            "zip": "97201", "country": "US"
+        }
+    })
+}
+
+#[tokio::test]
+async fn creates_and_reads_an_order() {
+    let orchestration = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/tasks"))
+        .respond_with(ResponseTemplate::new(201).set_body_json(json!({"task_uuid": TASK_UUID})))
+        .expect(1)
+        .mount(&orchestration)
+        .await;
+
+    let base = spawn_app(orchestration.uri()).await;
+    let client = reqwest::Client::new();
+
+    let resp = client
+        .post(format!("{base}/orders"))
+        .json(&order_body("WGT-A-001"))
+        .send()
+        .await
+        .expect("Failed to create order");
+    assert_eq!(resp.status(), 201);
+    let created: Value = resp.json().await.unwrap();
+    assert_eq!(created["data"]["status"], "processing");
+    assert_eq!(created["data"]["task_uuid"], TASK_UUID);
+    let id = created["data"]["id"].as_i64().unwrap();
+
+    let order: Value = client
+        .get(format!("{base}/orders/{id}"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let order = &order["data"];
+    assert_eq!(order["customer_email"], "sqlite@example.com");
+    assert_eq!(order["status"], "processing");
+    assert_eq!(order["task_uuid"], TASK_UUID);
+    assert_eq!(order["total"], "59.98");
+    assert_eq!(order["items"][0]["sku"], "WGT-A-001");
+
+    // The submitted task carries the resolved product id
+    let requests = orchestration.received_requests().await.unwrap();
+    let task: Value = serde_json::from_slice(&requests[0].body).unwrap();
+    assert_eq!(task["context"]["cart_items"][0]["product_id"], 1);
+    assert_eq!(task["context"]["app_order_id"], id);
+
+    let list: Value = client
+        .get(format!("{base}/orders"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(list["data"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn failed_submission_marks_the_order_failed() {
+    let base = spawn_app("http://127.0.0.1:9".to_string()).await;
+    let client = reqwest::Client::new();
+
+    let resp = client
+        .post(format!("{base}/orders"))
+        .json(&order_body("WGT-A-001"))
+        .send()
+        .await
+        .expect("Failed to create order");
+    assert_eq!(resp.status(), 503);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["error"]["code"], "task_submission_failed");
+    let id = body["error"]["id"].as_i64().unwrap();
+
+    let order: Value = client
+        .get(format!("{base}/orders/{id}"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(order["data"]["status"], "failed");
+    assert!(order["data"]["task_uuid"].is_null());
+}
+
+#[tokio::test]
+async fn unknown_sku_is_rejected_and_missing_order_is_404() {
+    let orchestration = MockServer::start().await;
+    let base = spawn_app(orchestration.uri()).await;
+    let client = reqwest::Client::new();
+
+    let resp = client
+        .post(format!("{base}/orders"))
+        .json(&order_body("NOPE-999"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 400);
+
+    let resp = client.get(format!("{base}/orders/42")).send().await.unwrap();
+    assert_eq!(resp.status(), 404);
+}
+
+#[tokio::test]
+async fn custom_initiator_reaches_the_task_request() {
+    let orchestration = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/tasks"))
+        .and(body_partial_json(json!({
+            "initiator": "billing-portal",
+            "source_system": "billing"
+        })))
+        .respond_with(ResponseTemplate::new(201).set_body_json(json!({"task_uuid": TASK_UUID})))
+        .expect(1)
+        .mount(&orchestration)
+        .await;
+
+    let base = spawn_app_with_initiators(
+        orchestration.uri(),
+        InitiatorAllowlist::new(["billing-portal"]),
+    )
+    .await;
+
+    let resp = reqwest::Client::new()
+        .post(format!("{base}/orders"))
+        .header("X-Initiator", "billing-portal")
+        .header("X-Source-System", "billing")
+        .json(&order_body("WGT-A-001"))
+        .send()
+        .await
+        .expect("Failed to create order");
+    assert_eq!(resp.status(), 201);
+    let created: Value = resp.json().await.unwrap();
+    assert_eq!(created["data"]["task_uuid"], TASK_UUID);
+
+    orchestration.verify().await;
+}
@@ -0,0 +1,130 @@
+//! Startup self-check tests.
+//!
+//! Uses a lazily-connected pool to an unreachable database and mock or
+//! unreachable orchestration servers, so no services are needed.
+//!
+//! Run: cargo test --test startup
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use sqlx::postgres::PgPoolOptions;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use example_axum_app::handler_registry::AxumHandlerRegistry;
+use example_axum_app::orchestration::OrchestrationClient;
+use example_axum_app::startup::startup_checks;
+
+/// Nothing listens on the discard port, so connections are refused at once.
+const UNREACHABLE: &str = "127.0.0.1:9";
+
+fn templates_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("config/templates")
+}
+
+fn unreachable_pool() -> sqlx::PgPool {
+    PgPoolOptions::new()
+        .acquire_timeout(Duration::from_secs(1))
+        .connect_lazy(&format!("postgresql://tasker:tasker@{UNREACHABLE}/example_axum"))
+        .expect("Failed to create lazy pool")
+}
+
+/// A uniquely named directory holding one template whose only step names an
+/// unregistered handler. Removed on drop.
+struct BrokenTemplates(PathBuf);
+
+impl BrokenTemplates {
+    fn new() -> Self {
+        let dir = std::env::temp_dir().join(format!("startup-checks-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("broken.yaml"),
+            "steps:\n  - name: mystery\n    handler:\n      callable: no_such_handler\n",
+        )
+        .unwrap();
+        Self(dir)
+    }
+
+    fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for BrokenTemplates {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[tokio::test]
+async fn strict_mode_fails_on_unavailable_dependencies() {
+    let templates = BrokenTemplates::new();
+    let report = startup_checks(
+        &unreachable_pool(),
+        &OrchestrationClient::new(format!("http://{UNREACHABLE}")),
+        &AxumHandlerRegistry::new(),
+        templates.path(),
+    )
+    .await;
+
+    assert!(!report.is_ready());
+    let failed: Vec<&str> = report.failures().iter().map(|check| check.name).collect();
+    assert_eq!(failed, vec!["database", "orchestration", "handlers"]);
+    assert!(report.failures()[2].detail.contains("no_such_handler"));
+
+    let err = report.enforce(true).unwrap_err().to_string();
+    assert!(err.contains("STRICT_STARTUP"), "{err}");
+    assert!(err.contains("handlers: no handler registered for no_such_handler"), "{err}");
+
+    // Outside strict mode, failures are only logged
+    assert!(report.enforce(false).is_ok());
+}
+
+#[tokio::test]
+async fn orchestration_and_bundled_templates_pass() {
+    let orchestration = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/health"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&orchestration)
+        .await;
+
+    let report = startup_checks(
+        &unreachable_pool(),
+        &OrchestrationClient::new(orchestration.uri()),
+        &AxumHandlerRegistry::new(),
+        &templates_dir(),
+    )
+    .await;
+
+    let passed: Vec<&str> = report
+        .checks
+        .iter()
+        .filter(|check| check.ok)
+        .map(|check| check.name)
+        .collect();
+    assert_eq!(passed, vec!["orchestration", "handlers"]);
+    assert!(report.enforce(true).is_err(), "the database is still unreachable");
+}
+
+#[tokio::test]
+async fn registry_missing_template_handlers_is_reported() {
+    // Only the payments handlers are registered
+    let registry = AxumHandlerRegistry::with_namespaces(Some(
+        ["payments_rs".to_string()].into_iter().collect(),
+    ));
+    let report = startup_checks(
+        &unreachable_pool(),
+        &OrchestrationClient::new(format!("http://{UNREACHABLE}")),
+        &registry,
+        &templates_dir(),
+    )
+    .await;
+
+    let handlers = &report.checks[2];
+    assert_eq!(handlers.name, "handlers");
+    assert!(!handlers.ok);
+    assert!(handlers.detail.contains("ecommerce_validate_cart"), "{}", handlers.detail);
+    assert!(!handlers.detail.contains("team_scaling_payments_"), "{}", handlers.detail);
+}
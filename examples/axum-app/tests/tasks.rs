@@ -0,0 +1,141 @@
+//! Task proxy tests: `GET /tasks/{uuid}` and `POST /tasks/{uuid}/cancel`.
+//!
+//! Orchestration is a wiremock server and the app is served with a
+//! lazily-connected pool, so no database or orchestration services are needed.
+//!
+//! Run: cargo test --test tasks
+
+mod common;
+
+use serde_json::{json, Value};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use example_axum_app::admin_auth::AdminAuth;
+use example_axum_app::orchestration::OrchestrationClient;
+use example_axum_app::AppConfig;
+
+const ADMIN_TOKEN: &str = "s3cret";
+
+/// Serve the app against `orchestration_url`, with [`ADMIN_TOKEN`] required
+/// for admin routes, and return its base URL.
+async fn spawn_app(orchestration_url: &str) -> String {
+    common::spawn_app_with_config(AppConfig {
+        orchestration: OrchestrationClient::new(orchestration_url),
+        admin_auth: AdminAuth::new(Some(ADMIN_TOKEN.to_string())),
+        ..Default::default()
+    })
+    .await
+}
+
+#[tokio::test]
+async fn task_is_fetched_from_orchestration() {
+    let server = MockServer::start().await;
+    let task_uuid = uuid::Uuid::new_v4();
+    let task = json!({
+        "task_uuid": task_uuid,
+        "status": "steps_in_process",
+        "completion_percentage": 50.0,
+        "steps": [{"name": "validate_cart", "current_state": "complete"}]
+    });
+    Mock::given(method("GET"))
+        .and(path(format!("/v1/tasks/{task_uuid}")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&task))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let base_url = spawn_app(&server.uri()).await;
+
+    let res = reqwest::get(format!("{base_url}/tasks/{task_uuid}"))
+        .await
+        .expect("Failed to send request");
+    assert_eq!(res.status(), 200);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["data"], task);
+}
+
+#[tokio::test]
+async fn unknown_task_is_a_404() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(404).set_body_json(json!({"error": "not found"})))
+        .mount(&server)
+        .await;
+    let base_url = spawn_app(&server.uri()).await;
+
+    let res = reqwest::get(format!("{base_url}/tasks/{}", uuid::Uuid::new_v4()))
+        .await
+        .expect("Failed to send request");
+    assert_eq!(res.status(), 404);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["error"]["code"], "not_found");
+}
+
+#[tokio::test]
+async fn malformed_uuid_is_a_400() {
+    // Nothing is forwarded, so orchestration is never reached
+    let base_url = spawn_app("http://127.0.0.1:9").await;
+    let client = reqwest::Client::new();
+
+    for res in [
+        client.get(format!("{base_url}/tasks/not-a-uuid")).send(),
+        client
+            .post(format!("{base_url}/tasks/not-a-uuid/cancel"))
+            .bearer_auth(ADMIN_TOKEN)
+            .send(),
+    ] {
+        let res = res.await.expect("Failed to send request");
+        assert_eq!(res.status(), 400);
+        let body: Value = res.json().await.unwrap();
+        assert_eq!(body["error"]["code"], "bad_request");
+    }
+}
+
+#[tokio::test]
+async fn task_is_cancelled_in_orchestration() {
+    let server = MockServer::start().await;
+    let task_uuid = uuid::Uuid::new_v4();
+    let finished = uuid::Uuid::new_v4();
+    Mock::given(method("DELETE"))
+        .and(path(format!("/v1/tasks/{task_uuid}")))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("DELETE"))
+        .and(path(format!("/v1/tasks/{finished}")))
+        .respond_with(ResponseTemplate::new(409))
+        .mount(&server)
+        .await;
+    let base_url = spawn_app(&server.uri()).await;
+    let client = reqwest::Client::new();
+
+    // Cancelling needs the admin token
+    let res = client
+        .post(format!("{base_url}/tasks/{task_uuid}/cancel"))
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert_eq!(res.status(), 401);
+
+    let res = client
+        .post(format!("{base_url}/tasks/{task_uuid}/cancel"))
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert_eq!(res.status(), 200);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(
+        body["data"],
+        json!({"task_uuid": task_uuid, "cancelled": true})
+    );
+
+    let res = client
+        .post(format!("{base_url}/tasks/{finished}/cancel"))
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert_eq!(res.status(), 409);
+}
@@ -0,0 +1,503 @@
+//! Request validation tests: 422 responses naming the offending field.
+//!
+//! Validation runs before any query, so the app is served with a
+//! lazily-connected pool and no database or orchestration is needed.
+//!
+//! Run: cargo test --test validation
+
+mod common;
+
+use serde_json::{json, Value};
+
+use example_axum_app::attribution::{InitiatorAllowlist, DEFAULT_INITIATOR};
+use example_axum_app::extract::FieldAliases;
+use example_axum_app::pagination::Cursor;
+use example_axum_app::routes::orders::MAX_BATCH_ORDERS;
+
+use common::spawn_app;
+
+fn order_with_address(shipping_address: Value) -> Value {
+    json!({
+        "customer_email": "validation@example.com",
+        "cart_items": [
+            {"sku": "1", "name": "Widget A", "quantity": 1, "unit_price": 29.99}
+        ],
+        "payment_token": "tok_test_success",
+        "shipping_address": shipping_address
+    })
+}
+
+fn full_address() -> Value {
+    json!({
+        "street": "123 Main St",
+        "city": "Anytown",
+        "state": "CA",
+        "zip": "90210",
+        "country": "US"
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Shipping address
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn missing_shipping_address_fields_return_422() {
+    let base_url = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    for field in ["street", "city", "state", "zip", "country"] {
+        let mut address = full_address();
+        address.as_object_mut().unwrap().remove(field);
+
+        for path in ["/orders", "/orders/async"] {
+            let res = client
+                .post(format!("{}{}", base_url, path))
+                .json(&order_with_address(address.clone()))
+                .send()
+                .await
+                .expect("Failed to send request");
+
+            assert_eq!(res.status(), 422, "{path} without {field}");
+            let body: Value = res.json().await.expect("Expected JSON error body");
+            assert_eq!(body["error"]["code"], "validation_failed");
+            assert_eq!(
+                body["error"]["field"],
+                format!("shipping_address.{field}"),
+                "{path} without {field}"
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn blank_shipping_address_field_returns_422() {
+    let base_url = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    let mut address = full_address();
+    address["zip"] = json!("  ");
+
+    let res = client
+        .post(format!("{}/orders", base_url))
+        .json(&order_with_address(address))
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(res.status(), 422);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["error"]["field"], "shipping_address.zip");
+}
+
+// ---------------------------------------------------------------------------
+// Cart and customer email
+// ---------------------------------------------------------------------------
+
+/// POST `order` to both create routes and return each 422 error object.
+async fn rejected_order_errors(base_url: &str, order: &Value) -> Vec<Value> {
+    let client = reqwest::Client::new();
+    let mut errors = Vec::new();
+    for path in ["/orders", "/orders/async"] {
+        let res = client
+            .post(format!("{}{}", base_url, path))
+            .json(order)
+            .send()
+            .await
+            .expect("Failed to send request");
+
+        assert_eq!(res.status(), 422, "{path}");
+        let body: Value = res.json().await.expect("Expected JSON error body");
+        assert_eq!(body["error"]["code"], "validation_failed", "{path}");
+        errors.push(body["error"].clone());
+    }
+    errors
+}
+
+#[tokio::test]
+async fn empty_cart_returns_422() {
+    let base_url = spawn_app().await;
+
+    let mut order = order_with_address(full_address());
+    order["cart_items"] = json!([]);
+
+    for error in rejected_order_errors(&base_url, &order).await {
+        assert_eq!(error["field"], "cart_items");
+    }
+}
+
+#[tokio::test]
+async fn non_positive_quantity_returns_422() {
+    let base_url = spawn_app().await;
+
+    for quantity in [0, -2] {
+        let mut order = order_with_address(full_address());
+        order["cart_items"] = json!([
+            {"sku": "1", "name": "Widget A", "quantity": 1, "unit_price": 29.99},
+            {"sku": "2", "name": "Widget B", "quantity": quantity, "unit_price": 9.99}
+        ]);
+
+        for error in rejected_order_errors(&base_url, &order).await {
+            assert_eq!(error["field"], "cart_items[1].quantity");
+            assert!(error["message"]
+                .as_str()
+                .unwrap()
+                .contains(&format!("got {quantity}")));
+        }
+    }
+}
+
+#[tokio::test]
+async fn malformed_customer_email_returns_422() {
+    let base_url = spawn_app().await;
+
+    for email in [
+        "",
+        "not-an-email",
+        "@example.com",
+        "user@localhost",
+        "a b@example.com",
+    ] {
+        let mut order = order_with_address(full_address());
+        order["customer_email"] = json!(email);
+
+        for error in rejected_order_errors(&base_url, &order).await {
+            assert_eq!(error["field"], "customer_email", "email {email:?}");
+        }
+    }
+}
+
+#[tokio::test]
+async fn unsupported_currency_returns_422() {
+    let base_url = spawn_app().await;
+
+    let mut order = order_with_address(full_address());
+    order["currency"] = json!("XYZ");
+
+    for error in rejected_order_errors(&base_url, &order).await {
+        assert_eq!(error["field"], "currency");
+        assert!(error["message"]
+            .as_str()
+            .unwrap()
+            .contains("Unsupported currency 'XYZ'"));
+    }
+}
+
+#[tokio::test]
+async fn malformed_email_is_rejected_on_registration_and_refund() {
+    let base_url = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    let res = client
+        .post(format!("{}/services/register", base_url))
+        .json(&json!({"user_email": "jane.example.com", "user_name": "Jane"}))
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert_eq!(res.status(), 422);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["error"]["field"], "user_email");
+
+    let res = client
+        .post(format!("{}/compliance/refund", base_url))
+        .json(&json!({
+            "check_type": "refund",
+            "namespace": "customer_success_rs",
+            "customer_email": "refund@",
+            "order_id": "ORD-EMAIL",
+            "refund_amount": 10.0,
+            "reason": "Email test"
+        }))
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert_eq!(res.status(), 422);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["error"]["field"], "customer_email");
+}
+
+#[tokio::test]
+async fn empty_or_oversized_order_batch_is_rejected() {
+    let base_url = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    let res = client
+        .post(format!("{}/orders/batch", base_url))
+        .json(&json!({"orders": []}))
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert_eq!(res.status(), 422);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["error"]["field"], "orders");
+
+    let orders = vec![order_with_address(full_address()); MAX_BATCH_ORDERS + 1];
+    let res = client
+        .post(format!("{}/orders/batch", base_url))
+        .json(&json!({ "orders": orders }))
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert_eq!(res.status(), 400);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["error"]["code"], "bad_request");
+    assert!(body["error"]["message"].as_str().unwrap().contains("at most 100"));
+}
+
+// ---------------------------------------------------------------------------
+// Analytics date range
+// ---------------------------------------------------------------------------
+
+async fn post_analytics_job(base_url: &str, date_range: Value) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(format!("{}/analytics", base_url))
+        .json(&json!({
+            "job_name": "date_validation",
+            "sources": ["sales"],
+            "date_range": date_range
+        }))
+        .send()
+        .await
+        .expect("Failed to send request")
+}
+
+#[tokio::test]
+async fn malformed_date_returns_422() {
+    let base_url = spawn_app().await;
+
+    for (range, field) in [
+        (json!({"start_date": "2026-13-01", "end_date": "2026-12-31"}), "date_range.start_date"),
+        (json!({"start_date": "2026-01-01", "end_date": "next tuesday"}), "date_range.end_date"),
+    ] {
+        let res = post_analytics_job(&base_url, range).await;
+        assert_eq!(res.status(), 422);
+        let body: Value = res.json().await.unwrap();
+        assert_eq!(body["error"]["field"], field);
+    }
+}
+
+#[tokio::test]
+async fn inverted_date_range_returns_422() {
+    let base_url = spawn_app().await;
+
+    let res = post_analytics_job(
+        &base_url,
+        json!({"start_date": "2026-03-31", "end_date": "2026-03-01"}),
+    )
+    .await;
+
+    assert_eq!(res.status(), 422);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["error"]["field"], "date_range");
+    assert!(body["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("before date_range.start_date"));
+}
+
+// ---------------------------------------------------------------------------
+// Refund amounts
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn sub_cent_refund_amount_returns_422() {
+    let base_url = spawn_app().await;
+
+    let res = reqwest::Client::new()
+        .post(format!("{}/compliance/refund", base_url))
+        .json(&json!({
+            "check_type": "refund",
+            "namespace": "customer_success_rs",
+            "customer_email": "refund@example.com",
+            "order_id": "ORD-PRECISION",
+            "refund_amount": 10.005,
+            "reason": "Rounding test"
+        }))
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(res.status(), 422);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["error"]["field"], "refund_amount");
+    assert!(body["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("more than 2 decimal places for USD"));
+}
+
+// ---------------------------------------------------------------------------
+// Field aliases
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn aliases_are_rejected_by_default() {
+    let base_url = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    let mut order = order_with_address(full_address());
+    let email = order.as_object_mut().unwrap().remove("customer_email").unwrap();
+    order["email"] = email;
+
+    let res = client
+        .post(format!("{}/orders", base_url))
+        .json(&order)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(res.status(), 422);
+    let body: Value = res.json().await.unwrap();
+    assert!(body["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("customer_email"));
+}
+
+#[test]
+fn field_aliases_parse_and_apply() {
+    let aliases = FieldAliases::parse("common, orders.buyer=customer_email").unwrap();
+
+    let mut order = json!({"buyer": "a@example.com", "items": []});
+    aliases.apply("orders", &mut order);
+    assert_eq!(order, json!({"customer_email": "a@example.com", "cart_items": []}));
+
+    // Scopes are independent: `email` means user_email for services
+    let mut registration = json!({"email": "b@example.com"});
+    aliases.apply("services", &mut registration);
+    assert_eq!(registration, json!({"user_email": "b@example.com"}));
+
+    // The canonical field wins when both are sent
+    let mut both = json!({"email": "alias@example.com", "customer_email": "canonical@example.com"});
+    aliases.apply("orders", &mut both);
+    assert_eq!(both["customer_email"], "canonical@example.com");
+
+    assert!(FieldAliases::parse("orders.email").is_err());
+    assert!(FieldAliases::parse("").unwrap().is_empty());
+}
+
+// ---------------------------------------------------------------------------
+// Tags
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn blank_tag_keys_return_422() {
+    let base_url = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    let mut order = order_with_address(full_address());
+    order["tags"] = json!({"": "black_friday"});
+    let res = client
+        .post(format!("{}/orders", base_url))
+        .json(&order)
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert_eq!(res.status(), 422);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["error"]["field"], "tags");
+
+    let res = client
+        .get(format!("{}/orders?tag.=black_friday", base_url))
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert_eq!(res.status(), 422);
+}
+
+// ---------------------------------------------------------------------------
+// Task attribution
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn unknown_initiator_returns_422() {
+    let base_url = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    for (header, value) in [("X-Initiator", "billing-portal"), ("X-Source-System", "has spaces")] {
+        let res = client
+            .post(format!("{}/orders", base_url))
+            .header(header, value)
+            .json(&order_with_address(full_address()))
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(res.status(), 422, "{header}: {value}");
+        let body: Value = res.json().await.unwrap();
+        assert_eq!(body["error"]["field"], header.to_ascii_lowercase());
+    }
+}
+
+#[tokio::test]
+async fn malformed_idempotency_key_returns_422() {
+    let base_url = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    for key in ["   ".to_string(), "k".repeat(256)] {
+        let res = client
+            .post(format!("{}/orders", base_url))
+            .header("Idempotency-Key", &key)
+            .json(&order_with_address(full_address()))
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(res.status(), 422, "{key:?}");
+        let body: Value = res.json().await.unwrap();
+        assert_eq!(body["error"]["field"], "idempotency-key");
+    }
+}
+
+#[test]
+fn initiator_allowlist_always_allows_the_default() {
+    let allowlist = InitiatorAllowlist::parse(" billing-portal, ,support-console ").unwrap();
+    assert_eq!(
+        allowlist.initiators(),
+        vec!["axum-example-app", "billing-portal", "support-console"]
+    );
+    assert!(allowlist.is_allowed(DEFAULT_INITIATOR));
+    assert!(!allowlist.is_allowed("BILLING-PORTAL"));
+
+    assert!(InitiatorAllowlist::parse("billing portal").is_err());
+}
+
+// ---------------------------------------------------------------------------
+// Pagination
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn invalid_page_params_return_422() {
+    let base_url = spawn_app().await;
+    let client = reqwest::Client::new();
+    let cursor = Cursor::new(chrono::Utc::now().naive_utc(), 7).encode();
+
+    for (query, field) in [
+        (vec![("cursor", "bm90IGEgY3Vyc29y")], "cursor"),
+        (vec![("limit", "0")], "limit"),
+        (vec![("limit", "ten")], "limit"),
+        (vec![("offset", "-1")], "offset"),
+        (vec![("cursor", cursor.as_str()), ("offset", "5")], "offset"),
+    ] {
+        let res = client
+            .get(format!("{}/analytics", base_url))
+            .query(&query)
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(res.status(), 422, "{query:?}");
+        let body: Value = res.json().await.unwrap();
+        assert_eq!(body["error"]["field"], field);
+    }
+}
+
+#[test]
+fn page_cursors_round_trip() {
+    let created_at = chrono::NaiveDate::from_ymd_opt(2024, 3, 9)
+        .unwrap()
+        .and_hms_micro_opt(14, 5, 6, 123_456)
+        .unwrap();
+    let cursor = Cursor::new(created_at, 42);
+    let token = cursor.encode();
+    assert!(token.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+    assert_eq!(Cursor::decode(&token), Some(cursor));
+    assert_eq!(Cursor::decode("%%%"), None);
+}
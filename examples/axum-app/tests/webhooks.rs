@@ -0,0 +1,102 @@
+//! Task webhook signature tests.
+//!
+//! Requests are rejected before the database is touched, so the app is served
+//! with a lazily-connected pool and no database or orchestration is needed.
+//! A correctly signed webhook updating an order is covered by the integration
+//! tests.
+//!
+//! Run: cargo test --test webhooks
+
+mod common;
+
+use serde_json::{json, Value};
+
+use example_axum_app::webhook_auth::{WebhookAuth, SIGNATURE_HEADER};
+use example_axum_app::AppConfig;
+
+const SECRET: &str = "webhook-test-secret";
+
+/// Serve the app with `webhook_auth` on a random local port and return its base URL.
+async fn spawn_app(webhook_auth: WebhookAuth) -> String {
+    common::spawn_app_with_config(AppConfig {
+        webhook_auth,
+        ..Default::default()
+    })
+    .await
+}
+
+fn webhook_body() -> Vec<u8> {
+    serde_json::to_vec(&json!({
+        "task_uuid": uuid::Uuid::new_v4(),
+        "status": "complete",
+        "completion_percentage": 100.0
+    }))
+    .unwrap()
+}
+
+async fn post_webhook(base_url: &str, body: Vec<u8>, signature: Option<&str>) -> reqwest::Response {
+    let mut request = reqwest::Client::new()
+        .post(format!("{}/webhooks/tasks", base_url))
+        .header("content-type", "application/json")
+        .body(body);
+    if let Some(signature) = signature {
+        request = request.header(SIGNATURE_HEADER, signature);
+    }
+    request.send().await.expect("Failed to send request")
+}
+
+#[test]
+fn signatures_verify_only_for_the_signed_body_and_secret() {
+    let auth = WebhookAuth::new(Some(SECRET.to_string()));
+    let body = webhook_body();
+    let signature = auth.sign(&body).unwrap();
+    assert!(signature.starts_with("sha256="));
+    assert!(auth.verify(&body, Some(&signature)));
+
+    assert!(!auth.verify(b"{}", Some(&signature)));
+    assert!(!auth.verify(&body, None));
+    assert!(!auth.verify(&body, Some(signature.trim_start_matches("sha256="))));
+
+    let other = WebhookAuth::new(Some("another-secret".to_string()));
+    assert!(!other.verify(&body, Some(&signature)));
+
+    let disabled = WebhookAuth::new(None);
+    assert!(disabled.sign(&body).is_none());
+    assert!(!disabled.verify(&body, Some(&signature)));
+}
+
+#[tokio::test]
+async fn unsigned_or_mis_signed_webhooks_return_401() {
+    let base_url = spawn_app(WebhookAuth::new(Some(SECRET.to_string()))).await;
+    let wrong_key = WebhookAuth::new(Some("another-secret".to_string()));
+
+    let body = webhook_body();
+    let wrong_signature = wrong_key.sign(&body).unwrap();
+    for signature in [None, Some("sha256=not-hex"), Some(wrong_signature.as_str())] {
+        let res = post_webhook(&base_url, body.clone(), signature).await;
+        assert_eq!(res.status(), 401, "{signature:?}");
+        let error: Value = res.json().await.expect("Expected JSON error body");
+        assert_eq!(error["error"]["code"], "unauthorized");
+    }
+}
+
+#[tokio::test]
+async fn webhooks_are_rejected_without_a_secret() {
+    let base_url = spawn_app(WebhookAuth::new(None)).await;
+
+    let body = webhook_body();
+    let signature = WebhookAuth::new(Some(SECRET.to_string())).sign(&body);
+    let res = post_webhook(&base_url, body, signature.as_deref()).await;
+    assert_eq!(res.status(), 401);
+}
+
+#[tokio::test]
+async fn signed_malformed_webhook_returns_400() {
+    let auth = WebhookAuth::new(Some(SECRET.to_string()));
+    let base_url = spawn_app(auth.clone()).await;
+
+    let body = br#"{"status": "complete"}"#.to_vec();
+    let signature = auth.sign(&body);
+    let res = post_webhook(&base_url, body, signature.as_deref()).await;
+    assert_eq!(res.status(), 400);
+}
@@ -0,0 +1,83 @@
+//! Worker poll interval override and namespace template tests.
+//!
+//! The override rewrites the app's own `config/worker.toml` and the template
+//! filter copies `config/templates`, so these tests check them against the
+//! real files. No services are needed.
+//!
+//! Run: cargo test --test worker_config
+
+use std::collections::HashSet;
+
+use example_axum_app::worker_config::{
+    poll_interval_ms, with_poll_interval, write_namespace_templates, write_overridden_config,
+};
+
+fn worker_toml() -> String {
+    std::fs::read_to_string("config/worker.toml").expect("Failed to read config/worker.toml")
+}
+
+#[test]
+fn override_replaces_every_poll_interval() {
+    let config = worker_toml();
+    assert_eq!(poll_interval_ms(&config), Some(250));
+
+    let overridden = with_poll_interval(&config, 25).unwrap();
+    assert_eq!(poll_interval_ms(&overridden), Some(25));
+    let mut before: toml::Table = config.parse().unwrap();
+    let after: toml::Table = overridden.parse().unwrap();
+    assert_eq!(
+        after["worker"]["event_systems"]["worker"]["metadata"]["fallback_poller"]
+            ["polling_interval_ms"]
+            .as_integer(),
+        Some(25)
+    );
+
+    // Everything else is left as it was
+    before["common"]["queues"]["pgmq"]
+        .as_table_mut()
+        .unwrap()
+        .insert("poll_interval_ms".to_string(), 25.into());
+    before["worker"]["event_systems"]["worker"]["metadata"]["fallback_poller"]
+        .as_table_mut()
+        .unwrap()
+        .insert("polling_interval_ms".to_string(), 25.into());
+    assert_eq!(before, after);
+}
+
+#[test]
+fn override_requires_a_poll_interval_setting() {
+    assert!(with_poll_interval("[worker]\nworker_id = \"w1\"\n", 25).is_err());
+    // Keys outside their section are not poll intervals
+    assert!(with_poll_interval("[common.queues]\npoll_interval_ms = 250\n", 25).is_err());
+    assert!(with_poll_interval("[common.queues.pgmq\n", 25).is_err());
+}
+
+#[test]
+fn overridden_config_is_written_to_a_copy() {
+    let path = write_overridden_config(std::path::Path::new("config/worker.toml"), 40).unwrap();
+    assert_ne!(path, std::path::Path::new("config/worker.toml"));
+    let written = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(poll_interval_ms(&written), Some(40));
+    assert_eq!(poll_interval_ms(&worker_toml()), Some(250));
+}
+
+#[test]
+fn namespace_templates_are_copied_for_enabled_namespaces_only() {
+    let templates = std::path::Path::new("config/templates");
+    let enabled = HashSet::from(["payments_rs".to_string(), "ecommerce_rs".to_string()]);
+    let dir = write_namespace_templates(templates, &enabled).unwrap();
+    assert_ne!(dir, templates);
+
+    let mut copied: Vec<String> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    copied.sort();
+    assert_eq!(
+        copied,
+        vec!["ecommerce_order_processing.yaml", "payments_process_refund.yaml"]
+    );
+
+    let unserved = HashSet::from(["unserved_rs".to_string()]);
+    assert!(write_namespace_templates(templates, &unserved).is_err());
+}